@@ -16,6 +16,10 @@ fn main() {
             .expect("protoc");
     }
 
+    // Scaffolding of the `rust_cast::ffi` bindings, see `src/rust_cast.udl`.
+    #[cfg(feature = "ffi")]
+    uniffi::generate_scaffolding("src/rust_cast.udl").expect("uniffi scaffolding");
+
     println!("rerun-if-env-changed=GENERATE_PROTO");
     println!("rerun-if-changed=protobuf/authority_keys.proto");
     println!("rerun-if-changed=protobuf/cast_channel.proto");
@@ -1,35 +1,66 @@
-use std::str::FromStr;
+use std::{
+    io::{self, BufRead},
+    path::Path,
+    str::FromStr,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
 
 use ansi_term::Colour::{Green, Red};
 use docopt::Docopt;
 use log::error;
 use mdns_sd::{ServiceDaemon, ServiceEvent};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use rust_cast::{
     channels::{
         heartbeat::HeartbeatResponse,
-        media::{Media, StatusEntry, StreamType},
-        receiver::CastDeviceApp,
+        media::{
+            self, GenericMediaMetadata, LoadOptions, Media, MediaQueue, QueueItem, QueueType,
+            Status, StatusEntry, StreamType, TextTrackType, Track, TrackType,
+        },
+        receiver::{CastDeviceApp, Volume},
     },
+    errors::Error,
+    message_manager::ConnectionState,
     CastDevice, ChannelMessage,
 };
 
 const SERVICE_TYPE: &str = "_googlecast._tcp.local.";
 const DEFAULT_DESTINATION_ID: &str = "receiver-0";
+const SUBTITLES_TRACK_ID: u32 = 1;
+#[cfg(feature = "discovery")]
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+#[cfg(feature = "http")]
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const WATCH_PING_INTERVAL: Duration = Duration::from_secs(5);
+const WATCH_SILENCE_TIMEOUT: Duration = Duration::from_secs(20);
+const WATCH_RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const WATCH_HELP: &str =
+    "Commands: status, play, pause, stop, seek <time>, vol <level>, mute, unmute, quit";
 
 const USAGE: &str = "
-Usage: rust-caster [-v] [-h] [-a <address>] [-p <port>] [-i | -r <app to run> | -s <app to stop> | --stop-current | [-m <media handle> [--media-type <media type>] [--media-stream-type <stream type>] [--media-app <media app>]] | [--media-volume <level> | --media-mute| --media-unmute | --media-pause | --media-play | --media-stop | --media-seek <time>] [--media-app <media app>]]
+Usage:
+    rust-caster --list [--json]
+    rust-caster [-v] [-h] [--json] [-a <address> | --device <name>] [-p <port>] [-i | --watch [--media-app <media app>] | -r <app to run> | -s <app to stop> | --stop-current | [(-m <media handle> | --media-queue <queue file> | --serve <path>) [--media-type <media type>] [--media-stream-type <stream type>] [--subtitles <url> [--subtitles-language <language>]] [--media-app <media app>]] | [--media-volume <level> | --media-mute| --media-unmute | --media-pause | --media-play | --media-stop | --media-seek <time> | --queue-next | --queue-prev | --queue-jump <n> | --subtitles-toggle] [--media-app <media app>]]
 
 Options:
     -a, --address <address>                 Cast device network address.
+        --device <name>                     Friendly name of the cast device to discover and connect to (requires `discovery` feature).
+        --list                              Lists cast devices discovered on the network (requires `discovery` feature).
     -p, --port <port>                       Cast device network port. [default: 8009]
     -r, --run <app_to_run>                  Run the app with specified id/name.
     -s, --stop <app_to_stop>                Stops the app with specified id/name.
         --stop-current                      Stops currently active app.
     -i, --info                              Returns the info about the receiver.
+        --watch                             Keeps printing device messages and runs commands from stdin (type `help` to list them) for the app passed in `--media-app`.
     -m, --media <media_handle>              Media handle (URL for image or video, URL token for youtube video etc.) to load on the Cast connected device.
-        --media-type <media_type>           Type of the media to load.
+        --media-queue <queue_file>          JSON (array of `url`, `type` and `title` objects) or M3U playlist to load as a media queue.
+        --serve <path>                      Serves the local file over HTTP from this machine and casts it to the device.
+        --media-type <media_type>           Type of the media to load (guessed from the extension of the media URL or file, probed over HTTP with `http` feature).
         --media-app <media_app>             Media app to use for streaming. [default: default]
         --media-stream-type <stream_type>   Media stream type to use (buffered, live or none). [default: none]
         --media-volume <level>              Media volume level.
@@ -39,19 +70,32 @@ Options:
         --media-play                        Play currently paused media in the app that is passed in `--media-app`.
         --media-stop                        Stops currently active media in the app that is passed in `--media-app`.
         --media-seek <time>                 Sets the current position in the media stream in the app that is passed in `--media-app`.
+        --queue-next                        Plays the next item of the queue in the app that is passed in `--media-app`.
+        --queue-prev                        Plays the previous item of the queue in the app that is passed in `--media-app`.
+        --queue-jump <n>                    Skips `n` items of the queue (backwards if negative, e.g. `--queue-jump=-2`) in the app that is passed in `--media-app`.
+        --subtitles <url>                   URL of the WebVTT subtitles to side-load with the media passed in `--media`.
+        --subtitles-language <language>     Language of the subtitles passed in `--subtitles`. [default: en-US]
+        --subtitles-toggle                  Shows or hides subtitles of the media in the app that is passed in `--media-app`.
+        --json                              Print device, receiver and media status as JSON (one message per line once media is playing).
     -v, --verbose                           Toggle verbose output.
     -h, --help                              Print this help menu.
 ";
 
 #[derive(Debug, Deserialize)]
 struct Args {
+    flag_json: bool,
     flag_address: Option<String>,
+    flag_device: Option<String>,
+    flag_list: bool,
     flag_port: u16,
     flag_run: Option<String>,
     flag_stop: Option<String>,
     flag_stop_current: bool,
     flag_info: Option<String>,
+    flag_watch: bool,
     flag_media: Option<String>,
+    flag_media_queue: Option<String>,
+    flag_serve: Option<String>,
     flag_media_type: Option<String>,
     flag_media_app: String,
     flag_media_stream_type: String,
@@ -62,10 +106,32 @@ struct Args {
     flag_media_play: bool,
     flag_media_stop: bool,
     flag_media_seek: Option<f32>,
+    flag_queue_next: bool,
+    flag_queue_prev: bool,
+    flag_queue_jump: Option<i32>,
+    flag_subtitles: Option<String>,
+    flag_subtitles_language: String,
+    flag_subtitles_toggle: bool,
+}
+
+/// Entry of the JSON queue file.
+#[derive(Debug, Deserialize)]
+struct QueueFileEntry {
+    url: String,
+    #[serde(rename = "type")]
+    content_type: Option<String>,
+    title: Option<String>,
 }
 
-fn print_info(device: &CastDevice) {
+fn print_json<T: Serialize>(value: &T) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+fn print_info(device: &CastDevice, json: bool) {
     let status = device.receiver.get_status().unwrap();
+    if json {
+        return print_json(&status);
+    }
 
     println!(
         "\n{} {}",
@@ -104,8 +170,29 @@ fn print_info(device: &CastDevice) {
     }
 }
 
-fn run_app(device: &CastDevice, app_to_run: &CastDeviceApp) {
+fn print_volume(volume: &Volume, json: bool) {
+    if json {
+        return print_json(volume);
+    }
+
+    if let Some(level) = volume.level {
+        println!(
+            "{} {}",
+            Green.paint("Volume level:"),
+            Red.paint(level.to_string())
+        );
+    }
+
+    if let Some(muted) = volume.muted {
+        println!("{} {}", Green.paint("Muted:"), Red.paint(muted.to_string()));
+    }
+}
+
+fn run_app(device: &CastDevice, app_to_run: &CastDeviceApp, json: bool) {
     let app = device.receiver.launch_app(app_to_run).unwrap();
+    if json {
+        return print_json(&app);
+    }
 
     println!(
         "{}{}{}{}{}{}{}",
@@ -119,7 +206,7 @@ fn run_app(device: &CastDevice, app_to_run: &CastDeviceApp) {
     );
 }
 
-fn stop_app(device: &CastDevice, app_to_run: &CastDeviceApp) {
+fn stop_app(device: &CastDevice, app_to_run: &CastDeviceApp, json: bool) {
     let status = device.receiver.get_status().unwrap();
 
     let app = status
@@ -127,6 +214,13 @@ fn stop_app(device: &CastDevice, app_to_run: &CastDeviceApp) {
         .iter()
         .find(|app| &CastDeviceApp::from_str(app.app_id.as_str()).unwrap() == app_to_run);
 
+    if json {
+        if let Some(app) = app {
+            device.receiver.stop_app(app.session_id.as_str()).unwrap();
+        }
+        return print_json(&app);
+    }
+
     match app {
         Some(app) => {
             device.receiver.stop_app(app.session_id.as_str()).unwrap();
@@ -153,8 +247,16 @@ fn stop_app(device: &CastDevice, app_to_run: &CastDeviceApp) {
     }
 }
 
-fn stop_current_app(device: &CastDevice) {
+fn stop_current_app(device: &CastDevice, json: bool) {
     let status = device.receiver.get_status().unwrap();
+    if json {
+        let app = status.applications.first();
+        if let Some(app) = app {
+            device.receiver.stop_app(app.session_id.as_str()).unwrap();
+        }
+        return print_json(&app);
+    }
+
     match status.applications.first() {
         Some(app) => {
             device.receiver.stop_app(app.session_id.as_str()).unwrap();
@@ -180,9 +282,49 @@ fn play_media(
     media: String,
     media_type: String,
     media_stream_type: StreamType,
+    subtitles: Option<Track>,
+    json: bool,
 ) {
     let app = device.receiver.launch_app(app_to_run).unwrap();
 
+    device
+        .connection
+        .connect(app.transport_id.as_str())
+        .unwrap();
+
+    // YouTube application is controlled through its own channel, `media` is just a video id.
+    #[cfg(feature = "youtube")]
+    if *app_to_run == CastDeviceApp::YouTube {
+        device
+            .youtube
+            .play_video(app.transport_id.as_str(), media.as_str())
+            .unwrap();
+        return;
+    }
+
+    let mut media = Media::builder(media, media_type).stream_type(media_stream_type);
+    let mut options = LoadOptions::default();
+    if let Some(subtitles) = subtitles {
+        options.active_track_ids = Some(vec![subtitles.track_id]);
+        media = media.track(subtitles);
+    }
+
+    let status = device
+        .media
+        .load_with_opts(
+            app.transport_id.as_str(),
+            app.session_id.as_str(),
+            &media.build(),
+            options,
+        )
+        .unwrap();
+
+    print_media_status(&status, json);
+}
+
+fn play_queue(device: &CastDevice, app_to_run: &CastDeviceApp, items: Vec<QueueItem>, json: bool) {
+    let app = device.receiver.launch_app(app_to_run).unwrap();
+
     device
         .connection
         .connect(app.transport_id.as_str())
@@ -190,19 +332,92 @@ fn play_media(
 
     let status = device
         .media
-        .load(
+        .load_queue(
             app.transport_id.as_str(),
             app.session_id.as_str(),
-            &Media {
-                content_id: media,
-                content_type: media_type,
-                stream_type: media_stream_type,
-                duration: None,
-                metadata: None,
+            &MediaQueue {
+                items,
+                start_index: 0,
+                queue_type: QueueType::Playlist,
             },
         )
         .unwrap();
 
+    print_media_status(&status, json);
+}
+
+/// Determines MIME type of the media at the `url`: guesses it from the extension and, if that
+/// fails, probes the server (requires `http` feature).
+fn media_content_type(url: &str) -> String {
+    if let Some(content_type) = media::guess_content_type(url) {
+        return content_type.to_string();
+    }
+
+    #[cfg(feature = "http")]
+    match media::probe_content_type(url, PROBE_TIMEOUT) {
+        Ok(Some(content_type)) => return content_type,
+        Ok(None) => {}
+        Err(err) => log::warn!("Could not probe content type of {url}: {err}"),
+    }
+
+    String::new()
+}
+
+/// Reads queue items from the JSON or M3U (any other extension) playlist file.
+fn read_queue(path: &str, media_type: &str, media_stream_type: StreamType) -> Vec<QueueItem> {
+    let contents = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Could not read queue file {}: {}", path, e));
+
+    let entries = if path.ends_with(".json") {
+        serde_json::from_str::<Vec<QueueFileEntry>>(&contents)
+            .unwrap_or_else(|e| panic!("Invalid queue file {}: {}", path, e))
+    } else {
+        let mut entries = vec![];
+        let mut title = None;
+        for line in contents.lines().map(str::trim) {
+            if let Some(info) = line.strip_prefix("#EXTINF:") {
+                title = info
+                    .split_once(',')
+                    .map(|(_, title)| title.trim().to_string());
+            } else if !line.is_empty() && !line.starts_with('#') {
+                entries.push(QueueFileEntry {
+                    url: line.to_string(),
+                    content_type: None,
+                    title: title.take(),
+                });
+            }
+        }
+        entries
+    };
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let content_type = entry.content_type.unwrap_or_else(|| {
+                if media_type.is_empty() {
+                    media_content_type(&entry.url)
+                } else {
+                    media_type.to_string()
+                }
+            });
+            let mut media = Media::builder(entry.url, content_type).stream_type(media_stream_type);
+            if let Some(title) = entry.title {
+                media = media.metadata(GenericMediaMetadata::builder().title(title).build());
+            }
+
+            QueueItem {
+                media: media.build(),
+                preload_time: None,
+            }
+        })
+        .collect()
+}
+
+fn print_media_status(status: &Status, json: bool) {
+    if json {
+        return print_json(status);
+    }
+
     for i in 0..status.entries.len() {
         println!(
             "{}{}{}",
@@ -257,7 +472,288 @@ fn play_media(
     }
 }
 
-fn discover() -> Option<(String, u16)> {
+fn print_status_entry(entry: &StatusEntry, json: bool) {
+    if json {
+        return print_json(entry);
+    }
+
+    println!("{}", Green.paint("Media:"));
+    println!(
+        "{} {}",
+        Green.paint("Playback rate:"),
+        Red.paint(entry.playback_rate.to_string())
+    );
+    println!(
+        "{} {}",
+        Green.paint("Player state:"),
+        Red.paint(entry.player_state.to_string())
+    );
+
+    if let Some(time) = entry.current_time {
+        println!(
+            "{} {}",
+            Green.paint("Current time:"),
+            Red.paint(time.to_string())
+        );
+    }
+
+    if let Some(ref media) = entry.media {
+        println!(
+            "{} {}",
+            Green.paint("Content Id:"),
+            Red.paint(media.content_id.as_str())
+        );
+        println!(
+            "{} {}",
+            Green.paint("Stream type:"),
+            Red.paint(media.stream_type.to_string())
+        );
+        println!(
+            "{} {}",
+            Green.paint("Content type:"),
+            Red.paint(media.content_type.as_str())
+        );
+
+        if let Some(duration) = media.duration {
+            println!(
+                "{} {}",
+                Green.paint("Duration:"),
+                Red.paint(duration.to_string())
+            );
+        }
+    }
+}
+
+/// Prints message received from the device and answers it if it's a heartbeat `PING`.
+fn print_message(device: &CastDevice, message: ChannelMessage, json: bool) {
+    if let ChannelMessage::Heartbeat(HeartbeatResponse::Ping) = message {
+        device.heartbeat.pong().unwrap();
+    }
+
+    // Every message is printed as a single JSON line, so the output can be streamed.
+    if json {
+        return println!("{}", serde_json::to_string(&message).unwrap());
+    }
+
+    match message {
+        ChannelMessage::Heartbeat(response) => println!("[Heartbeat] {:?}", response),
+        ChannelMessage::Connection(response) => println!("[Connection] {:?}", response),
+        ChannelMessage::DebugOverlay(response) => println!("[DebugOverlay] {:?}", response),
+        ChannelMessage::Error(response) => println!("[Error] {:?}", response),
+        ChannelMessage::Media(response) => println!("[Media] {:?}", response),
+        ChannelMessage::Mirroring(response) => println!("[Mirroring] {:?}", response),
+        ChannelMessage::Multizone(response) => println!("[Multizone] {:?}", response),
+        ChannelMessage::Receiver(response) => println!("[Receiver] {:?}", response),
+        ChannelMessage::Setup(response) => println!("[Setup] {:?}", response),
+        #[cfg(feature = "youtube")]
+        ChannelMessage::YouTube(response) => println!("[YouTube] {:?}", response),
+        ChannelMessage::Raw(response) => println!(
+            "Support for the following message type is not yet supported: {:?}",
+            response
+        ),
+        ChannelMessage::ParseError {
+            namespace, error, ..
+        } => println!("[Parse error] {} {}", namespace, error),
+    }
+}
+
+fn connect(address: &str, port: u16) -> Result<CastDevice, Error> {
+    let device = CastDevice::connect_without_host_verification(address.to_string(), port)?;
+    // Malformed messages shouldn't end the receive loops.
+    device.set_report_parse_errors(true);
+    device.connection.connect(DEFAULT_DESTINATION_ID)?;
+    device.heartbeat.ping()?;
+
+    Ok(device)
+}
+
+/// Connects to the running application and returns its transport id with the id of its current
+/// media session, if any.
+fn media_session(device: &CastDevice, app: &CastDeviceApp) -> Result<Option<(String, i32)>, Error> {
+    let status = device.receiver.get_status()?;
+    let app = status
+        .applications
+        .iter()
+        .find(|running| CastDeviceApp::from_str(running.app_id.as_str()).as_ref() == Ok(app));
+
+    let Some(app) = app else {
+        return Ok(None);
+    };
+
+    device.connection.connect(app.transport_id.as_str())?;
+    let status = device.media.get_status(app.transport_id.as_str(), None)?;
+
+    Ok(status
+        .entries
+        .first()
+        .map(|entry| (app.transport_id.clone(), entry.media_session_id)))
+}
+
+/// Runs single command entered in the watch mode, returns `false` if watching should stop.
+fn run_command(
+    device: &CastDevice,
+    app: &CastDeviceApp,
+    command: &str,
+    json: bool,
+) -> Result<bool, Error> {
+    let mut parts = command.split_whitespace();
+    let (name, argument) = (parts.next().unwrap_or_default(), parts.next());
+
+    match name {
+        "" => {}
+        "quit" | "exit" => return Ok(false),
+        "status" => print_info(device, json),
+        "vol" => match argument.and_then(|level| level.parse::<f32>().ok()) {
+            Some(level) => print_volume(&device.receiver.set_volume(level)?, json),
+            None => eprintln!("Usage: vol <level>"),
+        },
+        "mute" | "unmute" => print_volume(&device.receiver.set_volume(name == "mute")?, json),
+        "play" | "pause" | "stop" | "seek" => {
+            let Some((transport_id, media_session_id)) = media_session(device, app)? else {
+                eprintln!("Application `{}` doesn't play any media!", app);
+                return Ok(true);
+            };
+
+            let entry = match (name, argument.map(str::parse::<f32>)) {
+                ("play", _) => device.media.play(transport_id, media_session_id)?,
+                ("pause", _) => device.media.pause(transport_id, media_session_id)?,
+                ("stop", _) => device.media.stop(transport_id, media_session_id)?,
+                (_, Some(Ok(time))) => {
+                    device
+                        .media
+                        .seek(transport_id, media_session_id, Some(time), None)?
+                }
+                _ => {
+                    eprintln!("Usage: seek <time>");
+                    return Ok(true);
+                }
+            };
+            print_status_entry(&entry, json);
+        }
+        _ => eprintln!("{}", WATCH_HELP),
+    }
+
+    Ok(true)
+}
+
+/// Keeps the connection open, printing every message received from the device and running the
+/// commands read from stdin. Connection is re-established if it's closed or the device has been
+/// silent for too long.
+fn watch(mut device: CastDevice, address: &str, port: u16, app: &CastDeviceApp, json: bool) {
+    let (commands_sender, commands) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            if commands_sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    eprintln!("{}", WATCH_HELP);
+
+    let mut last_ping = Instant::now();
+    loop {
+        match device.receive_timeout(WATCH_POLL_INTERVAL) {
+            Ok(Some(message)) => print_message(&device, message, json),
+            Ok(None) => {}
+            Err(error) => error!("Error occurred while receiving message {}", error),
+        }
+
+        let silent = device
+            .health()
+            .silent_for()
+            .is_some_and(|silent_for| silent_for > WATCH_SILENCE_TIMEOUT);
+        if silent || device.state() == ConnectionState::Closed {
+            eprintln!("Connection with the device has been lost, reconnecting...");
+            match connect(address, port) {
+                Ok(reconnected) => {
+                    device = reconnected;
+                    last_ping = Instant::now();
+                }
+                Err(error) => {
+                    error!("Failed to reconnect: {}", error);
+                    thread::sleep(WATCH_RECONNECT_DELAY);
+                }
+            }
+            continue;
+        }
+
+        if last_ping.elapsed() >= WATCH_PING_INTERVAL {
+            last_ping = Instant::now();
+            if let Err(error) = device.heartbeat.ping() {
+                error!("Failed to ping the device: {}", error);
+            }
+        }
+
+        // Stdin may be closed (e.g. when watching in the background), keep watching regardless.
+        if let Ok(command) = commands.try_recv() {
+            match run_command(&device, app, command.trim(), json) {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(error) => eprintln!("Command `{}` failed: {}", command.trim(), error),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "discovery")]
+fn list_devices(json: bool) {
+    let devices = rust_cast::discovery::discover(DISCOVERY_TIMEOUT).unwrap();
+
+    if json {
+        let devices = devices
+            .iter()
+            .map(|device| {
+                serde_json::json!({
+                    "friendly_name": device.friendly_name,
+                    "model_name": device.model_name,
+                    "addresses": device.addresses,
+                    "port": device.port,
+                })
+            })
+            .collect::<Vec<_>>();
+        return print_json(&devices);
+    }
+
+    if devices.is_empty() {
+        return println!("{}", Green.paint("No Cast devices discovered!"));
+    }
+
+    for device in devices {
+        println!(
+            "{} {} {}",
+            Red.paint(device.friendly_name.as_deref().unwrap_or("<unnamed>")),
+            Green.paint(format!(
+                "({})",
+                device.model_name.as_deref().unwrap_or("unknown")
+            )),
+            Red.paint(
+                device
+                    .socket_addr()
+                    .map(|addr| addr.to_string())
+                    .unwrap_or_default()
+            )
+        );
+    }
+}
+
+/// Discovers the device with the specified friendly name (case insensitive).
+#[cfg(feature = "discovery")]
+fn find_device(name: &str) -> Option<(String, u16)> {
+    rust_cast::discovery::discover(DISCOVERY_TIMEOUT)
+        .unwrap()
+        .into_iter()
+        .filter(|device| {
+            device
+                .friendly_name
+                .as_ref()
+                .is_some_and(|friendly_name| friendly_name.eq_ignore_ascii_case(name))
+        })
+        .find_map(|device| device.socket_addr())
+        .map(|addr| (addr.ip().to_string(), addr.port()))
+}
+
+fn discover(json: bool) -> Option<(String, u16)> {
     let mdns = ServiceDaemon::new().expect("Failed to create mDNS daemon.");
 
     let receiver = mdns
@@ -272,6 +768,10 @@ fn discover() -> Option<(String, u16)> {
                     .iter()
                     .map(|address| address.to_string())
                     .collect::<Vec<_>>();
+                if json {
+                    return Some((addresses.remove(0), info.get_port()));
+                }
+
                 println!(
                     "{}{}",
                     Green.paint("Resolved a new service: "),
@@ -285,6 +785,7 @@ fn discover() -> Option<(String, u16)> {
                 // Based on mDNS crate code we should have at least one address available.
                 return Some((addresses.remove(0), info.get_port()));
             }
+            other_event if json => log::debug!("Received other service event: {:?}", other_event),
             other_event => {
                 println!(
                     "{}{}",
@@ -304,51 +805,82 @@ fn main() {
         .and_then(|d| d.deserialize())
         .unwrap_or_else(|e| e.exit());
 
-    let (address, port) = match args.flag_address {
-        Some(address) => (address, args.flag_port),
-        None => {
-            println!("Cast Device address is not specified, trying to discover...");
-            discover().unwrap_or_else(|| {
-                println!("No Cast device discovered, please specify device address explicitly.");
+    if args.flag_list {
+        #[cfg(feature = "discovery")]
+        return list_devices(args.flag_json);
+        #[cfg(not(feature = "discovery"))]
+        return eprintln!("Listing devices requires `discovery` feature.");
+    }
+
+    let (address, port) = match (args.flag_address, args.flag_device) {
+        (Some(address), _) => (address, args.flag_port),
+        #[cfg(feature = "discovery")]
+        (None, Some(name)) => find_device(&name).unwrap_or_else(|| {
+            eprintln!("Cast device `{}` hasn't been discovered.", name);
+            std::process::exit(1);
+        }),
+        #[cfg(not(feature = "discovery"))]
+        (None, Some(_)) => {
+            eprintln!("Selecting device by name requires `discovery` feature.");
+            std::process::exit(1);
+        }
+        (None, None) => {
+            if !args.flag_json {
+                println!("Cast Device address is not specified, trying to discover...");
+            }
+            discover(args.flag_json).unwrap_or_else(|| {
+                eprintln!("No Cast device discovered, please specify device address explicitly.");
                 std::process::exit(1);
             })
         }
     };
 
-    let cast_device = match CastDevice::connect_without_host_verification(address, port) {
+    let cast_device = match connect(&address, port) {
         Ok(cast_device) => cast_device,
         Err(err) => panic!("Could not establish connection with Cast Device: {:?}", err),
     };
 
-    cast_device
-        .connection
-        .connect(DEFAULT_DESTINATION_ID.to_string())
-        .unwrap();
-    cast_device.heartbeat.ping().unwrap();
+    // Watch device and control it with the commands from stdin.
+    if args.flag_watch {
+        let app = CastDeviceApp::from_str(args.flag_media_app.as_str()).unwrap();
+        return watch(cast_device, &address, port, &app, args.flag_json);
+    }
 
     // Information about cast device.
     if args.flag_info.is_some() {
-        return print_info(&cast_device);
+        return print_info(&cast_device, args.flag_json);
     }
 
     // Run specific application.
     if let Some(app) = args.flag_run {
-        return run_app(&cast_device, &CastDeviceApp::from_str(&app).unwrap());
+        return run_app(
+            &cast_device,
+            &CastDeviceApp::from_str(&app).unwrap(),
+            args.flag_json,
+        );
     }
 
     // Stop specific application.
     if let Some(app) = args.flag_stop {
-        return stop_app(&cast_device, &CastDeviceApp::from_str(&app).unwrap());
+        return stop_app(
+            &cast_device,
+            &CastDeviceApp::from_str(&app).unwrap(),
+            args.flag_json,
+        );
     }
 
     // Stop currently active application.
     if args.flag_stop_current {
-        return stop_current_app(&cast_device);
+        return stop_current_app(&cast_device, args.flag_json);
     }
 
     // Adjust volume level.
     if let Some(level) = args.flag_media_volume {
         let volume = cast_device.receiver.set_volume(level).unwrap();
+        if args.flag_json {
+            return print_json(&volume);
+        }
+
         println!(
             "{}{}",
             Green.paint("Volume level has been set to: "),
@@ -361,6 +893,10 @@ fn main() {
     if args.flag_media_mute || args.flag_media_unmute {
         let mute_or_unmute = args.flag_media_mute;
         let volume = cast_device.receiver.set_volume(mute_or_unmute).unwrap();
+        if args.flag_json {
+            return print_json(&volume);
+        }
+
         println!(
             "{}{}",
             Green.paint("Cast device is muted: "),
@@ -374,6 +910,10 @@ fn main() {
         || args.flag_media_play
         || args.flag_media_stop
         || args.flag_media_seek.is_some()
+        || args.flag_queue_next
+        || args.flag_queue_prev
+        || args.flag_queue_jump.is_some()
+        || args.flag_subtitles_toggle
     {
         let app_to_manage = CastDeviceApp::from_str(args.flag_media_app.as_str()).unwrap();
         let status = cast_device.receiver.get_status().unwrap();
@@ -431,56 +971,69 @@ fn main() {
                             )
                             .unwrap(),
                     );
-                }
-
-                if let Some(status_entry) = status_entry {
-                    println!("{}", Green.paint("Media:"));
-                    println!(
-                        "{} {}",
-                        Green.paint("Playback rate:"),
-                        Red.paint(status_entry.playback_rate.to_string())
+                } else if args.flag_queue_next {
+                    status_entry = Some(
+                        cast_device
+                            .media
+                            .queue_next(app.transport_id.as_str(), status.media_session_id)
+                            .unwrap(),
                     );
-                    println!(
-                        "{} {}",
-                        Green.paint("Player state:"),
-                        Red.paint(status_entry.player_state.to_string())
+                } else if args.flag_queue_prev {
+                    status_entry = Some(
+                        cast_device
+                            .media
+                            .queue_prev(app.transport_id.as_str(), status.media_session_id)
+                            .unwrap(),
                     );
+                } else if let Some(jump) = args.flag_queue_jump {
+                    status_entry = Some(
+                        cast_device
+                            .media
+                            .queue_jump(app.transport_id.as_str(), status.media_session_id, jump)
+                            .unwrap(),
+                    );
+                } else if args.flag_subtitles_toggle {
+                    // Hide subtitles if any text track is active, otherwise show the first one.
+                    let text_tracks = status
+                        .media
+                        .as_ref()
+                        .map(|media| {
+                            media
+                                .tracks
+                                .iter()
+                                .filter(|track| track.track_type == TrackType::Text)
+                                .map(|track| track.track_id)
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default();
+                    let active_track_ids = if status
+                        .active_track_ids
+                        .iter()
+                        .any(|id| text_tracks.contains(id))
+                    {
+                        vec![]
+                    } else {
+                        text_tracks.into_iter().take(1).collect()
+                    };
 
-                    if let Some(time) = status_entry.current_time {
-                        println!(
-                            "{} {}",
-                            Green.paint("Current time:"),
-                            Red.paint(time.to_string())
-                        );
-                    }
+                    status_entry = Some(
+                        cast_device
+                            .media
+                            .edit_tracks_info(
+                                app.transport_id.as_str(),
+                                status.media_session_id,
+                                Some(&active_track_ids),
+                                None,
+                            )
+                            .unwrap(),
+                    );
+                }
 
-                    if let Some(ref media) = status_entry.media {
-                        println!(
-                            "{} {}",
-                            Green.paint("Content Id:"),
-                            Red.paint(media.content_id.as_str())
-                        );
-                        println!(
-                            "{} {}",
-                            Green.paint("Stream type:"),
-                            Red.paint(media.stream_type.to_string())
-                        );
-                        println!(
-                            "{} {}",
-                            Green.paint("Content type:"),
-                            Red.paint(media.content_type.as_str())
-                        );
-
-                        if let Some(duration) = media.duration {
-                            println!(
-                                "{} {}",
-                                Green.paint("Duration:"),
-                                Red.paint(duration.to_string())
-                            );
-                        }
-                    }
+                if let Some(status_entry) = status_entry {
+                    print_status_entry(&status_entry, args.flag_json);
                 }
             }
+            None if args.flag_json => print_json(&serde_json::Value::Null),
             None => {
                 println!(
                     "{} `{}` {}",
@@ -493,9 +1046,31 @@ fn main() {
         return;
     }
 
-    // Play media and keep connection.
-    if let Some(media) = args.flag_media {
-        let media_type = args.flag_media_type.unwrap_or_default();
+    // Play media (media queue or local file) and keep connection.
+    if args.flag_media.is_some() || args.flag_media_queue.is_some() || args.flag_serve.is_some() {
+        let (media, media_type) = match args.flag_serve {
+            Some(path) => {
+                let path = Path::new(&path);
+                let url = file_server::serve(path, &address, port)
+                    .unwrap_or_else(|err| panic!("Could not serve {}: {}", path.display(), err));
+                if !args.flag_json {
+                    println!("{} {}", Green.paint("Serving at:"), Red.paint(&url));
+                }
+
+                let media_type = args
+                    .flag_media_type
+                    .unwrap_or_else(|| file_server::content_type(path).to_string());
+                (Some(url), media_type)
+            }
+            None => {
+                let media_type = match (&args.flag_media, args.flag_media_type) {
+                    (_, Some(media_type)) => media_type,
+                    (Some(media), None) => media_content_type(media),
+                    (None, None) => String::new(),
+                };
+                (args.flag_media, media_type)
+            }
+        };
 
         let media_stream_type = match args.flag_media_stream_type.as_str() {
             value @ "buffered" | value @ "live" | value @ "none" => {
@@ -504,34 +1079,249 @@ fn main() {
             _ => panic!("Unsupported stream type {}!", args.flag_media_stream_type),
         };
 
-        play_media(
-            &cast_device,
-            &CastDeviceApp::from_str(args.flag_media_app.as_str()).unwrap(),
-            media,
-            media_type,
-            media_stream_type,
-        );
+        let preferred_app = CastDeviceApp::from_str(args.flag_media_app.as_str()).unwrap();
+        let media_app = match cast_device.media_app(&preferred_app) {
+            Ok(app) => {
+                if app != preferred_app && !args.flag_json {
+                    println!(
+                        "{} {}",
+                        Green.paint("Audio-only device, casting with:"),
+                        Red.paint(app.to_string())
+                    );
+                }
+                app
+            }
+            Err(err) => {
+                error!("Could not detect audio-only device: {}", err);
+                preferred_app
+            }
+        };
+        match media {
+            Some(media) => play_media(
+                &cast_device,
+                &media_app,
+                media,
+                media_type,
+                media_stream_type,
+                args.flag_subtitles.map(|url| Track {
+                    track_content_id: Some(url),
+                    track_content_type: Some("text/vtt".to_string()),
+                    subtype: Some(TextTrackType::Subtitles),
+                    language: Some(args.flag_subtitles_language),
+                    ..Track::new(SUBTITLES_TRACK_ID, TrackType::Text)
+                }),
+                args.flag_json,
+            ),
+            None => play_queue(
+                &cast_device,
+                &media_app,
+                read_queue(
+                    args.flag_media_queue.as_deref().unwrap(),
+                    &media_type,
+                    media_stream_type,
+                ),
+                args.flag_json,
+            ),
+        }
 
         loop {
             match cast_device.receive() {
-                Ok(ChannelMessage::Heartbeat(response)) => {
-                    println!("[Heartbeat] {:?}", response);
+                Ok(message) => print_message(&cast_device, message, args.flag_json),
+                Err(error) => error!("Error occurred while receiving message {}", error),
+            }
+        }
+    }
+}
+
+/// Tiny HTTP/1.1 server for the `--serve` mode that serves a single local file (with `Range`
+/// requests support, so that receiver can seek) to the cast device.
+mod file_server {
+    use std::{
+        fs::File,
+        io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+        net::{
+            IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs,
+            UdpSocket,
+        },
+        path::Path,
+        thread,
+    };
+
+    use log::debug;
+    use rust_cast::channels::media;
+
+    /// Starts serving the file at `path` in the background and returns the URL the cast device at
+    /// `address` and `port` can fetch the file with.
+    pub fn serve(path: &Path, address: &str, port: u16) -> io::Result<String> {
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file path"))?;
+        if !File::open(path)?.metadata()?.is_file() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a file"));
+        }
+
+        let listener = TcpListener::bind((local_ip(address, port)?, 0))?;
+        let url_path = format!("/{}", encode(file_name));
+        let url = format!("http://{}{}", listener.local_addr()?, url_path);
 
-                    if let HeartbeatResponse::Ping = response {
-                        cast_device.heartbeat.pong().unwrap();
+        let path = path.to_path_buf();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (path, url_path) = (path.clone(), url_path.clone());
+                thread::spawn(move || {
+                    // Receivers routinely drop connections mid-response (e.g. when seeking).
+                    if let Err(err) = handle(stream, &path, &url_path) {
+                        debug!("Failed to serve {}: {}", path.display(), err);
                     }
+                });
+            }
+        });
+
+        Ok(url)
+    }
+
+    /// Guesses MIME type of the file from its extension.
+    pub fn content_type(path: &Path) -> &'static str {
+        media::guess_content_type(&path.to_string_lossy()).unwrap_or("application/octet-stream")
+    }
+
+    /// Returns IP address of the local interface the cast device is reachable through. Connecting
+    /// UDP socket doesn't send anything, it only makes OS pick the route.
+    fn local_ip(address: &str, port: u16) -> io::Result<IpAddr> {
+        let device_address = (address, port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "unresolved device address"))?;
+        let unspecified = match device_address {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+
+        let socket = UdpSocket::bind((unspecified, 0))?;
+        socket.connect(device_address)?;
+        Ok(socket.local_addr()?.ip())
+    }
+
+    /// Handles single request, the connection is closed once response is written.
+    fn handle(stream: TcpStream, path: &Path, url_path: &str) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut request_line = request_line.split_whitespace();
+        let method = request_line.next().unwrap_or_default().to_string();
+        let target = request_line.next().unwrap_or_default().to_string();
+
+        let mut range = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("range") {
+                    range = Some(value.trim().to_string());
                 }
+            }
+        }
+
+        let mut stream = stream;
+        if method != "GET" && method != "HEAD" {
+            return respond(&mut stream, "405 Method Not Allowed", &[]);
+        }
+        if target != url_path {
+            return respond(&mut stream, "404 Not Found", &[]);
+        }
+
+        let mut file = File::open(path)?;
+        let length = file.metadata()?.len();
+        let content_type = format!("Content-Type: {}", content_type(path));
 
-                Ok(ChannelMessage::Connection(response)) => println!("[Connection] {:?}", response),
-                Ok(ChannelMessage::Media(response)) => println!("[Media] {:?}", response),
-                Ok(ChannelMessage::Receiver(response)) => println!("[Receiver] {:?}", response),
-                Ok(ChannelMessage::Raw(response)) => println!(
-                    "Support for the following message type is not yet supported: {:?}",
-                    response
+        let (status, start, count, content_range) = match range {
+            None => ("200 OK", 0, length, None),
+            Some(range) => match parse_range(&range, length) {
+                Some((start, end)) => (
+                    "206 Partial Content",
+                    start,
+                    end - start + 1,
+                    Some(format!("Content-Range: bytes {}-{}/{}", start, end, length)),
                 ),
+                None => {
+                    let content_range = format!("Content-Range: bytes */{}", length);
+                    return respond(
+                        &mut stream,
+                        "416 Range Not Satisfiable",
+                        &[content_range.as_str()],
+                    );
+                }
+            },
+        };
 
-                Err(error) => error!("Error occurred while receiving message {}", error),
-            }
+        let content_length = format!("Content-Length: {}", count);
+        let mut headers = vec![content_type.as_str(), content_length.as_str()];
+        headers.extend(content_range.as_deref());
+        write_head(&mut stream, status, &headers)?;
+
+        if method == "GET" {
+            file.seek(SeekFrom::Start(start))?;
+            io::copy(&mut file.take(count), &mut stream)?;
         }
+
+        stream.flush()
+    }
+
+    /// Writes response without the body.
+    fn respond(stream: &mut TcpStream, status: &str, headers: &[&str]) -> io::Result<()> {
+        let mut headers = headers.to_vec();
+        headers.push("Content-Length: 0");
+        write_head(stream, status, &headers)?;
+        stream.flush()
+    }
+
+    fn write_head(stream: &mut TcpStream, status: &str, headers: &[&str]) -> io::Result<()> {
+        let mut head = format!("HTTP/1.1 {}\r\n", status);
+        // Receiver fetches some media (e.g. subtitles or HLS) with CORS requests.
+        for header in headers.iter().chain(&[
+            "Accept-Ranges: bytes",
+            "Access-Control-Allow-Origin: *",
+            "Connection: close",
+        ]) {
+            head.push_str(header);
+            head.push_str("\r\n");
+        }
+        head.push_str("\r\n");
+
+        stream.write_all(head.as_bytes())
+    }
+
+    /// Parses `bytes=<start>-<end>` value of the `Range` header into the inclusive byte range of
+    /// the file with `length` bytes, returns `None` if range is malformed or unsatisfiable.
+    /// Multiple ranges aren't supported.
+    fn parse_range(value: &str, length: u64) -> Option<(u64, u64)> {
+        let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+        let last = length.checked_sub(1)?;
+
+        let (start, end) = match (start.trim(), end.trim()) {
+            ("", suffix) => (length - suffix.parse::<u64>().ok()?.min(length), last),
+            (start, "") => (start.parse().ok()?, last),
+            (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(last)),
+        };
+
+        (start <= end).then_some((start, end))
+    }
+
+    /// Percent-encodes the file name so that it can be used as URL path.
+    fn encode(value: &str) -> String {
+        value
+            .bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    (byte as char).to_string()
+                }
+                _ => format!("%{:02X}", byte),
+            })
+            .collect()
     }
 }
@@ -0,0 +1,243 @@
+//! Capabilities of the cast devices: those the devices report (`ca` bitmask of the mDNS TXT
+//! records, receiver status) and media capabilities of the known device models, used to check the
+//! media before it's loaded (see `preflight` module).
+
+use crate::channels::receiver::Status;
+
+/// Bit of the `ca` bitmask set for the devices with video output.
+const CAPABILITY_VIDEO_OUT: u32 = 1 << 0;
+/// Bit of the `ca` bitmask set for the devices with audio output.
+const CAPABILITY_AUDIO_OUT: u32 = 1 << 2;
+/// Bit of the `ca` bitmask set for the speaker groups.
+const CAPABILITY_MULTIZONE_GROUP: u32 = 1 << 5;
+
+/// Capabilities of the cast device. Devices report only whether they have video and audio output
+/// and whether they are speaker groups, media capabilities are taken from the presets of the known
+/// device models (e.g. [`DeviceCapabilities::CHROMECAST_ULTRA`]), see
+/// [`DeviceCapabilities::from_bitmask`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DeviceCapabilities {
+    /// Whether the device has video output, audio-only devices (e.g. speakers) play audio only.
+    pub video_out: bool,
+    /// Whether the device has audio output.
+    pub audio_out: bool,
+    /// Whether the device can be a member of the speaker groups.
+    pub multizone: bool,
+    /// Whether the device is a speaker group (its leader plays the media for all the members).
+    pub group: bool,
+    /// Maximum height of the video (in pixels) the device is able to decode, e.g. `1080`.
+    pub max_video_height: u32,
+    /// Maximum frame rate of the video the device is able to decode at its maximum height.
+    pub max_frame_rate: f32,
+    /// Whether the device supports HDR video (HDR10, HLG or Dolby Vision).
+    pub hdr: bool,
+    /// Whether the device decodes HEVC (H.265) video.
+    pub hevc: bool,
+    /// Whether the device decodes VP9 video.
+    pub vp9: bool,
+    /// Whether the device decodes AV1 video.
+    pub av1: bool,
+    /// Whether the device passes the surround sound (AC-3 and E-AC-3) through to the receiver.
+    pub surround_sound: bool,
+}
+
+impl DeviceCapabilities {
+    /// Chromecast (1st generation): H.264 and VP8 up to 1080p30.
+    pub const CHROMECAST_1ST_GEN: DeviceCapabilities = DeviceCapabilities {
+        video_out: true,
+        audio_out: true,
+        multizone: false,
+        group: false,
+        max_video_height: 1080,
+        max_frame_rate: 30.,
+        hdr: false,
+        hevc: false,
+        vp9: false,
+        av1: false,
+        surround_sound: false,
+    };
+
+    /// Chromecast (2nd and 3rd generation): H.264 and VP8 up to 1080p60, VP9 up to 720p.
+    pub const CHROMECAST: DeviceCapabilities = DeviceCapabilities {
+        max_frame_rate: 60.,
+        vp9: true,
+        surround_sound: true,
+        ..DeviceCapabilities::CHROMECAST_1ST_GEN
+    };
+
+    /// Chromecast Ultra: H.264, HEVC and VP9 up to 2160p60, HDR.
+    pub const CHROMECAST_ULTRA: DeviceCapabilities = DeviceCapabilities {
+        video_out: true,
+        audio_out: true,
+        multizone: false,
+        group: false,
+        max_video_height: 2160,
+        max_frame_rate: 60.,
+        hdr: true,
+        hevc: true,
+        vp9: true,
+        av1: false,
+        surround_sound: true,
+    };
+
+    /// Chromecast with Google TV (4K): H.264, HEVC and VP9 up to 2160p60, HDR.
+    pub const CHROMECAST_GOOGLE_TV: DeviceCapabilities = DeviceCapabilities::CHROMECAST_ULTRA;
+
+    /// Chromecast with Google TV (HD) and Google TV Streamer: H.264, HEVC, VP9 and AV1 up to
+    /// 1080p60, HDR.
+    pub const CHROMECAST_GOOGLE_TV_HD: DeviceCapabilities = DeviceCapabilities {
+        max_video_height: 1080,
+        av1: true,
+        ..DeviceCapabilities::CHROMECAST_ULTRA
+    };
+
+    /// Smart displays (e.g. Nest Hub): H.264 and VP9 up to 720p60.
+    pub const SMART_DISPLAY: DeviceCapabilities = DeviceCapabilities {
+        max_video_height: 720,
+        multizone: true,
+        surround_sound: false,
+        ..DeviceCapabilities::CHROMECAST
+    };
+
+    /// Audio-only devices (e.g. Nest speakers and speaker groups).
+    pub const AUDIO: DeviceCapabilities = DeviceCapabilities {
+        video_out: false,
+        audio_out: true,
+        multizone: true,
+        group: false,
+        max_video_height: 0,
+        max_frame_rate: 0.,
+        hdr: false,
+        hevc: false,
+        vp9: false,
+        av1: false,
+        surround_sound: false,
+    };
+
+    /// Speaker groups.
+    pub const SPEAKER_GROUP: DeviceCapabilities = DeviceCapabilities {
+        group: true,
+        ..DeviceCapabilities::AUDIO
+    };
+
+    /// Returns the preset of the known device `model_name` (`md` TXT record of the device, e.g.
+    /// `Chromecast Ultra`). Chromecast generations share the `Chromecast` model name, so the
+    /// preset of the 2nd generation is returned for all of them.
+    pub fn from_model(model_name: &str) -> Option<DeviceCapabilities> {
+        let capabilities = match model_name {
+            "Chromecast" => DeviceCapabilities::CHROMECAST,
+            "Chromecast Ultra" => DeviceCapabilities::CHROMECAST_ULTRA,
+            "Chromecast HD" | "Google TV Streamer" => DeviceCapabilities::CHROMECAST_GOOGLE_TV_HD,
+            "Google Home Hub" | "Google Nest Hub" | "Google Nest Hub Max" => {
+                DeviceCapabilities::SMART_DISPLAY
+            }
+            "Chromecast Audio"
+            | "Google Home"
+            | "Google Home Mini"
+            | "Google Home Max"
+            | "Google Nest Mini"
+            | "Google Nest Audio"
+            | "Google Nest Wifi point" => DeviceCapabilities::AUDIO,
+            "Google Cast Group" => DeviceCapabilities::SPEAKER_GROUP,
+            _ => return None,
+        };
+
+        Some(capabilities)
+    }
+
+    /// Derives capabilities from the `ca` bitmask the device advertises in its mDNS TXT records:
+    /// the reported outputs and group flag override the preset of the `model_name` (if it's
+    /// known, the 2nd generation Chromecast or audio-only device preset otherwise).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_cast::capabilities::DeviceCapabilities;
+    ///
+    /// let capabilities = DeviceCapabilities::from_bitmask(201_221, Some("Chromecast Ultra"));
+    /// assert_eq!(2160, capabilities.max_video_height);
+    ///
+    /// let capabilities = DeviceCapabilities::from_bitmask(2084, None);
+    /// assert!(capabilities.is_audio_only());
+    /// assert!(capabilities.group);
+    /// ```
+    pub fn from_bitmask(ca: u32, model_name: Option<&str>) -> DeviceCapabilities {
+        let video_out = ca & CAPABILITY_VIDEO_OUT != 0;
+        let preset = model_name
+            .and_then(DeviceCapabilities::from_model)
+            .filter(|preset| preset.video_out == video_out);
+        let capabilities = match preset {
+            Some(preset) => preset,
+            None if video_out => DeviceCapabilities::CHROMECAST,
+            None => DeviceCapabilities::AUDIO,
+        };
+        let group = ca & CAPABILITY_MULTIZONE_GROUP != 0;
+
+        DeviceCapabilities {
+            video_out,
+            audio_out: ca & CAPABILITY_AUDIO_OUT != 0,
+            multizone: capabilities.multizone || group,
+            group,
+            ..capabilities
+        }
+    }
+
+    /// Derives capabilities from the receiver `status` when the `ca` bitmask isn't available
+    /// (e.g. the device hasn't been discovered via mDNS): the preset of the `model_name` is used
+    /// if it's known, otherwise the 2nd generation Chromecast or audio-only device preset (see
+    /// `Status::is_audio_device`). Either way, the devices that report their stand by and active
+    /// input state are connected to a display, so they have video output.
+    pub fn from_receiver_status(status: &Status, model_name: Option<&str>) -> DeviceCapabilities {
+        let capabilities = match model_name.and_then(DeviceCapabilities::from_model) {
+            Some(capabilities) => capabilities,
+            None if status.is_audio_device() => DeviceCapabilities::AUDIO,
+            None => DeviceCapabilities::CHROMECAST,
+        };
+
+        if status.supports_standby && !capabilities.video_out {
+            return DeviceCapabilities::CHROMECAST;
+        }
+
+        capabilities
+    }
+
+    /// Determines whether the device plays audio only (e.g. speakers and speaker groups).
+    pub fn is_audio_only(&self) -> bool {
+        self.audio_out && !self.video_out
+    }
+
+    /// Determines whether the device decodes 4K (2160p) video.
+    pub fn is_4k(&self) -> bool {
+        self.video_out && self.max_video_height >= 2160
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_bitmask() {
+        let chromecast = DeviceCapabilities::from_bitmask(4101, Some("Chromecast"));
+        assert_eq!(DeviceCapabilities::CHROMECAST, chromecast);
+        assert!(!chromecast.is_4k());
+
+        let ultra = DeviceCapabilities::from_bitmask(201_221, Some("Chromecast Ultra"));
+        assert!(ultra.is_4k() && ultra.hdr);
+
+        let speaker = DeviceCapabilities::from_bitmask(2052, Some("Google Nest Mini"));
+        assert_eq!(DeviceCapabilities::AUDIO, speaker);
+        assert!(speaker.is_audio_only() && !speaker.group);
+
+        let group = DeviceCapabilities::from_bitmask(2084, Some("Google Cast Group"));
+        assert_eq!(DeviceCapabilities::SPEAKER_GROUP, group);
+
+        // Reported outputs take precedence over the model preset.
+        let display = DeviceCapabilities::from_bitmask(4101, Some("Google Home"));
+        assert_eq!(DeviceCapabilities::CHROMECAST, display);
+        assert_eq!(
+            DeviceCapabilities::AUDIO,
+            DeviceCapabilities::from_bitmask(2052, Some("Chromecast"))
+        );
+    }
+}
@@ -1,3 +1,6 @@
+// Generated code still references lints that are removed in recent toolchains.
+#[allow(renamed_and_removed_lints)]
 pub mod authority_keys;
+#[allow(renamed_and_removed_lints)]
 pub mod cast_channel;
 pub mod proxies;
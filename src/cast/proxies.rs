@@ -2,12 +2,32 @@
 pub mod connection {
     use serde_derive::Serialize;
 
-    #[derive(Serialize, Debug)]
+    /// `CONNECT`/`CLOSE` request that opens or closes the virtual connection.
+    #[derive(Serialize, Clone, Debug)]
     pub struct ConnectionRequest {
         #[serde(rename = "type")]
         pub typ: String,
         #[serde(rename = "userAgent")]
         pub user_agent: String,
+        #[serde(rename = "connType", skip_serializing_if = "Option::is_none")]
+        pub conn_type: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub origin: Option<serde_json::Value>,
+        #[serde(rename = "senderInfo", skip_serializing_if = "Option::is_none")]
+        pub sender_info: Option<SenderInfo>,
+    }
+
+    /// Details of the sender passed with the `CONNECT` request.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct SenderInfo {
+        #[serde(rename = "sdkType")]
+        pub sdk_type: u32,
+        pub version: String,
+        #[serde(rename = "browserVersion", skip_serializing_if = "Option::is_none")]
+        pub browser_version: Option<String>,
+        pub platform: u32,
+        #[serde(rename = "connectionType")]
+        pub connection_type: u32,
     }
 }
 
@@ -15,7 +35,8 @@ pub mod connection {
 pub mod heartbeat {
     use serde_derive::Serialize;
 
-    #[derive(Serialize, Debug)]
+    /// `PING` or `PONG` message.
+    #[derive(Serialize, Clone, Debug)]
     pub struct HeartBeatRequest {
         #[serde(rename = "type")]
         pub typ: String,
@@ -24,10 +45,12 @@ pub mod heartbeat {
 
 /// Proxy classes for the `media` channel.
 pub mod media {
-    use serde::Deserialize;
-    use serde_derive::Serialize;
+    use std::collections::HashMap;
+
+    use serde_derive::{Deserialize, Serialize};
 
-    #[derive(Serialize, Debug)]
+    /// `GET_STATUS` request, optionally for the specific media session.
+    #[derive(Serialize, Clone, Debug)]
     pub struct GetStatusRequest {
         #[serde(rename = "requestId")]
         pub request_id: u32,
@@ -40,8 +63,8 @@ pub mod media {
     }
 
     // Really LoadRequest
-    /// https://developers.google.com/cast/docs/reference/web_sender/chrome.cast.media.LoadRequest
-    #[derive(Serialize, Debug)]
+    /// <https://developers.google.com/cast/docs/reference/web_sender/chrome.cast.media.LoadRequest>
+    #[derive(Serialize, Clone, Debug)]
     pub struct MediaRequest {
         #[serde(rename = "requestId")]
         pub request_id: u32,
@@ -64,10 +87,19 @@ pub mod media {
 
         #[serde(rename = "queueData", skip_serializing_if = "Option::is_none")]
         pub queue_data: Option<QueueData>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub credentials: Option<String>,
+
+        #[serde(rename = "credentialsType", skip_serializing_if = "Option::is_none")]
+        pub credentials_type: Option<String>,
+
+        #[serde(rename = "activeTrackIds", skip_serializing_if = "Option::is_none")]
+        pub active_track_ids: Option<Vec<u32>>,
     }
 
-    /// https://developers.google.com/cast/docs/reference/web_sender/chrome.cast.media.QueueItem
-    #[derive(Serialize, Debug)]
+    /// <https://developers.google.com/cast/docs/reference/web_sender/chrome.cast.media.QueueItem>
+    #[derive(Serialize, Clone, Debug)]
     pub struct QueueItem {
         #[serde(rename = "activeTrackIds")]
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -95,8 +127,8 @@ pub mod media {
         pub start_time: f64,
     }
 
-    /// https://developers.google.com/cast/docs/reference/web_sender/chrome.cast.media.QueueLoadRequest
-    #[derive(Serialize, Debug)]
+    /// <https://developers.google.com/cast/docs/reference/web_sender/chrome.cast.media.QueueLoadRequest>
+    #[derive(Serialize, Clone, Debug)]
     pub struct QueueLoadRequest {
         #[serde(rename = "type")]
         pub typ: String,
@@ -122,8 +154,8 @@ pub mod media {
         pub start_index: u16,
     }
 
-    /// https://developers.google.com/cast/docs/reference/web_sender/chrome.cast.media.QueueData
-    #[derive(Serialize, Debug)]
+    /// <https://developers.google.com/cast/docs/reference/web_sender/chrome.cast.media.QueueData>
+    #[derive(Serialize, Clone, Debug)]
     pub struct QueueData {
         pub items: Vec<QueueItem>,
 
@@ -138,7 +170,8 @@ pub mod media {
         pub start_index: u16,
     }
 
-    #[derive(Serialize, Debug)]
+    /// Playback request without arguments, e.g. `PLAY`, `PAUSE` or `STOP`.
+    #[derive(Serialize, Clone, Debug)]
     pub struct PlaybackGenericRequest {
         #[serde(rename = "requestId")]
         pub request_id: u32,
@@ -153,7 +186,90 @@ pub mod media {
         pub custom_data: CustomData,
     }
 
-    #[derive(Serialize, Debug)]
+    /// `QUEUE_UPDATE` request that moves playback within the queue.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct QueueUpdateRequest {
+        #[serde(rename = "requestId")]
+        pub request_id: u32,
+
+        #[serde(rename = "mediaSessionId")]
+        pub media_session_id: i32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        pub jump: i32,
+    }
+
+    /// `QUEUE_GET_ITEM_IDS` request.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct QueueGetItemIdsRequest {
+        #[serde(rename = "requestId")]
+        pub request_id: u32,
+
+        #[serde(rename = "mediaSessionId")]
+        pub media_session_id: i32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+    }
+
+    /// `QUEUE_GET_ITEM_RANGE` request.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct QueueGetItemRangeRequest {
+        #[serde(rename = "requestId")]
+        pub request_id: u32,
+
+        #[serde(rename = "mediaSessionId")]
+        pub media_session_id: i32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(rename = "itemId")]
+        pub item_id: u16,
+
+        #[serde(rename = "nextCount")]
+        pub next_count: u16,
+
+        #[serde(rename = "prevCount")]
+        pub prev_count: u16,
+    }
+
+    /// `QUEUE_GET_ITEMS` request.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct QueueGetItemsRequest {
+        #[serde(rename = "requestId")]
+        pub request_id: u32,
+
+        #[serde(rename = "mediaSessionId")]
+        pub media_session_id: i32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(rename = "itemIds")]
+        pub item_ids: Vec<u16>,
+    }
+
+    /// `PRELOAD` request.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct PreloadRequest {
+        #[serde(rename = "requestId")]
+        pub request_id: u32,
+
+        #[serde(rename = "mediaSessionId")]
+        pub media_session_id: i32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(rename = "itemId")]
+        pub item_id: u16,
+    }
+
+    /// `SEEK` request.
+    #[derive(Serialize, Clone, Debug)]
     pub struct PlaybackSeekRequest {
         #[serde(rename = "requestId")]
         pub request_id: u32,
@@ -174,23 +290,157 @@ pub mod media {
         pub custom_data: CustomData,
     }
 
-    #[derive(Serialize, Deserialize, Debug)]
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.EditTracksInfoRequestData>
+    #[derive(Serialize, Clone, Debug)]
+    pub struct EditTracksInfoRequest {
+        #[serde(rename = "requestId")]
+        pub request_id: u32,
+
+        #[serde(rename = "mediaSessionId")]
+        pub media_session_id: i32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(rename = "activeTrackIds", skip_serializing_if = "Option::is_none")]
+        pub active_track_ids: Option<Vec<u32>>,
+
+        #[serde(rename = "textTrackStyle", skip_serializing_if = "Option::is_none")]
+        pub text_track_style: Option<TextTrackStyle>,
+
+        #[serde(rename = "customData")]
+        pub custom_data: CustomData,
+    }
+
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.MediaInformation>
+    #[derive(Serialize, Deserialize, Clone, Debug)]
     pub struct Media {
-        #[serde(rename = "contentId")]
+        #[serde(rename = "contentId", default)]
         pub content_id: String,
+        #[serde(rename = "contentUrl", skip_serializing_if = "Option::is_none")]
+        pub content_url: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub entity: Option<String>,
+        #[serde(rename = "atvEntity", skip_serializing_if = "Option::is_none")]
+        pub atv_entity: Option<String>,
         #[serde(rename = "streamType", default)]
         pub stream_type: String,
-        #[serde(rename = "contentType")]
+        #[serde(rename = "contentType", default)]
         pub content_type: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub metadata: Option<Metadata>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub duration: Option<f32>,
+        #[serde(rename = "hlsSegmentFormat", skip_serializing_if = "Option::is_none")]
+        pub hls_segment_format: Option<String>,
+        #[serde(
+            rename = "hlsVideoSegmentFormat",
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub hls_video_segment_format: Option<String>,
+        #[serde(rename = "textTrackStyle", skip_serializing_if = "Option::is_none")]
+        pub text_track_style: Option<TextTrackStyle>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub breaks: Vec<Break>,
+        #[serde(rename = "breakClips", default, skip_serializing_if = "Vec::is_empty")]
+        pub break_clips: Vec<BreakClip>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub tracks: Vec<Track>,
+        #[serde(rename = "customData", skip_serializing_if = "Option::is_none")]
+        pub custom_data: Option<serde_json::Value>,
+    }
+
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.Track>
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct Track {
+        #[serde(rename = "trackId")]
+        pub track_id: u32,
+        #[serde(rename = "type")]
+        pub typ: String,
+        #[serde(rename = "trackContentId", skip_serializing_if = "Option::is_none")]
+        pub track_content_id: Option<String>,
+        #[serde(rename = "trackContentType", skip_serializing_if = "Option::is_none")]
+        pub track_content_type: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub subtype: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub language: Option<String>,
+    }
+
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.Break>
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct Break {
+        #[serde(default)]
+        pub id: String,
+        #[serde(rename = "breakClipIds", default)]
+        pub break_clip_ids: Vec<String>,
+        #[serde(default)]
+        pub position: f32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub duration: Option<f32>,
+        #[serde(rename = "isEmbedded", skip_serializing_if = "Option::is_none")]
+        pub is_embedded: Option<bool>,
+        #[serde(rename = "isWatched", default)]
+        pub is_watched: bool,
+    }
+
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.BreakClip>
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct BreakClip {
+        #[serde(default)]
+        pub id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub title: Option<String>,
+        #[serde(rename = "contentId", skip_serializing_if = "Option::is_none")]
+        pub content_id: Option<String>,
+        #[serde(rename = "contentType", skip_serializing_if = "Option::is_none")]
+        pub content_type: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub duration: Option<f32>,
+        #[serde(rename = "whenSkippable", skip_serializing_if = "Option::is_none")]
+        pub when_skippable: Option<f32>,
+        #[serde(rename = "clickThroughUrl", skip_serializing_if = "Option::is_none")]
+        pub click_through_url: Option<String>,
+        #[serde(rename = "posterUrl", skip_serializing_if = "Option::is_none")]
+        pub poster_url: Option<String>,
     }
 
-    #[derive(Serialize, Deserialize, Debug)]
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.TextTrackStyle>
+    #[derive(Serialize, Deserialize, Clone, Debug)]
+    pub struct TextTrackStyle {
+        #[serde(rename = "backgroundColor", skip_serializing_if = "Option::is_none")]
+        pub background_color: Option<String>,
+        #[serde(rename = "edgeColor", skip_serializing_if = "Option::is_none")]
+        pub edge_color: Option<String>,
+        #[serde(rename = "edgeType", skip_serializing_if = "Option::is_none")]
+        pub edge_type: Option<String>,
+        #[serde(rename = "fontFamily", skip_serializing_if = "Option::is_none")]
+        pub font_family: Option<String>,
+        #[serde(rename = "fontGenericFamily", skip_serializing_if = "Option::is_none")]
+        pub font_generic_family: Option<String>,
+        #[serde(rename = "fontScale", skip_serializing_if = "Option::is_none")]
+        pub font_scale: Option<f32>,
+        #[serde(rename = "fontStyle", skip_serializing_if = "Option::is_none")]
+        pub font_style: Option<String>,
+        #[serde(rename = "foregroundColor", skip_serializing_if = "Option::is_none")]
+        pub foreground_color: Option<String>,
+        #[serde(rename = "windowColor", skip_serializing_if = "Option::is_none")]
+        pub window_color: Option<String>,
+        #[serde(
+            rename = "windowRoundedCornerRadius",
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub window_rounded_corner_radius: Option<u32>,
+        #[serde(rename = "windowType", skip_serializing_if = "Option::is_none")]
+        pub window_type: Option<String>,
+    }
+
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.MediaMetadata>
+    #[derive(Serialize, Deserialize, Clone, Debug)]
     pub struct Metadata {
-        #[serde(rename = "metadataType")]
+        #[serde(rename = "metadataType", default)]
         pub metadata_type: u32,
 
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -214,6 +464,7 @@ pub mod media {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub composer: Option<String>,
 
+        #[serde(default)]
         pub images: Vec<Image>,
 
         #[serde(skip_serializing_if = "Option::is_none", rename = "releaseDate")]
@@ -285,7 +536,8 @@ pub mod media {
         }
     }
 
-    #[derive(Serialize, Deserialize, Debug)]
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.Image>
+    #[derive(Serialize, Deserialize, Clone, Debug)]
     pub struct Image {
         pub url: String,
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -294,33 +546,48 @@ pub mod media {
         pub height: Option<u32>,
     }
 
-    #[derive(Serialize, Debug)]
-    pub struct CustomData {}
+    /// Application specific data passed along with the request.
+    #[derive(Serialize, Clone, Debug, Default)]
+    pub struct CustomData {
+        #[serde(rename = "licenseUrl", skip_serializing_if = "Option::is_none")]
+        pub license_url: Option<String>,
+
+        #[serde(rename = "protectionSystem", skip_serializing_if = "Option::is_none")]
+        pub protection_system: Option<String>,
+
+        #[serde(
+            rename = "licenseRequestHeaders",
+            skip_serializing_if = "Option::is_none"
+        )]
+        pub license_request_headers: Option<HashMap<String, String>>,
+    }
 
     impl CustomData {
         pub fn new() -> CustomData {
-            CustomData {}
+            CustomData::default()
         }
     }
 
-    #[derive(Deserialize, Debug)]
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.ExtendedMediaStatus>
+    #[derive(Deserialize, Clone, Debug)]
     pub struct ExtendedStatus {
-        #[serde(rename = "playerState")]
+        #[serde(rename = "playerState", default)]
         pub player_state: String,
         #[serde(rename = "mediaSessionId")]
         pub media_session_id: Option<i32>,
         pub media: Option<Media>,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.MediaStatus>
+    #[derive(Deserialize, Clone, Debug)]
     pub struct Status {
         #[serde(rename = "mediaSessionId")]
         pub media_session_id: i32,
         #[serde(default)]
         pub media: Option<Media>,
-        #[serde(rename = "playbackRate")]
+        #[serde(rename = "playbackRate", default = "default_playback_rate")]
         pub playback_rate: f32,
-        #[serde(rename = "playerState")]
+        #[serde(rename = "playerState", default)]
         pub player_state: String,
         #[serde(rename = "currentItemId")]
         pub current_item_id: Option<u16>,
@@ -334,11 +601,57 @@ pub mod media {
         pub extended_status: Option<ExtendedStatus>,
         #[serde(rename = "currentTime")]
         pub current_time: Option<f32>,
-        #[serde(rename = "supportedMediaCommands")]
+        #[serde(rename = "supportedMediaCommands", default)]
         pub supported_media_commands: u32,
+        #[serde(rename = "liveSeekableRange")]
+        pub live_seekable_range: Option<LiveSeekableRange>,
+        #[serde(rename = "breakStatus")]
+        pub break_status: Option<BreakStatus>,
+        #[serde(rename = "videoInfo")]
+        pub video_info: Option<VideoInfo>,
+        #[serde(rename = "activeTrackIds", default)]
+        pub active_track_ids: Vec<u32>,
+    }
+
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.VideoInformation>
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct VideoInfo {
+        #[serde(default)]
+        pub width: u32,
+        #[serde(default)]
+        pub height: u32,
+        #[serde(rename = "hdrType", default)]
+        pub hdr_type: String,
+    }
+
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.BreakStatus>
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct BreakStatus {
+        #[serde(rename = "currentBreakTime")]
+        pub current_break_time: Option<f32>,
+        #[serde(rename = "currentBreakClipTime")]
+        pub current_break_clip_time: Option<f32>,
+        #[serde(rename = "breakId")]
+        pub break_id: Option<String>,
+        #[serde(rename = "breakClipId")]
+        pub break_clip_id: Option<String>,
+        #[serde(rename = "whenSkippable")]
+        pub when_skippable: Option<f32>,
+    }
+
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.LiveSeekableRange>
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct LiveSeekableRange {
+        pub start: Option<f32>,
+        pub end: Option<f32>,
+        #[serde(rename = "isMovingWindow", default)]
+        pub is_moving_window: bool,
+        #[serde(rename = "isLiveDone", default)]
+        pub is_live_done: bool,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// `MEDIA_STATUS` reply.
+    #[derive(Deserialize, Clone, Debug)]
     #[allow(dead_code)]
     pub struct StatusReply {
         #[serde(rename = "requestId", default)]
@@ -347,28 +660,92 @@ pub mod media {
         #[serde(rename = "type")]
         pub typ: String,
 
-        pub status: Vec<Status>,
+        /// Status entries are kept raw, so that every entry can be parsed individually and a
+        /// single malformed entry doesn't break the whole status.
+        #[serde(default)]
+        pub status: Vec<serde_json::Value>,
+    }
+
+    fn default_playback_rate() -> f32 {
+        1.0
     }
 
-    #[derive(Deserialize, Debug)]
+    /// `LOAD_CANCELLED` reply.
+    #[derive(Deserialize, Clone, Debug)]
     pub struct LoadCancelledReply {
         #[serde(rename = "requestId")]
         pub request_id: u32,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// `LOAD_FAILED` reply.
+    #[derive(Deserialize, Clone, Debug)]
     pub struct LoadFailedReply {
         #[serde(rename = "requestId")]
         pub request_id: u32,
+
+        #[serde(rename = "detailedErrorCode")]
+        pub detailed_error_code: Option<i32>,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// `INVALID_PLAYER_STATE` reply.
+    #[derive(Deserialize, Clone, Debug)]
     pub struct InvalidPlayerStateReply {
         #[serde(rename = "requestId")]
         pub request_id: u32,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// `QUEUE_ITEM_IDS` reply.
+    #[derive(Deserialize, Clone, Debug)]
+    #[allow(dead_code)]
+    pub struct QueueItemIdsReply {
+        #[serde(rename = "requestId", default)]
+        pub request_id: u32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(rename = "itemIds", default)]
+        pub item_ids: Vec<u16>,
+    }
+
+    /// `QUEUE_ITEMS` reply.
+    #[derive(Deserialize, Clone, Debug)]
+    #[allow(dead_code)]
+    pub struct QueueItemsReply {
+        #[serde(rename = "requestId", default)]
+        pub request_id: u32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(default)]
+        pub items: Vec<QueueItemReply>,
+    }
+
+    /// <https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.QueueItem>
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct QueueItemReply {
+        #[serde(rename = "itemId")]
+        pub item_id: u16,
+
+        pub media: Option<Media>,
+
+        #[serde(default = "default_autoplay")]
+        pub autoplay: bool,
+
+        #[serde(rename = "startTime")]
+        pub start_time: Option<f64>,
+
+        #[serde(rename = "preloadTime")]
+        pub preload_time: Option<f64>,
+    }
+
+    fn default_autoplay() -> bool {
+        true
+    }
+
+    /// `INVALID_REQUEST` reply.
+    #[derive(Deserialize, Clone, Debug)]
     #[allow(dead_code)]
     pub struct InvalidRequestReply {
         #[serde(rename = "requestId")]
@@ -381,14 +758,18 @@ pub mod media {
     }
 
     /// The media error encountered during media operations.
-    #[derive(Deserialize, Debug, PartialEq)]
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
     #[serde(rename_all = "camelCase")]
     pub struct MediaErrorReply {
+        /// Unique id of the request that caused the error if available.
+        pub request_id: Option<u32>,
         /// The detailed error code associated with the media error.
-        pub detailed_error_code: i32,
+        pub detailed_error_code: Option<i32>,
         /// The type of the error message.
         #[serde(rename = "type")]
         pub message_type: String,
+        /// Description of the error reason if available.
+        pub reason: Option<String>,
     }
 }
 
@@ -398,7 +779,8 @@ pub mod receiver {
 
     use serde_derive::{Deserialize, Serialize};
 
-    #[derive(Serialize, Debug)]
+    /// `LAUNCH` request.
+    #[derive(Serialize, Clone, Debug)]
     pub struct AppLaunchRequest {
         #[serde(rename = "requestId")]
         pub request_id: u32,
@@ -408,9 +790,22 @@ pub mod receiver {
 
         #[serde(rename = "appId")]
         pub app_id: String,
+
+        #[serde(rename = "credentialsData", skip_serializing_if = "Option::is_none")]
+        pub credentials_data: Option<CredentialsData>,
     }
 
-    #[derive(Serialize, Debug)]
+    /// Credentials passed with the `LAUNCH` request.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct CredentialsData {
+        pub credentials: String,
+
+        #[serde(rename = "credentialsType", skip_serializing_if = "Option::is_none")]
+        pub credentials_type: Option<String>,
+    }
+
+    /// `STOP` request.
+    #[derive(Serialize, Clone, Debug)]
     pub struct AppStopRequest<'a> {
         #[serde(rename = "requestId")]
         pub request_id: u32,
@@ -422,7 +817,8 @@ pub mod receiver {
         pub session_id: Cow<'a, str>,
     }
 
-    #[derive(Serialize, Debug)]
+    /// `GET_STATUS` request.
+    #[derive(Serialize, Clone, Debug)]
     pub struct GetStatusRequest {
         #[serde(rename = "requestId")]
         pub request_id: u32,
@@ -431,7 +827,20 @@ pub mod receiver {
         pub typ: String,
     }
 
-    #[derive(Serialize, Debug)]
+    /// `SET_STANDBY` request.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct SetStandbyRequest {
+        #[serde(rename = "requestId")]
+        pub request_id: u32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        pub standby: bool,
+    }
+
+    /// `SET_VOLUME` request.
+    #[derive(Serialize, Clone, Debug)]
     pub struct SetVolumeRequest {
         #[serde(rename = "requestId")]
         pub request_id: u32,
@@ -442,10 +851,12 @@ pub mod receiver {
         pub volume: Volume,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// `RECEIVER_STATUS` reply.
+    #[derive(Deserialize, Clone, Debug)]
     #[allow(dead_code)]
     pub struct StatusReply {
-        #[serde(rename = "requestId")]
+        /// Broadcasts of some devices (e.g. speakers) omit the request id.
+        #[serde(rename = "requestId", default)]
         pub request_id: u32,
 
         #[serde(rename = "type")]
@@ -454,27 +865,30 @@ pub mod receiver {
         pub status: Status,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// Status of the receiver.
+    #[derive(Deserialize, Clone, Debug)]
     pub struct Status {
         #[serde(default)]
         pub applications: Vec<Application>,
 
-        #[serde(rename = "isActiveInput", default)]
-        pub is_active_input: bool,
+        #[serde(rename = "isActiveInput")]
+        pub is_active_input: Option<bool>,
 
-        #[serde(rename = "isStandBy", default)]
-        pub is_stand_by: bool,
+        #[serde(rename = "isStandBy")]
+        pub is_stand_by: Option<bool>,
 
         /// Volume parameters of the currently active cast device.
+        #[serde(default)]
         pub volume: Volume,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// Application running on the receiver.
+    #[derive(Deserialize, Clone, Debug)]
     pub struct Application {
         #[serde(rename = "appId")]
         pub app_id: String,
 
-        #[serde(rename = "sessionId")]
+        #[serde(rename = "sessionId", default)]
         pub session_id: String,
 
         #[serde(rename = "transportId", default)]
@@ -483,28 +897,36 @@ pub mod receiver {
         #[serde(default)]
         pub namespaces: Vec<AppNamespace>,
 
-        #[serde(rename = "displayName")]
+        #[serde(rename = "displayName", default)]
         pub display_name: String,
 
-        #[serde(rename = "statusText")]
+        #[serde(rename = "statusText", default)]
         pub status_text: String,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// Namespace supported by the application.
+    #[derive(Deserialize, Clone, Debug)]
     pub struct AppNamespace {
         pub name: String,
     }
 
     /// Structure that describes possible cast device volume options.
-    #[derive(Deserialize, Serialize, Debug)]
+    #[derive(Deserialize, Serialize, Clone, Debug, Default)]
     pub struct Volume {
         /// Volume level.
         pub level: Option<f32>,
         /// Mute/unmute state.
         pub muted: Option<bool>,
+        /// Native volume step of the device.
+        #[serde(rename = "stepInterval", skip_serializing)]
+        pub step_interval: Option<f32>,
+        /// Volume control type of the device (`attenuation`, `fixed` or `master`).
+        #[serde(rename = "controlType", skip_serializing)]
+        pub control_type: Option<String>,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// `LAUNCH_ERROR` reply.
+    #[derive(Deserialize, Clone, Debug)]
     #[allow(dead_code)]
     pub struct LaunchErrorReply {
         #[serde(rename = "requestId")]
@@ -516,7 +938,8 @@ pub mod receiver {
         pub reason: Option<String>,
     }
 
-    #[derive(Deserialize, Debug)]
+    /// `INVALID_REQUEST` reply.
+    #[derive(Deserialize, Clone, Debug)]
     #[allow(dead_code)]
     pub struct InvalidRequestReply {
         #[serde(rename = "requestId")]
@@ -528,3 +951,202 @@ pub mod receiver {
         pub reason: Option<String>,
     }
 }
+
+/// Proxy classes for the `multizone` channel.
+pub mod multizone {
+    use serde_derive::{Deserialize, Serialize};
+
+    use super::receiver::Volume;
+
+    /// `GET_STATUS` request.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct GetStatusRequest {
+        #[serde(rename = "requestId")]
+        pub request_id: u32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+    }
+
+    /// `SET_DEVICE_VOLUME` request.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct SetDeviceVolumeRequest {
+        #[serde(rename = "requestId")]
+        pub request_id: u32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(rename = "deviceId")]
+        pub device_id: String,
+
+        pub volume: Volume,
+    }
+
+    /// `MULTIZONE_STATUS` reply.
+    #[derive(Deserialize, Clone, Debug)]
+    #[allow(dead_code)]
+    pub struct StatusReply {
+        #[serde(rename = "requestId", default)]
+        pub request_id: u32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        pub status: Status,
+    }
+
+    /// Status of the speaker group.
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct Status {
+        #[serde(default)]
+        pub devices: Vec<Device>,
+
+        #[serde(rename = "isMultichannel", default)]
+        pub is_multichannel: bool,
+    }
+
+    /// Member device of the speaker group.
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct Device {
+        #[serde(rename = "deviceId")]
+        pub device_id: String,
+
+        #[serde(default)]
+        pub name: String,
+
+        #[serde(default)]
+        pub capabilities: u64,
+
+        #[serde(default)]
+        pub volume: Volume,
+    }
+
+    /// `DEVICE_ADDED` or `DEVICE_UPDATED` reply.
+    #[derive(Deserialize, Clone, Debug)]
+    #[allow(dead_code)]
+    pub struct DeviceReply {
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(rename = "requestId", default)]
+        pub request_id: u32,
+
+        pub device: Device,
+    }
+
+    /// `DEVICE_REMOVED` reply.
+    #[derive(Deserialize, Clone, Debug)]
+    #[allow(dead_code)]
+    pub struct DeviceRemovedReply {
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(rename = "deviceId")]
+        pub device_id: String,
+    }
+}
+
+/// Proxy classes for the `youtube` channel.
+#[cfg(feature = "youtube")]
+pub mod youtube {
+    use serde_derive::{Deserialize, Serialize};
+
+    /// `getMdxSessionStatus` request.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct GetMdxSessionStatusRequest {
+        #[serde(rename = "type")]
+        pub typ: String,
+    }
+
+    /// `mdxSessionStatus` reply.
+    #[derive(Deserialize, Clone, Debug)]
+    #[allow(dead_code)]
+    pub struct MdxSessionStatusReply {
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        pub data: MdxSessionStatus,
+    }
+
+    /// Status of the `mdx` session.
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct MdxSessionStatus {
+        #[serde(rename = "screenId")]
+        pub screen_id: String,
+
+        #[serde(rename = "deviceId")]
+        pub device_id: Option<String>,
+    }
+
+    /// Reply of the Lounge API `get_lounge_token_batch` endpoint.
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct LoungeTokenBatchReply {
+        #[serde(default)]
+        pub screens: Vec<LoungeToken>,
+    }
+
+    /// Lounge token of the screen.
+    #[derive(Deserialize, Clone, Debug)]
+    #[allow(dead_code)]
+    pub struct LoungeToken {
+        #[serde(rename = "screenId")]
+        pub screen_id: String,
+
+        #[serde(rename = "loungeToken")]
+        pub lounge_token: String,
+
+        pub expiration: Option<u64>,
+    }
+}
+
+/// Proxy classes for the `error` channel.
+pub mod error {
+    use serde_derive::Deserialize;
+
+    /// Transport level error.
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct ErrorReply {
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(rename = "requestId", default)]
+        pub request_id: u32,
+
+        pub reason: Option<String>,
+    }
+}
+
+/// Proxy classes for the `setup` channel.
+pub mod setup {
+    use serde_derive::{Deserialize, Serialize};
+
+    /// Request of the setup namespace, e.g. `eureka_info`.
+    #[derive(Serialize, Clone, Debug)]
+    pub struct SetupRequest {
+        // Unlike other namespaces, setup one uses snake case.
+        pub request_id: u32,
+
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        pub data: serde_json::Value,
+    }
+
+    /// Reply of the setup namespace.
+    #[derive(Deserialize, Clone, Debug)]
+    pub struct SetupReply {
+        #[serde(rename = "type")]
+        pub typ: String,
+
+        #[serde(default)]
+        pub request_id: u32,
+
+        pub response_code: Option<u16>,
+
+        pub response_string: Option<String>,
+
+        #[serde(default)]
+        pub data: serde_json::Value,
+    }
+}
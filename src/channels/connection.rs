@@ -1,43 +1,140 @@
-use std::{
-    borrow::Cow,
-    io::{Read, Write},
-};
+use std::io::{Read, Write};
+
+use serde_derive::{Deserialize, Serialize};
 
 use crate::{
     cast::proxies,
     errors::Error,
     message_manager::{CastMessage, CastMessagePayload, MessageManager},
-    Lrc,
+    namespaces, Lrc,
 };
 
-pub(crate) const CHANNEL_NAMESPACE: &str = "urn:x-cast:com.google.cast.tp.connection";
+pub(crate) const CHANNEL_NAMESPACE: &str = namespaces::CONNECTION;
 const CHANNEL_USER_AGENT: &str = "RustCast";
 
 const MESSAGE_TYPE_CONNECT: &str = "CONNECT";
 const MESSAGE_TYPE_CLOSE: &str = "CLOSE";
 
-#[derive(Clone, Debug)]
+/// Type of the virtual connection, see `VirtualConnectionOptions::conn_type`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionType {
+    /// Regular connection that keeps the application alive.
+    Strong,
+    /// Connection that doesn't prevent the application from being stopped when idle.
+    Weak,
+    /// Connection that isn't reported to the application as a sender.
+    Invisible,
+}
+
+impl ConnectionType {
+    fn encode(self) -> u32 {
+        match self {
+            ConnectionType::Strong => 0,
+            ConnectionType::Weak => 1,
+            ConnectionType::Invisible => 2,
+        }
+    }
+}
+
+/// Details about the sender that official senders report in the `CONNECT` message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SenderInfo {
+    /// Type of the sender SDK (e.g. `2` for Chrome).
+    pub sdk_type: u32,
+    /// Version of the sender SDK or the sender platform.
+    pub version: String,
+    /// Version of the browser, only reported by web senders.
+    pub browser_version: Option<String>,
+    /// Platform the sender runs on (e.g. `4` for Linux).
+    pub platform: u32,
+    /// Type of the network connection with the receiver (e.g. `1` for local connection).
+    pub connection_type: u32,
+}
+
+/// Additional fields of the `CONNECT` message of the virtual connection (unlike
+/// `rust_cast::ConnectOptions` of the connection with the device itself), see
+/// `ConnectionChannel::connect_with`. Some receivers (and applications) behave differently unless
+/// they are provided.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VirtualConnectionOptions {
+    /// User agent of the sender, `RustCast` by default.
+    pub user_agent: String,
+    /// Type of the virtual connection, receiver assumes `ConnectionType::Strong` if not provided.
+    pub conn_type: Option<ConnectionType>,
+    /// Origin of the sender, official senders send an empty JSON object.
+    pub origin: Option<serde_json::Value>,
+    /// Details about the sender.
+    pub sender_info: Option<SenderInfo>,
+}
+
+impl Default for VirtualConnectionOptions {
+    fn default() -> Self {
+        VirtualConnectionOptions {
+            user_agent: CHANNEL_USER_AGENT.to_string(),
+            conn_type: None,
+            origin: None,
+            sender_info: None,
+        }
+    }
+}
+
+/// Reason the receiver has given for closing the virtual connection, both fields are optional in
+/// the `CLOSE` message.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CloseReason {
+    /// Numeric reason code, e.g. the transport of the stopped application has been closed.
+    pub code: Option<i64>,
+    /// Human readable description of the reason.
+    pub description: Option<String>,
+}
+
+impl std::fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.code, self.description.as_deref()) {
+            (Some(code), Some(description)) => write!(f, "{description} (code {code})"),
+            (Some(code), None) => write!(f, "code {code}"),
+            (None, Some(description)) => write!(f, "{description}"),
+            (None, None) => write!(f, "unknown"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ConnectionResponse {
     Connect,
-    Close,
+    /// Receiver has closed the virtual connection.
+    Close(CloseReason),
     NotImplemented(String, serde_json::Value),
 }
 
-pub struct ConnectionChannel<'a, W>
+pub struct ConnectionChannel<W>
 where
     W: Read + Write,
 {
-    sender: Cow<'a, str>,
+    sender: String,
     message_manager: Lrc<MessageManager<W>>,
 }
 
-impl<'a, W> ConnectionChannel<'a, W>
+/// Cloned channel shares the connection with the original one.
+impl<W> Clone for ConnectionChannel<W>
+where
+    W: Read + Write,
+{
+    fn clone(&self) -> Self {
+        ConnectionChannel {
+            sender: self.sender.clone(),
+            message_manager: Lrc::clone(&self.message_manager),
+        }
+    }
+}
+
+impl<W> ConnectionChannel<W>
 where
     W: Read + Write,
 {
-    pub fn new<S>(sender: S, message_manager: Lrc<MessageManager<W>>) -> ConnectionChannel<'a, W>
+    pub fn new<S>(sender: S, message_manager: Lrc<MessageManager<W>>) -> ConnectionChannel<W>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
         ConnectionChannel {
             sender: sender.into(),
@@ -47,53 +144,135 @@ where
 
     pub fn connect<S>(&self, destination: S) -> Result<(), Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
+        self.connect_with(destination, &VirtualConnectionOptions::default())
+    }
+
+    /// Establishes virtual connection with the `destination` providing additional fields in the
+    /// `CONNECT` message. Fields are sent again (as they are) every time the connection is
+    /// automatically re-established.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::{
+    /// #     channels::connection::{ConnectionType, VirtualConnectionOptions},
+    /// #     CastDevice,
+    /// # };
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.connection.connect_with(
+    ///     "receiver-0",
+    ///     &VirtualConnectionOptions {
+    ///         conn_type: Some(ConnectionType::Strong),
+    ///         origin: Some(serde_json::json!({})),
+    ///         ..VirtualConnectionOptions::default()
+    ///     },
+    /// )?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `receiver-0` or transport id of the application.
+    /// * `options` - Additional fields of the `CONNECT` message.
+    pub fn connect_with<S>(
+        &self,
+        destination: S,
+        options: &VirtualConnectionOptions,
+    ) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        trace_span!(
+            "connection.connect",
+            namespace = CHANNEL_NAMESPACE,
+            destination = %destination
+        );
+
         let payload = serde_json::to_string(&proxies::connection::ConnectionRequest {
             typ: MESSAGE_TYPE_CONNECT.to_string(),
-            user_agent: CHANNEL_USER_AGENT.to_string(),
+            user_agent: options.user_agent.clone(),
+            conn_type: options.conn_type.map(ConnectionType::encode),
+            origin: options.origin.clone(),
+            sender_info: options.sender_info.as_ref().map(|sender_info| {
+                proxies::connection::SenderInfo {
+                    sdk_type: sender_info.sdk_type,
+                    version: sender_info.version.clone(),
+                    browser_version: sender_info.browser_version.clone(),
+                    platform: sender_info.platform,
+                    connection_type: sender_info.connection_type,
+                }
+            }),
         })?;
 
-        self.message_manager.send(CastMessage {
+        let message = CastMessage {
             namespace: CHANNEL_NAMESPACE.to_string(),
             source: self.sender.to_string(),
-            destination: destination.into().to_string(),
+            destination: destination.to_string(),
             payload: CastMessagePayload::String(payload),
-        })
+        };
+
+        self.message_manager.send(message.clone())?;
+        self.message_manager.track_connection(message);
+
+        Ok(())
     }
 
     pub fn disconnect<S>(&self, destination: S) -> Result<(), Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
+        let destination = destination.into();
+        trace_span!(
+            "connection.disconnect",
+            namespace = CHANNEL_NAMESPACE,
+            destination = %destination
+        );
+
         let payload = serde_json::to_string(&proxies::connection::ConnectionRequest {
             typ: MESSAGE_TYPE_CLOSE.to_string(),
             user_agent: CHANNEL_USER_AGENT.to_string(),
+            conn_type: None,
+            origin: None,
+            sender_info: None,
         })?;
 
+        self.message_manager
+            .untrack_connection(&self.sender, &destination);
         self.message_manager.send(CastMessage {
             namespace: CHANNEL_NAMESPACE.to_string(),
             source: self.sender.to_string(),
-            destination: destination.into().to_string(),
+            destination: destination.to_string(),
             payload: CastMessagePayload::String(payload),
         })
     }
 
+    /// Checks whether virtual connection with the `destination` is established and hasn't been
+    /// closed by the receiver. Note that closed connection is automatically re-established on the
+    /// next message sent to the `destination`.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `receiver-0` or transport id of the application.
+    pub fn is_connected(&self, destination: &str) -> bool {
+        self.connected_destinations()
+            .iter()
+            .any(|connected| connected == destination)
+    }
+
+    /// Returns destinations of all virtual connections that are currently established.
+    pub fn connected_destinations(&self) -> Vec<String> {
+        self.message_manager.connected_destinations(&self.sender)
+    }
+
     pub fn can_handle(&self, message: &CastMessage) -> bool {
         message.namespace == CHANNEL_NAMESPACE
     }
 
     pub fn parse(&self, message: &CastMessage) -> Result<ConnectionResponse, Error> {
-        let reply = match message.payload {
-            CastMessagePayload::String(ref payload) => {
-                serde_json::from_str::<serde_json::Value>(payload)?
-            }
-            _ => {
-                return Err(Error::Internal(
-                    "Binary payload is not supported!".to_string(),
-                ))
-            }
-        };
+        let reply = message.payload.to_json()?;
 
         let message_type = reply
             .as_object()
@@ -104,10 +283,124 @@ where
 
         let response = match message_type.as_ref() {
             MESSAGE_TYPE_CONNECT => ConnectionResponse::Connect,
-            MESSAGE_TYPE_CLOSE => ConnectionResponse::Close,
+            MESSAGE_TYPE_CLOSE => ConnectionResponse::Close(close_reason(&reply)),
             _ => ConnectionResponse::NotImplemented(message_type.to_string(), reply),
         };
 
         Ok(response)
     }
 }
+
+/// Checks whether `message` is a `CLOSE` message sent over the connection namespace.
+///
+/// # Return value
+///
+/// Reason of the close if `message` is a `CLOSE` message, `None` otherwise.
+pub(crate) fn parse_close_message(message: &CastMessage) -> Option<CloseReason> {
+    if message.namespace != CHANNEL_NAMESPACE {
+        return None;
+    }
+
+    message
+        .payload
+        .to_json()
+        .ok()
+        .filter(|reply| {
+            reply.get("type").and_then(|property| property.as_str()) == Some(MESSAGE_TYPE_CLOSE)
+        })
+        .map(|reply| close_reason(&reply))
+}
+
+/// Extracts optional `reasonCode` and `reason` fields of the `CLOSE` message.
+fn close_reason(reply: &serde_json::Value) -> CloseReason {
+    CloseReason {
+        code: reply.get("reasonCode").and_then(|code| code.as_i64()),
+        description: reply
+            .get("reason")
+            .and_then(|reason| reason.as_str())
+            .map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::MockTcpStream, DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID};
+
+    #[test]
+    fn test_connect_with_options() {
+        let stream = MockTcpStream::new();
+        let channel = ConnectionChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        channel.connect(DEFAULT_RECEIVER_ID).unwrap();
+        channel
+            .connect_with(
+                "web-5",
+                &VirtualConnectionOptions {
+                    conn_type: Some(ConnectionType::Weak),
+                    origin: Some(serde_json::json!({})),
+                    sender_info: Some(SenderInfo {
+                        sdk_type: 2,
+                        version: "X11; Linux x86_64".to_string(),
+                        browser_version: None,
+                        platform: 4,
+                        connection_type: 1,
+                    }),
+                    ..VirtualConnectionOptions::default()
+                },
+            )
+            .unwrap();
+
+        let connect = stream.received_message(0).unwrap().cast_message();
+        let connect = connect.payload.to_json().unwrap();
+        assert_eq!(
+            serde_json::json!({"type": "CONNECT", "userAgent": "RustCast"}),
+            connect
+        );
+
+        let connect = stream.received_message(1).unwrap().cast_message();
+        assert_eq!("web-5", connect.destination);
+        let connect = connect.payload.to_json().unwrap();
+        assert_eq!(1, connect["connType"]);
+        assert_eq!(serde_json::json!({}), connect["origin"]);
+        assert_eq!(2, connect["senderInfo"]["sdkType"]);
+        assert_eq!(1, connect["senderInfo"]["connectionType"]);
+        assert!(connect["senderInfo"].get("browserVersion").is_none());
+        assert!(channel.is_connected("web-5"));
+    }
+
+    #[test]
+    fn test_parse_close() {
+        let channel = ConnectionChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
+        let close = |payload: &str| CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: "web-1".to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(payload.to_string()),
+        };
+
+        match channel
+            .parse(&close(r#"{"type":"CLOSE","reasonCode":1}"#))
+            .unwrap()
+        {
+            ConnectionResponse::Close(reason) => {
+                assert_eq!(Some(1), reason.code);
+                assert_eq!(None, reason.description);
+            }
+            response => panic!("unexpected response {response:?}"),
+        }
+        assert!(matches!(
+            channel.parse(&close(r#"{"type":"CLOSE"}"#)).unwrap(),
+            ConnectionResponse::Close(CloseReason {
+                code: None,
+                description: None
+            })
+        ));
+    }
+}
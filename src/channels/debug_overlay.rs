@@ -0,0 +1,312 @@
+//! Development helpers for the custom receiver applications: the debug overlay
+//! (`urn:x-cast:com.google.cast.debugoverlay`) can be shown, hidden and cleared from the sender and
+//! the receiver-side logs (`urn:x-cast:com.google.cast.debuglogger`) can be streamed back to it.
+//!
+//! Receivers don't handle these namespaces out of the box, the receiver application has to forward
+//! the overlay messages to `CastDebugLogger` of the Cast Application Framework and send its log
+//! messages (of at least the requested `level`) back to the sender, e.g.:
+//!
+//! ```js
+//! const context = cast.framework.CastReceiverContext.getInstance();
+//! const debugLogger = cast.debug.CastDebugLogger.getInstance();
+//! let logSender = null;
+//!
+//! context.addCustomMessageListener('urn:x-cast:com.google.cast.debugoverlay', (event) => {
+//!   debugLogger.setEnabled(true);
+//!   if (event.data.type === 'CLEAR_OVERLAY') debugLogger.clearDebugLogs();
+//!   else debugLogger.showDebugLogs(event.data.type === 'SHOW_OVERLAY');
+//! });
+//! context.addCustomMessageListener('urn:x-cast:com.google.cast.debuglogger', (event) => {
+//!   logSender = event.data.enabled ? event.senderId : null;
+//! });
+//!
+//! function log(level, tag, message) {
+//!   debugLogger.info(tag, message);
+//!   if (logSender) context.sendCustomMessage('urn:x-cast:com.google.cast.debuglogger', logSender,
+//!     { type: 'LOG', level, tag, message, timestamp: Date.now() });
+//! }
+//! ```
+
+use std::io::{Read, Write};
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    errors::Error,
+    message_manager::{CastMessage, CastMessagePayload, MessageManager},
+    namespaces, Lrc,
+};
+
+const OVERLAY_NAMESPACE: &str = namespaces::DEBUG_OVERLAY;
+const LOGGER_NAMESPACE: &str = namespaces::DEBUG_LOGGER;
+
+const MESSAGE_TYPE_SHOW_OVERLAY: &str = "SHOW_OVERLAY";
+const MESSAGE_TYPE_HIDE_OVERLAY: &str = "HIDE_OVERLAY";
+const MESSAGE_TYPE_CLEAR_OVERLAY: &str = "CLEAR_OVERLAY";
+const MESSAGE_TYPE_SET_LOGGER: &str = "SET_LOGGER";
+const MESSAGE_TYPE_LOG: &str = "LOG";
+
+/// Level of the receiver-side log messages, same as `cast.framework.LoggerLevel`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LogLevel {
+    /// Detailed tracing messages.
+    Verbose,
+    /// Debugging messages.
+    Debug,
+    /// Informational messages.
+    Info,
+    /// Recoverable problems.
+    Warning,
+    /// Failures.
+    Error,
+}
+
+/// Log message streamed by the receiver application.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Transport id of the application the message comes from.
+    pub source: String,
+    /// Level of the message, `None` if the receiver reported level that isn't known.
+    pub level: Option<LogLevel>,
+    /// Tag the message has been logged with, e.g. `MyApp.Player`.
+    pub tag: Option<String>,
+    /// Text of the message.
+    pub message: String,
+    /// Time the message has been logged at (milliseconds since UNIX epoch), if reported.
+    pub timestamp: Option<u64>,
+}
+
+/// Represents all currently supported incoming messages that debug overlay channel can handle.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum DebugOverlayResponse {
+    /// Log message of the receiver application.
+    Log(LogEntry),
+    /// Used every time we can't parse the message. Associated data contains `type` string field
+    /// and raw JSON data returned from cast device.
+    NotImplemented(String, Value),
+}
+
+/// Channel that controls debug overlay of the custom receiver applications and receives their
+/// logs, see the module documentation for the receiver side of it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_cast::{channels::debug_overlay::LogLevel, CastDevice};
+///
+/// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// # let transport_id = "web-4";
+/// cast_device.debug_overlay.set_overlay_visible(transport_id, true)?;
+/// cast_device.debug_overlay.set_logging(transport_id, Some(LogLevel::Debug))?;
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+pub struct DebugOverlayChannel<W>
+where
+    W: Read + Write,
+{
+    sender: String,
+    message_manager: Lrc<MessageManager<W>>,
+}
+
+/// Cloned channel shares the connection with the original one.
+impl<W> Clone for DebugOverlayChannel<W>
+where
+    W: Read + Write,
+{
+    fn clone(&self) -> Self {
+        DebugOverlayChannel {
+            sender: self.sender.clone(),
+            message_manager: Lrc::clone(&self.message_manager),
+        }
+    }
+}
+
+impl<W> DebugOverlayChannel<W>
+where
+    W: Read + Write,
+{
+    pub fn new<S>(sender: S, message_manager: Lrc<MessageManager<W>>) -> DebugOverlayChannel<W>
+    where
+        S: Into<String>,
+    {
+        DebugOverlayChannel {
+            sender: sender.into(),
+            message_manager,
+        }
+    }
+
+    /// Shows or hides debug overlay of the receiver application.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `transport_id` of the application;
+    /// * `visible` - Whether the overlay should be shown.
+    pub fn set_overlay_visible<S>(&self, destination: S, visible: bool) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let typ = if visible {
+            MESSAGE_TYPE_SHOW_OVERLAY
+        } else {
+            MESSAGE_TYPE_HIDE_OVERLAY
+        };
+
+        self.send_message(OVERLAY_NAMESPACE, destination, json!({ "type": typ }))
+    }
+
+    /// Clears log messages displayed in the debug overlay of the receiver application.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `transport_id` of the application.
+    pub fn clear_overlay<S>(&self, destination: S) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        self.send_message(
+            OVERLAY_NAMESPACE,
+            destination,
+            json!({ "type": MESSAGE_TYPE_CLEAR_OVERLAY }),
+        )
+    }
+
+    /// Starts streaming log messages of the receiver application to this sender, they are
+    /// returned by `CastDevice::receive` as `DebugOverlayResponse::Log`.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `transport_id` of the application;
+    /// * `level` - Minimum level of the streamed messages, `None` stops streaming.
+    pub fn set_logging<S>(&self, destination: S, level: Option<LogLevel>) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        self.send_message(
+            LOGGER_NAMESPACE,
+            destination,
+            json!({
+                "type": MESSAGE_TYPE_SET_LOGGER,
+                "enabled": level.is_some(),
+                "level": level.unwrap_or(LogLevel::Verbose),
+            }),
+        )
+    }
+
+    fn send_message<S>(&self, namespace: &str, destination: S, payload: Value) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        trace_span!(
+            "debug_overlay.send_message",
+            namespace,
+            destination = %destination
+        );
+
+        self.message_manager.send(CastMessage {
+            namespace: namespace.to_string(),
+            source: self.sender.to_string(),
+            destination,
+            payload: CastMessagePayload::String(serde_json::to_string(&payload)?),
+        })
+    }
+
+    pub fn can_handle(&self, message: &CastMessage) -> bool {
+        message.namespace == OVERLAY_NAMESPACE || message.namespace == LOGGER_NAMESPACE
+    }
+
+    pub fn parse(&self, message: &CastMessage) -> Result<DebugOverlayResponse, Error> {
+        let reply = message.payload.to_json()?;
+
+        let message_type = reply["type"].as_str().unwrap_or("");
+
+        let response = match (message.namespace.as_ref(), message_type) {
+            (LOGGER_NAMESPACE, MESSAGE_TYPE_LOG) => DebugOverlayResponse::Log(LogEntry {
+                source: message.source.clone(),
+                level: serde_json::from_value(reply["level"].clone()).ok(),
+                tag: reply["tag"].as_str().map(str::to_string),
+                message: reply["message"].as_str().unwrap_or("").to_string(),
+                timestamp: reply["timestamp"].as_u64(),
+            }),
+            _ => DebugOverlayResponse::NotImplemented(message_type.to_string(), reply),
+        };
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::MockTcpStream, DEFAULT_SENDER_ID};
+
+    #[test]
+    fn test_set_overlay_and_logging() {
+        let stream = MockTcpStream::new();
+        let channel = DebugOverlayChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        channel.set_overlay_visible("web-4", true).unwrap();
+        channel.set_logging("web-4", Some(LogLevel::Info)).unwrap();
+        channel.set_logging("web-4", None).unwrap();
+
+        let overlay = stream.received_message(0).unwrap().cast_message();
+        assert_eq!(OVERLAY_NAMESPACE, overlay.namespace);
+        assert_eq!("web-4", overlay.destination);
+        assert_eq!(
+            json!({"type": "SHOW_OVERLAY"}),
+            overlay.payload.to_json().unwrap()
+        );
+        let logger = stream.received_message(1).unwrap().cast_message();
+        assert_eq!(LOGGER_NAMESPACE, logger.namespace);
+        assert_eq!(
+            json!({"type": "SET_LOGGER", "enabled": true, "level": "INFO"}),
+            logger.payload.to_json().unwrap()
+        );
+        assert_eq!(
+            Value::from(false),
+            stream
+                .received_message(2)
+                .unwrap()
+                .cast_message()
+                .payload
+                .to_json()
+                .unwrap()["enabled"]
+        );
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let channel = DebugOverlayChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
+        let message = CastMessage {
+            namespace: LOGGER_NAMESPACE.to_string(),
+            source: "web-4".to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(
+                r#"{"type":"LOG","level":"WARNING","tag":"MyApp","message":"stalled","timestamp":1}"#
+                    .to_string(),
+            ),
+        };
+
+        match channel.parse(&message).unwrap() {
+            DebugOverlayResponse::Log(entry) => assert_eq!(
+                LogEntry {
+                    source: "web-4".to_string(),
+                    level: Some(LogLevel::Warning),
+                    tag: Some("MyApp".to_string()),
+                    message: "stalled".to_string(),
+                    timestamp: Some(1),
+                },
+                entry
+            ),
+            response => panic!("unexpected response {response:?}"),
+        }
+    }
+}
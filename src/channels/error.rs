@@ -0,0 +1,194 @@
+use std::{
+    fmt,
+    io::{Read, Write},
+};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{
+    cast::proxies,
+    errors::Error,
+    message_manager::{CastMessage, MessageManager, RequestId},
+    namespaces, Lrc,
+};
+
+pub(crate) const CHANNEL_NAMESPACE: &str = namespaces::ERROR;
+
+/// Error reported by the cast device at the transport level (e.g. `INVALID_REQUEST` for the
+/// message it couldn't route), rather than by the particular application namespace.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransportError {
+    /// Unique id of the request that caused this error, `RequestId::UNSOLICITED` if unknown.
+    pub request_id: RequestId,
+    /// Type of the error, e.g. `INVALID_REQUEST`.
+    pub error_type: String,
+    /// Description of the error reason if available.
+    pub reason: Option<String>,
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error_type)?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({reason})")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Represents all currently supported incoming messages that error channel can handle.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ErrorResponse {
+    /// Transport level error.
+    Error(TransportError),
+    /// Used every time we can't parse the message. Associated data contains `type` string field
+    /// and raw JSON data returned from cast device.
+    NotImplemented(String, serde_json::Value),
+}
+
+/// Channel that parses transport level errors, the latest one is also available via
+/// `ErrorChannel::last_error` and switches connection to `ConnectionState::Degraded`.
+pub struct ErrorChannel<W>
+where
+    W: Read + Write,
+{
+    sender: String,
+    message_manager: Lrc<MessageManager<W>>,
+}
+
+/// Cloned channel shares the connection with the original one.
+impl<W> Clone for ErrorChannel<W>
+where
+    W: Read + Write,
+{
+    fn clone(&self) -> Self {
+        ErrorChannel {
+            sender: self.sender.clone(),
+            message_manager: Lrc::clone(&self.message_manager),
+        }
+    }
+}
+
+impl<W> ErrorChannel<W>
+where
+    W: Read + Write,
+{
+    pub fn new<S>(sender: S, message_manager: Lrc<MessageManager<W>>) -> ErrorChannel<W>
+    where
+        S: Into<String>,
+    {
+        ErrorChannel {
+            sender: sender.into(),
+            message_manager,
+        }
+    }
+
+    /// Returns the latest transport level error received from the cast device, if any.
+    pub fn last_error(&self) -> Option<TransportError> {
+        self.message_manager.last_transport_error()
+    }
+
+    pub fn can_handle(&self, message: &CastMessage) -> bool {
+        message.namespace == CHANNEL_NAMESPACE
+            && (message.destination == "*" || message.destination == self.sender)
+    }
+
+    pub fn parse(&self, message: &CastMessage) -> Result<ErrorResponse, Error> {
+        let reply = message.payload.to_json()?;
+
+        match parse_error_reply(&reply) {
+            Some(error) => Ok(ErrorResponse::Error(error)),
+            None => Ok(ErrorResponse::NotImplemented(String::new(), reply)),
+        }
+    }
+}
+
+/// Checks whether `message` is sent over the error namespace.
+///
+/// # Return value
+///
+/// Parsed error if `message` is a transport level error, `None` otherwise.
+pub(crate) fn parse_error_message(message: &CastMessage) -> Option<TransportError> {
+    if message.namespace != CHANNEL_NAMESPACE {
+        return None;
+    }
+
+    message
+        .payload
+        .to_json()
+        .ok()
+        .and_then(|reply| parse_error_reply(&reply))
+}
+
+/// Parses error reply, replies without `type` aren't considered errors.
+fn parse_error_reply(reply: &serde_json::Value) -> Option<TransportError> {
+    let reply: proxies::error::ErrorReply = serde_json::value::from_value(reply.clone()).ok()?;
+
+    Some(TransportError {
+        request_id: reply.request_id.into(),
+        error_type: reply.typ,
+        reason: reply.reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cast::cast_channel::{
+            self,
+            cast_message::{PayloadType, ProtocolVersion},
+        },
+        message_manager::{CastMessagePayload, ConnectionState},
+        testing::MockTcpStream,
+        DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID,
+    };
+    use protobuf::EnumOrUnknown;
+
+    #[test]
+    fn test_transport_error_degrades_connection() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
+            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(
+                r#"{"type":"INVALID_REQUEST","requestId":3,"reason":"INVALID_COMMAND"}"#
+                    .to_string(),
+            ),
+            ..Default::default()
+        });
+        let message_manager = Lrc::new(MessageManager::new(stream));
+        let channel = ErrorChannel::new(DEFAULT_SENDER_ID, Lrc::clone(&message_manager));
+
+        let message = message_manager.receive().unwrap();
+
+        let expected = TransportError {
+            request_id: RequestId::new(3),
+            error_type: "INVALID_REQUEST".to_string(),
+            reason: Some("INVALID_COMMAND".to_string()),
+        };
+        assert!(channel.can_handle(&message));
+        match channel.parse(&message).unwrap() {
+            ErrorResponse::Error(error) => assert_eq!(expected, error),
+            response => panic!("unexpected response {response:?}"),
+        }
+        assert_eq!(Some(expected), channel.last_error());
+        assert_eq!(ConnectionState::Degraded, message_manager.state());
+    }
+
+    #[test]
+    fn test_parse_error_message_ignores_other_namespaces() {
+        let message = CastMessage {
+            namespace: crate::channels::receiver::CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(r#"{"type":"INVALID_REQUEST"}"#.to_string()),
+        };
+
+        assert_eq!(None, parse_error_message(&message));
+    }
+}
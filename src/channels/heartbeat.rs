@@ -1,37 +1,85 @@
 use std::{
-    borrow::Cow,
     io::{Read, Write},
+    time::{Duration, Instant},
 };
 
+use serde_derive::{Deserialize, Serialize};
+
 use crate::{
     cast::proxies,
     errors::Error,
-    message_manager::{CastMessage, CastMessagePayload, MessageManager},
-    Lrc,
+    message_manager::{CastMessage, CastMessagePayload, ConnectionState, Lock, MessageManager},
+    namespaces, Lrc,
 };
 
-pub(crate) const CHANNEL_NAMESPACE: &str = "urn:x-cast:com.google.cast.tp.heartbeat";
+pub(crate) const CHANNEL_NAMESPACE: &str = namespaces::HEARTBEAT;
 
 const MESSAGE_TYPE_PING: &str = "PING";
 const MESSAGE_TYPE_PONG: &str = "PONG";
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum HeartbeatResponse {
     Ping,
     Pong,
     NotImplemented(String, serde_json::Value),
 }
 
-pub struct HeartbeatChannel<'a, W>
+/// Liveness and latency of the connection with the cast device, see `HeartbeatChannel::health`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConnectionHealth {
+    /// Current state of the connection.
+    pub state: ConnectionState,
+    /// When the last `PING` has been sent to the device.
+    pub last_ping_sent: Option<Instant>,
+    /// Round-trip time between the last answered `PING` and the `PONG` that followed it.
+    pub last_rtt: Option<Duration>,
+    /// When the last heartbeat message (`PING` or `PONG`) has been received from the device.
+    pub last_seen: Option<Instant>,
+}
+
+impl ConnectionHealth {
+    /// Returns how long the device has been silent, `None` if it has never been heard from.
+    pub fn silent_for(&self) -> Option<Duration> {
+        self.last_seen.map(|last_seen| last_seen.elapsed())
+    }
+}
+
+/// Timestamps of the heartbeat messages exchanged with the device.
+#[derive(Debug, Default)]
+struct HeartbeatStats {
+    /// When unanswered `PING` has been sent, it's reset once `PONG` is received.
+    pending_ping: Option<Instant>,
+    last_ping_sent: Option<Instant>,
+    last_rtt: Option<Duration>,
+    last_seen: Option<Instant>,
+}
+
+pub struct HeartbeatChannel<W>
 where
     W: Read + Write,
 {
-    sender: Cow<'a, str>,
-    receiver: Cow<'a, str>,
+    sender: String,
+    receiver: String,
     message_manager: Lrc<MessageManager<W>>,
+    stats: Lrc<Lock<HeartbeatStats>>,
 }
 
-impl<'a, W> HeartbeatChannel<'a, W>
+/// Cloned channel shares the connection and the tracked state with the original one.
+impl<W> Clone for HeartbeatChannel<W>
+where
+    W: Read + Write,
+{
+    fn clone(&self) -> Self {
+        HeartbeatChannel {
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+            message_manager: Lrc::clone(&self.message_manager),
+            stats: Lrc::clone(&self.stats),
+        }
+    }
+}
+
+impl<W> HeartbeatChannel<W>
 where
     W: Read + Write,
 {
@@ -39,18 +87,25 @@ where
         sender: S,
         receiver: S,
         message_manager: Lrc<MessageManager<W>>,
-    ) -> HeartbeatChannel<'a, W>
+    ) -> HeartbeatChannel<W>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
         HeartbeatChannel {
             sender: sender.into(),
             receiver: receiver.into(),
             message_manager,
+            stats: Lrc::new(Lock::new(HeartbeatStats::default())),
         }
     }
 
     pub fn ping(&self) -> Result<(), Error> {
+        trace_span!(
+            "heartbeat.ping",
+            namespace = CHANNEL_NAMESPACE,
+            destination = %self.receiver
+        );
+
         let payload = serde_json::to_string(&proxies::heartbeat::HeartBeatRequest {
             typ: MESSAGE_TYPE_PING.to_string(),
         })?;
@@ -60,10 +115,25 @@ where
             source: self.sender.to_string(),
             destination: self.receiver.to_string(),
             payload: CastMessagePayload::String(payload),
-        })
+        })?;
+
+        // If the previous `PING` is still unanswered, round-trip time is measured from the latest
+        // one.
+        let now = Instant::now();
+        let mut stats = self.stats.borrow_mut();
+        stats.pending_ping = Some(now);
+        stats.last_ping_sent = Some(now);
+
+        Ok(())
     }
 
     pub fn pong(&self) -> Result<(), Error> {
+        trace_span!(
+            "heartbeat.pong",
+            namespace = CHANNEL_NAMESPACE,
+            destination = %self.receiver
+        );
+
         let payload = serde_json::to_string(&proxies::heartbeat::HeartBeatRequest {
             typ: MESSAGE_TYPE_PONG.to_string(),
         })?;
@@ -76,21 +146,35 @@ where
         })
     }
 
+    /// Returns round-trip time between the last answered `PING` and the `PONG` that followed it.
+    /// `PONG` is only taken into account once it's parsed (e.g. with `CastDevice::receive`).
+    pub fn last_rtt(&self) -> Option<Duration> {
+        self.stats.borrow_mut().last_rtt
+    }
+
+    /// Returns when the last heartbeat message (`PING` or `PONG`) has been received.
+    pub fn last_seen(&self) -> Option<Instant> {
+        self.stats.borrow_mut().last_seen
+    }
+
+    /// Returns liveness and latency of the connection with the device.
+    pub fn health(&self) -> ConnectionHealth {
+        let stats = self.stats.borrow_mut();
+
+        ConnectionHealth {
+            state: self.message_manager.state(),
+            last_ping_sent: stats.last_ping_sent,
+            last_rtt: stats.last_rtt,
+            last_seen: stats.last_seen,
+        }
+    }
+
     pub fn can_handle(&self, message: &CastMessage) -> bool {
         message.namespace == CHANNEL_NAMESPACE
     }
 
     pub fn parse(&self, message: &CastMessage) -> Result<HeartbeatResponse, Error> {
-        let reply = match message.payload {
-            CastMessagePayload::String(ref payload) => {
-                serde_json::from_str::<serde_json::Value>(payload)?
-            }
-            _ => {
-                return Err(Error::Internal(
-                    "Binary payload is not supported!".to_string(),
-                ))
-            }
-        };
+        let reply = message.payload.to_json()?;
 
         let message_type = reply
             .as_object()
@@ -105,6 +189,68 @@ where
             _ => HeartbeatResponse::NotImplemented(message_type.to_string(), reply),
         };
 
+        if let HeartbeatResponse::Ping | HeartbeatResponse::Pong = response {
+            let now = Instant::now();
+            let mut stats = self.stats.borrow_mut();
+            stats.last_seen = Some(now);
+
+            if let HeartbeatResponse::Pong = response {
+                if let Some(pending_ping) = stats.pending_ping.take() {
+                    stats.last_rtt = Some(now.duration_since(pending_ping));
+                }
+            }
+        }
+
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use protobuf::EnumOrUnknown;
+
+    use super::*;
+    use crate::{
+        cast::{
+            cast_channel,
+            cast_channel::cast_message::{PayloadType, ProtocolVersion},
+        },
+        testing::MockTcpStream,
+        DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID,
+    };
+
+    #[test]
+    fn test_ping_pong_rtt() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
+            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(r#"{"type":"PONG"}"#.to_string()),
+            ..Default::default()
+        });
+        let message_manager = Lrc::new(MessageManager::new(stream));
+        let channel = HeartbeatChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::clone(&message_manager),
+        );
+        assert_eq!(None, channel.last_seen());
+
+        channel.ping().unwrap();
+        assert_eq!(None, channel.last_rtt());
+
+        let message = message_manager.receive().unwrap();
+        assert!(matches!(
+            channel.parse(&message),
+            Ok(HeartbeatResponse::Pong)
+        ));
+
+        let health = channel.health();
+        assert_eq!(ConnectionState::Connected, health.state);
+        assert!(health.last_rtt.is_some());
+        assert!(health.last_seen >= health.last_ping_sent);
+    }
+}
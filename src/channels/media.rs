@@ -1,19 +1,24 @@
 use std::{
-    borrow::Cow,
+    collections::HashMap,
     fmt,
     io::{Read, Write},
+    ops::{BitAnd, BitOr, Range},
     str::FromStr,
     string::ToString,
+    time::{Duration, Instant},
 };
 
+use serde_derive::{Deserialize, Serialize};
+
 use crate::{
     cast::proxies,
+    channels::receiver::Credentials,
     errors::Error,
-    message_manager::{CastMessage, CastMessagePayload, MessageManager},
-    Lrc,
+    message_manager::{CastMessage, CastMessagePayload, Lock, MessageManager, RequestId},
+    namespaces, Lrc,
 };
 
-pub(crate) const CHANNEL_NAMESPACE: &str = "urn:x-cast:com.google.cast.media";
+pub(crate) const CHANNEL_NAMESPACE: &str = namespaces::MEDIA;
 
 const MESSAGE_TYPE_GET_STATUS: &str = "GET_STATUS";
 const MESSAGE_TYPE_LOAD: &str = "LOAD";
@@ -22,15 +27,28 @@ const MESSAGE_TYPE_PLAY: &str = "PLAY";
 const MESSAGE_TYPE_PAUSE: &str = "PAUSE";
 const MESSAGE_TYPE_STOP: &str = "STOP";
 const MESSAGE_TYPE_SEEK: &str = "SEEK";
+const MESSAGE_TYPE_SKIP_AD: &str = "SKIP_AD";
+const MESSAGE_TYPE_PRELOAD: &str = "PRELOAD";
+const MESSAGE_TYPE_QUEUE_UPDATE: &str = "QUEUE_UPDATE";
+const MESSAGE_TYPE_QUEUE_GET_ITEM_IDS: &str = "QUEUE_GET_ITEM_IDS";
+const MESSAGE_TYPE_QUEUE_GET_ITEM_RANGE: &str = "QUEUE_GET_ITEM_RANGE";
+const MESSAGE_TYPE_QUEUE_GET_ITEMS: &str = "QUEUE_GET_ITEMS";
+const MESSAGE_TYPE_EDIT_TRACKS_INFO: &str = "EDIT_TRACKS_INFO";
 const MESSAGE_TYPE_MEDIA_STATUS: &str = "MEDIA_STATUS";
+const MESSAGE_TYPE_QUEUE_ITEM_IDS: &str = "QUEUE_ITEM_IDS";
+const MESSAGE_TYPE_QUEUE_ITEMS: &str = "QUEUE_ITEMS";
 const MESSAGE_TYPE_LOAD_CANCELLED: &str = "LOAD_CANCELLED";
 const MESSAGE_TYPE_LOAD_FAILED: &str = "LOAD_FAILED";
 const MESSAGE_TYPE_INVALID_PLAYER_STATE: &str = "INVALID_PLAYER_STATE";
 const MESSAGE_TYPE_INVALID_REQUEST: &str = "INVALID_REQUEST";
 const MESSAGE_TYPE_ERROR: &str = "ERROR";
 
+/// Difference (in seconds) between the reported and extrapolated position, above which
+/// `StatusEntry::diff` considers the position changed (e.g. by seeking).
+const POSITION_TOLERANCE: f32 = 1.0;
+
 /// Describes the way cast device should stream content.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StreamType {
     /// This variant allows cast device to automatically choose whatever way it's most comfortable
     /// with.
@@ -66,7 +84,7 @@ impl fmt::Display for StreamType {
 }
 
 /// Generic, movie, TV show, music track, or photo metadata.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Metadata {
     Generic(GenericMediaMetadata),
     Movie(MovieMediaMetadata),
@@ -201,7 +219,7 @@ impl TryFrom<&proxies::media::Metadata> for Metadata {
 /// Generic media metadata.
 ///
 /// See also the [`GenericMediaMetadata` Cast reference](https://developers.google.com/cast/docs/reference/messages#GenericMediaMetadata).
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct GenericMediaMetadata {
     /// Descriptive title of the content.
     pub title: Option<String>,
@@ -213,10 +231,66 @@ pub struct GenericMediaMetadata {
     pub release_date: Option<String>,
 }
 
+impl GenericMediaMetadata {
+    /// Creates builder of the generic media metadata with all properties unset.
+    pub fn builder() -> GenericMediaMetadataBuilder {
+        GenericMediaMetadataBuilder::default()
+    }
+}
+
+impl From<GenericMediaMetadata> for Metadata {
+    fn from(metadata: GenericMediaMetadata) -> Self {
+        Metadata::Generic(metadata)
+    }
+}
+
+/// Builder of the [`GenericMediaMetadata`], see [`GenericMediaMetadata::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct GenericMediaMetadataBuilder {
+    metadata: GenericMediaMetadata,
+}
+
+impl GenericMediaMetadataBuilder {
+    /// Sets descriptive title of the content.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.metadata.title = Some(title.into());
+        self
+    }
+
+    /// Sets descriptive subtitle of the content.
+    pub fn subtitle<S: Into<String>>(mut self, subtitle: S) -> Self {
+        self.metadata.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Adds image associated with the content.
+    pub fn image(mut self, image: Image) -> Self {
+        self.metadata.images.push(image);
+        self
+    }
+
+    /// Replaces images associated with the content.
+    pub fn images(mut self, images: Vec<Image>) -> Self {
+        self.metadata.images = images;
+        self
+    }
+
+    /// Sets date and time the content was released, formatted as ISO 8601.
+    pub fn release_date<S: Into<String>>(mut self, release_date: S) -> Self {
+        self.metadata.release_date = Some(release_date.into());
+        self
+    }
+
+    /// Returns the built generic media metadata.
+    pub fn build(self) -> GenericMediaMetadata {
+        self.metadata
+    }
+}
+
 /// Movie media metadata.
 ///
 /// See also the [`MovieMediaMetadata` Cast reference](https://developers.google.com/cast/docs/reference/messages#MovieMediaMetadata).
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct MovieMediaMetadata {
     /// Title of the movie.
     pub title: Option<String>,
@@ -230,10 +304,72 @@ pub struct MovieMediaMetadata {
     pub release_date: Option<String>,
 }
 
+impl MovieMediaMetadata {
+    /// Creates builder of the movie media metadata with all properties unset.
+    pub fn builder() -> MovieMediaMetadataBuilder {
+        MovieMediaMetadataBuilder::default()
+    }
+}
+
+impl From<MovieMediaMetadata> for Metadata {
+    fn from(metadata: MovieMediaMetadata) -> Self {
+        Metadata::Movie(metadata)
+    }
+}
+
+/// Builder of the [`MovieMediaMetadata`], see [`MovieMediaMetadata::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct MovieMediaMetadataBuilder {
+    metadata: MovieMediaMetadata,
+}
+
+impl MovieMediaMetadataBuilder {
+    /// Sets title of the movie.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.metadata.title = Some(title.into());
+        self
+    }
+
+    /// Sets subtitle of the movie.
+    pub fn subtitle<S: Into<String>>(mut self, subtitle: S) -> Self {
+        self.metadata.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Sets studio which released the movie.
+    pub fn studio<S: Into<String>>(mut self, studio: S) -> Self {
+        self.metadata.studio = Some(studio.into());
+        self
+    }
+
+    /// Adds image associated with the content.
+    pub fn image(mut self, image: Image) -> Self {
+        self.metadata.images.push(image);
+        self
+    }
+
+    /// Replaces images associated with the content.
+    pub fn images(mut self, images: Vec<Image>) -> Self {
+        self.metadata.images = images;
+        self
+    }
+
+    /// Sets date and time the movie was released, formatted as ISO 8601.
+    pub fn release_date<S: Into<String>>(mut self, release_date: S) -> Self {
+        self.metadata.release_date = Some(release_date.into());
+        self
+    }
+
+    /// Returns the built movie media metadata.
+    pub fn build(self) -> MovieMediaMetadata {
+        self.metadata
+    }
+}
+
 /// TV show media metadata.
 ///
 /// See also the [`TvShowMediaMetadata` Cast reference](https://developers.google.com/cast/docs/reference/messages#TvShowMediaMetadata).
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct TvShowMediaMetadata {
     /// Title of the TV series.
     pub series_title: Option<String>,
@@ -249,10 +385,78 @@ pub struct TvShowMediaMetadata {
     pub original_air_date: Option<String>,
 }
 
+impl TvShowMediaMetadata {
+    /// Creates builder of the TV show media metadata with all properties unset.
+    pub fn builder() -> TvShowMediaMetadataBuilder {
+        TvShowMediaMetadataBuilder::default()
+    }
+}
+
+impl From<TvShowMediaMetadata> for Metadata {
+    fn from(metadata: TvShowMediaMetadata) -> Self {
+        Metadata::TvShow(metadata)
+    }
+}
+
+/// Builder of the [`TvShowMediaMetadata`], see [`TvShowMediaMetadata::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct TvShowMediaMetadataBuilder {
+    metadata: TvShowMediaMetadata,
+}
+
+impl TvShowMediaMetadataBuilder {
+    /// Sets title of the TV series.
+    pub fn series_title<S: Into<String>>(mut self, series_title: S) -> Self {
+        self.metadata.series_title = Some(series_title.into());
+        self
+    }
+
+    /// Sets title of the episode.
+    pub fn episode_title<S: Into<String>>(mut self, episode_title: S) -> Self {
+        self.metadata.episode_title = Some(episode_title.into());
+        self
+    }
+
+    /// Sets season number of the TV show.
+    pub fn season(mut self, season: u32) -> Self {
+        self.metadata.season = Some(season);
+        self
+    }
+
+    /// Sets episode number (in the season) of the episode.
+    pub fn episode(mut self, episode: u32) -> Self {
+        self.metadata.episode = Some(episode);
+        self
+    }
+
+    /// Adds image associated with the content.
+    pub fn image(mut self, image: Image) -> Self {
+        self.metadata.images.push(image);
+        self
+    }
+
+    /// Replaces images associated with the content.
+    pub fn images(mut self, images: Vec<Image>) -> Self {
+        self.metadata.images = images;
+        self
+    }
+
+    /// Sets date and time this episode was released, formatted as ISO 8601.
+    pub fn original_air_date<S: Into<String>>(mut self, original_air_date: S) -> Self {
+        self.metadata.original_air_date = Some(original_air_date.into());
+        self
+    }
+
+    /// Returns the built TV show media metadata.
+    pub fn build(self) -> TvShowMediaMetadata {
+        self.metadata
+    }
+}
+
 /// Music track media metadata.
 ///
 /// See also the [`MusicTrackMediaMetadata` Cast reference](https://developers.google.com/cast/docs/reference/messages#MusicTrackMediaMetadata).
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct MusicTrackMediaMetadata {
     /// Album or collection from which the track is taken.
     pub album_name: Option<String>,
@@ -274,10 +478,96 @@ pub struct MusicTrackMediaMetadata {
     pub release_date: Option<String>,
 }
 
+impl MusicTrackMediaMetadata {
+    /// Creates builder of the music track media metadata with all properties unset.
+    pub fn builder() -> MusicTrackMediaMetadataBuilder {
+        MusicTrackMediaMetadataBuilder::default()
+    }
+}
+
+impl From<MusicTrackMediaMetadata> for Metadata {
+    fn from(metadata: MusicTrackMediaMetadata) -> Self {
+        Metadata::MusicTrack(metadata)
+    }
+}
+
+/// Builder of the [`MusicTrackMediaMetadata`], see [`MusicTrackMediaMetadata::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct MusicTrackMediaMetadataBuilder {
+    metadata: MusicTrackMediaMetadata,
+}
+
+impl MusicTrackMediaMetadataBuilder {
+    /// Sets album or collection from which the track is taken.
+    pub fn album_name<S: Into<String>>(mut self, album_name: S) -> Self {
+        self.metadata.album_name = Some(album_name.into());
+        self
+    }
+
+    /// Sets name of the track (for example, song title).
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.metadata.title = Some(title.into());
+        self
+    }
+
+    /// Sets name of the artist associated with the album featuring this track.
+    pub fn album_artist<S: Into<String>>(mut self, album_artist: S) -> Self {
+        self.metadata.album_artist = Some(album_artist.into());
+        self
+    }
+
+    /// Sets name of the artist associated with the track.
+    pub fn artist<S: Into<String>>(mut self, artist: S) -> Self {
+        self.metadata.artist = Some(artist.into());
+        self
+    }
+
+    /// Sets name of the composer associated with the track.
+    pub fn composer<S: Into<String>>(mut self, composer: S) -> Self {
+        self.metadata.composer = Some(composer.into());
+        self
+    }
+
+    /// Sets number of the track on the album.
+    pub fn track_number(mut self, track_number: u32) -> Self {
+        self.metadata.track_number = Some(track_number);
+        self
+    }
+
+    /// Sets number of the volume (for example, a disc) of the album.
+    pub fn disc_number(mut self, disc_number: u32) -> Self {
+        self.metadata.disc_number = Some(disc_number);
+        self
+    }
+
+    /// Adds image associated with the content.
+    pub fn image(mut self, image: Image) -> Self {
+        self.metadata.images.push(image);
+        self
+    }
+
+    /// Replaces images associated with the content.
+    pub fn images(mut self, images: Vec<Image>) -> Self {
+        self.metadata.images = images;
+        self
+    }
+
+    /// Sets date and time the content was released, formatted as ISO 8601.
+    pub fn release_date<S: Into<String>>(mut self, release_date: S) -> Self {
+        self.metadata.release_date = Some(release_date.into());
+        self
+    }
+
+    /// Returns the built music track media metadata.
+    pub fn build(self) -> MusicTrackMediaMetadata {
+        self.metadata
+    }
+}
+
 /// Photo media metadata.
 ///
 /// See also the [`PhotoMediaMetadata` Cast reference](https://developers.google.com/cast/docs/reference/messages#PhotoMediaMetadata).
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct PhotoMediaMetadata {
     /// Title of the photograph.
     pub title: Option<String>,
@@ -293,6 +583,68 @@ pub struct PhotoMediaMetadata {
     pub creation_date_time: Option<String>,
 }
 
+impl PhotoMediaMetadata {
+    /// Creates builder of the photo media metadata with all properties unset.
+    pub fn builder() -> PhotoMediaMetadataBuilder {
+        PhotoMediaMetadataBuilder::default()
+    }
+}
+
+impl From<PhotoMediaMetadata> for Metadata {
+    fn from(metadata: PhotoMediaMetadata) -> Self {
+        Metadata::Photo(metadata)
+    }
+}
+
+/// Builder of the [`PhotoMediaMetadata`], see [`PhotoMediaMetadata::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct PhotoMediaMetadataBuilder {
+    metadata: PhotoMediaMetadata,
+}
+
+impl PhotoMediaMetadataBuilder {
+    /// Sets title of the photograph.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.metadata.title = Some(title.into());
+        self
+    }
+
+    /// Sets name of the photographer.
+    pub fn artist<S: Into<String>>(mut self, artist: S) -> Self {
+        self.metadata.artist = Some(artist.into());
+        self
+    }
+
+    /// Sets verbal location where the photograph was taken, for example “Madrid, Spain”.
+    pub fn location<S: Into<String>>(mut self, location: S) -> Self {
+        self.metadata.location = Some(location.into());
+        self
+    }
+
+    /// Sets latitude and longitude of the location where the photograph was taken.
+    pub fn latitude_longitude(mut self, latitude_longitude: (f64, f64)) -> Self {
+        self.metadata.latitude_longitude = Some(latitude_longitude);
+        self
+    }
+
+    /// Sets width and height of the photograph in pixels.
+    pub fn dimensions(mut self, dimensions: (u32, u32)) -> Self {
+        self.metadata.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Sets date and time the photograph was taken, formatted as ISO 8601.
+    pub fn creation_date_time<S: Into<String>>(mut self, creation_date_time: S) -> Self {
+        self.metadata.creation_date_time = Some(creation_date_time.into());
+        self
+    }
+
+    /// Returns the built photo media metadata.
+    pub fn build(self) -> PhotoMediaMetadata {
+        self.metadata
+    }
+}
+
 /// Image URL and optionally size metadata.
 ///
 /// This is the description of an image, including a small amount of metadata to
@@ -301,7 +653,7 @@ pub struct PhotoMediaMetadata {
 /// of images.
 ///
 /// See also the [`Image` Cast reference](https://developers.google.com/cast/docs/reference/messages#Image).
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Image {
     /// URL of the image.
     pub url: String,
@@ -317,6 +669,58 @@ impl Image {
         }
     }
 
+    /// Creates image with the specified `url` and size in pixels.
+    pub fn with_dimensions<S: Into<String>>(url: S, width: u32, height: u32) -> Image {
+        Image {
+            url: url.into(),
+            dimensions: Some((width, height)),
+        }
+    }
+
+    /// Checks that the image has non-empty URL and, if specified, non-zero dimensions.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::Validation` describing the first problem found.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.url.trim().is_empty() {
+            return Err(Error::Validation("image URL is empty".to_string()));
+        }
+
+        if let Some((width, height)) = self.dimensions {
+            if width == 0 || height == 0 {
+                return Err(Error::Validation(format!(
+                    "image {} has invalid dimensions {}x{}",
+                    self.url, width, height
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks every image in the list (see [`Image::validate`]) and that dimensions are omitted
+    /// on one image at most, as the Cast reference requires.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::Validation` describing the first problem found.
+    pub fn validate_all(images: &[Image]) -> Result<(), Error> {
+        for image in images {
+            image.validate()?;
+        }
+
+        let without_dimensions = images.iter().filter(|i| i.dimensions.is_none()).count();
+        if without_dimensions > 1 {
+            return Err(Error::Validation(format!(
+                "dimensions can be omitted on one image only, but {} images have none",
+                without_dimensions
+            )));
+        }
+
+        Ok(())
+    }
+
     fn encode(&self) -> proxies::media::Image {
         proxies::media::Image {
             url: self.url.clone(),
@@ -326,6 +730,13 @@ impl Image {
     }
 }
 
+#[cfg(feature = "url")]
+impl From<url::Url> for Image {
+    fn from(url: url::Url) -> Self {
+        Image::new(url.into())
+    }
+}
+
 impl From<&proxies::media::Image> for Image {
     fn from(i: &proxies::media::Image) -> Self {
         let mut dimensions = None;
@@ -342,7 +753,7 @@ impl From<&proxies::media::Image> for Image {
 }
 
 /// Describes possible player states.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PlayerState {
     /// Player has not been loaded yet.
     Idle,
@@ -352,6 +763,11 @@ pub enum PlayerState {
     Buffering,
     /// Player is paused.
     Paused,
+    /// Player is loading the media.
+    Loading,
+    /// Player state that is unknown to this library, associated data contains the raw state
+    /// string returned by the receiver.
+    Unknown(String),
 }
 
 impl FromStr for PlayerState {
@@ -363,7 +779,8 @@ impl FromStr for PlayerState {
             "PLAYING" => Ok(PlayerState::Playing),
             "BUFFERING" => Ok(PlayerState::Buffering),
             "PAUSED" => Ok(PlayerState::Paused),
-            _ => Err(Error::Internal(format!("Unknown player state {}", s))),
+            "LOADING" => Ok(PlayerState::Loading),
+            _ => Ok(PlayerState::Unknown(s.to_string())),
         }
     }
 }
@@ -375,6 +792,8 @@ impl fmt::Display for PlayerState {
             PlayerState::Playing => "PLAYING",
             PlayerState::Buffering => "BUFFERING",
             PlayerState::Paused => "PAUSED",
+            PlayerState::Loading => "LOADING",
+            PlayerState::Unknown(ref player_state) => player_state,
         };
 
         write!(f, "{}", player_state)
@@ -383,10 +802,13 @@ impl fmt::Display for PlayerState {
 
 /// Describes possible player states.
 /// Can appear when the base state is PlayerState::Idle
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExtendedPlayerState {
     /// Player is loading the next media
     Loading,
+    /// Extended player state that is unknown to this library, associated data contains the raw
+    /// state string returned by the receiver.
+    Unknown(String),
 }
 
 impl FromStr for ExtendedPlayerState {
@@ -395,10 +817,7 @@ impl FromStr for ExtendedPlayerState {
     fn from_str(s: &str) -> Result<Self, Error> {
         match s {
             "LOADING" => Ok(Self::Loading),
-            _ => Err(Error::Internal(format!(
-                "Unknown extended player state {}",
-                s
-            ))),
+            _ => Ok(Self::Unknown(s.to_string())),
         }
     }
 }
@@ -407,6 +826,7 @@ impl fmt::Display for ExtendedPlayerState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let player_state = match *self {
             Self::Loading => "LOADING",
+            Self::Unknown(ref player_state) => player_state,
         };
 
         write!(f, "{}", player_state)
@@ -414,7 +834,7 @@ impl fmt::Display for ExtendedPlayerState {
 }
 
 /// Describes possible player idle reasons.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IdleReason {
     /// A sender requested to stop playback using the STOP command.
     Cancelled,
@@ -425,6 +845,9 @@ pub enum IdleReason {
     /// The media was interrupted due to an error; For example, if the player could not download the
     /// media due to network issues.
     Error,
+    /// Idle reason that is unknown to this library, associated data contains the raw reason string
+    /// returned by the receiver.
+    Unknown(String),
 }
 
 impl FromStr for IdleReason {
@@ -436,13 +859,27 @@ impl FromStr for IdleReason {
             "INTERRUPTED" => Ok(IdleReason::Interrupted),
             "FINISHED" => Ok(IdleReason::Finished),
             "ERROR" => Ok(IdleReason::Error),
-            _ => Err(Error::Internal(format!("Unknown idle reason {}", s))),
+            _ => Ok(IdleReason::Unknown(s.to_string())),
         }
     }
 }
 
+impl fmt::Display for IdleReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let idle_reason = match *self {
+            IdleReason::Cancelled => "CANCELLED",
+            IdleReason::Interrupted => "INTERRUPTED",
+            IdleReason::Finished => "FINISHED",
+            IdleReason::Error => "ERROR",
+            IdleReason::Unknown(ref idle_reason) => idle_reason,
+        };
+
+        write!(f, "{}", idle_reason)
+    }
+}
+
 /// <https://developers.google.com/cast/docs/reference/web_sender/chrome.cast.media#.QueueType>
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum QueueType {
     Album,
     Playlist,
@@ -492,7 +929,7 @@ impl fmt::Display for QueueType {
 }
 
 /// Describes the operation to perform with playback while seeking.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ResumeState {
     /// Forces media to start.
     PlaybackStart,
@@ -523,14 +960,109 @@ impl fmt::Display for ResumeState {
     }
 }
 
+/// Describes the format of the HLS audio segments (or of the muxed audio/video segments).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HlsSegmentFormat {
+    /// AAC packed audio elementary stream.
+    Aac,
+    /// AC3 packed audio elementary stream.
+    Ac3,
+    /// MP3 packed audio elementary stream.
+    Mp3,
+    /// MPEG-2 transport stream.
+    Ts,
+    /// AAC packed MPEG-2 transport stream.
+    TsAac,
+    /// E-AC3 packed audio elementary stream.
+    EAc3,
+    /// Audio packed in ISO BMFF CMAF Fragmented MP4.
+    Fmp4,
+}
+
+impl FromStr for HlsSegmentFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<HlsSegmentFormat, Error> {
+        match s {
+            "aac" => Ok(HlsSegmentFormat::Aac),
+            "ac3" => Ok(HlsSegmentFormat::Ac3),
+            "mp3" => Ok(HlsSegmentFormat::Mp3),
+            "ts" => Ok(HlsSegmentFormat::Ts),
+            "ts_aac" => Ok(HlsSegmentFormat::TsAac),
+            "e_ac3" => Ok(HlsSegmentFormat::EAc3),
+            "fmp4" => Ok(HlsSegmentFormat::Fmp4),
+            _ => Err(Error::Parsing(format!("Unknown HLS segment format {}", s))),
+        }
+    }
+}
+
+impl fmt::Display for HlsSegmentFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = match *self {
+            HlsSegmentFormat::Aac => "aac",
+            HlsSegmentFormat::Ac3 => "ac3",
+            HlsSegmentFormat::Mp3 => "mp3",
+            HlsSegmentFormat::Ts => "ts",
+            HlsSegmentFormat::TsAac => "ts_aac",
+            HlsSegmentFormat::EAc3 => "e_ac3",
+            HlsSegmentFormat::Fmp4 => "fmp4",
+        };
+
+        write!(f, "{}", format)
+    }
+}
+
+/// Describes the format of the HLS video segments.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HlsVideoSegmentFormat {
+    /// MPEG-2 transport stream.
+    Mpeg2Ts,
+    /// Video packed in ISO BMFF CMAF Fragmented MP4.
+    Fmp4,
+}
+
+impl FromStr for HlsVideoSegmentFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<HlsVideoSegmentFormat, Error> {
+        match s {
+            "mpeg2_ts" => Ok(HlsVideoSegmentFormat::Mpeg2Ts),
+            "fmp4" => Ok(HlsVideoSegmentFormat::Fmp4),
+            _ => Err(Error::Parsing(format!(
+                "Unknown HLS video segment format {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for HlsVideoSegmentFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = match *self {
+            HlsVideoSegmentFormat::Mpeg2Ts => "mpeg2_ts",
+            HlsVideoSegmentFormat::Fmp4 => "fmp4",
+        };
+
+        write!(f, "{}", format)
+    }
+}
+
 /// This data structure describes a media stream.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Media {
     /// Service-specific identifier of the content currently loaded by the media player. This is a
     /// free form string and is specific to the application. In most cases, this will be the URL to
     /// the media, but the sender can choose to pass a string that the receiver can interpret
     /// properly. Max length: 1k.
     pub content_id: String,
+    /// Optional media URL, to allow using `content_id` for real ID. If `content_url` is provided,
+    /// it will be used as media URL, otherwise `content_id` will be used as the media URL.
+    pub content_url: Option<String>,
+    /// Optional media entity, commonly a deep-link URL interpreted by the receiver application
+    /// (e.g. YouTube or Android TV applications) instead of the raw content URL.
+    pub entity: Option<String>,
+    /// Optional media entity used by Android TV receivers, if omitted `entity` is used.
+    pub atv_entity: Option<String>,
     /// Describes the type of media artifact.
     pub stream_type: StreamType,
     /// MIME content type of the media being played.
@@ -539,18 +1071,98 @@ pub struct Media {
     pub metadata: Option<Metadata>,
     /// Duration of the currently playing stream in seconds.
     pub duration: Option<f32>,
+    /// Format of the HLS audio segments, helps receiver to play HLS streams without guessing.
+    pub hls_segment_format: Option<HlsSegmentFormat>,
+    /// Format of the HLS video segments, helps receiver to play HLS streams without guessing.
+    pub hls_video_segment_format: Option<HlsVideoSegmentFormat>,
+    /// Requested style of the text tracks (e.g. captions or subtitles) of the media.
+    pub text_track_style: Option<TextTrackStyle>,
+    /// List of ad breaks of the media.
+    pub breaks: Vec<Break>,
+    /// List of ad break clips that ad breaks consist of.
+    pub break_clips: Vec<BreakClip>,
+    /// List of text, audio or video tracks of the media (e.g. side-loaded subtitles).
+    pub tracks: Vec<Track>,
+    /// Application specific data passed along with the media to the receiver application.
+    pub custom_data: Option<serde_json::Value>,
 }
 
 impl Media {
+    /// Creates builder of the media with the specified `content_id` (usually the URL of the media)
+    /// and MIME `content_type`, the stream type defaults to [`StreamType::Buffered`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_cast::channels::media::{Media, MovieMediaMetadata, StreamType};
+    ///
+    /// let media = Media::builder("https://example.com/movie.mp4", "video/mp4")
+    ///     .stream_type(StreamType::Buffered)
+    ///     .duration(5_400.0)
+    ///     .metadata(MovieMediaMetadata::builder().title("Movie").build())
+    ///     .build();
+    ///
+    /// assert_eq!(media.duration, Some(5_400.0));
+    /// ```
+    pub fn builder<S, T>(content_id: S, content_type: T) -> MediaBuilder
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        MediaBuilder {
+            media: Media {
+                content_id: content_id.into(),
+                content_url: None,
+                entity: None,
+                atv_entity: None,
+                stream_type: StreamType::Buffered,
+                content_type: content_type.into(),
+                metadata: None,
+                duration: None,
+                hls_segment_format: None,
+                hls_video_segment_format: None,
+                text_track_style: None,
+                breaks: vec![],
+                break_clips: vec![],
+                tracks: vec![],
+                custom_data: None,
+            },
+        }
+    }
+
+    /// Encodes the media with additional `images` appended to its metadata.
+    fn encode_with_images(&self, images: &[Image]) -> proxies::media::Media {
+        let mut media = self.encode();
+        if !images.is_empty() {
+            media
+                .metadata
+                .get_or_insert_with(|| proxies::media::Metadata::new(0))
+                .images
+                .extend(images.iter().map(|i| i.encode()));
+        }
+
+        media
+    }
+
     fn encode(&self) -> proxies::media::Media {
         let metadata = self.metadata.as_ref().map(|m| m.encode());
 
         proxies::media::Media {
             content_id: self.content_id.clone(),
+            content_url: self.content_url.clone(),
+            entity: self.entity.clone(),
+            atv_entity: self.atv_entity.clone(),
             stream_type: self.stream_type.to_string(),
             content_type: self.content_type.clone(),
             metadata,
             duration: self.duration,
+            hls_segment_format: self.hls_segment_format.map(|f| f.to_string()),
+            hls_video_segment_format: self.hls_video_segment_format.map(|f| f.to_string()),
+            text_track_style: self.text_track_style.as_ref().map(|t| t.encode()),
+            breaks: self.breaks.iter().map(|b| b.encode()).collect(),
+            break_clips: self.break_clips.iter().map(|bc| bc.encode()).collect(),
+            tracks: self.tracks.iter().map(|t| t.encode()).collect(),
+            custom_data: self.custom_data.clone(),
         }
     }
 }
@@ -561,44 +1173,811 @@ impl TryFrom<&proxies::media::Media> for Media {
     fn try_from(m: &proxies::media::Media) -> Result<Self, Error> {
         Ok(Self {
             content_id: m.content_id.to_string(),
+            content_url: m.content_url.clone(),
+            entity: m.entity.clone(),
+            atv_entity: m.atv_entity.clone(),
             stream_type: StreamType::from_str(m.stream_type.as_ref())?,
             content_type: m.content_type.to_string(),
             metadata: m.metadata.as_ref().map(TryInto::try_into).transpose()?,
             duration: m.duration,
+            hls_segment_format: m
+                .hls_segment_format
+                .as_deref()
+                .map(HlsSegmentFormat::from_str)
+                .transpose()?,
+            hls_video_segment_format: m
+                .hls_video_segment_format
+                .as_deref()
+                .map(HlsVideoSegmentFormat::from_str)
+                .transpose()?,
+            text_track_style: m
+                .text_track_style
+                .as_ref()
+                .map(TryInto::try_into)
+                .transpose()?,
+            breaks: m.breaks.iter().map(Break::from).collect(),
+            break_clips: m.break_clips.iter().map(BreakClip::from).collect(),
+            tracks: m
+                .tracks
+                .iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            custom_data: m.custom_data.clone(),
         })
     }
 }
 
-/// One item in a queue
-#[derive(Clone, Debug)]
-pub struct QueueItem {
-    /// The item as media
-    pub media: Media,
+/// Guesses MIME type of the media from the extension of its `url` (or file path), query and
+/// fragment of the URL are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use rust_cast::channels::media::guess_content_type;
+///
+/// assert_eq!(
+///     guess_content_type("https://example.com/movie.MP4?token=1"),
+///     Some("video/mp4")
+/// );
+/// assert_eq!(
+///     guess_content_type("https://example.com/live/index.m3u8"),
+///     Some("application/x-mpegurl")
+/// );
+/// assert_eq!(guess_content_type("https://example.com/watch"), None);
+/// ```
+///
+/// # Return value
+///
+/// MIME type of the media, `None` if the extension is missing or unknown.
+pub fn guess_content_type(url: &str) -> Option<&'static str> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let name = path.rsplit(['/', '\\']).next().unwrap_or_default();
+    let (_, extension) = name.rsplit_once('.')?;
+
+    let content_type = match extension.to_ascii_lowercase().as_str() {
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        "mkv" => "video/x-matroska",
+        "mov" => "video/quicktime",
+        "ts" => "video/mp2t",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "aac" => "audio/aac",
+        "flac" => "audio/flac",
+        "ogg" | "oga" | "opus" => "audio/ogg",
+        "wav" => "audio/wav",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "vtt" => "text/vtt",
+        "ttml" => "application/ttml+xml",
+        "m3u8" => "application/x-mpegurl",
+        "mpd" => "application/dash+xml",
+        "ism" | "isml" => "application/vnd.ms-sstr+xml",
+        _ => return None,
+    };
+
+    Some(content_type)
 }
 
-impl QueueItem {
-    fn encode(&self) -> proxies::media::QueueItem {
-        proxies::media::QueueItem {
-            active_track_ids: None,
-            autoplay: true,
-            custom_data: None,
-            item_id: None,
-            media: self.media.encode(),
-            playback_duration: None,
-            preload_time: 20.,
-            start_time: 0.,
-        }
+/// Probes the media at the `url` with `HEAD` request (following redirects) and returns MIME type
+/// the server reports for it, without parameters (e.g. `charset`). Unlike
+/// [`guess_content_type`] it works for the URLs without extension, but costs a round trip to the
+/// server.
+///
+/// # Arguments
+///
+/// * `url` - `http` or `https` URL of the media.
+/// * `timeout` - Maximum time connecting to the server and each read or write may take.
+///
+/// # Errors
+///
+/// Fails with `Error::Validation` if the `url` isn't a valid `http` or `https` URL or the server
+/// doesn't respond to it with success (e.g. the media doesn't exist), and with `Error::Io`,
+/// `Error::Tls` or `Error::Parsing` if the request fails.
+///
+/// # Return value
+///
+/// MIME type of the media, `None` if the server doesn't report it or reports generic
+/// `application/octet-stream` type.
+#[cfg(feature = "http")]
+pub fn probe_content_type(url: &str, timeout: Duration) -> Result<Option<String>, Error> {
+    let response = crate::http::request("HEAD", url, timeout)?;
+    if !response.is_success() {
+        return Err(Error::Validation(format!(
+            "media {} is not available, server responded with status {}",
+            response.url, response.status
+        )));
     }
+
+    Ok(response
+        .header("content-type")
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|content_type| content_type.trim().to_ascii_lowercase())
+        .filter(|content_type| {
+            !content_type.is_empty()
+                && content_type != "application/octet-stream"
+                && content_type != "binary/octet-stream"
+        }))
 }
 
-/// A queue of items to play in sequence
+/// Builder of the [`Media`], see [`Media::builder`].
 #[derive(Clone, Debug)]
-pub struct MediaQueue {
-    /// Every item in the queue, in order
-    pub items: Vec<QueueItem>,
-    /// Array index of the first item to be played
-    /// Starts at zero.
-    pub start_index: u16,
+pub struct MediaBuilder {
+    media: Media,
+}
+
+impl MediaBuilder {
+    /// Sets media URL, to allow using `content_id` for real ID.
+    pub fn content_url<S: Into<String>>(mut self, content_url: S) -> Self {
+        self.media.content_url = Some(content_url.into());
+        self
+    }
+
+    /// Sets media entity, commonly a deep-link URL interpreted by the receiver application.
+    pub fn entity<S: Into<String>>(mut self, entity: S) -> Self {
+        self.media.entity = Some(entity.into());
+        self
+    }
+
+    /// Sets media entity used by Android TV receivers.
+    pub fn atv_entity<S: Into<String>>(mut self, atv_entity: S) -> Self {
+        self.media.atv_entity = Some(atv_entity.into());
+        self
+    }
+
+    /// Sets the way cast device should stream the content.
+    pub fn stream_type(mut self, stream_type: StreamType) -> Self {
+        self.media.stream_type = stream_type;
+        self
+    }
+
+    /// Sets generic, movie, TV show, music track, or photo metadata.
+    pub fn metadata<M: Into<Metadata>>(mut self, metadata: M) -> Self {
+        self.media.metadata = Some(metadata.into());
+        self
+    }
+
+    /// Sets duration of the stream in seconds.
+    pub fn duration(mut self, duration: f32) -> Self {
+        self.media.duration = Some(duration);
+        self
+    }
+
+    /// Sets format of the HLS audio segments.
+    pub fn hls_segment_format(mut self, format: HlsSegmentFormat) -> Self {
+        self.media.hls_segment_format = Some(format);
+        self
+    }
+
+    /// Sets format of the HLS video segments.
+    pub fn hls_video_segment_format(mut self, format: HlsVideoSegmentFormat) -> Self {
+        self.media.hls_video_segment_format = Some(format);
+        self
+    }
+
+    /// Sets requested style of the text tracks of the media.
+    pub fn text_track_style(mut self, style: TextTrackStyle) -> Self {
+        self.media.text_track_style = Some(style);
+        self
+    }
+
+    /// Sets ad breaks of the media and ad break clips they consist of.
+    pub fn breaks(mut self, breaks: Vec<Break>, break_clips: Vec<BreakClip>) -> Self {
+        self.media.breaks = breaks;
+        self.media.break_clips = break_clips;
+        self
+    }
+
+    /// Adds text, audio or video track to the media.
+    pub fn track(mut self, track: Track) -> Self {
+        self.media.tracks.push(track);
+        self
+    }
+
+    /// Replaces text, audio or video tracks of the media.
+    pub fn tracks(mut self, tracks: Vec<Track>) -> Self {
+        self.media.tracks = tracks;
+        self
+    }
+
+    /// Sets application specific data passed along with the media.
+    pub fn custom_data(mut self, custom_data: serde_json::Value) -> Self {
+        self.media.custom_data = Some(custom_data);
+        self
+    }
+
+    /// Returns the built media.
+    pub fn build(self) -> Media {
+        self.media
+    }
+}
+
+/// Ad break that consists of one or more break clips.
+///
+/// See also the [`Break` Cast reference](https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.Break).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Break {
+    /// Unique identifier of the break.
+    pub id: String,
+    /// List of identifiers of the break clips the break consists of.
+    pub break_clip_ids: Vec<String>,
+    /// Position of the break in the content in seconds, `-1` means the end of the content.
+    pub position: f32,
+    /// Duration of the break in seconds.
+    pub duration: Option<f32>,
+    /// Whether the break is embedded into the main stream.
+    pub is_embedded: Option<bool>,
+    /// Whether the break has already been watched.
+    pub is_watched: bool,
+}
+
+impl Break {
+    fn encode(&self) -> proxies::media::Break {
+        proxies::media::Break {
+            id: self.id.clone(),
+            break_clip_ids: self.break_clip_ids.clone(),
+            position: self.position,
+            duration: self.duration,
+            is_embedded: self.is_embedded,
+            is_watched: self.is_watched,
+        }
+    }
+}
+
+impl From<&proxies::media::Break> for Break {
+    fn from(b: &proxies::media::Break) -> Self {
+        Self {
+            id: b.id.clone(),
+            break_clip_ids: b.break_clip_ids.clone(),
+            position: b.position,
+            duration: b.duration,
+            is_embedded: b.is_embedded,
+            is_watched: b.is_watched,
+        }
+    }
+}
+
+/// Ad break clip, e.g. a single ad within an ad break.
+///
+/// See also the [`BreakClip` Cast reference](https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.BreakClip).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BreakClip {
+    /// Unique identifier of the break clip.
+    pub id: String,
+    /// Title of the break clip.
+    pub title: Option<String>,
+    /// URL or identifier of the break clip content.
+    pub content_id: Option<String>,
+    /// MIME content type of the break clip content.
+    pub content_type: Option<String>,
+    /// Duration of the break clip in seconds.
+    pub duration: Option<f32>,
+    /// Time in seconds since the beginning of the clip after which the clip can be skipped.
+    pub when_skippable: Option<f32>,
+    /// URL of the page the sender displays when the user clicks on the clip.
+    pub click_through_url: Option<String>,
+    /// URL of the image displayed while the clip is loading.
+    pub poster_url: Option<String>,
+}
+
+impl BreakClip {
+    fn encode(&self) -> proxies::media::BreakClip {
+        proxies::media::BreakClip {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            content_id: self.content_id.clone(),
+            content_type: self.content_type.clone(),
+            duration: self.duration,
+            when_skippable: self.when_skippable,
+            click_through_url: self.click_through_url.clone(),
+            poster_url: self.poster_url.clone(),
+        }
+    }
+}
+
+impl From<&proxies::media::BreakClip> for BreakClip {
+    fn from(bc: &proxies::media::BreakClip) -> Self {
+        Self {
+            id: bc.id.clone(),
+            title: bc.title.clone(),
+            content_id: bc.content_id.clone(),
+            content_type: bc.content_type.clone(),
+            duration: bc.duration,
+            when_skippable: bc.when_skippable,
+            click_through_url: bc.click_through_url.clone(),
+            poster_url: bc.poster_url.clone(),
+        }
+    }
+}
+
+/// Type of the media track.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrackType {
+    /// Text track, e.g. subtitles or captions.
+    Text,
+    /// Audio track.
+    Audio,
+    /// Video track.
+    Video,
+}
+
+impl FromStr for TrackType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TrackType, Error> {
+        match s {
+            "TEXT" => Ok(TrackType::Text),
+            "AUDIO" => Ok(TrackType::Audio),
+            "VIDEO" => Ok(TrackType::Video),
+            _ => Err(Error::Parsing(format!("Unknown track type {}", s))),
+        }
+    }
+}
+
+impl fmt::Display for TrackType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match *self {
+            TrackType::Text => "TEXT",
+            TrackType::Audio => "AUDIO",
+            TrackType::Video => "VIDEO",
+        };
+
+        write!(f, "{}", value)
+    }
+}
+
+/// Kind of the text track.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextTrackType {
+    /// Transcription or translation of the dialogue.
+    Subtitles,
+    /// Transcription of the dialogue and sound effects, suitable for deaf or hard of hearing.
+    Captions,
+    /// Textual description of the video component of the media.
+    Descriptions,
+    /// Chapter titles, intended to be used for navigating the media.
+    Chapters,
+    /// Tracks intended for use by scripts, not displayed by the receiver.
+    Metadata,
+}
+
+impl FromStr for TextTrackType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TextTrackType, Error> {
+        match s {
+            "SUBTITLES" => Ok(TextTrackType::Subtitles),
+            "CAPTIONS" => Ok(TextTrackType::Captions),
+            "DESCRIPTIONS" => Ok(TextTrackType::Descriptions),
+            "CHAPTERS" => Ok(TextTrackType::Chapters),
+            "METADATA" => Ok(TextTrackType::Metadata),
+            _ => Err(Error::Parsing(format!("Unknown text track type {}", s))),
+        }
+    }
+}
+
+impl fmt::Display for TextTrackType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match *self {
+            TextTrackType::Subtitles => "SUBTITLES",
+            TextTrackType::Captions => "CAPTIONS",
+            TextTrackType::Descriptions => "DESCRIPTIONS",
+            TextTrackType::Chapters => "CHAPTERS",
+            TextTrackType::Metadata => "METADATA",
+        };
+
+        write!(f, "{}", value)
+    }
+}
+
+/// Text, audio or video track of the media.
+///
+/// See also the [`Track` Cast reference](https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.Track).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Track {
+    /// Unique identifier of the track within the media.
+    pub track_id: u32,
+    /// Type of the track.
+    pub track_type: TrackType,
+    /// URL or identifier of the track content, e.g. URL of the WebVTT file for side-loaded
+    /// subtitles.
+    pub track_content_id: Option<String>,
+    /// MIME content type of the track content, e.g. `text/vtt`.
+    pub track_content_type: Option<String>,
+    /// Kind of the track, only applies to text tracks.
+    pub subtype: Option<TextTrackType>,
+    /// Human readable name of the track.
+    pub name: Option<String>,
+    /// Language of the track as RFC 5646 tag, e.g. `en-US`.
+    pub language: Option<String>,
+}
+
+impl Track {
+    /// Creates track with the specified `track_id` and `track_type` and no other properties.
+    pub fn new(track_id: u32, track_type: TrackType) -> Track {
+        Track {
+            track_id,
+            track_type,
+            track_content_id: None,
+            track_content_type: None,
+            subtype: None,
+            name: None,
+            language: None,
+        }
+    }
+
+    fn encode(&self) -> proxies::media::Track {
+        proxies::media::Track {
+            track_id: self.track_id,
+            typ: self.track_type.to_string(),
+            track_content_id: self.track_content_id.clone(),
+            track_content_type: self.track_content_type.clone(),
+            subtype: self.subtype.map(|s| s.to_string()),
+            name: self.name.clone(),
+            language: self.language.clone(),
+        }
+    }
+}
+
+impl TryFrom<&proxies::media::Track> for Track {
+    type Error = Error;
+
+    fn try_from(t: &proxies::media::Track) -> Result<Self, Error> {
+        Ok(Self {
+            track_id: t.track_id,
+            track_type: TrackType::from_str(&t.typ)?,
+            track_content_id: t.track_content_id.clone(),
+            track_content_type: t.track_content_type.clone(),
+            subtype: t
+                .subtype
+                .as_deref()
+                .map(TextTrackType::from_str)
+                .transpose()?,
+            name: t.name.clone(),
+            language: t.language.clone(),
+        })
+    }
+}
+
+/// Style of the text tracks (e.g. captions or subtitles) displayed by the receiver. Colors are
+/// specified as `#RRGGBBAA` hex strings, fields that are `None` are left up to the receiver.
+///
+/// See also the [`TextTrackStyle` Cast reference](https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.TextTrackStyle).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextTrackStyle {
+    /// Background color of the text.
+    pub background_color: Option<String>,
+    /// Color of the text edge, used with `edge_type`.
+    pub edge_color: Option<String>,
+    /// Type of the text edge.
+    pub edge_type: Option<TextTrackEdgeType>,
+    /// Specific font family of the text (e.g. `Droid Sans`).
+    pub font_family: Option<String>,
+    /// Generic font family of the text, used when `font_family` is not available.
+    pub font_generic_family: Option<TextTrackFontGenericFamily>,
+    /// Scale factor of the font size, `1.0` is the default size.
+    pub font_scale: Option<f32>,
+    /// Style of the font.
+    pub font_style: Option<TextTrackFontStyle>,
+    /// Color of the text.
+    pub foreground_color: Option<String>,
+    /// Color of the window around the text, used with `window_type`.
+    pub window_color: Option<String>,
+    /// Radius of the rounded corners of the window in pixels, used with
+    /// `TextTrackWindowType::RoundedCorners`.
+    pub window_rounded_corner_radius: Option<u32>,
+    /// Type of the window around the text.
+    pub window_type: Option<TextTrackWindowType>,
+}
+
+impl TextTrackStyle {
+    fn encode(&self) -> proxies::media::TextTrackStyle {
+        proxies::media::TextTrackStyle {
+            background_color: self.background_color.clone(),
+            edge_color: self.edge_color.clone(),
+            edge_type: self.edge_type.map(|t| t.to_string()),
+            font_family: self.font_family.clone(),
+            font_generic_family: self.font_generic_family.map(|f| f.to_string()),
+            font_scale: self.font_scale,
+            font_style: self.font_style.map(|f| f.to_string()),
+            foreground_color: self.foreground_color.clone(),
+            window_color: self.window_color.clone(),
+            window_rounded_corner_radius: self.window_rounded_corner_radius,
+            window_type: self.window_type.map(|t| t.to_string()),
+        }
+    }
+}
+
+impl TryFrom<&proxies::media::TextTrackStyle> for TextTrackStyle {
+    type Error = Error;
+
+    fn try_from(t: &proxies::media::TextTrackStyle) -> Result<Self, Error> {
+        Ok(Self {
+            background_color: t.background_color.clone(),
+            edge_color: t.edge_color.clone(),
+            edge_type: t
+                .edge_type
+                .as_deref()
+                .map(TextTrackEdgeType::from_str)
+                .transpose()?,
+            font_family: t.font_family.clone(),
+            font_generic_family: t
+                .font_generic_family
+                .as_deref()
+                .map(TextTrackFontGenericFamily::from_str)
+                .transpose()?,
+            font_scale: t.font_scale,
+            font_style: t
+                .font_style
+                .as_deref()
+                .map(TextTrackFontStyle::from_str)
+                .transpose()?,
+            foreground_color: t.foreground_color.clone(),
+            window_color: t.window_color.clone(),
+            window_rounded_corner_radius: t.window_rounded_corner_radius,
+            window_type: t
+                .window_type
+                .as_deref()
+                .map(TextTrackWindowType::from_str)
+                .transpose()?,
+        })
+    }
+}
+
+/// Type of the edge drawn around the text track characters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextTrackEdgeType {
+    /// No edge.
+    None,
+    /// Outlined characters.
+    Outline,
+    /// Characters with a drop shadow.
+    DropShadow,
+    /// Raised characters.
+    Raised,
+    /// Depressed characters.
+    Depressed,
+}
+
+impl FromStr for TextTrackEdgeType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TextTrackEdgeType, Error> {
+        match s {
+            "NONE" => Ok(TextTrackEdgeType::None),
+            "OUTLINE" => Ok(TextTrackEdgeType::Outline),
+            "DROP_SHADOW" => Ok(TextTrackEdgeType::DropShadow),
+            "RAISED" => Ok(TextTrackEdgeType::Raised),
+            "DEPRESSED" => Ok(TextTrackEdgeType::Depressed),
+            _ => Err(Error::Parsing(format!(
+                "Unknown text track edge type {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for TextTrackEdgeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match *self {
+            TextTrackEdgeType::None => "NONE",
+            TextTrackEdgeType::Outline => "OUTLINE",
+            TextTrackEdgeType::DropShadow => "DROP_SHADOW",
+            TextTrackEdgeType::Raised => "RAISED",
+            TextTrackEdgeType::Depressed => "DEPRESSED",
+        };
+
+        write!(f, "{}", value)
+    }
+}
+
+/// Generic font family of the text track.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextTrackFontGenericFamily {
+    /// Sans serif font.
+    SansSerif,
+    /// Monospaced sans serif font.
+    MonospacedSansSerif,
+    /// Serif font.
+    Serif,
+    /// Monospaced serif font.
+    MonospacedSerif,
+    /// Casual font.
+    Casual,
+    /// Cursive font.
+    Cursive,
+    /// Small capitals font.
+    SmallCapitals,
+}
+
+impl FromStr for TextTrackFontGenericFamily {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TextTrackFontGenericFamily, Error> {
+        match s {
+            "SANS_SERIF" => Ok(TextTrackFontGenericFamily::SansSerif),
+            "MONOSPACED_SANS_SERIF" => Ok(TextTrackFontGenericFamily::MonospacedSansSerif),
+            "SERIF" => Ok(TextTrackFontGenericFamily::Serif),
+            "MONOSPACED_SERIF" => Ok(TextTrackFontGenericFamily::MonospacedSerif),
+            "CASUAL" => Ok(TextTrackFontGenericFamily::Casual),
+            "CURSIVE" => Ok(TextTrackFontGenericFamily::Cursive),
+            "SMALL_CAPITALS" => Ok(TextTrackFontGenericFamily::SmallCapitals),
+            _ => Err(Error::Parsing(format!(
+                "Unknown text track font generic family {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for TextTrackFontGenericFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match *self {
+            TextTrackFontGenericFamily::SansSerif => "SANS_SERIF",
+            TextTrackFontGenericFamily::MonospacedSansSerif => "MONOSPACED_SANS_SERIF",
+            TextTrackFontGenericFamily::Serif => "SERIF",
+            TextTrackFontGenericFamily::MonospacedSerif => "MONOSPACED_SERIF",
+            TextTrackFontGenericFamily::Casual => "CASUAL",
+            TextTrackFontGenericFamily::Cursive => "CURSIVE",
+            TextTrackFontGenericFamily::SmallCapitals => "SMALL_CAPITALS",
+        };
+
+        write!(f, "{}", value)
+    }
+}
+
+/// Style of the text track font.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextTrackFontStyle {
+    /// Regular font.
+    Normal,
+    /// Bold font.
+    Bold,
+    /// Bold and italic font.
+    BoldItalic,
+    /// Italic font.
+    Italic,
+}
+
+impl FromStr for TextTrackFontStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TextTrackFontStyle, Error> {
+        match s {
+            "NORMAL" => Ok(TextTrackFontStyle::Normal),
+            "BOLD" => Ok(TextTrackFontStyle::Bold),
+            "BOLD_ITALIC" => Ok(TextTrackFontStyle::BoldItalic),
+            "ITALIC" => Ok(TextTrackFontStyle::Italic),
+            _ => Err(Error::Parsing(format!(
+                "Unknown text track font style {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for TextTrackFontStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match *self {
+            TextTrackFontStyle::Normal => "NORMAL",
+            TextTrackFontStyle::Bold => "BOLD",
+            TextTrackFontStyle::BoldItalic => "BOLD_ITALIC",
+            TextTrackFontStyle::Italic => "ITALIC",
+        };
+
+        write!(f, "{}", value)
+    }
+}
+
+/// Type of the window drawn around the text track.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextTrackWindowType {
+    /// No window.
+    None,
+    /// Rectangular window.
+    Normal,
+    /// Window with rounded corners.
+    RoundedCorners,
+}
+
+impl FromStr for TextTrackWindowType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<TextTrackWindowType, Error> {
+        match s {
+            "NONE" => Ok(TextTrackWindowType::None),
+            "NORMAL" => Ok(TextTrackWindowType::Normal),
+            "ROUNDED_CORNERS" => Ok(TextTrackWindowType::RoundedCorners),
+            _ => Err(Error::Parsing(format!(
+                "Unknown text track window type {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for TextTrackWindowType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match *self {
+            TextTrackWindowType::None => "NONE",
+            TextTrackWindowType::Normal => "NORMAL",
+            TextTrackWindowType::RoundedCorners => "ROUNDED_CORNERS",
+        };
+
+        write!(f, "{}", value)
+    }
+}
+
+/// Status of the ad break that is currently being played.
+///
+/// See also the [`BreakStatus` Cast reference](https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.BreakStatus).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BreakStatus {
+    /// Time in seconds elapsed since the beginning of the current break.
+    pub current_break_time: Option<f32>,
+    /// Time in seconds elapsed since the beginning of the current break clip.
+    pub current_break_clip_time: Option<f32>,
+    /// Identifier of the current break.
+    pub break_id: Option<String>,
+    /// Identifier of the current break clip.
+    pub break_clip_id: Option<String>,
+    /// Time in seconds since the beginning of the clip after which the clip can be skipped (see
+    /// `MediaChannel::skip_ad`).
+    pub when_skippable: Option<f32>,
+}
+
+impl From<&proxies::media::BreakStatus> for BreakStatus {
+    fn from(bs: &proxies::media::BreakStatus) -> Self {
+        Self {
+            current_break_time: bs.current_break_time,
+            current_break_clip_time: bs.current_break_clip_time,
+            break_id: bs.break_id.clone(),
+            break_clip_id: bs.break_clip_id.clone(),
+            when_skippable: bs.when_skippable,
+        }
+    }
+}
+
+/// Time (in seconds) before the end of the previous item the next queue item is preloaded at, if
+/// not specified otherwise.
+const DEFAULT_PRELOAD_TIME: f64 = 20.;
+
+/// One item in a queue
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QueueItem {
+    /// The item as media
+    pub media: Media,
+    /// Time (in seconds) before the end of the previous item the receiver starts preloading this
+    /// one at, 20 seconds if not provided. Zero disables preloading of the item.
+    pub preload_time: Option<f64>,
+}
+
+impl QueueItem {
+    fn encode(&self) -> proxies::media::QueueItem {
+        proxies::media::QueueItem {
+            active_track_ids: None,
+            autoplay: true,
+            custom_data: None,
+            item_id: None,
+            media: self.media.encode(),
+            playback_duration: None,
+            preload_time: self.preload_time.unwrap_or(DEFAULT_PRELOAD_TIME),
+            start_time: 0.,
+        }
+    }
+}
+
+/// A queue of items to play in sequence
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MediaQueue {
+    /// Every item in the queue, in order
+    pub items: Vec<QueueItem>,
+    /// Array index of the first item to be played
+    /// Starts at zero.
+    pub start_index: u16,
     /// What the queue represents
     pub queue_type: QueueType,
 }
@@ -615,16 +1994,316 @@ impl MediaQueue {
 }
 
 /// Describes the current status of the media artifact with respect to the session.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Status {
     /// Unique id of the request that requested the status.
-    pub request_id: u32,
+    pub request_id: RequestId,
     /// Detailed status of every media status entry.
     pub entries: Vec<StatusEntry>,
 }
 
+/// Maximum number of items `QueueView` fetches with a single request by default.
+const QUEUE_VIEW_BATCH_SIZE: usize = 20;
+
+/// Ids of the queue items, returned in response to `queue_get_item_ids` and
+/// `queue_get_item_range` requests.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueItemIds {
+    /// Unique id of the request that requested the ids.
+    pub request_id: RequestId,
+    /// Ids of the queue items in the order they are played.
+    pub item_ids: Vec<u16>,
+}
+
+/// Queue items returned in response to `queue_get_items` request.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueueItems {
+    /// Unique id of the request that requested the items.
+    pub request_id: RequestId,
+    /// Requested items, items that don't exist (anymore) are omitted by the receiver.
+    pub items: Vec<QueueItemInfo>,
+}
+
+/// Queue item as reported by the receiver.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueueItemInfo {
+    /// Unique id of the item in the queue.
+    pub item_id: u16,
+    /// Media of the item, if provided.
+    pub media: Option<Media>,
+    /// Whether playback starts automatically once the item becomes current.
+    pub autoplay: bool,
+    /// Position (in seconds) the playback of the item starts at.
+    pub start_time: Option<f64>,
+    /// Time (in seconds) before the end of the previous item the item is preloaded at.
+    pub preload_time: Option<f64>,
+}
+
+impl TryFrom<proxies::media::QueueItemReply> for QueueItemInfo {
+    type Error = Error;
+
+    fn try_from(item: proxies::media::QueueItemReply) -> Result<Self, Error> {
+        Ok(QueueItemInfo {
+            item_id: item.item_id,
+            media: item.media.as_ref().map(TryInto::try_into).transpose()?,
+            autoplay: item.autoplay,
+            start_time: item.start_time,
+            preload_time: item.preload_time,
+        })
+    }
+}
+
+/// Lazily fetched view of the long media queue: only ids of the items are retrieved upfront, while
+/// the items themselves are fetched in batches once they are accessed (e.g. as the UI scrolls) and
+/// cached afterwards.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_cast::channels::media::QueueView;
+///
+/// # use rust_cast::CastDevice;
+/// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// let mut queue = QueueView::new(&cast_device.media, "web-4", 1)?;
+///
+/// // Only the first page of items is fetched.
+/// for item in queue.items(0..10)? {
+///     println!("{}: {:?}", item.item_id, item.media.as_ref().map(|media| &media.content_id));
+/// }
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+pub struct QueueView<'c, W>
+where
+    W: Read + Write,
+{
+    channel: &'c MediaChannel<W>,
+    destination: String,
+    media_session_id: i32,
+    batch_size: usize,
+    item_ids: Vec<u16>,
+    items: HashMap<u16, QueueItemInfo>,
+}
+
+impl<'c, W> QueueView<'c, W>
+where
+    W: Read + Write,
+{
+    /// Creates view of the queue played by the media session, fetching ids of all the items.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - Media channel to fetch the queue with;
+    /// * `destination` - `protocol` of the media application (e.g. `web-1`);
+    /// * `media_session_id` - ID of the media session that plays the queue.
+    pub fn new<S>(
+        channel: &'c MediaChannel<W>,
+        destination: S,
+        media_session_id: i32,
+    ) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let mut view = QueueView {
+            channel,
+            destination: destination.into(),
+            media_session_id,
+            batch_size: QUEUE_VIEW_BATCH_SIZE,
+            item_ids: vec![],
+            items: HashMap::new(),
+        };
+        view.refresh()?;
+
+        Ok(view)
+    }
+
+    /// Sets maximum number of items fetched with a single request, 20 by default.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Fetches ids of the queue items again (e.g. once the queue has changed), items that are
+    /// still in the queue stay cached.
+    pub fn refresh(&mut self) -> Result<(), Error> {
+        self.item_ids = self
+            .channel
+            .queue_get_item_ids(self.destination.clone(), self.media_session_id)?;
+
+        let item_ids = &self.item_ids;
+        self.items.retain(|item_id, _| item_ids.contains(item_id));
+
+        Ok(())
+    }
+
+    /// Returns number of the items in the queue.
+    pub fn len(&self) -> usize {
+        self.item_ids.len()
+    }
+
+    /// Determines whether the queue has no items.
+    pub fn is_empty(&self) -> bool {
+        self.item_ids.is_empty()
+    }
+
+    /// Returns ids of all the queue items in the order they are played.
+    pub fn item_ids(&self) -> &[u16] {
+        &self.item_ids
+    }
+
+    /// Returns item at the `index` of the queue, fetching it (along with the following items of
+    /// the batch) if it's not cached yet.
+    pub fn get(&mut self, index: usize) -> Result<Option<&QueueItemInfo>, Error> {
+        Ok(self.items(index..index + 1)?.into_iter().next())
+    }
+
+    /// Returns items in the `range` of the queue indexes, fetching the ones that aren't cached yet.
+    /// Range is clamped to the queue length, items the receiver hasn't returned are skipped.
+    pub fn items(&mut self, range: Range<usize>) -> Result<Vec<&QueueItemInfo>, Error> {
+        let end = range.end.min(self.item_ids.len());
+        let start = range.start.min(end);
+
+        // Fetch complete batches starting with the first missing item, so that sequential
+        // access (e.g. scrolling) takes as few requests as possible.
+        let mut index = start;
+        while index < end {
+            if self.items.contains_key(&self.item_ids[index]) {
+                index += 1;
+                continue;
+            }
+
+            let batch_end = (index + self.batch_size).min(self.item_ids.len());
+            let missing = self.item_ids[index..batch_end]
+                .iter()
+                .copied()
+                .filter(|item_id| !self.items.contains_key(item_id))
+                .collect::<Vec<_>>();
+
+            for item in self.channel.queue_get_items(
+                self.destination.clone(),
+                self.media_session_id,
+                &missing,
+            )? {
+                self.items.insert(item.item_id, item);
+            }
+
+            index = batch_end;
+        }
+
+        Ok(self.item_ids[start..end]
+            .iter()
+            .filter_map(|item_id| self.items.get(item_id))
+            .collect())
+    }
+}
+
+/// Change of the queue playback derived from the consecutive status entries, see `QueueTracker`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QueueEvent {
+    /// Receiver has started preloading the item with the specified id.
+    Preloading(u16),
+    /// Preloaded item with the specified id has been discarded without being played (e.g. because
+    /// the queue has been changed).
+    PreloadDiscarded(u16),
+    /// Receiver has switched to the next item.
+    ItemChanged {
+        /// Id of the item that has been played before, if any.
+        previous: Option<u16>,
+        /// Id of the item that is played now.
+        current: u16,
+        /// Whether the item has been preloaded, i.e. the transition has been gapless.
+        preloaded: bool,
+    },
+}
+
+/// Tracks queue item ids reported in the status updates of the media session, so that
+/// preloading and gapless transitions can be observed: every status entry received (either as a
+/// response or as a broadcast) is fed to `QueueTracker::update` that returns what has changed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_cast::{
+///     channels::media::{MediaResponse, QueueEvent, QueueTracker},
+///     CastDevice, ChannelMessage,
+/// };
+///
+/// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// let mut tracker = QueueTracker::default();
+///
+/// loop {
+///     if let ChannelMessage::Media(MediaResponse::Status(status)) = cast_device.receive()? {
+///         for entry in &status.entries {
+///             for event in tracker.update(entry) {
+///                 if let QueueEvent::Preloading(item_id) = event {
+///                     println!("Item {item_id} is up next.");
+///                 }
+///             }
+///         }
+///     }
+/// }
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct QueueTracker {
+    media_session_id: Option<i32>,
+    current_item_id: Option<u16>,
+    preloaded_item_id: Option<u16>,
+}
+
+impl QueueTracker {
+    /// Compares the `entry` with the previously seen one and returns the changes in order they
+    /// have happened. Entry of another media session resets the tracker.
+    pub fn update(&mut self, entry: &StatusEntry) -> Vec<QueueEvent> {
+        let mut events = vec![];
+
+        if self.media_session_id != Some(entry.media_session_id) {
+            *self = QueueTracker {
+                media_session_id: Some(entry.media_session_id),
+                ..QueueTracker::default()
+            };
+        }
+
+        let previous_preloaded = self.preloaded_item_id;
+
+        if let Some(current) = entry.current_item_id {
+            if self.current_item_id != Some(current) {
+                events.push(QueueEvent::ItemChanged {
+                    previous: self.current_item_id,
+                    current,
+                    preloaded: previous_preloaded == Some(current),
+                });
+                self.current_item_id = Some(current);
+            }
+        }
+
+        if entry.preloaded_item_id != previous_preloaded {
+            if let Some(discarded) =
+                previous_preloaded.filter(|preloaded| Some(*preloaded) != self.current_item_id)
+            {
+                events.push(QueueEvent::PreloadDiscarded(discarded));
+            }
+            if let Some(preloaded) = entry.preloaded_item_id {
+                events.push(QueueEvent::Preloading(preloaded));
+            }
+            self.preloaded_item_id = entry.preloaded_item_id;
+        }
+
+        events
+    }
+
+    /// Returns id of the queue item that is played now, if known.
+    pub fn current_item_id(&self) -> Option<u16> {
+        self.current_item_id
+    }
+
+    /// Returns id of the queue item that is currently preloaded, if any.
+    pub fn preloaded_item_id(&self) -> Option<u16> {
+        self.preloaded_item_id
+    }
+}
+
 /// Status of loading the next media
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ExtendedStatus {
     /// Describes the state of the player.
     pub player_state: ExtendedPlayerState,
@@ -649,8 +2328,109 @@ impl TryFrom<&proxies::media::ExtendedStatus> for ExtendedStatus {
     }
 }
 
+/// Set of media commands supported by the media player, can be used to enable or disable
+/// corresponding UI controls. Bits that are unknown to this library are preserved.
+///
+/// See also the [`Command` Cast reference](https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages#.Command).
+///
+/// # Examples
+///
+/// ```
+/// use rust_cast::channels::media::MediaCommands;
+///
+/// let commands = MediaCommands::from_bits(15);
+/// assert!(commands.contains(MediaCommands::PAUSE | MediaCommands::SEEK));
+/// assert!(!commands.contains(MediaCommands::QUEUE_NEXT));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MediaCommands(u32);
+
+impl MediaCommands {
+    /// Pause command.
+    pub const PAUSE: MediaCommands = MediaCommands(1);
+    /// Seek command.
+    pub const SEEK: MediaCommands = MediaCommands(1 << 1);
+    /// Stream volume command.
+    pub const STREAM_VOLUME: MediaCommands = MediaCommands(1 << 2);
+    /// Stream mute command.
+    pub const STREAM_MUTE: MediaCommands = MediaCommands(1 << 3);
+    /// All basic media commands: pause, seek, stream volume and stream mute.
+    pub const ALL_BASIC_MEDIA: MediaCommands = MediaCommands(15);
+    /// Queue next command.
+    pub const QUEUE_NEXT: MediaCommands = MediaCommands(1 << 6);
+    /// Queue previous command.
+    pub const QUEUE_PREV: MediaCommands = MediaCommands(1 << 7);
+    /// Queue shuffle command.
+    pub const QUEUE_SHUFFLE: MediaCommands = MediaCommands(1 << 8);
+    /// Skip ad command.
+    pub const SKIP_AD: MediaCommands = MediaCommands(1 << 9);
+    /// Queue repeat all command.
+    pub const QUEUE_REPEAT_ALL: MediaCommands = MediaCommands(1 << 10);
+    /// Queue repeat one command.
+    pub const QUEUE_REPEAT_ONE: MediaCommands = MediaCommands(1 << 11);
+    /// Edit tracks command.
+    pub const EDIT_TRACKS: MediaCommands = MediaCommands(1 << 12);
+    /// Playback rate command.
+    pub const PLAYBACK_RATE: MediaCommands = MediaCommands(1 << 13);
+    /// Like command.
+    pub const LIKE: MediaCommands = MediaCommands(1 << 14);
+    /// Dislike command.
+    pub const DISLIKE: MediaCommands = MediaCommands(1 << 15);
+    /// Follow command.
+    pub const FOLLOW: MediaCommands = MediaCommands(1 << 16);
+    /// Unfollow command.
+    pub const UNFOLLOW: MediaCommands = MediaCommands(1 << 17);
+    /// Stream transfer command.
+    pub const STREAM_TRANSFER: MediaCommands = MediaCommands(1 << 18);
+    /// Lyrics command.
+    pub const LYRICS: MediaCommands = MediaCommands(1 << 19);
+
+    /// Creates set of media commands from the raw `supportedMediaCommands` value.
+    pub const fn from_bits(bits: u32) -> MediaCommands {
+        MediaCommands(bits)
+    }
+
+    /// Returns raw `supportedMediaCommands` value.
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` if all of the `commands` are supported.
+    pub const fn contains(&self, commands: MediaCommands) -> bool {
+        self.0 & commands.0 == commands.0
+    }
+
+    /// Returns `true` if none of the commands is supported.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for MediaCommands {
+    type Output = MediaCommands;
+
+    fn bitor(self, rhs: MediaCommands) -> MediaCommands {
+        MediaCommands(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for MediaCommands {
+    type Output = MediaCommands;
+
+    fn bitand(self, rhs: MediaCommands) -> MediaCommands {
+        MediaCommands(self.0 & rhs.0)
+    }
+}
+
+impl From<u32> for MediaCommands {
+    fn from(bits: u32) -> Self {
+        MediaCommands(bits)
+    }
+}
+
 /// Detailed status of the media artifact with respect to the session.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StatusEntry {
     /// Unique ID for the playback of this specific session. This ID is set by the receiver at LOAD
     /// and can be used to identify a specific instance of a playback. For example, two playbacks of
@@ -682,18 +2462,268 @@ pub struct StatusEntry {
     /// If this a live stream content, then this field represents the time in seconds from the
     /// beginning of the event that should be known to the player.
     pub current_time: Option<f32>,
-    /// Flags describing which media commands the media player supports:
-    /// * `1` `Pause`;
-    /// * `2` `Seek`;
-    /// * `4` `Stream volume`;
-    /// * `8` `Stream mute`;
-    /// * `16` `Skip forward`;
-    /// * `32` `Skip backward`;
-    /// * `1 << 12` `Unknown`;
-    /// * `1 << 13` `Unknown`;
-    /// * `1 << 18` `Unknown`.
-    /// Combinations are described as summations; for example, Pause+Seek+StreamVolume+Mute == 15.
-    pub supported_media_commands: u32,
+    /// Media commands the media player supports.
+    pub supported_media_commands: MediaCommands,
+    /// Seekable range of the live or event stream, it's only provided for live streams.
+    pub live_seekable_range: Option<LiveSeekableRange>,
+    /// Status of the ad break that is currently being played, if any.
+    pub break_status: Option<BreakStatus>,
+    /// Information about the video being played (resolution and HDR mode), if provided by the
+    /// receiver.
+    pub video_info: Option<VideoInfo>,
+    /// IDs of the currently active text, audio or video tracks of the media.
+    pub active_track_ids: Vec<u32>,
+    /// Moment the status has been received (or deserialized) at, it's used to extrapolate the
+    /// current position, see [`StatusEntry::estimated_current_time`].
+    #[serde(skip, default = "Instant::now")]
+    pub received_at: Instant,
+}
+
+impl StatusEntry {
+    /// Estimates the current position of the media player by extrapolating `current_time` with
+    /// the time elapsed since the status has been received. Position only progresses while media
+    /// is playing (with `playback_rate`) and doesn't go beyond the media duration, if it's known.
+    pub fn estimated_current_time(&self) -> Option<f32> {
+        self.current_time_at(Instant::now())
+    }
+
+    /// Extrapolates `current_time` to the moment `at`, see `estimated_current_time`.
+    fn current_time_at(&self, at: Instant) -> Option<f32> {
+        let current_time = self.current_time?;
+        if self.player_state != PlayerState::Playing {
+            return Some(current_time);
+        }
+
+        let elapsed = at.saturating_duration_since(self.received_at).as_secs_f32();
+        let position = (current_time + elapsed * self.playback_rate).max(0.0);
+
+        match self.media.as_ref().and_then(|media| media.duration) {
+            Some(duration) => Some(position.min(duration)),
+            None => Some(position),
+        }
+    }
+
+    /// Compares two consecutive status entries and returns what has changed between them. Media
+    /// is only reported by the receiver once it changes, so `new` entry without media keeps the
+    /// media of the `old` one. Position is considered changed only if it's not where `old` entry
+    /// extrapolated to the moment `new` one has been received expects it to be (e.g. after seek).
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - Previously received status entry.
+    /// * `new` - Status entry received after the `old` one.
+    pub fn diff(old: &StatusEntry, new: &StatusEntry) -> Vec<MediaStatusChange> {
+        let mut changes = vec![];
+
+        if old.media_session_id != new.media_session_id {
+            changes.push(MediaStatusChange::SessionChanged(new.media_session_id));
+        }
+
+        if let Some(media) = new
+            .media
+            .as_ref()
+            .filter(|media| old.media.as_ref() != Some(media))
+        {
+            changes.push(MediaStatusChange::MediaChanged(Box::new(media.clone())));
+        }
+
+        if old.current_item_id != new.current_item_id {
+            changes.push(MediaStatusChange::ItemChanged(new.current_item_id));
+        }
+
+        if old.player_state != new.player_state {
+            changes.push(MediaStatusChange::PlayerStateChanged {
+                old: old.player_state.clone(),
+                new: new.player_state.clone(),
+                idle_reason: new.idle_reason.clone(),
+            });
+        }
+
+        if old.playback_rate != new.playback_rate {
+            changes.push(MediaStatusChange::PlaybackRateChanged(new.playback_rate));
+        }
+
+        if old.active_track_ids != new.active_track_ids {
+            changes.push(MediaStatusChange::ActiveTracksChanged(
+                new.active_track_ids.clone(),
+            ));
+        }
+
+        if let Some(position) = new.current_time {
+            let expected = old.current_time_at(new.received_at);
+            if expected.is_none_or(|expected| (position - expected).abs() > POSITION_TOLERANCE) {
+                changes.push(MediaStatusChange::PositionChanged(position));
+            }
+        }
+
+        changes
+    }
+}
+
+/// Describes the change between two consecutive media status entries, see `StatusEntry::diff`
+/// and `ProgressTracker`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MediaStatusChange {
+    /// Another media session (with the specified id) has started.
+    SessionChanged(i32),
+    /// Media of the session has changed.
+    MediaChanged(Box<Media>),
+    /// Id of the current queue item has changed.
+    ItemChanged(Option<u16>),
+    /// Player has switched to another state.
+    PlayerStateChanged {
+        /// State before the change.
+        old: PlayerState,
+        /// State after the change.
+        new: PlayerState,
+        /// Reason of the switch to `Idle` state, if known.
+        idle_reason: Option<IdleReason>,
+    },
+    /// Playback rate has changed.
+    PlaybackRateChanged(f32),
+    /// Set of the active tracks has changed.
+    ActiveTracksChanged(Vec<u32>),
+    /// Position of the media player (in seconds) has changed, either reported by the receiver or
+    /// extrapolated by `ProgressTracker::tick`.
+    PositionChanged(f32),
+}
+
+/// Turns the status entries of the media session into `MediaStatusChange` events and emits
+/// `PositionChanged` events at the fixed rate while media is playing, so that progress can be
+/// displayed smoothly without polling the receiver: position is extrapolated from the latest
+/// status entry, see `StatusEntry::estimated_current_time`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use rust_cast::{
+///     channels::media::{MediaResponse, MediaStatusChange, ProgressTracker},
+///     CastDevice, ChannelMessage,
+/// };
+///
+/// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// let mut tracker = ProgressTracker::new(Duration::from_millis(250));
+///
+/// loop {
+///     let message = cast_device.receive_timeout(tracker.timeout())?;
+///     if let Some(ChannelMessage::Media(MediaResponse::Status(status))) = message {
+///         for entry in &status.entries {
+///             tracker.update(entry);
+///         }
+///     }
+///
+///     if let Some(MediaStatusChange::PositionChanged(position)) = tracker.tick() {
+///         println!("Playing at {position:.1}s");
+///     }
+/// }
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProgressTracker {
+    interval: Duration,
+    entry: Option<StatusEntry>,
+    next_tick: Instant,
+}
+
+impl ProgressTracker {
+    /// Creates tracker that emits `PositionChanged` events every `interval` while media is
+    /// playing.
+    pub fn new(interval: Duration) -> ProgressTracker {
+        ProgressTracker {
+            interval,
+            entry: None,
+            next_tick: Instant::now() + interval,
+        }
+    }
+
+    /// Compares the `entry` with the previously seen one and returns the changes, see
+    /// `StatusEntry::diff`. The first entry is only remembered.
+    pub fn update(&mut self, entry: &StatusEntry) -> Vec<MediaStatusChange> {
+        let mut entry = entry.clone();
+        let changes = match self.entry.take() {
+            Some(old) => {
+                let changes = StatusEntry::diff(&old, &entry);
+                if entry.media.is_none() && old.media_session_id == entry.media_session_id {
+                    entry.media = old.media;
+                }
+                changes
+            }
+            None => vec![],
+        };
+
+        self.entry = Some(entry);
+        self.next_tick = Instant::now() + self.interval;
+
+        changes
+    }
+
+    /// Returns `PositionChanged` event with the extrapolated position if the `interval` has
+    /// elapsed since the previous event (or status entry) and media is playing.
+    pub fn tick(&mut self) -> Option<MediaStatusChange> {
+        let now = Instant::now();
+        if now < self.next_tick {
+            return None;
+        }
+
+        self.next_tick = now + self.interval;
+
+        self.entry
+            .as_ref()
+            .filter(|entry| entry.player_state == PlayerState::Playing)
+            .and_then(|entry| entry.current_time_at(now))
+            .map(MediaStatusChange::PositionChanged)
+    }
+
+    /// Returns how long an event loop can wait for the messages before `tick` is due.
+    pub fn timeout(&self) -> Duration {
+        self.next_tick.saturating_duration_since(Instant::now())
+    }
+
+    /// Returns the latest status entry (with the media of the previous ones), if any.
+    pub fn entry(&self) -> Option<&StatusEntry> {
+        self.entry.as_ref()
+    }
+}
+
+// Receive timestamp is not a part of the status reported by the receiver.
+impl PartialEq for StatusEntry {
+    fn eq(&self, other: &Self) -> bool {
+        let StatusEntry {
+            media_session_id,
+            media,
+            playback_rate,
+            player_state,
+            current_item_id,
+            loading_item_id,
+            preloaded_item_id,
+            idle_reason,
+            extended_status,
+            current_time,
+            supported_media_commands,
+            live_seekable_range,
+            break_status,
+            video_info,
+            active_track_ids,
+            received_at: _,
+        } = self;
+
+        *media_session_id == other.media_session_id
+            && *media == other.media
+            && *playback_rate == other.playback_rate
+            && *player_state == other.player_state
+            && *current_item_id == other.current_item_id
+            && *loading_item_id == other.loading_item_id
+            && *preloaded_item_id == other.preloaded_item_id
+            && *idle_reason == other.idle_reason
+            && *extended_status == other.extended_status
+            && *current_time == other.current_time
+            && *supported_media_commands == other.supported_media_commands
+            && *live_seekable_range == other.live_seekable_range
+            && *break_status == other.break_status
+            && *video_info == other.video_info
+            && *active_track_ids == other.active_track_ids
+    }
 }
 
 impl TryFrom<&proxies::media::Status> for StatusEntry {
@@ -719,32 +2749,141 @@ impl TryFrom<&proxies::media::Status> for StatusEntry {
                 .map(ExtendedStatus::try_from)
                 .transpose()?,
             current_time: x.current_time,
-            supported_media_commands: x.supported_media_commands,
+            supported_media_commands: MediaCommands::from_bits(x.supported_media_commands),
+            live_seekable_range: x.live_seekable_range.as_ref().map(LiveSeekableRange::from),
+            break_status: x.break_status.as_ref().map(BreakStatus::from),
+            video_info: x.video_info.as_ref().map(VideoInfo::try_from).transpose()?,
+            active_track_ids: x.active_track_ids.clone(),
+            received_at: Instant::now(),
+        })
+    }
+}
+
+/// Describes HDR mode of the video being played.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HdrType {
+    /// Standard dynamic range.
+    Sdr,
+    /// High dynamic range.
+    Hdr,
+    /// Dolby Vision.
+    DolbyVision,
+    /// HDR type that isn't known to this library yet.
+    Unknown(String),
+}
+
+impl FromStr for HdrType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<HdrType, Error> {
+        match s {
+            "sdr" => Ok(HdrType::Sdr),
+            "hdr" => Ok(HdrType::Hdr),
+            "dv" => Ok(HdrType::DolbyVision),
+            unknown => Ok(HdrType::Unknown(unknown.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for HdrType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hdr_type = match *self {
+            HdrType::Sdr => "sdr",
+            HdrType::Hdr => "hdr",
+            HdrType::DolbyVision => "dv",
+            HdrType::Unknown(ref hdr_type) => hdr_type,
+        };
+
+        write!(f, "{}", hdr_type)
+    }
+}
+
+/// Information about the video being played.
+///
+/// See also the [`VideoInformation` Cast reference](https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.VideoInformation).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VideoInfo {
+    /// Width of the video in pixels.
+    pub width: u32,
+    /// Height of the video in pixels.
+    pub height: u32,
+    /// HDR mode of the video.
+    pub hdr_type: HdrType,
+}
+
+impl TryFrom<&proxies::media::VideoInfo> for VideoInfo {
+    type Error = Error;
+
+    fn try_from(vi: &proxies::media::VideoInfo) -> Result<Self, Error> {
+        Ok(Self {
+            width: vi.width,
+            height: vi.height,
+            hdr_type: HdrType::from_str(&vi.hdr_type)?,
         })
     }
 }
 
+/// Seekable range of the live or event stream.
+///
+/// See also the [`LiveSeekableRange` Cast reference](https://developers.google.com/cast/docs/reference/web_receiver/cast.framework.messages.LiveSeekableRange).
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LiveSeekableRange {
+    /// The start time of the range in seconds.
+    pub start: Option<f32>,
+    /// The end time of the range in seconds (the live edge).
+    pub end: Option<f32>,
+    /// Whether the seekable range is a moving window, i.e. start time moves along with the live
+    /// edge. If `false`, the start time is fixed (e.g. event stream with DVR).
+    pub is_moving_window: bool,
+    /// Whether the live stream is done, i.e. no more content is appended to the stream.
+    pub is_live_done: bool,
+}
+
+impl From<&proxies::media::LiveSeekableRange> for LiveSeekableRange {
+    fn from(range: &proxies::media::LiveSeekableRange) -> Self {
+        Self {
+            start: range.start,
+            end: range.end,
+            is_moving_window: range.is_moving_window,
+            is_live_done: range.is_live_done,
+        }
+    }
+}
+
 /// Describes the load cancelled error.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LoadCancelled {
     /// Unique id of the request that caused this error.
-    pub request_id: u32,
+    pub request_id: RequestId,
 }
 
 /// Describes the load failed error.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LoadFailed {
     /// Unique id of the request that caused this error.
-    pub request_id: u32,
+    pub request_id: RequestId,
+    /// The detailed error code of the failure if provided by the receiver.
+    pub detailed_error_code: Option<MediaDetailedErrorCode>,
 }
 
 /// The additional options for a load command request.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct LoadOptions {
     /// The current time of the content to start the playback at.
     pub current_time: f64,
     /// Whether to start playback automatically after the media has been loaded.
     pub autoplay: bool,
+    /// DRM configuration of the protected media, passed to the receiver via `customData`.
+    pub drm: Option<DrmConfig>,
+    /// Credentials of the user for the receivers that require sender-supplied auth tokens.
+    pub credentials: Option<Credentials>,
+    /// Artwork displayed by the receiver (e.g. Default Media Receiver) while the media is
+    /// playing, appended to the images of the media metadata (generic metadata is used if the
+    /// media has none).
+    pub images: Vec<Image>,
+    /// IDs of the tracks that should be active once the media is loaded, `None` leaves the choice
+    /// up to the receiver.
+    pub active_track_ids: Option<Vec<u32>>,
 }
 
 impl Default for LoadOptions {
@@ -752,38 +2891,108 @@ impl Default for LoadOptions {
         LoadOptions {
             current_time: 0f64,
             autoplay: true,
+            drm: None,
+            credentials: None,
+            images: vec![],
+            active_track_ids: None,
+        }
+    }
+}
+
+/// Content protection system used by the protected media.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProtectionSystem {
+    /// Google Widevine.
+    Widevine,
+    /// Microsoft PlayReady.
+    PlayReady,
+    /// W3C Clear Key.
+    ClearKey,
+}
+
+impl fmt::Display for ProtectionSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let protection_system = match *self {
+            ProtectionSystem::Widevine => "widevine",
+            ProtectionSystem::PlayReady => "playready",
+            ProtectionSystem::ClearKey => "clearkey",
+        };
+
+        write!(f, "{}", protection_system)
+    }
+}
+
+/// DRM configuration of the protected media. The configuration is sent to the receiver as
+/// `customData.licenseUrl`, `customData.protectionSystem` and `customData.licenseRequestHeaders`,
+/// so the receiver application has to support these fields (e.g. Default Media Receiver does).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrmConfig {
+    /// URL of the license server.
+    pub license_url: String,
+    /// Content protection system of the media.
+    pub protection_system: ProtectionSystem,
+    /// Additional HTTP headers sent along with the license requests (e.g. authorization tokens).
+    pub license_request_headers: HashMap<String, String>,
+}
+
+impl DrmConfig {
+    /// Creates Widevine DRM configuration with the provided license server URL.
+    pub fn widevine<S>(license_url: S) -> DrmConfig
+    where
+        S: Into<String>,
+    {
+        DrmConfig {
+            license_url: license_url.into(),
+            protection_system: ProtectionSystem::Widevine,
+            license_request_headers: HashMap::new(),
+        }
+    }
+
+    fn encode(&self) -> proxies::media::CustomData {
+        proxies::media::CustomData {
+            license_url: Some(self.license_url.clone()),
+            protection_system: Some(self.protection_system.to_string()),
+            license_request_headers: if self.license_request_headers.is_empty() {
+                None
+            } else {
+                Some(self.license_request_headers.clone())
+            },
         }
     }
 }
 
 /// Describes the invalid player state error.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct InvalidPlayerState {
     /// Unique id of the request that caused this error.
-    pub request_id: u32,
+    pub request_id: RequestId,
 }
 
 /// Describes the invalid request error.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct InvalidRequest {
     /// Unique id of the invalid request.
-    pub request_id: u32,
+    pub request_id: RequestId,
     /// Description of the invalid request reason if available.
     pub reason: Option<String>,
 }
 
 /// The media error encountered during media operations.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MediaError {
+    /// Unique id of the request that caused this error if available.
+    pub request_id: Option<RequestId>,
     /// The detailed error code associated with the media error.
     pub detailed_error_code: MediaDetailedErrorCode,
     /// The type of the error message.
     pub message_type: String,
+    /// Description of the error reason if available.
+    pub reason: Option<String>,
 }
 
 /// The detailed media error code.
-/// https://developers.google.com/android/reference/com/google/android/gms/cast/MediaError.DetailedErrorCode#constants
-#[derive(Clone, Debug, PartialEq)]
+/// <https://developers.google.com/android/reference/com/google/android/gms/cast/MediaError.DetailedErrorCode#constants>
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MediaDetailedErrorCode {
     /// An error occurs outside of the framework (e.g., if an event handler throws an error).
     App = 900,
@@ -921,7 +3130,7 @@ impl TryFrom<i32> for MediaDetailedErrorCode {
 }
 
 /// Represents all currently supported incoming messages that media channel can handle.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MediaResponse {
     /// Statuses of the currently active media.
     Status(Status),
@@ -936,462 +3145,1003 @@ pub enum MediaResponse {
     InvalidRequest(InvalidRequest),
     /// The media error that occurred while executing a media operation on the media channel.
     Error(MediaError),
+    /// Ids of the queue items.
+    QueueItemIds(QueueItemIds),
+    /// Requested queue items.
+    QueueItems(QueueItems),
     /// Used every time when channel can't parse the message. Associated data contains `type` string
     /// field and raw JSON data returned from cast device.
     NotImplemented(String, serde_json::Value),
 }
 
-pub struct MediaChannel<'a, W>
+/// Reports that the media session has stopped playing, see `MediaChannel::on_playback_finished`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaybackFinished {
+    /// ID of the media session that has become idle.
+    pub media_session_id: i32,
+    /// Why the playback has stopped: media has completed, has been stopped (`Cancelled`),
+    /// replaced with another media (`Interrupted`) or has failed (`Error`).
+    pub reason: IdleReason,
+}
+
+/// Function that is called every time the media session stops playing.
+type PlaybackFinishedListener = Box<dyn Fn(&PlaybackFinished) + Send>;
+
+/// Media sessions that have stopped playing, so that every session is reported only once, even
+/// if the receiver keeps reporting it as idle.
+#[derive(Default)]
+struct PlaybackWatchdog {
+    finished: HashMap<i32, IdleReason>,
+    listeners: Vec<PlaybackFinishedListener>,
+}
+
+pub struct MediaChannel<W>
 where
     W: Read + Write,
 {
-    sender: Cow<'a, str>,
+    sender: String,
     message_manager: Lrc<MessageManager<W>>,
+    watchdog: Lrc<Lock<PlaybackWatchdog>>,
+}
+
+/// Cloned channel shares the connection and the tracked state with the original one.
+impl<W> Clone for MediaChannel<W>
+where
+    W: Read + Write,
+{
+    fn clone(&self) -> Self {
+        MediaChannel {
+            sender: self.sender.clone(),
+            message_manager: Lrc::clone(&self.message_manager),
+            watchdog: Lrc::clone(&self.watchdog),
+        }
+    }
 }
 
-impl<'a, W> MediaChannel<'a, W>
-where
-    W: Read + Write,
-{
-    pub fn new<S>(sender: S, message_manager: Lrc<MessageManager<W>>) -> MediaChannel<'a, W>
-    where
-        S: Into<Cow<'a, str>>,
-    {
-        MediaChannel {
-            sender: sender.into(),
-            message_manager,
-        }
+impl<W> MediaChannel<W>
+where
+    W: Read + Write,
+{
+    pub fn new<S>(sender: S, message_manager: Lrc<MessageManager<W>>) -> MediaChannel<W>
+    where
+        S: Into<String>,
+    {
+        MediaChannel {
+            sender: sender.into(),
+            message_manager,
+            watchdog: Lrc::new(Lock::new(PlaybackWatchdog::default())),
+        }
+    }
+
+    /// Retrieves status of the cast device media session.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `protocol` identifier of specific app media session;
+    /// * `media_session_id` - Media session ID of the media for which the media status should be
+    ///   returned. If none is provided, then the status for all media session IDs will be provided.
+    ///
+    /// # Return value
+    ///
+    /// Returned `Result` should consist of either `Status` instance or an `Error`.
+    pub fn get_status<S>(
+        &self,
+        destination: S,
+        media_session_id: Option<i32>,
+    ) -> Result<Status, Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.get_status",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
+
+        let payload = serde_json::to_string(&proxies::media::GetStatusRequest {
+            typ: MESSAGE_TYPE_GET_STATUS.to_string(),
+            request_id: request_id.get(),
+            media_session_id,
+        })?;
+
+        self.message_manager.send(CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: self.sender.to_string(),
+            destination: destination.to_string(),
+            payload: CastMessagePayload::String(payload),
+        })?;
+
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
+
+                match self.parse(message)? {
+                    MediaResponse::Status(status) if status.request_id == request_id => {
+                        return Ok(Some(status));
+                    }
+                    MediaResponse::InvalidRequest(error) if error.request_id == request_id => {
+                        return Err(error.into());
+                    }
+                    MediaResponse::Error(error) if error.request_id == Some(request_id) => {
+                        return Err(error.into());
+                    }
+                    _ => {}
+                }
+
+                Ok(None)
+            })
+    }
+
+    /// Loads provided media to the application.
+    ///
+    /// # Arguments
+    /// * `destination` - `protocol` of the application to load media with (e.g. `web-1`);
+    /// * `session_id` - Current session identifier of the player application;
+    /// * `media` - `Media` instance that describes the media we'd like to load.
+    ///
+    /// # Return value
+    ///
+    /// Returned `Result` should consist of either `Status` instance or an `Error`.
+    pub fn load<S>(&self, destination: S, session_id: S, media: &Media) -> Result<Status, Error>
+    where
+        S: Into<String>,
+    {
+        self.load_with_opts(destination, session_id, media, LoadOptions::default())
+    }
+
+    /// Loads provided media to the application with the additional provided options.
+    ///
+    /// # Arguments
+    /// * `destination` - `protocol` of the application to load media with (e.g. `web-1`);
+    /// * `session_id` - Current session identifier of the player application;
+    /// * `media` - `Media` instance that describes the media we'd like to load.
+    /// * `options` - Additional options for the load request.
+    ///
+    /// # Return value
+    ///
+    /// Returned `Result` should consist of either `Status` instance or an `Error`.
+    pub fn load_with_opts<S>(
+        &self,
+        destination: S,
+        session_id: S,
+        media: &Media,
+        options: LoadOptions,
+    ) -> Result<Status, Error>
+    where
+        S: Into<String>,
+    {
+        self.load_with_queue(destination, session_id, media, None, options)
+    }
+
+    /// Loads provided media to the application.
+    ///
+    /// # Arguments
+    /// * `destination` - `protocol` of the application to load media with (e.g. `web-1`);
+    /// * `session_id` - Current session identifier of the player application;
+    /// * `media` - `Media` instance that describes the media we'd like to load.
+    ///
+    /// # Return value
+    ///
+    /// Returned `Result` should consist of either `Status` instance or an `Error`.
+    pub fn load_with_queue<S>(
+        &self,
+        destination: S,
+        session_id: S,
+        media: &Media,
+        queue: Option<&MediaQueue>,
+        options: LoadOptions,
+    ) -> Result<Status, Error>
+    where
+        S: Into<String>,
+    {
+        Image::validate_all(&options.images)?;
+
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.load_with_queue",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
+
+        let payload = serde_json::to_string(&proxies::media::MediaRequest {
+            request_id: request_id.get(),
+            session_id: session_id.into().to_string(),
+            typ: MESSAGE_TYPE_LOAD.to_string(),
+
+            media: media.encode_with_images(&options.images),
+
+            current_time: options.current_time,
+            autoplay: options.autoplay,
+            custom_data: options
+                .drm
+                .as_ref()
+                .map(|drm| drm.encode())
+                .unwrap_or_default(),
+            queue_data: queue.map(|qd| qd.encode()),
+            credentials: options
+                .credentials
+                .as_ref()
+                .map(|credentials| credentials.credentials.clone()),
+            credentials_type: options
+                .credentials
+                .as_ref()
+                .and_then(|credentials| credentials.credentials_type.clone()),
+            active_track_ids: options.active_track_ids,
+        })?;
+
+        // Once media is loaded cast receiver device should emit status update event, or load failed
+        // event if something went wrong.
+        self.request(destination, payload, || {
+            self.message_manager
+                .receive_response(request_id, |message| {
+                    if !self.can_handle(message) {
+                        return Ok(None);
+                    }
+
+                    match self.parse(message)? {
+                        MediaResponse::Status(status) => {
+                            if status.request_id == request_id {
+                                return Ok(Some(status));
+                            }
+
+                            // [WORKAROUND] In some cases we don't receive response (e.g. from YouTube app),
+                            // so let's just wait for the response with the media we're interested in and
+                            // return it.
+                            let has_media = {
+                                status.entries.iter().any(|entry| {
+                                    if let Some(ref loaded_media) = entry.media {
+                                        return loaded_media.content_id == media.content_id;
+                                    }
+
+                                    false
+                                })
+                            };
+
+                            if has_media {
+                                return Ok(Some(status));
+                            }
+                        }
+                        MediaResponse::LoadFailed(error) if error.request_id == request_id => {
+                            return Err(error.into());
+                        }
+                        MediaResponse::LoadCancelled(error) if error.request_id == request_id => {
+                            return Err(error.into());
+                        }
+                        MediaResponse::InvalidPlayerState(error)
+                            if error.request_id == request_id =>
+                        {
+                            return Err(error.into());
+                        }
+                        MediaResponse::InvalidRequest(error) if error.request_id == request_id => {
+                            return Err(error.into());
+                        }
+                        MediaResponse::Error(error) if error.request_id == Some(request_id) => {
+                            return Err(error.into());
+                        }
+                        _ => {}
+                    }
+
+                    Ok(None)
+                })
+        })
+    }
+
+    pub fn load_queue<S>(
+        &self,
+        destination: S,
+        _session_id: S,
+        queue: &MediaQueue,
+    ) -> Result<Status, Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.load_queue",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
+
+        let payload = serde_json::to_string(&proxies::media::QueueLoadRequest {
+            typ: MESSAGE_TYPE_QUEUE_LOAD.to_string(),
+            request_id: request_id.get(),
+            custom_data: None,
+            items: queue.items.iter().map(|qi| qi.encode()).collect(),
+            queue_type: Some(queue.queue_type.to_string()),
+            repeat_mode: "REPEAT_OFF".to_owned(),
+            start_index: queue.start_index,
+        })?;
+
+        // Once media is loaded cast receiver device should emit status update event, or load failed
+        // event if something went wrong.
+        self.request(destination, payload, || {
+            self.message_manager
+                .receive_response(request_id, |message| {
+                    if !self.can_handle(message) {
+                        return Ok(None);
+                    }
+
+                    match self.parse(message)? {
+                        MediaResponse::Status(status) if status.request_id == request_id => {
+                            return Ok(Some(status));
+                        }
+                        MediaResponse::LoadFailed(error) if error.request_id == request_id => {
+                            return Err(error.into());
+                        }
+                        MediaResponse::LoadCancelled(error) if error.request_id == request_id => {
+                            return Err(error.into());
+                        }
+                        MediaResponse::InvalidPlayerState(error)
+                            if error.request_id == request_id =>
+                        {
+                            return Err(error.into());
+                        }
+                        MediaResponse::InvalidRequest(error) if error.request_id == request_id => {
+                            return Err(error.into());
+                        }
+                        MediaResponse::Error(error) if error.request_id == Some(request_id) => {
+                            return Err(error.into());
+                        }
+                        _ => {}
+                    }
+
+                    Ok(None)
+                })
+        })
+    }
+
+    /// Pauses playback of the current content. Triggers a STATUS event notification to all sender
+    /// applications.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `protocol` of the media application (e.g. `web-1`);
+    /// * `media_session_id` - ID of the media session to be paused.
+    ///
+    /// # Return value
+    ///
+    /// Returned `Result` should consist of either `Status` instance or an `Error`.
+    pub fn pause<S>(&self, destination: S, media_session_id: i32) -> Result<StatusEntry, Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.pause",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
+
+        let payload = serde_json::to_string(&proxies::media::PlaybackGenericRequest {
+            request_id: request_id.get(),
+            media_session_id,
+            typ: MESSAGE_TYPE_PAUSE.to_string(),
+            custom_data: proxies::media::CustomData::new(),
+        })?;
+
+        self.request(destination, payload, || {
+            self.receive_status_entry(request_id, media_session_id)
+        })
     }
 
-    /// Retrieves status of the cast device media session.
+    /// Begins playback of the content that was loaded with the load call, playback is continued
+    /// from the current time position.
     ///
     /// # Arguments
     ///
-    /// * `destination` - `protocol` identifier of specific app media session;
-    /// * `media_session_id` - Media session ID of the media for which the media status should be
-    /// returned. If none is provided, then the status for all media session IDs will be provided.
+    /// * `destination` - `protocol` of the media application (e.g. `web-1`);
+    /// * `media_session_id` - ID of the media session to be played.
     ///
     /// # Return value
     ///
     /// Returned `Result` should consist of either `Status` instance or an `Error`.
-    pub fn get_status<S>(
-        &self,
-        destination: S,
-        media_session_id: Option<i32>,
-    ) -> Result<Status, Error>
+    pub fn play<S>(&self, destination: S, media_session_id: i32) -> Result<StatusEntry, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        let request_id = self.message_manager.generate_request_id().get();
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.play",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
 
-        let payload = serde_json::to_string(&proxies::media::GetStatusRequest {
-            typ: MESSAGE_TYPE_GET_STATUS.to_string(),
-            request_id,
+        let payload = serde_json::to_string(&proxies::media::PlaybackGenericRequest {
+            request_id: request_id.get(),
             media_session_id,
+            typ: MESSAGE_TYPE_PLAY.to_string(),
+            custom_data: proxies::media::CustomData::new(),
         })?;
 
-        self.message_manager.send(CastMessage {
-            namespace: CHANNEL_NAMESPACE.to_string(),
-            source: self.sender.to_string(),
-            destination: destination.into().to_string(),
-            payload: CastMessagePayload::String(payload),
-        })?;
-
-        self.message_manager.receive_find_map(|message| {
-            if !self.can_handle(message) {
-                return Ok(None);
-            }
-
-            match self.parse(message)? {
-                MediaResponse::Status(status) => {
-                    if status.request_id == request_id {
-                        return Ok(Some(status));
-                    }
-                }
-                MediaResponse::InvalidRequest(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(format!(
-                            "Invalid request ({}).",
-                            error.reason.unwrap_or_else(|| "Unknown".to_string())
-                        )));
-                    }
-                }
-                _ => {}
-            }
-
-            Ok(None)
+        self.request(destination, payload, || {
+            self.receive_status_entry(request_id, media_session_id)
         })
     }
 
-    /// Loads provided media to the application.
+    /// Stops playback of the current content. Triggers a STATUS event notification to all sender
+    /// applications. After this command the content will no longer be loaded and the
+    /// media_session_id is invalidated.
     ///
     /// # Arguments
-    /// * `destination` - `protocol` of the application to load media with (e.g. `web-1`);
-    /// * `session_id` - Current session identifier of the player application;
-    /// * `media` - `Media` instance that describes the media we'd like to load.
+    ///
+    /// * `destination` - `protocol` of the media application (e.g. `web-1`);
+    /// * `media_session_id` - ID of the media session to be stopped.
     ///
     /// # Return value
     ///
     /// Returned `Result` should consist of either `Status` instance or an `Error`.
-    pub fn load<S>(&self, destination: S, session_id: S, media: &Media) -> Result<Status, Error>
+    pub fn stop<S>(&self, destination: S, media_session_id: i32) -> Result<StatusEntry, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        self.load_with_opts(destination, session_id, media, LoadOptions::default())
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.stop",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
+
+        let payload = serde_json::to_string(&proxies::media::PlaybackGenericRequest {
+            request_id: request_id.get(),
+            media_session_id,
+            typ: MESSAGE_TYPE_STOP.to_string(),
+            custom_data: proxies::media::CustomData::new(),
+        })?;
+
+        self.request(destination, payload, || {
+            self.receive_status_entry(request_id, media_session_id)
+        })
     }
 
-    /// Loads provided media to the application with the additional provided options.
+    /// Sets the current position in the stream. Triggers a STATUS event notification to all sender
+    /// applications. If the position provided is outside the range of valid positions for the
+    /// current content, then the player should pick a valid position as close to the requested
+    /// position as possible.
     ///
     /// # Arguments
-    /// * `destination` - `protocol` of the application to load media with (e.g. `web-1`);
-    /// * `session_id` - Current session identifier of the player application;
-    /// * `media` - `Media` instance that describes the media we'd like to load.
-    /// * `options` - Additional options for the load request.
+    ///
+    /// * `destination` - `protocol` of the media application (e.g. `web-1`);
+    /// * `media_session_id` - ID of the media session to seek in;
+    /// * `current_time` - Time in seconds to seek to.
     ///
     /// # Return value
     ///
     /// Returned `Result` should consist of either `Status` instance or an `Error`.
-    pub fn load_with_opts<S>(
+    pub fn seek<S>(
         &self,
         destination: S,
-        session_id: S,
-        media: &Media,
-        options: LoadOptions,
-    ) -> Result<Status, Error>
+        media_session_id: i32,
+        current_time: Option<f32>,
+        resume_state: Option<ResumeState>,
+    ) -> Result<StatusEntry, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        self.load_with_queue(destination, session_id, media, None, options)
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.seek",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
+
+        let payload = serde_json::to_string(&proxies::media::PlaybackSeekRequest {
+            request_id: request_id.get(),
+            media_session_id,
+            typ: MESSAGE_TYPE_SEEK.to_string(),
+            current_time,
+            resume_state: resume_state.map(|s| s.to_string()),
+            custom_data: proxies::media::CustomData::new(),
+        })?;
+
+        self.request(destination, payload, || {
+            self.receive_status_entry(request_id, media_session_id)
+        })
     }
 
-    /// Loads provided media to the application.
+    /// Moves the current position in the stream by `delta` seconds (backwards if `delta` is
+    /// negative). The current position is retrieved with [`MediaChannel::get_status`] first, the
+    /// resulting position is clamped to the `[0, duration]` range (duration is only known if the
+    /// receiver reports it).
     ///
     /// # Arguments
-    /// * `destination` - `protocol` of the application to load media with (e.g. `web-1`);
-    /// * `session_id` - Current session identifier of the player application;
-    /// * `media` - `Media` instance that describes the media we'd like to load.
+    ///
+    /// * `destination` - `protocol` of the media application (e.g. `web-1`);
+    /// * `media_session_id` - ID of the media session to seek in;
+    /// * `delta` - Offset in seconds relative to the current position.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Unsupported` if the receiver doesn't report the current position of the
+    /// media session.
     ///
     /// # Return value
     ///
     /// Returned `Result` should consist of either `Status` instance or an `Error`.
-    pub fn load_with_queue<S>(
+    pub fn seek_relative<S>(
         &self,
         destination: S,
-        session_id: S,
-        media: &Media,
-        queue: Option<&MediaQueue>,
-        options: LoadOptions,
-    ) -> Result<Status, Error>
+        media_session_id: i32,
+        delta: f32,
+    ) -> Result<StatusEntry, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        let request_id = self.message_manager.generate_request_id().get();
+        let destination = destination.into();
+
+        let status = self.get_status(destination.clone(), Some(media_session_id))?;
+        let entry = status
+            .entries
+            .iter()
+            .find(|entry| entry.media_session_id == media_session_id);
+        let current_time = entry.and_then(|entry| entry.current_time).ok_or_else(|| {
+            Error::Unsupported(format!(
+                "media session {media_session_id} doesn't report current position"
+            ))
+        })?;
+        let duration = entry
+            .and_then(|entry| entry.media.as_ref())
+            .and_then(|media| media.duration);
 
-        let payload = serde_json::to_string(&proxies::media::MediaRequest {
-            request_id,
-            session_id: session_id.into().to_string(),
-            typ: MESSAGE_TYPE_LOAD.to_string(),
+        let mut position = (current_time + delta).max(0.0);
+        if let Some(duration) = duration {
+            position = position.min(duration);
+        }
 
-            media: media.encode(),
+        self.seek(destination, media_session_id, Some(position), None)
+    }
 
-            current_time: options.current_time,
-            autoplay: options.autoplay,
-            custom_data: proxies::media::CustomData::new(),
-            queue_data: queue.map(|qd| qd.encode()),
-        })?;
+    /// Skips the ad break clip that is currently being played. Clip can only be skipped once
+    /// `BreakStatus::when_skippable` time has passed.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `protocol` of the media application (e.g. `web-1`);
+    /// * `media_session_id` - ID of the media session that plays the ad.
+    ///
+    /// # Return value
+    ///
+    /// Returned `Result` should consist of either `Status` instance or an `Error`.
+    pub fn skip_ad<S>(&self, destination: S, media_session_id: i32) -> Result<StatusEntry, Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.skip_ad",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
 
-        self.message_manager.send(CastMessage {
-            namespace: CHANNEL_NAMESPACE.to_string(),
-            source: self.sender.to_string(),
-            destination: destination.into().to_string(),
-            payload: CastMessagePayload::String(payload),
+        let payload = serde_json::to_string(&proxies::media::PlaybackGenericRequest {
+            request_id: request_id.get(),
+            media_session_id,
+            typ: MESSAGE_TYPE_SKIP_AD.to_string(),
+            custom_data: proxies::media::CustomData::new(),
         })?;
 
-        // Once media is loaded cast receiver device should emit status update event, or load failed
-        // event if something went wrong.
-        self.message_manager.receive_find_map(|message| {
-            if !self.can_handle(message) {
-                return Ok(None);
-            }
+        self.request(destination, payload, || {
+            self.receive_status_entry(request_id, media_session_id)
+        })
+    }
 
-            match self.parse(message)? {
-                MediaResponse::Status(status) => {
-                    if status.request_id == request_id {
-                        return Ok(Some(status));
-                    }
+    /// Moves playback of the queue `jump` items forward, or backward if `jump` is negative.
+    /// Triggers a STATUS event notification to all sender applications.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `protocol` of the media application (e.g. `web-1`);
+    /// * `media_session_id` - ID of the media session that plays the queue;
+    /// * `jump` - Number of items to skip relative to the current one.
+    ///
+    /// # Return value
+    ///
+    /// Returned `Result` should consist of either `Status` instance or an `Error`.
+    pub fn queue_jump<S>(
+        &self,
+        destination: S,
+        media_session_id: i32,
+        jump: i32,
+    ) -> Result<StatusEntry, Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.queue_jump",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
 
-                    // [WORKAROUND] In some cases we don't receive response (e.g. from YouTube app),
-                    // so let's just wait for the response with the media we're interested in and
-                    // return it.
-                    let has_media = {
-                        status.entries.iter().any(|entry| {
-                            if let Some(ref loaded_media) = entry.media {
-                                return loaded_media.content_id == media.content_id;
-                            }
+        let payload = serde_json::to_string(&proxies::media::QueueUpdateRequest {
+            request_id: request_id.get(),
+            media_session_id,
+            typ: MESSAGE_TYPE_QUEUE_UPDATE.to_string(),
+            jump,
+        })?;
 
-                            false
-                        })
-                    };
+        self.request(destination, payload, || {
+            self.receive_status_entry(request_id, media_session_id)
+        })
+    }
 
-                    if has_media {
-                        return Ok(Some(status));
-                    }
-                }
-                MediaResponse::LoadFailed(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal("Failed to load media.".to_string()));
-                    }
-                }
-                MediaResponse::LoadCancelled(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(
-                            "Load cancelled by another request.".to_string(),
-                        ));
-                    }
-                }
-                MediaResponse::InvalidPlayerState(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(
-                            "Load failed because of invalid player state.".to_string(),
-                        ));
-                    }
-                }
-                MediaResponse::InvalidRequest(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(format!(
-                            "Load failed because of invalid media request (reason: {}).",
-                            error.reason.unwrap_or_else(|| "UNKNOWN".to_string())
-                        )));
-                    }
-                }
-                _ => {}
-            }
+    /// Moves playback to the next item of the queue, see [`MediaChannel::queue_jump`].
+    pub fn queue_next<S>(&self, destination: S, media_session_id: i32) -> Result<StatusEntry, Error>
+    where
+        S: Into<String>,
+    {
+        self.queue_jump(destination, media_session_id, 1)
+    }
 
-            Ok(None)
-        })
+    /// Moves playback to the previous item of the queue, see [`MediaChannel::queue_jump`].
+    pub fn queue_prev<S>(&self, destination: S, media_session_id: i32) -> Result<StatusEntry, Error>
+    where
+        S: Into<String>,
+    {
+        self.queue_jump(destination, media_session_id, -1)
     }
 
-    pub fn load_queue<S>(
+    /// Retrieves ids of all the items of the queue played by the media session.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `protocol` of the media application (e.g. `web-1`);
+    /// * `media_session_id` - ID of the media session that plays the queue.
+    ///
+    /// # Return value
+    ///
+    /// Ids of the queue items in the order they are played.
+    pub fn queue_get_item_ids<S>(
         &self,
         destination: S,
-        _session_id: S,
-        queue: &MediaQueue,
-    ) -> Result<Status, Error>
+        media_session_id: i32,
+    ) -> Result<Vec<u16>, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        let request_id = self.message_manager.generate_request_id().get();
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.queue_get_item_ids",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
 
-        let payload = serde_json::to_string(&proxies::media::QueueLoadRequest {
-            typ: MESSAGE_TYPE_QUEUE_LOAD.to_string(),
-            request_id,
-            custom_data: None,
-            items: queue.items.iter().map(|qi| qi.encode()).collect(),
-            queue_type: Some(queue.queue_type.to_string()),
-            repeat_mode: "REPEAT_OFF".to_owned(),
-            start_index: queue.start_index,
+        let payload = serde_json::to_string(&proxies::media::QueueGetItemIdsRequest {
+            request_id: request_id.get(),
+            media_session_id,
+            typ: MESSAGE_TYPE_QUEUE_GET_ITEM_IDS.to_string(),
         })?;
 
-        self.message_manager.send(CastMessage {
-            namespace: CHANNEL_NAMESPACE.to_string(),
-            source: self.sender.to_string(),
-            destination: destination.into().to_string(),
-            payload: CastMessagePayload::String(payload),
-        })?;
+        self.send_queue_request(destination, payload)?;
+        self.receive_queue_item_ids(request_id)
+    }
 
-        // Once media is loaded cast receiver device should emit status update event, or load failed
-        // event if something went wrong.
-        self.message_manager.receive_find_map(|message| {
-            if !self.can_handle(message) {
-                return Ok(None);
-            }
+    /// Retrieves ids of the items around the specified queue item.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `protocol` of the media application (e.g. `web-1`);
+    /// * `media_session_id` - ID of the media session that plays the queue;
+    /// * `item_id` - ID of the queue item the range is centered at;
+    /// * `prev_count` - Number of the items before `item_id` to retrieve ids of;
+    /// * `next_count` - Number of the items after `item_id` to retrieve ids of.
+    ///
+    /// # Return value
+    ///
+    /// Ids of the queue items in the order they are played.
+    pub fn queue_get_item_range<S>(
+        &self,
+        destination: S,
+        media_session_id: i32,
+        item_id: u16,
+        prev_count: u16,
+        next_count: u16,
+    ) -> Result<Vec<u16>, Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.queue_get_item_range",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
 
-            match self.parse(message)? {
-                MediaResponse::Status(status) => {
-                    if status.request_id == request_id {
-                        return Ok(Some(status));
-                    }
-                }
-                MediaResponse::LoadFailed(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal("Failed to load media.".to_string()));
-                    }
-                }
-                MediaResponse::LoadCancelled(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(
-                            "Load cancelled by another request.".to_string(),
-                        ));
-                    }
-                }
-                MediaResponse::InvalidPlayerState(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(
-                            "Load failed because of invalid player state.".to_string(),
-                        ));
-                    }
-                }
-                MediaResponse::InvalidRequest(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(format!(
-                            "Load failed because of invalid media request (reason: {}).",
-                            error.reason.unwrap_or_else(|| "UNKNOWN".to_string())
-                        )));
-                    }
-                }
-                _ => {}
-            }
+        let payload = serde_json::to_string(&proxies::media::QueueGetItemRangeRequest {
+            request_id: request_id.get(),
+            media_session_id,
+            typ: MESSAGE_TYPE_QUEUE_GET_ITEM_RANGE.to_string(),
+            item_id,
+            next_count,
+            prev_count,
+        })?;
 
-            Ok(None)
-        })
+        self.send_queue_request(destination, payload)?;
+        self.receive_queue_item_ids(request_id)
     }
 
-    /// Pauses playback of the current content. Triggers a STATUS event notification to all sender
-    /// applications.
+    /// Retrieves the queue items with the specified ids, see `QueueView` for the lazily fetched
+    /// queue.
     ///
     /// # Arguments
     ///
     /// * `destination` - `protocol` of the media application (e.g. `web-1`);
-    /// * `media_session_id` - ID of the media session to be paused.
+    /// * `media_session_id` - ID of the media session that plays the queue;
+    /// * `item_ids` - IDs of the items to retrieve.
     ///
     /// # Return value
-    ///
-    /// Returned `Result` should consist of either `Status` instance or an `Error`.
-    pub fn pause<S>(&self, destination: S, media_session_id: i32) -> Result<StatusEntry, Error>
+    ///
+    /// Requested items, items that don't exist are omitted.
+    pub fn queue_get_items<S>(
+        &self,
+        destination: S,
+        media_session_id: i32,
+        item_ids: &[u16],
+    ) -> Result<Vec<QueueItemInfo>, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        let request_id = self.message_manager.generate_request_id().get();
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.queue_get_items",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
 
-        let payload = serde_json::to_string(&proxies::media::PlaybackGenericRequest {
-            request_id,
+        let payload = serde_json::to_string(&proxies::media::QueueGetItemsRequest {
+            request_id: request_id.get(),
             media_session_id,
-            typ: MESSAGE_TYPE_PAUSE.to_string(),
-            custom_data: proxies::media::CustomData::new(),
+            typ: MESSAGE_TYPE_QUEUE_GET_ITEMS.to_string(),
+            item_ids: item_ids.to_vec(),
         })?;
 
-        self.message_manager.send(CastMessage {
-            namespace: CHANNEL_NAMESPACE.to_string(),
-            source: self.sender.to_string(),
-            destination: destination.into().to_string(),
-            payload: CastMessagePayload::String(payload),
-        })?;
+        self.send_queue_request(destination, payload)?;
+
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
 
-        self.receive_status_entry(request_id, media_session_id)
+                match self.parse(message)? {
+                    MediaResponse::QueueItems(items) if items.request_id == request_id => {
+                        Ok(Some(items.items))
+                    }
+                    response => Self::queue_error(request_id, response),
+                }
+            })
     }
 
-    /// Begins playback of the content that was loaded with the load call, playback is continued
-    /// from the current time position.
+    /// Asks the receiver to preload the queue item ahead of its `QueueItem::preload_time`, so
+    /// that transition to it is gapless. Progress is reported with `preloaded_item_id` of the
+    /// status entry, see `QueueTracker`.
     ///
     /// # Arguments
     ///
     /// * `destination` - `protocol` of the media application (e.g. `web-1`);
-    /// * `media_session_id` - ID of the media session to be played.
+    /// * `media_session_id` - ID of the media session that plays the queue;
+    /// * `item_id` - ID of the queue item to preload.
     ///
     /// # Return value
     ///
     /// Returned `Result` should consist of either `Status` instance or an `Error`.
-    pub fn play<S>(&self, destination: S, media_session_id: i32) -> Result<StatusEntry, Error>
+    pub fn preload<S>(
+        &self,
+        destination: S,
+        media_session_id: i32,
+        item_id: u16,
+    ) -> Result<StatusEntry, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        let request_id = self.message_manager.generate_request_id().get();
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.preload",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
 
-        let payload = serde_json::to_string(&proxies::media::PlaybackGenericRequest {
-            request_id,
+        let payload = serde_json::to_string(&proxies::media::PreloadRequest {
+            request_id: request_id.get(),
             media_session_id,
-            typ: MESSAGE_TYPE_PLAY.to_string(),
-            custom_data: proxies::media::CustomData::new(),
-        })?;
-
-        self.message_manager.send(CastMessage {
-            namespace: CHANNEL_NAMESPACE.to_string(),
-            source: self.sender.to_string(),
-            destination: destination.into().to_string(),
-            payload: CastMessagePayload::String(payload),
+            typ: MESSAGE_TYPE_PRELOAD.to_string(),
+            item_id,
         })?;
 
-        self.receive_status_entry(request_id, media_session_id)
+        self.request(destination, payload, || {
+            self.receive_status_entry(request_id, media_session_id)
+        })
     }
 
-    /// Stops playback of the current content. Triggers a STATUS event notification to all sender
-    /// applications. After this command the content will no longer be loaded and the
-    /// media_session_id is invalidated.
+    /// Changes the active text, audio or video tracks and/or the text track style of the media
+    /// session. Triggers a STATUS event notification to all sender applications.
     ///
     /// # Arguments
     ///
     /// * `destination` - `protocol` of the media application (e.g. `web-1`);
-    /// * `media_session_id` - ID of the media session to be stopped.
+    /// * `media_session_id` - ID of the media session to edit tracks of;
+    /// * `active_track_ids` - IDs of the tracks that should be active, empty list disables all
+    ///   tracks, `None` leaves active tracks unchanged;
+    /// * `text_track_style` - Style of the text tracks, `None` leaves the style unchanged.
     ///
     /// # Return value
     ///
     /// Returned `Result` should consist of either `Status` instance or an `Error`.
-    pub fn stop<S>(&self, destination: S, media_session_id: i32) -> Result<StatusEntry, Error>
+    pub fn edit_tracks_info<S>(
+        &self,
+        destination: S,
+        media_session_id: i32,
+        active_track_ids: Option<&[u32]>,
+        text_track_style: Option<&TextTrackStyle>,
+    ) -> Result<StatusEntry, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        let request_id = self.message_manager.generate_request_id().get();
+        let destination = destination.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "media.edit_tracks_info",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %destination
+        );
 
-        let payload = serde_json::to_string(&proxies::media::PlaybackGenericRequest {
-            request_id,
+        let payload = serde_json::to_string(&proxies::media::EditTracksInfoRequest {
+            request_id: request_id.get(),
             media_session_id,
-            typ: MESSAGE_TYPE_STOP.to_string(),
+            typ: MESSAGE_TYPE_EDIT_TRACKS_INFO.to_string(),
+            active_track_ids: active_track_ids.map(|ids| ids.to_vec()),
+            text_track_style: text_track_style.map(|style| style.encode()),
             custom_data: proxies::media::CustomData::new(),
         })?;
 
-        self.message_manager.send(CastMessage {
-            namespace: CHANNEL_NAMESPACE.to_string(),
-            source: self.sender.to_string(),
-            destination: destination.into().to_string(),
-            payload: CastMessagePayload::String(payload),
-        })?;
-
-        self.receive_status_entry(request_id, media_session_id)
+        self.request(destination, payload, || {
+            self.receive_status_entry(request_id, media_session_id)
+        })
     }
 
-    /// Sets the current position in the stream. Triggers a STATUS event notification to all sender
-    /// applications. If the position provided is outside the range of valid positions for the
-    /// current content, then the player should pick a valid position as close to the requested
-    /// position as possible.
+    /// Seeks live stream to its live edge (the end of the live seekable range), so that playback
+    /// continues with the most recent content.
     ///
     /// # Arguments
     ///
     /// * `destination` - `protocol` of the media application (e.g. `web-1`);
-    /// * `media_session_id` - ID of the media session to seek in;
-    /// * `current_time` - Time in seconds to seek to.
+    /// * `media_session_id` - ID of the media session to seek in.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Unsupported` if media is not a live stream with known seekable range.
     ///
     /// # Return value
     ///
     /// Returned `Result` should consist of either `Status` instance or an `Error`.
-    pub fn seek<S>(
+    pub fn seek_to_live_edge<S>(
         &self,
         destination: S,
         media_session_id: i32,
-        current_time: Option<f32>,
-        resume_state: Option<ResumeState>,
     ) -> Result<StatusEntry, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        let request_id = self.message_manager.generate_request_id().get();
+        let destination = destination.into();
+
+        let live_edge = self
+            .get_status(destination.clone(), Some(media_session_id))?
+            .entries
+            .iter()
+            .find(|entry| entry.media_session_id == media_session_id)
+            .and_then(|entry| entry.live_seekable_range)
+            .and_then(|range| range.end)
+            .ok_or_else(|| {
+                Error::Unsupported(format!(
+                    "media session {media_session_id} doesn't have live seekable range"
+                ))
+            })?;
+
+        self.seek(destination, media_session_id, Some(live_edge), None)
+    }
 
-        let payload = serde_json::to_string(&proxies::media::PlaybackSeekRequest {
-            request_id,
-            media_session_id,
-            typ: MESSAGE_TYPE_SEEK.to_string(),
-            current_time,
-            resume_state: resume_state.map(|s| s.to_string()),
-            custom_data: proxies::media::CustomData::new(),
-        })?;
+    /// Registers `listener` that is called once for every media session that stops playing (its
+    /// player becomes `IDLE` with known reason). Completion is detected in every media status
+    /// parsed by the channel, e.g. while waiting for `CastDevice::receive` or for a response to a
+    /// request. Listener must not send or receive any messages or register other listeners.
+    ///
+    /// # Arguments
+    ///
+    /// * `listener` - Function that is called with the finished media session.
+    pub fn on_playback_finished<F>(&self, listener: F)
+    where
+        F: Fn(&PlaybackFinished) + Send + 'static,
+    {
+        self.watchdog
+            .borrow_mut()
+            .listeners
+            .push(Box::new(listener));
+    }
 
-        self.message_manager.send(CastMessage {
-            namespace: CHANNEL_NAMESPACE.to_string(),
-            source: self.sender.to_string(),
-            destination: destination.into().to_string(),
-            payload: CastMessagePayload::String(payload),
-        })?;
+    /// Returns how the media session has finished, if the channel has already seen it stopping.
+    ///
+    /// # Arguments
+    ///
+    /// * `media_session_id` - ID of the media session to check.
+    pub fn playback_finished(&self, media_session_id: i32) -> Option<PlaybackFinished> {
+        self.watchdog
+            .borrow_mut()
+            .finished
+            .get(&media_session_id)
+            .map(|reason| PlaybackFinished {
+                media_session_id,
+                reason: reason.clone(),
+            })
+    }
+
+    /// Records media sessions of the `status` that have stopped playing and notifies listeners.
+    fn watch_playback(&self, status: &Status) {
+        let mut watchdog = self.watchdog.borrow_mut();
 
-        self.receive_status_entry(request_id, media_session_id)
+        for entry in &status.entries {
+            let reason = match (&entry.player_state, &entry.idle_reason) {
+                (PlayerState::Idle, Some(reason)) => reason,
+                _ => continue,
+            };
+
+            if watchdog.finished.contains_key(&entry.media_session_id) {
+                continue;
+            }
+            watchdog
+                .finished
+                .insert(entry.media_session_id, reason.clone());
+
+            let finished = PlaybackFinished {
+                media_session_id: entry.media_session_id,
+                reason: reason.clone(),
+            };
+            for listener in &watchdog.listeners {
+                listener(&finished);
+            }
+        }
     }
 
     pub fn can_handle(&self, message: &CastMessage) -> bool {
@@ -1399,16 +4149,7 @@ where
     }
 
     pub fn parse(&self, message: &CastMessage) -> Result<MediaResponse, Error> {
-        let reply = match message.payload {
-            CastMessagePayload::String(ref payload) => {
-                serde_json::from_str::<serde_json::Value>(payload)?
-            }
-            _ => {
-                return Err(Error::Internal(
-                    "Binary payload is not supported!".to_string(),
-                ));
-            }
-        };
+        let reply = message.payload.to_json()?;
 
         let message_type = reply
             .as_object()
@@ -1423,28 +4164,50 @@ where
 
                 let entries = reply
                     .status
-                    .iter()
-                    .map(StatusEntry::try_from)
-                    .collect::<Result<_, _>>()?;
+                    .into_iter()
+                    .filter_map(|entry| {
+                        let entry = serde_json::value::from_value(entry)
+                            .map_err(Error::from)
+                            .and_then(|entry: proxies::media::Status| {
+                                StatusEntry::try_from(&entry)
+                            });
+
+                        match entry {
+                            Ok(entry) => Some(entry),
+                            Err(error) => {
+                                log::warn!(
+                                    "Skipping media status entry that can't be parsed: {error}"
+                                );
+                                None
+                            }
+                        }
+                    })
+                    .collect();
 
-                MediaResponse::Status(Status {
-                    request_id: reply.request_id,
+                let status = Status {
+                    request_id: reply.request_id.into(),
                     entries,
-                })
+                };
+                self.watch_playback(&status);
+
+                MediaResponse::Status(status)
             }
             MESSAGE_TYPE_LOAD_CANCELLED => {
                 let reply: proxies::media::LoadCancelledReply =
                     serde_json::value::from_value(reply)?;
 
                 MediaResponse::LoadCancelled(LoadCancelled {
-                    request_id: reply.request_id,
+                    request_id: reply.request_id.into(),
                 })
             }
             MESSAGE_TYPE_LOAD_FAILED => {
                 let reply: proxies::media::LoadFailedReply = serde_json::value::from_value(reply)?;
 
                 MediaResponse::LoadFailed(LoadFailed {
-                    request_id: reply.request_id,
+                    request_id: reply.request_id.into(),
+                    detailed_error_code: reply
+                        .detailed_error_code
+                        .and_then(|code| MediaDetailedErrorCode::try_from(code).ok()),
                 })
             }
             MESSAGE_TYPE_INVALID_PLAYER_STATE => {
@@ -1452,7 +4215,7 @@ where
                     serde_json::value::from_value(reply)?;
 
                 MediaResponse::InvalidPlayerState(InvalidPlayerState {
-                    request_id: reply.request_id,
+                    request_id: reply.request_id.into(),
                 })
             }
             MESSAGE_TYPE_INVALID_REQUEST => {
@@ -1460,18 +4223,44 @@ where
                     serde_json::value::from_value(reply)?;
 
                 MediaResponse::InvalidRequest(InvalidRequest {
-                    request_id: reply.request_id,
+                    request_id: reply.request_id.into(),
                     reason: reply.reason,
                 })
             }
             MESSAGE_TYPE_ERROR => {
                 let reply: proxies::media::MediaErrorReply = serde_json::value::from_value(reply)?;
-                let detailed_error_code =
-                    MediaDetailedErrorCode::try_from(reply.detailed_error_code)?;
+                // Error codes unknown to this library are reported as generic errors.
+                let detailed_error_code = reply
+                    .detailed_error_code
+                    .and_then(|code| MediaDetailedErrorCode::try_from(code).ok())
+                    .unwrap_or(MediaDetailedErrorCode::Generic);
 
                 MediaResponse::Error(MediaError {
+                    request_id: reply.request_id.map(RequestId::from),
                     detailed_error_code,
                     message_type: reply.message_type,
+                    reason: reply.reason,
+                })
+            }
+            MESSAGE_TYPE_QUEUE_ITEM_IDS => {
+                let reply: proxies::media::QueueItemIdsReply =
+                    serde_json::value::from_value(reply)?;
+
+                MediaResponse::QueueItemIds(QueueItemIds {
+                    request_id: reply.request_id.into(),
+                    item_ids: reply.item_ids,
+                })
+            }
+            MESSAGE_TYPE_QUEUE_ITEMS => {
+                let reply: proxies::media::QueueItemsReply = serde_json::value::from_value(reply)?;
+
+                MediaResponse::QueueItems(QueueItems {
+                    request_id: reply.request_id.into(),
+                    items: reply
+                        .items
+                        .into_iter()
+                        .map(QueueItemInfo::try_from)
+                        .collect::<Result<_, _>>()?,
                 })
             }
             _ => MediaResponse::NotImplemented(message_type.to_string(), reply),
@@ -1480,6 +4269,72 @@ where
         Ok(response)
     }
 
+    /// Sends request with the `payload` to the `destination` and waits for the response with
+    /// `receive`. Request is repeated according to the retry policy of the `MessageManager` while
+    /// receiver reports invalid player state (e.g. while the application is still starting).
+    fn request<F, R>(&self, destination: String, payload: String, receive: F) -> Result<R, Error>
+    where
+        F: Fn() -> Result<R, Error>,
+    {
+        self.message_manager.retry(
+            |error| matches!(error, Error::InvalidPlayerState { .. }),
+            || {
+                self.message_manager.send(CastMessage {
+                    namespace: CHANNEL_NAMESPACE.to_string(),
+                    source: self.sender.to_string(),
+                    destination: destination.to_string(),
+                    payload: CastMessagePayload::String(payload.clone()),
+                })?;
+
+                receive()
+            },
+        )
+    }
+
+    /// Sends queue request with the `payload` to the `destination`.
+    fn send_queue_request(&self, destination: String, payload: String) -> Result<(), Error> {
+        self.message_manager.send(CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: self.sender.to_string(),
+            destination: destination.to_string(),
+            payload: CastMessagePayload::String(payload),
+        })
+    }
+
+    /// Waits for the queue item ids sent in response to the request with `request_id`.
+    fn receive_queue_item_ids(&self, request_id: RequestId) -> Result<Vec<u16>, Error> {
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
+
+                match self.parse(message)? {
+                    MediaResponse::QueueItemIds(ids) if ids.request_id == request_id => {
+                        Ok(Some(ids.item_ids))
+                    }
+                    response => Self::queue_error(request_id, response),
+                }
+            })
+    }
+
+    /// Converts error `response` to the request with `request_id` into `Error`, other responses
+    /// are skipped.
+    fn queue_error<T>(request_id: RequestId, response: MediaResponse) -> Result<Option<T>, Error> {
+        match response {
+            MediaResponse::InvalidPlayerState(error) if error.request_id == request_id => {
+                Err(error.into())
+            }
+            MediaResponse::InvalidRequest(error) if error.request_id == request_id => {
+                Err(error.into())
+            }
+            MediaResponse::Error(error) if error.request_id == Some(request_id) => {
+                Err(error.into())
+            }
+            _ => Ok(None),
+        }
+    }
+
     /// Waits for the status entry with specified `request_id` and `media_session_id`. This method
     /// is very handy for the media playback methods where particular `StatusEntry` is required.
     ///
@@ -1493,17 +4348,17 @@ where
     /// Returned `Result` should consist of either `Status` instance or an `Error`.
     fn receive_status_entry(
         &self,
-        request_id: u32,
+        request_id: RequestId,
         media_session_id: i32,
     ) -> Result<StatusEntry, Error> {
-        self.message_manager.receive_find_map(|message| {
-            if !self.can_handle(message) {
-                return Ok(None);
-            }
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
 
-            match self.parse(message)? {
-                MediaResponse::Status(mut status) => {
-                    if status.request_id == request_id {
+                match self.parse(message)? {
+                    MediaResponse::Status(mut status) if status.request_id == request_id => {
                         let position = status
                             .entries
                             .iter()
@@ -1511,85 +4366,503 @@ where
 
                         return Ok(position.map(|position| status.entries.remove(position)));
                     }
-                }
-                MediaResponse::InvalidPlayerState(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(
-                            "Request failed because of invalid player state.".to_string(),
-                        ));
+                    MediaResponse::InvalidPlayerState(error) if error.request_id == request_id => {
+                        return Err(error.into());
                     }
-                }
-                MediaResponse::InvalidRequest(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(format!(
-                            "Invalid request ({}).",
-                            error.reason.unwrap_or_else(|| "Unknown".to_string())
-                        )));
+                    MediaResponse::InvalidRequest(error) if error.request_id == request_id => {
+                        return Err(error.into());
                     }
+                    MediaResponse::Error(error) if error.request_id == Some(request_id) => {
+                        return Err(error.into());
+                    }
+                    _ => {}
                 }
-                _ => {}
-            }
 
-            Ok(None)
-        })
+                Ok(None)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cast::cast_channel::cast_message::{PayloadType, ProtocolVersion},
+        testing::MockTcpStream,
+        DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID,
+    };
+    use protobuf::EnumOrUnknown;
+
+    use super::*;
+
+    #[test]
+    fn test_get_status() {
+        let mut stream = MockTcpStream::new();
+        let payload = format!(
+            r#"{{
+            "requestId":1,
+            "type":"{}",
+            "status":[
+                {{
+                    "mediaSessionId":1,
+                    "playerState":"PLAYING",
+                    "playbackRate":1.0,
+                    "supportedMediaCommands":2300
+                }}
+            ]
+        }}"#,
+            MESSAGE_TYPE_MEDIA_STATUS
+        );
+        stream.add_message(crate::cast::cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(payload),
+            payload_binary: None,
+            continued: None,
+            remaining_length: None,
+            special_fields: Default::default(),
+        });
+        let channel = MediaChannel::new(DEFAULT_SENDER_ID, Lrc::new(MessageManager::new(stream)));
+
+        let result = channel.get_status("MyAppTransportId", None).unwrap();
+
+        assert_eq!(RequestId::new(1), result.request_id);
+        if let Some(entry) = result.entries.first() {
+            assert_eq!(1, entry.media_session_id);
+            assert_eq!(PlayerState::Playing, entry.player_state);
+            assert_eq!(1.0, entry.playback_rate);
+            assert_eq!(2300, entry.supported_media_commands.bits());
+            assert!(entry
+                .supported_media_commands
+                .contains(MediaCommands::QUEUE_NEXT | MediaCommands::QUEUE_PREV));
+            assert!(!entry
+                .supported_media_commands
+                .contains(MediaCommands::PAUSE));
+        }
+    }
+
+    #[test]
+    fn test_load_failed() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(crate::cast::cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some("MyAppTransportId".to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(format!(
+                r#"{{"requestId":1,"type":"{}","detailedErrorCode":104}}"#,
+                MESSAGE_TYPE_LOAD_FAILED
+            )),
+            ..Default::default()
+        });
+        let channel = MediaChannel::new(DEFAULT_SENDER_ID, Lrc::new(MessageManager::new(stream)));
+        let media = Media {
+            content_id: "https://example.com/video.mp4".to_string(),
+            content_url: None,
+            entity: None,
+            atv_entity: None,
+            stream_type: StreamType::Buffered,
+            content_type: "video/mp4".to_string(),
+            metadata: None,
+            duration: None,
+            hls_segment_format: None,
+            hls_video_segment_format: None,
+            text_track_style: None,
+            breaks: vec![],
+            break_clips: vec![],
+            tracks: vec![],
+            custom_data: None,
+        };
+
+        let result = channel.load("MyAppTransportId", "MySessionId", &media);
+
+        assert!(matches!(
+            result,
+            Err(Error::LoadFailed {
+                request_id,
+                detailed_error_code: Some(MediaDetailedErrorCode::MediaSrcNotSupported),
+            }) if request_id == RequestId::new(1)
+        ));
+    }
+
+    #[test]
+    fn test_load_encodes_optional_media_fields() {
+        let stream = MockTcpStream::new();
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+        let media = Media {
+            content_id: "video-id".to_string(),
+            content_url: Some("https://example.com/video.mp4".to_string()),
+            entity: Some("https://example.com/watch?v=video-id".to_string()),
+            atv_entity: None,
+            stream_type: StreamType::Buffered,
+            content_type: "video/mp4".to_string(),
+            metadata: None,
+            duration: None,
+            hls_segment_format: Some(HlsSegmentFormat::TsAac),
+            hls_video_segment_format: Some(HlsVideoSegmentFormat::Fmp4),
+            text_track_style: Some(TextTrackStyle {
+                font_scale: Some(1.5),
+                edge_type: Some(TextTrackEdgeType::DropShadow),
+                foreground_color: Some("#FFFFFFFF".to_string()),
+                ..Default::default()
+            }),
+            breaks: vec![],
+            break_clips: vec![],
+            tracks: vec![],
+            custom_data: None,
+        };
+
+        // There is no response in the stream, we're only interested in the sent request.
+        let _ = channel.load("MyAppTransportId", "MySessionId", &media);
+
+        let request = stream
+            .received_message(0)
+            .expect("expected a message to have been sent")
+            .message();
+        let request = serde_json::from_str::<serde_json::Value>(request.payload_utf8()).unwrap();
+        assert_eq!("video-id", request["media"]["contentId"]);
+        assert_eq!(
+            "https://example.com/video.mp4",
+            request["media"]["contentUrl"]
+        );
+        assert_eq!(
+            "https://example.com/watch?v=video-id",
+            request["media"]["entity"]
+        );
+        assert!(request["media"].get("atvEntity").is_none());
+        assert_eq!("ts_aac", request["media"]["hlsSegmentFormat"]);
+        assert_eq!("fmp4", request["media"]["hlsVideoSegmentFormat"]);
+        assert_eq!(
+            serde_json::json!({
+                "edgeType": "DROP_SHADOW",
+                "fontScale": 1.5,
+                "foregroundColor": "#FFFFFFFF"
+            }),
+            request["media"]["textTrackStyle"]
+        );
+    }
+
+    #[test]
+    fn test_load_encodes_built_media() {
+        let stream = MockTcpStream::new();
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+        let media = Media::builder("https://example.com/song.mp3", "audio/mpeg")
+            .duration(215.0)
+            .metadata(
+                MusicTrackMediaMetadata::builder()
+                    .title("Song")
+                    .artist("Artist")
+                    .track_number(3)
+                    .image(Image::new("https://example.com/cover.jpg".to_string()))
+                    .build(),
+            )
+            .track(Track {
+                track_content_id: Some("https://example.com/lyrics.vtt".to_string()),
+                track_content_type: Some("text/vtt".to_string()),
+                subtype: Some(TextTrackType::Subtitles),
+                language: Some("en-US".to_string()),
+                ..Track::new(1, TrackType::Text)
+            })
+            .custom_data(serde_json::json!({"playlist": "favourites"}))
+            .build();
+        let options = LoadOptions {
+            active_track_ids: Some(vec![1]),
+            ..Default::default()
+        };
+
+        // There is no response in the stream, we're only interested in the sent request.
+        let _ = channel.load_with_opts("MyAppTransportId", "MySessionId", &media, options);
+
+        let request = stream
+            .received_message(0)
+            .expect("expected a message to have been sent")
+            .message();
+        let request = serde_json::from_str::<serde_json::Value>(request.payload_utf8()).unwrap();
+        assert_eq!("BUFFERED", request["media"]["streamType"]);
+        assert_eq!(215.0, request["media"]["duration"]);
+        assert_eq!(
+            serde_json::json!({
+                "metadataType": 3,
+                "title": "Song",
+                "artist": "Artist",
+                "trackNumber": 3,
+                "images": [{"url": "https://example.com/cover.jpg"}]
+            }),
+            request["media"]["metadata"]
+        );
+        assert_eq!(
+            serde_json::json!([{
+                "trackId": 1,
+                "type": "TEXT",
+                "trackContentId": "https://example.com/lyrics.vtt",
+                "trackContentType": "text/vtt",
+                "subtype": "SUBTITLES",
+                "language": "en-US"
+            }]),
+            request["media"]["tracks"]
+        );
+        assert_eq!(
+            serde_json::json!({"playlist": "favourites"}),
+            request["media"]["customData"]
+        );
+        assert_eq!(serde_json::json!([1]), request["activeTrackIds"]);
+    }
+
+    #[test]
+    fn test_load_encodes_drm_config() {
+        let stream = MockTcpStream::new();
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+        let media = Media {
+            content_id: "https://example.com/video.mpd".to_string(),
+            content_url: None,
+            entity: None,
+            atv_entity: None,
+            stream_type: StreamType::Buffered,
+            content_type: "application/dash+xml".to_string(),
+            metadata: None,
+            duration: None,
+            hls_segment_format: None,
+            hls_video_segment_format: None,
+            text_track_style: None,
+            breaks: vec![],
+            break_clips: vec![],
+            tracks: vec![],
+            custom_data: None,
+        };
+        let mut drm = DrmConfig::widevine("https://example.com/license");
+        drm.license_request_headers
+            .insert("Authorization".to_string(), "Bearer token".to_string());
+        let options = LoadOptions {
+            drm: Some(drm),
+            ..Default::default()
+        };
+
+        // There is no response in the stream, we're only interested in the sent request.
+        let _ = channel.load_with_opts("MyAppTransportId", "MySessionId", &media, options);
+
+        let request = stream
+            .received_message(0)
+            .expect("expected a message to have been sent")
+            .message();
+        let request = serde_json::from_str::<serde_json::Value>(request.payload_utf8()).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "licenseUrl": "https://example.com/license",
+                "protectionSystem": "widevine",
+                "licenseRequestHeaders": {"Authorization": "Bearer token"}
+            }),
+            request["customData"]
+        );
+    }
+
+    #[test]
+    fn test_load_passes_credentials() {
+        let stream = MockTcpStream::new();
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+        let media = Media {
+            content_id: "spotify:track:1".to_string(),
+            content_url: None,
+            entity: None,
+            atv_entity: None,
+            stream_type: StreamType::Buffered,
+            content_type: "application/x-spotify.track".to_string(),
+            metadata: None,
+            duration: None,
+            hls_segment_format: None,
+            hls_video_segment_format: None,
+            text_track_style: None,
+            breaks: vec![],
+            break_clips: vec![],
+            tracks: vec![],
+            custom_data: None,
+        };
+        let options = LoadOptions {
+            credentials: Some(Credentials::new("access-token").with_type("cloud")),
+            ..Default::default()
+        };
+
+        // There is no response in the stream, we're only interested in the sent request.
+        let _ = channel.load_with_opts("MyAppTransportId", "MySessionId", &media, options);
+
+        let request = stream.received_message(0).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!("access-token", request["credentials"]);
+        assert_eq!("cloud", request["credentialsType"]);
+    }
+
+    #[test]
+    fn test_load_appends_artwork_images() {
+        let stream = MockTcpStream::new();
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+        let media = Media::builder("https://example.com/song.mp3", "audio/mpeg").build();
+        let options = LoadOptions {
+            images: vec![Image::with_dimensions(
+                "https://example.com/cover.jpg",
+                512,
+                512,
+            )],
+            ..Default::default()
+        };
+
+        // There is no response in the stream, we're only interested in the sent request.
+        let _ = channel.load_with_opts("MyAppTransportId", "MySessionId", &media, options);
+
+        let request = stream.received_message(0).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "metadataType": 0,
+                "images": [{"url": "https://example.com/cover.jpg", "width": 512, "height": 512}]
+            }),
+            request["media"]["metadata"]
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{
-        cast::cast_channel::cast_message::{PayloadType, ProtocolVersion},
-        tests::MockTcpStream,
-        DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID,
-    };
-    use protobuf::EnumOrUnknown;
+    #[test]
+    fn test_image_validation() {
+        assert!(Image::with_dimensions("https://example.com/a.jpg", 64, 64)
+            .validate()
+            .is_ok());
+        assert!(matches!(
+            Image::new(" ".to_string()).validate(),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            Image::with_dimensions("https://example.com/a.jpg", 0, 64).validate(),
+            Err(Error::Validation(_))
+        ));
+
+        assert!(Image::validate_all(&[
+            Image::new("https://example.com/a.jpg".to_string()),
+            Image::with_dimensions("https://example.com/b.jpg", 64, 64),
+        ])
+        .is_ok());
+        assert!(matches!(
+            Image::validate_all(&[
+                Image::new("https://example.com/a.jpg".to_string()),
+                Image::new("https://example.com/b.jpg".to_string()),
+            ]),
+            Err(Error::Validation(_))
+        ));
 
-    use super::*;
+        let stream = MockTcpStream::new();
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+        let media = Media::builder("https://example.com/song.mp3", "audio/mpeg").build();
+        let options = LoadOptions {
+            images: vec![Image::with_dimensions("", 64, 64)],
+            ..Default::default()
+        };
+        assert!(matches!(
+            channel.load_with_opts("MyAppTransportId", "MySessionId", &media, options),
+            Err(Error::Validation(_))
+        ));
+        assert!(stream.received_message(0).is_none());
+    }
 
     #[test]
-    fn test_get_status() {
-        let mut stream = MockTcpStream::new();
-        let payload = format!(
-            r#"{{
-            "requestId":1,
-            "type":"{}",
-            "status":[
-                {{
-                    "mediaSessionId":1,
-                    "playerState":"PLAYING",
-                    "playbackRate":1.0,
-                    "supportedMediaCommands":2300
-                }}
-            ]
-        }}"#,
-            MESSAGE_TYPE_MEDIA_STATUS
+    fn test_status_serde_round_trip() {
+        let message = CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(format!(
+                r#"{{"requestId":4,"type":"{}","status":[{{"mediaSessionId":1,
+                    "playerState":"PAUSED","playbackRate":1.0,"supportedMediaCommands":3,
+                    "currentTime":12.5,"media":{{"contentId":"a","streamType":"BUFFERED",
+                    "contentType":"video/mp4","metadata":{{"metadataType":0,"title":"A",
+                    "images":[]}}}}}}]}}"#,
+                MESSAGE_TYPE_MEDIA_STATUS
+            )),
+        };
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
         );
-        stream.add_message(crate::cast::cast_channel::CastMessage {
-            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
-            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
-            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
-            namespace: Some(CHANNEL_NAMESPACE.to_string()),
-            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
-            payload_utf8: Some(payload),
-            payload_binary: None,
-            continued: None,
-            remaining_length: None,
-            special_fields: Default::default(),
-        });
-        let channel = MediaChannel {
-            sender: Cow::from(DEFAULT_SENDER_ID),
-            message_manager: Lrc::new(MessageManager::new(stream)),
+        let MediaResponse::Status(status) = channel.parse(&message).unwrap() else {
+            panic!("expected status response");
         };
 
-        let result = channel.get_status("MyAppTransportId", None).unwrap();
+        assert_eq!(1, status.entries.len());
+        let snapshot = serde_json::to_string(&status).unwrap();
 
-        assert_eq!(1, result.request_id);
-        if let Some(entry) = result.entries.first() {
-            assert_eq!(1, entry.media_session_id);
-            assert_eq!(PlayerState::Playing, entry.player_state);
-            assert_eq!(1.0, entry.playback_rate);
-            assert_eq!(2300, entry.supported_media_commands);
-        }
+        assert_eq!(status, serde_json::from_str::<Status>(&snapshot).unwrap());
+        let snapshot: serde_json::Value = serde_json::from_str(&snapshot).unwrap();
+        assert_eq!(4, snapshot["request_id"]);
+        assert_eq!(3, snapshot["entries"][0]["supported_media_commands"]);
+    }
+
+    #[test]
+    fn test_parse_live_stream_status() {
+        let message = CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(format!(
+                r#"{{
+                "requestId":0,
+                "type":"{}",
+                "status":[
+                    {{
+                        "mediaSessionId":1,
+                        "playerState":"PLAYING",
+                        "playbackRate":1.0,
+                        "supportedMediaCommands":2,
+                        "liveSeekableRange":{{"start":10.0,"end":130.5,"isMovingWindow":true}},
+                        "videoInfo":{{"width":3840,"height":2160,"hdrType":"dv"}}
+                    }}
+                ]
+            }}"#,
+                MESSAGE_TYPE_MEDIA_STATUS
+            )),
+        };
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
+
+        let response = channel.parse(&message).unwrap();
+
+        let MediaResponse::Status(status) = response else {
+            panic!("expected status response, got {:?}", response);
+        };
+        assert_eq!(
+            Some(LiveSeekableRange {
+                start: Some(10.0),
+                end: Some(130.5),
+                is_moving_window: true,
+                is_live_done: false,
+            }),
+            status.entries[0].live_seekable_range
+        );
+        assert_eq!(
+            Some(VideoInfo {
+                width: 3840,
+                height: 2160,
+                hdr_type: HdrType::DolbyVision,
+            }),
+            status.entries[0].video_info
+        );
     }
 
     #[test]
@@ -1602,13 +4875,15 @@ mod tests {
                 "{\"type\":\"ERROR\",\"detailedErrorCode\":104,\"itemId\":1}".to_string(),
             ),
         };
-        let channel = MediaChannel {
-            sender: Cow::from(DEFAULT_SENDER_ID),
-            message_manager: Lrc::new(MessageManager::new(MockTcpStream::new())),
-        };
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
         let expected_result = MediaError {
+            request_id: None,
             detailed_error_code: MediaDetailedErrorCode::MediaSrcNotSupported,
             message_type: MESSAGE_TYPE_ERROR.to_string(),
+            reason: None,
         };
 
         let response = channel.parse(&message).unwrap();
@@ -1616,6 +4891,140 @@ mod tests {
         assert_eq!(MediaResponse::Error(expected_result), response);
     }
 
+    #[test]
+    fn test_parse_partially_valid_status() {
+        let message = CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(format!(
+                r#"{{
+                "requestId":3,
+                "type":"{}",
+                "status":[
+                    {{"playerState":"PLAYING"}},
+                    {{"mediaSessionId":2,"playerState":"PAUSED"}}
+                ]
+            }}"#,
+                MESSAGE_TYPE_MEDIA_STATUS
+            )),
+        };
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
+
+        let status = match channel.parse(&message).unwrap() {
+            MediaResponse::Status(status) => status,
+            response => panic!("unexpected response {:?}", response),
+        };
+
+        assert_eq!(RequestId::new(3), status.request_id);
+        assert_eq!(1, status.entries.len());
+        assert_eq!(2, status.entries[0].media_session_id);
+        assert_eq!(PlayerState::Paused, status.entries[0].player_state);
+        assert_eq!(1.0, status.entries[0].playback_rate);
+        assert!(status.entries[0].supported_media_commands.is_empty());
+    }
+
+    #[test]
+    fn test_parse_audio_device_status() {
+        let message = CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(format!(
+                r#"{{
+                "requestId":0,
+                "type":"{}",
+                "status":[{{
+                    "mediaSessionId":1,
+                    "playerState":"PLAYING",
+                    "media":{{"metadata":{{"title":"Song","artist":"Band"}}}}
+                }}]
+            }}"#,
+                MESSAGE_TYPE_MEDIA_STATUS
+            )),
+        };
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
+
+        let status = match channel.parse(&message).unwrap() {
+            MediaResponse::Status(status) => status,
+            response => panic!("unexpected response {:?}", response),
+        };
+
+        let media = status.entries[0].media.as_ref().unwrap();
+        assert_eq!("", media.content_id);
+        assert_eq!(
+            Some(Metadata::Generic(GenericMediaMetadata {
+                title: Some("Song".to_string()),
+                subtitle: None,
+                images: vec![],
+                release_date: None,
+            })),
+            media.metadata
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_player_state_and_idle_reason() {
+        let message = CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(format!(
+                r#"{{
+                "requestId":0,
+                "type":"{}",
+                "status":[
+                    {{
+                        "mediaSessionId":1,
+                        "playerState":"IDLE",
+                        "idleReason":"SOMETHING_NEW",
+                        "extendedStatus":{{"playerState":"PRELOADING"}},
+                        "playbackRate":1.0,
+                        "supportedMediaCommands":15
+                    }},
+                    {{
+                        "mediaSessionId":2,
+                        "playerState":"LOADING",
+                        "playbackRate":1.0,
+                        "supportedMediaCommands":15
+                    }}
+                ]
+            }}"#,
+                MESSAGE_TYPE_MEDIA_STATUS
+            )),
+        };
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
+
+        let status = match channel.parse(&message).unwrap() {
+            MediaResponse::Status(status) => status,
+            response => panic!("unexpected response {:?}", response),
+        };
+
+        let entry = &status.entries[0];
+        assert_eq!(PlayerState::Idle, entry.player_state);
+        assert_eq!(
+            Some(IdleReason::Unknown("SOMETHING_NEW".to_string())),
+            entry.idle_reason
+        );
+        assert_eq!(
+            Some(ExtendedPlayerState::Unknown("PRELOADING".to_string())),
+            entry
+                .extended_status
+                .as_ref()
+                .map(|es| es.player_state.clone())
+        );
+        assert_eq!(PlayerState::Loading, status.entries[1].player_state);
+    }
+
     #[test]
     fn test_parse_unknown_message_type() {
         let message_type = "FOO_BAR";
@@ -1628,10 +5037,7 @@ mod tests {
             payload: CastMessagePayload::String(payload),
         };
         let stream = MockTcpStream::new();
-        let channel = MediaChannel {
-            sender: Cow::from(DEFAULT_SENDER_ID),
-            message_manager: Lrc::new(MessageManager::new(stream)),
-        };
+        let channel = MediaChannel::new(DEFAULT_SENDER_ID, Lrc::new(MessageManager::new(stream)));
         let expected_result =
             MediaResponse::NotImplemented(message_type.to_string(), expected_payload);
 
@@ -1639,4 +5045,516 @@ mod tests {
 
         assert_eq!(expected_result, result);
     }
+
+    #[test]
+    fn test_preload() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(crate::cast::cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some("MyAppTransportId".to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(format!(
+                r#"{{"requestId":1,"type":"{}","status":[{{"mediaSessionId":7,
+                    "playerState":"PLAYING","currentItemId":1,"preloadedItemId":2}}]}}"#,
+                MESSAGE_TYPE_MEDIA_STATUS
+            )),
+            ..Default::default()
+        });
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let entry = channel.preload("MyAppTransportId", 7, 2).unwrap();
+
+        assert_eq!(Some(2), entry.preloaded_item_id);
+        let request = stream.received_message(0).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!(MESSAGE_TYPE_PRELOAD, request["type"]);
+        assert_eq!(2, request["itemId"]);
+        assert_eq!(7, request["mediaSessionId"]);
+    }
+
+    #[test]
+    fn test_queue_prev() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(crate::cast::cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some("MyAppTransportId".to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(format!(
+                r#"{{"requestId":1,"type":"{}","status":[{{"mediaSessionId":7,
+                    "playerState":"BUFFERING","currentItemId":3,"activeTrackIds":[2]}}]}}"#,
+                MESSAGE_TYPE_MEDIA_STATUS
+            )),
+            ..Default::default()
+        });
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let entry = channel.queue_prev("MyAppTransportId", 7).unwrap();
+
+        assert_eq!(Some(3), entry.current_item_id);
+        assert_eq!(vec![2], entry.active_track_ids);
+        let request = stream.received_message(0).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!(MESSAGE_TYPE_QUEUE_UPDATE, request["type"]);
+        assert_eq!(-1, request["jump"]);
+        assert_eq!(7, request["mediaSessionId"]);
+    }
+
+    #[test]
+    fn test_seek_relative_clamps_to_duration() {
+        let mut stream = MockTcpStream::new();
+        for (request_id, current_time) in [(1, 100.0), (2, 120.0)] {
+            stream.add_message(crate::cast::cast_channel::CastMessage {
+                protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+                source_id: Some("MyAppTransportId".to_string()),
+                destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+                namespace: Some(CHANNEL_NAMESPACE.to_string()),
+                payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+                payload_utf8: Some(format!(
+                    r#"{{"requestId":{},"type":"{}","status":[{{"mediaSessionId":7,
+                        "playerState":"PLAYING","currentTime":{},"media":{{"contentId":"a",
+                        "streamType":"BUFFERED","contentType":"video/mp4","duration":120.0}}}}]}}"#,
+                    request_id, MESSAGE_TYPE_MEDIA_STATUS, current_time
+                )),
+                ..Default::default()
+            });
+        }
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let entry = channel.seek_relative("MyAppTransportId", 7, 30.0).unwrap();
+
+        assert_eq!(Some(120.0), entry.current_time);
+        let request = stream.received_message(1).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!(MESSAGE_TYPE_SEEK, request["type"]);
+        assert_eq!(120.0, request["currentTime"]);
+        assert_eq!(7, request["mediaSessionId"]);
+    }
+
+    #[test]
+    fn test_estimated_current_time() {
+        let message = CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(format!(
+                r#"{{"requestId":0,"type":"{}","status":[{{"mediaSessionId":1,
+                    "playerState":"PLAYING","playbackRate":2.0,"currentTime":10.0,
+                    "media":{{"contentId":"a","streamType":"BUFFERED",
+                    "contentType":"video/mp4","duration":30.0}}}}]}}"#,
+                MESSAGE_TYPE_MEDIA_STATUS
+            )),
+        };
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
+        let MediaResponse::Status(status) = channel.parse(&message).unwrap() else {
+            panic!("expected status response");
+        };
+        let mut entry = status.entries[0].clone();
+        entry.received_at = Instant::now() - std::time::Duration::from_secs(5);
+
+        let estimated = entry.estimated_current_time().unwrap();
+        assert!((20.0..21.0).contains(&estimated), "{estimated}");
+
+        entry.received_at = Instant::now() - std::time::Duration::from_secs(60);
+        assert_eq!(Some(30.0), entry.estimated_current_time());
+
+        entry.player_state = PlayerState::Paused;
+        assert_eq!(Some(10.0), entry.estimated_current_time());
+
+        // Receive timestamp doesn't affect equality.
+        entry.player_state = PlayerState::Playing;
+        assert_eq!(status.entries[0], entry);
+    }
+
+    #[test]
+    fn test_playback_finished_reported_once() {
+        let mut stream = MockTcpStream::new();
+        for player_state in ["PLAYING", "IDLE", "IDLE"] {
+            stream.add_message(crate::cast::cast_channel::CastMessage {
+                protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+                source_id: Some("MyAppTransportId".to_string()),
+                destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+                namespace: Some(CHANNEL_NAMESPACE.to_string()),
+                payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+                payload_utf8: Some(format!(
+                    r#"{{"requestId":0,"type":"{}","status":[{{"mediaSessionId":7,
+                        "playerState":"{}","idleReason":"FINISHED"}}]}}"#,
+                    MESSAGE_TYPE_MEDIA_STATUS, player_state
+                )),
+                ..Default::default()
+            });
+        }
+        let message_manager = Lrc::new(MessageManager::new(stream));
+        let channel = MediaChannel::new(DEFAULT_SENDER_ID, Lrc::clone(&message_manager));
+        let reported = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let listener_reported = std::sync::Arc::clone(&reported);
+        channel.on_playback_finished(move |finished| {
+            listener_reported.lock().unwrap().push(finished.clone());
+        });
+
+        assert_eq!(None, channel.playback_finished(7));
+        for _ in 0..3 {
+            channel.parse(&message_manager.receive().unwrap()).unwrap();
+        }
+
+        let finished = PlaybackFinished {
+            media_session_id: 7,
+            reason: IdleReason::Finished,
+        };
+        assert_eq!(vec![finished.clone()], *reported.lock().unwrap());
+        assert_eq!(Some(finished), channel.playback_finished(7));
+    }
+
+    #[test]
+    fn test_cloned_channel_shares_state() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(crate::cast::cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some("MyAppTransportId".to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(format!(
+                r#"{{"requestId":0,"type":"{}","status":[{{"mediaSessionId":7,
+                    "playerState":"IDLE","idleReason":"FINISHED"}}]}}"#,
+                MESSAGE_TYPE_MEDIA_STATUS
+            )),
+            ..Default::default()
+        });
+        let message_manager = Lrc::new(MessageManager::new(stream));
+        let channel = MediaChannel::new(DEFAULT_SENDER_ID, Lrc::clone(&message_manager));
+        let handle = channel.clone();
+
+        handle.parse(&message_manager.receive().unwrap()).unwrap();
+        assert_eq!(
+            Some(IdleReason::Finished),
+            channel.playback_finished(7).map(|finished| finished.reason)
+        );
+    }
+
+    #[test]
+    fn test_retry_invalid_player_state() {
+        let mut stream = MockTcpStream::new();
+        for payload in [
+            r#"{"requestId":1,"type":"INVALID_PLAYER_STATE"}"#.to_string(),
+            format!(
+                r#"{{"requestId":1,"type":"{}","status":[{{"mediaSessionId":7,
+                    "playerState":"PLAYING"}}]}}"#,
+                MESSAGE_TYPE_MEDIA_STATUS
+            ),
+        ] {
+            stream.add_message(crate::cast::cast_channel::CastMessage {
+                protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+                source_id: Some("MyAppTransportId".to_string()),
+                destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+                namespace: Some(CHANNEL_NAMESPACE.to_string()),
+                payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+                payload_utf8: Some(payload),
+                ..Default::default()
+            });
+        }
+        let message_manager = Lrc::new(MessageManager::new(stream.clone()));
+        message_manager.set_retry_policy(crate::message_manager::RetryPolicy {
+            initial_backoff: std::time::Duration::ZERO,
+            ..Default::default()
+        });
+        let channel = MediaChannel::new(DEFAULT_SENDER_ID, message_manager);
+
+        let entry = channel.play("MyAppTransportId", 7).unwrap();
+
+        assert_eq!(PlayerState::Playing, entry.player_state);
+        for index in 0..2 {
+            let request = stream.received_message(index).unwrap().cast_message();
+            let request = request.payload.to_json().unwrap();
+            assert_eq!(MESSAGE_TYPE_PLAY, request["type"]);
+            assert_eq!(1, request["requestId"]);
+        }
+        assert!(stream.received_message(2).is_none());
+    }
+
+    #[test]
+    fn test_queue_get_item_range() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(crate::cast::cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some("MyAppTransportId".to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(format!(
+                r#"{{"requestId":1,"type":"{}","itemIds":[3,4,5]}}"#,
+                MESSAGE_TYPE_QUEUE_ITEM_IDS
+            )),
+            ..Default::default()
+        });
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let item_ids = channel
+            .queue_get_item_range("MyAppTransportId", 7, 4, 1, 1)
+            .unwrap();
+
+        assert_eq!(vec![3, 4, 5], item_ids);
+        let request = stream.received_message(0).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!(MESSAGE_TYPE_QUEUE_GET_ITEM_RANGE, request["type"]);
+        assert_eq!(4, request["itemId"]);
+        assert_eq!(1, request["prevCount"]);
+        assert_eq!(1, request["nextCount"]);
+    }
+
+    #[test]
+    fn test_queue_view() {
+        let reply = |payload: String| crate::cast::cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some("MyAppTransportId".to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(payload),
+            ..Default::default()
+        };
+        let mut stream = MockTcpStream::new();
+        stream.add_message(reply(format!(
+            r#"{{"requestId":1,"type":"{}","itemIds":[1,2,3,4,5]}}"#,
+            MESSAGE_TYPE_QUEUE_ITEM_IDS
+        )));
+        stream.add_message(reply(format!(
+            r#"{{"requestId":2,"type":"{}","items":[{{"itemId":2,"media":{{"contentId":"b",
+                "streamType":"BUFFERED","contentType":"audio/mp3"}}}},{{"itemId":3}}]}}"#,
+            MESSAGE_TYPE_QUEUE_ITEMS
+        )));
+        stream.add_message(reply(format!(
+            r#"{{"requestId":3,"type":"{}","items":[{{"itemId":4,"autoplay":false}}]}}"#,
+            MESSAGE_TYPE_QUEUE_ITEMS
+        )));
+        let channel = MediaChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let mut queue = QueueView::new(&channel, "MyAppTransportId", 7)
+            .unwrap()
+            .with_batch_size(2);
+        assert_eq!(5, queue.len());
+
+        let item = queue.get(1).unwrap().unwrap();
+        assert_eq!(2, item.item_id);
+        assert!(item.autoplay);
+        assert_eq!("b", item.media.as_ref().unwrap().content_id);
+        // Item 3 has been fetched with the same batch.
+        assert_eq!(3, queue.get(2).unwrap().unwrap().item_id);
+
+        // Item 5 is missing from the reply, so it's skipped.
+        let items = queue.items(3..10).unwrap();
+        assert_eq!(1, items.len());
+        assert!(!items[0].autoplay);
+
+        let request = stream.received_message(1).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!(MESSAGE_TYPE_QUEUE_GET_ITEMS, request["type"]);
+        assert_eq!(serde_json::json!([2, 3]), request["itemIds"]);
+        let request = stream.received_message(2).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!(serde_json::json!([4, 5]), request["itemIds"]);
+        assert!(stream.received_message(3).is_none());
+    }
+
+    fn playing_entry(current_time: f32) -> StatusEntry {
+        StatusEntry {
+            media_session_id: 1,
+            media: None,
+            playback_rate: 1.,
+            player_state: PlayerState::Playing,
+            current_item_id: Some(1),
+            loading_item_id: None,
+            preloaded_item_id: None,
+            idle_reason: None,
+            extended_status: None,
+            current_time: Some(current_time),
+            supported_media_commands: MediaCommands::default(),
+            live_seekable_range: None,
+            break_status: None,
+            video_info: None,
+            active_track_ids: vec![],
+            received_at: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_status_entry_diff() {
+        let mut old = playing_entry(10.);
+        old.received_at = Instant::now() - Duration::from_secs(5);
+
+        // Position has progressed as expected.
+        let new = playing_entry(15.);
+        assert!(StatusEntry::diff(&old, &new).is_empty());
+
+        let mut new = playing_entry(60.);
+        new.player_state = PlayerState::Paused;
+        new.current_item_id = Some(2);
+        new.active_track_ids = vec![1];
+        assert_eq!(
+            vec![
+                MediaStatusChange::ItemChanged(Some(2)),
+                MediaStatusChange::PlayerStateChanged {
+                    old: PlayerState::Playing,
+                    new: PlayerState::Paused,
+                    idle_reason: None,
+                },
+                MediaStatusChange::ActiveTracksChanged(vec![1]),
+                MediaStatusChange::PositionChanged(60.),
+            ],
+            StatusEntry::diff(&old, &new)
+        );
+    }
+
+    #[test]
+    fn test_progress_tracker() {
+        let mut tracker = ProgressTracker::new(Duration::ZERO);
+        assert_eq!(None, tracker.tick());
+
+        let mut entry = playing_entry(10.);
+        entry.media = Some(Media::builder("a", "video/mp4").build());
+        assert!(tracker.update(&entry).is_empty());
+        assert_eq!(Duration::ZERO, tracker.timeout());
+
+        let Some(MediaStatusChange::PositionChanged(position)) = tracker.tick() else {
+            panic!("expected position change");
+        };
+        assert!((10.0..11.0).contains(&position), "{position}");
+
+        // Media of the previous entry is kept.
+        let mut entry = playing_entry(10.);
+        entry.player_state = PlayerState::Paused;
+        assert_eq!(1, tracker.update(&entry).len());
+        assert!(tracker.entry().unwrap().media.is_some());
+        assert_eq!(None, tracker.tick());
+
+        let mut tracker = ProgressTracker::new(Duration::from_secs(60));
+        tracker.update(&playing_entry(10.));
+        assert_eq!(None, tracker.tick());
+        assert!(tracker.timeout() > Duration::from_secs(59));
+    }
+
+    #[test]
+    fn test_queue_tracker() {
+        let entry = |media_session_id, current_item_id, preloaded_item_id| StatusEntry {
+            media_session_id,
+            media: None,
+            playback_rate: 1.,
+            player_state: PlayerState::Playing,
+            current_item_id,
+            loading_item_id: None,
+            preloaded_item_id,
+            idle_reason: None,
+            extended_status: None,
+            current_time: None,
+            supported_media_commands: MediaCommands::default(),
+            live_seekable_range: None,
+            break_status: None,
+            video_info: None,
+            active_track_ids: vec![],
+            received_at: Instant::now(),
+        };
+        let mut tracker = QueueTracker::default();
+
+        assert_eq!(
+            vec![QueueEvent::ItemChanged {
+                previous: None,
+                current: 1,
+                preloaded: false
+            }],
+            tracker.update(&entry(1, Some(1), None))
+        );
+        assert_eq!(
+            vec![QueueEvent::Preloading(2)],
+            tracker.update(&entry(1, Some(1), Some(2)))
+        );
+        assert!(tracker.update(&entry(1, Some(1), Some(2))).is_empty());
+        assert_eq!(
+            vec![QueueEvent::ItemChanged {
+                previous: Some(1),
+                current: 2,
+                preloaded: true
+            }],
+            tracker.update(&entry(1, Some(2), None))
+        );
+        assert_eq!(
+            vec![
+                QueueEvent::Preloading(3),
+                QueueEvent::PreloadDiscarded(3),
+                QueueEvent::Preloading(4)
+            ],
+            [Some(3), Some(4)]
+                .into_iter()
+                .flat_map(|preloaded| tracker.update(&entry(1, Some(2), preloaded)))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(4), tracker.preloaded_item_id());
+
+        // Another media session starts from scratch.
+        tracker.update(&entry(2, Some(1), None));
+        assert_eq!(Some(1), tracker.current_item_id());
+        assert_eq!(None, tracker.preloaded_item_id());
+    }
+
+    #[test]
+    fn test_guess_content_type() {
+        assert_eq!(
+            Some("video/mp4"),
+            guess_content_type("https://example.com/movie.mp4#t=10")
+        );
+        assert_eq!(
+            Some("application/dash+xml"),
+            guess_content_type("https://example.com/stream/manifest.MPD?token=a.b")
+        );
+        assert_eq!(
+            Some("audio/flac"),
+            guess_content_type("C:\\Music\\track.flac")
+        );
+        assert_eq!(None, guess_content_type("https://example.com/v1.2/watch"));
+        assert_eq!(None, guess_content_type("https://example.com/movie.xyz"));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_probe_content_type() {
+        let base = crate::http::serve(vec![
+            "HTTP/1.0 200 OK\r\nContent-Type: Video/MP4; charset=binary\r\n\r\n".to_string(),
+            "HTTP/1.0 200 OK\r\nContent-Type: application/octet-stream\r\n\r\n".to_string(),
+            "HTTP/1.0 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string(),
+        ]);
+        let timeout = Duration::from_secs(5);
+
+        assert_eq!(
+            Some("video/mp4".to_string()),
+            probe_content_type(&format!("{base}/watch"), timeout).unwrap()
+        );
+        assert_eq!(
+            None,
+            probe_content_type(&format!("{base}/watch"), timeout).unwrap()
+        );
+        assert!(matches!(
+            probe_content_type(&format!("{base}/missing"), timeout),
+            Err(Error::Validation(_))
+        ));
+    }
 }
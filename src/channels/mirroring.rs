@@ -0,0 +1,347 @@
+//! Scaffolding for the screen mirroring (`webrtc`) and remoting namespaces: only the signaling
+//! envelopes (e.g. `OFFER`/`ANSWER`) are parsed, their bodies are exposed as raw JSON so that the
+//! streaming itself can be implemented on top of it.
+
+use std::io::{Read, Write};
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    errors::Error,
+    message_manager::{CastMessage, CastMessagePayload, MessageManager},
+    namespaces, Lrc,
+};
+
+pub(crate) const WEBRTC_NAMESPACE: &str = namespaces::WEBRTC;
+pub(crate) const REMOTING_NAMESPACE: &str = namespaces::REMOTING;
+
+const MESSAGE_TYPE_OFFER: &str = "OFFER";
+const MESSAGE_TYPE_ANSWER: &str = "ANSWER";
+
+/// Namespace the signaling message is exchanged over.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MirroringNamespace {
+    /// Screen mirroring session negotiation (`urn:x-cast:com.google.cast.webrtc`).
+    WebRtc,
+    /// Media remoting control (`urn:x-cast:com.google.cast.remoting`).
+    Remoting,
+}
+
+impl MirroringNamespace {
+    /// Returns the namespace string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MirroringNamespace::WebRtc => WEBRTC_NAMESPACE,
+            MirroringNamespace::Remoting => REMOTING_NAMESPACE,
+        }
+    }
+
+    fn from_namespace(namespace: &str) -> Option<Self> {
+        match namespace {
+            WEBRTC_NAMESPACE => Some(MirroringNamespace::WebRtc),
+            REMOTING_NAMESPACE => Some(MirroringNamespace::Remoting),
+            _ => None,
+        }
+    }
+}
+
+/// Signaling message envelope, body of the message is kept as is.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignalingMessage {
+    /// Namespace the message has been received on.
+    pub namespace: MirroringNamespace,
+    /// Type of the message, e.g. `OFFER` or `RPC`.
+    pub typ: String,
+    /// Sequence number that pairs the request with its response, if any.
+    pub seq_num: Option<u32>,
+    /// Raw JSON payload of the message, including `type` and `seqNum` fields.
+    pub payload: Value,
+}
+
+/// Answer of the receiver to the `OFFER` of the mirroring session.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Answer {
+    /// Sequence number of the `OFFER` this answer belongs to.
+    pub seq_num: u32,
+    /// Result of the negotiation, e.g. `ok` or `error`.
+    pub result: Option<String>,
+    /// Raw `answer` body (selected streams, UDP port etc.) if the offer has been accepted.
+    pub answer: Option<Value>,
+    /// Raw `error` body if the offer has been rejected.
+    pub error: Option<Value>,
+}
+
+impl Answer {
+    /// Determines whether the receiver has accepted the offer.
+    pub fn is_ok(&self) -> bool {
+        self.result.as_deref() == Some("ok")
+    }
+}
+
+/// Represents all currently supported incoming messages that mirroring channel can handle.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum MirroringResponse {
+    /// Answer to the mirroring session offer.
+    Answer(Answer),
+    /// Any other signaling message.
+    Signaling(SignalingMessage),
+}
+
+/// Channel that exchanges signaling messages of the screen mirroring and remoting namespaces.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde_json::json;
+///
+/// # use rust_cast::CastDevice;
+/// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// let answer = cast_device.mirroring.send_offer(
+///     "web-4",
+///     json!({"castMode": "mirroring", "supportedStreams": []}),
+/// )?;
+///
+/// if answer.is_ok() {
+///     println!("Receiver has selected: {:?}", answer.answer);
+/// }
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+pub struct MirroringChannel<W>
+where
+    W: Read + Write,
+{
+    sender: String,
+    message_manager: Lrc<MessageManager<W>>,
+}
+
+/// Cloned channel shares the connection with the original one.
+impl<W> Clone for MirroringChannel<W>
+where
+    W: Read + Write,
+{
+    fn clone(&self) -> Self {
+        MirroringChannel {
+            sender: self.sender.clone(),
+            message_manager: Lrc::clone(&self.message_manager),
+        }
+    }
+}
+
+impl<W> MirroringChannel<W>
+where
+    W: Read + Write,
+{
+    pub fn new<S>(sender: S, message_manager: Lrc<MessageManager<W>>) -> MirroringChannel<W>
+    where
+        S: Into<String>,
+    {
+        MirroringChannel {
+            sender: sender.into(),
+            message_manager,
+        }
+    }
+
+    /// Offers the mirroring session to the receiver application and waits for its answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `protocol` of the mirroring application (e.g. `web-1`);
+    /// * `offer` - Raw `offer` body (supported streams, cast mode etc.).
+    ///
+    /// # Return value
+    ///
+    /// Answer of the receiver, see `Answer::is_ok`.
+    pub fn send_offer<S>(&self, destination: S, offer: Value) -> Result<Answer, Error>
+    where
+        S: Into<String>,
+    {
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        let seq_num = request_id.get();
+        self.send_message_with_seq_num(
+            MirroringNamespace::WebRtc,
+            destination.into(),
+            MESSAGE_TYPE_OFFER,
+            Map::from_iter([("offer".to_string(), offer)]),
+            seq_num,
+        )?;
+
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
+
+                match self.parse(message)? {
+                    MirroringResponse::Answer(answer) if answer.seq_num == seq_num => {
+                        Ok(Some(answer))
+                    }
+                    _ => Ok(None),
+                }
+            })
+    }
+
+    /// Sends signaling message of the `typ` type, with the generated `seqNum` and the `body`
+    /// fields, without waiting for the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace to send message over;
+    /// * `destination` - `protocol` of the mirroring application (e.g. `web-1`);
+    /// * `typ` - Type of the message, e.g. `GET_CAPABILITIES`;
+    /// * `body` - Rest of the message fields.
+    ///
+    /// # Return value
+    ///
+    /// Sequence number of the message the response can be matched by.
+    pub fn send_message<S>(
+        &self,
+        namespace: MirroringNamespace,
+        destination: S,
+        typ: &str,
+        body: Map<String, Value>,
+    ) -> Result<u32, Error>
+    where
+        S: Into<String>,
+    {
+        let seq_num = self.message_manager.generate_request_id().get();
+        self.send_message_with_seq_num(namespace, destination.into(), typ, body, seq_num)?;
+
+        Ok(seq_num)
+    }
+
+    /// Sends signaling message of the `typ` type with the `seq_num` and the `body` fields.
+    fn send_message_with_seq_num(
+        &self,
+        namespace: MirroringNamespace,
+        destination: String,
+        typ: &str,
+        body: Map<String, Value>,
+        seq_num: u32,
+    ) -> Result<(), Error> {
+        trace_span!(
+            "mirroring.send_message",
+            namespace = namespace.as_str(),
+            request_id = seq_num,
+            destination = %destination,
+            typ
+        );
+
+        let mut payload = body;
+        payload.insert("type".to_string(), Value::from(typ));
+        payload.insert("seqNum".to_string(), Value::from(seq_num));
+
+        self.message_manager.send(CastMessage {
+            namespace: namespace.as_str().to_string(),
+            source: self.sender.to_string(),
+            destination: destination.to_string(),
+            payload: CastMessagePayload::String(serde_json::to_string(&payload)?),
+        })
+    }
+
+    pub fn can_handle(&self, message: &CastMessage) -> bool {
+        MirroringNamespace::from_namespace(&message.namespace).is_some()
+    }
+
+    pub fn parse(&self, message: &CastMessage) -> Result<MirroringResponse, Error> {
+        let namespace =
+            MirroringNamespace::from_namespace(&message.namespace).ok_or_else(|| {
+                Error::Namespace(format!("'{}' isn't mirroring namespace", message.namespace))
+            })?;
+        let payload = message.payload.to_json()?;
+
+        let typ = payload["type"].as_str().unwrap_or("").to_string();
+        let seq_num = payload["seqNum"].as_u64().map(|seq_num| seq_num as u32);
+
+        let response = match (typ.as_ref(), seq_num) {
+            (MESSAGE_TYPE_ANSWER, Some(seq_num)) => MirroringResponse::Answer(Answer {
+                seq_num,
+                result: payload["result"].as_str().map(str::to_string),
+                answer: payload.get("answer").cloned(),
+                error: payload.get("error").cloned(),
+            }),
+            _ => MirroringResponse::Signaling(SignalingMessage {
+                namespace,
+                typ,
+                seq_num,
+                payload,
+            }),
+        };
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cast::cast_channel::{
+            self,
+            cast_message::{PayloadType, ProtocolVersion},
+        },
+        testing::MockTcpStream,
+        DEFAULT_SENDER_ID,
+    };
+    use protobuf::EnumOrUnknown;
+    use serde_json::json;
+
+    #[test]
+    fn test_send_offer() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
+            source_id: Some("web-4".to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(WEBRTC_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(
+                r#"{"type":"ANSWER","seqNum":1,"result":"ok","answer":{"udpPort":2344}}"#
+                    .to_string(),
+            ),
+            ..Default::default()
+        });
+        let channel = MirroringChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let answer = channel
+            .send_offer("web-4", json!({"castMode": "mirroring"}))
+            .unwrap();
+
+        assert!(answer.is_ok());
+        assert_eq!(Some(json!({"udpPort": 2344})), answer.answer);
+        let request = stream.received_message(0).unwrap().cast_message();
+        assert_eq!(WEBRTC_NAMESPACE, request.namespace);
+        assert_eq!(
+            json!({"type": "OFFER", "seqNum": 1, "offer": {"castMode": "mirroring"}}),
+            request.payload.to_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_remoting_message() {
+        let channel = MirroringChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
+        let message = CastMessage {
+            namespace: REMOTING_NAMESPACE.to_string(),
+            source: "web-4".to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(r#"{"type":"RPC","rpc":"CAE="}"#.to_string()),
+        };
+
+        match channel.parse(&message).unwrap() {
+            MirroringResponse::Signaling(signaling) => {
+                assert_eq!(MirroringNamespace::Remoting, signaling.namespace);
+                assert_eq!("RPC", signaling.typ);
+                assert_eq!(None, signaling.seq_num);
+                assert_eq!("CAE=", signaling.payload["rpc"]);
+            }
+            response => panic!("unexpected response {response:?}"),
+        }
+    }
+}
@@ -1,4 +1,11 @@
 pub mod connection;
+pub mod debug_overlay;
+pub mod error;
 pub mod heartbeat;
 pub mod media;
+pub mod mirroring;
+pub mod multizone;
 pub mod receiver;
+pub mod setup;
+#[cfg(feature = "youtube")]
+pub mod youtube;
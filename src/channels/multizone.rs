@@ -0,0 +1,339 @@
+use std::io::{Read, Write};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{
+    cast::proxies,
+    channels::receiver::Volume,
+    errors::Error,
+    message_manager::{CastMessage, CastMessagePayload, MessageManager, RequestId},
+    namespaces, Lrc,
+};
+
+pub(crate) const CHANNEL_NAMESPACE: &str = namespaces::MULTIZONE;
+
+const MESSAGE_TYPE_GET_STATUS: &str = "GET_STATUS";
+const MESSAGE_TYPE_SET_DEVICE_VOLUME: &str = "SET_DEVICE_VOLUME";
+
+const MESSAGE_TYPE_MULTIZONE_STATUS: &str = "MULTIZONE_STATUS";
+const MESSAGE_TYPE_DEVICE_ADDED: &str = "DEVICE_ADDED";
+const MESSAGE_TYPE_DEVICE_UPDATED: &str = "DEVICE_UPDATED";
+const MESSAGE_TYPE_DEVICE_REMOVED: &str = "DEVICE_REMOVED";
+
+/// Cast device that is a member of the speaker group.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GroupMember {
+    /// Unique id of the member device.
+    pub device_id: String,
+    /// Name of the member device set by the user.
+    pub name: String,
+    /// Capabilities bitmask of the member device.
+    pub capabilities: u64,
+    /// Volume of the member device.
+    pub volume: Volume,
+}
+
+impl From<&proxies::multizone::Device> for GroupMember {
+    fn from(device: &proxies::multizone::Device) -> Self {
+        GroupMember {
+            device_id: device.device_id.clone(),
+            name: device.name.clone(),
+            capabilities: device.capabilities,
+            volume: Volume::from(&device.volume),
+        }
+    }
+}
+
+/// Describes the current status of the speaker group.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultizoneStatus {
+    /// Unique id of the request that requested the status.
+    pub request_id: RequestId,
+    /// Devices the group consists of.
+    pub members: Vec<GroupMember>,
+    /// Determines whether members play different channels (e.g. stereo pair).
+    pub is_multichannel: bool,
+}
+
+/// Represents all currently supported incoming messages that multizone channel can handle.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum MultizoneResponse {
+    /// Status of the speaker group.
+    Status(MultizoneStatus),
+    /// Device has joined the group.
+    MemberAdded(GroupMember),
+    /// Member device has changed (e.g. its volume), along with id of the request that has caused
+    /// the change or `RequestId::UNSOLICITED` if it has been changed by another sender.
+    MemberUpdated(GroupMember, RequestId),
+    /// Device with the specified id has left the group.
+    MemberRemoved(String),
+    /// Used every time when channel can't parse the message. Associated data contains `type` string
+    /// field and raw JSON data returned from cast device.
+    NotImplemented(String, serde_json::Value),
+}
+
+/// Channel that manages members of the speaker group, it's only supported by the group leader.
+pub struct MultizoneChannel<W>
+where
+    W: Write + Read,
+{
+    sender: String,
+    receiver: String,
+    message_manager: Lrc<MessageManager<W>>,
+}
+
+/// Cloned channel shares the connection with the original one.
+impl<W> Clone for MultizoneChannel<W>
+where
+    W: Write + Read,
+{
+    fn clone(&self) -> Self {
+        MultizoneChannel {
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+            message_manager: Lrc::clone(&self.message_manager),
+        }
+    }
+}
+
+impl<W> MultizoneChannel<W>
+where
+    W: Write + Read,
+{
+    pub fn new<S>(
+        sender: S,
+        receiver: S,
+        message_manager: Lrc<MessageManager<W>>,
+    ) -> MultizoneChannel<W>
+    where
+        S: Into<String>,
+    {
+        MultizoneChannel {
+            sender: sender.into(),
+            receiver: receiver.into(),
+            message_manager,
+        }
+    }
+
+    /// Retrieves status of the speaker group.
+    ///
+    /// # Return value
+    ///
+    /// Returned `Result` should consist of either `MultizoneStatus` instance or an `Error`.
+    pub fn get_status(&self) -> Result<MultizoneStatus, Error> {
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "multizone.get_status",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %self.receiver
+        );
+
+        let payload = serde_json::to_string(&proxies::multizone::GetStatusRequest {
+            typ: MESSAGE_TYPE_GET_STATUS.to_string(),
+            request_id: request_id.get(),
+        })?;
+
+        self.message_manager.send(CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: self.sender.to_string(),
+            destination: self.receiver.to_string(),
+            payload: CastMessagePayload::String(payload),
+        })?;
+
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
+
+                match self.parse(message)? {
+                    MultizoneResponse::Status(status) if status.request_id == request_id => {
+                        Ok(Some(status))
+                    }
+                    _ => Ok(None),
+                }
+            })
+    }
+
+    /// Sets volume of the individual member of the speaker group. Device doesn't acknowledge the
+    /// request, new volume is reported with `MultizoneResponse::MemberUpdated` message instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `device_id` - Unique id of the member device (see `GroupMember::device_id`).
+    /// * `volume` - anything that can be converted to a valid `Volume` structure. It's possible to
+    ///   set volume level, mute/unmute state or both altogether.
+    ///
+    /// # Return value
+    ///
+    /// Id of the sent request, so that `MemberUpdated` message caused by it can be told apart.
+    pub fn set_member_volume<S, T>(&self, device_id: S, volume: T) -> Result<RequestId, Error>
+    where
+        S: Into<String>,
+        T: Into<Volume>,
+    {
+        let request_id = self.message_manager.generate_request_id();
+        trace_span!(
+            "multizone.set_member_volume",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %self.receiver
+        );
+        let volume = volume.into();
+
+        let payload = serde_json::to_string(&proxies::multizone::SetDeviceVolumeRequest {
+            typ: MESSAGE_TYPE_SET_DEVICE_VOLUME.to_string(),
+            request_id: request_id.get(),
+            device_id: device_id.into().to_string(),
+            volume: proxies::receiver::Volume {
+                level: volume.level,
+                muted: volume.muted,
+                step_interval: None,
+                control_type: None,
+            },
+        })?;
+
+        self.message_manager.send(CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: self.sender.to_string(),
+            destination: self.receiver.to_string(),
+            payload: CastMessagePayload::String(payload),
+        })?;
+
+        Ok(request_id)
+    }
+
+    pub fn can_handle(&self, message: &CastMessage) -> bool {
+        message.namespace == CHANNEL_NAMESPACE
+    }
+
+    pub fn parse(&self, message: &CastMessage) -> Result<MultizoneResponse, Error> {
+        let reply = message.payload.to_json()?;
+
+        let message_type = reply
+            .as_object()
+            .and_then(|object| object.get("type"))
+            .and_then(|property| property.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        let response = match message_type.as_ref() {
+            MESSAGE_TYPE_MULTIZONE_STATUS => {
+                let reply: proxies::multizone::StatusReply = serde_json::value::from_value(reply)?;
+
+                MultizoneResponse::Status(MultizoneStatus {
+                    request_id: reply.request_id.into(),
+                    members: reply.status.devices.iter().map(GroupMember::from).collect(),
+                    is_multichannel: reply.status.is_multichannel,
+                })
+            }
+            MESSAGE_TYPE_DEVICE_ADDED => {
+                let reply: proxies::multizone::DeviceReply = serde_json::value::from_value(reply)?;
+
+                MultizoneResponse::MemberAdded(GroupMember::from(&reply.device))
+            }
+            MESSAGE_TYPE_DEVICE_UPDATED => {
+                let reply: proxies::multizone::DeviceReply = serde_json::value::from_value(reply)?;
+
+                MultizoneResponse::MemberUpdated(
+                    GroupMember::from(&reply.device),
+                    reply.request_id.into(),
+                )
+            }
+            MESSAGE_TYPE_DEVICE_REMOVED => {
+                let reply: proxies::multizone::DeviceRemovedReply =
+                    serde_json::value::from_value(reply)?;
+
+                MultizoneResponse::MemberRemoved(reply.device_id)
+            }
+            _ => MultizoneResponse::NotImplemented(message_type.to_string(), reply),
+        };
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::EnumOrUnknown;
+
+    use super::*;
+    use crate::{
+        cast::{
+            cast_channel,
+            cast_channel::cast_message::{PayloadType, ProtocolVersion},
+        },
+        testing::MockTcpStream,
+        DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID,
+    };
+
+    #[test]
+    fn test_get_status() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
+            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(
+                r#"{"type":"MULTIZONE_STATUS","requestId":1,"status":{"devices":[
+                    {"deviceId":"a1","name":"Kitchen","capabilities":4,
+                     "volume":{"level":0.5,"muted":false}},
+                    {"deviceId":"b2","name":"Bedroom"}]}}"#
+                    .to_string(),
+            ),
+            ..Default::default()
+        });
+        let channel = MultizoneChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let status = channel.get_status().unwrap();
+
+        assert_eq!(2, status.members.len());
+        assert_eq!("a1", status.members[0].device_id);
+        assert_eq!("Kitchen", status.members[0].name);
+        assert_eq!(Some(0.5), status.members[0].volume.level);
+        assert_eq!(None, status.members[1].volume.level);
+        let request = stream.received_message(0).unwrap().message();
+        assert_eq!(CHANNEL_NAMESPACE, request.namespace());
+    }
+    #[test]
+    fn test_set_member_volume_request_id() {
+        let stream = MockTcpStream::new();
+        let channel = MultizoneChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let request_id = channel.set_member_volume("a1", 0.3).unwrap();
+
+        let request = stream.received_message(0).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!("SET_DEVICE_VOLUME", request["type"]);
+        assert_eq!(request_id.get(), request["requestId"]);
+
+        let update = CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(format!(
+                r#"{{"type":"DEVICE_UPDATED","requestId":{request_id},
+                    "device":{{"deviceId":"a1","name":"Kitchen","volume":{{"level":0.3}}}}}}"#
+            )),
+        };
+        match channel.parse(&update).unwrap() {
+            MultizoneResponse::MemberUpdated(member, id) => {
+                assert_eq!("a1", member.device_id);
+                assert_eq!(request_id, id);
+            }
+            response => panic!("unexpected response {response:?}"),
+        }
+    }
+}
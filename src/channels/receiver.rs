@@ -5,23 +5,27 @@ use std::{
     io::{Read, Write},
     str::FromStr,
     string::ToString,
+    time::{Duration, Instant},
 };
 
-use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
 use crate::{
     cast::proxies,
     errors::Error,
-    message_manager::{CastMessage, CastMessagePayload, MessageManager},
+    message_manager::{CastMessage, CastMessagePayload, MessageManager, RequestId},
+    namespaces,
+    policy::{Decision, Operation},
     Lrc,
 };
 
-pub(crate) const CHANNEL_NAMESPACE: &str = "urn:x-cast:com.google.cast.receiver";
+pub(crate) const CHANNEL_NAMESPACE: &str = namespaces::RECEIVER;
 
 const MESSAGE_TYPE_LAUNCH: &str = "LAUNCH";
 const MESSAGE_TYPE_STOP: &str = "STOP";
 const MESSAGE_TYPE_GET_STATUS: &str = "GET_STATUS";
 const MESSAGE_TYPE_SET_VOLUME: &str = "SET_VOLUME";
+const MESSAGE_TYPE_SET_STANDBY: &str = "SET_STANDBY";
 
 const MESSAGE_TYPE_RECEIVER_STATUS: &str = "RECEIVER_STATUS";
 const MESSAGE_TYPE_LAUNCH_ERROR: &str = "LAUNCH_ERROR";
@@ -31,13 +35,63 @@ const APP_DEFAULT_MEDIA_RECEIVER_ID: &str = "CC1AD845";
 const APP_BACKDROP_ID: &str = "E8C28D3C";
 const APP_YOUTUBE_ID: &str = "233637DE";
 
+/// Volume step used by `volume_up` and `volume_down` if the device doesn't report its own.
+const DEFAULT_VOLUME_STEP_INTERVAL: f32 = 0.05;
+/// Interval between the volume requests of `fade_volume`.
+const VOLUME_FADE_STEP_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Describes the way volume of the cast device can be controlled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VolumeControlType {
+    /// Cast device volume can be changed, but it's attenuated relative to the system volume.
+    Attenuation,
+    /// Cast device volume is fixed and can't be changed (e.g. devices connected over HDMI).
+    Fixed,
+    /// Cast device volume is the master system volume.
+    Master,
+}
+
+impl FromStr for VolumeControlType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<VolumeControlType, Error> {
+        match s {
+            "attenuation" => Ok(VolumeControlType::Attenuation),
+            "fixed" => Ok(VolumeControlType::Fixed),
+            "master" => Ok(VolumeControlType::Master),
+            _ => Err(Error::Internal(format!(
+                "Unknown volume control type {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for VolumeControlType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let control_type = match *self {
+            VolumeControlType::Attenuation => "attenuation",
+            VolumeControlType::Fixed => "fixed",
+            VolumeControlType::Master => "master",
+        };
+
+        write!(f, "{}", control_type)
+    }
+}
+
 /// Structure that describes possible cast device volume options.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Volume {
     /// Volume level.
     pub level: Option<f32>,
     /// Mute/unmute state.
     pub muted: Option<bool>,
+    /// Native volume step of the device, it's only reported by the device and ignored when volume
+    /// is set.
+    pub step_interval: Option<f32>,
+    /// Volume control type of the device, it's only reported by the device and ignored when volume
+    /// is set.
+    pub control_type: Option<VolumeControlType>,
 }
 
 /// This `From<f32>` implementation is useful when only volume level is needed.
@@ -46,6 +100,8 @@ impl From<f32> for Volume {
         Self {
             level: Some(level),
             muted: None,
+            step_interval: None,
+            control_type: None,
         }
     }
 }
@@ -56,6 +112,8 @@ impl From<bool> for Volume {
         Self {
             level: None,
             muted: Some(muted),
+            step_interval: None,
+            control_type: None,
         }
     }
 }
@@ -67,12 +125,100 @@ impl From<(f32, bool)> for Volume {
         Self {
             level: Some(level),
             muted: Some(muted),
+            step_interval: None,
+            control_type: None,
+        }
+    }
+}
+
+impl From<&proxies::receiver::Volume> for Volume {
+    fn from(volume: &proxies::receiver::Volume) -> Self {
+        Self {
+            level: volume.level,
+            muted: volume.muted,
+            step_interval: volume.step_interval,
+            // Control types unknown to this library are ignored.
+            control_type: volume
+                .control_type
+                .as_deref()
+                .and_then(|control_type| control_type.parse().ok()),
         }
     }
 }
 
+/// Namespace the receiver application accepts messages on, e.g.
+/// `urn:x-cast:com.google.cast.media`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Namespace(String);
+
+impl Namespace {
+    /// Creates namespace with the specified `name`.
+    pub fn new<S: Into<String>>(name: S) -> Namespace {
+        Namespace(name.into())
+    }
+
+    /// Returns name of the namespace.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Determines whether it's the media namespace, see `MediaChannel`.
+    pub fn is_media(&self) -> bool {
+        self.0 == namespaces::MEDIA
+    }
+}
+
+impl AsRef<str> for Namespace {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for Namespace {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Namespace {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<Namespace> for str {
+    fn eq(&self, other: &Namespace) -> bool {
+        self == other.0
+    }
+}
+
+impl PartialEq<Namespace> for &str {
+    fn eq(&self, other: &Namespace) -> bool {
+        *self == other.0
+    }
+}
+
+impl From<String> for Namespace {
+    fn from(name: String) -> Self {
+        Namespace(name)
+    }
+}
+
+impl From<&str> for Namespace {
+    fn from(name: &str) -> Self {
+        Namespace(name.to_string())
+    }
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// Structure that describes currently run Cast Device application.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Application {
     /// The identifier of the Cast application. Not for display.
     pub app_id: String,
@@ -81,48 +227,212 @@ pub struct Application {
     /// Name of the `pipe` to talk to the application.
     pub transport_id: String,
     /// A list of the namespaces supported by the receiver application.
-    pub namespaces: Vec<String>,
+    pub namespaces: Vec<Namespace>,
     /// The human-readable name of the Cast application, for example, "YouTube".
     pub display_name: String,
     /// Descriptive text for the current application content, for example “My vacations”.
     pub status_text: String,
 }
 
+impl Application {
+    /// Determines whether the application accepts messages on the `namespace`.
+    pub fn supports(&self, namespace: &str) -> bool {
+        self.namespaces
+            .iter()
+            .any(|supported| supported == namespace)
+    }
+
+    /// Determines whether the application accepts media messages, i.e. media can be loaded into
+    /// it and controlled with `MediaChannel`.
+    pub fn supports_media(&self) -> bool {
+        self.namespaces.iter().any(Namespace::is_media)
+    }
+}
+
 /// Describes the current status of the receiver cast device.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Status {
     /// Unique id of the request that requested the status.
-    pub request_id: u32,
+    pub request_id: RequestId,
     /// Contains the list of applications that are currently run.
     pub applications: Vec<Application>,
     /// Determines whether the Cast device is the active input or not.
     pub is_active_input: bool,
     /// Determines whether the Cast device is in stand by mode.
     pub is_stand_by: bool,
+    /// Determines whether the Cast device reports its stand by and active input state, only such
+    /// devices (e.g. TVs or devices connected over HDMI) can be put into stand by mode.
+    pub supports_standby: bool,
     /// Volume parameters of the currently active cast device.
     pub volume: Volume,
 }
 
+impl Status {
+    /// Determines whether the status comes from the audio-only device (e.g. Chromecast Audio or
+    /// Nest speaker): only the devices connected to a display report their stand by and active
+    /// input state. It's a heuristic, `DeviceCapabilities` derived from mDNS TXT records are more
+    /// reliable when available.
+    pub fn is_audio_device(&self) -> bool {
+        !self.supports_standby
+    }
+
+    /// Compares two consecutive statuses of the receiver and returns what has changed between
+    /// them, in the order: stopped applications, launched applications, volume, stand by and active
+    /// input. Applications are matched by their session id, so relaunched application is reported
+    /// as stopped and launched again.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::{CastDevice, channels::receiver::{Status, StatusChange}};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// let old = cast_device.receiver.get_status()?;
+    /// let new = cast_device.receiver.get_status()?;
+    /// for change in Status::diff(&old, &new) {
+    ///     if let StatusChange::AppStopped(app) = change {
+    ///         println!("{} has been stopped", app.display_name);
+    ///     }
+    /// }
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - Previously received status.
+    /// * `new` - Status received after the `old` one.
+    pub fn diff(old: &Status, new: &Status) -> Vec<StatusChange> {
+        let mut changes = Vec::new();
+
+        let is_running = |status: &Status, app: &Application| {
+            status
+                .applications
+                .iter()
+                .any(|running| running.session_id == app.session_id)
+        };
+
+        changes.extend(
+            old.applications
+                .iter()
+                .filter(|app| !is_running(new, app))
+                .cloned()
+                .map(StatusChange::AppStopped),
+        );
+        changes.extend(
+            new.applications
+                .iter()
+                .filter(|app| !is_running(old, app))
+                .cloned()
+                .map(StatusChange::AppLaunched),
+        );
+
+        if old.volume != new.volume {
+            changes.push(StatusChange::VolumeChanged {
+                old: old.volume,
+                new: new.volume,
+            });
+        }
+
+        if old.is_stand_by != new.is_stand_by {
+            changes.push(StatusChange::StandbyChanged(new.is_stand_by));
+        }
+
+        if old.is_active_input != new.is_active_input {
+            changes.push(StatusChange::ActiveInputChanged(new.is_active_input));
+        }
+
+        changes
+    }
+}
+
+/// Describes the change between two consecutive receiver statuses, see `Status::diff`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StatusChange {
+    /// Application has been launched.
+    AppLaunched(Application),
+    /// Application has been stopped.
+    AppStopped(Application),
+    /// Volume level, mute state or volume control parameters have changed.
+    VolumeChanged {
+        /// Volume before the change.
+        old: Volume,
+        /// Volume after the change.
+        new: Volume,
+    },
+    /// Device has entered (`true`) or left (`false`) stand by mode.
+    StandbyChanged(bool),
+    /// Device has become (`true`) or stopped being (`false`) the active input.
+    ActiveInputChanged(bool),
+}
+
+/// Reasons the receiver reports the application launch failure with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LaunchErrorReason {
+    /// Application isn't known to the receiver, e.g. the id is wrong or the custom application
+    /// hasn't been published (or the device isn't registered for its development).
+    NotFound,
+    /// Launch has been cancelled, e.g. by the user or by another launch request.
+    Cancelled,
+    /// Sender isn't allowed to launch the application, e.g. the device is locked down.
+    NotAllowed,
+    /// User has to consent to the launch on the screen of the device (e.g. Google TV) first.
+    ConsentRequired,
+    /// Receiver failed to launch the application, retrying may succeed.
+    SystemError,
+    /// Reason that is unknown to this library, associated data contains the raw reason string
+    /// returned by the receiver.
+    Other(String),
+}
+
+impl FromStr for LaunchErrorReason {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<LaunchErrorReason, Error> {
+        match s {
+            "NOT_FOUND" => Ok(LaunchErrorReason::NotFound),
+            "CANCELLED" => Ok(LaunchErrorReason::Cancelled),
+            "NOT_ALLOWED" => Ok(LaunchErrorReason::NotAllowed),
+            "CONSENT_REQUIRED" => Ok(LaunchErrorReason::ConsentRequired),
+            "SYSTEM_ERROR" => Ok(LaunchErrorReason::SystemError),
+            _ => Ok(LaunchErrorReason::Other(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for LaunchErrorReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match *self {
+            LaunchErrorReason::NotFound => "NOT_FOUND",
+            LaunchErrorReason::Cancelled => "CANCELLED",
+            LaunchErrorReason::NotAllowed => "NOT_ALLOWED",
+            LaunchErrorReason::ConsentRequired => "CONSENT_REQUIRED",
+            LaunchErrorReason::SystemError => "SYSTEM_ERROR",
+            LaunchErrorReason::Other(ref reason) => reason,
+        };
+
+        write!(f, "{}", reason)
+    }
+}
+
 /// Describes the application launch error.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LaunchError {
     /// Unique id of the request that tried to launch application.
-    pub request_id: u32,
-    /// Description of the launch error reason if available.
-    pub reason: Option<String>,
+    pub request_id: RequestId,
+    /// Launch error reason if available.
+    pub reason: Option<LaunchErrorReason>,
 }
 
 /// Describes the invalid request error.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InvalidRequest {
     /// Unique id of the invalid request.
-    pub request_id: u32,
+    pub request_id: RequestId,
     /// Description of the invalid request reason if available.
     pub reason: Option<String>,
 }
 
 /// Represents all currently supported incoming messages that receiver channel can handle.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ReceiverResponse {
     /// Status of the currently active receiver.
     Status(Status),
@@ -135,6 +445,32 @@ pub enum ReceiverResponse {
     NotImplemented(String, serde_json::Value),
 }
 
+/// Auth token supplied by the sender for the receivers that require it (e.g. Spotify), see
+/// `ReceiverChannel::launch_app_with_credentials` and `LoadOptions::credentials`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Credentials {
+    /// Opaque credentials (usually access token) the receiver authenticates the user with.
+    pub credentials: String,
+    /// Type of the credentials the receiver expects, e.g. `cloud`, `android` or `web`.
+    pub credentials_type: Option<String>,
+}
+
+impl Credentials {
+    /// Creates credentials of the unspecified type.
+    pub fn new<S: Into<String>>(credentials: S) -> Self {
+        Credentials {
+            credentials: credentials.into(),
+            credentials_type: None,
+        }
+    }
+
+    /// Sets type of the credentials.
+    pub fn with_type<S: Into<String>>(mut self, credentials_type: S) -> Self {
+        self.credentials_type = Some(credentials_type.into());
+        self
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum CastDeviceApp {
     DefaultMediaReceiver,
@@ -143,6 +479,15 @@ pub enum CastDeviceApp {
     Custom(String),
 }
 
+impl CastDeviceApp {
+    /// Determines whether the application can be launched on the audio-only devices (e.g. Nest
+    /// speakers): they reject video applications such as the idle screen or YouTube. Custom
+    /// applications are assumed to support them.
+    pub fn supports_audio_devices(&self) -> bool {
+        !matches!(self, CastDeviceApp::Backdrop | CastDeviceApp::YouTube)
+    }
+}
+
 impl FromStr for CastDeviceApp {
     type Err = ();
 
@@ -170,16 +515,30 @@ impl fmt::Display for CastDeviceApp {
     }
 }
 
-pub struct ReceiverChannel<'a, W>
+pub struct ReceiverChannel<W>
 where
     W: Write + Read,
 {
-    sender: Cow<'a, str>,
-    receiver: Cow<'a, str>,
+    sender: String,
+    receiver: String,
     message_manager: Lrc<MessageManager<W>>,
 }
 
-impl<'a, W> ReceiverChannel<'a, W>
+/// Cloned channel shares the connection with the original one.
+impl<W> Clone for ReceiverChannel<W>
+where
+    W: Write + Read,
+{
+    fn clone(&self) -> Self {
+        ReceiverChannel {
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+            message_manager: Lrc::clone(&self.message_manager),
+        }
+    }
+}
+
+impl<W> ReceiverChannel<W>
 where
     W: Write + Read,
 {
@@ -187,9 +546,9 @@ where
         sender: S,
         receiver: S,
         message_manager: Lrc<MessageManager<W>>,
-    ) -> ReceiverChannel<'a, W>
+    ) -> ReceiverChannel<W>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
         ReceiverChannel {
             sender: sender.into(),
@@ -214,12 +573,65 @@ where
     ///
     /// * `app` - `CastDeviceApp` instance reference to run.
     pub fn launch_app(&self, app: &CastDeviceApp) -> Result<Application, Error> {
-        let request_id = self.message_manager.generate_request_id().get();
+        self.launch_app_with(app, None)
+    }
+
+    /// Launches the specified receiver's application, passing the sender-supplied `credentials`
+    /// (CAF `credentialsData`) to the receivers that require them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rust_cast::{CastDevice, channels::receiver::{CastDeviceApp, Credentials}};
+    ///
+    /// # let cast_device = CastDevice::connect_without_host_verification("host", 1234).unwrap();
+    /// let app = CastDeviceApp::Custom("CC32E753".to_string());
+    /// let credentials = Credentials::new("access-token").with_type("cloud");
+    /// cast_device.receiver.launch_app_with_credentials(&app, &credentials)?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - `CastDeviceApp` instance reference to run;
+    /// * `credentials` - Credentials the application should authenticate the user with.
+    pub fn launch_app_with_credentials(
+        &self,
+        app: &CastDeviceApp,
+        credentials: &Credentials,
+    ) -> Result<Application, Error> {
+        self.launch_app_with(app, Some(credentials))
+    }
+
+    fn launch_app_with(
+        &self,
+        app: &CastDeviceApp,
+        credentials: Option<&Credentials>,
+    ) -> Result<Application, Error> {
+        let operation = Operation::Launch {
+            app_id: app.to_string(),
+        };
+        if let Decision::Block(reason) = self.message_manager.check_policy(&operation) {
+            return Err(Error::PolicyBlocked(reason));
+        }
+
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "receiver.launch_app",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %self.receiver
+        );
 
         let payload = serde_json::to_string(&proxies::receiver::AppLaunchRequest {
             typ: MESSAGE_TYPE_LAUNCH.to_string(),
-            request_id,
+            request_id: request_id.get(),
             app_id: app.to_string(),
+            credentials_data: credentials.map(|credentials| proxies::receiver::CredentialsData {
+                credentials: credentials.credentials.clone(),
+                credentials_type: credentials.credentials_type.clone(),
+            }),
         })?;
 
         self.message_manager.send(CastMessage {
@@ -231,30 +643,24 @@ where
 
         // Once application is run cast receiver device should emit status update event, or launch
         // error event if something went wrong.
-        self.message_manager.receive_find_map(|message| {
-            if !self.can_handle(message) {
-                return Ok(None);
-            }
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
 
-            match self.parse(message)? {
-                ReceiverResponse::Status(mut status) => {
-                    if status.request_id == request_id {
+                match self.parse(message)? {
+                    ReceiverResponse::Status(mut status) if status.request_id == request_id => {
                         return Ok(Some(status.applications.remove(0)));
                     }
-                }
-                ReceiverResponse::LaunchError(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(format!(
-                            "Could not run application ({}).",
-                            error.reason.unwrap_or_else(|| "Unknown".to_string())
-                        )));
+                    ReceiverResponse::LaunchError(error) if error.request_id == request_id => {
+                        return Err(error.into());
                     }
+                    _ => {}
                 }
-                _ => {}
-            }
 
-            Ok(None)
-        })
+                Ok(None)
+            })
     }
 
     /// Broadcasts a message over a cast device's message bus.
@@ -273,15 +679,16 @@ where
     ///
     /// * `namespace` - Message namespace that should start with `urn:x-cast:`.
     /// * `message` - Message instance to send.
-    pub fn broadcast_message<M: Serialize>(
+    pub fn broadcast_message<M: serde::Serialize>(
         &self,
         namespace: &str,
         message: &M,
     ) -> Result<(), Error> {
-        if !namespace.starts_with("urn:x-cast:") {
+        if !namespaces::is_cast_namespace(namespace) {
             return Err(Error::Namespace(format!(
-                "'{}' should start with 'urn:x-cast:' prefix",
-                namespace
+                "'{}' should start with '{}' prefix",
+                namespace,
+                namespaces::PREFIX
             )));
         }
         let payload = serde_json::to_string(message)?;
@@ -301,14 +708,21 @@ where
     /// * `session_id` - identifier of the active application session from `Application` instance.
     pub fn stop_app<S>(&self, session_id: S) -> Result<(), Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        let request_id = self.message_manager.generate_request_id().get();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "receiver.stop_app",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %self.receiver
+        );
 
         let payload = serde_json::to_string(&proxies::receiver::AppStopRequest {
             typ: MESSAGE_TYPE_STOP.to_string(),
-            request_id,
-            session_id: session_id.into(),
+            request_id: request_id.get(),
+            session_id: Cow::Owned(session_id.into()),
         })?;
 
         self.message_manager.send(CastMessage {
@@ -320,30 +734,24 @@ where
 
         // Once application is stopped cast receiver device should emit status update event, or
         // invalid request event if provided session id is not valid.
-        self.message_manager.receive_find_map(|message| {
-            if !self.can_handle(message) {
-                return Ok(None);
-            }
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
 
-            match self.parse(message)? {
-                ReceiverResponse::Status(status) => {
-                    if status.request_id == request_id {
+                match self.parse(message)? {
+                    ReceiverResponse::Status(status) if status.request_id == request_id => {
                         return Ok(Some(()));
                     }
-                }
-                ReceiverResponse::InvalidRequest(error) => {
-                    if error.request_id == request_id {
-                        return Err(Error::Internal(format!(
-                            "Invalid request ({}).",
-                            error.reason.unwrap_or_else(|| "Unknown".to_string())
-                        )));
+                    ReceiverResponse::InvalidRequest(error) if error.request_id == request_id => {
+                        return Err(error.into());
                     }
+                    _ => {}
                 }
-                _ => {}
-            }
 
-            Ok(None)
-        })
+                Ok(None)
+            })
     }
 
     /// Retrieves status of the cast device receiver.
@@ -352,11 +760,18 @@ where
     ///
     /// Returned `Result` should consist of either `Status` instance or an `Error`.
     pub fn get_status(&self) -> Result<Status, Error> {
-        let request_id = self.message_manager.generate_request_id().get();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "receiver.get_status",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %self.receiver
+        );
 
         let payload = serde_json::to_string(&proxies::receiver::GetStatusRequest {
             typ: MESSAGE_TYPE_GET_STATUS.to_string(),
-            request_id,
+            request_id: request_id.get(),
         })?;
 
         self.message_manager.send(CastMessage {
@@ -366,20 +781,36 @@ where
             payload: CastMessagePayload::String(payload),
         })?;
 
-        self.message_manager.receive_find_map(|message| {
-            if !self.can_handle(message) {
-                return Ok(None);
-            }
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
 
-            let message = self.parse(message)?;
-            if let ReceiverResponse::Status(status) = message {
-                if status.request_id == request_id {
-                    return Ok(Some(status));
+                let message = self.parse(message)?;
+                if let ReceiverResponse::Status(status) = message {
+                    if status.request_id == request_id {
+                        return Ok(Some(status));
+                    }
                 }
-            }
 
-            Ok(None)
-        })
+                Ok(None)
+            })
+    }
+
+    /// Returns the application that is currently running on the cast device, the idle screen
+    /// (`Backdrop`) application is not taken into account.
+    ///
+    /// # Return value
+    ///
+    /// Running `Application` or `None` if no application is running.
+    pub fn get_running_app(&self) -> Result<Option<Application>, Error> {
+        let status = self.get_status()?;
+
+        Ok(status
+            .applications
+            .into_iter()
+            .find(|app| app.app_id != APP_BACKDROP_ID))
     }
 
     /// Sets volume for the active cast device.
@@ -387,7 +818,7 @@ where
     /// # Arguments
     ///
     /// * `volume` - anything that can be converted to a valid `Volume` structure. It's possible to
-    ///              set volume level, mute/unmute state or both altogether.
+    ///   set volume level, mute/unmute state or both altogether.
     ///
     /// # Return value
     ///
@@ -400,16 +831,62 @@ where
     where
         T: Into<Volume>,
     {
-        let request_id = self.message_manager.generate_request_id().get();
-        let volume = volume.into();
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        self.send_volume(volume.into(), request_id)?;
 
-        let payload = serde_json::to_string(&proxies::receiver::SetVolumeRequest {
-            typ: MESSAGE_TYPE_SET_VOLUME.to_string(),
-            request_id,
-            volume: proxies::receiver::Volume {
-                level: volume.level,
-                muted: volume.muted,
-            },
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
+
+                let message = self.parse(message)?;
+                if let ReceiverResponse::Status(status) = message {
+                    if status.request_id == request_id {
+                        return Ok(Some(status.volume));
+                    }
+                }
+
+                Ok(None)
+            })
+    }
+
+    /// Puts the display of the cast device into stand by mode or wakes it up. Stand by is only
+    /// supported by devices that report their stand by state (see `Status::supports_standby`), for
+    /// the rest of the devices method fails without sending the command.
+    ///
+    /// # Arguments
+    ///
+    /// * `standby` - `true` to put the display to sleep, `false` to wake it up.
+    ///
+    /// # Return value
+    ///
+    /// Receiver `Status` returned by the device after the command has been processed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the device doesn't support stand by mode or rejects the command.
+    pub fn set_standby(&self, standby: bool) -> Result<Status, Error> {
+        if !self.get_status()?.supports_standby {
+            return Err(Error::Unsupported(
+                "cast device doesn't support stand by mode".to_string(),
+            ));
+        }
+
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "receiver.set_standby",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %self.receiver
+        );
+
+        let payload = serde_json::to_string(&proxies::receiver::SetStandbyRequest {
+            typ: MESSAGE_TYPE_SET_STANDBY.to_string(),
+            request_id: request_id.get(),
+            standby,
         })?;
 
         self.message_manager.send(CastMessage {
@@ -419,20 +896,168 @@ where
             payload: CastMessagePayload::String(payload),
         })?;
 
-        self.message_manager.receive_find_map(|message| {
-            if !self.can_handle(message) {
-                return Ok(None);
-            }
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
 
-            let message = self.parse(message)?;
-            if let ReceiverResponse::Status(status) = message {
-                if status.request_id == request_id {
-                    return Ok(Some(status.volume));
+                match self.parse(message)? {
+                    ReceiverResponse::Status(status) if status.request_id == request_id => {
+                        return Ok(Some(status));
+                    }
+                    ReceiverResponse::InvalidRequest(error) if error.request_id == request_id => {
+                        return Err(error.into());
+                    }
+                    _ => {}
                 }
-            }
 
-            Ok(None)
-        })
+                Ok(None)
+            })
+    }
+
+    /// Increases volume of the active cast device by its native volume step (`stepInterval`),
+    /// volume level is clamped to `1.0`.
+    ///
+    /// # Return value
+    ///
+    /// Actual `Volume` instance returned by receiver.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the device has fixed volume or doesn't report its current volume level.
+    pub fn volume_up(&self) -> Result<Volume, Error> {
+        self.step_volume(1.0)
+    }
+
+    /// Decreases volume of the active cast device by its native volume step (`stepInterval`),
+    /// volume level is clamped to `0.0`.
+    ///
+    /// # Return value
+    ///
+    /// Actual `Volume` instance returned by receiver.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the device has fixed volume or doesn't report its current volume level.
+    pub fn volume_down(&self) -> Result<Volume, Error> {
+        self.step_volume(-1.0)
+    }
+
+    /// Gradually changes volume level of the active cast device to `to_level` over the
+    /// `duration`, one volume request every 100 ms, see `fade_volume_in_steps`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use rust_cast::{CastDevice, message_manager::CancellationToken};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.connection.connect("receiver-0")?;
+    /// cast_device.receiver.fade_volume(0.0, Duration::from_secs(3))?;
+    ///
+    /// // Fade can be aborted from another thread with the cancellation token.
+    /// let token = CancellationToken::new();
+    /// cast_device.with_cancellation(&token, |device| {
+    ///     device.receiver.fade_volume(0.5, Duration::from_secs(10))
+    /// })?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    pub fn fade_volume(&self, to_level: f32, duration: Duration) -> Result<Volume, Error> {
+        let steps = duration
+            .as_millis()
+            .div_ceil(VOLUME_FADE_STEP_INTERVAL.as_millis())
+            .clamp(1, u128::from(u32::MAX));
+
+        self.fade_volume_in_steps(to_level, duration, steps as u32)
+    }
+
+    /// Gradually changes volume level of the active cast device to `to_level` over the
+    /// `duration` in `steps` evenly spaced `SET_VOLUME` requests. Requests are paced by the clock
+    /// rather than by the responses, so that slow responses don't stretch the fade, and only the
+    /// response of the last request is waited for. Fade is aborted between the requests once the
+    /// cancellation token is cancelled, see `CastDevice::with_cancellation`, volume stays at the
+    /// level of the last sent request then.
+    ///
+    /// # Arguments
+    ///
+    /// * `to_level` - Volume level the fade ends with, from `0.0` to `1.0`.
+    /// * `duration` - Duration of the fade.
+    /// * `steps` - Number of the volume requests, the last one sets `to_level`.
+    ///
+    /// # Return value
+    ///
+    /// Actual `Volume` instance returned by receiver for the last request.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::Validation` if `to_level` is out of range or `steps` is zero, with
+    /// `Error::Unsupported` if the device has fixed volume or doesn't report its current volume
+    /// level, and with `Error::Cancelled` (or `Error::Timeout`) if the fade has been aborted.
+    pub fn fade_volume_in_steps(
+        &self,
+        to_level: f32,
+        duration: Duration,
+        steps: u32,
+    ) -> Result<Volume, Error> {
+        if !(0.0..=1.0).contains(&to_level) {
+            return Err(Error::Validation(format!(
+                "volume level {to_level} is out of range 0.0 to 1.0"
+            )));
+        }
+        if steps == 0 {
+            return Err(Error::Validation(
+                "volume fade needs at least one step".to_string(),
+            ));
+        }
+
+        let from_level = self.changeable_volume()?.level.unwrap_or_default();
+        let started_at = Instant::now();
+        let mut pending = Vec::with_capacity(steps as usize);
+        // Only the response to the last request is waited for, so only its id is reserved.
+        let mut last_request = None;
+
+        for step in 1..=steps {
+            self.message_manager.wait_until(
+                started_at + duration.mul_f64(f64::from(step - 1) / f64::from(steps)),
+            )?;
+
+            let progress = step as f32 / steps as f32;
+            let level = from_level + (to_level - from_level) * progress;
+            let request_id = if step < steps {
+                self.message_manager.generate_request_id()
+            } else {
+                last_request
+                    .insert(self.message_manager.reserve_request_id())
+                    .id()
+            };
+            self.send_volume(Volume::from(level), request_id)?;
+            pending.push(request_id);
+        }
+
+        // Responses to the intermediate requests are consumed as well, so they don't pile up.
+        let last_id = pending[pending.len() - 1];
+        loop {
+            let (request_id, volume) =
+                self.message_manager.receive_response(last_id, |message| {
+                    if !self.can_handle(message) {
+                        return Ok(None);
+                    }
+
+                    match self.parse(message)? {
+                        ReceiverResponse::Status(status)
+                            if pending.contains(&status.request_id) =>
+                        {
+                            Ok(Some((status.request_id, status.volume)))
+                        }
+                        _ => Ok(None),
+                    }
+                })?;
+
+            if request_id == last_id {
+                return Ok(volume);
+            }
+        }
     }
 
     pub fn can_handle(&self, message: &CastMessage) -> bool {
@@ -440,16 +1065,7 @@ where
     }
 
     pub fn parse(&self, message: &CastMessage) -> Result<ReceiverResponse, Error> {
-        let reply = match message.payload {
-            CastMessagePayload::String(ref payload) => {
-                serde_json::from_str::<serde_json::Value>(payload)?
-            }
-            _ => {
-                return Err(Error::Internal(
-                    "Binary payload is not supported!".to_string(),
-                ))
-            }
-        };
+        let reply = message.payload.to_json()?;
 
         let message_type = reply
             .as_object()
@@ -464,7 +1080,7 @@ where
                     serde_json::value::from_value(reply)?;
 
                 let status = Status {
-                    request_id: status_reply.request_id,
+                    request_id: status_reply.request_id.into(),
                     applications: status_reply
                         .status
                         .applications
@@ -476,18 +1092,16 @@ where
                             namespaces: app
                                 .namespaces
                                 .iter()
-                                .map(|ns| ns.name.clone())
-                                .collect::<Vec<String>>(),
+                                .map(|ns| Namespace::new(ns.name.as_str()))
+                                .collect::<Vec<Namespace>>(),
                             display_name: app.display_name.clone(),
                             status_text: app.status_text.clone(),
                         })
                         .collect::<Vec<Application>>(),
-                    is_active_input: status_reply.status.is_active_input,
-                    is_stand_by: status_reply.status.is_stand_by,
-                    volume: Volume {
-                        level: status_reply.status.volume.level,
-                        muted: status_reply.status.volume.muted,
-                    },
+                    is_active_input: status_reply.status.is_active_input.unwrap_or_default(),
+                    is_stand_by: status_reply.status.is_stand_by.unwrap_or_default(),
+                    supports_standby: status_reply.status.is_stand_by.is_some(),
+                    volume: Volume::from(&status_reply.status.volume),
                 };
 
                 ReceiverResponse::Status(status)
@@ -497,8 +1111,12 @@ where
                     serde_json::value::from_value(reply)?;
 
                 ReceiverResponse::LaunchError(LaunchError {
-                    request_id: reply.request_id,
-                    reason: reply.reason,
+                    request_id: reply.request_id.into(),
+                    reason: reply
+                        .reason
+                        .as_deref()
+                        .map(LaunchErrorReason::from_str)
+                        .transpose()?,
                 })
             }
             MESSAGE_TYPE_INVALID_REQUEST => {
@@ -506,7 +1124,7 @@ where
                     serde_json::value::from_value(reply)?;
 
                 ReceiverResponse::InvalidRequest(InvalidRequest {
-                    request_id: reply.request_id,
+                    request_id: reply.request_id.into(),
                     reason: reply.reason,
                 })
             }
@@ -515,4 +1133,403 @@ where
 
         Ok(response)
     }
+
+    fn step_volume(&self, direction: f32) -> Result<Volume, Error> {
+        let volume = self.changeable_volume()?;
+        let level = volume.level.unwrap_or_default();
+        let step_interval = volume
+            .step_interval
+            .filter(|step_interval| *step_interval > 0.0)
+            .unwrap_or(DEFAULT_VOLUME_STEP_INTERVAL);
+
+        self.set_volume((level + direction * step_interval).clamp(0.0, 1.0))
+    }
+
+    /// Returns the current volume of the device, failing if it can't be changed or its level isn't
+    /// reported.
+    fn changeable_volume(&self) -> Result<Volume, Error> {
+        let volume = self.get_status()?.volume;
+
+        if volume.control_type == Some(VolumeControlType::Fixed) {
+            return Err(Error::Unsupported(
+                "cast device has fixed volume that can't be changed".to_string(),
+            ));
+        }
+        if volume.level.is_none() {
+            return Err(Error::Unsupported(
+                "cast device doesn't report its volume level".to_string(),
+            ));
+        }
+
+        Ok(volume)
+    }
+
+    /// Sends `SET_VOLUME` request with the `request_id` without waiting for the response, volume
+    /// level is checked against the policy first.
+    fn send_volume(&self, mut volume: Volume, request_id: RequestId) -> Result<(), Error> {
+        if let Some(level) = volume.level {
+            match self
+                .message_manager
+                .check_policy(&Operation::SetVolume { level })
+            {
+                Decision::Allow => {}
+                Decision::Block(reason) => return Err(Error::PolicyBlocked(reason)),
+                Decision::CapVolume(max_level) => volume.level = Some(level.min(max_level)),
+            }
+        }
+
+        trace_span!(
+            "receiver.set_volume",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %self.receiver
+        );
+
+        let payload = serde_json::to_string(&proxies::receiver::SetVolumeRequest {
+            typ: MESSAGE_TYPE_SET_VOLUME.to_string(),
+            request_id: request_id.get(),
+            volume: proxies::receiver::Volume {
+                level: volume.level,
+                muted: volume.muted,
+                step_interval: None,
+                control_type: None,
+            },
+        })?;
+
+        self.message_manager.send(CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: self.sender.to_string(),
+            destination: self.receiver.to_string(),
+            payload: CastMessagePayload::String(payload),
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cast::cast_channel::{
+            self,
+            cast_message::{PayloadType, ProtocolVersion},
+        },
+        message_manager::CancellationToken,
+        testing::MockTcpStream,
+        DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID,
+    };
+    use protobuf::EnumOrUnknown;
+
+    use super::*;
+
+    fn status_message(request_id: u32, volume: &str) -> cast_channel::CastMessage {
+        cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
+            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(format!(
+                r#"{{"requestId":{},"type":"{}","status":{{"volume":{}}}}}"#,
+                request_id, MESSAGE_TYPE_RECEIVER_STATUS, volume
+            )),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_get_running_app_ignores_backdrop() {
+        let mut stream = MockTcpStream::new();
+        let mut message = status_message(1, r#"{"level":0.5,"muted":false}"#);
+        message.payload_utf8 = Some(format!(
+            r#"{{"requestId":1,"type":"{}","status":{{"applications":[
+                {{"appId":"{}","sessionId":"1","transportId":"backdrop-1"}},
+                {{"appId":"{}","sessionId":"2","transportId":"web-2"}}
+            ]}}}}"#,
+            MESSAGE_TYPE_RECEIVER_STATUS, APP_BACKDROP_ID, APP_DEFAULT_MEDIA_RECEIVER_ID
+        ));
+        stream.add_message(message);
+        let channel = ReceiverChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(stream)),
+        );
+
+        let app = channel
+            .get_running_app()
+            .unwrap()
+            .expect("expected running app");
+
+        assert_eq!(APP_DEFAULT_MEDIA_RECEIVER_ID, app.app_id);
+        assert_eq!("web-2", app.transport_id);
+    }
+
+    #[test]
+    fn test_launch_app_with_credentials() {
+        let mut stream = MockTcpStream::new();
+        let mut message = status_message(1, r#"{"level":0.5,"muted":false}"#);
+        message.payload_utf8 = Some(format!(
+            r#"{{"requestId":1,"type":"{}","status":{{"applications":[
+                {{"appId":"CC32E753","sessionId":"1","transportId":"web-1"}}
+            ]}}}}"#,
+            MESSAGE_TYPE_RECEIVER_STATUS
+        ));
+        stream.add_message(message);
+        let channel = ReceiverChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let app = channel
+            .launch_app_with_credentials(
+                &CastDeviceApp::Custom("CC32E753".to_string()),
+                &Credentials::new("access-token"),
+            )
+            .unwrap();
+
+        assert_eq!("web-1", app.transport_id);
+        let request = stream.received_message(0).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!(
+            serde_json::json!({"credentials": "access-token"}),
+            request["credentialsData"]
+        );
+    }
+
+    #[test]
+    fn test_set_standby_requires_support() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(status_message(1, r#"{"level":0.5,"muted":false}"#));
+        let channel = ReceiverChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        assert!(matches!(
+            channel.set_standby(true),
+            Err(Error::Unsupported(_))
+        ));
+        // Only the status request should have been sent.
+        assert!(stream.received_message(1).is_none());
+    }
+
+    #[test]
+    fn test_volume_up_uses_step_interval() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(status_message(
+            1,
+            r#"{"level":0.95,"muted":false,"stepInterval":0.1,"controlType":"attenuation"}"#,
+        ));
+        stream.add_message(status_message(
+            2,
+            r#"{"level":1.0,"muted":false,"stepInterval":0.1,"controlType":"attenuation"}"#,
+        ));
+        let channel = ReceiverChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let volume = channel.volume_up().unwrap();
+
+        assert_eq!(Some(1.0), volume.level);
+        assert_eq!(Some(0.1), volume.step_interval);
+        assert_eq!(Some(VolumeControlType::Attenuation), volume.control_type);
+
+        let request = stream
+            .received_message(1)
+            .expect("expected volume request to have been sent")
+            .message();
+        let request = serde_json::from_str::<serde_json::Value>(request.payload_utf8()).unwrap();
+        assert_eq!(MESSAGE_TYPE_SET_VOLUME, request["type"]);
+        assert_eq!(1.0, request["volume"]["level"]);
+    }
+
+    #[test]
+    fn test_volume_down_fails_for_fixed_volume() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(status_message(
+            1,
+            r#"{"level":1.0,"muted":false,"controlType":"fixed"}"#,
+        ));
+        let channel = ReceiverChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(stream)),
+        );
+
+        assert!(matches!(channel.volume_down(), Err(Error::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_fade_volume() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(status_message(1, r#"{"level":0.8,"muted":false}"#));
+        for (request_id, level) in [(2, 0.7), (3, 0.6), (4, 0.5), (5, 0.4)] {
+            stream.add_message(status_message(
+                request_id,
+                &format!(r#"{{"level":{level},"muted":false}}"#),
+            ));
+        }
+        let message_manager = Lrc::new(MessageManager::new(stream.clone()));
+        let channel = ReceiverChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::clone(&message_manager),
+        );
+
+        assert!(matches!(
+            channel.fade_volume_in_steps(1.5, Duration::ZERO, 1),
+            Err(Error::Validation(_))
+        ));
+
+        let volume = channel
+            .fade_volume_in_steps(0.4, Duration::from_millis(30), 4)
+            .unwrap();
+        assert_eq!(Some(0.4), volume.level);
+
+        let levels = (1..=4)
+            .map(|index| {
+                let request = stream.received_message(index).unwrap().message();
+                let request =
+                    serde_json::from_str::<serde_json::Value>(request.payload_utf8()).unwrap();
+                assert_eq!(MESSAGE_TYPE_SET_VOLUME, request["type"]);
+                (request["volume"]["level"].as_f64().unwrap() * 100.).round() as u32
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![70, 60, 50, 40], levels);
+
+        // Fade is aborted between the requests.
+        stream.add_message(status_message(6, r#"{"level":0.4,"muted":false}"#));
+        let token = CancellationToken::with_timeout(Duration::from_millis(20));
+        let result = message_manager.with_cancellation(&token, || {
+            channel.fade_volume_in_steps(1.0, Duration::from_secs(10), 2)
+        });
+        assert!(matches!(result, Err(Error::Timeout(_))));
+        assert!(stream.received_message(6).is_some());
+        assert!(stream.received_message(7).is_none());
+    }
+
+    #[test]
+    fn test_parse_status_with_missing_fields() {
+        let message = CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(format!(
+                r#"{{
+                "requestId":1,
+                "type":"{}",
+                "status":{{
+                    "applications":[{{"appId":"MultizoneLeader","sessionId":"1234"}}]
+                }}
+            }}"#,
+                MESSAGE_TYPE_RECEIVER_STATUS
+            )),
+        };
+        let channel = ReceiverChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
+
+        let status = match channel.parse(&message).unwrap() {
+            ReceiverResponse::Status(status) => status,
+            response => panic!("unexpected response {:?}", response),
+        };
+
+        assert_eq!(1, status.applications.len());
+        assert_eq!("MultizoneLeader", status.applications[0].app_id);
+        assert_eq!("", status.applications[0].display_name);
+        assert!(!status.is_active_input);
+        assert!(!status.supports_standby);
+        assert!(status.is_audio_device());
+        assert_eq!(None, status.volume.level);
+    }
+
+    #[test]
+    fn test_parse_application_namespaces() {
+        let message = CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(format!(
+                r#"{{"requestId":1,"type":"{}","status":{{"applications":[
+                    {{"appId":"CC1AD845","sessionId":"1","transportId":"web-1","namespaces":[
+                        {{"name":"urn:x-cast:com.google.cast.media"}},
+                        {{"name":"urn:x-cast:com.example.custom"}}]}},
+                    {{"appId":"E8C28D3C","sessionId":"2","transportId":"backdrop-2"}}
+                ]}}}}"#,
+                MESSAGE_TYPE_RECEIVER_STATUS
+            )),
+        };
+        let channel = ReceiverChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(MockTcpStream::new())),
+        );
+
+        let status = match channel.parse(&message).unwrap() {
+            ReceiverResponse::Status(status) => status,
+            response => panic!("unexpected response {:?}", response),
+        };
+
+        let media_receiver = &status.applications[0];
+        assert!(media_receiver.namespaces[0].is_media());
+        assert_eq!(
+            "urn:x-cast:com.example.custom",
+            media_receiver.namespaces[1]
+        );
+        assert!(media_receiver.supports_media());
+        assert!(media_receiver.supports("urn:x-cast:com.example.custom"));
+        assert!(!status.applications[1].supports_media());
+    }
+
+    #[test]
+    fn test_status_diff() {
+        let app = |app_id: &str, session_id: &str| Application {
+            app_id: app_id.to_string(),
+            session_id: session_id.to_string(),
+            transport_id: format!("web-{session_id}"),
+            namespaces: Vec::new(),
+            display_name: String::new(),
+            status_text: String::new(),
+        };
+        let old = Status {
+            request_id: 1.into(),
+            applications: vec![app(APP_BACKDROP_ID, "1")],
+            is_active_input: false,
+            is_stand_by: true,
+            supports_standby: true,
+            volume: Volume::from(0.5),
+        };
+        let mut new = Status {
+            request_id: 2.into(),
+            applications: vec![app(APP_DEFAULT_MEDIA_RECEIVER_ID, "2")],
+            is_stand_by: false,
+            volume: Volume::from(0.7),
+            ..old.clone()
+        };
+
+        assert_eq!(
+            vec![
+                StatusChange::AppStopped(app(APP_BACKDROP_ID, "1")),
+                StatusChange::AppLaunched(app(APP_DEFAULT_MEDIA_RECEIVER_ID, "2")),
+                StatusChange::VolumeChanged {
+                    old: Volume::from(0.5),
+                    new: Volume::from(0.7),
+                },
+                StatusChange::StandbyChanged(false),
+            ],
+            Status::diff(&old, &new)
+        );
+
+        // Request id and status text changes aren't reported.
+        let old = new.clone();
+        new.request_id = 3.into();
+        new.applications[0].status_text = "Casting".to_string();
+        assert!(Status::diff(&old, &new).is_empty());
+    }
 }
@@ -0,0 +1,391 @@
+use std::io::{Read, Write};
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    cast::proxies,
+    errors::Error,
+    message_manager::{CastMessage, CastMessagePayload, MessageManager, RequestId},
+    namespaces, Lrc,
+};
+
+pub(crate) const CHANNEL_NAMESPACE: &str = namespaces::SETUP;
+
+const MESSAGE_TYPE_EUREKA_INFO: &str = "eureka_info";
+const MESSAGE_TYPE_SET_EUREKA_INFO: &str = "set_eureka_info";
+const MESSAGE_TYPE_REBOOT: &str = "reboot";
+
+/// Fields of the device settings requested with `SetupChannel::get_info`.
+const EUREKA_INFO_PARAMS: &str = "name,device_info,opt_in";
+
+/// Response code of the successfully handled request.
+const RESPONSE_CODE_OK: u16 = 200;
+
+/// Settings operations the cast device allows, see `SetupChannel::capabilities`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetupCapabilities {
+    /// Whether the device can be renamed.
+    pub rename: bool,
+    /// Whether visibility of the device to the guests can be toggled.
+    pub guest_mode: bool,
+    /// Whether the device can be rebooted remotely.
+    pub reboot: bool,
+}
+
+/// Device settings reported over the setup namespace.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SetupInfo {
+    /// Name of the device set by the user.
+    pub name: Option<String>,
+    /// Whether the device is visible to the guests (nearby devices not on the same network),
+    /// `None` if the device doesn't report it.
+    pub guest_mode: Option<bool>,
+    /// Settings operations the device allows.
+    pub capabilities: SetupCapabilities,
+    /// Raw data of the reply, for the settings not covered by the fields above.
+    pub data: serde_json::Value,
+}
+
+impl From<serde_json::Value> for SetupInfo {
+    fn from(data: serde_json::Value) -> Self {
+        let name = data["name"].as_str().map(str::to_string);
+        let guest_mode = data["opt_in"]["opencast"].as_bool();
+        let capabilities = &data["device_info"]["capabilities"];
+
+        SetupInfo {
+            capabilities: SetupCapabilities {
+                rename: name.is_some() && capabilities["setup_supported"].as_bool() != Some(false),
+                guest_mode: guest_mode.is_some(),
+                reboot: capabilities["reboot_supported"].as_bool().unwrap_or(false),
+            },
+            name,
+            guest_mode,
+            data,
+        }
+    }
+}
+
+/// Reply to the setup request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SetupReply {
+    /// Unique id of the request the reply belongs to.
+    pub request_id: RequestId,
+    /// Type of the request the reply belongs to.
+    pub typ: String,
+    /// HTTP-like response code, `200` if the request has been handled successfully.
+    pub response_code: u16,
+    /// Description of the response code if available.
+    pub response_string: Option<String>,
+    /// Data of the reply.
+    pub data: serde_json::Value,
+}
+
+/// Represents all currently supported incoming messages that setup channel can handle.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SetupResponse {
+    /// Reply to the setup request.
+    Reply(SetupReply),
+    /// Used every time we can't parse the message. Associated data contains `type` string field
+    /// and raw JSON data returned from cast device.
+    NotImplemented(String, serde_json::Value),
+}
+
+/// Channel that manages device settings (name, guest mode, reboot) over the setup namespace.
+/// Operations are only sent if the device reports that it allows them, see `capabilities`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use rust_cast::CastDevice;
+/// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// if cast_device.setup.capabilities()?.rename {
+///     cast_device.setup.set_name("Living Room TV")?;
+/// }
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+pub struct SetupChannel<W>
+where
+    W: Read + Write,
+{
+    sender: String,
+    receiver: String,
+    message_manager: Lrc<MessageManager<W>>,
+}
+
+/// Cloned channel shares the connection with the original one.
+impl<W> Clone for SetupChannel<W>
+where
+    W: Read + Write,
+{
+    fn clone(&self) -> Self {
+        SetupChannel {
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+            message_manager: Lrc::clone(&self.message_manager),
+        }
+    }
+}
+
+impl<W> SetupChannel<W>
+where
+    W: Read + Write,
+{
+    pub fn new<S>(
+        sender: S,
+        receiver: S,
+        message_manager: Lrc<MessageManager<W>>,
+    ) -> SetupChannel<W>
+    where
+        S: Into<String>,
+    {
+        SetupChannel {
+            sender: sender.into(),
+            receiver: receiver.into(),
+            message_manager,
+        }
+    }
+
+    /// Retrieves the device settings (name, guest mode) along with the operations it allows.
+    pub fn get_info(&self) -> Result<SetupInfo, Error> {
+        let reply = self.request(
+            MESSAGE_TYPE_EUREKA_INFO,
+            json!({ "params": EUREKA_INFO_PARAMS }),
+        )?;
+
+        Ok(SetupInfo::from(reply.data))
+    }
+
+    /// Retrieves settings operations the device allows.
+    pub fn capabilities(&self) -> Result<SetupCapabilities, Error> {
+        Ok(self.get_info()?.capabilities)
+    }
+
+    /// Renames the device.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - New name of the device.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the device doesn't allow renaming or rejects the new name.
+    pub fn set_name(&self, name: &str) -> Result<(), Error> {
+        self.require(|capabilities| capabilities.rename, "renaming")?;
+        self.request(MESSAGE_TYPE_SET_EUREKA_INFO, json!({ "name": name }))?;
+
+        Ok(())
+    }
+
+    /// Makes the device visible (or invisible) to the guests, i.e. nearby users that aren't
+    /// connected to the same network.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the device doesn't support guest mode.
+    pub fn set_guest_mode(&self, enabled: bool) -> Result<(), Error> {
+        self.require(|capabilities| capabilities.guest_mode, "guest mode")?;
+        self.request(
+            MESSAGE_TYPE_SET_EUREKA_INFO,
+            json!({ "opt_in": { "opencast": enabled } }),
+        )?;
+
+        Ok(())
+    }
+
+    /// Reboots the device, the connection is closed by the device shortly afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the device doesn't allow remote reboot.
+    pub fn reboot(&self) -> Result<(), Error> {
+        self.require(|capabilities| capabilities.reboot, "reboot")?;
+        self.request(MESSAGE_TYPE_REBOOT, json!({ "params": "now" }))?;
+
+        Ok(())
+    }
+
+    pub fn can_handle(&self, message: &CastMessage) -> bool {
+        message.namespace == CHANNEL_NAMESPACE
+    }
+
+    pub fn parse(&self, message: &CastMessage) -> Result<SetupResponse, Error> {
+        let reply = message.payload.to_json()?;
+
+        let message_type = reply
+            .as_object()
+            .and_then(|object| object.get("type"))
+            .and_then(|property| property.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        let response = match message_type.as_ref() {
+            MESSAGE_TYPE_EUREKA_INFO | MESSAGE_TYPE_SET_EUREKA_INFO | MESSAGE_TYPE_REBOOT => {
+                let reply: proxies::setup::SetupReply = serde_json::value::from_value(reply)?;
+
+                SetupResponse::Reply(SetupReply {
+                    request_id: reply.request_id.into(),
+                    typ: reply.typ,
+                    // Replies without response code are considered successful.
+                    response_code: reply.response_code.unwrap_or(RESPONSE_CODE_OK),
+                    response_string: reply.response_string,
+                    data: reply.data,
+                })
+            }
+            _ => SetupResponse::NotImplemented(message_type.to_string(), reply),
+        };
+
+        Ok(response)
+    }
+
+    /// Fails with `Error::Unsupported` unless `is_allowed` is true for the device capabilities.
+    fn require<F>(&self, is_allowed: F, operation: &str) -> Result<(), Error>
+    where
+        F: Fn(&SetupCapabilities) -> bool,
+    {
+        if is_allowed(&self.capabilities()?) {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(format!(
+                "cast device doesn't allow {operation}"
+            )))
+        }
+    }
+
+    /// Sends setup request of the `typ` type with `data` and waits for the successful reply.
+    fn request(&self, typ: &str, data: serde_json::Value) -> Result<SetupReply, Error> {
+        let request = self.message_manager.reserve_request_id();
+        let request_id = request.id();
+        trace_span!(
+            "setup.request",
+            namespace = CHANNEL_NAMESPACE,
+            request_id = request_id.get(),
+            destination = %self.receiver,
+            typ
+        );
+
+        let payload = serde_json::to_string(&proxies::setup::SetupRequest {
+            request_id: request_id.get(),
+            typ: typ.to_string(),
+            data,
+        })?;
+
+        self.message_manager.send(CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: self.sender.to_string(),
+            destination: self.receiver.to_string(),
+            payload: CastMessagePayload::String(payload),
+        })?;
+
+        self.message_manager
+            .receive_response(request_id, |message| {
+                if !self.can_handle(message) {
+                    return Ok(None);
+                }
+
+                match self.parse(message)? {
+                    SetupResponse::Reply(reply) if reply.request_id == request_id => {
+                        if reply.response_code == RESPONSE_CODE_OK {
+                            Ok(Some(reply))
+                        } else {
+                            Err(Error::InvalidRequest {
+                                request_id,
+                                reason: reply.response_string,
+                            })
+                        }
+                    }
+                    _ => Ok(None),
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cast::cast_channel::{
+            self,
+            cast_message::{PayloadType, ProtocolVersion},
+        },
+        testing::MockTcpStream,
+        DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID,
+    };
+    use protobuf::EnumOrUnknown;
+
+    fn reply_message(payload: &str) -> cast_channel::CastMessage {
+        cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
+            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(payload.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_set_name() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(reply_message(
+            r#"{"type":"eureka_info","request_id":1,"response_code":200,"data":{
+                "name":"Kitchen","opt_in":{"opencast":false},
+                "device_info":{"capabilities":{"reboot_supported":false}}}}"#,
+        ));
+        stream.add_message(reply_message(
+            r#"{"type":"set_eureka_info","request_id":2,"response_code":200}"#,
+        ));
+        let channel = SetupChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        channel.set_name("Living Room").unwrap();
+
+        let request = stream.received_message(1).unwrap().cast_message();
+        let request = request.payload.to_json().unwrap();
+        assert_eq!(MESSAGE_TYPE_SET_EUREKA_INFO, request["type"]);
+        assert_eq!(2, request["request_id"]);
+        assert_eq!("Living Room", request["data"]["name"]);
+    }
+
+    #[test]
+    fn test_reboot_requires_capability() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(reply_message(
+            r#"{"type":"eureka_info","request_id":1,"data":{"name":"Kitchen"}}"#,
+        ));
+        let channel = SetupChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        assert!(matches!(channel.reboot(), Err(Error::Unsupported(_))));
+        // Only the info request should have been sent.
+        assert!(stream.received_message(1).is_none());
+    }
+
+    #[test]
+    fn test_request_fails_on_error_response_code() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(reply_message(
+            r#"{"type":"eureka_info","request_id":1,"response_code":403,
+                "response_string":"Forbidden"}"#,
+        ));
+        let channel = SetupChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(stream)),
+        );
+
+        match channel.get_info() {
+            Err(Error::InvalidRequest { reason, .. }) => {
+                assert_eq!(Some("Forbidden".to_string()), reason)
+            }
+            result => panic!("unexpected result {result:?}"),
+        }
+    }
+}
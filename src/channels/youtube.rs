@@ -0,0 +1,551 @@
+//! Channel of the YouTube receiver application, requires `youtube` feature. YouTube doesn't
+//! accept plain media `LOAD` requests reliably, instead the sender gets the screen id of the
+//! receiver over the `mdx` namespace and then controls the playback through the YouTube Lounge
+//! API (`www.youtube.com`), the same way the official senders do.
+
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::TcpStream,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rustls::{pki_types::ServerName, ClientConfig, ClientConnection, StreamOwned};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{
+    cast::proxies,
+    errors::Error,
+    message_manager::{CastMessage, CastMessagePayload, Lock, MessageManager},
+    namespaces, Lrc,
+};
+
+pub(crate) const CHANNEL_NAMESPACE: &str = namespaces::YOUTUBE;
+
+const MESSAGE_TYPE_GET_MDX_SESSION_STATUS: &str = "getMdxSessionStatus";
+
+const MESSAGE_TYPE_MDX_SESSION_STATUS: &str = "mdxSessionStatus";
+
+/// Host that serves YouTube Lounge API.
+const LOUNGE_HOST: &str = "www.youtube.com";
+const LOUNGE_TOKEN_PATH: &str = "/api/lounge/pairing/get_lounge_token_batch";
+const LOUNGE_BIND_PATH: &str = "/api/lounge/bc/bind";
+
+/// Name of the sender the receiver displays (e.g. in the "connected devices" list).
+const LOUNGE_SENDER_NAME: &str = "RustCast";
+
+/// Default time to wait for the YouTube Lounge API to respond.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Status of the `mdx` session of the YouTube receiver application.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MdxSessionStatus {
+    /// Id of the screen the Lounge API session is bound to.
+    pub screen_id: String,
+    /// Id of the receiver device, if reported.
+    pub device_id: Option<String>,
+}
+
+/// Represents all currently supported incoming messages that youtube channel can handle.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum YouTubeResponse {
+    /// Status of the `mdx` session.
+    MdxSessionStatus(MdxSessionStatus),
+    /// Used every time we can't parse the message. Associated data contains `type` string field
+    /// and raw JSON data returned from cast device.
+    NotImplemented(String, serde_json::Value),
+}
+
+/// Session of the YouTube Lounge API bound to the particular screen.
+#[derive(Clone, Debug)]
+struct LoungeSession {
+    screen_id: String,
+    lounge_token: String,
+    sid: String,
+    gsessionid: String,
+    /// Id of the next request within the session.
+    rid: u32,
+    /// Number of the commands sent within the session.
+    ofs: u32,
+}
+
+/// Command sent to the receiver through the Lounge API session.
+enum LoungeCommand<'c> {
+    SetPlaylist(&'c str),
+    AddVideo(&'c str),
+}
+
+impl LoungeCommand<'_> {
+    fn form_fields(&self) -> Vec<(&'static str, &str)> {
+        match self {
+            LoungeCommand::SetPlaylist(video_id) => vec![
+                ("req0__sc", "setPlaylist"),
+                ("req0_videoId", video_id),
+                ("req0_currentTime", "0"),
+                ("req0_currentIndex", "-1"),
+                ("req0_audioOnly", "false"),
+                ("req0_params", ""),
+                ("req0_playerParams", ""),
+                (
+                    "req0_prioritizeMobileSenderPlaybackStateOnConnection",
+                    "true",
+                ),
+            ],
+            LoungeCommand::AddVideo(video_id) => vec![
+                ("req0__sc", "addVideo"),
+                ("req0_videoId", video_id),
+                ("req0_videoSources", "XX"),
+            ],
+        }
+    }
+}
+
+/// Channel that controls YouTube receiver application.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_cast::{channels::receiver::CastDeviceApp, CastDevice};
+///
+/// let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// let app = cast_device.receiver.launch_app(&CastDeviceApp::YouTube)?;
+/// cast_device.connection.connect(app.transport_id.as_str())?;
+///
+/// cast_device.youtube.play_video(app.transport_id.as_str(), "7LcUOEP7Brc")?;
+/// cast_device.youtube.add_to_queue(app.transport_id.as_str(), "dQw4w9WgXcQ")?;
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+pub struct YouTubeChannel<W>
+where
+    W: Read + Write,
+{
+    sender: String,
+    message_manager: Lrc<MessageManager<W>>,
+    lounge_session: Lrc<Lock<Option<LoungeSession>>>,
+}
+
+/// Cloned channel shares the connection and the tracked state with the original one.
+impl<W> Clone for YouTubeChannel<W>
+where
+    W: Read + Write,
+{
+    fn clone(&self) -> Self {
+        YouTubeChannel {
+            sender: self.sender.clone(),
+            message_manager: Lrc::clone(&self.message_manager),
+            lounge_session: Lrc::clone(&self.lounge_session),
+        }
+    }
+}
+
+impl<W> YouTubeChannel<W>
+where
+    W: Read + Write,
+{
+    pub fn new<S>(sender: S, message_manager: Lrc<MessageManager<W>>) -> YouTubeChannel<W>
+    where
+        S: Into<String>,
+    {
+        YouTubeChannel {
+            sender: sender.into(),
+            message_manager,
+            lounge_session: Lrc::new(Lock::new(None)),
+        }
+    }
+
+    /// Retrieves status of the `mdx` session of the YouTube application.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `protocol` of the YouTube application (e.g. `web-1`).
+    ///
+    /// # Return value
+    ///
+    /// Status of the `mdx` session, including the screen id of the receiver.
+    pub fn get_mdx_session_status<S>(&self, destination: S) -> Result<MdxSessionStatus, Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        trace_span!(
+            "youtube.get_mdx_session_status",
+            namespace = CHANNEL_NAMESPACE,
+            destination = %destination
+        );
+
+        let payload = serde_json::to_string(&proxies::youtube::GetMdxSessionStatusRequest {
+            typ: MESSAGE_TYPE_GET_MDX_SESSION_STATUS.to_string(),
+        })?;
+
+        self.message_manager.send(CastMessage {
+            namespace: CHANNEL_NAMESPACE.to_string(),
+            source: self.sender.to_string(),
+            destination: destination.to_string(),
+            payload: CastMessagePayload::String(payload),
+        })?;
+
+        // Mdx messages don't carry request id, so the status is matched by its source.
+        self.message_manager.receive_find_map(|message| {
+            if !self.can_handle(message) || message.source != destination {
+                return Ok(None);
+            }
+
+            match self.parse(message)? {
+                YouTubeResponse::MdxSessionStatus(status) => Ok(Some(status)),
+                _ => Ok(None),
+            }
+        })
+    }
+
+    /// Starts playback of the YouTube video, replacing the current playlist.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `protocol` of the YouTube application (e.g. `web-1`);
+    /// * `video_id` - ID of the YouTube video (e.g. `7LcUOEP7Brc`).
+    ///
+    /// # Errors
+    ///
+    /// Fails if the screen id can't be retrieved from the application or the YouTube Lounge API
+    /// can't be reached.
+    pub fn play_video<S>(&self, destination: S, video_id: &str) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        trace_span!(
+            "youtube.play_video",
+            namespace = CHANNEL_NAMESPACE,
+            destination = %destination
+        );
+
+        self.send_lounge_command(destination, LoungeCommand::SetPlaylist(video_id))
+    }
+
+    /// Adds the YouTube video to the end of the current playlist.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - `protocol` of the YouTube application (e.g. `web-1`);
+    /// * `video_id` - ID of the YouTube video (e.g. `7LcUOEP7Brc`).
+    ///
+    /// # Errors
+    ///
+    /// Fails if the screen id can't be retrieved from the application or the YouTube Lounge API
+    /// can't be reached.
+    pub fn add_to_queue<S>(&self, destination: S, video_id: &str) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        trace_span!(
+            "youtube.add_to_queue",
+            namespace = CHANNEL_NAMESPACE,
+            destination = %destination
+        );
+
+        self.send_lounge_command(destination, LoungeCommand::AddVideo(video_id))
+    }
+
+    pub fn can_handle(&self, message: &CastMessage) -> bool {
+        message.namespace == CHANNEL_NAMESPACE
+    }
+
+    pub fn parse(&self, message: &CastMessage) -> Result<YouTubeResponse, Error> {
+        let reply = message.payload.to_json()?;
+
+        let message_type = reply
+            .as_object()
+            .and_then(|object| object.get("type"))
+            .and_then(|property| property.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        let response = match message_type.as_ref() {
+            MESSAGE_TYPE_MDX_SESSION_STATUS => {
+                let reply: proxies::youtube::MdxSessionStatusReply =
+                    serde_json::value::from_value(reply)?;
+
+                YouTubeResponse::MdxSessionStatus(MdxSessionStatus {
+                    screen_id: reply.data.screen_id,
+                    device_id: reply.data.device_id,
+                })
+            }
+            _ => YouTubeResponse::NotImplemented(message_type.to_string(), reply),
+        };
+
+        Ok(response)
+    }
+
+    /// Sends `command` through the Lounge API session bound to the screen of the application at
+    /// `destination`, the session is (re-)established if needed.
+    fn send_lounge_command(
+        &self,
+        destination: String,
+        command: LoungeCommand,
+    ) -> Result<(), Error> {
+        let screen_id = self.get_mdx_session_status(destination)?.screen_id;
+
+        let mut lounge_session = self.lounge_session.borrow_mut();
+        if let Some(session) = lounge_session
+            .as_mut()
+            .filter(|session| session.screen_id == screen_id)
+        {
+            match send_command(session, &command) {
+                Ok(()) => return Ok(()),
+                // Session may have expired, let's try once again with the new one.
+                Err(err) => log::debug!("Lounge session command failed, rebinding: {err}"),
+            }
+        }
+
+        let mut session = bind(&screen_id)?;
+        send_command(&mut session, &command)?;
+        *lounge_session = Some(session);
+
+        Ok(())
+    }
+}
+
+/// Retrieves the lounge token for the screen and binds the new Lounge API session to it.
+fn bind(screen_id: &str) -> Result<LoungeSession, Error> {
+    let reply = lounge_post(
+        LOUNGE_TOKEN_PATH,
+        &form_encode(&[("screen_ids", screen_id)]),
+    )?;
+    let reply: proxies::youtube::LoungeTokenBatchReply = serde_json::from_str(&reply)?;
+    let lounge_token = reply
+        .screens
+        .into_iter()
+        .find(|screen| screen.screen_id == screen_id)
+        .map(|screen| screen.lounge_token)
+        .ok_or_else(|| Error::Parsing(format!("No lounge token for screen {screen_id}.")))?;
+
+    let rid = 1;
+    let device_id = sender_device_id();
+    let query = form_encode(&[
+        ("RID", &rid.to_string()),
+        ("VER", "8"),
+        ("CVER", "1"),
+        ("name", LOUNGE_SENDER_NAME),
+        ("app", "youtube-desktop"),
+        ("device", "REMOTE_CONTROL"),
+        ("id", &device_id),
+        ("loungeIdToken", &lounge_token),
+        ("mdx-version", "3"),
+        ("pairing_type", "cast"),
+        ("theme", "cl"),
+    ]);
+    let reply = lounge_post(&format!("{LOUNGE_BIND_PATH}?{query}"), "count=0")?;
+    let (sid, gsessionid) = parse_bind_response(&reply)?;
+
+    Ok(LoungeSession {
+        screen_id: screen_id.to_string(),
+        lounge_token,
+        sid,
+        gsessionid,
+        rid: rid + 1,
+        ofs: 0,
+    })
+}
+
+/// Sends `command` within the bound Lounge API `session`.
+fn send_command(session: &mut LoungeSession, command: &LoungeCommand) -> Result<(), Error> {
+    let query = form_encode(&[
+        ("SID", &session.sid),
+        ("gsessionid", &session.gsessionid),
+        ("RID", &session.rid.to_string()),
+        ("VER", "8"),
+        ("CVER", "1"),
+        ("loungeIdToken", &session.lounge_token),
+    ]);
+
+    let ofs = session.ofs.to_string();
+    let mut fields = vec![("count", "1"), ("ofs", ofs.as_str())];
+    fields.extend(command.form_fields());
+
+    session.rid += 1;
+    lounge_post(
+        &format!("{LOUNGE_BIND_PATH}?{query}"),
+        &form_encode(&fields),
+    )?;
+    session.ofs += 1;
+
+    Ok(())
+}
+
+/// Extracts session id (`SID`) and `gsessionid` from the response to the bind request, it consists
+/// of the length-prefixed JSON arrays, e.g. `[[0,["c","<SID>","",8]],[1,["S","<gsessionid>"]]]`.
+fn parse_bind_response(response: &str) -> Result<(String, String), Error> {
+    let field = |prefix: &str| {
+        response.find(prefix).and_then(|start| {
+            let value = &response[start + prefix.len()..];
+            value.find('"').map(|end| value[..end].to_string())
+        })
+    };
+
+    match (field(r#"["c",""#), field(r#"["S",""#)) {
+        (Some(sid), Some(gsessionid)) => Ok((sid, gsessionid)),
+        _ => Err(Error::Parsing(
+            "Lounge bind response is missing session ids.".to_string(),
+        )),
+    }
+}
+
+/// Generates id the sender is identified with within the Lounge API sessions.
+fn sender_device_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+
+    format!("rust-cast-{nanos:x}")
+}
+
+/// Encodes `fields` as `application/x-www-form-urlencoded` string.
+fn form_encode(fields: &[(&str, &str)]) -> String {
+    let encode = |value: &str| {
+        value
+            .bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    (byte as char).to_string()
+                }
+                _ => format!("%{byte:02X}"),
+            })
+            .collect::<String>()
+    };
+
+    fields
+        .iter()
+        .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Sends form `body` to the YouTube Lounge API `path` and returns body of the response.
+fn lounge_post(path: &str, body: &str) -> Result<String, Error> {
+    let config = ClientConfig::builder_with_provider(crate::crypto_provider(None)?)
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(crate::native_root_store(&[])?)
+        .with_no_client_auth();
+    let connection = ClientConnection::new(Arc::new(config), ServerName::try_from(LOUNGE_HOST)?)?;
+
+    let tcp_stream = TcpStream::connect((LOUNGE_HOST, 443))?;
+    tcp_stream.set_read_timeout(Some(DEFAULT_TIMEOUT))?;
+    tcp_stream.set_write_timeout(Some(DEFAULT_TIMEOUT))?;
+    let mut stream = StreamOwned::new(connection, tcp_stream);
+
+    // HTTP/1.0 guarantees that response isn't chunked and connection is closed once it's sent.
+    write!(
+        stream,
+        "POST {path} HTTP/1.0\r\nHost: {LOUNGE_HOST}\r\n\
+         Content-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()?;
+
+    let mut response = vec![];
+    match stream.read_to_end(&mut response) {
+        // Server may close connection without TLS `close_notify`.
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof && !response.is_empty() => {}
+        result => {
+            result?;
+        }
+    }
+
+    parse_http_response(&response)
+}
+
+/// Returns body of the raw HTTP response, unless response status isn't successful.
+fn parse_http_response(response: &[u8]) -> Result<String, Error> {
+    let separator = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| Error::Parsing("Malformed Lounge API HTTP response.".to_string()))?;
+
+    let head = String::from_utf8_lossy(&response[..separator]);
+    let status_line = head.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        return Err(Error::Parsing(format!(
+            "Unexpected Lounge API response status: {status_line}"
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&response[separator + 4..]).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cast::cast_channel::cast_message::{PayloadType, ProtocolVersion},
+        testing::MockTcpStream,
+        DEFAULT_SENDER_ID,
+    };
+    use protobuf::EnumOrUnknown;
+
+    #[test]
+    fn test_get_mdx_session_status() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(crate::cast::cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some("web-7".to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(format!(
+                r#"{{"type":"{MESSAGE_TYPE_MDX_SESSION_STATUS}","data":{{"screenId":"screen-1",
+                    "deviceId":"device-1"}}}}"#
+            )),
+            ..Default::default()
+        });
+        let channel = YouTubeChannel::new(
+            DEFAULT_SENDER_ID,
+            Lrc::new(MessageManager::new(stream.clone())),
+        );
+
+        let status = channel.get_mdx_session_status("web-7").unwrap();
+
+        assert_eq!("screen-1", status.screen_id);
+        assert_eq!(Some("device-1".to_string()), status.device_id);
+        let request = stream.received_message(0).unwrap().cast_message();
+        assert_eq!(CHANNEL_NAMESPACE, request.namespace);
+        assert_eq!(
+            MESSAGE_TYPE_GET_MDX_SESSION_STATUS,
+            request.payload.to_json().unwrap()["type"]
+        );
+    }
+
+    #[test]
+    fn test_parse_bind_response() {
+        let response = "98\n[[0,[\"c\",\"8B2B6B9F4DD0A67B\",\"\",8]]\n,[1,[\"S\",\"8a51bc1a\"]]\n\
+                        ,[2,[\"loungeStatus\",{}]]\n]\n";
+
+        assert_eq!(
+            ("8B2B6B9F4DD0A67B".to_string(), "8a51bc1a".to_string()),
+            parse_bind_response(response).unwrap()
+        );
+        assert!(parse_bind_response("[[0,[\"noop\"]]]").is_err());
+    }
+
+    #[test]
+    fn test_form_encode() {
+        assert_eq!(
+            "req0__sc=setPlaylist&req0_params=&name=Rust%20Cast%2F1",
+            form_encode(&[
+                ("req0__sc", "setPlaylist"),
+                ("req0_params", ""),
+                ("name", "Rust Cast/1")
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_http_response() {
+        assert_eq!(
+            "{}",
+            parse_http_response(b"HTTP/1.0 200 OK\r\nContent-Length: 2\r\n\r\n{}").unwrap()
+        );
+        assert!(parse_http_response(b"HTTP/1.0 400 Unknown SID\r\n\r\n").is_err());
+    }
+}
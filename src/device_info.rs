@@ -0,0 +1,246 @@
+//! Device metadata exposed by the local `/setup/eureka_info` HTTP endpoint of the cast device,
+//! requires `device_info` feature. It complements what mDNS TXT records provide (e.g. build
+//! version and Wi-Fi status).
+
+use std::{
+    io::{Read, Write},
+    net::{IpAddr, SocketAddr, TcpStream},
+    time::Duration,
+};
+
+use crate::errors::Error;
+
+/// Port the cast device serves its local HTTP API on.
+pub const EUREKA_INFO_PORT: u16 = 8008;
+
+/// Default time to wait for the device to respond.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+const EUREKA_INFO_PATH: &str =
+    "/setup/eureka_info?params=name,build_info,device_info,net,wifi&options=detail";
+
+/// Wi-Fi status of the device.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WifiStatus {
+    /// Name of the Wi-Fi network the device is connected to.
+    pub ssid: Option<String>,
+    /// MAC address of the access point the device is connected to.
+    pub bssid: Option<String>,
+    /// Signal level in dBm.
+    pub signal_level: Option<i32>,
+    /// Noise level in dBm.
+    pub noise_level: Option<i32>,
+}
+
+/// Metadata of the cast device, see `fetch`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Name of the device set by the user.
+    pub friendly_name: Option<String>,
+    /// Version of the cast firmware, e.g. `1.56.500000`.
+    pub build_version: Option<String>,
+    /// Model of the device, e.g. `Chromecast Ultra`.
+    pub model_name: Option<String>,
+    /// Manufacturer of the device, e.g. `Google Inc.`.
+    pub manufacturer: Option<String>,
+    /// Whether device can only play audio (e.g. speaker or audio group), `None` if unknown.
+    pub is_audio_device: Option<bool>,
+    /// IP address the device reports for itself.
+    pub ip_address: Option<String>,
+    /// Whether device is connected via Ethernet rather than Wi-Fi, `None` if unknown.
+    pub ethernet_connected: Option<bool>,
+    /// Wi-Fi status, `None` if device doesn't report it.
+    pub wifi: Option<WifiStatus>,
+}
+
+/// Fetches metadata of the cast device at `ip` using default port and timeout.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_cast::device_info;
+///
+/// let info = device_info::fetch("192.168.1.2".parse().unwrap())?;
+/// println!("{:?} runs {:?}", info.friendly_name, info.build_version);
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Fails if the device can't be reached or its response can't be parsed.
+pub fn fetch(ip: IpAddr) -> Result<DeviceInfo, Error> {
+    fetch_from(SocketAddr::new(ip, EUREKA_INFO_PORT), DEFAULT_TIMEOUT)
+}
+
+/// Fetches metadata of the cast device serving HTTP API at `addr`.
+///
+/// # Arguments
+///
+/// * `addr` - Address of the device HTTP API (port is usually `EUREKA_INFO_PORT`).
+/// * `timeout` - Maximum time to wait for the connection and for every read or write.
+///
+/// # Errors
+///
+/// Fails if the device can't be reached or its response can't be parsed.
+pub fn fetch_from(addr: SocketAddr, timeout: Duration) -> Result<DeviceInfo, Error> {
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    write!(
+        stream,
+        "GET {EUREKA_INFO_PATH} HTTP/1.0\r\nHost: {addr}\r\nAccept: application/json\r\n\r\n"
+    )?;
+
+    let mut response = vec![];
+    stream.read_to_end(&mut response)?;
+
+    parse_response(&response)
+}
+
+/// Parses raw HTTP response of the `/setup/eureka_info` endpoint.
+fn parse_response(response: &[u8]) -> Result<DeviceInfo, Error> {
+    let separator = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| Error::Parsing("Malformed eureka_info HTTP response.".to_string()))?;
+
+    let head = String::from_utf8_lossy(&response[..separator]);
+    let status_line = head.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        return Err(Error::Parsing(format!(
+            "Unexpected eureka_info response status: {status_line}"
+        )));
+    }
+
+    let reply: proxies::EurekaInfo = serde_json::from_slice(&response[separator + 4..])?;
+
+    Ok(DeviceInfo::from(reply))
+}
+
+impl From<proxies::EurekaInfo> for DeviceInfo {
+    fn from(reply: proxies::EurekaInfo) -> Self {
+        let build_info = reply.build_info.unwrap_or_default();
+        let device_info = reply.device_info.unwrap_or_default();
+        let net = reply.net.unwrap_or_default();
+        // Older firmware reports Wi-Fi fields at the top level.
+        let wifi = reply.wifi.unwrap_or_default();
+        let wifi = WifiStatus {
+            ssid: wifi.ssid.or(reply.ssid),
+            bssid: wifi.bssid.or(reply.bssid),
+            signal_level: wifi.signal_level.or(reply.signal_level),
+            noise_level: wifi.noise_level.or(reply.noise_level),
+        };
+
+        DeviceInfo {
+            friendly_name: reply.name,
+            build_version: build_info
+                .cast_build_revision
+                .or(reply.cast_build_revision)
+                .or(reply.build_version),
+            model_name: device_info.model_name,
+            manufacturer: device_info.manufacturer,
+            is_audio_device: device_info
+                .capabilities
+                .and_then(|capabilities| capabilities.display_supported)
+                .map(|display_supported| !display_supported),
+            ip_address: net.ip_address.or(reply.ip_address),
+            ethernet_connected: net.ethernet_connected.or(reply.ethernet_connected),
+            wifi: (wifi != WifiStatus::default()).then_some(wifi),
+        }
+    }
+}
+
+mod proxies {
+    use serde_derive::Deserialize;
+
+    #[derive(Deserialize, Debug, Default)]
+    pub struct EurekaInfo {
+        pub name: Option<String>,
+        pub build_info: Option<BuildInfo>,
+        pub device_info: Option<DeviceInfo>,
+        pub net: Option<Net>,
+        pub wifi: Option<Wifi>,
+        // Fields reported by older firmware at the top level.
+        pub build_version: Option<String>,
+        pub cast_build_revision: Option<String>,
+        pub ip_address: Option<String>,
+        pub ethernet_connected: Option<bool>,
+        pub ssid: Option<String>,
+        pub bssid: Option<String>,
+        pub signal_level: Option<i32>,
+        pub noise_level: Option<i32>,
+    }
+
+    #[derive(Deserialize, Debug, Default)]
+    pub struct BuildInfo {
+        pub cast_build_revision: Option<String>,
+    }
+
+    #[derive(Deserialize, Debug, Default)]
+    pub struct DeviceInfo {
+        pub model_name: Option<String>,
+        pub manufacturer: Option<String>,
+        pub capabilities: Option<Capabilities>,
+    }
+
+    #[derive(Deserialize, Debug, Default)]
+    pub struct Capabilities {
+        pub display_supported: Option<bool>,
+    }
+
+    #[derive(Deserialize, Debug, Default)]
+    pub struct Net {
+        pub ip_address: Option<String>,
+        pub ethernet_connected: Option<bool>,
+    }
+
+    #[derive(Deserialize, Debug, Default)]
+    pub struct Wifi {
+        pub ssid: Option<String>,
+        pub bssid: Option<String>,
+        pub signal_level: Option<i32>,
+        pub noise_level: Option<i32>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let response = concat!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n",
+            r#"{"name":"Kitchen speaker","build_info":{"cast_build_revision":"1.56.500000"},"#,
+            r#""device_info":{"model_name":"Google Home Mini","manufacturer":"Google Inc.","#,
+            r#""capabilities":{"display_supported":false}},"#,
+            r#""net":{"ip_address":"192.168.1.2","ethernet_connected":false},"#,
+            r#""wifi":{"ssid":"Home","signal_level":-45,"noise_level":-90}}"#
+        );
+
+        let info = parse_response(response.as_bytes()).unwrap();
+
+        assert_eq!(Some("Kitchen speaker"), info.friendly_name.as_deref());
+        assert_eq!(Some("1.56.500000"), info.build_version.as_deref());
+        assert_eq!(Some("Google Home Mini"), info.model_name.as_deref());
+        assert_eq!(Some(true), info.is_audio_device);
+        assert_eq!(Some("192.168.1.2"), info.ip_address.as_deref());
+        assert_eq!(
+            Some(WifiStatus {
+                ssid: Some("Home".to_string()),
+                bssid: None,
+                signal_level: Some(-45),
+                noise_level: Some(-90),
+            }),
+            info.wifi
+        );
+    }
+
+    #[test]
+    fn test_parse_response_error_status() {
+        let response = b"HTTP/1.1 403 Forbidden\r\n\r\n";
+
+        assert!(matches!(parse_response(response), Err(Error::Parsing(_))));
+    }
+}
@@ -0,0 +1,293 @@
+//! Discovery of the cast devices on the local network via mDNS, requires `discovery` feature.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use mdns_sd::{Receiver, ServiceDaemon, ServiceEvent, ServiceInfo};
+
+use crate::{capabilities::DeviceCapabilities, errors::Error};
+
+/// mDNS service type the cast devices advertise themselves with.
+pub const SERVICE_TYPE: &str = "_googlecast._tcp.local.";
+
+/// Cast device found on the local network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiscoveredDevice {
+    /// Full mDNS service instance name, it uniquely identifies the device on the network.
+    pub fullname: String,
+    /// Unique id of the device (`id` TXT record).
+    pub id: Option<String>,
+    /// Name of the device set by the user, e.g. `Living Room TV` (`fn` TXT record).
+    pub friendly_name: Option<String>,
+    /// Model of the device, e.g. `Chromecast Ultra` (`md` TXT record).
+    pub model_name: Option<String>,
+    /// Addresses the device can be reached at, IPv4 addresses go first.
+    pub addresses: Vec<IpAddr>,
+    /// Port the device accepts cast connections on.
+    pub port: u16,
+    /// All TXT records advertised by the device.
+    pub txt_records: HashMap<String, String>,
+}
+
+impl DiscoveredDevice {
+    /// Returns the address `CastDevice::connect_to_addr` can use to connect to the device.
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        self.addresses
+            .first()
+            .map(|address| SocketAddr::new(*address, self.port))
+    }
+
+    /// Returns capabilities of the device derived from its `ca` and `md` TXT records, see
+    /// `DeviceCapabilities::from_bitmask`, `None` if the device doesn't advertise the bitmask.
+    pub fn capabilities(&self) -> Option<DeviceCapabilities> {
+        let ca = self.txt_records.get("ca")?.parse().ok()?;
+        Some(DeviceCapabilities::from_bitmask(
+            ca,
+            self.model_name.as_deref(),
+        ))
+    }
+}
+
+impl From<&ServiceInfo> for DiscoveredDevice {
+    fn from(info: &ServiceInfo) -> Self {
+        let mut addresses: Vec<IpAddr> = info.get_addresses().iter().copied().collect();
+        addresses.sort_by_key(|address| (address.is_ipv6(), *address));
+
+        let txt_records: HashMap<String, String> = info
+            .get_properties()
+            .iter()
+            .map(|property| (property.key().to_string(), property.val_str().to_string()))
+            .collect();
+
+        DiscoveredDevice {
+            fullname: info.get_fullname().to_string(),
+            id: txt_records.get("id").cloned(),
+            friendly_name: txt_records.get("fn").cloned(),
+            model_name: txt_records.get("md").cloned(),
+            addresses,
+            port: info.get_port(),
+            txt_records,
+        }
+    }
+}
+
+/// Change of the set of the cast devices available on the network, see `watch`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeviceEvent {
+    /// New device has appeared on the network.
+    Found(DiscoveredDevice),
+    /// Already known device has changed (e.g. its name or addresses).
+    Updated(DiscoveredDevice),
+    /// Device has disappeared from the network, last known state of the device is included.
+    Lost(DiscoveredDevice),
+}
+
+/// Continuous monitor of the cast devices available on the network, see `watch`. Iterating over
+/// watcher blocks until the next event arrives, mDNS daemon is stopped once watcher is dropped.
+pub struct DeviceWatcher {
+    daemon: ServiceDaemon,
+    receiver: Receiver<ServiceEvent>,
+    devices: HashMap<String, DiscoveredDevice>,
+}
+
+impl DeviceWatcher {
+    /// Returns all the devices that are currently known to be available.
+    pub fn devices(&self) -> impl Iterator<Item = &DiscoveredDevice> {
+        self.devices.values()
+    }
+
+    /// Waits for the next event at most for the `timeout`.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Maximum time to wait for the event.
+    ///
+    /// # Return value
+    ///
+    /// `Result` containing the next event, `None` if no event has happened within the `timeout`,
+    /// or `Error` if mDNS daemon has stopped.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<DeviceEvent>, Error> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let event = match self.receiver.recv_deadline(deadline) {
+                Ok(event) => event,
+                Err(_) if self.receiver.is_disconnected() => {
+                    return Err(Error::Discovery("mDNS daemon has stopped".to_string()))
+                }
+                Err(_) => return Ok(None),
+            };
+
+            if let Some(event) = apply_event(&mut self.devices, event) {
+                return Ok(Some(event));
+            }
+        }
+    }
+}
+
+impl Iterator for DeviceWatcher {
+    type Item = DeviceEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let event = self.receiver.recv().ok()?;
+
+            if let Some(event) = apply_event(&mut self.devices, event) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+impl Drop for DeviceWatcher {
+    fn drop(&mut self) {
+        if let Err(err) = self.daemon.shutdown() {
+            log::warn!("Failed to shut down mDNS daemon: {err}");
+        }
+    }
+}
+
+/// Updates the set of known `devices` with the mDNS `event` and converts it into the device event,
+/// if the event changes anything.
+fn apply_event(
+    devices: &mut HashMap<String, DiscoveredDevice>,
+    event: ServiceEvent,
+) -> Option<DeviceEvent> {
+    match event {
+        ServiceEvent::ServiceResolved(info) => {
+            let device = DiscoveredDevice::from(&info);
+
+            match devices.insert(device.fullname.clone(), device.clone()) {
+                None => Some(DeviceEvent::Found(device)),
+                Some(previous) if previous != device => Some(DeviceEvent::Updated(device)),
+                Some(_) => None,
+            }
+        }
+        ServiceEvent::ServiceRemoved(_, fullname) => {
+            devices.remove(&fullname).map(DeviceEvent::Lost)
+        }
+        ServiceEvent::SearchStarted(_)
+        | ServiceEvent::ServiceFound(_, _)
+        | ServiceEvent::SearchStopped(_) => None,
+    }
+}
+
+/// Starts monitoring the cast devices available on the local network.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_cast::discovery::{self, DeviceEvent};
+///
+/// for event in discovery::watch()? {
+///     match event {
+///         DeviceEvent::Found(device) => println!("Found {:?}", device.friendly_name),
+///         DeviceEvent::Updated(device) => println!("Updated {:?}", device.friendly_name),
+///         DeviceEvent::Lost(device) => println!("Lost {:?}", device.friendly_name),
+///     }
+/// }
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Fails if mDNS daemon can't be started (e.g. there is no network interface available).
+pub fn watch() -> Result<DeviceWatcher, Error> {
+    let daemon = ServiceDaemon::new()?;
+    let receiver = daemon.browse(SERVICE_TYPE)?;
+
+    Ok(DeviceWatcher {
+        daemon,
+        receiver,
+        devices: HashMap::new(),
+    })
+}
+
+/// Collects the cast devices that have announced themselves on the local network within the
+/// `timeout`.
+///
+/// # Arguments
+///
+/// * `timeout` - How long to wait for the devices to respond.
+///
+/// # Errors
+///
+/// Fails if mDNS daemon can't be started (e.g. there is no network interface available).
+pub fn discover(timeout: Duration) -> Result<Vec<DiscoveredDevice>, Error> {
+    let deadline = Instant::now() + timeout;
+    let mut watcher = watch()?;
+
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        if watcher.recv_timeout(remaining)?.is_none() {
+            break;
+        }
+    }
+
+    Ok(watcher.devices().cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_event() {
+        let mut devices = HashMap::new();
+        let info = |name: &str| {
+            ServiceInfo::new(
+                SERVICE_TYPE,
+                "Chromecast-1234",
+                "1234.local.",
+                "192.168.1.2",
+                8009,
+                &[
+                    ("id", "1234"),
+                    ("fn", name),
+                    ("md", "Chromecast"),
+                    ("ca", "4101"),
+                ][..],
+            )
+            .unwrap()
+        };
+
+        let device = match apply_event(&mut devices, ServiceEvent::ServiceResolved(info("Kitchen")))
+        {
+            Some(DeviceEvent::Found(device)) => device,
+            other => panic!("unexpected event {other:?}"),
+        };
+        assert_eq!(Some("Kitchen"), device.friendly_name.as_deref());
+        assert_eq!(Some("1234"), device.id.as_deref());
+        assert_eq!(Some("Chromecast"), device.model_name.as_deref());
+        assert_eq!(Some(DeviceCapabilities::CHROMECAST), device.capabilities());
+        assert_eq!(
+            Some("192.168.1.2:8009".parse().unwrap()),
+            device.socket_addr()
+        );
+
+        // Unchanged device doesn't produce any event.
+        assert_eq!(
+            None,
+            apply_event(&mut devices, ServiceEvent::ServiceResolved(info("Kitchen")))
+        );
+
+        let updated = apply_event(
+            &mut devices,
+            ServiceEvent::ServiceResolved(info("Living Room")),
+        );
+        assert!(matches!(
+            updated,
+            Some(DeviceEvent::Updated(ref device))
+                if device.friendly_name.as_deref() == Some("Living Room")
+        ));
+
+        let lost = apply_event(
+            &mut devices,
+            ServiceEvent::ServiceRemoved(SERVICE_TYPE.to_string(), device.fullname.clone()),
+        );
+        assert!(matches!(lost, Some(DeviceEvent::Lost(_))));
+        assert!(devices.is_empty());
+    }
+}
@@ -5,6 +5,11 @@ use rustls::pki_types::InvalidDnsNameError;
 use serde_json::error::Error as SerializationError;
 use thiserror::Error;
 
+use crate::{
+    channels::{connection::CloseReason, error, media, media::MediaDetailedErrorCode, receiver},
+    message_manager::{FramingError, RequestId},
+};
+
 /// Consolidates possible error types that can occur in the lib.
 #[derive(Debug, Error)]
 pub enum Error {
@@ -30,12 +35,134 @@ pub enum Error {
     /// This variant includes any error that comes from rustls.
     #[error("{0}")]
     Tls(rustls::Error),
+    /// This variant includes any error that comes from native-tls (requires `native-tls` feature).
+    #[cfg(feature = "native-tls")]
+    #[error("{0}")]
+    NativeTls(native_tls::Error),
     /// Problems with given namespace
     #[error("{0}")]
     Namespace(String),
     /// This variant is used when message retrieval takes too long.
     #[error("{0}")]
     Timeout(String),
+    /// This variant is used when request has been cancelled with `CancellationToken`.
+    #[error("request has been cancelled")]
+    Cancelled,
+    /// This variant is used when connection has been shut down with `CastDevice::shutdown`.
+    #[error("connection has been shut down")]
+    Shutdown,
+    /// This variant is used when requested operation isn't supported by the device, application
+    /// or media (e.g. seeking to the live edge of non-live media).
+    #[error("operation is not supported, {0}")]
+    Unsupported(String),
+    /// This variant is used when arguments of the request are rejected before it's sent (e.g.
+    /// image with zero width).
+    #[error("validation failed, {0}")]
+    Validation(String),
+    /// This variant is used when the operation is rejected by the policy set with
+    /// `CastDevice::set_policy` (e.g. launch during quiet hours) before its request is sent.
+    #[error("operation is blocked by policy, {0}")]
+    PolicyBlocked(String),
+    /// This variant is used when cast devices can't be discovered via mDNS.
+    #[error("mDNS discovery failed, {0}")]
+    Discovery(String),
+    /// This variant is used when proxy refuses to establish the connection with the device.
+    #[error("proxy connection failed, {0}")]
+    Proxy(String),
+    /// Requested application is not running on the receiver.
+    #[error("application {0} is not running")]
+    AppNotRunning(String),
+    /// Receiver failed to launch the requested application.
+    #[error("could not launch application (request {request_id}, reason: {})", .reason.as_ref().map_or_else(|| "unknown".to_string(), ToString::to_string))]
+    LaunchError {
+        /// Unique id of the request that tried to launch application.
+        request_id: RequestId,
+        /// Launch error reason if available.
+        reason: Option<receiver::LaunchErrorReason>,
+    },
+    /// Receiver or media application considered the request invalid.
+    #[error("invalid request (request {request_id}, reason: {})", .reason.as_deref().unwrap_or("unknown"))]
+    InvalidRequest {
+        /// Unique id of the invalid request.
+        request_id: RequestId,
+        /// Description of the invalid request reason if available.
+        reason: Option<String>,
+    },
+    /// Media application failed to load the media.
+    #[error(
+        "failed to load media (request {request_id}, detailed error code: {detailed_error_code:?})"
+    )]
+    LoadFailed {
+        /// Unique id of the load request.
+        request_id: RequestId,
+        /// The detailed error code of the failure if provided by the receiver.
+        detailed_error_code: Option<MediaDetailedErrorCode>,
+    },
+    /// Media load has been cancelled by another load request.
+    #[error("load cancelled by another request (request {request_id})")]
+    LoadCancelled {
+        /// Unique id of the cancelled load request.
+        request_id: RequestId,
+    },
+    /// Request can't be fulfilled because the player is not in a valid state.
+    #[error("request failed because of invalid player state (request {request_id})")]
+    InvalidPlayerState {
+        /// Unique id of the failed request.
+        request_id: RequestId,
+    },
+    /// Receiver has closed the virtual connection with the destination for the given reason, so
+    /// it's not re-established automatically. Connect to the destination again to reopen it.
+    #[error("connection with {destination} has been closed by receiver, reason: {reason}")]
+    ConnectionClosed {
+        /// Destination (`receiver-0` or transport id of the application) of the connection.
+        destination: String,
+        /// Reason the receiver has given for closing the connection.
+        reason: CloseReason,
+    },
+    /// Cast device reported an error at the transport level (e.g. message it couldn't route).
+    #[error("transport error {error_type} (request {request_id:?}, reason: {})", .reason.as_deref().unwrap_or("unknown"))]
+    Transport {
+        /// Unique id of the request that caused this error, `RequestId::UNSOLICITED` if unknown.
+        request_id: RequestId,
+        /// Type of the error, e.g. `INVALID_REQUEST`.
+        error_type: String,
+        /// Description of the error reason if available.
+        reason: Option<String>,
+    },
+    /// Media application reported an error while executing media operation.
+    #[error("media error {detailed_error_code:?} (request {request_id:?}, reason: {})", .reason.as_deref().unwrap_or("unknown"))]
+    Media {
+        /// Unique id of the request that caused this error if available.
+        request_id: Option<RequestId>,
+        /// The detailed error code associated with the media error.
+        detailed_error_code: MediaDetailedErrorCode,
+        /// Description of the error reason if available.
+        reason: Option<String>,
+    },
+    /// Message can't be sent because the outgoing queue is full, see `SendQueueOptions`.
+    #[error("send queue is full, {0} messages are waiting to be written")]
+    QueueFull(usize),
+    /// Frame read from the stream is invalid, the stream can't be used anymore since the frame
+    /// boundaries are lost.
+    #[error("invalid frame, {0}")]
+    Framing(FramingError),
+}
+
+impl Error {
+    /// Determines whether the failed request may succeed if it's repeated as is: the stream
+    /// couldn't accept the data right away or receiver has rejected the request because of the
+    /// player state that changes on its own (e.g. while the application is starting), see
+    /// `RetryPolicy`.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Io(error) => matches!(
+                error.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::Interrupted
+            ),
+            Error::InvalidPlayerState { .. } | Error::QueueFull(_) => true,
+            _ => false,
+        }
+    }
 }
 
 impl From<IoError> for Error {
@@ -62,8 +189,94 @@ impl From<rustls::Error> for Error {
     }
 }
 
+#[cfg(feature = "native-tls")]
+impl From<native_tls::Error> for Error {
+    fn from(err: native_tls::Error) -> Error {
+        Error::NativeTls(err)
+    }
+}
+
 impl From<InvalidDnsNameError> for Error {
     fn from(err: InvalidDnsNameError) -> Error {
         Error::Dns(err)
     }
 }
+
+#[cfg(feature = "discovery")]
+impl From<mdns_sd::Error> for Error {
+    fn from(err: mdns_sd::Error) -> Error {
+        Error::Discovery(err.to_string())
+    }
+}
+
+impl From<error::TransportError> for Error {
+    fn from(err: error::TransportError) -> Error {
+        Error::Transport {
+            request_id: err.request_id,
+            error_type: err.error_type,
+            reason: err.reason,
+        }
+    }
+}
+
+impl From<receiver::LaunchError> for Error {
+    fn from(err: receiver::LaunchError) -> Error {
+        Error::LaunchError {
+            request_id: err.request_id,
+            reason: err.reason,
+        }
+    }
+}
+
+impl From<receiver::InvalidRequest> for Error {
+    fn from(err: receiver::InvalidRequest) -> Error {
+        Error::InvalidRequest {
+            request_id: err.request_id,
+            reason: err.reason,
+        }
+    }
+}
+
+impl From<media::InvalidRequest> for Error {
+    fn from(err: media::InvalidRequest) -> Error {
+        Error::InvalidRequest {
+            request_id: err.request_id,
+            reason: err.reason,
+        }
+    }
+}
+
+impl From<media::LoadFailed> for Error {
+    fn from(err: media::LoadFailed) -> Error {
+        Error::LoadFailed {
+            request_id: err.request_id,
+            detailed_error_code: err.detailed_error_code,
+        }
+    }
+}
+
+impl From<media::LoadCancelled> for Error {
+    fn from(err: media::LoadCancelled) -> Error {
+        Error::LoadCancelled {
+            request_id: err.request_id,
+        }
+    }
+}
+
+impl From<media::InvalidPlayerState> for Error {
+    fn from(err: media::InvalidPlayerState) -> Error {
+        Error::InvalidPlayerState {
+            request_id: err.request_id,
+        }
+    }
+}
+
+impl From<media::MediaError> for Error {
+    fn from(err: media::MediaError) -> Error {
+        Error::Media {
+            request_id: err.request_id,
+            detailed_error_code: err.detailed_error_code,
+            reason: err.reason,
+        }
+    }
+}
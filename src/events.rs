@@ -0,0 +1,642 @@
+//! High-level events of the cast device (applications, volume, media playback) derived from the
+//! raw messages, so that applications don't have to track and compare the statuses themselves.
+//! Events can be pulled with `CastDevice::events` iterator or, if `thread_safe` feature is enabled,
+//! delivered from the background thread with `CastDevice::subscribe`, `CastDevice::event_channel`
+//! or (`async` feature) `CastDevice::event_stream`.
+
+use std::{collections::VecDeque, time::Duration};
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll, Waker},
+};
+#[cfg(feature = "thread_safe")]
+use std::{sync::Arc, thread, thread::JoinHandle};
+
+use crate::{
+    channels::{
+        error::ErrorResponse,
+        heartbeat::HeartbeatResponse,
+        media::{
+            IdleReason, Media, MediaResponse, MediaStatusChange, PlayerState, ProgressTracker,
+            QueueEvent, QueueTracker, StatusEntry,
+        },
+        receiver::{self, Application, ReceiverResponse, StatusChange, Volume},
+    },
+    errors::Error,
+    message_manager::{CastStream, ConnectionState},
+    CastDevice, ChannelMessage, TlsStream,
+};
+
+/// Interval the status entries are tracked with when position events are disabled, it's never
+/// waited for.
+const UNUSED_PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Event of the cast device emitted by `EventLoop`.
+#[derive(Debug)]
+pub enum CastEvent {
+    /// Connection with the device is established, it's always the first event.
+    DeviceConnected,
+    /// Connection with the device has been lost or shut down, it's always the last event.
+    Disconnected,
+    /// Application has been launched (or was already running once the loop has started).
+    AppLaunched(Application),
+    /// Application has been stopped.
+    AppStopped(Application),
+    /// Volume of the device has changed (or has been reported for the first time).
+    VolumeChanged(Volume),
+    /// Media has been loaded into the media session.
+    MediaLoaded {
+        /// Id of the media session.
+        media_session_id: i32,
+        /// Media that has been loaded.
+        media: Box<Media>,
+    },
+    /// Player of the media session has switched to another state.
+    PlaybackStateChanged {
+        /// Id of the media session.
+        media_session_id: i32,
+        /// State of the player.
+        state: PlayerState,
+        /// Reason of the switch to `Idle` state, if known.
+        idle_reason: Option<IdleReason>,
+    },
+    /// Position of the media player (in seconds) has changed: either reported by the receiver
+    /// (e.g. after seek) or extrapolated, see `EventLoop::with_progress_interval`.
+    PositionChanged {
+        /// Id of the media session.
+        media_session_id: i32,
+        /// Current position of the media player.
+        position: f32,
+    },
+    /// Queue of the media session has switched to another item or preloads the next one.
+    QueueChanged(QueueEvent),
+    /// Device has reported an error (e.g. media has failed to load) or the message couldn't be
+    /// processed. Errors that end the connection are followed by `Disconnected` event.
+    Error(Error),
+}
+
+/// Iterator over the events of the cast device: receives messages from the device, answers its
+/// heartbeat pings and turns the messages into `CastEvent`s. Iterator ends after `Disconnected`
+/// event. Responses to the requests are consumed by the requests themselves, so only broadcasts
+/// (e.g. status changes caused by other senders or the device itself) produce events.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use rust_cast::{events::CastEvent, CastDevice};
+///
+/// let device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// device.connection.connect("receiver-0")?;
+///
+/// for event in device.events().with_progress_interval(Duration::from_millis(500)) {
+///     match event {
+///         CastEvent::AppLaunched(app) => println!("{} has been launched", app.display_name),
+///         CastEvent::PositionChanged { position, .. } => println!("{position:.1}s"),
+///         CastEvent::Error(error) => eprintln!("{error}"),
+///         _ => {}
+///     }
+/// }
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+pub struct EventLoop<'a, W = TlsStream>
+where
+    W: CastStream,
+{
+    device: &'a CastDevice<W>,
+    tracker: EventTracker,
+}
+
+impl<'a, W> EventLoop<'a, W>
+where
+    W: CastStream,
+{
+    /// Creates event loop of the `device`, see `CastDevice::events`.
+    pub fn new(device: &'a CastDevice<W>) -> EventLoop<'a, W> {
+        EventLoop {
+            device,
+            tracker: EventTracker::new(None),
+        }
+    }
+
+    /// Makes the loop emit `PositionChanged` events every `interval` while media is playing, with
+    /// the position extrapolated from the latest media status. Disabled by default.
+    pub fn with_progress_interval(mut self, interval: Duration) -> Self {
+        self.tracker = EventTracker::new(Some(interval));
+        self
+    }
+}
+
+impl<W> Iterator for EventLoop<'_, W>
+where
+    W: CastStream,
+{
+    type Item = CastEvent;
+
+    fn next(&mut self) -> Option<CastEvent> {
+        loop {
+            if let Some(event) = self.tracker.next_pending(self.device) {
+                return Some(event);
+            }
+
+            if self.tracker.is_finished() {
+                return None;
+            }
+
+            self.tracker.poll(self.device, None);
+        }
+    }
+}
+
+/// State of the event loop kept apart from the device, so that the loops of several devices can
+/// share the threads, see `CastManager`.
+pub(crate) struct EventTracker {
+    progress_interval: Option<Duration>,
+    pending: VecDeque<CastEvent>,
+    receiver_status: Option<receiver::Status>,
+    progress: ProgressTracker,
+    queue: QueueTracker,
+    started: bool,
+    finished: bool,
+}
+
+impl EventTracker {
+    pub(crate) fn new(progress_interval: Option<Duration>) -> EventTracker {
+        EventTracker {
+            progress_interval,
+            pending: VecDeque::new(),
+            receiver_status: None,
+            progress: ProgressTracker::new(progress_interval.unwrap_or(UNUSED_PROGRESS_INTERVAL)),
+            queue: QueueTracker::default(),
+            started: false,
+            finished: false,
+        }
+    }
+
+    /// Returns the next event that has already been produced, `DeviceConnected` goes first.
+    pub(crate) fn next_pending<W: CastStream>(
+        &mut self,
+        device: &CastDevice<W>,
+    ) -> Option<CastEvent> {
+        if !self.started {
+            self.started = true;
+            if device.state() != ConnectionState::Closed {
+                return Some(CastEvent::DeviceConnected);
+            }
+        }
+
+        self.pending.pop_front()
+    }
+
+    /// Determines whether the loop has produced its last event.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Receives the next message (waiting at most till the next position event is due, if they
+    /// are enabled, or for the `max_wait`) and queues the events it has produced.
+    pub(crate) fn poll<W: CastStream>(
+        &mut self,
+        device: &CastDevice<W>,
+        max_wait: Option<Duration>,
+    ) {
+        if self.poll_progress() {
+            return;
+        }
+
+        let result = Self::receive(device, self.receive_timeout(max_wait));
+        self.handle_received(device, result);
+    }
+
+    /// Queues position event if it's due. Returns `false` if no event has been queued and the
+    /// next message should be received.
+    pub(crate) fn poll_progress(&mut self) -> bool {
+        if self.progress_interval.is_some() {
+            if let Some(MediaStatusChange::PositionChanged(position)) = self.progress.tick() {
+                let media_session_id = self.media_session_id();
+                self.pending.push_back(CastEvent::PositionChanged {
+                    media_session_id,
+                    position,
+                });
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Returns how long the next message should be waited for: till the next position event is
+    /// due, if they are enabled, but at most for the `max_wait`.
+    pub(crate) fn receive_timeout(&self, max_wait: Option<Duration>) -> Option<Duration> {
+        match (self.progress_interval, max_wait) {
+            (Some(_), Some(max_wait)) => Some(self.progress.timeout().min(max_wait)),
+            (Some(_), None) => Some(self.progress.timeout()),
+            (None, max_wait) => max_wait,
+        }
+    }
+
+    /// Receives the next message from the `device` waiting at most for the `timeout`.
+    pub(crate) fn receive<W: CastStream>(
+        device: &CastDevice<W>,
+        timeout: Option<Duration>,
+    ) -> Result<Option<ChannelMessage>, Error> {
+        match timeout {
+            Some(timeout) => device.receive_timeout(timeout),
+            None => device.receive().map(Some),
+        }
+    }
+
+    /// Queues the events produced by the `result` of `receive`.
+    pub(crate) fn handle_received<W: CastStream>(
+        &mut self,
+        device: &CastDevice<W>,
+        result: Result<Option<ChannelMessage>, Error>,
+    ) {
+        match result {
+            Ok(Some(message)) => self.handle_message(device, message),
+            Ok(None) => {}
+            Err(error) => self.handle_error(device, error),
+        }
+    }
+
+    /// Returns id of the latest media session, `0` if none has been seen yet.
+    fn media_session_id(&self) -> i32 {
+        self.progress
+            .entry()
+            .map_or(0, |entry| entry.media_session_id)
+    }
+
+    fn handle_message<W: CastStream>(&mut self, device: &CastDevice<W>, message: ChannelMessage) {
+        let error: Error = match message {
+            ChannelMessage::Heartbeat(HeartbeatResponse::Ping) => {
+                if let Err(error) = device.heartbeat.pong() {
+                    self.handle_error(device, error);
+                }
+                return;
+            }
+            ChannelMessage::Receiver(ReceiverResponse::Status(status)) => {
+                self.handle_receiver_status(status);
+                return;
+            }
+            ChannelMessage::Receiver(ReceiverResponse::LaunchError(error)) => error.into(),
+            ChannelMessage::Receiver(ReceiverResponse::InvalidRequest(error)) => error.into(),
+            ChannelMessage::Media(MediaResponse::Status(status)) => {
+                for entry in &status.entries {
+                    self.handle_media_status(entry);
+                }
+                return;
+            }
+            ChannelMessage::Media(MediaResponse::LoadFailed(error)) => error.into(),
+            ChannelMessage::Media(MediaResponse::LoadCancelled(error)) => error.into(),
+            ChannelMessage::Media(MediaResponse::InvalidPlayerState(error)) => error.into(),
+            ChannelMessage::Media(MediaResponse::InvalidRequest(error)) => error.into(),
+            ChannelMessage::Media(MediaResponse::Error(error)) => error.into(),
+            ChannelMessage::Error(ErrorResponse::Error(error)) => error.into(),
+            ChannelMessage::ParseError { error, .. } => Error::Parsing(error),
+            _ => return,
+        };
+
+        self.pending.push_back(CastEvent::Error(error));
+    }
+
+    /// Queues the error, as well as `Disconnected` event if the error has ended the connection.
+    fn handle_error<W: CastStream>(&mut self, device: &CastDevice<W>, error: Error) {
+        let closed = matches!(error, Error::Shutdown) || device.state() == ConnectionState::Closed;
+
+        self.pending.push_back(CastEvent::Error(error));
+
+        if closed {
+            self.pending.push_back(CastEvent::Disconnected);
+            self.finished = true;
+        }
+    }
+
+    fn handle_receiver_status(&mut self, status: receiver::Status) {
+        match self.receiver_status {
+            Some(ref previous) => {
+                for change in receiver::Status::diff(previous, &status) {
+                    self.pending.push_back(match change {
+                        StatusChange::AppLaunched(app) => CastEvent::AppLaunched(app),
+                        StatusChange::AppStopped(app) => CastEvent::AppStopped(app),
+                        StatusChange::VolumeChanged { new, .. } => CastEvent::VolumeChanged(new),
+                        _ => continue,
+                    });
+                }
+            }
+            None => {
+                self.pending.extend(
+                    status
+                        .applications
+                        .iter()
+                        .cloned()
+                        .map(CastEvent::AppLaunched),
+                );
+                self.pending
+                    .push_back(CastEvent::VolumeChanged(status.volume));
+            }
+        }
+
+        self.receiver_status = Some(status);
+    }
+
+    fn handle_media_status(&mut self, entry: &StatusEntry) {
+        let media_session_id = entry.media_session_id;
+
+        if self.progress.entry().is_none() {
+            if let Some(ref media) = entry.media {
+                self.pending.push_back(CastEvent::MediaLoaded {
+                    media_session_id,
+                    media: Box::new(media.clone()),
+                });
+            }
+            self.pending.push_back(CastEvent::PlaybackStateChanged {
+                media_session_id,
+                state: entry.player_state.clone(),
+                idle_reason: entry.idle_reason.clone(),
+            });
+        }
+
+        for change in self.progress.update(entry) {
+            self.pending.push_back(match change {
+                MediaStatusChange::MediaChanged(media) => CastEvent::MediaLoaded {
+                    media_session_id,
+                    media,
+                },
+                MediaStatusChange::PlayerStateChanged {
+                    new, idle_reason, ..
+                } => CastEvent::PlaybackStateChanged {
+                    media_session_id,
+                    state: new,
+                    idle_reason,
+                },
+                MediaStatusChange::PositionChanged(position) => CastEvent::PositionChanged {
+                    media_session_id,
+                    position,
+                },
+                _ => continue,
+            });
+        }
+
+        self.pending.extend(
+            self.queue
+                .update(entry)
+                .into_iter()
+                .map(CastEvent::QueueChanged),
+        );
+    }
+}
+
+/// Runs the event loop of the `device` in the background thread and passes every event to
+/// `deliver` until the loop ends or `deliver` returns `false`.
+#[cfg(feature = "thread_safe")]
+pub(crate) fn spawn<W, F>(
+    device: Arc<CastDevice<W>>,
+    progress_interval: Option<Duration>,
+    mut deliver: F,
+) -> JoinHandle<()>
+where
+    W: CastStream + Send + 'static,
+    F: FnMut(CastEvent) -> bool + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut events = EventLoop::new(&device);
+        if let Some(interval) = progress_interval {
+            events = events.with_progress_interval(interval);
+        }
+
+        for event in events {
+            if !deliver(event) {
+                break;
+            }
+        }
+    })
+}
+
+/// Events the background event loop has emitted, but `EventStream` hasn't returned yet.
+#[cfg(feature = "async")]
+#[derive(Default)]
+struct EventQueue {
+    events: VecDeque<CastEvent>,
+    /// Waker of the task that waits for the next event.
+    waker: Option<Waker>,
+    /// Determines whether the loop has emitted its last event.
+    finished: bool,
+    /// Determines whether the stream has been dropped, so the loop should stop.
+    dropped: bool,
+}
+
+/// `Stream` of the events of the cast device, see `CastDevice::event_stream`.
+#[cfg(feature = "async")]
+pub struct EventStream {
+    queue: Arc<Mutex<EventQueue>>,
+}
+
+#[cfg(feature = "async")]
+impl EventStream {
+    /// Runs the event loop of the `device` in the background thread, see `spawn`.
+    pub(crate) fn spawn<W>(device: Arc<CastDevice<W>>, progress_interval: Option<Duration>) -> Self
+    where
+        W: CastStream + Send + 'static,
+    {
+        let queue = Arc::new(Mutex::new(EventQueue::default()));
+        let loop_queue = Arc::clone(&queue);
+
+        spawn(device, progress_interval, move |event| {
+            let mut queue = loop_queue.lock().unwrap();
+            if queue.dropped {
+                return false;
+            }
+
+            queue.finished = matches!(event, CastEvent::Disconnected);
+            queue.events.push_back(event);
+            if let Some(waker) = queue.waker.take() {
+                waker.wake();
+            }
+
+            true
+        });
+
+        EventStream { queue }
+    }
+}
+
+#[cfg(feature = "async")]
+impl futures_core::Stream for EventStream {
+    type Item = CastEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<CastEvent>> {
+        let mut queue = self.queue.lock().unwrap();
+
+        match queue.events.pop_front() {
+            Some(event) => Poll::Ready(Some(event)),
+            None if queue.finished => Poll::Ready(None),
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.dropped = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::EnumOrUnknown;
+
+    use crate::{
+        cast::cast_channel::{
+            self,
+            cast_message::{PayloadType, ProtocolVersion},
+        },
+        testing::MockTcpStream,
+        DEFAULT_RECEIVER_ID,
+    };
+
+    use super::*;
+
+    fn message(namespace: &str, payload: &str) -> cast_channel::CastMessage {
+        cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
+            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            destination_id: Some("*".to_string()),
+            namespace: Some(namespace.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(payload.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns stream with receiver and media statuses the events are derived from.
+    fn status_stream() -> MockTcpStream {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(message(
+            "urn:x-cast:com.google.cast.receiver",
+            r#"{"requestId":0,"type":"RECEIVER_STATUS","status":{"volume":{"level":0.5}}}"#,
+        ));
+        stream.add_message(message(
+            "urn:x-cast:com.google.cast.receiver",
+            r#"{"requestId":0,"type":"RECEIVER_STATUS","status":{"volume":{"level":0.5},
+                "applications":[{"appId":"CC1AD845","sessionId":"1","transportId":"web-1"}]}}"#,
+        ));
+        stream.add_message(message(
+            "urn:x-cast:com.google.cast.tp.heartbeat",
+            r#"{"type":"PING"}"#,
+        ));
+        stream.add_message(message(
+            "urn:x-cast:com.google.cast.media",
+            r#"{"requestId":0,"type":"MEDIA_STATUS","status":[{"mediaSessionId":1,
+                "playerState":"BUFFERING","playbackRate":1.0,"currentItemId":1,
+                "media":{"contentId":"a","streamType":"BUFFERED","contentType":"video/mp4"}}]}"#,
+        ));
+        stream.add_message(message(
+            "urn:x-cast:com.google.cast.media",
+            r#"{"requestId":0,"type":"MEDIA_STATUS","status":[{"mediaSessionId":1,
+                "playerState":"PLAYING","playbackRate":1.0,"currentItemId":1}]}"#,
+        ));
+        stream
+    }
+
+    #[test]
+    fn test_events() {
+        let device = CastDevice::from_stream(status_stream()).unwrap();
+
+        let events = device.events().collect::<Vec<_>>();
+
+        assert!(matches!(events[0], CastEvent::DeviceConnected));
+        assert!(matches!(events[1], CastEvent::VolumeChanged(volume) if volume.level == Some(0.5)));
+        assert!(matches!(events[2], CastEvent::AppLaunched(ref app) if app.session_id == "1"));
+        assert!(matches!(
+            events[3],
+            CastEvent::MediaLoaded { media_session_id: 1, ref media } if media.content_id == "a"
+        ));
+        assert!(matches!(
+            events[4],
+            CastEvent::PlaybackStateChanged {
+                state: PlayerState::Buffering,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events[5],
+            CastEvent::QueueChanged(QueueEvent::ItemChanged { current: 1, .. })
+        ));
+        assert!(matches!(
+            events[6],
+            CastEvent::PlaybackStateChanged {
+                media_session_id: 1,
+                state: PlayerState::Playing,
+                idle_reason: None,
+            }
+        ));
+        // Stream has no more messages, so the connection is closed.
+        assert!(matches!(events[7], CastEvent::Error(Error::Io(_))));
+        assert!(matches!(events[8], CastEvent::Disconnected));
+        assert_eq!(9, events.len());
+    }
+
+    #[test]
+    #[cfg(feature = "thread_safe")]
+    fn test_event_delivery() {
+        use std::sync::Mutex;
+
+        let device = Arc::new(CastDevice::from_stream(status_stream()).unwrap());
+        let events = Arc::new(Mutex::new(vec![]));
+        let subscriber_events = Arc::clone(&events);
+        device
+            .subscribe(None, move |event| {
+                subscriber_events.lock().unwrap().push(event)
+            })
+            .join()
+            .unwrap();
+        assert_eq!(9, events.lock().unwrap().len());
+
+        let device = Arc::new(CastDevice::from_stream(status_stream()).unwrap());
+        let events = device.event_channel(None).iter().collect::<Vec<_>>();
+        assert!(matches!(events[0], CastEvent::DeviceConnected));
+        assert!(matches!(events[8], CastEvent::Disconnected));
+        assert_eq!(9, events.len());
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn test_event_stream() {
+        use std::task::Wake;
+
+        use futures_core::Stream;
+
+        struct ThreadWaker(thread::Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let device = Arc::new(CastDevice::from_stream(status_stream()).unwrap());
+        let mut stream = device.event_stream(None);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut events = vec![];
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(event)) => events.push(event),
+                Poll::Ready(None) => break,
+                Poll::Pending => thread::park(),
+            }
+        }
+
+        assert!(matches!(events[8], CastEvent::Disconnected));
+        assert_eq!(9, events.len());
+    }
+}
@@ -0,0 +1,457 @@
+//! Facade of the high-level API for the foreign language bindings (e.g. UniFFI bindings for
+//! Kotlin and Swift, see `rust_cast.udl`): objects are shared through `Arc` and are safe to call
+//! from any thread, arguments and records are plain owned types, and errors are flattened into
+//! `FfiError`, so that the facade maps to the binding definitions one to one. UniFFI scaffolding
+//! of the definitions is generated by the build script and exported from the crate root.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    channels::{
+        media::{LoadOptions, Media, QueueEvent},
+        receiver::{CastDeviceApp, Volume},
+    },
+    errors::Error,
+    events::{CastEvent, EventTracker},
+    message_manager::CastStream,
+    CastDevice, TlsStream,
+};
+
+/// Maximum time `RemoteDevice::next_event` waits for the message at once.
+const POLL_SLICE: Duration = Duration::from_millis(20);
+
+/// Error of the facade calls.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum FfiError {
+    /// Connection with the device can't be established or has been lost.
+    #[error("connection failed, {0}")]
+    Connection(String),
+    /// Device has rejected or failed to answer the request.
+    #[error("request failed, {0}")]
+    Request(String),
+    /// Media command has been issued before any media has been cast.
+    #[error("no media has been cast")]
+    NoMedia,
+}
+
+impl From<Error> for FfiError {
+    fn from(error: Error) -> FfiError {
+        match error {
+            Error::Io(_) | Error::Tls(_) | Error::Dns(_) | Error::Proxy(_) | Error::Shutdown => {
+                FfiError::Connection(error.to_string())
+            }
+            #[cfg(feature = "native-tls")]
+            Error::NativeTls(_) => FfiError::Connection(error.to_string()),
+            error => FfiError::Request(error.to_string()),
+        }
+    }
+}
+
+/// Playback state reported by `RemoteDevice::playback`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaybackInfo {
+    /// State of the player, e.g. `PLAYING` or `PAUSED`.
+    pub player_state: String,
+    /// Estimated position of the player in seconds, if known.
+    pub position: Option<f64>,
+    /// Duration of the media in seconds, if known.
+    pub duration: Option<f64>,
+}
+
+/// Event of the device, flattened `CastEvent`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeviceEvent {
+    /// Connection with the device is established.
+    Connected,
+    /// Connection with the device has been lost or closed, no more events follow.
+    Disconnected,
+    /// Application has been launched.
+    AppLaunched {
+        /// Id of the application.
+        app_id: String,
+        /// Human-readable name of the application.
+        display_name: String,
+    },
+    /// Application has been stopped.
+    AppStopped {
+        /// Id of the application.
+        app_id: String,
+    },
+    /// Volume of the device has changed.
+    VolumeChanged {
+        /// Volume level, from `0.0` to `1.0`.
+        level: Option<f32>,
+        /// Whether the device is muted.
+        muted: Option<bool>,
+    },
+    /// Media has been loaded.
+    MediaLoaded {
+        /// Id (usually URL) of the media.
+        content_id: String,
+    },
+    /// Player has switched to another state.
+    PlaybackStateChanged {
+        /// State of the player, e.g. `PLAYING` or `PAUSED`.
+        player_state: String,
+    },
+    /// Position of the player (in seconds) has changed.
+    PositionChanged {
+        /// Current position of the player.
+        position: f64,
+    },
+    /// Queue has switched to another item.
+    QueueItemChanged {
+        /// Id of the item that is played now.
+        item_id: u16,
+    },
+    /// Device has reported an error.
+    Error {
+        /// Description of the error.
+        message: String,
+    },
+}
+
+impl DeviceEvent {
+    /// Flattens the `event`, `None` if the event has no counterpart (e.g. queue preloading).
+    fn from_event(event: CastEvent) -> Option<DeviceEvent> {
+        Some(match event {
+            CastEvent::DeviceConnected => DeviceEvent::Connected,
+            CastEvent::Disconnected => DeviceEvent::Disconnected,
+            CastEvent::AppLaunched(app) => DeviceEvent::AppLaunched {
+                app_id: app.app_id,
+                display_name: app.display_name,
+            },
+            CastEvent::AppStopped(app) => DeviceEvent::AppStopped { app_id: app.app_id },
+            CastEvent::VolumeChanged(volume) => DeviceEvent::VolumeChanged {
+                level: volume.level,
+                muted: volume.muted,
+            },
+            CastEvent::MediaLoaded { media, .. } => DeviceEvent::MediaLoaded {
+                content_id: media.content_id,
+            },
+            CastEvent::PlaybackStateChanged { state, .. } => DeviceEvent::PlaybackStateChanged {
+                player_state: state.to_string(),
+            },
+            CastEvent::PositionChanged { position, .. } => DeviceEvent::PositionChanged {
+                position: f64::from(position),
+            },
+            CastEvent::QueueChanged(QueueEvent::ItemChanged { current, .. }) => {
+                DeviceEvent::QueueItemChanged { item_id: current }
+            }
+            CastEvent::QueueChanged(_) => return None,
+            CastEvent::Error(error) => DeviceEvent::Error {
+                message: error.to_string(),
+            },
+        })
+    }
+}
+
+/// Media cast with `RemoteDevice::cast` that the media commands control.
+struct CastMedia {
+    transport_id: String,
+    media_session_id: i32,
+}
+
+/// Remote control of the cast device: casts media to the Default Media Receiver and controls its
+/// playback and the volume of the device.
+pub struct RemoteDevice<W = TlsStream>
+where
+    W: CastStream,
+{
+    device: CastDevice<W>,
+    media: Mutex<Option<CastMedia>>,
+    events: Mutex<EventTracker>,
+}
+
+impl RemoteDevice {
+    /// Connects to the device at `host` and `port`, see `CastDevice::connect`.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - Host name or IP address of the device.
+    /// * `port` - Port of the device, usually `8009`.
+    /// * `verify_host` - Whether certificate of the device should be verified, cast devices
+    ///   usually present self-signed certificates.
+    pub fn connect(host: String, port: u16, verify_host: bool) -> Result<Self, FfiError> {
+        let device = if verify_host {
+            CastDevice::connect(host, port)?
+        } else {
+            CastDevice::connect_without_host_verification(host, port)?
+        };
+
+        RemoteDevice::new(device)
+    }
+}
+
+impl<W> RemoteDevice<W>
+where
+    W: CastStream,
+{
+    /// Creates remote control of the already connected `device`, connects to its receiver.
+    pub fn from_device(device: CastDevice<W>) -> Result<Arc<Self>, FfiError> {
+        RemoteDevice::new(device).map(Arc::new)
+    }
+
+    fn new(device: CastDevice<W>) -> Result<Self, FfiError> {
+        device
+            .connection
+            .connect(device.receiver_id().to_string())?;
+
+        Ok(RemoteDevice {
+            device,
+            media: Mutex::new(None),
+            events: Mutex::new(EventTracker::new(None)),
+        })
+    }
+
+    /// Launches the Default Media Receiver (unless it's already running) and plays the media.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - URL of the media.
+    /// * `content_type` - MIME type of the media, e.g. `video/mp4`.
+    /// * `autoplay` - Whether the playback should start right away.
+    pub fn cast(&self, url: String, content_type: String, autoplay: bool) -> Result<(), FfiError> {
+        let session = self
+            .device
+            .launch_app_if_not_running(&CastDeviceApp::DefaultMediaReceiver, false)?;
+        let media = Media::builder(url, content_type).build();
+        let status = self.device.media.load_with_opts(
+            session.application.transport_id.as_str(),
+            session.application.session_id.as_str(),
+            &media,
+            LoadOptions {
+                autoplay,
+                ..LoadOptions::default()
+            },
+        )?;
+
+        let media_session_id = status
+            .entries
+            .first()
+            .map(|entry| entry.media_session_id)
+            .ok_or_else(|| FfiError::Request("media status has no entries".to_string()))?;
+        *self.media.lock().unwrap() = Some(CastMedia {
+            transport_id: session.application.transport_id,
+            media_session_id,
+        });
+
+        Ok(())
+    }
+
+    /// Resumes the playback of the cast media.
+    pub fn play(&self) -> Result<(), FfiError> {
+        self.with_media(|device, media| {
+            device
+                .media
+                .play(media.transport_id.as_str(), media.media_session_id)
+        })
+    }
+
+    /// Pauses the playback of the cast media.
+    pub fn pause(&self) -> Result<(), FfiError> {
+        self.with_media(|device, media| {
+            device
+                .media
+                .pause(media.transport_id.as_str(), media.media_session_id)
+        })
+    }
+
+    /// Stops the playback of the cast media.
+    pub fn stop(&self) -> Result<(), FfiError> {
+        self.with_media(|device, media| {
+            device
+                .media
+                .stop(media.transport_id.as_str(), media.media_session_id)
+        })?;
+        *self.media.lock().unwrap() = None;
+
+        Ok(())
+    }
+
+    /// Seeks the cast media to the `position` (in seconds).
+    pub fn seek(&self, position: f64) -> Result<(), FfiError> {
+        self.with_media(|device, media| {
+            device.media.seek(
+                media.transport_id.as_str(),
+                media.media_session_id,
+                Some(position as f32),
+                None,
+            )
+        })
+    }
+
+    /// Returns playback state of the cast media.
+    pub fn playback(&self) -> Result<PlaybackInfo, FfiError> {
+        let media = self.media.lock().unwrap();
+        let media = media.as_ref().ok_or(FfiError::NoMedia)?;
+        let status = self
+            .device
+            .media
+            .get_status(media.transport_id.as_str(), Some(media.media_session_id))?;
+        let entry = status
+            .entries
+            .first()
+            .ok_or_else(|| FfiError::Request("media status has no entries".to_string()))?;
+
+        Ok(PlaybackInfo {
+            player_state: entry.player_state.to_string(),
+            position: entry.estimated_current_time().map(f64::from),
+            duration: entry
+                .media
+                .as_ref()
+                .and_then(|media| media.duration)
+                .map(f64::from),
+        })
+    }
+
+    /// Sets volume level of the device, from `0.0` to `1.0`.
+    pub fn set_volume(&self, level: f32) -> Result<(), FfiError> {
+        self.device.receiver.set_volume(level)?;
+        Ok(())
+    }
+
+    /// Mutes or unmutes the device.
+    pub fn set_muted(&self, muted: bool) -> Result<(), FfiError> {
+        self.device.receiver.set_volume(Volume::from(muted))?;
+        Ok(())
+    }
+
+    /// Waits for the next event of the device at most for `timeout_ms` milliseconds, answering
+    /// heartbeat pings of the device meanwhile. Should be polled regularly, otherwise the device
+    /// closes the connection.
+    ///
+    /// # Return value
+    ///
+    /// Next event, `None` if no event has arrived within the timeout.
+    pub fn next_event(&self, timeout_ms: u64) -> Option<DeviceEvent> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let mut polled = false;
+
+        loop {
+            let timeout = {
+                let mut events = self.events.lock().unwrap();
+                while let Some(event) = events.next_pending(&self.device) {
+                    if let Some(event) = DeviceEvent::from_event(event) {
+                        return Some(event);
+                    }
+                }
+
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if (polled && remaining.is_zero()) || events.is_finished() {
+                    return None;
+                }
+
+                polled = true;
+                if events.poll_progress() {
+                    continue;
+                }
+                events.receive_timeout(Some(remaining.min(POLL_SLICE)))
+            };
+
+            // Neither the events nor the stream are locked between the slices, so that the other
+            // calls aren't blocked for the whole timeout.
+            let result = EventTracker::receive(&self.device, timeout);
+            self.events
+                .lock()
+                .unwrap()
+                .handle_received(&self.device, result);
+        }
+    }
+
+    /// Disconnects from the device.
+    pub fn disconnect(&self) -> Result<(), FfiError> {
+        Ok(self.device.disconnect()?)
+    }
+
+    /// Calls `f` with the currently cast media.
+    fn with_media<F, T>(&self, f: F) -> Result<(), FfiError>
+    where
+        F: FnOnce(&CastDevice<W>, &CastMedia) -> Result<T, Error>,
+    {
+        let media = self.media.lock().unwrap();
+        let media = media.as_ref().ok_or(FfiError::NoMedia)?;
+        f(&self.device, media)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        channels::receiver,
+        message_manager::{CastMessage, CastMessagePayload},
+        testing::FakeReceiver,
+        DEFAULT_RECEIVER_ID,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_remote_device() {
+        let fake_receiver = FakeReceiver::new();
+        let remote =
+            RemoteDevice::from_device(CastDevice::from_stream(fake_receiver.clone()).unwrap())
+                .unwrap();
+
+        assert_eq!(Err(FfiError::NoMedia), remote.play());
+
+        remote
+            .cast(
+                "https://example.com/video.mp4".to_string(),
+                "video/mp4".to_string(),
+                false,
+            )
+            .unwrap();
+        assert_eq!("PAUSED", remote.playback().unwrap().player_state);
+
+        remote.play().unwrap();
+        assert_eq!("PLAYING", remote.playback().unwrap().player_state);
+
+        remote.stop().unwrap();
+        assert_eq!(Err(FfiError::NoMedia), remote.playback().map(|_| ()));
+
+        assert_eq!(Some(DeviceEvent::Connected), remote.next_event(0));
+        assert!(fake_receiver.running_app().is_some());
+    }
+
+    #[test]
+    fn test_next_event_does_not_block_requests() {
+        let fake_receiver = FakeReceiver::new();
+        let remote =
+            RemoteDevice::from_device(CastDevice::from_stream(fake_receiver.clone()).unwrap())
+                .unwrap();
+        assert_eq!(Some(DeviceEvent::Connected), remote.next_event(0));
+
+        let events = {
+            let remote = Arc::clone(&remote);
+            std::thread::spawn(move || remote.next_event(5000))
+        };
+        std::thread::sleep(Duration::from_millis(50));
+
+        let started = Instant::now();
+        remote.set_volume(0.5).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // Response to the request goes to the requesting thread, the event comes with the
+        // unsolicited status.
+        fake_receiver.push_message(CastMessage {
+            namespace: receiver::CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: "*".to_string(),
+            payload: CastMessagePayload::String(
+                r#"{"type":"RECEIVER_STATUS","requestId":0,"status":{"volume":{"level":0.5}}}"#
+                    .to_string(),
+            ),
+        });
+
+        assert!(matches!(
+            events.join().unwrap(),
+            Some(DeviceEvent::VolumeChanged { .. })
+        ));
+    }
+}
@@ -0,0 +1,136 @@
+//! Speaker groups: group is represented by its leader device that plays the media for all the
+//! group members and manages them through the multizone channel.
+
+use crate::{
+    channels::{
+        media::StatusEntry,
+        multizone::GroupMember,
+        receiver::{Application, Volume},
+    },
+    errors::Error,
+    message_manager::RequestId,
+    CastDevice,
+};
+
+/// Speaker group that is controlled through the connection with the group leader (the device the
+/// group is advertised by via mDNS).
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_cast::{group::CastGroup, CastDevice};
+///
+/// let leader = CastDevice::connect_without_host_verification("192.168.1.2", 32187)?;
+/// leader.connection.connect("receiver-0")?;
+///
+/// let group = CastGroup::new(leader);
+/// for member in group.members()? {
+///     group.set_member_volume(member.device_id, 0.3)?;
+/// }
+/// group.pause()?;
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+pub struct CastGroup {
+    leader: CastDevice,
+}
+
+impl CastGroup {
+    /// Creates group controlled through the connection with the group `leader`.
+    pub fn new(leader: CastDevice) -> CastGroup {
+        CastGroup { leader }
+    }
+
+    /// Returns connection with the group leader, e.g. to launch application or load media.
+    pub fn leader(&self) -> &CastDevice {
+        &self.leader
+    }
+
+    /// Returns connection with the group leader, consuming the group.
+    pub fn into_leader(self) -> CastDevice {
+        self.leader
+    }
+
+    /// Retrieves the devices the group consists of.
+    pub fn members(&self) -> Result<Vec<GroupMember>, Error> {
+        Ok(self.leader.multizone.get_status()?.members)
+    }
+
+    /// Sets volume of the individual group member.
+    ///
+    /// # Arguments
+    ///
+    /// * `device_id` - Unique id of the member device (see `GroupMember::device_id`).
+    /// * `volume` - anything that can be converted to a valid `Volume` structure.
+    ///
+    /// # Return value
+    ///
+    /// Id of the sent request, see `MultizoneChannel::set_member_volume`.
+    pub fn set_member_volume<S, T>(&self, device_id: S, volume: T) -> Result<RequestId, Error>
+    where
+        S: Into<String>,
+        T: Into<Volume>,
+    {
+        self.leader.multizone.set_member_volume(device_id, volume)
+    }
+
+    /// Sets volume of the whole group, members keep their volume relative to each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `volume` - anything that can be converted to a valid `Volume` structure.
+    pub fn set_volume<T>(&self, volume: T) -> Result<Volume, Error>
+    where
+        T: Into<Volume>,
+    {
+        self.leader.receiver.set_volume(volume)
+    }
+
+    /// Resumes playback of the media the group plays.
+    pub fn play(&self) -> Result<StatusEntry, Error> {
+        let (transport_id, media_session_id) = self.media_session()?;
+        self.leader.media.play(transport_id, media_session_id)
+    }
+
+    /// Pauses playback of the media the group plays.
+    pub fn pause(&self) -> Result<StatusEntry, Error> {
+        let (transport_id, media_session_id) = self.media_session()?;
+        self.leader.media.pause(transport_id, media_session_id)
+    }
+
+    /// Stops playback of the media the group plays.
+    pub fn stop(&self) -> Result<StatusEntry, Error> {
+        let (transport_id, media_session_id) = self.media_session()?;
+        self.leader.media.stop(transport_id, media_session_id)
+    }
+
+    /// Finds media session of the application that runs on the group leader.
+    ///
+    /// # Return value
+    ///
+    /// Transport id of the application and id of its media session.
+    fn media_session(&self) -> Result<(String, i32), Error> {
+        let no_media = || Error::Unsupported("speaker group doesn't play any media".to_string());
+
+        let application = self
+            .leader
+            .receiver
+            .get_running_app()?
+            .filter(Application::supports_media)
+            .ok_or_else(no_media)?;
+
+        self.leader
+            .connection
+            .connect(application.transport_id.to_string())?;
+
+        let media_session_id = self
+            .leader
+            .media
+            .get_status(application.transport_id.to_string(), None)?
+            .entries
+            .first()
+            .map(|entry| entry.media_session_id)
+            .ok_or_else(no_media)?;
+
+        Ok((application.transport_id, media_session_id))
+    }
+}
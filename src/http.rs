@@ -0,0 +1,265 @@
+//! Minimal blocking HTTP/1.0 client used to probe and fetch the media before it's cast (e.g. to
+//! find out its content type), plain `http` goes over TCP and `https` over rustls verified with
+//! the platform root certificates.
+
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::Arc,
+    time::Duration,
+};
+
+use rustls::{ClientConfig, ClientConnection, StreamOwned};
+use url::Url;
+
+use crate::{crypto_provider, errors::Error, native_root_store, server_name};
+
+/// Maximum number of redirects followed before the request is given up.
+const MAX_REDIRECTS: usize = 5;
+/// Maximum size of the response (head and body), the client only fetches small documents
+/// (e.g. manifests).
+const MAX_RESPONSE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Response of the HTTP server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Response {
+    /// URL of the response, differs from the requested one if the request has been redirected.
+    pub url: Url,
+    /// Status code of the response.
+    pub status: u16,
+    /// Headers of the response, names are lowercase.
+    pub headers: Vec<(String, String)>,
+    /// Body of the response, empty for `HEAD` requests.
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Returns value of the first header with the `name` (lowercase).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Determines whether the status code of the response is `2xx`.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+trait Stream: Read + Write {}
+
+impl<T: Read + Write> Stream for T {}
+
+/// Sends the request with the `method` (`HEAD` or `GET`) to the `url` following redirects.
+///
+/// # Errors
+///
+/// Fails with `Error::Validation` if the URL isn't a valid `http` or `https` URL, with
+/// `Error::Io` or `Error::Tls` if the connection fails (including the `timeout` of a single read
+/// or write) and with `Error::Parsing` if the server sends malformed response or redirects too many
+/// times.
+pub(crate) fn request(method: &str, url: &str, timeout: Duration) -> Result<Response, Error> {
+    let mut url =
+        Url::parse(url).map_err(|err| Error::Validation(format!("invalid URL {url}: {err}")))?;
+
+    for _ in 0..=MAX_REDIRECTS {
+        let response = request_once(method, &url, timeout)?;
+        let location = match response.status {
+            301 | 302 | 303 | 307 | 308 => response.header("location"),
+            _ => None,
+        };
+
+        match location {
+            Some(location) => {
+                url = url.join(location).map_err(|err| {
+                    Error::Parsing(format!("invalid redirect location {location}: {err}"))
+                })?;
+            }
+            None => return Ok(response),
+        }
+    }
+
+    Err(Error::Parsing(format!(
+        "more than {MAX_REDIRECTS} redirects requesting {url}"
+    )))
+}
+
+fn request_once(method: &str, url: &Url, timeout: Duration) -> Result<Response, Error> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| Error::Validation(format!("URL {url} has no host")))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| Error::Validation(format!("URL {url} has no port")))?;
+    let secure = match url.scheme() {
+        "http" => false,
+        "https" => true,
+        scheme => {
+            return Err(Error::Validation(format!(
+                "unsupported URL scheme {scheme}, expected http or https"
+            )))
+        }
+    };
+
+    let tcp_stream = connect(host, port, timeout)?;
+    let mut stream: Box<dyn Stream> = if secure {
+        let provider = crypto_provider(None)?;
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(native_root_store(&[])?)
+            .with_no_client_auth();
+        let conn = ClientConnection::new(Arc::new(config), server_name(host)?)?;
+        Box::new(StreamOwned::new(conn, tcp_stream))
+    } else {
+        Box::new(tcp_stream)
+    };
+
+    let host_header = match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    };
+    let target = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    };
+    // HTTP/1.0 guarantees that response isn't chunked and connection is closed once it's sent.
+    write!(
+        stream,
+        "{method} {target} HTTP/1.0\r\nHost: {host_header}\r\nUser-Agent: rust_cast\r\n\
+         Accept: */*\r\n\r\n"
+    )?;
+    stream.flush()?;
+
+    read_response(&mut stream, url.clone())
+}
+
+fn connect(host: &str, port: u16, timeout: Duration) -> Result<TcpStream, Error> {
+    let host = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+    let mut last_error = None;
+
+    for address in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    Err(last_error
+        .unwrap_or_else(|| std::io::Error::new(ErrorKind::NotFound, "unresolved host"))
+        .into())
+}
+
+/// Reads the response till the end of the stream and splits it into the status, headers and body.
+fn read_response<S: Read>(stream: &mut S, url: Url) -> Result<Response, Error> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0; 8 * 1024];
+
+    loop {
+        match stream.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+            // Servers routinely close TLS connections without `close_notify`.
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof && !buffer.is_empty() => break,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        if buffer.len() > MAX_RESPONSE_SIZE {
+            return Err(Error::Parsing(format!(
+                "response of {url} exceeds {MAX_RESPONSE_SIZE} bytes"
+            )));
+        }
+    }
+
+    let separator = buffer
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| Error::Parsing(format!("malformed HTTP response of {url}")))?;
+    let head = String::from_utf8_lossy(&buffer[..separator]);
+    let mut lines = head.split("\r\n");
+
+    let status_line = lines.next().unwrap_or_default();
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| Error::Parsing(format!("malformed HTTP status line {status_line}")))?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let body = buffer.split_off(separator + 4);
+
+    Ok(Response {
+        url,
+        status,
+        headers,
+        body,
+    })
+}
+
+/// Serves `responses` to the consecutive connections, returns base URL of the server.
+#[cfg(test)]
+pub(crate) fn serve(responses: Vec<String>) -> String {
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+        thread,
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for response in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    format!("http://{address}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request() {
+        let base = serve(vec![
+            "HTTP/1.0 302 Found\r\nLocation: /video.mp4\r\n\r\n".to_string(),
+            "HTTP/1.0 200 OK\r\nContent-Type: video/mp4\r\n\r\nabc".to_string(),
+            "HTTP/1.0 500 Internal Server Error\r\n\r\n".to_string(),
+        ]);
+        let timeout = Duration::from_secs(5);
+
+        let response = request("GET", &format!("{base}/redirect"), timeout).unwrap();
+        assert_eq!(format!("{base}/video.mp4"), response.url.as_str());
+        assert!(response.is_success());
+        assert_eq!(Some("video/mp4"), response.header("content-type"));
+        assert_eq!(b"abc".to_vec(), response.body);
+
+        let response = request("HEAD", &format!("{base}/video.mp4"), timeout).unwrap();
+        assert_eq!(500, response.status);
+        assert!(!response.is_success());
+
+        assert!(matches!(
+            request("GET", "ftp://example.com/video.mp4", timeout),
+            Err(Error::Validation(_))
+        ));
+    }
+}
@@ -1,74 +1,397 @@
 #![deny(warnings)]
 
-use std::{borrow::Cow, net::TcpStream, sync::Arc};
+use std::{
+    fs,
+    net::{IpAddr, SocketAddr, TcpStream},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 use rustls::{
     client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
-    crypto::{aws_lc_rs::default_provider, verify_tls12_signature, verify_tls13_signature},
+    crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider},
     pki_types::{CertificateDer, ServerName, UnixTime},
-    ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore, StreamOwned,
+    ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore,
 };
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
+use capabilities::DeviceCapabilities;
 use channels::{
     connection::{ConnectionChannel, ConnectionResponse},
-    heartbeat::{HeartbeatChannel, HeartbeatResponse},
-    media::{MediaChannel, MediaResponse},
-    receiver::{ReceiverChannel, ReceiverResponse},
+    debug_overlay::{DebugOverlayChannel, DebugOverlayResponse},
+    error::{ErrorChannel, ErrorResponse},
+    heartbeat::{ConnectionHealth, HeartbeatChannel, HeartbeatResponse},
+    media::{Media, MediaChannel, MediaResponse, PlaybackFinished, StatusEntry},
+    mirroring::{MirroringChannel, MirroringResponse},
+    multizone::{MultizoneChannel, MultizoneResponse},
+    receiver::{Application, CastDeviceApp, ReceiverChannel, ReceiverResponse},
+    setup::{SetupChannel, SetupResponse},
 };
 use errors::Error;
-use message_manager::{CastMessage, MessageManager};
+use events::EventLoop;
+use message_manager::{
+    CancellationToken, CastMessage, CastMessagePayload, CastStream, ConnectionState, Direction,
+    Lock, MessageManager, RetryPolicy, SendQueueOptions, ShutdownHandle, TrafficMetrics,
+};
+use policy::OperationPolicy;
+use proxy::{ProxyConfig, ProxyTarget};
+use sender::CastSender;
+use wire_log::WireLogSink;
+
+#[cfg(feature = "youtube")]
+use channels::youtube::{YouTubeChannel, YouTubeResponse};
+
+/// Enters debug span with the specified name and fields till the end of the current scope if
+/// `tracing` feature is enabled, does nothing otherwise.
+macro_rules! trace_span {
+    ($name:expr $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($name $(, $($fields)*)?).entered();
+    };
+}
 
+pub mod capabilities;
 #[cfg(not(feature = "cast"))]
 mod cast;
 #[cfg(feature = "cast")]
 pub mod cast;
 pub mod channels;
+#[cfg(feature = "device_info")]
+pub mod device_info;
+#[cfg(feature = "discovery")]
+pub mod discovery;
 pub mod errors;
+pub mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod group;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "thread_safe")]
+pub mod manager;
 pub mod message_manager;
+pub mod namespaces;
+#[cfg(feature = "native-tls")]
+mod native_tls_stream;
+pub mod policy;
+#[cfg(feature = "http")]
+pub mod preflight;
+pub mod protocol;
+pub mod proxy;
+pub mod replay;
+pub mod sender;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+pub mod slideshow;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+mod tls;
 mod utils;
+pub mod wire_log;
+
+/// Generated scaffolding of the UniFFI bindings, see `rust_cast.udl`.
+#[cfg(feature = "ffi")]
+#[allow(clippy::empty_line_after_doc_comments)]
+mod uniffi_scaffolding {
+    use crate::ffi::{DeviceEvent, FfiError, PlaybackInfo, RemoteDevice};
+
+    uniffi::include_scaffolding!("rust_cast");
+}
+// Scaffolding refers to its tag type from the crate root.
+#[cfg(feature = "ffi")]
+use uniffi_scaffolding::UniFfiTag;
 
 const DEFAULT_SENDER_ID: &str = "sender-0";
 const DEFAULT_RECEIVER_ID: &str = "receiver-0";
 
+/// Pointer channels share the `MessageManager` through: `Arc` if `thread_safe` feature is enabled,
+/// `Rc` otherwise.
 #[cfg(feature = "thread_safe")]
-type Lrc<T> = std::sync::Arc<T>;
+pub type Lrc<T> = std::sync::Arc<T>;
+/// Pointer channels share the `MessageManager` through: `Arc` if `thread_safe` feature is enabled,
+/// `Rc` otherwise.
 #[cfg(not(feature = "thread_safe"))]
-type Lrc<T> = std::rc::Rc<T>;
+pub type Lrc<T> = std::rc::Rc<T>;
+
+/// Weak counterpart of `Lrc`: `sync::Weak` if `thread_safe` feature is enabled, `rc::Weak`
+/// otherwise.
+#[cfg(feature = "thread_safe")]
+pub type WeakLrc<T> = std::sync::Weak<T>;
+/// Weak counterpart of `Lrc`: `sync::Weak` if `thread_safe` feature is enabled, `rc::Weak`
+/// otherwise.
+#[cfg(not(feature = "thread_safe"))]
+pub type WeakLrc<T> = std::rc::Weak<T>;
 
 /// Supported channel message types.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ChannelMessage {
     /// Message to be processed by `ConnectionChannel`.
     Connection(ConnectionResponse),
+    /// Message to be processed by `DebugOverlayChannel`.
+    DebugOverlay(DebugOverlayResponse),
+    /// Message to be processed by `ErrorChannel`.
+    Error(ErrorResponse),
     /// Message to be processed by `HeartbeatChannel`.
     Heartbeat(HeartbeatResponse),
     /// Message to be processed by `MediaChannel`.
     Media(MediaResponse),
+    /// Message to be processed by `MirroringChannel`.
+    Mirroring(MirroringResponse),
+    /// Message to be processed by `MultizoneChannel`.
+    Multizone(MultizoneResponse),
     /// Message to be processed by `ReceiverChannel`.
     Receiver(ReceiverResponse),
+    /// Message to be processed by `SetupChannel`.
+    Setup(SetupResponse),
+    /// Message to be processed by `YouTubeChannel`.
+    #[cfg(feature = "youtube")]
+    YouTube(YouTubeResponse),
     /// Raw message is returned when built-in channels can't process it (e.g. because of unknown
     /// `namespace`).
     Raw(CastMessage),
+    /// Message that couldn't be parsed, it's only returned if parse errors are reported as
+    /// messages, see `CastDevice::set_report_parse_errors`.
+    ParseError {
+        /// Namespace of the message, empty if the frame itself couldn't be decoded.
+        namespace: String,
+        /// Payload of the message, or the whole frame if it couldn't be decoded.
+        raw: CastMessagePayload,
+        /// Description of the parse error.
+        error: String,
+    },
+}
+
+/// Channel message along with the routing details of the cast message it has been parsed from,
+/// see `CastDevice::receive_envelope`. Allows to tell e.g. which of several running applications
+/// a media status belongs to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MessageEnvelope {
+    /// Namespace the message has been received on.
+    pub namespace: String,
+    /// Id of the message sender, e.g. transport id of the application or `receiver-0`.
+    pub source: String,
+    /// Id of the message recipient, e.g. our sender id or `*` for broadcasts.
+    pub destination: String,
+    /// Parsed message.
+    pub message: ChannelMessage,
+}
+
+/// Application session that is already running on the cast device, see
+/// `CastDevice::join_session`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Session {
+    /// Running application, its `transport_id` is the destination for the media requests.
+    pub application: Application,
+    /// Status of the current media session of the application, `None` if no media is loaded or
+    /// the application doesn't support media namespace.
+    pub media_status: Option<StatusEntry>,
+}
+
+impl Session {
+    /// Returns ID of the current media session if there is any.
+    pub fn media_session_id(&self) -> Option<i32> {
+        self.media_status
+            .as_ref()
+            .map(|status| status.media_session_id)
+    }
+}
+
+/// Serializable snapshot of the application session, so that a restarted controller process can
+/// reattach to the session that is still playing, see `CastDevice::resume_from`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use rust_cast::{CastDevice, SessionSnapshot, channels::receiver::CastDeviceApp};
+/// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// cast_device.connection.connect("receiver-0")?;
+/// let session = cast_device.join_session(&CastDeviceApp::DefaultMediaReceiver)?;
+/// SessionSnapshot::new("192.168.1.2", 8009, &session).save("cast-session.json")?;
+///
+/// // After restart.
+/// let snapshot = SessionSnapshot::load("cast-session.json")?;
+/// let cast_device =
+///     CastDevice::connect_without_host_verification(snapshot.host.as_str(), snapshot.port)?;
+/// cast_device.connection.connect("receiver-0")?;
+/// let session = cast_device.resume_from(&snapshot)?;
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSnapshot {
+    /// Host name or IP address of the device.
+    pub host: String,
+    /// Port of the device.
+    pub port: u16,
+    /// Id of the application.
+    pub app_id: String,
+    /// Id of the application session.
+    pub session_id: String,
+    /// Transport id of the application.
+    pub transport_id: String,
+    /// Id of the media session, `None` if no media has been loaded.
+    pub media_session_id: Option<i32>,
+    /// Last known position of the media (in seconds), e.g. to show until fresh status arrives.
+    pub position: Option<f32>,
+}
+
+impl SessionSnapshot {
+    /// Takes snapshot of the `session` of the device at `host` and `port`.
+    pub fn new<S: Into<String>>(host: S, port: u16, session: &Session) -> SessionSnapshot {
+        let mut snapshot = SessionSnapshot {
+            host: host.into(),
+            port,
+            app_id: session.application.app_id.clone(),
+            session_id: session.application.session_id.clone(),
+            transport_id: session.application.transport_id.clone(),
+            media_session_id: None,
+            position: None,
+        };
+        if let Some(entry) = &session.media_status {
+            snapshot.update(entry);
+        }
+
+        snapshot
+    }
+
+    /// Updates media session and position of the snapshot from the media status `entry`.
+    pub fn update(&mut self, entry: &StatusEntry) {
+        self.media_session_id = Some(entry.media_session_id);
+        self.position = entry.estimated_current_time();
+    }
+
+    /// Writes the snapshot to the file at `path` as JSON, the file is replaced if it exists.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// Reads the snapshot written by `save` from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SessionSnapshot, Error> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
 }
 
-/// Structure that manages connection to a cast device.
-pub struct CastDevice<'a> {
-    message_manager: Lrc<MessageManager<StreamOwned<ClientConnection, TcpStream>>>,
+#[cfg(feature = "native-tls")]
+pub use native_tls_stream::NativeTlsStream;
+pub use tls::TlsStream;
+
+/// Structure that manages connection to a cast device over the `W` stream.
+pub struct CastDevice<W = TlsStream>
+where
+    W: CastStream,
+{
+    message_manager: Lrc<MessageManager<W>>,
 
     /// Channel that manages connection responses/requests.
-    pub connection: ConnectionChannel<'a, StreamOwned<ClientConnection, TcpStream>>,
+    pub connection: ConnectionChannel<W>,
+
+    /// Channel that controls debug overlay and logs of the custom receiver applications.
+    pub debug_overlay: DebugOverlayChannel<W>,
+
+    /// Channel that reports transport level errors.
+    pub error: ErrorChannel<W>,
 
     /// Channel that allows connection to stay alive (via ping-pong requests/responses).
-    pub heartbeat: HeartbeatChannel<'a, StreamOwned<ClientConnection, TcpStream>>,
+    pub heartbeat: HeartbeatChannel<W>,
 
     /// Channel that manages various media stuff.
-    pub media: MediaChannel<'a, StreamOwned<ClientConnection, TcpStream>>,
+    pub media: MediaChannel<W>,
+
+    /// Channel that exchanges screen mirroring and remoting signaling messages.
+    pub mirroring: MirroringChannel<W>,
+
+    /// Channel that manages members of the speaker group (if device is a group leader).
+    pub multizone: MultizoneChannel<W>,
 
     /// Channel that manages receiving platform (e.g. Chromecast).
-    pub receiver: ReceiverChannel<'a, StreamOwned<ClientConnection, TcpStream>>,
+    pub receiver: ReceiverChannel<W>,
+
+    /// Channel that manages settings of the device (e.g. its name).
+    pub setup: SetupChannel<W>,
+
+    /// Channel that controls YouTube application.
+    #[cfg(feature = "youtube")]
+    pub youtube: YouTubeChannel<W>,
+
+    /// Certificate chain presented by the device during TLS handshake.
+    peer_certificates: Vec<CertificateDer<'static>>,
+
+    /// Determines whether messages addressed to other senders are dropped by `receive`.
+    filter_destinations: AtomicBool,
+
+    /// Determines whether `receive` returns messages that can't be parsed as
+    /// `ChannelMessage::ParseError` instead of failing.
+    report_parse_errors: AtomicBool,
+
+    /// Id the channels of the device send their messages with, see `ConnectOptions::sender_id`.
+    sender_id: String,
+
+    /// Id of the receiving platform, see `ConnectOptions::receiver_id`.
+    receiver_id: String,
+
+    /// Ids of the default sender and of all the senders created with `new_sender` that are still
+    /// alive.
+    sender_ids: Lrc<Lock<Vec<String>>>,
+
+    /// Shuts down the socket of the stream without locking it, see `shutdown`.
+    shutdown_handle: Option<ShutdownHandle>,
+}
+
+/// Handle that observes the connection with the cast device without keeping it alive: the stream
+/// is still closed once `CastDevice` and all its channel handles are dropped.
+pub struct WeakCastDevice<W = TlsStream>
+where
+    W: CastStream,
+{
+    message_manager: WeakLrc<MessageManager<W>>,
 }
 
-impl<'a> CastDevice<'a> {
+impl<W> WeakCastDevice<W>
+where
+    W: CastStream,
+{
+    /// Determines whether the connection is still alive, i.e. it's neither dropped nor closed.
+    pub fn is_alive(&self) -> bool {
+        self.state() != ConnectionState::Closed
+    }
+
+    /// Returns the current state of the connection, `Closed` if the connection has been dropped.
+    pub fn state(&self) -> ConnectionState {
+        self.message_manager
+            .upgrade()
+            .map_or(ConnectionState::Closed, |message_manager| {
+                message_manager.state()
+            })
+    }
+
+    /// Returns snapshot of the traffic exchanged with the cast device, `None` if the connection has
+    /// been dropped.
+    pub fn metrics(&self) -> Option<TrafficMetrics> {
+        self.message_manager
+            .upgrade()
+            .map(|message_manager| message_manager.metrics())
+    }
+}
+
+impl<W> Clone for WeakCastDevice<W>
+where
+    W: CastStream,
+{
+    /// Returns another weak handle of the same connection.
+    fn clone(&self) -> Self {
+        WeakCastDevice {
+            message_manager: WeakLrc::clone(&self.message_manager),
+        }
+    }
+}
+
+impl CastDevice {
     /// Connects to the cast device using host name and port.
     ///
     /// # Examples
@@ -93,40 +416,11 @@ impl<'a> CastDevice<'a> {
     /// # Return value
     ///
     /// Instance of `CastDevice` that allows you to manage connection.
-    pub fn connect<S>(host: S, port: u16) -> Result<CastDevice<'a>, Error>
+    pub fn connect<S>(host: S, port: u16) -> Result<CastDevice, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
-        let host = host.into();
-        log::debug!("Establishing connection with cast device at {host}:{port}…");
-
-        let mut root_store = RootCertStore::empty();
-        let (valid, invalid) = root_store.add_parsable_certificates(
-            rustls_native_certs::load_native_certs().expect("Could not load platform certs."),
-        );
-        if invalid > 0 {
-            log::warn!(
-                "Failed to parse {invalid} out of {} root certificates.",
-                valid + invalid
-            );
-        } else {
-            log::debug!("Successfully parsed {valid} root certificates.");
-        }
-
-        let mut config = ClientConfig::builder()
-            .with_root_certificates(root_store)
-            .with_no_client_auth();
-        config.key_log = Arc::new(rustls::KeyLogFile::new());
-
-        let conn = ClientConnection::new(
-            config.into(),
-            ServerName::try_from(host.as_ref())?.to_owned(),
-        )?;
-        let stream = StreamOwned::new(conn, TcpStream::connect((host.as_ref(), port))?);
-
-        log::debug!("Connection with {host}:{port} successfully established.");
-
-        CastDevice::connect_to_device(stream)
+        CastDevice::connect_with_options(host, port, ConnectOptions::default())
     }
 
     /// Connects to the cast device using host name and port _without_ host verification. Use on
@@ -154,34 +448,188 @@ impl<'a> CastDevice<'a> {
     /// # Return value
     ///
     /// Instance of `CastDevice` that allows you to manage connection.
-    pub fn connect_without_host_verification<S>(host: S, port: u16) -> Result<CastDevice<'a>, Error>
+    pub fn connect_without_host_verification<S>(host: S, port: u16) -> Result<CastDevice, Error>
+    where
+        S: Into<String>,
+    {
+        let options = ConnectOptions {
+            verify_host: false,
+            ..ConnectOptions::default()
+        };
+        CastDevice::connect_with_options(host, port, options)
+    }
+
+    /// Connects to the cast device using host name and port with additional options. If the
+    /// `proxy` is set, the host name is resolved by the proxy, so that devices on the remote LAN
+    /// can be reached by the names known only there.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rust_cast::{proxy::ProxyConfig, CastDevice, ConnectOptions};
+    ///
+    /// let options = ConnectOptions::default().proxy(ProxyConfig::Socks5 {
+    ///     address: "127.0.0.1:1080".to_string(),
+    ///     credentials: None,
+    /// });
+    /// let device = CastDevice::connect_with_options("living-room.lan", 8009, options)?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - Cast device host name.
+    /// * `port` - Cast device port number.
+    /// * `options` - Additional options for the connection.
+    ///
+    /// # Errors
+    ///
+    /// This method may fail if connection to Cast device can't be established for some reason
+    /// (e.g. wrong host name or port).
+    ///
+    /// # Return value
+    ///
+    /// Instance of `CastDevice` that allows you to manage connection.
+    pub fn connect_with_options<S>(
+        host: S,
+        port: u16,
+        options: ConnectOptions,
+    ) -> Result<CastDevice, Error>
     where
-        S: Into<Cow<'a, str>>,
+        S: Into<String>,
     {
         let host = host.into();
+        trace_span!("cast_device.connect", %host, port, verify_host = options.verify_host);
+        log::debug!("Establishing connection with cast device at {host}:{port}…");
+
+        let server_name = match options.server_name.clone() {
+            Some(server_name) => ServerName::try_from(server_name)?,
+            None => server_name(host.as_ref())?,
+        };
+        let address = strip_ip_brackets(host.as_ref());
+        let tcp_stream = match &options.proxy {
+            Some(proxy) => proxy.connect(ProxyTarget::Host(address, port))?,
+            None => TcpStream::connect((address, port))?,
+        };
+        let device = CastDevice::connect_tls(tcp_stream, server_name, &options)?;
+
+        log::debug!("Connection with {host}:{port} successfully established.");
+
+        Ok(device)
+    }
 
-        log::debug!("Establishing non-verified connection with cast device at {host}:{port}…");
+    /// Connects to the cast device using already resolved socket address (e.g. the one discovered
+    /// via mDNS `A`/`AAAA` records). Both IPv4 and IPv6 (including link-local addresses with scope
+    /// id) addresses are supported.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::net::SocketAddr;
+    /// use rust_cast::{CastDevice, ConnectOptions};
+    ///
+    /// let address: SocketAddr = "[fe80::1%2]:8009".parse().unwrap();
+    /// let device = CastDevice::connect_to_addr(
+    ///     address,
+    ///     ConnectOptions {
+    ///         verify_host: false,
+    ///         ..ConnectOptions::default()
+    ///     },
+    /// )?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Cast device socket address.
+    /// * `options` - Additional options for the connection.
+    ///
+    /// # Errors
+    ///
+    /// This method may fail if connection to Cast device can't be established for some reason
+    /// (e.g. wrong address or certificate that doesn't match the address).
+    ///
+    /// # Return value
+    ///
+    /// Instance of `CastDevice` that allows you to manage connection.
+    pub fn connect_to_addr(addr: SocketAddr, options: ConnectOptions) -> Result<CastDevice, Error> {
+        trace_span!("cast_device.connect", %addr, verify_host = options.verify_host);
+        log::debug!("Establishing connection with cast device at {addr}…");
+
+        // Unless told otherwise, the certificate is verified against the IP address SANs.
+        let server_name = match options.server_name.clone() {
+            Some(server_name) => ServerName::try_from(server_name)?,
+            None => ServerName::IpAddress(addr.ip().into()),
+        };
+        let tcp_stream = match &options.proxy {
+            Some(proxy) => proxy.connect(ProxyTarget::Addr(addr))?,
+            None => TcpStream::connect(addr)?,
+        };
+        let device = CastDevice::connect_tls(tcp_stream, server_name, &options)?;
+
+        log::debug!("Connection with {addr} successfully established.");
+
+        Ok(device)
+    }
 
-        let mut config = ClientConfig::builder()
-            .dangerous()
-            .with_custom_certificate_verifier(Arc::new(NoCertificateVerification {}))
-            .with_no_client_auth();
+    /// Establishes TLS session over the provided TCP stream and connects to the cast device.
+    ///
+    /// # Arguments
+    ///
+    /// * `tcp_stream` - TCP connection established with the device.
+    /// * `server_name` - Name (or IP address) the device certificate is verified against.
+    /// * `options` - Options the socket and TLS session are set up with.
+    ///
+    /// # Return value
+    ///
+    /// Instance of `CastDevice` that allows you to manage connection.
+    fn connect_tls(
+        tcp_stream: TcpStream,
+        server_name: ServerName<'static>,
+        options: &ConnectOptions,
+    ) -> Result<CastDevice, Error> {
+        options.socket.apply(&tcp_stream)?;
+
+        let provider = crypto_provider(options.crypto_provider.as_ref())?;
+        let builder = ClientConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()?;
+        let mut config = if options.verify_host {
+            builder
+                .with_root_certificates(native_root_store(&options.fallback_root_certificates)?)
+                .with_no_client_auth()
+        } else {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(NoCertificateVerification::new(
+                    provider,
+                )))
+                .with_no_client_auth()
+        };
         config.key_log = Arc::new(rustls::KeyLogFile::new());
-        let stream = StreamOwned::new(
-            ClientConnection::new(
-                Arc::new(config),
-                ServerName::try_from(host.as_ref())?.to_owned(),
-            )?,
-            TcpStream::connect((host.as_ref(), port))?,
-        );
 
-        log::debug!("Connection with {host}:{port} successfully established.");
+        let mut conn = ClientConnection::new(Arc::new(config), server_name)?;
+        let mut tcp_stream = tcp_stream;
 
-        CastDevice::connect_to_device(stream)
+        // Complete handshake right away so that certificate issues are reported on connect and
+        // peer certificates are available immediately.
+        while conn.is_handshaking() {
+            conn.complete_io(&mut tcp_stream)?;
+        }
+
+        CastDevice::from_stream_with_ids(
+            TlsStream::new(conn, tcp_stream)?,
+            options.sender_id.as_str(),
+            options.receiver_id.as_str(),
+        )
     }
+}
 
+impl<W> CastDevice<W>
+where
+    W: CastStream,
+{
     /// Waits for any message returned by cast device (e.g. Chromecast) and returns its parsed
-    /// version.
+    /// version. Messages addressed to other senders are skipped, see `set_destination_filter`.
     ///
     /// # Examples
     ///
@@ -208,319 +656,1860 @@ impl<'a> CastDevice<'a> {
     ///
     /// Parsed channel message.
     pub fn receive(&self) -> Result<ChannelMessage, Error> {
-        let cast_message = self.message_manager.receive()?;
-
-        if self.connection.can_handle(&cast_message) {
-            return Ok(ChannelMessage::Connection(
-                self.connection.parse(&cast_message)?,
-            ));
-        }
+        Ok(self.receive_envelope()?.message)
+    }
 
-        if self.heartbeat.can_handle(&cast_message) {
-            return Ok(ChannelMessage::Heartbeat(
-                self.heartbeat.parse(&cast_message)?,
-            ));
+    /// Waits for any message returned by cast device and returns its parsed version along with
+    /// the namespace, source and destination of the message.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rust_cast::{channels::media::MediaResponse, ChannelMessage};
+    ///
+    /// # use rust_cast::CastDevice;
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    ///
+    /// let envelope = cast_device.receive_envelope()?;
+    /// if let ChannelMessage::Media(MediaResponse::Status(status)) = envelope.message {
+    ///     println!("Status of {} media: {:?}", envelope.source, status.entries);
+    /// }
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Usually fails if message returned by device can't be parsed.
+    ///
+    /// # Returned values
+    ///
+    /// Parsed channel message with its routing details.
+    pub fn receive_envelope(&self) -> Result<MessageEnvelope, Error> {
+        trace_span!("cast_device.receive");
+
+        loop {
+            let cast_message = match self.message_manager.receive() {
+                Err(Error::Protobuf(error)) => return self.frame_parse_error(error),
+                result => result?,
+            };
+
+            if self.is_addressed_to_us(&cast_message) {
+                return self.parse_message(cast_message);
+            }
         }
+    }
 
-        if self.media.can_handle(&cast_message) {
-            return Ok(ChannelMessage::Media(self.media.parse(&cast_message)?));
-        }
+    /// Waits for the next message from the cast device at most for the `timeout`, allows event
+    /// loops to interleave cast message handling with other work.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use rust_cast::CastDevice;
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// loop {
+    ///     if let Some(message) = cast_device.receive_timeout(Duration::from_millis(100))? {
+    ///         println!("{message:?}");
+    ///     }
+    ///     // Do some other work.
+    /// }
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Maximum time to wait for the message, `Duration::ZERO` doesn't wait at all.
+    ///
+    /// # Errors
+    ///
+    /// Usually fails if message returned by device can't be parsed.
+    ///
+    /// # Returned values
+    ///
+    /// Parsed channel message or `None` if no message has arrived within the `timeout`.
+    pub fn receive_timeout(&self, timeout: Duration) -> Result<Option<ChannelMessage>, Error> {
+        Ok(self
+            .receive_envelope_timeout(timeout)?
+            .map(|envelope| envelope.message))
+    }
 
-        if self.receiver.can_handle(&cast_message) {
-            return Ok(ChannelMessage::Receiver(
-                self.receiver.parse(&cast_message)?,
-            ));
+    /// Waits for the next message from the cast device at most for the `timeout`, see
+    /// `receive_timeout` and `receive_envelope`.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Maximum time to wait for the message, `Duration::ZERO` doesn't wait at all.
+    ///
+    /// # Errors
+    ///
+    /// Usually fails if message returned by device can't be parsed.
+    ///
+    /// # Returned values
+    ///
+    /// Parsed channel message with its routing details or `None` if no message has arrived within
+    /// the `timeout`.
+    pub fn receive_envelope_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<MessageEnvelope>, Error> {
+        trace_span!("cast_device.receive", ?timeout);
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+
+            let cast_message = match self.message_manager.receive_timeout(timeout) {
+                Err(Error::Protobuf(error)) => return self.frame_parse_error(error).map(Some),
+                result => result?,
+            };
+
+            match cast_message {
+                Some(cast_message) if self.is_addressed_to_us(&cast_message) => {
+                    return self.parse_message(cast_message).map(Some)
+                }
+                Some(_) => {}
+                None => return Ok(None),
+            }
         }
+    }
 
-        Ok(ChannelMessage::Raw(cast_message))
+    /// Returns iterator over the high-level events of the device (applications, volume, media
+    /// playback), see `EventLoop`. Heartbeat pings of the device are answered while iterating.
+    pub fn events(&self) -> EventLoop<'_, W> {
+        EventLoop::new(self)
     }
 
-    /// Connects to the cast device using provided ssl stream.
+    /// Runs the event loop (see `events`) in the background thread and passes every event to
+    /// `callback`. Only one event loop should run for the device, since every message is received
+    /// by only one of them. The thread keeps the device alive until `Disconnected` event, use
+    /// `shutdown` to stop it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use rust_cast::{events::CastEvent, CastDevice};
+    ///
+    /// let device = Arc::new(CastDevice::connect_without_host_verification("192.168.1.2", 8009)?);
+    /// device.connection.connect("receiver-0")?;
+    /// device.subscribe(None, |event| {
+    ///     if let CastEvent::VolumeChanged(volume) = event {
+    ///         println!("Volume: {:?}", volume.level);
+    ///     }
+    /// });
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
     ///
     /// # Arguments
     ///
-    /// * `ssl_stream` - SSL Stream for the TCP connection established with the device.
+    /// * `progress_interval` - Interval of `PositionChanged` events, see
+    ///   `EventLoop::with_progress_interval`.
+    /// * `callback` - Function that is called with every event.
     ///
     /// # Return value
     ///
-    /// Instance of `CastDevice` that allows you to manage connection.
-    fn connect_to_device(
-        ssl_stream: StreamOwned<ClientConnection, TcpStream>,
-    ) -> Result<CastDevice<'a>, Error> {
-        let message_manager_rc = Lrc::new(MessageManager::new(ssl_stream));
-
-        let heartbeat = HeartbeatChannel::new(
-            DEFAULT_SENDER_ID,
-            DEFAULT_RECEIVER_ID,
-            Lrc::clone(&message_manager_rc),
-        );
-        let connection = ConnectionChannel::new(DEFAULT_SENDER_ID, Lrc::clone(&message_manager_rc));
-        let receiver = ReceiverChannel::new(
-            DEFAULT_SENDER_ID,
-            DEFAULT_RECEIVER_ID,
-            Lrc::clone(&message_manager_rc),
-        );
-        let media = MediaChannel::new(DEFAULT_SENDER_ID, Lrc::clone(&message_manager_rc));
-
-        Ok(CastDevice {
-            message_manager: message_manager_rc,
-            heartbeat,
-            connection,
-            receiver,
-            media,
+    /// Handle of the event loop thread, the thread ends after `Disconnected` event.
+    #[cfg(feature = "thread_safe")]
+    pub fn subscribe<F>(
+        self: &std::sync::Arc<Self>,
+        progress_interval: Option<Duration>,
+        mut callback: F,
+    ) -> std::thread::JoinHandle<()>
+    where
+        W: Send + 'static,
+        F: FnMut(events::CastEvent) + Send + 'static,
+    {
+        events::spawn(Lrc::clone(self), progress_interval, move |event| {
+            callback(event);
+            true
         })
     }
-}
-
-#[cfg(test)]
-pub(crate) mod tests {
-    use byteorder::{BigEndian, WriteBytesExt};
-    use log::warn;
-    use protobuf::Message;
-    use std::{
-        fmt::Display,
-        io::{Read, Write},
-        sync::{Arc, RwLock},
-    };
 
-    use crate::{cast::cast_channel, utils::read_u32_from_buffer};
-
-    #[test]
+    /// Runs the event loop (see `events`) in the background thread and sends the events to the
+    /// returned channel, see `subscribe`. The loop stops after the next event once the receiver is
+    /// dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress_interval` - Interval of `PositionChanged` events, see
+    ///   `EventLoop::with_progress_interval`.
     #[cfg(feature = "thread_safe")]
-    fn test_thread_safe() {
-        use crate::CastDevice;
-
-        fn is_sync<T: Sync>() {}
-        fn is_send<T: Send>() {}
+    pub fn event_channel(
+        self: &std::sync::Arc<Self>,
+        progress_interval: Option<Duration>,
+    ) -> std::sync::mpsc::Receiver<events::CastEvent>
+    where
+        W: Send + 'static,
+    {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        events::spawn(Lrc::clone(self), progress_interval, move |event| {
+            sender.send(event).is_ok()
+        });
 
-        is_sync::<CastDevice>();
-        is_send::<CastDevice>();
+        receiver
     }
 
-    /// Represents the reader half of a split mock TCP stream for testing purposes.
-    #[derive(Debug)]
-    pub struct ReaderHalf(MockTcpStream);
-
-    impl Read for ReaderHalf {
-        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-            self.0.inner_read(buf)
-        }
+    /// Runs the event loop (see `events`) in the background thread and returns `Stream` of the
+    /// events for the async consumers, see `subscribe`. The loop stops after the next event once
+    /// the stream is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress_interval` - Interval of `PositionChanged` events, see
+    ///   `EventLoop::with_progress_interval`.
+    #[cfg(feature = "async")]
+    pub fn event_stream(
+        self: &std::sync::Arc<Self>,
+        progress_interval: Option<Duration>,
+    ) -> events::EventStream
+    where
+        W: Send + 'static,
+    {
+        events::EventStream::spawn(Lrc::clone(self), progress_interval)
     }
 
-    /// Represents the writer half of a split mock TCP stream for testing purposes.
-    #[derive(Debug)]
-    pub struct WriterHalf<'a>(&'a MockTcpStream);
-
-    impl<'a> Write for WriterHalf<'a> {
-        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-            self.0.inner_write(buf)
-        }
-
-        fn flush(&mut self) -> std::io::Result<()> {
-            self.0.inner_flush()
+    /// Blocks until the media session stops playing (e.g. media has completed), answering
+    /// heartbeat pings of the device in the meantime. Returns immediately if the media channel has
+    /// already seen the session stopping, see `MediaChannel::on_playback_finished`. Other messages
+    /// received while waiting are kept and can be retrieved with `receive` afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::{channels::media::IdleReason, CastDevice};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// # let media_session_id = 1;
+    /// let finished = cast_device.wait_for_completion(media_session_id)?;
+    /// if finished.reason == IdleReason::Finished {
+    ///     // Load the next photo or video.
+    /// }
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `media_session_id` - ID of the media session to wait for.
+    ///
+    /// # Errors
+    ///
+    /// Usually fails if connection with the device is lost.
+    pub fn wait_for_completion(&self, media_session_id: i32) -> Result<PlaybackFinished, Error> {
+        trace_span!("cast_device.wait_for_completion", media_session_id);
+
+        loop {
+            if let Some(finished) = self.media.playback_finished(media_session_id) {
+                return Ok(finished);
+            }
+
+            // Only pings and the media statuses are consumed, the rest is left in the buffer.
+            let ping = self.message_manager.receive_find_map(|message| {
+                if self.heartbeat.can_handle(message) {
+                    return Ok(
+                        matches!(self.heartbeat.parse(message)?, HeartbeatResponse::Ping)
+                            .then_some(true),
+                    );
+                }
+
+                // Parsed status records the finished media sessions.
+                if self.media.can_handle(message) && self.media.parse(message).is_ok() {
+                    return Ok(self
+                        .media
+                        .playback_finished(media_session_id)
+                        .map(|_| false));
+                }
+
+                Ok(None)
+            })?;
+
+            if ping {
+                self.heartbeat.pong()?;
+            }
+        }
+    }
+
+    /// Enables or disables filtering of the received messages by their destination. By default
+    /// `receive` (and its variants) only return messages addressed to our sender id or broadcasted
+    /// to all the senders (`*`), while messages addressed to other senders of the shared session
+    /// are dropped. Tools that sniff all the traffic of the device can disable the filtering.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether messages addressed to other senders should be dropped.
+    pub fn set_destination_filter(&self, enabled: bool) {
+        self.filter_destinations.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Enables or disables reporting of parse errors as messages. By default `receive` (and its
+    /// variants) fails if the frame or its payload can't be parsed, which usually ends the
+    /// caller's receive loop. Once enabled, such messages are returned as
+    /// `ChannelMessage::ParseError` instead, and the connection keeps running. Requests waiting
+    /// for the response are not affected.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether parse errors should be returned as messages.
+    pub fn set_report_parse_errors(&self, enabled: bool) {
+        self.report_parse_errors.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Creates additional logical sender that shares the connection with the cast device, but
+    /// sends its messages with the `sender_id` and establishes its own virtual connections. Messages
+    /// addressed to the new sender are returned by `receive` along with the ones of the default
+    /// sender, use `receive_envelope` to tell them apart.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::CastDevice;
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// let remote = cast_device.new_sender("sender-1");
+    /// remote.connection.connect("receiver-0")?;
+    ///
+    /// let status = remote.receiver.get_status()?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `sender_id` - Id of the new sender, it should differ from the id of the default sender
+    ///   (`sender-0` unless it's configured, see `sender_id`) and from the ids of other senders,
+    ///   otherwise they share virtual connections.
+    pub fn new_sender<S>(&self, sender_id: S) -> CastSender<W>
+    where
+        S: Into<String>,
+    {
+        let sender_id = sender_id.into();
+
+        // Every sender keeps its id here until it's dropped, even if it shares the id with another.
+        self.sender_ids.borrow_mut().push(sender_id.clone());
+
+        CastSender::new(
+            sender_id,
+            self.receiver_id.clone(),
+            Lrc::clone(&self.message_manager),
+            Lrc::clone(&self.sender_ids),
+        )
+    }
+
+    /// Returns id the channels of the device send their messages with, see
+    /// `ConnectOptions::sender_id`.
+    pub fn sender_id(&self) -> &str {
+        &self.sender_id
+    }
+
+    /// Returns id of the receiving platform the channels send their requests to, see
+    /// `ConnectOptions::receiver_id`.
+    pub fn receiver_id(&self) -> &str {
+        &self.receiver_id
+    }
+
+    /// Determines whether `cast_message` should be returned by `receive`, see
+    /// `set_destination_filter`.
+    fn is_addressed_to_us(&self, cast_message: &CastMessage) -> bool {
+        let addressed_to_us = !self.filter_destinations.load(Ordering::Relaxed)
+            || cast_message.destination == "*"
+            || self
+                .sender_ids
+                .borrow_mut()
+                .contains(&cast_message.destination);
+
+        if !addressed_to_us {
+            log::trace!(
+                "Dropping message addressed to {}: {:?}",
+                cast_message.destination,
+                cast_message.payload
+            );
+        }
+
+        addressed_to_us
+    }
+
+    /// Returns the next message from the cast device if it's already available, doesn't block
+    /// otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Usually fails if message returned by device can't be parsed.
+    ///
+    /// # Returned values
+    ///
+    /// Parsed channel message or `None` if no message is available.
+    pub fn try_receive(&self) -> Result<Option<ChannelMessage>, Error> {
+        self.receive_timeout(Duration::ZERO)
+    }
+
+    /// Returns the current state of the connection with the cast device, allows to tell whether
+    /// device is still reachable without issuing any request.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::{CastDevice, message_manager::ConnectionState};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// if cast_device.state() == ConnectionState::Closed {
+    ///     // Reconnect to the device.
+    /// }
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    pub fn state(&self) -> ConnectionState {
+        self.message_manager.state()
+    }
+
+    /// Registers `listener` that is called with the new state every time the state of the
+    /// connection with the cast device changes. Listener must not send or receive any messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `listener` - Function that is called with the new connection state.
+    pub fn on_state_change<F>(&self, listener: F)
+    where
+        F: Fn(ConnectionState) + Send + 'static,
+    {
+        self.message_manager.on_state_change(listener)
+    }
+
+    /// Sets the policy requests failed with transient errors (e.g. media requests rejected while
+    /// the application is still starting) are retried with, see `RetryPolicy`.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Policy to retry requests with, `RetryPolicy::none()` disables retries.
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        self.message_manager.set_retry_policy(policy)
+    }
+
+    /// Configures the queue of the messages the device doesn't accept in time (i.e. writes that
+    /// reach `SocketOptions::write_timeout`), see `SendQueueOptions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Capacity of the queue and what to do once it's full.
+    pub fn set_send_queue(&self, options: SendQueueOptions) {
+        self.message_manager.set_send_queue(options)
+    }
+
+    /// Returns liveness and latency of the connection with the cast device: connection state and
+    /// timestamps of the heartbeat messages exchanged with the device.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use rust_cast::CastDevice;
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// let health = cast_device.health();
+    /// if health.silent_for() > Some(Duration::from_secs(15)) {
+    ///     println!("Device has been silent for too long, last RTT: {:?}", health.last_rtt);
+    /// }
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    pub fn health(&self) -> ConnectionHealth {
+        self.heartbeat.health()
+    }
+
+    /// Returns snapshot of the traffic (messages and bytes per namespace, parse failures) exchanged
+    /// with the cast device.
+    pub fn metrics(&self) -> TrafficMetrics {
+        self.message_manager.metrics()
+    }
+
+    /// Sets `observer` that is called for every message sent to or received from the cast device,
+    /// replacing the previous one. Observer must not send or receive any messages.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::CastDevice;
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.set_message_observer(|message, direction| {
+    ///     println!("{:?} {}: {:?}", direction, message.namespace, message.payload);
+    /// });
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    pub fn set_message_observer<F>(&self, observer: F)
+    where
+        F: Fn(&CastMessage, Direction) + Send + 'static,
+    {
+        self.message_manager.set_message_observer(observer)
+    }
+
+    /// Enables wire log mode: every frame exchanged with the cast device is written (with sensitive
+    /// fields redacted) to the `sink`, replacing the previous one. See
+    /// `MessageManager::set_wire_log`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::{wire_log::JsonLinesSink, CastDevice};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.set_wire_log(JsonLinesSink::create("cast-wire.log")?);
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    pub fn set_wire_log<L>(&self, sink: L)
+    where
+        L: WireLogSink + 'static,
+    {
+        self.message_manager.set_wire_log(sink)
+    }
+
+    /// Disables wire log mode, dropping the sink.
+    pub fn clear_wire_log(&self) {
+        self.message_manager.clear_wire_log()
+    }
+
+    /// Sets the policy launch and volume requests are checked against before they're sent (e.g.
+    /// `QuietHours`), replacing the previous one. Blocked requests fail with
+    /// `Error::PolicyBlocked`, volume levels above the cap are lowered. See
+    /// `MessageManager::set_policy`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use rust_cast::{policy::{QuietHours, TimeOfDay}, CastDevice};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// let quiet_hours = QuietHours::new(60).block(TimeOfDay::new(22, 0), TimeOfDay::new(7, 0));
+    /// cast_device.set_policy(Arc::new(quiet_hours));
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    pub fn set_policy(&self, policy: Arc<dyn OperationPolicy>) {
+        self.message_manager.set_policy(policy)
+    }
+
+    /// Removes the policy, all the operations are allowed afterwards.
+    pub fn clear_policy(&self) {
+        self.message_manager.clear_policy()
+    }
+
+    /// Parses message received from the cast device with the channel it belongs to, messages that
+    /// can't be parsed are counted in the traffic metrics.
+    fn parse_message(&self, cast_message: CastMessage) -> Result<MessageEnvelope, Error> {
+        let namespace = cast_message.namespace.clone();
+        let source = cast_message.source.clone();
+        let destination = cast_message.destination.clone();
+        let report_parse_errors = self.report_parse_errors.load(Ordering::Relaxed);
+        let raw = report_parse_errors.then(|| cast_message.payload.clone());
+
+        let message = match (self.parse_channel_message(cast_message), raw) {
+            (Ok(message), _) => message,
+            (Err(err), raw) => {
+                self.message_manager.record_parse_failure();
+
+                let Some(raw) = raw else {
+                    return Err(err);
+                };
+                log::warn!("Failed to parse message on {namespace}: {err}");
+                ChannelMessage::ParseError {
+                    namespace: namespace.clone(),
+                    raw,
+                    error: err.to_string(),
+                }
+            }
+        };
+
+        Ok(MessageEnvelope {
+            namespace,
+            source,
+            destination,
+            message,
+        })
+    }
+
+    /// Turns `error` of the frame that couldn't be decoded into `ChannelMessage::ParseError` if
+    /// parse errors are reported as messages, see `set_report_parse_errors`.
+    fn frame_parse_error(&self, error: protobuf::Error) -> Result<MessageEnvelope, Error> {
+        if !self.report_parse_errors.load(Ordering::Relaxed) {
+            return Err(Error::Protobuf(error));
+        }
+
+        log::warn!("Failed to decode frame: {error}");
+        Ok(MessageEnvelope {
+            namespace: String::new(),
+            source: String::new(),
+            destination: String::new(),
+            message: ChannelMessage::ParseError {
+                namespace: String::new(),
+                raw: CastMessagePayload::Binary(self.message_manager.take_malformed_frame()),
+                error: error.to_string(),
+            },
+        })
+    }
+
+    /// Parses message received from the cast device with the channel it belongs to.
+    fn parse_channel_message(&self, cast_message: CastMessage) -> Result<ChannelMessage, Error> {
+        if self.connection.can_handle(&cast_message) {
+            return Ok(ChannelMessage::Connection(
+                self.connection.parse(&cast_message)?,
+            ));
+        }
+
+        if self.debug_overlay.can_handle(&cast_message) {
+            return Ok(ChannelMessage::DebugOverlay(
+                self.debug_overlay.parse(&cast_message)?,
+            ));
+        }
+
+        if self.error.can_handle(&cast_message) {
+            return Ok(ChannelMessage::Error(self.error.parse(&cast_message)?));
+        }
+
+        if self.heartbeat.can_handle(&cast_message) {
+            return Ok(ChannelMessage::Heartbeat(
+                self.heartbeat.parse(&cast_message)?,
+            ));
+        }
+
+        if self.media.can_handle(&cast_message) {
+            return Ok(ChannelMessage::Media(self.media.parse(&cast_message)?));
+        }
+
+        if self.mirroring.can_handle(&cast_message) {
+            return Ok(ChannelMessage::Mirroring(
+                self.mirroring.parse(&cast_message)?,
+            ));
+        }
+
+        if self.multizone.can_handle(&cast_message) {
+            return Ok(ChannelMessage::Multizone(
+                self.multizone.parse(&cast_message)?,
+            ));
+        }
+
+        if self.receiver.can_handle(&cast_message) {
+            return Ok(ChannelMessage::Receiver(
+                self.receiver.parse(&cast_message)?,
+            ));
+        }
+
+        if self.setup.can_handle(&cast_message) {
+            return Ok(ChannelMessage::Setup(self.setup.parse(&cast_message)?));
+        }
+
+        #[cfg(feature = "youtube")]
+        if self.youtube.can_handle(&cast_message) {
+            return Ok(ChannelMessage::YouTube(self.youtube.parse(&cast_message)?));
+        }
+
+        Ok(ChannelMessage::Raw(cast_message))
+    }
+
+    /// Sends raw message with string (usually JSON) or binary payload to the cast device, so that
+    /// namespaces that aren't covered by the built-in channels can be used. Responses are returned
+    /// by `receive` as `ChannelMessage::Raw` and can be sent back with `send_raw` as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::{CastDevice, message_manager::CastMessagePayload};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.send_raw(
+    ///     "urn:x-cast:com.example.castdata",
+    ///     "web-5",
+    ///     CastMessagePayload::String(r#"{"type":"HELLO"}"#.to_string()),
+    /// )?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Message namespace that should start with `urn:x-cast:`.
+    /// * `destination` - Destination of the message (e.g. `receiver-0` or application transport
+    ///   id).
+    /// * `payload` - Payload of the message.
+    ///
+    /// # Errors
+    ///
+    /// Fails if namespace is not valid or if network connection with cast device is lost.
+    pub fn send_raw(
+        &self,
+        namespace: &str,
+        destination: &str,
+        payload: CastMessagePayload,
+    ) -> Result<(), Error> {
+        if !namespaces::is_cast_namespace(namespace) {
+            return Err(Error::Namespace(format!(
+                "'{}' should start with '{}' prefix",
+                namespace,
+                namespaces::PREFIX
+            )));
+        }
+
+        self.message_manager.send(CastMessage {
+            namespace: namespace.to_string(),
+            source: self.sender_id.clone(),
+            destination: destination.to_string(),
+            payload,
+        })
+    }
+
+    /// Returns certificate chain presented by the device during TLS handshake, the first
+    /// certificate is the device (end-entity) certificate. Useful to implement trust-on-first-use
+    /// certificate pinning on top of `connect_without_host_verification`.
+    pub fn peer_certificates(&self) -> &[CertificateDer<'static>] {
+        &self.peer_certificates
+    }
+
+    /// Returns SHA-256 fingerprint of the device (end-entity) certificate if the device presented
+    /// any.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::CastDevice;
+    /// # let pinned_fingerprint = [0u8; 32];
+    /// let device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// if device.peer_fingerprint() != Some(pinned_fingerprint) {
+    ///     panic!("Device certificate has changed!");
+    /// }
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    pub fn peer_fingerprint(&self) -> Option<[u8; 32]> {
+        self.peer_certificates.first().map(certificate_fingerprint)
+    }
+
+    /// Returns capabilities of the device derived from its receiver status and `model_name` (e.g.
+    /// `md` TXT record of the device or `DeviceInfo::model_name`), see
+    /// `DeviceCapabilities::from_receiver_status`. Prefer `DiscoveredDevice::capabilities` for the
+    /// devices discovered via mDNS, they report their outputs more precisely.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::CastDevice;
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.connection.connect("receiver-0")?;
+    /// if cast_device.capabilities(Some("Chromecast Ultra"))?.is_4k() {
+    ///     println!("Device plays 4K video");
+    /// }
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if receiver status can't be retrieved.
+    pub fn capabilities(&self, model_name: Option<&str>) -> Result<DeviceCapabilities, Error> {
+        let status = self.receiver.get_status()?;
+        Ok(DeviceCapabilities::from_receiver_status(
+            &status, model_name,
+        ))
+    }
+
+    /// Determines whether the device is audio-only (e.g. Chromecast Audio or Nest speaker), see
+    /// `Status::is_audio_device`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if receiver status can't be retrieved.
+    pub fn is_audio_device(&self) -> Result<bool, Error> {
+        Ok(self.receiver.get_status()?.is_audio_device())
+    }
+
+    /// Picks the application the media should be cast with: the `preferred` one, unless the device
+    /// is audio-only and the application can't be launched on such devices (see
+    /// `CastDeviceApp::supports_audio_devices`), the Default Media Receiver is picked then.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::{CastDevice, channels::receiver::CastDeviceApp};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.connection.connect("receiver-0")?;
+    /// let app = cast_device.media_app(&CastDeviceApp::YouTube)?;
+    /// let session = cast_device.launch_app_if_not_running(&app, false)?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if receiver status can't be retrieved.
+    pub fn media_app(&self, preferred: &CastDeviceApp) -> Result<CastDeviceApp, Error> {
+        if preferred.supports_audio_devices() || !self.is_audio_device()? {
+            return Ok(preferred.clone());
+        }
+
+        Ok(CastDeviceApp::DefaultMediaReceiver)
+    }
+
+    /// Stops casting: stops media sessions of the running applications (if there are any) with
+    /// media `STOP` and then stops the applications themselves with receiver `STOP`. Does nothing
+    /// if no application is running.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::CastDevice;
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.connection.connect("receiver-0")?;
+    /// cast_device.stop_casting()?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if status can't be retrieved or any of the stop commands is rejected by the device.
+    /// Media sessions that are already idle are ignored.
+    pub fn stop_casting(&self) -> Result<(), Error> {
+        let status = self.receiver.get_status()?;
+
+        for application in &status.applications {
+            if application.supports_media() {
+                let transport_id = application.transport_id.as_str();
+                self.connection.connect(transport_id.to_string())?;
+
+                let media_status = self.media.get_status(transport_id.to_string(), None)?;
+                for entry in &media_status.entries {
+                    match self
+                        .media
+                        .stop(transport_id.to_string(), entry.media_session_id)
+                    {
+                        Ok(_) | Err(Error::InvalidPlayerState { .. }) => {}
+                        Err(error) => return Err(error),
+                    }
+                }
+            }
+
+            self.receiver.stop_app(application.session_id.to_string())?;
         }
+
+        Ok(())
     }
 
-    /// A mock implementation of a TCP stream for testing purposes.
-    ///
-    /// # Example
+    /// Joins application session that is already running on the cast device (e.g. started by
+    /// another sender), so that its media can be controlled without launching a new session:
+    /// connects to the application transport and fetches its current media status.
     ///
-    /// ```rust
-    /// use rust_cast::channels::media::MediaChannel;
-    /// use rust_cast::message_manager::MessageManager;
-    /// use rust_cast::Lrc;
+    /// # Examples
     ///
-    /// let stream = MockTcpStream::new();
-    /// let message_manager = Lrc::new(MessageManager::new(stream));
-    /// let channel = MediaChannel::new(
-    ///     "sender-0",
-    ///     message_manager
-    /// );
+    /// ```no_run
+    /// # use rust_cast::{CastDevice, channels::receiver::CastDeviceApp};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.connection.connect("receiver-0")?;
+    /// let session = cast_device.join_session(&CastDeviceApp::DefaultMediaReceiver)?;
+    /// if let Some(media_session_id) = session.media_session_id() {
+    ///     cast_device.media.pause(session.application.transport_id.as_str(), media_session_id)?;
+    /// }
+    /// # Ok::<(), rust_cast::errors::Error>(())
     /// ```
-    #[derive(Debug, Default, Clone)]
-    pub struct MockTcpStream {
-        /// Inner stream of the TCP stream which allows cloning and referencing the same stream source.
-        inner: Arc<RwLock<InnerStream>>,
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - `CastDeviceApp` instance reference to join.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::AppNotRunning` if the application is not running on the device.
+    pub fn join_session(&self, app: &CastDeviceApp) -> Result<Session, Error> {
+        let app_id = app.to_string();
+        let application = self
+            .receiver
+            .get_status()?
+            .applications
+            .into_iter()
+            .find(|application| application.app_id == app_id)
+            .ok_or(Error::AppNotRunning(app_id))?;
+
+        self.connection
+            .connect(application.transport_id.to_string())?;
+
+        let media_status = if application.supports_media() {
+            self.media
+                .get_status(application.transport_id.to_string(), None)?
+                .entries
+                .into_iter()
+                .next()
+        } else {
+            None
+        };
+
+        Ok(Session {
+            application,
+            media_status,
+        })
     }
 
-    impl MockTcpStream {
-        /// Creates a new empty `MockTcpStream` instance.
-        pub fn new() -> Self {
-            MockTcpStream {
-                inner: Arc::new(RwLock::new(InnerStream::default())),
+    /// Reattaches to the session captured with `SessionSnapshot` (e.g. by the controller process
+    /// before it has been restarted): connects to the application transport and fetches status of
+    /// the captured media session. Device must be the one the snapshot has been taken of.
+    ///
+    /// # Return value
+    ///
+    /// Resumed session, its `media_status` is `None` if the captured media session has ended.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::AppNotRunning` if the captured session isn't running anymore (e.g. the
+    /// application has been stopped or relaunched by another sender).
+    pub fn resume_from(&self, snapshot: &SessionSnapshot) -> Result<Session, Error> {
+        let application = self
+            .receiver
+            .get_status()?
+            .applications
+            .into_iter()
+            .find(|application| application.session_id == snapshot.session_id)
+            .ok_or_else(|| Error::AppNotRunning(snapshot.app_id.clone()))?;
+
+        self.connection
+            .connect(application.transport_id.to_string())?;
+
+        let media_status = match snapshot.media_session_id {
+            Some(media_session_id) if application.supports_media() => self
+                .media
+                .get_status(application.transport_id.to_string(), None)?
+                .entries
+                .into_iter()
+                .find(|entry| entry.media_session_id == media_session_id),
+            _ => None,
+        };
+
+        Ok(Session {
+            application,
+            media_status,
+        })
+    }
+
+    /// Launches the application unless it's already running on the cast device, in which case its
+    /// session is joined instead (see `join_session`): launching the running application again
+    /// restarts it, along with the media it plays. Either way the application transport is
+    /// connected.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::{CastDevice, channels::receiver::CastDeviceApp};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.connection.connect("receiver-0")?;
+    /// let session =
+    ///     cast_device.launch_app_if_not_running(&CastDeviceApp::DefaultMediaReceiver, false)?;
+    /// if session.media_status.is_none() {
+    ///     // Nothing is playing yet, load the media.
+    /// }
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - `CastDeviceApp` instance reference to launch or join.
+    /// * `force_relaunch` - Whether the application should be relaunched even if it's already
+    ///   running.
+    ///
+    /// # Return value
+    ///
+    /// Session of the joined application, or of the launched one (without media status).
+    pub fn launch_app_if_not_running(
+        &self,
+        app: &CastDeviceApp,
+        force_relaunch: bool,
+    ) -> Result<Session, Error> {
+        if !force_relaunch {
+            match self.join_session(app) {
+                Err(Error::AppNotRunning(_)) => {}
+                result => return result,
             }
         }
 
-        /// Add a response message to be returned by read operations on the stream.
-        pub fn add_message<M: protobuf::Message>(&mut self, message: M) {
-            let message = message.write_to_bytes().unwrap();
-            let mut mutex = self.inner.write().unwrap();
-            mutex.response_messages.push(message);
+        let application = self.receiver.launch_app(app)?;
+        self.connection
+            .connect(application.transport_id.to_string())?;
+
+        Ok(Session {
+            application,
+            media_status: None,
+        })
+    }
+
+    /// Loads `media` into the running `application`, making sure first that the application
+    /// supports media namespace at all (e.g. Backdrop or custom applications may not), so that the
+    /// request doesn't go unanswered.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::{CastDevice, channels::{media::Media, receiver::CastDeviceApp}};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// # let media = Media::builder("https://example.com/video.mp4", "video/mp4").build();
+    /// cast_device.connection.connect("receiver-0")?;
+    /// let session =
+    ///     cast_device.launch_app_if_not_running(&CastDeviceApp::DefaultMediaReceiver, false)?;
+    /// cast_device.load_media(&session.application, &media)?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `application` - Running application to load media into, its transport must be connected.
+    /// * `media` - `Media` instance that describes the media we'd like to load.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::Unsupported` if the application doesn't support media namespace.
+    pub fn load_media(
+        &self,
+        application: &Application,
+        media: &Media,
+    ) -> Result<channels::media::Status, Error> {
+        if !application.supports_media() {
+            return Err(Error::Unsupported(format!(
+                "application {} doesn't support {} namespace",
+                application.app_id,
+                namespaces::MEDIA
+            )));
         }
 
-        /// Returns the received message at the given index if present, else [None].
-        pub fn received_message(&self, index: usize) -> Option<TcpMessage> {
-            self.inner
-                .read()
-                .expect("expected to acquire read lock")
-                .received_messages
-                .get(index)
-                .cloned()
+        self.media.load(
+            application.transport_id.as_str(),
+            application.session_id.as_str(),
+            media,
+        )
+    }
+
+    /// Runs `f` with the cancellation `token`, so that requests made through any of the channels
+    /// while `f` runs fail with `Error::Cancelled` once the token is cancelled (e.g. from the UI
+    /// thread), or with `Error::Timeout` once the token deadline is reached.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use rust_cast::{CastDevice, message_manager::CancellationToken};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// let token = CancellationToken::with_timeout(Duration::from_secs(5));
+    /// let status = cast_device.with_cancellation(&token, |device| device.receiver.get_status())?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    pub fn with_cancellation<F, R>(&self, token: &CancellationToken, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&Self) -> Result<R, Error>,
+    {
+        self.message_manager.with_cancellation(token, || f(self))
+    }
+
+    /// Gracefully disconnects from the cast device: closes all virtual connections that are still
+    /// open (including the ones of the senders created with `new_sender`), flushes pending data
+    /// and shuts down the TLS session. This method is automatically
+    /// called when `CastDevice` is dropped, but calling it explicitly allows to handle errors.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::CastDevice;
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// cast_device.connection.connect("receiver-0")?;
+    /// cast_device.disconnect()?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Usually fails if network connection with cast device is already lost for some reason.
+    pub fn disconnect(&self) -> Result<(), Error> {
+        // There is nothing to disconnect gracefully once the socket is shut down.
+        if self.message_manager.is_shut_down() {
+            return Ok(());
         }
 
-        fn inner_read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
-            self.inner.write().unwrap().read(buf)
+        // Virtual connections of the senders created with `new_sender` are closed as well, they
+        // can't close them on their own once the stream is closed.
+        for (source, destination) in self.message_manager.open_connections() {
+            ConnectionChannel::new(source, Lrc::clone(&self.message_manager))
+                .disconnect(destination)?;
         }
 
-        fn inner_write(&self, buf: &[u8]) -> std::io::Result<usize> {
-            self.inner.write().unwrap().write(buf)
+        self.message_manager.close(CastStream::shutdown)
+    }
+
+    /// Shuts down the connection with the cast device right away, without closing virtual
+    /// connections or the TLS session (see `disconnect` for that). Requests of all the channel
+    /// handles (including their clones) and of the senders, as well as the ones that are blocked
+    /// waiting for the response in the other threads, fail with `Error::Shutdown` from now on.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use rust_cast::{CastDevice, errors::Error};
+    /// # let cast_device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+    /// let receiver = cast_device.receiver.clone();
+    /// cast_device.shutdown()?;
+    /// assert!(matches!(receiver.get_status(), Err(Error::Shutdown)));
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if the underlying socket can't be shut down, connection is considered shut down
+    /// anyway.
+    pub fn shutdown(&self) -> Result<(), Error> {
+        self.message_manager.shutdown();
+
+        match self.shutdown_handle {
+            Some(ref shutdown) => match shutdown() {
+                // Socket may already be closed by the device.
+                Err(err) if err.kind() != std::io::ErrorKind::NotConnected => Err(err.into()),
+                _ => Ok(()),
+            },
+            None => Ok(()),
         }
+    }
+
+    /// Determines whether the connection has been shut down with `shutdown`.
+    pub fn is_shut_down(&self) -> bool {
+        self.message_manager.is_shut_down()
+    }
 
-        fn inner_flush(&self) -> std::io::Result<()> {
-            self.inner.write().unwrap().flush()
+    /// Returns weak handle that allows to observe the connection without keeping it alive.
+    pub fn downgrade(&self) -> WeakCastDevice<W> {
+        WeakCastDevice {
+            message_manager: Lrc::downgrade(&self.message_manager),
         }
     }
 
-    impl Read for MockTcpStream {
-        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-            self.inner_read(buf)
+    /// Connects to the cast device over already established `stream`, e.g. the custom transport
+    /// (SOCKS proxy, SSH tunnel, test double) that implements `CastStream`. The stream is used as
+    /// is, so it's up to the caller to establish TLS session over it if it's needed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::net::TcpStream;
+    /// use rust_cast::CastDevice;
+    ///
+    /// // Local end of the tunnel that terminates TLS session with the device.
+    /// let stream = TcpStream::connect("127.0.0.1:18009")?;
+    /// let device = CastDevice::from_stream(stream)?;
+    /// device.connection.connect("receiver-0")?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - Stream established with the device.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the writer thread of the stream can't be spawned, see `from_stream_with_ids`.
+    ///
+    /// # Return value
+    ///
+    /// Instance of `CastDevice` that allows you to manage connection.
+    pub fn from_stream(stream: W) -> Result<CastDevice<W>, Error> {
+        CastDevice::from_stream_with_ids(stream, DEFAULT_SENDER_ID, DEFAULT_RECEIVER_ID)
+    }
+
+    /// Connects to the cast device over already established `stream` (see `from_stream`), the
+    /// channels send their messages with the `sender_id` to the receiving platform with the
+    /// `receiver_id` (see `ConnectOptions::sender_id` and `ConnectOptions::receiver_id`).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::net::TcpStream;
+    /// use rust_cast::CastDevice;
+    ///
+    /// let stream = TcpStream::connect("127.0.0.1:18009")?;
+    /// let device = CastDevice::from_stream_with_ids(stream, "sender-kitchen-panel", "receiver-0")?;
+    /// device.connection.connect(device.receiver_id())?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if the writer thread of the stream that provides the write half (see
+    /// `CastStream::split_writer`) can't be spawned.
+    pub fn from_stream_with_ids(
+        mut stream: W,
+        sender_id: &str,
+        receiver_id: &str,
+    ) -> Result<CastDevice<W>, Error> {
+        let peer_certificates = stream.peer_certificates();
+        let shutdown_handle = stream.shutdown_handle();
+        let message_manager = match stream.split_writer() {
+            Some(writer) => MessageManager::new_split(stream, writer)?,
+            None => MessageManager::new_cancellable(stream),
+        };
+        let message_manager_rc = Lrc::new(message_manager);
+        message_manager_rc.set_receiver_id(receiver_id);
+
+        let heartbeat =
+            HeartbeatChannel::new(sender_id, receiver_id, Lrc::clone(&message_manager_rc));
+        let connection = ConnectionChannel::new(sender_id, Lrc::clone(&message_manager_rc));
+        let receiver =
+            ReceiverChannel::new(sender_id, receiver_id, Lrc::clone(&message_manager_rc));
+        let media = MediaChannel::new(sender_id, Lrc::clone(&message_manager_rc));
+        let multizone =
+            MultizoneChannel::new(sender_id, receiver_id, Lrc::clone(&message_manager_rc));
+
+        Ok(CastDevice {
+            #[cfg(feature = "youtube")]
+            youtube: YouTubeChannel::new(sender_id, Lrc::clone(&message_manager_rc)),
+            debug_overlay: DebugOverlayChannel::new(sender_id, Lrc::clone(&message_manager_rc)),
+            error: ErrorChannel::new(sender_id, Lrc::clone(&message_manager_rc)),
+            mirroring: MirroringChannel::new(sender_id, Lrc::clone(&message_manager_rc)),
+            setup: SetupChannel::new(sender_id, receiver_id, Lrc::clone(&message_manager_rc)),
+            message_manager: message_manager_rc,
+            heartbeat,
+            connection,
+            receiver,
+            media,
+            multizone,
+            peer_certificates,
+            filter_destinations: AtomicBool::new(true),
+            report_parse_errors: AtomicBool::new(false),
+            sender_id: sender_id.to_string(),
+            receiver_id: receiver_id.to_string(),
+            sender_ids: Lrc::new(Lock::new(vec![sender_id.to_string()])),
+            shutdown_handle,
+        })
+    }
+}
+
+/// The additional options for connecting to the cast device.
+#[derive(Clone, Debug)]
+pub struct ConnectOptions {
+    /// Whether the certificate presented by the device should be verified. Use `false` on your own
+    /// risk!
+    pub verify_host: bool,
+    /// Name the device certificate should be verified against. If not provided, the certificate is
+    /// verified against the IP address the connection is established with.
+    pub server_name: Option<String>,
+    /// Root certificates (e.g. bundled Cast root CA) the device certificate is verified against
+    /// if the platform has no root certificates available (e.g. minimal container images).
+    pub fallback_root_certificates: Vec<CertificateDer<'static>>,
+    /// Cryptography provider the TLS session is established with. If not provided, the process
+    /// default one is used (see `CryptoProvider::install_default`), or the one of the enabled
+    /// `aws-lc-rs` or `ring` feature.
+    pub crypto_provider: Option<Arc<CryptoProvider>>,
+    /// Proxy the TCP connection is established through, `None` to connect directly.
+    pub proxy: Option<ProxyConfig>,
+    /// Options of the TCP socket the connection is established with.
+    pub socket: SocketOptions,
+    /// Id the channels of the device send their messages with, `sender-0` by default. Some
+    /// receivers need distinct ids to tell several controllers on the same host apart, see
+    /// `unique_sender_id`.
+    pub sender_id: String,
+    /// Id of the receiving platform the channels send their requests to, `receiver-0` by default.
+    pub receiver_id: String,
+}
+
+impl ConnectOptions {
+    /// Establishes the connection through the `proxy` (e.g. jump host in front of the remote LAN)
+    /// before the TLS handshake. Host names passed to `connect_with_options` are resolved by the
+    /// proxy.
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sends the messages of the device channels with the `sender_id`.
+    pub fn sender_id<S: Into<String>>(mut self, sender_id: S) -> Self {
+        self.sender_id = sender_id.into();
+        self
+    }
+
+    /// Sends the messages of the device channels with the random `sender-<uuid>` id, so that the
+    /// receiver tells this controller apart from the other ones on the same host.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_cast::ConnectOptions;
+    ///
+    /// let options = ConnectOptions::default().unique_sender_id();
+    /// assert!(options.sender_id.starts_with("sender-"));
+    /// assert_ne!(options.sender_id, ConnectOptions::default().unique_sender_id().sender_id);
+    /// ```
+    pub fn unique_sender_id(self) -> Self {
+        self.sender_id(format!("sender-{}", utils::random_uuid()))
+    }
+
+    /// Sends the requests of the device channels to the receiving platform with the
+    /// `receiver_id`.
+    pub fn receiver_id<S: Into<String>>(mut self, receiver_id: S) -> Self {
+        self.receiver_id = receiver_id.into();
+        self
+    }
+}
+
+impl PartialEq for ConnectOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.verify_host == other.verify_host
+            && self.server_name == other.server_name
+            && self.fallback_root_certificates == other.fallback_root_certificates
+            && self.proxy == other.proxy
+            && self.socket == other.socket
+            && self.sender_id == other.sender_id
+            && self.receiver_id == other.receiver_id
+            && match (&self.crypto_provider, &other.crypto_provider) {
+                (Some(provider), Some(other_provider)) => Arc::ptr_eq(provider, other_provider),
+                (provider, other_provider) => provider.is_none() && other_provider.is_none(),
+            }
+    }
+}
+
+impl Eq for ConnectOptions {}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        ConnectOptions {
+            verify_host: true,
+            server_name: None,
+            fallback_root_certificates: Vec::new(),
+            crypto_provider: None,
+            proxy: None,
+            socket: SocketOptions::default(),
+            sender_id: DEFAULT_SENDER_ID.to_string(),
+            receiver_id: DEFAULT_RECEIVER_ID.to_string(),
         }
     }
+}
 
-    impl Write for MockTcpStream {
-        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-            self.inner_write(buf)
+/// Options of the TCP socket the connection with the cast device is established with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SocketOptions {
+    /// Whether Nagle's algorithm is disabled (`TCP_NODELAY`), so that small control messages are
+    /// sent right away instead of being delayed till more data is written.
+    pub nodelay: bool,
+    /// Idle time after which TCP keepalive probes are sent, and the interval between them (where
+    /// platform supports it). Keeps NAT mappings of idle connections alive, `None` leaves the
+    /// keepalive disabled.
+    pub keepalive: Option<Duration>,
+    /// Size of the receive buffer of the socket (`SO_RCVBUF`), `None` keeps the system default.
+    pub recv_buffer_size: Option<usize>,
+    /// Maximum time the write may block for, messages that aren't written in time are queued
+    /// (see `CastDevice::set_send_queue`) so that callers don't stall on the slow device. `None`
+    /// makes writes block until the data is written.
+    pub write_timeout: Option<Duration>,
+}
+
+impl SocketOptions {
+    /// Applies the options to the `stream`.
+    fn apply(&self, stream: &TcpStream) -> Result<(), Error> {
+        let socket = socket2::SockRef::from(stream);
+        socket.set_nodelay(self.nodelay)?;
+
+        if let Some(keepalive) = self.keepalive {
+            let params = socket2::TcpKeepalive::new().with_time(keepalive);
+            #[cfg(any(
+                target_os = "android",
+                target_os = "ios",
+                target_os = "linux",
+                target_os = "macos",
+                target_os = "windows",
+            ))]
+            let params = params.with_interval(keepalive);
+            socket.set_tcp_keepalive(&params)?;
         }
 
-        fn flush(&mut self) -> std::io::Result<()> {
-            self.inner_flush()
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
         }
-    }
 
-    /// Represents a TCP message containing a received payload from the sender.
-    #[derive(Debug, Clone)]
-    #[allow(dead_code)]
-    pub struct TcpMessage {
-        /// The known length of the message.
-        pub message_length: u32,
-        /// The payload of the message.
-        pub payload: Vec<u8>,
+        stream.set_write_timeout(self.write_timeout)?;
+
+        Ok(())
     }
+}
 
-    impl TcpMessage {
-        /// Parses and returns the CastMessage contained in the payload.
-        pub fn message(&self) -> cast_channel::CastMessage {
-            <cast_channel::CastMessage as Message>::parse_from_bytes(self.payload.as_slice())
-                .unwrap()
+impl Default for SocketOptions {
+    fn default() -> Self {
+        SocketOptions {
+            nodelay: true,
+            keepalive: None,
+            recv_buffer_size: None,
+            write_timeout: None,
         }
     }
+}
+
+/// Computes SHA-256 fingerprint of the DER-encoded certificate.
+///
+/// # Arguments
+///
+/// * `certificate` - Certificate to compute fingerprint for (e.g. one of
+///   `CastDevice::peer_certificates`).
+pub fn certificate_fingerprint(certificate: &CertificateDer<'_>) -> [u8; 32] {
+    Sha256::digest(certificate.as_ref()).into()
+}
+
+/// Picks cryptography provider: the `custom` one if provided, then the process default one and
+/// finally the one of the enabled crate feature.
+///
+/// # Errors
+///
+/// Fails with `Error::Tls` if there is no provider available at all.
+pub(crate) fn crypto_provider(
+    custom: Option<&Arc<CryptoProvider>>,
+) -> Result<Arc<CryptoProvider>, Error> {
+    if let Some(provider) = custom.or(CryptoProvider::get_default()) {
+        return Ok(Arc::clone(provider));
+    }
+
+    #[cfg(feature = "aws-lc-rs")]
+    let provider = Some(rustls::crypto::aws_lc_rs::default_provider());
+    #[cfg(all(feature = "ring", not(feature = "aws-lc-rs")))]
+    let provider = Some(rustls::crypto::ring::default_provider());
+    #[cfg(not(any(feature = "aws-lc-rs", feature = "ring")))]
+    let provider: Option<CryptoProvider> = None;
+
+    provider.map(Arc::new).ok_or_else(|| {
+        Error::Tls(rustls::Error::General(
+            "no crypto provider, enable `aws-lc-rs` or `ring` feature or install the default one"
+                .to_string(),
+        ))
+    })
+}
 
-    impl Display for TcpMessage {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            write!(f, "{}", String::from_utf8_lossy(self.payload.as_slice()))
+/// Loads root certificates of the platform, certificates that can't be parsed are skipped.
+/// `fallback` certificates are used instead if the platform has no usable root certificates.
+///
+/// # Errors
+///
+/// Fails with `Error::Tls` if there are no root certificates to verify against at all.
+pub(crate) fn native_root_store(
+    fallback: &[CertificateDer<'static>],
+) -> Result<RootCertStore, Error> {
+    root_store(rustls_native_certs::load_native_certs(), fallback)
+}
+
+fn root_store(
+    native: std::io::Result<Vec<CertificateDer<'static>>>,
+    fallback: &[CertificateDer<'static>],
+) -> Result<RootCertStore, Error> {
+    let mut root_store = RootCertStore::empty();
+    let reason = match native {
+        Ok(certificates) => {
+            let (valid, invalid) = root_store.add_parsable_certificates(certificates);
+            if invalid > 0 {
+                log::warn!(
+                    "Failed to parse {invalid} out of {} root certificates.",
+                    valid + invalid
+                );
+            } else {
+                log::debug!("Successfully parsed {valid} root certificates.");
+            }
+            "platform has no valid root certificates".to_string()
         }
+        Err(err) => {
+            log::warn!("Could not load platform root certificates: {err}");
+            format!("could not load platform root certificates: {err}")
+        }
+    };
+
+    if root_store.is_empty() {
+        let (valid, _) = root_store.add_parsable_certificates(fallback.iter().cloned());
+        log::debug!("Using {valid} fallback root certificates.");
     }
 
-    #[derive(Debug, Clone, PartialEq)]
-    enum CursorLocation {
-        Length,
-        Payload,
+    if root_store.is_empty() {
+        return Err(Error::Tls(rustls::Error::General(reason)));
     }
 
-    #[derive(Debug, Clone)]
-    struct ReadCursor {
-        pub location: CursorLocation,
-        pub index: usize,
+    Ok(root_store)
+}
+
+/// Removes square brackets around IPv6 literal (e.g. `[::1]`), if any.
+fn strip_ip_brackets(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+/// Converts host name or IP literal (IPv6 literals may be wrapped into square brackets) into
+/// `ServerName` the device certificate is verified against.
+fn server_name(host: &str) -> Result<ServerName<'static>, Error> {
+    let host = strip_ip_brackets(host);
+    match host.parse::<IpAddr>() {
+        Ok(ip) => Ok(ServerName::IpAddress(ip.into())),
+        Err(_) => Ok(ServerName::try_from(host.to_string())?),
     }
+}
 
-    impl ReadCursor {
-        pub fn next(&self) -> Self {
-            match self.location {
-                CursorLocation::Length => Self {
-                    location: CursorLocation::Payload,
-                    index: self.index,
-                },
-                CursorLocation::Payload => Self {
-                    location: CursorLocation::Length,
-                    index: self.index + 1,
-                },
-            }
+impl<W> Drop for CastDevice<W>
+where
+    W: CastStream,
+{
+    fn drop(&mut self) {
+        if let Err(err) = self.disconnect() {
+            log::warn!("Failed to gracefully disconnect from cast device: {err}");
         }
     }
+}
 
-    impl Default for ReadCursor {
-        fn default() -> Self {
-            Self {
-                location: CursorLocation::Length,
-                index: 0,
-            }
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "thread_safe")]
+    fn test_thread_safe() {
+        use crate::CastDevice;
+
+        fn is_sync<T: Sync>() {}
+        fn is_send<T: Send>() {}
+        fn is_static<T: 'static>() {}
+
+        is_sync::<CastDevice>();
+        is_send::<CastDevice>();
+        is_static::<CastDevice>();
+        is_sync::<crate::sender::CastSender>();
+        is_send::<crate::sender::CastSender>();
+    }
+
+    #[test]
+    fn test_server_name() {
+        use rustls::pki_types::ServerName;
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+        assert_eq!(
+            ServerName::IpAddress(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)).into()),
+            crate::server_name("192.168.1.2").unwrap()
+        );
+        assert_eq!(
+            ServerName::IpAddress(IpAddr::V6(Ipv6Addr::LOCALHOST).into()),
+            crate::server_name("[::1]").unwrap()
+        );
+        assert_eq!(
+            ServerName::try_from("chromecast.local").unwrap(),
+            crate::server_name("chromecast.local").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_root_store() {
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(matches!(
+            crate::root_store(Err(missing), &[]),
+            Err(crate::errors::Error::Tls(rustls::Error::General(_)))
+        ));
+        assert!(crate::root_store(Ok(vec![]), &[]).is_err());
+
+        #[cfg(feature = "simulator")]
+        {
+            let certified_key =
+                rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+            let fallback = vec![certified_key.cert.der().clone()];
+
+            let root_store = crate::root_store(Ok(vec![]), &fallback).unwrap();
+            assert_eq!(1, root_store.len());
         }
     }
 
-    /// Inner representation of a stream used by `MockTcpStream` for testing purposes.
-    #[derive(Debug, Default)]
-    struct InnerStream {
-        /// The current position of the read cursor.
-        cursor: ReadCursor,
-        /// Buffer containing the messages which should be returned by the read operation.
-        response_messages: Vec<Vec<u8>>,
-        /// Buffer for storing the payload of the current message being written.
-        payload_buffer: Option<TcpMessage>,
-        /// Vector containing the received messages from the sender.
-        received_messages: Vec<TcpMessage>,
-    }
-
-    impl Read for InnerStream {
-        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-            if let Some(message) = self.response_messages.get(self.cursor.index) {
-                let result: std::io::Result<usize> = match &self.cursor.location {
-                    CursorLocation::Length => {
-                        let mut len = Vec::<u8>::new();
-                        len.write_u32::<BigEndian>(message.len() as u32).unwrap();
-                        buf[..4].copy_from_slice(len.as_slice());
-                        Ok(4)
-                    }
-                    CursorLocation::Payload => {
-                        let len = message.len();
-                        buf[..len].copy_from_slice(message.as_slice());
-                        Ok(len)
-                    }
-                };
+    #[test]
+    #[cfg(feature = "ring")]
+    fn test_crypto_provider() {
+        use std::sync::Arc;
 
-                self.cursor = self.cursor.next();
-                result
-            } else {
-                warn!("No more messages to read");
-                Ok(0)
-            }
+        let custom = Arc::new(rustls::crypto::ring::default_provider());
+        let provider = crate::crypto_provider(Some(&custom)).unwrap();
+        assert!(Arc::ptr_eq(&custom, &provider));
+
+        assert!(crate::crypto_provider(None).is_ok());
+    }
+
+    #[test]
+    fn test_socket_options() {
+        use std::{
+            net::{TcpListener, TcpStream},
+            time::Duration,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+        crate::SocketOptions {
+            nodelay: true,
+            keepalive: Some(Duration::from_secs(30)),
+            recv_buffer_size: Some(64 * 1024),
+            write_timeout: Some(Duration::from_secs(1)),
         }
+        .apply(&stream)
+        .unwrap();
+
+        let socket = socket2::SockRef::from(&stream);
+        assert!(socket.nodelay().unwrap());
+        assert!(socket.keepalive().unwrap());
+        assert!(socket.recv_buffer_size().unwrap() >= 64 * 1024);
+        assert_eq!(
+            Some(Duration::from_secs(1)),
+            stream.write_timeout().unwrap()
+        );
     }
 
-    impl Write for InnerStream {
-        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-            if let Some(mut payload_buffer) = self.payload_buffer.take() {
-                payload_buffer.payload = buf.to_vec();
-                self.received_messages.push(payload_buffer);
-            } else {
-                let length = read_u32_from_buffer(buf).unwrap();
-                self.payload_buffer = Some(TcpMessage {
-                    message_length: length,
-                    payload: vec![],
-                });
-            }
-            Ok(buf.len())
+    #[test]
+    fn test_shutdown() {
+        use std::net::{TcpListener, TcpStream};
+
+        use crate::{errors::Error, message_manager::ConnectionState, CastDevice};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let _device_end = listener.accept().unwrap();
+
+        let device = CastDevice::from_stream(stream).unwrap();
+        let receiver = device.receiver.clone();
+        let observer = device.downgrade();
+        assert!(observer.is_alive());
+
+        // Request that waits for the response must be interrupted by the shutdown.
+        #[cfg(feature = "thread_safe")]
+        std::thread::scope(|scope| {
+            let blocked = scope.spawn(|| device.receive());
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            device.shutdown().unwrap();
+            assert!(matches!(blocked.join().unwrap(), Err(Error::Shutdown)));
+        });
+        #[cfg(not(feature = "thread_safe"))]
+        device.shutdown().unwrap();
+
+        assert!(device.is_shut_down());
+        assert!(matches!(receiver.get_status(), Err(Error::Shutdown)));
+        assert!(matches!(device.receive(), Err(Error::Shutdown)));
+        assert_eq!(ConnectionState::Closed, observer.state());
+        assert!(device.disconnect().is_ok());
+
+        // Weak handle doesn't keep the connection alive.
+        drop(device);
+        drop(receiver);
+        assert!(observer.metrics().is_none());
+    }
+
+    #[test]
+    fn test_resume_from() {
+        use crate::{
+            channels::{media::Media, receiver::CastDeviceApp},
+            errors::Error,
+            testing::FakeReceiver,
+            CastDevice, SessionSnapshot,
+        };
+
+        let fake_receiver = FakeReceiver::new();
+        let device = CastDevice::from_stream(fake_receiver.clone()).unwrap();
+        let session = device
+            .launch_app_if_not_running(&CastDeviceApp::DefaultMediaReceiver, false)
+            .unwrap();
+        let media = Media::builder("https://example.com/video.mp4", "video/mp4").build();
+        let status = device.load_media(&session.application, &media).unwrap();
+
+        let mut snapshot = SessionSnapshot::new("192.168.1.2", 8009, &session);
+        assert_eq!(None, snapshot.media_session_id);
+        snapshot.update(&status.entries[0]);
+
+        let path = std::env::temp_dir().join(format!("rust-cast-{}.json", std::process::id()));
+        snapshot.save(&path).unwrap();
+        let snapshot = SessionSnapshot::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!("192.168.1.2", snapshot.host);
+        assert_eq!(session.application.session_id, snapshot.session_id);
+
+        // Controller has restarted, the connection is established anew.
+        drop(device);
+        let device = CastDevice::from_stream(fake_receiver.clone()).unwrap();
+        let resumed = device.resume_from(&snapshot).unwrap();
+        assert_eq!(
+            session.application.transport_id,
+            resumed.application.transport_id
+        );
+        assert_eq!(snapshot.media_session_id, resumed.media_session_id());
+
+        device
+            .receiver
+            .launch_app(&CastDeviceApp::DefaultMediaReceiver)
+            .unwrap();
+        assert!(matches!(
+            device.resume_from(&snapshot),
+            Err(Error::AppNotRunning(_))
+        ));
+    }
+
+    #[test]
+    fn test_disconnect_closes_connections_of_all_senders() {
+        use crate::{testing::FakeReceiver, CastDevice, DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID};
+
+        let fake_receiver = FakeReceiver::new();
+        let device = CastDevice::from_stream(fake_receiver.clone()).unwrap();
+        device.connection.connect(DEFAULT_RECEIVER_ID).unwrap();
+        let remote = device.new_sender("sender-remote");
+        remote.connection.connect(DEFAULT_RECEIVER_ID).unwrap();
+
+        device.disconnect().unwrap();
+
+        let closed = fake_receiver
+            .sent_messages()
+            .into_iter()
+            .filter(|message| message.payload.to_json().unwrap()["type"] == "CLOSE")
+            .map(|message| (message.source, message.destination))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (
+                    DEFAULT_SENDER_ID.to_string(),
+                    DEFAULT_RECEIVER_ID.to_string()
+                ),
+                ("sender-remote".to_string(), DEFAULT_RECEIVER_ID.to_string()),
+            ],
+            closed
+        );
+    }
+
+    #[test]
+    fn test_sender_ids_of_dropped_senders() {
+        use crate::{
+            message_manager::{CastMessage, CastMessagePayload},
+            testing::FakeReceiver,
+            CastDevice, ChannelMessage,
+        };
+
+        let fake_receiver = FakeReceiver::new();
+        let device = CastDevice::from_stream(fake_receiver.clone()).unwrap();
+        let message = |destination: &str| CastMessage {
+            namespace: "urn:x-cast:com.example.custom".to_string(),
+            source: "web-7".to_string(),
+            destination: destination.to_string(),
+            payload: CastMessagePayload::String(destination.to_string()),
+        };
+
+        let remote = device.new_sender("sender-remote");
+        let twin = device.new_sender("sender-remote");
+        drop(twin);
+        fake_receiver.push_message(message("sender-remote"));
+        match device.receive().unwrap() {
+            ChannelMessage::Raw(message) => assert_eq!("sender-remote", message.destination),
+            message => panic!("unexpected message {message:?}"),
         }
 
-        fn flush(&mut self) -> std::io::Result<()> {
-            // flush is never called when sending messages
-            // so we don't execute any logic here
-            Ok(())
+        drop(remote);
+        fake_receiver.push_message(message("sender-remote"));
+        fake_receiver.push_message(message("*"));
+        match device.receive().unwrap() {
+            ChannelMessage::Raw(message) => assert_eq!("*", message.destination),
+            message => panic!("unexpected message {message:?}"),
         }
     }
+
+    #[test]
+    fn test_sender_and_receiver_ids() {
+        use crate::{testing::FakeReceiver, CastDevice};
+
+        let fake_receiver = FakeReceiver::new();
+        let device =
+            CastDevice::from_stream_with_ids(fake_receiver.clone(), "sender-panel", "receiver-0")
+                .unwrap();
+        assert_eq!("sender-panel", device.sender_id());
+
+        device.connection.connect(device.receiver_id()).unwrap();
+        assert!(device.receiver.get_status().is_ok());
+        device
+            .new_sender("sender-remote")
+            .receiver
+            .get_status()
+            .unwrap();
+
+        let routes = fake_receiver
+            .sent_messages()
+            .into_iter()
+            .map(|message| (message.source, message.destination))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("sender-panel".to_string(), "receiver-0".to_string()),
+                ("sender-panel".to_string(), "receiver-0".to_string()),
+                ("sender-remote".to_string(), "receiver-0".to_string()),
+            ],
+            routes
+        );
+    }
+
+    #[test]
+    fn test_custom_receiver_id_state() {
+        use crate::{
+            message_manager::{CastMessage, CastMessagePayload, ConnectionState},
+            testing::FakeReceiver,
+            CastDevice,
+        };
+
+        let fake_receiver = FakeReceiver::new();
+        let device =
+            CastDevice::from_stream_with_ids(fake_receiver.clone(), "sender-0", "receiver-7")
+                .unwrap();
+        fake_receiver.push_message(CastMessage {
+            namespace: crate::namespaces::CONNECTION.to_string(),
+            source: "receiver-7".to_string(),
+            destination: "sender-0".to_string(),
+            payload: CastMessagePayload::String(r#"{"type":"CLOSE"}"#.to_string()),
+        });
+
+        device.receive().unwrap();
+        assert_eq!(ConnectionState::Degraded, device.state());
+    }
+
+    #[test]
+    fn test_wait_for_completion_keeps_other_messages() {
+        use crate::{
+            channels::{media::IdleReason, receiver::ReceiverResponse},
+            message_manager::{CastMessage, CastMessagePayload},
+            testing::FakeReceiver,
+            CastDevice, ChannelMessage, DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID,
+        };
+
+        let message = |namespace: &str, payload: &str| CastMessage {
+            namespace: namespace.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(payload.to_string()),
+        };
+        let fake_receiver = FakeReceiver::new();
+        let device = CastDevice::from_stream(fake_receiver.clone()).unwrap();
+        fake_receiver.push_message(message(
+            crate::namespaces::RECEIVER,
+            r#"{"requestId":0,"type":"RECEIVER_STATUS","status":{"applications":[]}}"#,
+        ));
+        fake_receiver.push_message(message(crate::namespaces::HEARTBEAT, r#"{"type":"PING"}"#));
+        fake_receiver.push_message(message(
+            crate::namespaces::MEDIA,
+            r#"{"requestId":0,"type":"MEDIA_STATUS","status":[{"mediaSessionId":7,
+                "playerState":"IDLE","idleReason":"FINISHED"}]}"#,
+        ));
+
+        let finished = device.wait_for_completion(7).unwrap();
+        assert_eq!(IdleReason::Finished, finished.reason);
+
+        // Ping has been answered, the unrelated status is still there.
+        assert!(fake_receiver.sent_messages().iter().any(|message| message
+            .payload
+            .to_json()
+            .unwrap()["type"]
+            == "PONG"));
+        assert!(matches!(
+            device.receive().unwrap(),
+            ChannelMessage::Receiver(ReceiverResponse::Status(_))
+        ));
+    }
+
+    #[test]
+    fn test_certificate_fingerprint() {
+        let certificate = rustls::pki_types::CertificateDer::from(b"abc".to_vec());
+
+        assert_eq!(
+            [
+                0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae,
+                0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61,
+                0xf2, 0x00, 0x15, 0xad
+            ],
+            crate::certificate_fingerprint(&certificate)
+        );
+    }
 }
 
 #[derive(Debug)]
-pub struct NoCertificateVerification;
+pub struct NoCertificateVerification {
+    provider: Arc<CryptoProvider>,
+}
+
+impl NoCertificateVerification {
+    /// Creates verifier that accepts any certificate, but still checks handshake signatures with
+    /// the algorithms of the `provider`.
+    pub fn new(provider: Arc<CryptoProvider>) -> Self {
+        NoCertificateVerification { provider }
+    }
+}
+
 impl ServerCertVerifier for NoCertificateVerification {
     fn verify_server_cert(
         &self,
@@ -543,7 +2532,7 @@ impl ServerCertVerifier for NoCertificateVerification {
             message,
             cert,
             dss,
-            &default_provider().signature_verification_algorithms,
+            &self.provider.signature_verification_algorithms,
         )
     }
 
@@ -557,12 +2546,12 @@ impl ServerCertVerifier for NoCertificateVerification {
             message,
             cert,
             dss,
-            &default_provider().signature_verification_algorithms,
+            &self.provider.signature_verification_algorithms,
         )
     }
 
     fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
-        default_provider()
+        self.provider
             .signature_verification_algorithms
             .supported_schemes()
     }
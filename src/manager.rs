@@ -0,0 +1,794 @@
+//! Connections with several cast devices (e.g. all the speakers of the home) that are managed
+//! together: their event loops share a small pool of worker threads and events are tagged with
+//! the name of the device they come from.
+
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, MutexGuard, RwLock,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    channels::{
+        media::{LoadOptions, Media, PlayerState},
+        receiver::CastDeviceApp,
+    },
+    errors::Error,
+    events::{CastEvent, EventTracker},
+    message_manager::CastStream,
+    policy::OperationPolicy,
+    CastDevice, TlsStream,
+};
+
+/// Maximum time a worker waits for the message of one device before it polls the next one.
+const POLL_SLICE: Duration = Duration::from_millis(20);
+
+/// Number of the status requests the latency of every group device is measured with.
+const LATENCY_SAMPLES: usize = 3;
+
+/// Margin the group playback starts with after the `PLAY` request has been sent to the slowest
+/// device, so that the requests are scheduled rather than sent late.
+const GROUP_START_DELAY: Duration = Duration::from_millis(200);
+
+/// Playback started on one of the devices by `CastManager::play_on_group`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupPlayback {
+    /// Name of the device.
+    pub device: String,
+    /// Transport id of the Default Media Receiver the media is played by.
+    pub transport_id: String,
+    /// Id of the media session.
+    pub media_session_id: i32,
+    /// Estimated one-way latency of the device, its `PLAY` request has been sent that much earlier
+    /// than the requests of the faster devices.
+    pub latency: Duration,
+}
+
+/// Media moved to another device by `CastManager::transfer`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transfer {
+    /// Name of the device the media has been moved to.
+    pub device: String,
+    /// Transport id of the application the media is played by.
+    pub transport_id: String,
+    /// Id of the media session on the device.
+    pub media_session_id: i32,
+    /// Position (in seconds) the playback has been resumed at, if the source has reported it.
+    pub position: Option<f32>,
+}
+
+/// Event of one of the devices managed by `CastManager`.
+#[derive(Debug)]
+pub struct DeviceEvent {
+    /// Name the device has been added to the manager with.
+    pub device: String,
+    /// Event of the device.
+    pub event: CastEvent,
+}
+
+/// Device managed by `CastManager` along with the state of its event loop.
+struct ManagedDevice<W>
+where
+    W: CastStream,
+{
+    name: String,
+    device: Arc<CastDevice<W>>,
+    tracker: Mutex<EventTracker>,
+    /// Index of the worker that polls the device.
+    worker: usize,
+    /// Determines whether the device has been removed from the manager.
+    removed: AtomicBool,
+}
+
+/// State shared by the manager and its workers.
+struct Shared<W>
+where
+    W: CastStream,
+{
+    devices: RwLock<Vec<Arc<ManagedDevice<W>>>>,
+    stopped: AtomicBool,
+}
+
+/// Owns connections with several cast devices and runs their event loops (see
+/// `CastDevice::events`) on a fixed number of worker threads, so that whole-home control apps
+/// don't need a thread per device. Events of all the devices are received with `next_event`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_cast::{manager::CastManager, CastDevice};
+///
+/// let manager = CastManager::new(2);
+/// for (name, host) in [("Living Room", "192.168.1.2"), ("Kitchen", "192.168.1.3")] {
+///     let device = CastDevice::connect_without_host_verification(host, 8009)?;
+///     device.connection.connect("receiver-0")?;
+///     manager.add(name, device);
+/// }
+///
+/// manager
+///     .with_device("Living Room", |device| device.receiver.set_volume(0.3))
+///     .transpose()?;
+///
+/// while let Some(event) = manager.next_event() {
+///     println!("{}: {:?}", event.device, event.event);
+/// }
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+pub struct CastManager<W = TlsStream>
+where
+    W: CastStream + Send + 'static,
+{
+    shared: Arc<Shared<W>>,
+    events: Mutex<Receiver<DeviceEvent>>,
+    workers: Vec<JoinHandle<()>>,
+    next_worker: AtomicUsize,
+    progress_interval: Option<Duration>,
+    policy: Mutex<Option<Arc<dyn OperationPolicy>>>,
+}
+
+impl<W> CastManager<W>
+where
+    W: CastStream + Send + 'static,
+{
+    /// Creates manager that runs event loops of its devices on `workers` threads.
+    pub fn new(workers: usize) -> CastManager<W> {
+        let workers = workers.max(1);
+        let (sender, receiver) = mpsc::channel();
+        let shared = Arc::new(Shared {
+            devices: RwLock::new(Vec::new()),
+            stopped: AtomicBool::new(false),
+        });
+
+        CastManager {
+            workers: (0..workers)
+                .map(|index| {
+                    let shared = Arc::clone(&shared);
+                    let sender = sender.clone();
+                    thread::spawn(move || run_worker(&shared, index, &sender))
+                })
+                .collect(),
+            shared,
+            events: Mutex::new(receiver),
+            next_worker: AtomicUsize::new(0),
+            progress_interval: None,
+            policy: Mutex::new(None),
+        }
+    }
+
+    /// Makes event loops of the devices added afterwards emit `PositionChanged` events, see
+    /// `EventLoop::with_progress_interval`.
+    pub fn with_progress_interval(mut self, interval: Duration) -> Self {
+        self.progress_interval = Some(interval);
+        self
+    }
+
+    /// Adds the `device` to the manager, replacing the device previously added with the same
+    /// `name`. Event loop of the device starts right away and the policy set with `set_policy`
+    /// (if any) applies to the device.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name the device is looked up by and its events are tagged with, e.g. friendly
+    ///   name of the device.
+    /// * `device` - Connection with the device.
+    ///
+    /// # Return value
+    ///
+    /// Shared connection with the device.
+    pub fn add<S: Into<String>>(&self, name: S, device: CastDevice<W>) -> Arc<CastDevice<W>> {
+        let name = name.into();
+        self.remove(&name);
+        if let Some(policy) = self.policy.lock().unwrap().as_ref() {
+            device.set_policy(Arc::clone(policy));
+        }
+
+        let managed = Arc::new(ManagedDevice {
+            name,
+            device: Arc::new(device),
+            tracker: Mutex::new(EventTracker::new(self.progress_interval)),
+            worker: self.next_worker.fetch_add(1, Ordering::Relaxed) % self.workers.len(),
+            removed: AtomicBool::new(false),
+        });
+        let device = Arc::clone(&managed.device);
+        self.shared.devices.write().unwrap().push(managed);
+
+        device
+    }
+
+    /// Sets the policy launch and volume requests of all the devices (including the ones added
+    /// afterwards) are checked against, see `CastDevice::set_policy`.
+    pub fn set_policy(&self, policy: Arc<dyn OperationPolicy>) {
+        let mut current = self.policy.lock().unwrap();
+        for managed in self.shared.devices.read().unwrap().iter() {
+            managed.device.set_policy(Arc::clone(&policy));
+        }
+        *current = Some(policy);
+    }
+
+    /// Removes the policy from all the devices, see `set_policy`.
+    pub fn clear_policy(&self) {
+        let mut current = self.policy.lock().unwrap();
+        for managed in self.shared.devices.read().unwrap().iter() {
+            managed.device.clear_policy();
+        }
+        *current = None;
+    }
+
+    /// Removes the device from the manager and stops its event loop, connection is closed once
+    /// the returned device is dropped.
+    pub fn remove(&self, name: &str) -> Option<Arc<CastDevice<W>>> {
+        let mut devices = self.shared.devices.write().unwrap();
+        let index = devices.iter().position(|managed| managed.name == name)?;
+        let managed = devices.remove(index);
+        managed.removed.store(true, Ordering::Relaxed);
+
+        Some(Arc::clone(&managed.device))
+    }
+
+    /// Returns the device added with the `name`, if any. Event loop of the device keeps running,
+    /// so it may receive the responses the requests made through it wait for, use `with_device`
+    /// for such requests.
+    pub fn device(&self, name: &str) -> Option<Arc<CastDevice<W>>> {
+        self.managed(name)
+            .map(|managed| Arc::clone(&managed.device))
+    }
+
+    /// Pauses the event loop of the device added with the `name` and calls `f` with the device,
+    /// so that the responses to the requests `f` makes can't be taken by the loop. Messages
+    /// received meanwhile are passed to the loop once `f` returns.
+    ///
+    /// # Return value
+    ///
+    /// Result of `f`, `None` if there is no device with the `name`.
+    pub fn with_device<F, R>(&self, name: &str, f: F) -> Option<R>
+    where
+        F: FnOnce(&CastDevice<W>) -> R,
+    {
+        let managed = self.managed(name)?;
+        let _paused = managed.tracker.lock().unwrap();
+
+        Some(f(&managed.device))
+    }
+
+    /// Launches the Default Media Receiver on the `devices` (unless it's already running), loads
+    /// the `media` paused and starts the playback on all of them at once. Start is aligned on the
+    /// best effort basis: latency of every device is estimated with the status requests, and the
+    /// `PLAY` requests of the slower devices are sent earlier. Event loops of the devices are
+    /// paused meanwhile, see `with_device`. Speaker groups (see `CastGroup`) synchronize their
+    /// members themselves and don't need this.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rust_cast::{channels::media::Media, manager::CastManager, CastDevice};
+    ///
+    /// let manager = CastManager::new(1);
+    /// for (name, host) in [("Left", "192.168.1.2"), ("Right", "192.168.1.3")] {
+    ///     let device = CastDevice::connect_without_host_verification(host, 8009)?;
+    ///     device.connection.connect("receiver-0")?;
+    ///     manager.add(name, device);
+    /// }
+    ///
+    /// let media = Media::builder("https://example.com/wall.mp4", "video/mp4").build();
+    /// manager.play_on_group(&media, &["Left", "Right"])?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `media` - Media to play on all the devices.
+    /// * `devices` - Names of the devices.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::Validation` if any of the `devices` isn't managed or is listed more than
+    /// once, or with the first error any device has failed with. Playback may have started on some devices anyway.
+    pub fn play_on_group(
+        &self,
+        media: &Media,
+        devices: &[&str],
+    ) -> Result<Vec<GroupPlayback>, Error> {
+        if let Some(name) = devices
+            .iter()
+            .enumerate()
+            .find_map(|(index, name)| devices[..index].contains(name).then_some(name))
+        {
+            return Err(Error::Validation(format!(
+                "device {name} is listed more than once"
+            )));
+        }
+
+        let managed = devices
+            .iter()
+            .map(|name| {
+                self.managed(name)
+                    .ok_or_else(|| Error::Validation(format!("unknown device {name}")))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let _paused = pause_event_loops(&managed);
+
+        let mut playbacks = thread::scope(|scope| {
+            managed
+                .iter()
+                .map(|managed| scope.spawn(|| prepare_group_playback(managed, media)))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Result<Vec<_>, Error>>()
+        })?;
+
+        let slowest = playbacks
+            .iter()
+            .map(|playback| playback.latency)
+            .max()
+            .unwrap_or_default();
+        let start = Instant::now() + slowest + GROUP_START_DELAY;
+
+        thread::scope(|scope| {
+            managed
+                .iter()
+                .zip(&playbacks)
+                .map(|(managed, playback)| {
+                    scope.spawn(move || {
+                        thread::sleep(
+                            (start - playback.latency).saturating_duration_since(Instant::now()),
+                        );
+                        managed
+                            .device
+                            .media
+                            .play(playback.transport_id.as_str(), playback.media_session_id)
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .try_for_each(|handle| handle.join().unwrap().map(|_| ()))
+        })?;
+
+        playbacks.sort_by_key(|playback| devices.iter().position(|name| *name == playback.device));
+
+        Ok(playbacks)
+    }
+
+    /// Moves the media that is playing on the `source` device (or speaker group) to the `target`
+    /// one, like "Transfer audio" of Google Home: the media is loaded into the same application
+    /// on the target (or the Default Media Receiver if the target is audio-only and the
+    /// application can't run there, see `CastDevice::media_app`) at the position it has reached
+    /// on the source, with the same active tracks, and is then stopped on the source along with
+    /// its application. Paused media stays paused. Event loops of both devices are paused
+    /// meanwhile, see `with_device`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rust_cast::{manager::CastManager, CastDevice};
+    ///
+    /// let manager = CastManager::new(1);
+    /// for (name, host) in [("Kitchen", "192.168.1.2"), ("Living Room", "192.168.1.3")] {
+    ///     let device = CastDevice::connect_without_host_verification(host, 8009)?;
+    ///     device.connection.connect("receiver-0")?;
+    ///     manager.add(name, device);
+    /// }
+    ///
+    /// let transfer = manager.transfer("Kitchen", "Living Room")?;
+    /// println!("Playing at {:?} on {}", transfer.position, transfer.device);
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Name of the device the media is playing on.
+    /// * `target` - Name of the device the media should be moved to.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::Validation` if any of the devices isn't managed, they are the same
+    /// device or no media is loaded on the `source`. If the media can't be loaded on the `target`
+    /// it keeps playing on the `source`.
+    pub fn transfer(&self, source: &str, target: &str) -> Result<Transfer, Error> {
+        if source == target {
+            return Err(Error::Validation(format!(
+                "media can't be transferred from {source} to itself"
+            )));
+        }
+
+        let [source_managed, target_managed] = [source, target].map(|name| {
+            self.managed(name)
+                .ok_or_else(|| Error::Validation(format!("unknown device {name}")))
+        });
+        let (source_managed, target_managed) = (source_managed?, target_managed?);
+        let managed = [source_managed, target_managed];
+        let _paused = pause_event_loops(&managed);
+        let [source_managed, target_managed] = &managed;
+        let source_device = &source_managed.device;
+        let target_device = &target_managed.device;
+
+        let no_media = || Error::Validation(format!("no media is playing on {source}"));
+        let application = source_device
+            .receiver
+            .get_running_app()?
+            .filter(|application| application.supports_media())
+            .ok_or_else(no_media)?;
+        let app = CastDeviceApp::from_str(application.app_id.as_str()).unwrap();
+        let session = source_device.join_session(&app)?;
+        let (entry, media) = session
+            .media_status
+            .and_then(|entry| entry.media.clone().map(|media| (entry, media)))
+            .ok_or_else(no_media)?;
+        let position = entry.estimated_current_time();
+
+        let target_app = target_device.media_app(&app)?;
+        let target_session = target_device.launch_app_if_not_running(&target_app, false)?;
+        let transport_id = target_session.application.transport_id;
+        let status = target_device.media.load_with_opts(
+            transport_id.as_str(),
+            target_session.application.session_id.as_str(),
+            &media,
+            LoadOptions {
+                current_time: position.map(f64::from).unwrap_or_default(),
+                autoplay: entry.player_state != PlayerState::Paused,
+                active_track_ids: Some(entry.active_track_ids.clone())
+                    .filter(|track_ids| !track_ids.is_empty()),
+                ..LoadOptions::default()
+            },
+        )?;
+        let media_session_id = status
+            .entries
+            .first()
+            .map(|entry| entry.media_session_id)
+            .ok_or_else(|| Error::Internal("media status has no entries".to_string()))?;
+
+        match source_device.media.stop(
+            session.application.transport_id.as_str(),
+            entry.media_session_id,
+        ) {
+            Ok(_) | Err(Error::InvalidPlayerState { .. }) => {}
+            Err(error) => return Err(error),
+        }
+        source_device
+            .receiver
+            .stop_app(session.application.session_id.as_str())?;
+
+        Ok(Transfer {
+            device: target_managed.name.clone(),
+            transport_id,
+            media_session_id,
+            position,
+        })
+    }
+
+    /// Returns the managed device with the `name`.
+    fn managed(&self, name: &str) -> Option<Arc<ManagedDevice<W>>> {
+        self.shared
+            .devices
+            .read()
+            .unwrap()
+            .iter()
+            .find(|managed| managed.name == name)
+            .cloned()
+    }
+
+    /// Returns names of all the managed devices in the order they have been added.
+    pub fn device_names(&self) -> Vec<String> {
+        self.shared
+            .devices
+            .read()
+            .unwrap()
+            .iter()
+            .map(|managed| managed.name.clone())
+            .collect()
+    }
+
+    /// Waits for the next event of any device, `None` is returned only if the workers have stopped
+    /// (e.g. panicked).
+    pub fn next_event(&self) -> Option<DeviceEvent> {
+        self.events.lock().unwrap().recv().ok()
+    }
+
+    /// Waits for the next event of any device at most for the `timeout`.
+    ///
+    /// # Return value
+    ///
+    /// Next event, `None` if no event has arrived within the `timeout`.
+    pub fn next_event_timeout(&self, timeout: Duration) -> Option<DeviceEvent> {
+        self.events.lock().unwrap().recv_timeout(timeout).ok()
+    }
+}
+
+impl<W> Drop for CastManager<W>
+where
+    W: CastStream + Send + 'static,
+{
+    fn drop(&mut self) {
+        self.shared.stopped.store(true, Ordering::Relaxed);
+
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                log::warn!("Cast manager worker has panicked.");
+            }
+        }
+    }
+}
+
+/// Launches the Default Media Receiver on the device, loads the `media` paused and estimates the
+/// latency of the device.
+fn prepare_group_playback<W>(
+    managed: &ManagedDevice<W>,
+    media: &Media,
+) -> Result<GroupPlayback, Error>
+where
+    W: CastStream + Send + 'static,
+{
+    let device = &managed.device;
+    let session = device.launch_app_if_not_running(&CastDeviceApp::DefaultMediaReceiver, false)?;
+    let transport_id = session.application.transport_id;
+
+    let status = device.media.load_with_opts(
+        transport_id.as_str(),
+        session.application.session_id.as_str(),
+        media,
+        LoadOptions {
+            autoplay: false,
+            ..LoadOptions::default()
+        },
+    )?;
+    let media_session_id = status
+        .entries
+        .first()
+        .map(|entry| entry.media_session_id)
+        .ok_or_else(|| Error::Internal("media status has no entries".to_string()))?;
+
+    let mut round_trip = Duration::MAX;
+    for _ in 0..LATENCY_SAMPLES {
+        let sent_at = Instant::now();
+        device
+            .media
+            .get_status(transport_id.as_str(), Some(media_session_id))?;
+        round_trip = round_trip.min(sent_at.elapsed());
+    }
+
+    Ok(GroupPlayback {
+        device: managed.name.clone(),
+        transport_id,
+        media_session_id,
+        latency: round_trip / 2,
+    })
+}
+
+/// Pauses event loops of the distinct `devices` until the returned guards are dropped. Trackers
+/// are always locked in the same order (by address), so that the concurrent calls pausing the
+/// same devices in different order don't deadlock.
+fn pause_event_loops<W>(devices: &[Arc<ManagedDevice<W>>]) -> Vec<MutexGuard<'_, EventTracker>>
+where
+    W: CastStream,
+{
+    let mut devices = devices.iter().collect::<Vec<_>>();
+    devices.sort_by_key(|managed| Arc::as_ptr(managed));
+
+    devices
+        .into_iter()
+        .map(|managed| managed.tracker.lock().unwrap())
+        .collect()
+}
+
+/// Polls the devices assigned to the worker with `index` one after another and sends their events
+/// to `events` until the manager is dropped.
+fn run_worker<W>(shared: &Shared<W>, index: usize, events: &Sender<DeviceEvent>)
+where
+    W: CastStream + Send + 'static,
+{
+    while !shared.stopped.load(Ordering::Relaxed) {
+        let devices = shared
+            .devices
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|managed| managed.worker == index)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut polled = false;
+        for managed in devices {
+            // Event loop of the device is paused by `with_device`, `play_on_group` or `transfer`.
+            let Ok(mut tracker) = managed.tracker.try_lock() else {
+                continue;
+            };
+            if tracker.is_finished() {
+                continue;
+            }
+
+            tracker.poll(&managed.device, Some(POLL_SLICE));
+            polled = true;
+
+            while let Some(event) = tracker.next_pending(&managed.device) {
+                if managed.removed.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                let event = DeviceEvent {
+                    device: managed.name.clone(),
+                    event,
+                };
+                // Nobody waits for the events anymore if the manager is being dropped.
+                let _ = events.send(event);
+            }
+        }
+
+        if !polled {
+            thread::sleep(POLL_SLICE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::MockTcpStream;
+
+    use super::*;
+
+    #[test]
+    fn test_cast_manager() {
+        let manager = CastManager::new(2);
+        manager.add(
+            "Living Room",
+            CastDevice::from_stream(MockTcpStream::new()).unwrap(),
+        );
+        manager.add(
+            "Kitchen",
+            CastDevice::from_stream(MockTcpStream::new()).unwrap(),
+        );
+
+        assert!(manager.device("Bedroom").is_none());
+        assert_eq!(vec!["Living Room", "Kitchen"], manager.device_names());
+
+        let next_event = || manager.next_event_timeout(Duration::from_secs(5)).unwrap();
+        let mut connected = vec![next_event(), next_event()]
+            .into_iter()
+            .inspect(|event| assert!(matches!(event.event, CastEvent::DeviceConnected)))
+            .map(|event| event.device)
+            .collect::<Vec<_>>();
+        connected.sort();
+        assert_eq!(vec!["Kitchen", "Living Room"], connected);
+
+        manager.device("Kitchen").unwrap().shutdown().unwrap();
+        let event = next_event();
+        assert_eq!("Kitchen", event.device);
+        assert!(matches!(event.event, CastEvent::Error(Error::Shutdown)));
+        assert!(matches!(next_event().event, CastEvent::Disconnected));
+        assert!(manager
+            .next_event_timeout(Duration::from_millis(50))
+            .is_none());
+
+        assert!(manager.remove("Kitchen").is_some());
+        assert_eq!(vec!["Living Room"], manager.device_names());
+    }
+
+    #[test]
+    fn test_play_on_group() {
+        use crate::testing::FakeReceiver;
+
+        let manager = CastManager::new(1);
+        let receivers = [FakeReceiver::new(), FakeReceiver::new()];
+        for (name, receiver) in ["Left", "Right"].into_iter().zip(&receivers) {
+            let device = CastDevice::from_stream(receiver.clone()).unwrap();
+            manager.add(name, device);
+        }
+
+        let media = Media::builder("https://example.com/wall.mp4", "video/mp4").build();
+        assert!(matches!(
+            manager.play_on_group(&media, &["Left", "Middle"]),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            manager.play_on_group(&media, &["Left", "Right", "Left"]),
+            Err(Error::Validation(_))
+        ));
+
+        let playbacks = manager.play_on_group(&media, &["Right", "Left"]).unwrap();
+        assert_eq!(
+            vec!["Right", "Left"],
+            playbacks
+                .iter()
+                .map(|playback| playback.device.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        for (receiver, playback) in receivers.iter().zip(playbacks.iter().rev()) {
+            let app = receiver.running_app().unwrap();
+            assert_eq!(app.transport_id, playback.transport_id);
+
+            let sent_types = receiver
+                .sent_messages()
+                .iter()
+                .filter_map(|message| message.payload.to_json().ok())
+                .filter_map(|payload| payload["type"].as_str().map(str::to_string))
+                .filter(|message_type| message_type == "LOAD" || message_type == "PLAY")
+                .collect::<Vec<_>>();
+            assert_eq!(vec!["LOAD", "PLAY"], sent_types);
+        }
+
+        let status = manager
+            .with_device("Left", |device| {
+                device
+                    .media
+                    .get_status(playbacks[1].transport_id.as_str(), None)
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(PlayerState::Playing, status.entries[0].player_state);
+    }
+
+    #[test]
+    fn test_transfer() {
+        use crate::testing::FakeReceiver;
+
+        let manager = CastManager::new(1);
+        let receivers = [FakeReceiver::new(), FakeReceiver::new()];
+        for (name, receiver) in ["Kitchen", "Living Room"].into_iter().zip(&receivers) {
+            let device = CastDevice::from_stream(receiver.clone()).unwrap();
+            manager.add(name, device);
+        }
+
+        assert!(matches!(
+            manager.transfer("Kitchen", "Living Room"),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            manager.transfer("Kitchen", "Kitchen"),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            manager.transfer("Kitchen", "Bedroom"),
+            Err(Error::Validation(_))
+        ));
+
+        let media = Media::builder("https://example.com/song.mp3", "audio/mpeg").build();
+        manager
+            .with_device("Kitchen", |device| {
+                let session = device
+                    .launch_app_if_not_running(&CastDeviceApp::DefaultMediaReceiver, false)?;
+                let status = device.load_media(&session.application, &media)?;
+                device.media.seek(
+                    session.application.transport_id.as_str(),
+                    status.entries[0].media_session_id,
+                    Some(42.),
+                    None,
+                )?;
+                device.media.pause(
+                    session.application.transport_id.as_str(),
+                    status.entries[0].media_session_id,
+                )
+            })
+            .unwrap()
+            .unwrap();
+
+        let transfer = manager.transfer("Kitchen", "Living Room").unwrap();
+        assert_eq!("Living Room", transfer.device);
+        assert_eq!(Some(42.), transfer.position);
+        assert!(receivers[0].running_app().is_none());
+        assert_eq!(
+            transfer.transport_id,
+            receivers[1].running_app().unwrap().transport_id
+        );
+
+        let status = manager
+            .with_device("Living Room", |device| {
+                device
+                    .media
+                    .get_status(transfer.transport_id.as_str(), None)
+            })
+            .unwrap()
+            .unwrap();
+        let entry = &status.entries[0];
+        assert_eq!(PlayerState::Paused, entry.player_state);
+        assert_eq!(Some(42.), entry.current_time);
+        assert_eq!(
+            "https://example.com/song.mp3",
+            entry.media.as_ref().unwrap().content_id
+        );
+    }
+}
@@ -1,24 +1,43 @@
 use std::{
-    io::{Read, Write},
+    collections::{HashMap, HashSet, VecDeque},
+    io::{Error as IoError, ErrorKind, Read, Write},
+    net::{Shutdown, TcpStream},
     num::NonZeroU32,
     ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
+use protobuf::Message;
+use rustls::{pki_types::CertificateDer, ClientConnection, StreamOwned};
+use serde_derive::{Deserialize, Serialize};
+
 use crate::{
     cast::{
         cast_channel,
         cast_channel::cast_message::{PayloadType, ProtocolVersion},
     },
+    channels::{
+        connection::{self, CloseReason},
+        error::{self, TransportError},
+        heartbeat,
+    },
     errors::Error,
+    policy::{Decision, Operation, OperationPolicy},
     utils,
+    wire_log::{WireFrame, WireLogSink},
 };
 
-struct Lock<T>(
+pub(crate) struct Lock<T>(
     #[cfg(feature = "thread_safe")] std::sync::Mutex<T>,
     #[cfg(not(feature = "thread_safe"))] std::cell::RefCell<T>,
 );
 
-struct LockGuardMut<'a, T>(
+pub(crate) struct LockGuardMut<'a, T>(
     #[cfg(feature = "thread_safe")] std::sync::MutexGuard<'a, T>,
     #[cfg(not(feature = "thread_safe"))] std::cell::RefMut<'a, T>,
 );
@@ -38,7 +57,7 @@ impl<'a, T> DerefMut for LockGuardMut<'a, T> {
 }
 
 impl<T> Lock<T> {
-    fn new(data: T) -> Self {
+    pub(crate) fn new(data: T) -> Self {
         Lock({
             #[cfg(feature = "thread_safe")]
             let lock = std::sync::Mutex::new(data);
@@ -48,7 +67,7 @@ impl<T> Lock<T> {
         })
     }
 
-    fn borrow_mut(&self) -> LockGuardMut<'_, T> {
+    pub(crate) fn borrow_mut(&self) -> LockGuardMut<'_, T> {
         LockGuardMut({
             #[cfg(feature = "thread_safe")]
             let guard = self.0.lock().unwrap();
@@ -60,7 +79,7 @@ impl<T> Lock<T> {
 }
 
 /// Type of the payload that `CastMessage` can have.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CastMessagePayload {
     /// Payload represented by UTF-8 string (usually it's just a JSON string).
     String(String),
@@ -68,8 +87,43 @@ pub enum CastMessagePayload {
     Binary(Vec<u8>),
 }
 
+impl CastMessagePayload {
+    /// Returns string payload, or `None` if payload is binary.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            CastMessagePayload::String(ref payload) => Some(payload),
+            CastMessagePayload::Binary(_) => None,
+        }
+    }
+
+    /// Returns raw bytes of the payload (UTF-8 bytes in case of string payload).
+    pub fn as_bytes(&self) -> &[u8] {
+        match *self {
+            CastMessagePayload::String(ref payload) => payload.as_bytes(),
+            CastMessagePayload::Binary(ref payload) => payload,
+        }
+    }
+
+    /// Parses JSON payload, binary payload is expected to contain UTF-8 encoded JSON.
+    pub fn to_json(&self) -> Result<serde_json::Value, Error> {
+        Ok(serde_json::from_slice(self.as_bytes())?)
+    }
+}
+
+impl From<String> for CastMessagePayload {
+    fn from(payload: String) -> Self {
+        CastMessagePayload::String(payload)
+    }
+}
+
+impl From<Vec<u8>> for CastMessagePayload {
+    fn from(payload: Vec<u8>) -> Self {
+        CastMessagePayload::Binary(payload)
+    }
+}
+
 /// Base structure that represents messages that are exchanged between Receiver and Sender.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CastMessage {
     /// A namespace is a labeled protocol. That is, messages that are exchanged throughout the
     /// Cast ecosystem utilize namespaces to identify the protocol of the message being sent.
@@ -82,266 +136,3444 @@ pub struct CastMessage {
     pub payload: CastMessagePayload,
 }
 
-/// Static structure that is responsible for (de)serializing and sending/receiving Cast protocol
-/// messages.
-pub struct MessageManager<S>
-where
-    S: Write + Read,
-{
-    message_buffer: Lock<Vec<CastMessage>>,
-    stream: Lock<S>,
-    request_counter: Lock<NonZeroU32>,
-}
+/// Maximum size of the serialized message that can be sent within a single frame, larger messages
+/// are split into chunks.
+const MAX_FRAME_SIZE: usize = 64 * 1024;
 
-impl<S> MessageManager<S>
-where
-    S: Write + Read,
-{
-    pub fn new(stream: S) -> Self {
-        MessageManager {
-            stream: Lock::new(stream),
-            message_buffer: Lock::new(vec![]),
-            request_counter: Lock::new(NonZeroU32::MIN),
-        }
-    }
+/// Default maximum length of the frame that can be received, see
+/// `MessageManager::set_max_frame_length`.
+pub const DEFAULT_MAX_FRAME_LENGTH: u32 = MAX_FRAME_SIZE as u32;
 
-    /// Sends `message` to the Cast Device.
-    ///
-    /// # Arguments
-    ///
-    /// * `message` - `CastMessage` instance to be sent to the Cast Device.
-    pub fn send(&self, message: CastMessage) -> Result<(), Error> {
-        let mut raw_message = cast_channel::CastMessage::new();
+/// Default maximum length of the partially received messages that are being assembled from several
+/// frames, see `MessageManager::set_max_message_length`.
+pub const DEFAULT_MAX_MESSAGE_LENGTH: usize = 4 * 1024 * 1024;
 
-        raw_message.set_protocol_version(ProtocolVersion::CASTV2_1_0);
+/// Reason the frame read from the stream is invalid, see `Error::Framing`.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum FramingError {
+    /// Length prefix of the frame is zero.
+    #[error("frame is empty")]
+    Empty,
+    /// Length prefix of the frame exceeds the maximum, see `MessageManager::set_max_frame_length`.
+    #[error("frame length {length} exceeds the maximum of {max_length} bytes")]
+    TooLarge {
+        /// Length of the frame as stated in its prefix.
+        length: u32,
+        /// Maximum length of the frame that can be received.
+        max_length: u32,
+    },
+    /// Stream has ended before the whole frame has been read.
+    #[error("stream ended after {read} out of {length} bytes of the frame")]
+    Truncated {
+        /// Number of bytes of the frame that have been read.
+        read: usize,
+        /// Length of the frame as stated in its prefix.
+        length: usize,
+    },
+    /// Partially received messages exceed the maximum length, see
+    /// `MessageManager::set_max_message_length`.
+    #[error(
+        "partially received messages of {length} bytes exceed the maximum of {max_length} bytes"
+    )]
+    MessageTooLarge {
+        /// Length of all the partially received messages including the latest chunk.
+        length: usize,
+        /// Maximum length of the partially received messages.
+        max_length: usize,
+    },
+}
 
-        raw_message.set_namespace(message.namespace);
-        raw_message.set_source_id(message.source);
-        raw_message.set_destination_id(message.destination);
+/// Converts `message` into the protobuf message, moving all the data.
+pub(crate) fn raw_message(message: CastMessage) -> cast_channel::CastMessage {
+    let mut raw_message = cast_channel::CastMessage::new();
 
-        match message.payload {
-            CastMessagePayload::String(payload) => {
-                raw_message.set_payload_type(PayloadType::STRING);
-                raw_message.set_payload_utf8(payload);
-            }
+    raw_message.set_protocol_version(ProtocolVersion::CASTV2_1_0);
 
-            CastMessagePayload::Binary(payload) => {
-                raw_message.set_payload_type(PayloadType::BINARY);
-                raw_message.set_payload_binary(payload);
-            }
-        };
+    raw_message.set_namespace(message.namespace);
+    raw_message.set_source_id(message.source);
+    raw_message.set_destination_id(message.destination);
 
-        let message_content_buffer = utils::to_vec(&raw_message)?;
-        let message_length_buffer =
-            utils::write_u32_to_buffer(message_content_buffer.len() as u32)?;
+    set_payload(&mut raw_message, message.payload);
 
-        let writer = &mut *self.stream.borrow_mut();
+    raw_message
+}
+
+/// Converts protobuf `raw_message` into `CastMessage`, moving all the data out of it.
+pub(crate) fn cast_message(raw_message: &mut cast_channel::CastMessage) -> CastMessage {
+    CastMessage {
+        namespace: raw_message.take_namespace(),
+        source: raw_message.take_source_id(),
+        destination: raw_message.take_destination_id(),
+        payload: take_payload(raw_message),
+    }
+}
 
-        writer.write_all(&message_length_buffer)?;
-        writer.write_all(&message_content_buffer)?;
+/// Splits `payload` into chunks which are at most `chunk_size` bytes long. String payload is only
+/// split at the character boundaries, so that every chunk remains valid UTF-8.
+fn split_payload(payload: &CastMessagePayload, chunk_size: usize) -> Vec<CastMessagePayload> {
+    match payload {
+        CastMessagePayload::String(payload) => {
+            let mut chunks = vec![];
+            let mut rest = payload.as_str();
 
-        log::debug!("Message sent: {:?}", raw_message);
+            while !rest.is_empty() {
+                let mut end = chunk_size.min(rest.len());
+                while !rest.is_char_boundary(end) {
+                    end -= 1;
+                }
 
-        Ok(())
+                let (chunk, tail) = rest.split_at(end);
+                chunks.push(CastMessagePayload::String(chunk.to_string()));
+                rest = tail;
+            }
+
+            chunks
+        }
+        CastMessagePayload::Binary(payload) => payload
+            .chunks(chunk_size)
+            .map(|chunk| CastMessagePayload::Binary(chunk.to_vec()))
+            .collect(),
     }
+}
 
-    /// Waits for the next `CastMessage` available. Can also return existing message from the
-    /// internal message buffer containing messages that have been received previously, but haven't
-    /// been consumed for some reason (e.g. during `receive_find_map` call).
-    ///
-    /// # Return value
-    ///
-    /// `Result` containing parsed `CastMessage` or `Error`.
-    pub fn receive(&self) -> Result<CastMessage, Error> {
-        let mut message_buffer = self.message_buffer.borrow_mut();
+/// Sets `payload` of the protobuf message.
+fn set_payload(raw_message: &mut cast_channel::CastMessage, payload: CastMessagePayload) {
+    match payload {
+        CastMessagePayload::String(payload) => {
+            raw_message.set_payload_type(PayloadType::STRING);
+            raw_message.set_payload_utf8(payload);
+        }
 
-        // If we have messages in the buffer, let's return them from it.
-        if message_buffer.is_empty() {
-            self.read()
-        } else {
-            Ok(message_buffer.remove(0))
+        CastMessagePayload::Binary(payload) => {
+            raw_message.set_payload_type(PayloadType::BINARY);
+            raw_message.set_payload_binary(payload);
         }
+    };
+}
+
+/// Moves payload out of the protobuf message without copying it.
+fn take_payload(raw_message: &mut cast_channel::CastMessage) -> CastMessagePayload {
+    match raw_message.payload_type() {
+        PayloadType::STRING => CastMessagePayload::String(raw_message.take_payload_utf8()),
+        PayloadType::BINARY => CastMessagePayload::Binary(raw_message.take_payload_binary()),
     }
+}
 
-    /// Waits for the next `CastMessage` for which `f` returns valid mapped value. Messages in which
-    /// `f` is not interested are placed into internal message buffer and can be later retrieved
-    /// with `receive`. This method always reads from the stream.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use std::net::TcpStream;
-    /// # use rust_cast::message_manager::{CastMessage, MessageManager};
-    /// # use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
-    /// # use rustls::pki_types::ServerName;
-    /// # let config = ClientConfig::builder()
-    /// #   .with_root_certificates(RootCertStore::empty())
-    /// #   .with_no_client_auth();
-    /// # let server_name = ServerName::try_from("0")?.to_owned();
-    /// # let conn = ClientConnection::new(config.into(), server_name)?;
-    /// # let tcp_stream = TcpStream::connect(("0", 8009)).unwrap();
-    /// # let ssl_stream = StreamOwned::new(conn, tcp_stream);
-    /// # let message_manager = MessageManager::new(ssl_stream);
-    /// # fn can_handle(message: &CastMessage) -> bool { unimplemented!() }
-    /// # fn parse(message: &CastMessage) { unimplemented!() }
-    /// message_manager.receive_find_map(|message| {
-    ///   if !can_handle(message) {
-    ///     return Ok(None);
-    ///   }
-    ///
-    ///   parse(message);
-    ///
-    ///   Ok(Some(()))
-    /// })?;
-    /// # Ok::<(), rust_cast::errors::Error>(())
-    /// ```
-    ///
-    /// # Arguments
-    ///
-    /// * `f` - Function that analyzes and maps `CastMessage` to any other type. If message doesn't
-    /// look like something `f` is looking for, then `Ok(None)` should be returned so that message
-    /// is not lost and placed into internal message buffer for later retrieval.
-    ///
-    /// # Return value
-    ///
-    /// `Result` containing parsed `CastMessage` or `Error`.
-    pub fn receive_find_map<F, B>(&self, f: F) -> Result<B, Error>
-    where
-        F: Fn(&CastMessage) -> Result<Option<B>, Error>,
-    {
-        loop {
-            let message = self.read()?;
+/// Determines whether write has failed because the stream hasn't accepted the data in time.
+fn is_blocked(error: &Error) -> bool {
+    matches!(error, Error::Io(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut))
+}
 
-            // If message is found, just return mapped result, otherwise keep unprocessed message
-            // in the buffer, it can be later retrieved with `receive`.
-            match f(&message)? {
-                Some(r) => return Ok(r),
-                None => self.message_buffer.borrow_mut().push(message),
-            }
+/// Releases memory of the reusable frame buffer if it has grown beyond the frame size (e.g. because
+/// of the oversized frame sent by the receiver), so that single huge frame doesn't pin memory.
+fn shrink_buffer(buffer: &mut Vec<u8>) {
+    if buffer.capacity() > MAX_FRAME_SIZE {
+        buffer.clear();
+        buffer.shrink_to(MAX_FRAME_SIZE);
+    }
+}
+
+/// Appends `chunk` to the `payload` of the partially received message.
+fn append_payload(
+    payload: &mut CastMessagePayload,
+    chunk: CastMessagePayload,
+) -> Result<(), Error> {
+    match (payload, chunk) {
+        (CastMessagePayload::String(payload), CastMessagePayload::String(chunk)) => {
+            payload.push_str(&chunk)
+        }
+        (CastMessagePayload::Binary(payload), CastMessagePayload::Binary(chunk)) => {
+            payload.extend_from_slice(&chunk)
+        }
+        _ => {
+            return Err(Error::Parsing(
+                "Message chunks have different payload types.".to_string(),
+            ))
         }
     }
 
-    /// Generates unique integer number that is used in some requests to map them with the response.
-    ///
-    /// # Return value
-    ///
-    /// Unique (in the scope of this particular `MessageManager` instance) integer number.
-    pub fn generate_request_id(&self) -> NonZeroU32 {
-        let mut counter = self.request_counter.borrow_mut();
-        let request_id = *counter;
-        *counter = counter.checked_add(1).unwrap();
-        request_id
+    Ok(())
+}
+
+/// State of the connection with the cast device, see `MessageManager::state`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Stream has been established, but no message has been exchanged over it yet.
+    Connecting,
+    /// The last attempt to exchange messages with the device has succeeded.
+    Connected,
+    /// Device hasn't responded in time, has closed the platform virtual connection or the stream
+    /// has failed with recoverable error. Connection is considered `Connected` again once the next
+    /// message is successfully exchanged.
+    Degraded,
+    /// Stream has been closed by either side and can't be used anymore.
+    Closed,
+}
+
+/// Function that is called every time the connection state changes.
+type StateListener = Box<dyn Fn(ConnectionState) + Send>;
+
+/// Id that maps the request with the responses to it, see `MessageManager::generate_request_id`.
+/// Messages the receiver sends on its own (e.g. status broadcasts) carry
+/// `RequestId::UNSOLICITED`.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct RequestId(u32);
+
+impl RequestId {
+    /// Id of the messages that aren't responses to any request.
+    pub const UNSOLICITED: RequestId = RequestId(0);
+
+    /// Creates request id from its raw value.
+    pub const fn new(request_id: u32) -> RequestId {
+        RequestId(request_id)
     }
 
-    /// Reads next `CastMessage` from the stream.
-    ///
-    /// # Return value
-    ///
-    /// `Result` containing parsed `CastMessage` or `Error`.
-    fn read(&self) -> Result<CastMessage, Error> {
-        let mut buffer: [u8; 4] = [0; 4];
+    /// Returns raw value of the request id as it's sent over the wire.
+    pub const fn get(self) -> u32 {
+        self.0
+    }
 
-        let reader = &mut *self.stream.borrow_mut();
+    /// Determines whether message with this id isn't a response to any request.
+    pub const fn is_unsolicited(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<u32> for RequestId {
+    fn from(request_id: u32) -> Self {
+        RequestId(request_id)
+    }
+}
 
-        reader.read_exact(&mut buffer)?;
+impl From<RequestId> for u32 {
+    fn from(request_id: RequestId) -> Self {
+        request_id.0
+    }
+}
 
-        let length = utils::read_u32_from_buffer(&buffer)?;
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
 
-        let mut buffer: Vec<u8> = Vec::with_capacity(length as usize);
-        let mut limited_reader = reader.take(u64::from(length));
+/// Direction in which message travels, see `MessageManager::set_message_observer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Message has been received from the cast device.
+    Incoming,
+    /// Message is sent to the cast device.
+    Outgoing,
+}
 
-        limited_reader.read_to_end(&mut buffer)?;
+/// Function that is called for every message sent or received.
+type MessageObserver = Box<dyn Fn(&CastMessage, Direction) + Send>;
 
-        let raw_message = utils::from_vec::<cast_channel::CastMessage>(buffer.to_vec())?;
+/// Traffic of the single namespace, see `TrafficMetrics`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NamespaceTraffic {
+    /// Number of messages sent to the device.
+    pub messages_sent: u64,
+    /// Number of bytes (including framing) sent to the device.
+    pub bytes_sent: u64,
+    /// Number of messages received from the device.
+    pub messages_received: u64,
+    /// Number of bytes (including framing) received from the device.
+    pub bytes_received: u64,
+}
 
-        log::debug!("Message received: {:?}", raw_message);
+/// Traffic exchanged with the cast device, see `MessageManager::metrics`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrafficMetrics {
+    /// Traffic per message namespace.
+    pub namespaces: HashMap<String, NamespaceTraffic>,
+    /// Number of received messages that couldn't be parsed.
+    pub parse_failures: u64,
+}
 
-        Ok(CastMessage {
-            namespace: raw_message.namespace().to_string(),
-            source: raw_message.source_id().to_string(),
-            destination: raw_message.destination_id().to_string(),
-            payload: match raw_message.payload_type() {
-                PayloadType::STRING => {
-                    CastMessagePayload::String(raw_message.payload_utf8().to_string())
+impl TrafficMetrics {
+    /// Returns traffic of all the namespaces combined.
+    pub fn total(&self) -> NamespaceTraffic {
+        self.namespaces
+            .values()
+            .fold(NamespaceTraffic::default(), |total, traffic| {
+                NamespaceTraffic {
+                    messages_sent: total.messages_sent + traffic.messages_sent,
+                    bytes_sent: total.bytes_sent + traffic.bytes_sent,
+                    messages_received: total.messages_received + traffic.messages_received,
+                    bytes_received: total.bytes_received + traffic.bytes_received,
                 }
-                PayloadType::BINARY => {
-                    CastMessagePayload::Binary(raw_message.payload_binary().to_owned())
-                }
-            },
-        })
+            })
+    }
+
+    /// Adds `messages` and `bytes` travelling in the `direction` to the `namespace` traffic.
+    fn record(&mut self, namespace: &str, direction: Direction, messages: u64, bytes: u64) {
+        let traffic = match self.namespaces.get_mut(namespace) {
+            Some(traffic) => traffic,
+            None => self.namespaces.entry(namespace.to_string()).or_default(),
+        };
+
+        match direction {
+            Direction::Incoming => {
+                traffic.messages_received += messages;
+                traffic.bytes_received += bytes;
+            }
+            Direction::Outgoing => {
+                traffic.messages_sent += messages;
+                traffic.bytes_sent += bytes;
+            }
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use protobuf::EnumOrUnknown;
+/// How often cancellation token is checked while waiting for the response.
+const CANCELLATION_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
-    use crate::{tests::MockTcpStream, DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID};
+/// Signature of the function that sets read timeout of the stream, see `ReadTimeout`.
+type SetReadTimeout<S> = fn(&mut S, Option<Duration>) -> Result<(), IoError>;
 
-    use super::*;
+/// Token that allows to abort blocking requests (e.g. when receiver never answers), see
+/// `MessageManager::with_cancellation`. Token can be cloned and cancelled from any thread.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use rust_cast::message_manager::CancellationToken;
+///
+/// let token = CancellationToken::with_timeout(Duration::from_secs(10));
+/// let ui_token = token.clone();
+/// ui_token.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
 
-    #[test]
-    fn test_receive() {
-        let mut stream = MockTcpStream::new();
-        let payload = r#"{"type":"PING"}"#;
-        stream.add_message(cast_channel::CastMessage {
-            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
-            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
-            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
-            namespace: Some(crate::channels::heartbeat::CHANNEL_NAMESPACE.to_string()),
-            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
-            payload_utf8: Some(payload.to_string()),
-            payload_binary: None,
-            continued: None,
-            remaining_length: None,
-            special_fields: Default::default(),
-        });
-        let message_manager = MessageManager::new(stream);
-        let expected_result = CastMessage {
-            namespace: crate::channels::heartbeat::CHANNEL_NAMESPACE.to_string(),
-            source: DEFAULT_RECEIVER_ID.to_string(),
-            destination: DEFAULT_SENDER_ID.to_string(),
-            payload: CastMessagePayload::String(payload.to_string()),
-        };
+impl CancellationToken {
+    /// Creates token that is only cancelled with `cancel`.
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
 
-        let result = message_manager
-            .receive()
-            .expect("expected to receive a message");
+    /// Creates token that is cancelled with `cancel` or automatically once `timeout` elapses.
+    pub fn with_timeout(timeout: Duration) -> CancellationToken {
+        CancellationToken::with_deadline(Instant::now() + timeout)
+    }
 
-        assert_eq!(expected_result, result);
+    /// Creates token that is cancelled with `cancel` or automatically once `deadline` is reached.
+    pub fn with_deadline(deadline: Instant) -> CancellationToken {
+        CancellationToken {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: Some(deadline),
+        }
     }
 
-    #[test]
-    fn test_send() {
-        let payload = r#"{"type":"PONG"}"#;
-        let namespace = crate::channels::heartbeat::CHANNEL_NAMESPACE;
-        let stream = MockTcpStream::new();
-        let message_manager = MessageManager::new(stream.clone());
-        let expected_message = cast_channel::CastMessage {
-            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
-            source_id: Some(DEFAULT_SENDER_ID.to_string()),
-            destination_id: Some(DEFAULT_RECEIVER_ID.to_string()),
-            namespace: Some(namespace.to_string()),
-            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
-            payload_utf8: Some(payload.to_string()),
-            payload_binary: None,
-            continued: None,
-            remaining_length: None,
-            special_fields: Default::default(),
-        };
+    /// Cancels the requests that use this token (or any of its clones).
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
 
-        message_manager
-            .send(CastMessage {
-                namespace: namespace.to_string(),
-                source: DEFAULT_SENDER_ID.to_string(),
-                destination: DEFAULT_RECEIVER_ID.to_string(),
-                payload: CastMessagePayload::String(payload.to_string()),
+    /// Determines whether token has been cancelled or its deadline has been reached.
+    pub fn is_cancelled(&self) -> bool {
+        self.check().is_err()
+    }
+
+    /// Returns the error the cancelled request should fail with, if any.
+    fn check(&self) -> Result<(), Error> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return Err(Error::Cancelled);
+        }
+
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(Error::Timeout(
+                "request deadline has been reached".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns how long to wait for the data before checking the token again.
+    fn poll_interval(&self) -> Duration {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+            .map_or(CANCELLATION_POLL_INTERVAL, |remaining| {
+                remaining.min(CANCELLATION_POLL_INTERVAL)
             })
-            .unwrap();
+    }
+}
 
-        let tcp_message = stream
-            .received_message(0)
-            .expect("expected a message to have been received");
-        assert_eq!(expected_message, tcp_message.message());
+/// Determines how requests that fail with transient errors are retried, see
+/// `MessageManager::set_retry_policy`. Writes to the stream are retried when they fail with
+/// `WouldBlock` or `Interrupted` errors, and media requests are repeated when receiver answers with
+/// `INVALID_PLAYER_STATE` (e.g. while the application is still starting).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use rust_cast::message_manager::RetryPolicy;
+///
+/// let policy = RetryPolicy {
+///     max_attempts: 5,
+///     ..RetryPolicy::default()
+/// };
+/// assert_eq!(Duration::from_millis(200), policy.backoff(1));
+/// assert_eq!(1, RetryPolicy::none().max_attempts);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry, it's doubled before every next one.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between the attempts.
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates policy that doesn't retry anything, every failure is returned immediately.
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Returns delay before the retry with the zero-based `retry` number.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Determines what `send` does when the outgoing queue is full, see `SendQueueOptions`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Waits until the writer thread makes room in the queue, honoring the current cancellation
+    /// token, if any (see `MessageManager::with_cancellation`).
+    Block,
+    /// Drops the oldest queued heartbeat message (e.g. `PING`) that isn't being written yet,
+    /// fails like `Error` if there is none.
+    DropOldest,
+    /// Fails with `Error::QueueFull` right away.
+    Error,
+}
+
+/// Configures the outgoing queue of `MessageManager` created with `new_split` (`CastDevice` uses
+/// it for every stream that provides the write half, see `CastStream::split_writer`). Messages are
+/// written by the writer thread in the background, `send` only waits when the queue is full, see
+/// `BackpressurePolicy`. Heartbeats and `CLOSE` of the virtual connections are queued ahead of the
+/// other messages, so that the device doesn't drop the connection while large payloads (e.g.
+/// `QUEUE_LOAD`) are transferred, all the other messages are written in the order they have been
+/// sent. `MessageManager` created with `new` writes to the stream on the sending thread instead
+/// and fails with `Error::Timeout` if the stream doesn't accept the message in time.
+///
+/// # Examples
+///
+/// ```
+/// use rust_cast::message_manager::{BackpressurePolicy, SendQueueOptions};
+///
+/// let options = SendQueueOptions {
+///     backpressure: BackpressurePolicy::DropOldest,
+///     ..SendQueueOptions::default()
+/// };
+/// assert_eq!(64, options.capacity);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SendQueueOptions {
+    /// Maximum number of messages that can wait in the queue.
+    pub capacity: usize,
+    /// What happens to the message sent while the queue is full.
+    pub backpressure: BackpressurePolicy,
+}
+
+impl Default for SendQueueOptions {
+    fn default() -> Self {
+        SendQueueOptions {
+            capacity: 64,
+            backpressure: BackpressurePolicy::Block,
+        }
+    }
+}
+
+/// Class of the outgoing message: queued messages of the higher class are written before the
+/// queued messages of the lower ones, so that heartbeats aren't starved by large payloads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum MessagePriority {
+    /// All the application messages (including `CONNECT`), they are never reordered.
+    Normal,
+    /// `CLOSE` of the virtual connection.
+    Control,
+    /// `PING` and `PONG` messages.
+    Heartbeat,
+}
+
+/// Frame (or frames of the chunked message) that is waiting in the outgoing queue.
+#[derive(Debug)]
+struct OutgoingFrame {
+    bytes: Vec<u8>,
+    priority: MessagePriority,
+}
+
+impl OutgoingFrame {
+    /// Determines whether the frame can be dropped to make room for others.
+    fn is_droppable(&self) -> bool {
+        self.priority == MessagePriority::Heartbeat
+    }
+}
+
+/// How long the writer thread waits before writing to the stream that hasn't accepted the data in
+/// time once again.
+const WRITE_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long `close` waits for the writer thread to write the queued messages.
+const CLOSE_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Outgoing queue shared with the writer thread, see `MessageManager::new_split`.
+#[derive(Default)]
+struct WriteQueue {
+    state: Mutex<WriteQueueState>,
+    /// Notified every time a frame is queued or written and when the queue is closed.
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct WriteQueueState {
+    frames: VecDeque<OutgoingFrame>,
+    /// Determines whether the writer thread is writing the frame it has taken from the queue.
+    writing: bool,
+    /// Set once the queue is closed, the writer thread exits as soon as it notices it.
+    closed: bool,
+    /// Kind and description of the error the writer thread has failed with, every subsequent
+    /// `send` fails with it.
+    error: Option<(ErrorKind, String)>,
+}
+
+impl WriteQueue {
+    fn lock(&self) -> MutexGuard<'_, WriteQueueState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Closes the queue, frames that are still queued are dropped.
+    fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        state.frames.clear();
+        self.changed.notify_all();
+    }
+
+    /// Waits at most for the `timeout` until the writer thread has written all the queued frames.
+    /// Returns `false` if some of them are still waiting.
+    fn drain(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.lock();
+        while !state.closed && state.error.is_none() && (state.writing || !state.frames.is_empty())
+        {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return false;
+            }
+            state = self.changed.wait_timeout(state, remaining).unwrap().0;
+        }
+
+        state.frames.is_empty()
+    }
+}
+
+/// Handle of the writer thread, the thread exits once the handle is dropped.
+struct WriterHandle(Arc<WriteQueue>);
+
+impl Drop for WriterHandle {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+/// Writes the frames queued in the `queue` to the `writer` one by one until the queue is closed or
+/// the writer fails.
+fn run_writer(queue: &WriteQueue, mut writer: StreamWriter) {
+    let mut state = queue.lock();
+
+    loop {
+        if state.closed {
+            return;
+        }
+
+        let Some(frame) = state.frames.pop_front() else {
+            state = queue.changed.wait(state).unwrap();
+            continue;
+        };
+
+        state.writing = true;
+        drop(state);
+        let result = write_frame(queue, &mut writer, &frame.bytes);
+        state = queue.lock();
+        state.writing = false;
+
+        if let Err(error) = result {
+            if !state.closed {
+                log::debug!("Writer thread has failed: {error}");
+                state.error = Some((error.kind(), error.to_string()));
+                state.frames.clear();
+            }
+            queue.changed.notify_all();
+            return;
+        }
+
+        queue.changed.notify_all();
+    }
+}
+
+/// Writes all the `bytes` to the `writer` and flushes it, writes and flushes the `writer` doesn't
+/// accept in time are repeated until the `queue` is closed.
+fn write_frame(queue: &WriteQueue, writer: &mut StreamWriter, bytes: &[u8]) -> Result<(), IoError> {
+    let mut written = 0;
+    while written < bytes.len() {
+        match writer.write(&bytes[written..]) {
+            Ok(0) => return Err(ErrorKind::WriteZero.into()),
+            Ok(count) => written += count,
+            Err(error) => wait_for_writer(queue, error)?,
+        }
+    }
+
+    loop {
+        match writer.flush() {
+            Ok(()) => return Ok(()),
+            Err(error) => wait_for_writer(queue, error)?,
+        }
+    }
+}
+
+/// Waits before the write that has failed with the transient `error` is repeated, returns the
+/// `error` if it's not a transient one or if the `queue` has been closed meanwhile.
+fn wait_for_writer(queue: &WriteQueue, error: IoError) -> Result<(), IoError> {
+    match error.kind() {
+        ErrorKind::Interrupted => Ok(()),
+        ErrorKind::WouldBlock | ErrorKind::TimedOut if !queue.lock().closed => {
+            thread::sleep(WRITE_RETRY_INTERVAL);
+            Ok(())
+        }
+        _ => Err(error),
+    }
+}
+
+/// Stream which allows to limit the time blocking read waits for the data, it's required for
+/// `receive_timeout` and `try_receive`.
+pub trait ReadTimeout {
+    /// Sets read timeout of the stream: `None` makes reads block indefinitely and
+    /// `Some(Duration::ZERO)` makes reads non-blocking. Reads that time out should fail with
+    /// `WouldBlock` or `TimedOut` error.
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), IoError>;
+}
+
+/// Shortest read timeout of the socket, zero timeout isn't allowed by the sockets.
+const MIN_SOCKET_READ_TIMEOUT: Duration = Duration::from_millis(1);
+
+impl ReadTimeout for TcpStream {
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), IoError> {
+        // Socket is never switched to the non-blocking mode, since it affects the write half of
+        // it as well (see `CastStream::split_writer`).
+        TcpStream::set_read_timeout(
+            self,
+            timeout.map(|timeout| timeout.max(MIN_SOCKET_READ_TIMEOUT)),
+        )
+    }
+}
+
+impl<T> ReadTimeout for Box<T>
+where
+    T: ReadTimeout + ?Sized,
+{
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), IoError> {
+        (**self).set_read_timeout(timeout)
+    }
+}
+
+impl<C, T> ReadTimeout for StreamOwned<C, T>
+where
+    T: Read + Write + ReadTimeout,
+{
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), IoError> {
+        self.sock.set_read_timeout(timeout)
+    }
+}
+
+/// Function that shuts down the underlying socket of the `CastStream` from any thread, it unblocks
+/// reads and writes the other threads may be waiting in.
+pub type ShutdownHandle = Box<dyn Fn() -> Result<(), IoError> + Send + Sync>;
+
+/// Write half of the `CastStream` that is written from the writer thread while the stream itself
+/// is being read, see `CastStream::split_writer`.
+pub type StreamWriter = Box<dyn Write + Send>;
+
+/// Stream `CastDevice` talks to the cast device over, `TlsStream` by default. Implement it to run
+/// the connection over custom transport (e.g. proxy or tunnel), see `CastDevice::from_stream`.
+pub trait CastStream: Read + Write + ReadTimeout {
+    /// Returns certificate chain presented by the device, if stream is a TLS one.
+    fn peer_certificates(&self) -> Vec<CertificateDer<'static>> {
+        Vec::new()
+    }
+
+    /// Gracefully shuts down the stream (e.g. sends TLS `close_notify` and closes the socket),
+    /// pending writes are already flushed by the time it's called. Does nothing by default.
+    fn shutdown(&mut self) -> Result<(), IoError> {
+        Ok(())
+    }
+
+    /// Returns handle that shuts down the underlying socket without access to the stream, see
+    /// `CastDevice::shutdown`. Returns `None` by default, blocked reads then aren't interrupted.
+    fn shutdown_handle(&self) -> Option<ShutdownHandle> {
+        None
+    }
+
+    /// Returns the write half of the stream that can be written from another thread while the
+    /// stream is being read, see `MessageManager::new_split`. Returns `None` by default, messages
+    /// are then written to the stream itself on the sending thread.
+    fn split_writer(&mut self) -> Option<StreamWriter> {
+        None
+    }
+}
+
+/// Returns handle that shuts down the clone of the `socket`.
+pub(crate) fn socket_shutdown_handle(socket: &TcpStream) -> Option<ShutdownHandle> {
+    let socket = socket.try_clone().ok()?;
+    Some(Box::new(move || socket.shutdown(Shutdown::Both)))
+}
+
+impl CastStream for TcpStream {
+    fn shutdown(&mut self) -> Result<(), IoError> {
+        TcpStream::shutdown(self, Shutdown::Both)
+    }
+
+    fn shutdown_handle(&self) -> Option<ShutdownHandle> {
+        socket_shutdown_handle(self)
+    }
+
+    fn split_writer(&mut self) -> Option<StreamWriter> {
+        Some(Box::new(self.try_clone().ok()?))
+    }
+}
+
+impl<T> CastStream for Box<T>
+where
+    T: CastStream + ?Sized,
+{
+    fn peer_certificates(&self) -> Vec<CertificateDer<'static>> {
+        (**self).peer_certificates()
+    }
+
+    fn shutdown(&mut self) -> Result<(), IoError> {
+        (**self).shutdown()
+    }
+
+    fn shutdown_handle(&self) -> Option<ShutdownHandle> {
+        (**self).shutdown_handle()
+    }
+
+    fn split_writer(&mut self) -> Option<StreamWriter> {
+        (**self).split_writer()
+    }
+}
+
+impl CastStream for StreamOwned<ClientConnection, TcpStream> {
+    fn peer_certificates(&self) -> Vec<CertificateDer<'static>> {
+        self.conn
+            .peer_certificates()
+            .map(|certificates| {
+                certificates
+                    .iter()
+                    .map(|certificate| certificate.clone().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn shutdown(&mut self) -> Result<(), IoError> {
+        self.conn.send_close_notify();
+        self.flush()?;
+        self.sock.shutdown(Shutdown::Both)
+    }
+
+    fn shutdown_handle(&self) -> Option<ShutdownHandle> {
+        socket_shutdown_handle(&self.sock)
+    }
+}
+
+/// Virtual connection established with a particular destination (e.g. `receiver-0` or transport id
+/// of the running application).
+#[derive(Debug, Clone)]
+struct VirtualConnection {
+    /// `CONNECT` message that has been used to establish the connection, it's re-sent every time the
+    /// connection needs to be re-established.
+    connect_message: CastMessage,
+    /// Determines whether the connection has been closed by the receiver.
+    closed: bool,
+    /// Reason of the close if receiver has provided the reason code. Such connections are closed
+    /// deliberately (e.g. because the application has stopped) and aren't re-established.
+    close_reason: Option<CloseReason>,
+}
+
+/// Received messages that haven't been consumed yet, shared by all the threads that receive
+/// messages through the same `MessageManager`. Only one thread reads the stream at a time, the
+/// others wait for it to hand them their messages.
+#[derive(Default)]
+struct Inbox {
+    state: Mutex<InboxState>,
+    /// Notified every time a message is added and when the stream is no longer read.
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct InboxState {
+    /// Messages in the order they have been received.
+    messages: VecDeque<InboxMessage>,
+    /// Id the next message added to the inbox gets.
+    next_id: u64,
+    /// Ids of the requests that wait for the responses, see `MessageManager::reserve_request_id`.
+    pending: HashSet<RequestId>,
+    /// Determines whether some thread is reading the stream.
+    reading: bool,
+}
+
+/// Message kept in the `Inbox`.
+struct InboxMessage {
+    /// Id that tells the message apart from the others while the inbox isn't locked.
+    id: u64,
+    /// Id of the pending request the message responds to, if any.
+    request_id: Option<RequestId>,
+    message: CastMessage,
+}
+
+impl Inbox {
+    fn lock(&self) -> MutexGuard<'_, InboxState> {
+        self.state.lock().unwrap()
+    }
+}
+
+impl InboxState {
+    /// Adds the `message` that responds to the pending request with `request_id` (if any).
+    fn push(&mut self, request_id: Option<RequestId>, message: CastMessage) {
+        self.messages.push_back(InboxMessage {
+            id: self.next_id,
+            request_id,
+            message,
+        });
+        self.next_id += 1;
+    }
+
+    /// Removes the message with `id`, unless some other thread has taken it already.
+    fn take(&mut self, id: u64) -> Option<CastMessage> {
+        let index = self.messages.iter().position(|message| message.id == id)?;
+        self.messages.remove(index).map(|message| message.message)
+    }
+
+    /// Returns id of the pending request the `message` responds to, if any. Responses carry the id
+    /// of the request in the `requestId` field (or `seqNum` for the mirroring messages).
+    fn pending_request(&self, message: &CastMessage) -> Option<RequestId> {
+        /// Field of the response that matches it with the request.
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "requestId", alias = "seqNum")]
+            request_id: Option<RequestId>,
+        }
+
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        serde_json::from_str::<Response>(message.payload.as_str()?)
+            .ok()?
+            .request_id
+            .filter(|request_id| self.pending.contains(request_id))
+    }
+}
+
+/// Request id reserved with `MessageManager::reserve_request_id`, the reservation is released once
+/// it's dropped.
+pub struct PendingRequest<'a, S>
+where
+    S: Write + Read,
+{
+    message_manager: &'a MessageManager<S>,
+    request_id: RequestId,
+}
+
+impl<S> PendingRequest<'_, S>
+where
+    S: Write + Read,
+{
+    /// Returns the reserved request id.
+    pub fn id(&self) -> RequestId {
+        self.request_id
+    }
+}
+
+impl<S> Drop for PendingRequest<'_, S>
+where
+    S: Write + Read,
+{
+    fn drop(&mut self) {
+        let mut inbox = self.message_manager.inbox.lock();
+        inbox.pending.remove(&self.request_id);
+
+        // Responses nobody waits for anymore are handed to whoever receives messages next.
+        let mut released = false;
+        for message in inbox.messages.iter_mut() {
+            if message.request_id == Some(self.request_id) {
+                message.request_id = None;
+                released = true;
+            }
+        }
+        if released {
+            self.message_manager.inbox.changed.notify_all();
+        }
+    }
+}
+
+/// Static structure that is responsible for (de)serializing and sending/receiving Cast protocol
+/// messages.
+pub struct MessageManager<S>
+where
+    S: Write + Read,
+{
+    inbox: Inbox,
+    stream: Lock<S>,
+    request_counter: Lock<NonZeroU32>,
+    connections: Lock<HashMap<(String, String), VirtualConnection>>,
+    closed: Lock<bool>,
+    shut_down: Lock<bool>,
+    partial_messages: Lock<HashMap<(String, String, String), CastMessagePayload>>,
+    discarded_messages: Lock<HashSet<(String, String, String)>>,
+    read_buffer: Lock<Vec<u8>>,
+    write_buffer: Lock<Vec<u8>>,
+    cancellation: Lock<Option<CancellationToken>>,
+    state: Lock<ConnectionState>,
+    state_listeners: Lock<Vec<StateListener>>,
+    metrics: Lock<TrafficMetrics>,
+    message_observer: Lock<Option<MessageObserver>>,
+    wire_log: Lock<Option<Box<dyn WireLogSink>>>,
+    policy: Lock<Option<Arc<dyn OperationPolicy>>>,
+    last_transport_error: Lock<Option<TransportError>>,
+    retry_policy: Lock<RetryPolicy>,
+    send_queue: Lock<SendQueueOptions>,
+    unsent: Lock<Vec<u8>>,
+    writer: Option<WriterHandle>,
+    malformed_frame: Lock<Vec<u8>>,
+    max_frame_length: Lock<u32>,
+    max_message_length: Lock<usize>,
+    receiver_id: Lock<String>,
+    set_read_timeout: Option<SetReadTimeout<S>>,
+}
+
+impl<S> MessageManager<S>
+where
+    S: Write + Read,
+{
+    pub fn new(stream: S) -> Self {
+        MessageManager {
+            stream: Lock::new(stream),
+            inbox: Inbox::default(),
+            request_counter: Lock::new(NonZeroU32::MIN),
+            connections: Lock::new(HashMap::new()),
+            closed: Lock::new(false),
+            shut_down: Lock::new(false),
+            partial_messages: Lock::new(HashMap::new()),
+            discarded_messages: Lock::new(HashSet::new()),
+            read_buffer: Lock::new(Vec::new()),
+            write_buffer: Lock::new(Vec::new()),
+            cancellation: Lock::new(None),
+            state: Lock::new(ConnectionState::Connecting),
+            state_listeners: Lock::new(Vec::new()),
+            metrics: Lock::new(TrafficMetrics::default()),
+            message_observer: Lock::new(None),
+            wire_log: Lock::new(None),
+            policy: Lock::new(None),
+            last_transport_error: Lock::new(None),
+            retry_policy: Lock::new(RetryPolicy::default()),
+            send_queue: Lock::new(SendQueueOptions::default()),
+            unsent: Lock::new(Vec::new()),
+            writer: None,
+            malformed_frame: Lock::new(Vec::new()),
+            max_frame_length: Lock::new(DEFAULT_MAX_FRAME_LENGTH),
+            max_message_length: Lock::new(DEFAULT_MAX_MESSAGE_LENGTH),
+            receiver_id: Lock::new(crate::DEFAULT_RECEIVER_ID.to_string()),
+            set_read_timeout: None,
+        }
+    }
+
+    /// Creates `MessageManager` which requests can be cancelled while they wait for the response,
+    /// see `with_cancellation`. Requests of the `MessageManager` created with `new` can only be
+    /// cancelled between the received messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - Stream that supports read timeouts.
+    pub fn new_cancellable(stream: S) -> Self
+    where
+        S: ReadTimeout,
+    {
+        MessageManager {
+            set_read_timeout: Some(S::set_read_timeout),
+            ..MessageManager::new(stream)
+        }
+    }
+
+    /// Creates cancellable `MessageManager` (see `new_cancellable`) that writes the messages to the
+    /// write half of the stream from the dedicated writer thread, so that `send` neither waits for
+    /// the `stream` that is being read nor for the device to accept the data, see
+    /// `SendQueueOptions`. The thread exits once `MessageManager` is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - Stream that supports read timeouts, it's only read from.
+    /// * `writer` - Write half of the `stream`, see `CastStream::split_writer`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the writer thread can't be spawned.
+    pub fn new_split(stream: S, writer: StreamWriter) -> Result<Self, Error>
+    where
+        S: ReadTimeout,
+    {
+        let queue = Arc::new(WriteQueue::default());
+        let writer_queue = Arc::clone(&queue);
+        thread::Builder::new()
+            .name("rust-cast-writer".to_string())
+            .spawn(move || run_writer(&writer_queue, writer))?;
+
+        Ok(MessageManager {
+            writer: Some(WriterHandle(queue)),
+            ..MessageManager::new_cancellable(stream)
+        })
+    }
+
+    /// Runs `f` with the cancellation `token`: requests made through this `MessageManager` (e.g.
+    /// with any of the channels) while `f` runs fail with `Error::Cancelled` once the token is
+    /// cancelled, or with `Error::Timeout` once the token deadline is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - Cancellation token that can be cancelled from another thread.
+    /// * `f` - Function that makes the requests.
+    pub fn with_cancellation<F, R>(&self, token: &CancellationToken, f: F) -> Result<R, Error>
+    where
+        F: FnOnce() -> Result<R, Error>,
+    {
+        let previous_token = self.cancellation.borrow_mut().replace(token.clone());
+        let result = f();
+        *self.cancellation.borrow_mut() = previous_token;
+
+        result
+    }
+
+    /// Waits until the `deadline` honoring the current cancellation token, if any (see
+    /// `with_cancellation`), so that the requests paced over time (e.g. volume fade) can be
+    /// aborted between the requests.
+    pub(crate) fn wait_until(&self, deadline: Instant) -> Result<(), Error> {
+        loop {
+            let token = self.cancellation.borrow_mut().clone();
+            if let Some(token) = &token {
+                token.check()?;
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(());
+            }
+
+            thread::sleep(token.map_or(remaining, |token| remaining.min(token.poll_interval())));
+        }
+    }
+
+    /// Returns the current state of the connection: it's updated every time message is sent or
+    /// received, when the stream fails, when request times out and when receiver closes the
+    /// platform virtual connection.
+    pub fn state(&self) -> ConnectionState {
+        *self.state.borrow_mut()
+    }
+
+    /// Registers `listener` that is called with the new state every time the connection state
+    /// changes. Listener is called while the stream is in use, so it must not send or receive any
+    /// messages itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `listener` - Function that is called with the new connection state.
+    pub fn on_state_change<F>(&self, listener: F)
+    where
+        F: Fn(ConnectionState) + Send + 'static,
+    {
+        self.state_listeners.borrow_mut().push(Box::new(listener));
+    }
+
+    /// Sets the policy requests failed with transient errors are retried with, replacing the
+    /// previous one. `RetryPolicy::default()` is used unless it's changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Policy to retry requests with, `RetryPolicy::none()` disables retries.
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        *self.retry_policy.borrow_mut() = policy;
+    }
+
+    /// Returns the policy requests failed with transient errors are retried with.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        *self.retry_policy.borrow_mut()
+    }
+
+    /// Configures the outgoing queue, replacing the previous options. Messages that are already
+    /// queued are kept even if they exceed the new capacity. `SendQueueOptions::default()` is used
+    /// unless it's changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Capacity of the queue and the backpressure policy.
+    pub fn set_send_queue(&self, options: SendQueueOptions) {
+        *self.send_queue.borrow_mut() = options;
+    }
+
+    /// Returns the number of messages waiting in the outgoing queue, including the one the writer
+    /// thread is writing. Without the writer thread it's `1` while the rest of the message the
+    /// stream hasn't accepted in time waits to be written before the next one.
+    pub fn queued_messages(&self) -> usize {
+        match &self.writer {
+            Some(WriterHandle(queue)) => {
+                let state = queue.lock();
+                state.frames.len() + usize::from(state.writing)
+            }
+            None => usize::from(!self.unsent.borrow_mut().is_empty()),
+        }
+    }
+
+    /// Sets the maximum length of the frame that can be received, longer frames (likely caused by
+    /// the corrupted stream) fail with `FramingError::TooLarge` instead of being allocated.
+    /// `DEFAULT_MAX_FRAME_LENGTH` is used unless it's changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_length` - Maximum length of the frame in bytes, excluding its length prefix.
+    pub fn set_max_frame_length(&self, max_length: u32) {
+        *self.max_frame_length.borrow_mut() = max_length;
+    }
+
+    /// Sets the maximum length of the messages that are being assembled from several frames, all
+    /// the partially received messages count towards it. Chunk that exceeds it fails with
+    /// `FramingError::MessageTooLarge` and the message it belongs to is discarded, the connection
+    /// is only `Degraded` as the frames that follow are still read. `DEFAULT_MAX_MESSAGE_LENGTH`
+    /// is used unless it's changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_length` - Maximum length of the partially received messages in bytes.
+    pub fn set_max_message_length(&self, max_length: usize) {
+        *self.max_message_length.borrow_mut() = max_length;
+    }
+
+    /// Sets the id of the receiving platform, closing of the virtual connection with it degrades
+    /// the connection state. `receiver-0` is used unless it's changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `receiver_id` - Id of the receiving platform, see `ConnectOptions::receiver_id`.
+    pub fn set_receiver_id<T: Into<String>>(&self, receiver_id: T) {
+        *self.receiver_id.borrow_mut() = receiver_id.into();
+    }
+
+    /// Runs `f` and repeats it according to the retry policy while it fails with the error
+    /// `is_transient` accepts. The last error is returned once attempts are exhausted.
+    pub(crate) fn retry<F, P, R>(&self, is_transient: P, mut f: F) -> Result<R, Error>
+    where
+        F: FnMut() -> Result<R, Error>,
+        P: Fn(&Error) -> bool,
+    {
+        let policy = self.retry_policy();
+        let mut retry = 0;
+
+        loop {
+            match f() {
+                Err(error) if retry + 1 < policy.max_attempts && is_transient(&error) => {
+                    let backoff = policy.backoff(retry);
+                    log::debug!("Retrying request in {backoff:?} after transient error: {error}");
+
+                    std::thread::sleep(backoff);
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns snapshot of the traffic exchanged with the device since `MessageManager` has been
+    /// created.
+    pub fn metrics(&self) -> TrafficMetrics {
+        self.metrics.borrow_mut().clone()
+    }
+
+    /// Sets `observer` that is called for every message sent to or received from the device (e.g.
+    /// to implement protocol logging), replacing the previous one. Observer is called while the
+    /// stream is in use, so it must not send or receive any messages itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - Function that is called with the message and its direction.
+    pub fn set_message_observer<F>(&self, observer: F)
+    where
+        F: Fn(&CastMessage, Direction) + Send + 'static,
+    {
+        *self.message_observer.borrow_mut() = Some(Box::new(observer));
+    }
+
+    /// Enables wire log mode: every frame sent to or received from the device is captured (with
+    /// sensitive payload fields redacted) and written to the `sink`, replacing the previous one.
+    /// Errors returned by the sink are logged and don't affect the communication with the device.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::net::TcpStream;
+    /// # use rust_cast::{message_manager::MessageManager, wire_log::JsonLinesSink};
+    /// # let tcp_stream = TcpStream::connect(("0", 8009))?;
+    /// # let message_manager = MessageManager::new(tcp_stream);
+    /// message_manager.set_wire_log(JsonLinesSink::create("cast-wire.log")?);
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - Destination of the captured frames, e.g. `JsonLinesSink` or a closure.
+    pub fn set_wire_log<L>(&self, sink: L)
+    where
+        L: WireLogSink + 'static,
+    {
+        *self.wire_log.borrow_mut() = Some(Box::new(sink));
+    }
+
+    /// Disables wire log mode, dropping the sink.
+    pub fn clear_wire_log(&self) {
+        *self.wire_log.borrow_mut() = None;
+    }
+
+    /// Sets the policy launch and volume requests of the channels are checked against before
+    /// they're sent, replacing the previous one. Policy can be shared by several connections
+    /// (e.g. all the devices of `CastManager`).
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Policy to check the operations with, e.g. `QuietHours` or a closure.
+    pub fn set_policy(&self, policy: Arc<dyn OperationPolicy>) {
+        *self.policy.borrow_mut() = Some(policy);
+    }
+
+    /// Removes the policy, all the operations are allowed afterwards.
+    pub fn clear_policy(&self) {
+        *self.policy.borrow_mut() = None;
+    }
+
+    /// Returns decision of the current policy about the `operation`, operations are allowed if
+    /// there is no policy.
+    pub(crate) fn check_policy(&self, operation: &Operation) -> Decision {
+        let policy = self.policy.borrow_mut().clone();
+        policy.map_or(Decision::Allow, |policy| policy.check(operation))
+    }
+
+    /// Passes `raw_message` frame to the wire log sink, if wire log mode is enabled.
+    fn log_frame(&self, direction: Direction, raw_message: &cast_channel::CastMessage) {
+        if let Some(sink) = self.wire_log.borrow_mut().as_mut() {
+            if let Err(err) = sink.write_frame(&WireFrame::capture(direction, raw_message)) {
+                log::warn!("Failed to write frame to the wire log: {err}");
+            }
+        }
+    }
+
+    /// Returns the latest error the cast device has reported at the transport level, if any.
+    pub fn last_transport_error(&self) -> Option<TransportError> {
+        self.last_transport_error.borrow_mut().clone()
+    }
+
+    /// Returns the last frame that couldn't be decoded, if it hasn't been taken yet.
+    pub(crate) fn take_malformed_frame(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.malformed_frame.borrow_mut())
+    }
+
+    /// Counts received message that couldn't be parsed by any of the channels.
+    pub(crate) fn record_parse_failure(&self) {
+        self.metrics.borrow_mut().parse_failures += 1;
+    }
+
+    /// Sends `message` to the Cast Device. Message is written with a single write and the stream
+    /// is flushed before the method returns, unless `MessageManager` has been created with
+    /// `new_split`: the writer thread then writes it in the background.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - `CastMessage` instance to be sent to the Cast Device.
+    pub fn send(&self, message: CastMessage) -> Result<(), Error> {
+        trace_span!(
+            "message_manager.send",
+            namespace = %message.namespace,
+            source = %message.source,
+            destination = %message.destination
+        );
+
+        self.check_shut_down()?;
+
+        // If receiver has closed virtual connection we've previously established with the
+        // destination, let's transparently re-establish it before sending anything else, unless
+        // receiver has told us why it has been closed.
+        if message.namespace != connection::CHANNEL_NAMESPACE {
+            let connect_message = match self
+                .connections
+                .borrow_mut()
+                .get_mut(&(message.source.clone(), message.destination.clone()))
+                .filter(|connection| connection.closed)
+            {
+                Some(VirtualConnection {
+                    close_reason: Some(reason),
+                    ..
+                }) => {
+                    return Err(Error::ConnectionClosed {
+                        destination: message.destination,
+                        reason: reason.clone(),
+                    })
+                }
+                Some(connection) => {
+                    connection.closed = false;
+                    Some(connection.connect_message.clone())
+                }
+                None => None,
+            };
+
+            if let Some(connect_message) = connect_message {
+                log::debug!(
+                    "Re-establishing closed connection with {}.",
+                    connect_message.destination
+                );
+                self.write(connect_message)?;
+            }
+        }
+
+        self.write(message)
+    }
+
+    /// Waits for the next `CastMessage` available. Can also return existing message from the
+    /// internal message buffer containing messages that have been received previously, but haven't
+    /// been consumed for some reason (e.g. during `receive_find_map` call). Responses to the
+    /// requests other threads wait for (see `receive_response`) are never returned.
+    ///
+    /// # Return value
+    ///
+    /// `Result` containing parsed `CastMessage` or `Error`.
+    pub fn receive(&self) -> Result<CastMessage, Error> {
+        trace_span!("message_manager.receive");
+
+        self.receive_matching(None, None, self.set_read_timeout, |_| Ok(Some(())))
+            .map(|received| received.map(|(message, _)| message))?
+            .ok_or_else(|| Error::Internal("Message hasn't been received.".to_string()))
+    }
+
+    /// Waits for the next `CastMessage` for which `f` returns valid mapped value. Messages that are
+    /// already in the internal message buffer are checked first (in the order they were received)
+    /// and only then the stream is read. Messages in which `f` is not interested are kept in the
+    /// internal message buffer (or placed into it) and can be later retrieved with `receive` or by
+    /// the subsequent `receive_find_map` call, so no message is ever dropped on the floor. Responses
+    /// to the requests other threads wait for (see `receive_response`) are left for them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::net::TcpStream;
+    /// # use rust_cast::message_manager::{CastMessage, MessageManager};
+    /// # use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+    /// # use rustls::pki_types::ServerName;
+    /// # let config = ClientConfig::builder()
+    /// #   .with_root_certificates(RootCertStore::empty())
+    /// #   .with_no_client_auth();
+    /// # let server_name = ServerName::try_from("0")?.to_owned();
+    /// # let conn = ClientConnection::new(config.into(), server_name)?;
+    /// # let tcp_stream = TcpStream::connect(("0", 8009)).unwrap();
+    /// # let ssl_stream = StreamOwned::new(conn, tcp_stream);
+    /// # let message_manager = MessageManager::new(ssl_stream);
+    /// # fn can_handle(message: &CastMessage) -> bool { unimplemented!() }
+    /// # fn parse(message: &CastMessage) { unimplemented!() }
+    /// message_manager.receive_find_map(|message| {
+    ///   if !can_handle(message) {
+    ///     return Ok(None);
+    ///   }
+    ///
+    ///   parse(message);
+    ///
+    ///   Ok(Some(()))
+    /// })?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Function that analyzes and maps `CastMessage` to any other type. If message doesn't
+    ///   look like something `f` is looking for, then `Ok(None)` should be returned so that message
+    ///   is not lost and placed into internal message buffer for later retrieval. If `f` returns an
+    ///   error, the message is considered consumed and the error is returned to the caller.
+    ///
+    /// # Return value
+    ///
+    /// `Result` containing parsed `CastMessage` or `Error`.
+    pub fn receive_find_map<F, B>(&self, f: F) -> Result<B, Error>
+    where
+        F: Fn(&CastMessage) -> Result<Option<B>, Error>,
+    {
+        trace_span!("message_manager.receive_find_map");
+
+        self.find_map(None, f)
+    }
+
+    /// Waits for the response to the request with the `request_id` reserved with
+    /// `reserve_request_id`, the same way `receive_find_map` does: `f` maps the messages that
+    /// respond to this request and the messages that don't respond to any pending request, the
+    /// responses to the requests other threads wait for are kept for them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::net::TcpStream;
+    /// # use rust_cast::message_manager::{CastMessage, CastMessagePayload, MessageManager};
+    /// # let message_manager = MessageManager::new(TcpStream::connect("127.0.0.1:8009")?);
+    /// let request = message_manager.reserve_request_id();
+    /// let request_id = request.id().get();
+    /// message_manager.send(CastMessage {
+    ///     namespace: "urn:x-cast:com.example".to_string(),
+    ///     source: "sender-0".to_string(),
+    ///     destination: "web-1".to_string(),
+    ///     payload: CastMessagePayload::String(format!(r#"{{"requestId":{request_id}}}"#)),
+    /// })?;
+    /// let response = message_manager.receive_response(request.id(), |message| {
+    ///     Ok((message.namespace == "urn:x-cast:com.example").then(|| message.clone()))
+    /// })?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    pub fn receive_response<F, B>(&self, request_id: RequestId, f: F) -> Result<B, Error>
+    where
+        F: Fn(&CastMessage) -> Result<Option<B>, Error>,
+    {
+        trace_span!(
+            "message_manager.receive_response",
+            request_id = request_id.get()
+        );
+
+        self.find_map(Some(request_id), f)
+    }
+
+    /// Runs `receive_matching` without deadline and maps its result with `f`.
+    fn find_map<F, B>(&self, request_id: Option<RequestId>, f: F) -> Result<B, Error>
+    where
+        F: Fn(&CastMessage) -> Result<Option<B>, Error>,
+    {
+        self.receive_matching(request_id, None, self.set_read_timeout, f)?
+            .map(|(_, mapped)| mapped)
+            .ok_or_else(|| Error::Internal("Message hasn't been received.".to_string()))
+    }
+
+    /// Generates request id (see `generate_request_id`) and reserves it until the returned
+    /// `PendingRequest` is dropped: responses to the request (with the id in `requestId` field)
+    /// are then only returned by `receive_response` with this id, even if other threads receive
+    /// messages at the same time.
+    pub fn reserve_request_id(&self) -> PendingRequest<'_, S> {
+        let request_id = self.generate_request_id();
+        self.inbox.lock().pending.insert(request_id);
+
+        PendingRequest {
+            message_manager: self,
+            request_id,
+        }
+    }
+
+    /// Generates id that is used in some requests to map them with the response. Ids are shared by
+    /// all the channels and destinations using this `MessageManager`, so the response of one
+    /// destination can never be mistaken for the response of another one.
+    ///
+    /// # Return value
+    ///
+    /// Unique (in the scope of this particular `MessageManager` instance) request id, sequence wraps
+    /// around once `u32::MAX` is reached.
+    pub fn generate_request_id(&self) -> RequestId {
+        let mut counter = self.request_counter.borrow_mut();
+        let request_id = RequestId(counter.get());
+        // Zero stands for unsolicited messages, so it's skipped when the sequence wraps around.
+        *counter = counter.checked_add(1).unwrap_or(NonZeroU32::MIN);
+        request_id
+    }
+
+    /// Remembers virtual connection established with `CONNECT` message, so that it can be tracked
+    /// and automatically re-established if receiver closes it.
+    ///
+    /// # Arguments
+    ///
+    /// * `connect_message` - `CONNECT` message that has been sent to establish the connection.
+    pub(crate) fn track_connection(&self, connect_message: CastMessage) {
+        self.connections.borrow_mut().insert(
+            (
+                connect_message.source.clone(),
+                connect_message.destination.clone(),
+            ),
+            VirtualConnection {
+                connect_message,
+                closed: false,
+                close_reason: None,
+            },
+        );
+    }
+
+    /// Stops tracking virtual connection between `source` and `destination` (e.g. because sender
+    /// has closed it).
+    pub(crate) fn untrack_connection(&self, source: &str, destination: &str) {
+        self.connections
+            .borrow_mut()
+            .remove(&(source.to_string(), destination.to_string()));
+    }
+
+    /// Returns destinations of all virtual connections that are established by `source` and haven't
+    /// been closed by the receiver.
+    pub(crate) fn connected_destinations(&self, source: &str) -> Vec<String> {
+        self.connections
+            .borrow_mut()
+            .iter()
+            .filter(|((connection_source, _), connection)| {
+                connection_source == source && !connection.closed
+            })
+            .map(|((_, destination), _)| destination.clone())
+            .collect()
+    }
+
+    /// Returns `(source, destination)` pairs of all the virtual connections that are open, of all
+    /// the senders.
+    pub(crate) fn open_connections(&self) -> Vec<(String, String)> {
+        let mut connections = self
+            .connections
+            .borrow_mut()
+            .iter()
+            .filter(|(_, connection)| !connection.closed)
+            .map(|(route, _)| route.clone())
+            .collect::<Vec<_>>();
+        connections.sort();
+
+        connections
+    }
+
+    /// Flushes the stream and lets `f` gracefully shut it down (e.g. send TLS `close_notify` and
+    /// shut down the underlying socket). Stream is closed only once, subsequent calls are no-op.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Function that shuts down the stream.
+    pub(crate) fn close<F>(&self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut S) -> Result<(), IoError>,
+    {
+        let mut closed = self.closed.borrow_mut();
+        if *closed {
+            return Ok(());
+        }
+
+        *closed = true;
+
+        // Queued messages are written on the best effort basis, nobody waits for them anymore.
+        if let Some(WriterHandle(queue)) = &self.writer {
+            if !queue.drain(CLOSE_DRAIN_TIMEOUT) {
+                log::debug!("Failed to write queued messages before closing.");
+            }
+            queue.close();
+        }
+
+        let result = {
+            let stream = &mut *self.stream.borrow_mut();
+            if self.writer.is_none() {
+                if let Err(err) = self.write_unsent(stream, &mut self.unsent.borrow_mut()) {
+                    log::debug!("Failed to write queued message before closing: {err}");
+                }
+            }
+            stream.flush().and_then(|_| f(stream))
+        };
+        self.set_state(ConnectionState::Closed);
+
+        Ok(result?)
+    }
+
+    /// Marks the connection as shut down: all the subsequent requests, as well as the ones that
+    /// are blocked on the stream and fail once the socket is shut down, return `Error::Shutdown`.
+    /// The stream itself is left as is, it's up to the caller to interrupt it.
+    pub(crate) fn shutdown(&self) {
+        *self.shut_down.borrow_mut() = true;
+        self.set_state(ConnectionState::Closed);
+    }
+
+    /// Determines whether the connection has been shut down with `shutdown`.
+    pub fn is_shut_down(&self) -> bool {
+        *self.shut_down.borrow_mut()
+    }
+
+    /// Fails with `Error::Shutdown` if the connection has been shut down.
+    fn check_shut_down(&self) -> Result<(), Error> {
+        if self.is_shut_down() {
+            return Err(Error::Shutdown);
+        }
+
+        Ok(())
+    }
+
+    /// Switches connection to the new `state` and notifies the listeners, `Closed` connection never
+    /// changes its state.
+    fn set_state(&self, state: ConnectionState) {
+        {
+            let mut current_state = self.state.borrow_mut();
+            if *current_state == state || *current_state == ConnectionState::Closed {
+                return;
+            }
+
+            *current_state = state;
+        }
+
+        log::debug!("Connection state has changed to {:?}.", state);
+
+        for listener in self.state_listeners.borrow_mut().iter() {
+            listener(state);
+        }
+    }
+
+    /// Updates connection state if `result` is an error caused by the stream failure or timeout.
+    /// Errors of the connection that has been shut down meanwhile are reported as
+    /// `Error::Shutdown`.
+    fn track_result<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        if result.is_err() && self.is_shut_down() {
+            return Err(Error::Shutdown);
+        }
+
+        match result {
+            Err(Error::Io(ref error)) => self.set_state(match error.kind() {
+                ErrorKind::UnexpectedEof
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::BrokenPipe
+                | ErrorKind::NotConnected => ConnectionState::Closed,
+                _ => ConnectionState::Degraded,
+            }),
+            Err(Error::Timeout(_)) => self.set_state(ConnectionState::Degraded),
+            // Oversized partial message is discarded and the frames that follow are still read.
+            Err(Error::Framing(FramingError::MessageTooLarge { .. })) => {
+                self.set_state(ConnectionState::Degraded)
+            }
+            Err(Error::Framing(_)) => self.set_state(ConnectionState::Closed),
+            _ => {}
+        }
+
+        result
+    }
+
+    /// Writes `message` to the stream. Messages which frame exceeds `MAX_FRAME_SIZE` are split into
+    /// several chunks marked with `continued` and `remaining_length` fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - `CastMessage` instance to be written to the stream.
+    fn write(&self, message: CastMessage) -> Result<(), Error> {
+        self.notify_observer(&message, Direction::Outgoing);
+
+        let priority = match message.namespace.as_str() {
+            heartbeat::CHANNEL_NAMESPACE => MessagePriority::Heartbeat,
+            connection::CHANNEL_NAMESPACE
+                if connection::parse_close_message(&message).is_some() =>
+            {
+                MessagePriority::Control
+            }
+            _ => MessagePriority::Normal,
+        };
+
+        let mut raw_message = raw_message(message);
+        let buffer = &mut *self.write_buffer.borrow_mut();
+
+        buffer.clear();
+
+        if raw_message.compute_size() > MAX_FRAME_SIZE as u64 {
+            self.encode_chunks(&mut raw_message, buffer)?;
+        } else {
+            self.encode_frame(&raw_message, buffer)?;
+            log::debug!("Message sent: {:?}", raw_message);
+        }
+
+        let result = self.write_buffer_to_stream(buffer, priority);
+
+        if result.is_ok() {
+            self.metrics.borrow_mut().record(
+                raw_message.namespace(),
+                Direction::Outgoing,
+                1,
+                buffer.len() as u64,
+            );
+            self.set_state(ConnectionState::Connected);
+        }
+        shrink_buffer(buffer);
+
+        self.track_result(result)
+    }
+
+    /// Passes `message` to the message observer, if any.
+    fn notify_observer(&self, message: &CastMessage, direction: Direction) {
+        if let Some(observer) = self.message_observer.borrow_mut().as_ref() {
+            observer(message, direction);
+        }
+    }
+
+    /// Writes all the frames staged in the `buffer` with a single write and flushes the stream, so
+    /// that the message is neither split into several TLS records nor left in any buffer, or queues
+    /// them for the writer thread according to their `priority`, see `SendQueueOptions`.
+    /// Writes that fail with transient errors are retried according to the retry policy, starting
+    /// from the first byte that hasn't been written yet. Fails with `Error::Timeout` if the stream
+    /// doesn't accept the whole message in time, the rest of it is written before the next message.
+    fn write_buffer_to_stream(
+        &self,
+        buffer: &[u8],
+        priority: MessagePriority,
+    ) -> Result<(), Error> {
+        if let Some(WriterHandle(queue)) = &self.writer {
+            return self.enqueue(queue, buffer, priority);
+        }
+
+        let writer = &mut *self.stream.borrow_mut();
+        let unsent = &mut *self.unsent.borrow_mut();
+
+        // Frames are never interleaved, the rest of the previous message goes first.
+        self.write_unsent(writer, unsent)?;
+
+        let written = self.write_available(writer, buffer)?;
+        if written < buffer.len() {
+            if written > 0 {
+                unsent.extend_from_slice(&buffer[written..]);
+            }
+            return Err(Error::Timeout(
+                "stream hasn't accepted the message in time".to_string(),
+            ));
+        }
+
+        self.flush_stream(writer)
+    }
+
+    /// Writes the rest of the message the stream hasn't accepted in time before, fails with
+    /// `Error::Timeout` if the stream doesn't accept it in time again.
+    fn write_unsent(&self, writer: &mut S, unsent: &mut Vec<u8>) -> Result<(), Error> {
+        if unsent.is_empty() {
+            return Ok(());
+        }
+
+        let written = self.write_available(writer, unsent)?;
+        unsent.drain(..written);
+        if !unsent.is_empty() {
+            return Err(Error::Timeout(
+                "stream hasn't accepted the previous message in time".to_string(),
+            ));
+        }
+
+        self.flush_stream(writer)
+    }
+
+    /// Queues the frames staged in the `buffer` for the writer thread, the frames go right after
+    /// the last queued frame of the same or higher `priority`.
+    fn enqueue(
+        &self,
+        queue: &WriteQueue,
+        buffer: &[u8],
+        priority: MessagePriority,
+    ) -> Result<(), Error> {
+        let options = *self.send_queue.borrow_mut();
+        let mut state = queue.lock();
+
+        loop {
+            if let Some((kind, error)) = &state.error {
+                return Err(Error::Io(IoError::new(*kind, error.clone())));
+            }
+            if state.closed {
+                return Err(Error::Io(ErrorKind::NotConnected.into()));
+            }
+            if state.frames.len() < options.capacity {
+                break;
+            }
+
+            match options.backpressure {
+                BackpressurePolicy::Block => {
+                    self.check_shut_down()?;
+                    let token = self.cancellation.borrow_mut().clone();
+                    if let Some(token) = &token {
+                        token.check()?;
+                    }
+                    let timeout =
+                        token.map_or(CANCELLATION_POLL_INTERVAL, |token| token.poll_interval());
+                    state = queue.changed.wait_timeout(state, timeout).unwrap().0;
+                }
+                BackpressurePolicy::DropOldest => {
+                    match state.frames.iter().position(OutgoingFrame::is_droppable) {
+                        Some(index) => {
+                            log::debug!("Send queue is full, dropping the oldest heartbeat.");
+                            state.frames.remove(index);
+                        }
+                        None => return Err(Error::QueueFull(state.frames.len())),
+                    }
+                }
+                BackpressurePolicy::Error => return Err(Error::QueueFull(state.frames.len())),
+            }
+        }
+
+        let index = state
+            .frames
+            .iter()
+            .rposition(|frame| frame.priority >= priority)
+            .map_or(0, |index| index + 1);
+        state.frames.insert(
+            index,
+            OutgoingFrame {
+                bytes: buffer.to_vec(),
+                priority,
+            },
+        );
+        queue.changed.notify_all();
+
+        Ok(())
+    }
+
+    /// Writes the `bytes` until the stream stops accepting them in time (fails with `WouldBlock`
+    /// or `TimedOut` even after the retries).
+    ///
+    /// # Return value
+    ///
+    /// Number of bytes written.
+    fn write_available(&self, writer: &mut S, bytes: &[u8]) -> Result<usize, Error> {
+        let mut written = 0;
+        while written < bytes.len() {
+            match self.retry(Error::is_transient, || {
+                match writer.write(&bytes[written..]) {
+                    Ok(0) => Err(Error::Io(IoError::from(std::io::ErrorKind::WriteZero))),
+                    result => Ok(result?),
+                }
+            }) {
+                Ok(count) => written += count,
+                Err(error) if is_blocked(&error) => break,
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Flushes the stream, fails with `Error::Timeout` if the stream doesn't accept the buffered
+    /// data in time, the data then goes out with the next write.
+    fn flush_stream(&self, writer: &mut S) -> Result<(), Error> {
+        match self.retry(Error::is_transient, || Ok(writer.flush()?)) {
+            Err(error) if is_blocked(&error) => Err(Error::Timeout(
+                "stream hasn't been flushed in time".to_string(),
+            )),
+            result => result,
+        }
+    }
+
+    /// Splits `raw_message` into several chunks that fit into the frame and appends them to the
+    /// `buffer` one after another, so that chunks are never interleaved with any other message.
+    fn encode_chunks(
+        &self,
+        raw_message: &mut cast_channel::CastMessage,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let payload = take_payload(raw_message);
+
+        // Every chunk carries the same header, let's figure out how much room is left for the
+        // payload, assuming the largest possible `remaining_length` and payload length varints.
+        raw_message.set_protocol_version(ProtocolVersion::CASTV2_1_1);
+        raw_message.set_continued(true);
+        raw_message.set_remaining_length(u32::MAX);
+        let chunk_size = MAX_FRAME_SIZE
+            .checked_sub(raw_message.compute_size() as usize + 6)
+            .filter(|chunk_size| *chunk_size > 0)
+            .ok_or_else(|| Error::Internal("Message header exceeds frame size.".to_string()))?;
+
+        let chunks = split_payload(&payload, chunk_size);
+        let mut remaining_length = payload.as_bytes().len();
+        let chunk_count = chunks.len();
+
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            remaining_length -= chunk.as_bytes().len();
+
+            set_payload(raw_message, chunk);
+            raw_message.set_continued(index + 1 < chunk_count);
+            raw_message.set_remaining_length(remaining_length as u32);
+
+            self.encode_frame(raw_message, buffer)?;
+        }
+
+        log::debug!(
+            "Message sent in {} chunks: {:?}",
+            chunk_count,
+            CastMessage {
+                namespace: raw_message.namespace().to_string(),
+                source: raw_message.source_id().to_string(),
+                destination: raw_message.destination_id().to_string(),
+                payload,
+            }
+        );
+
+        Ok(())
+    }
+
+    /// Appends single frame (length prefix followed by the serialized message) to the `buffer`.
+    fn encode_frame(
+        &self,
+        raw_message: &cast_channel::CastMessage,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.log_frame(Direction::Outgoing, raw_message);
+
+        let start = buffer.len();
+
+        // Length prefix is patched once the message is serialized right after it.
+        buffer.extend_from_slice(&[0; 4]);
+        raw_message.write_to_vec(buffer)?;
+
+        let length = (buffer.len() - start - 4) as u32;
+        buffer[start..start + 4].copy_from_slice(&utils::write_u32_to_buffer(length)?);
+
+        Ok(())
+    }
+
+    /// Reads next `CastMessage` from the stream.
+    ///
+    /// # Return value
+    ///
+    /// `Result` containing parsed `CastMessage` or `Error`.
+    fn read(&self) -> Result<CastMessage, Error> {
+        let mut buffer: [u8; 4] = [0; 4];
+
+        let reader = &mut *self.stream.borrow_mut();
+
+        loop {
+            let result = reader
+                .read_exact(&mut buffer)
+                .map_err(Error::from)
+                .and_then(|_| self.read_message(reader, buffer));
+
+            if let Some(message) = self.track_result(result)? {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// Reads the rest of the `CastMessage` which length prefix has already been read from the
+    /// stream. The frame is read into the buffer that is reused between the frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Stream to read the message from.
+    /// * `length_buffer` - Length prefix of the message.
+    ///
+    /// # Return value
+    ///
+    /// `Result` containing parsed `CastMessage`, `None` if the frame is just a chunk of the message
+    /// that is not complete yet, or `Error`.
+    fn read_message(
+        &self,
+        reader: &mut S,
+        length_buffer: [u8; 4],
+    ) -> Result<Option<CastMessage>, Error> {
+        let length = utils::read_u32_from_buffer(&length_buffer)?;
+        let max_length = *self.max_frame_length.borrow_mut();
+        if length == 0 {
+            return Err(Error::Framing(FramingError::Empty));
+        }
+        if length > max_length {
+            return Err(Error::Framing(FramingError::TooLarge {
+                length,
+                max_length,
+            }));
+        }
+
+        let mut raw_message = {
+            let buffer = &mut *self.read_buffer.borrow_mut();
+
+            buffer.resize(length as usize, 0);
+            Self::read_frame(reader, buffer)?;
+
+            let raw_message = cast_channel::CastMessage::parse_from_bytes(buffer);
+            if raw_message.is_err() {
+                self.record_parse_failure();
+                // Keep the frame around, so that it can be reported along with the error.
+                *self.malformed_frame.borrow_mut() = buffer.clone();
+            }
+            shrink_buffer(buffer);
+            raw_message?
+        };
+
+        log::debug!("Message received: {:?}", raw_message);
+        self.log_frame(Direction::Incoming, &raw_message);
+
+        // Every chunk counts towards the traffic, but only complete messages are counted.
+        self.metrics.borrow_mut().record(
+            raw_message.namespace(),
+            Direction::Incoming,
+            u64::from(!raw_message.continued()),
+            u64::from(length) + 4,
+        );
+
+        let continued = raw_message.continued();
+        let message = cast_message(&mut raw_message);
+
+        // Large messages are split into several chunks, let's assemble them back before the
+        // message is handed over to anyone.
+        let mut partial_messages = self.partial_messages.borrow_mut();
+        let mut discarded_messages = self.discarded_messages.borrow_mut();
+
+        let message = if continued || !partial_messages.is_empty() || !discarded_messages.is_empty()
+        {
+            let key = (
+                message.source.clone(),
+                message.destination.clone(),
+                message.namespace.clone(),
+            );
+
+            // Remaining chunks of the oversized message are skipped.
+            if discarded_messages.contains(&key) {
+                if !continued {
+                    discarded_messages.remove(&key);
+                }
+
+                return Ok(None);
+            }
+
+            if continued || partial_messages.contains_key(&key) {
+                let length = partial_messages
+                    .values()
+                    .map(|payload| payload.as_bytes().len())
+                    .sum::<usize>()
+                    + message.payload.as_bytes().len();
+                let max_length = *self.max_message_length.borrow_mut();
+
+                if length > max_length {
+                    partial_messages.remove(&key);
+                    if continued {
+                        discarded_messages.insert(key);
+                    }
+
+                    return Err(Error::Framing(FramingError::MessageTooLarge {
+                        length,
+                        max_length,
+                    }));
+                }
+            }
+
+            if continued {
+                match partial_messages.get_mut(&key) {
+                    Some(payload) => append_payload(payload, message.payload)?,
+                    None => {
+                        partial_messages.insert(key, message.payload);
+                    }
+                }
+
+                return Ok(None);
+            }
+
+            match partial_messages.remove(&key) {
+                Some(mut payload) => {
+                    append_payload(&mut payload, message.payload)?;
+                    CastMessage { payload, ..message }
+                }
+                None => message,
+            }
+        } else {
+            message
+        };
+        drop(partial_messages);
+        drop(discarded_messages);
+
+        self.set_state(ConnectionState::Connected);
+        self.notify_observer(&message, Direction::Incoming);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            namespace = %message.namespace,
+            source = %message.source,
+            destination = %message.destination,
+            "Message received."
+        );
+
+        // Receiver may close virtual connection at any time (e.g. after some time of inactivity),
+        // let's remember that so that we can re-establish it on the next send.
+        if let Some(reason) = connection::parse_close_message(&message) {
+            if message.source == *self.receiver_id.borrow_mut() {
+                self.set_state(ConnectionState::Degraded);
+            }
+
+            for ((source, destination), connection) in self.connections.borrow_mut().iter_mut() {
+                if *destination == message.source
+                    && (*source == message.destination || message.destination == "*")
+                {
+                    log::debug!(
+                        "Connection with {destination} has been closed by receiver, reason: \
+                         {reason}."
+                    );
+                    connection.closed = true;
+                    connection.close_reason = reason.code.map(|_| reason.clone());
+                }
+            }
+        }
+
+        // Transport level error means that some of our messages couldn't be handled, so the
+        // connection isn't considered healthy until the next successful exchange.
+        if let Some(error) = error::parse_error_message(&message) {
+            log::warn!("Transport error received from {}: {error}.", message.source);
+            self.set_state(ConnectionState::Degraded);
+            *self.last_transport_error.borrow_mut() = Some(error);
+        }
+
+        Ok(Some(message))
+    }
+
+    /// Fills the `buffer` with the frame, frame may arrive in any number of reads (e.g. when it
+    /// spans several TLS records).
+    fn read_frame(reader: &mut S, buffer: &mut [u8]) -> Result<(), Error> {
+        let mut position = 0;
+
+        while position < buffer.len() {
+            match reader.read(&mut buffer[position..]) {
+                Ok(0) => {
+                    return Err(Error::Framing(FramingError::Truncated {
+                        read: position,
+                        length: buffer.len(),
+                    }))
+                }
+                Ok(read) => position += read,
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Waits for the message `f` maps (along with the mapped value) honoring the current
+    /// cancellation token, if any. Messages received previously are checked first, then the stream
+    /// is read unless another thread is reading it already: the thread that reads the stream hands
+    /// the messages it isn't interested in to the others. Messages `f` isn't interested in are kept
+    /// in the internal message buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_id` - Id of the pending request `f` maps the responses of, `None` skips the
+    ///   responses to all the pending requests.
+    /// * `deadline` - Time to stop waiting at, `None` waits indefinitely.
+    /// * `set_read_timeout` - Function that sets read timeout of the stream, if it's supported.
+    /// * `f` - Function that maps the message, messages are skipped if it returns `None`.
+    ///
+    /// # Return value
+    ///
+    /// `Result` containing message and its mapped value, `None` if the `deadline` has been reached,
+    /// or `Error`.
+    fn receive_matching<F, B>(
+        &self,
+        request_id: Option<RequestId>,
+        deadline: Option<Instant>,
+        set_read_timeout: Option<SetReadTimeout<S>>,
+        f: F,
+    ) -> Result<Option<(CastMessage, B)>, Error>
+    where
+        F: Fn(&CastMessage) -> Result<Option<B>, Error>,
+    {
+        // Ids of the buffered messages `f` has been called with already.
+        let mut checked = HashSet::new();
+        let mut inbox = self.inbox.lock();
+
+        loop {
+            self.check_shut_down()?;
+
+            // Some other request may have buffered the message we're looking for, let's check
+            // that first. `f` is called without the inbox locked (it may e.g. run the listeners
+            // of the media channel), so that other threads can receive their messages meanwhile.
+            let candidates = inbox
+                .messages
+                .iter()
+                .filter(|message| message.request_id.is_none() || message.request_id == request_id)
+                .filter(|message| !checked.contains(&message.id))
+                .map(|message| (message.id, message.message.clone()))
+                .collect::<Vec<_>>();
+            if !candidates.is_empty() {
+                drop(inbox);
+                for (id, message) in candidates {
+                    checked.insert(id);
+                    let result = f(&message);
+                    // Message may have been taken by another thread meanwhile.
+                    if !matches!(result, Ok(None)) && self.inbox.lock().take(id).is_some() {
+                        return result.map(|mapped| mapped.map(|mapped| (message, mapped)));
+                    }
+                }
+                inbox = self.inbox.lock();
+                continue;
+            }
+
+            let token = self.cancellation.borrow_mut().clone();
+            if let Some(token) = &token {
+                token.check()?;
+            }
+
+            let remaining =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            if inbox.reading {
+                if remaining.is_some_and(|remaining| remaining.is_zero()) {
+                    return Ok(None);
+                }
+
+                // Shutdown and cancellation are checked every now and then meanwhile.
+                let timeout = remaining
+                    .unwrap_or(CANCELLATION_POLL_INTERVAL)
+                    .min(token.map_or(CANCELLATION_POLL_INTERVAL, |token| token.poll_interval()));
+                inbox = self.inbox.changed.wait_timeout(inbox, timeout).unwrap().0;
+                continue;
+            }
+
+            inbox.reading = true;
+            drop(inbox);
+            let result = self.read_until(remaining, token, set_read_timeout);
+            inbox = self.inbox.lock();
+            inbox.reading = false;
+            self.inbox.changed.notify_all();
+
+            let message = match result? {
+                Some(message) => message,
+                None if remaining.is_some_and(|remaining| remaining.is_zero()) => return Ok(None),
+                None => continue,
+            };
+
+            // If message is found, just return mapped result, otherwise keep unprocessed message
+            // in the buffer, it can be later retrieved with `receive` or by the thread waiting for
+            // it.
+            let message_request_id = inbox.pending_request(&message);
+            if message_request_id.is_none() || message_request_id == request_id {
+                drop(inbox);
+                let mapped = f(&message)?;
+                inbox = self.inbox.lock();
+                if let Some(mapped) = mapped {
+                    return Ok(Some((message, mapped)));
+                }
+                checked.insert(inbox.next_id);
+            }
+            inbox.push(message_request_id, message);
+            self.inbox.changed.notify_all();
+        }
+    }
+
+    /// Reads next `CastMessage` from the stream waiting at most for the `timeout` and the current
+    /// cancellation `token` poll interval, if the stream supports read timeouts.
+    ///
+    /// # Return value
+    ///
+    /// `Result` containing parsed `CastMessage`, `None` if the token should be checked again (or
+    /// only a chunk of the message has been received), or `Error`.
+    fn read_until(
+        &self,
+        timeout: Option<Duration>,
+        token: Option<CancellationToken>,
+        set_read_timeout: Option<SetReadTimeout<S>>,
+    ) -> Result<Option<CastMessage>, Error> {
+        let timeout = match (timeout, token) {
+            (Some(timeout), Some(token)) => Some(timeout.min(token.poll_interval())),
+            (None, Some(token)) => Some(token.poll_interval()),
+            (timeout, None) => timeout,
+        };
+
+        let result = match (timeout, set_read_timeout) {
+            (Some(timeout), Some(set_read_timeout)) => self.read_timeout(timeout, set_read_timeout),
+            _ => self.read().map(Some),
+        };
+
+        self.track_result(result)
+    }
+
+    /// Reads next `CastMessage` from the stream waiting at most for the `timeout`. Once the first
+    /// bytes of the message have arrived, the rest of the message is read without timeout, so that
+    /// the message is never lost half-read.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Maximum time to wait for the message.
+    /// * `set_read_timeout` - Function that sets read timeout of the stream.
+    ///
+    /// # Return value
+    ///
+    /// `Result` containing parsed `CastMessage`, `None` if no complete message has arrived within
+    /// the `timeout`, or `Error`.
+    fn read_timeout(
+        &self,
+        timeout: Duration,
+        set_read_timeout: SetReadTimeout<S>,
+    ) -> Result<Option<CastMessage>, Error> {
+        let mut buffer: [u8; 4] = [0; 4];
+
+        let reader = &mut *self.stream.borrow_mut();
+
+        set_read_timeout(reader, Some(timeout))?;
+        let result = Self::read_length_prefix(reader, &mut buffer, set_read_timeout);
+        set_read_timeout(reader, None)?;
+
+        if !result? {
+            return Ok(None);
+        }
+
+        self.read_message(reader, buffer)
+    }
+
+    /// Reads length prefix of the message from the stream that has read timeout set. Returns
+    /// `false` if read has timed out before any data has arrived.
+    fn read_length_prefix(
+        reader: &mut S,
+        buffer: &mut [u8; 4],
+        set_read_timeout: SetReadTimeout<S>,
+    ) -> Result<bool, IoError> {
+        let mut position = 0;
+
+        while position < buffer.len() {
+            match reader.read(&mut buffer[position..]) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(read) => {
+                    // Message has started to arrive, the rest of it must not time out.
+                    if position == 0 {
+                        set_read_timeout(reader, None)?;
+                    }
+                    position += read;
+                }
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error)
+                    if position == 0
+                        && matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+                {
+                    return Ok(false);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl<S> MessageManager<S>
+where
+    S: Write + Read + ReadTimeout,
+{
+    /// Waits for the next `CastMessage` at most for the `timeout`, messages from the internal
+    /// message buffer are returned immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Maximum time to wait for the message, `Duration::ZERO` doesn't wait at all.
+    ///
+    /// # Return value
+    ///
+    /// `Result` containing parsed `CastMessage`, `None` if no message has arrived within the
+    /// `timeout`, or `Error`.
+    pub fn receive_timeout(&self, timeout: Duration) -> Result<Option<CastMessage>, Error> {
+        trace_span!("message_manager.receive", ?timeout);
+
+        let deadline = Instant::now() + timeout;
+        let received =
+            self.receive_matching(None, Some(deadline), Some(S::set_read_timeout), |_| {
+                Ok(Some(()))
+            })?;
+
+        Ok(received.map(|(message, _)| message))
+    }
+
+    /// Returns the next `CastMessage` if it's already available, doesn't block otherwise.
+    ///
+    /// # Return value
+    ///
+    /// `Result` containing parsed `CastMessage`, `None` if no message is available, or `Error`.
+    pub fn try_receive(&self) -> Result<Option<CastMessage>, Error> {
+        self.receive_timeout(Duration::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::EnumOrUnknown;
+
+    use crate::{testing::MockTcpStream, DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID};
+
+    use super::*;
+
+    #[test]
+    fn test_receive() {
+        let mut stream = MockTcpStream::new();
+        let payload = r#"{"type":"PING"}"#;
+        stream.add_message(cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(crate::channels::heartbeat::CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(payload.to_string()),
+            payload_binary: None,
+            continued: None,
+            remaining_length: None,
+            special_fields: Default::default(),
+        });
+        let message_manager = MessageManager::new(stream);
+        let expected_result = CastMessage {
+            namespace: crate::channels::heartbeat::CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_RECEIVER_ID.to_string(),
+            destination: DEFAULT_SENDER_ID.to_string(),
+            payload: CastMessagePayload::String(payload.to_string()),
+        };
+
+        let result = message_manager
+            .receive()
+            .expect("expected to receive a message");
+
+        assert_eq!(expected_result, result);
+    }
+
+    #[test]
+    fn test_receive_find_map_buffers_skipped_messages() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(heartbeat_message(r#"{"type":"PING"}"#));
+        stream.add_message(heartbeat_message(r#"{"type":"PONG"}"#));
+        let message_manager = MessageManager::new(stream);
+
+        let result = message_manager
+            .receive_find_map(|message| {
+                let payload = string_payload(r#"{"type":"PONG"}"#);
+                Ok((message.payload == payload).then_some(payload))
+            })
+            .expect("expected to find a message");
+        assert_eq!(string_payload(r#"{"type":"PONG"}"#), result);
+
+        let skipped = message_manager
+            .receive()
+            .expect("expected to receive a buffered message");
+        assert_eq!(string_payload(r#"{"type":"PING"}"#), skipped.payload);
+    }
+
+    #[test]
+    fn test_receive_find_map_checks_buffered_messages_first() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(heartbeat_message(r#"{"type":"PING"}"#));
+        stream.add_message(heartbeat_message(r#"{"type":"PONG"}"#));
+        let message_manager = MessageManager::new(stream);
+
+        message_manager
+            .receive_find_map(|message| {
+                Ok((message.payload == string_payload(r#"{"type":"PONG"}"#)).then_some(()))
+            })
+            .expect("expected to find a message");
+
+        // The stream has no more messages, so the only way to succeed is to use the buffer.
+        let result = message_manager
+            .receive_find_map(|message| Ok(Some(message.payload.clone())))
+            .expect("expected to find a buffered message");
+        assert_eq!(string_payload(r#"{"type":"PING"}"#), result);
+    }
+
+    #[test]
+    fn test_receive_find_map_runs_f_without_inbox_locked() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(heartbeat_message(r#"{"type":"PING"}"#));
+        stream.add_message(heartbeat_message(r#"{"type":"PONG"}"#));
+        let message_manager = MessageManager::new(stream);
+
+        // Both the buffered and the freshly read messages are mapped by `f` that uses the inbox.
+        for expected in [r#"{"type":"PONG"}"#, r#"{"type":"PING"}"#] {
+            let request_id = message_manager
+                .receive_find_map(|message| {
+                    let request = message_manager.reserve_request_id();
+                    Ok((message.payload == string_payload(expected)).then_some(request.id()))
+                })
+                .unwrap();
+            assert!(!request_id.is_unsolicited());
+        }
+    }
+
+    #[test]
+    fn test_send() {
+        let payload = r#"{"type":"PONG"}"#;
+        let namespace = crate::channels::heartbeat::CHANNEL_NAMESPACE;
+        let stream = MockTcpStream::new();
+        let message_manager = MessageManager::new(stream.clone());
+        let expected_message = cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
+            source_id: Some(DEFAULT_SENDER_ID.to_string()),
+            destination_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            namespace: Some(namespace.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(payload.to_string()),
+            payload_binary: None,
+            continued: None,
+            remaining_length: None,
+            special_fields: Default::default(),
+        };
+
+        message_manager
+            .send(CastMessage {
+                namespace: namespace.to_string(),
+                source: DEFAULT_SENDER_ID.to_string(),
+                destination: DEFAULT_RECEIVER_ID.to_string(),
+                payload: CastMessagePayload::String(payload.to_string()),
+            })
+            .unwrap();
+
+        let tcp_message = stream
+            .received_message(0)
+            .expect("expected a message to have been received");
+        assert_eq!(expected_message, tcp_message.message());
+        // Length prefix and the message itself are written at once.
+        assert_eq!(1, stream.write_count());
+    }
+
+    #[test]
+    fn test_binary_payload_round_trip() {
+        let namespace = "urn:x-cast:com.example.binary";
+        let payload = vec![0x08, 0x96, 0x01, 0xff];
+        let mut stream = MockTcpStream::new();
+        stream.add_message(cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
+            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(namespace.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::BINARY)),
+            payload_binary: Some(payload.clone()),
+            ..Default::default()
+        });
+        let message_manager = MessageManager::new(stream.clone());
+
+        let message = message_manager.receive().unwrap();
+        assert_eq!(CastMessagePayload::Binary(payload.clone()), message.payload);
+
+        message_manager
+            .send(CastMessage {
+                namespace: message.namespace,
+                source: message.destination,
+                destination: message.source,
+                payload: message.payload,
+            })
+            .unwrap();
+
+        let sent_message = stream
+            .received_message(0)
+            .expect("expected a message to have been sent")
+            .message();
+        assert_eq!(PayloadType::BINARY, sent_message.payload_type());
+        assert_eq!(payload.as_slice(), sent_message.payload_binary());
+    }
+
+    #[test]
+    fn test_binary_payload_to_json() {
+        let payload = CastMessagePayload::from(br#"{"type":"PING"}"#.to_vec());
+
+        assert_eq!(None, payload.as_str());
+        assert_eq!("PING", payload.to_json().unwrap()["type"]);
+    }
+
+    #[test]
+    fn test_generate_request_id_wraps_around() {
+        let message_manager = MessageManager::new(MockTcpStream::new());
+        assert_eq!(RequestId::new(1), message_manager.generate_request_id());
+
+        *message_manager.request_counter.borrow_mut() = NonZeroU32::MAX;
+
+        assert_eq!(
+            RequestId::new(u32::MAX),
+            message_manager.generate_request_id()
+        );
+        let request_id = message_manager.generate_request_id();
+        assert_eq!(RequestId::new(1), request_id);
+        assert!(!request_id.is_unsolicited());
+    }
+
+    #[test]
+    fn test_send_splits_large_message() {
+        let stream = MockTcpStream::new();
+        let message_manager = MessageManager::new(stream.clone());
+        // Multi-byte characters make sure chunks are only split at the character boundaries.
+        let payload = "ü".repeat(MAX_FRAME_SIZE);
+
+        message_manager
+            .send(CastMessage {
+                namespace: crate::channels::media::CHANNEL_NAMESPACE.to_string(),
+                source: DEFAULT_SENDER_ID.to_string(),
+                destination: DEFAULT_RECEIVER_ID.to_string(),
+                payload: string_payload(&payload),
+            })
+            .unwrap();
+
+        let mut chunks = vec![];
+        while let Some(tcp_message) = stream.received_message(chunks.len()) {
+            chunks.push(tcp_message.message());
+        }
+
+        assert_eq!(3, chunks.len());
+        assert_eq!(1, stream.write_count());
+        let mut remaining_length = payload.len();
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert!(chunk.compute_size() <= MAX_FRAME_SIZE as u64);
+            assert_eq!(index + 1 < chunks.len(), chunk.continued());
+            remaining_length -= chunk.payload_utf8().len();
+            assert_eq!(remaining_length as u32, chunk.remaining_length());
+        }
+        assert_eq!(
+            payload,
+            chunks
+                .iter()
+                .map(|chunk| chunk.payload_utf8())
+                .collect::<String>()
+        );
+    }
+
+    #[test]
+    fn test_receive_assembles_chunked_message() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(cast_channel::CastMessage {
+            continued: Some(true),
+            remaining_length: Some(7),
+            ..heartbeat_message(r#"{"type":"#)
+        });
+        // Messages from the other sources may arrive in between the chunks.
+        stream.add_message(cast_channel::CastMessage {
+            source_id: Some("web-1".to_string()),
+            ..heartbeat_message(r#"{"type":"PONG"}"#)
+        });
+        stream.add_message(cast_channel::CastMessage {
+            continued: Some(false),
+            remaining_length: Some(0),
+            ..heartbeat_message(r#""PING"}"#)
+        });
+        let message_manager = MessageManager::new(stream);
+
+        let message = message_manager.receive().unwrap();
+        assert_eq!("web-1", message.source);
+        assert_eq!(string_payload(r#"{"type":"PONG"}"#), message.payload);
+
+        let message = message_manager.receive().unwrap();
+        assert_eq!(DEFAULT_RECEIVER_ID, message.source);
+        assert_eq!(string_payload(r#"{"type":"PING"}"#), message.payload);
+    }
+
+    #[test]
+    fn test_receive_limits_chunked_message_length() {
+        let chunk = |payload: &str, continued: bool| cast_channel::CastMessage {
+            continued: Some(continued),
+            ..heartbeat_message(payload)
+        };
+        let mut stream = MockTcpStream::new();
+        stream.add_message(chunk(r#"{"type":"#, true));
+        stream.add_message(chunk(r#""PING","#, true));
+        stream.add_message(chunk(r#""x":1}"#, false));
+        stream.add_message(heartbeat_message(r#"{"type":"PONG"}"#));
+        let message_manager = MessageManager::new(stream);
+        message_manager.set_max_message_length(12);
+
+        match message_manager.receive() {
+            Err(Error::Framing(FramingError::MessageTooLarge { length, max_length })) => {
+                assert_eq!((15, 12), (length, max_length));
+            }
+            result => panic!("Unexpected result: {result:?}"),
+        }
+        assert_eq!(ConnectionState::Degraded, message_manager.state());
+
+        // The rest of the oversized message is discarded, the following message isn't affected.
+        let message = message_manager.receive().unwrap();
+        assert_eq!(string_payload(r#"{"type":"PONG"}"#), message.payload);
+    }
+
+    #[test]
+    fn test_send_reconnects_closed_connection() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some("web-1".to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(connection::CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(r#"{"type":"CLOSE"}"#.to_string()),
+            ..Default::default()
+        });
+        let message_manager = MessageManager::new(stream.clone());
+        let connect_message = CastMessage {
+            namespace: connection::CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_SENDER_ID.to_string(),
+            destination: "web-1".to_string(),
+            payload: string_payload(r#"{"type":"CONNECT"}"#),
+        };
+        message_manager.track_connection(connect_message.clone());
+        assert_eq!(
+            vec!["web-1".to_string()],
+            message_manager.connected_destinations(DEFAULT_SENDER_ID)
+        );
+
+        message_manager
+            .receive()
+            .expect("expected to receive a message");
+        assert!(message_manager
+            .connected_destinations(DEFAULT_SENDER_ID)
+            .is_empty());
+
+        message_manager
+            .send(CastMessage {
+                namespace: crate::channels::media::CHANNEL_NAMESPACE.to_string(),
+                source: DEFAULT_SENDER_ID.to_string(),
+                destination: "web-1".to_string(),
+                payload: string_payload(r#"{"type":"GET_STATUS"}"#),
+            })
+            .unwrap();
+
+        let reconnect_message = stream
+            .received_message(0)
+            .expect("expected a message to have been received")
+            .message();
+        assert_eq!(connection::CHANNEL_NAMESPACE, reconnect_message.namespace());
+        assert_eq!(r#"{"type":"CONNECT"}"#, reconnect_message.payload_utf8());
+        let media_message = stream
+            .received_message(1)
+            .expect("expected a message to have been received")
+            .message();
+        assert_eq!(
+            crate::channels::media::CHANNEL_NAMESPACE,
+            media_message.namespace()
+        );
+        assert_eq!(
+            vec!["web-1".to_string()],
+            message_manager.connected_destinations(DEFAULT_SENDER_ID)
+        );
+    }
+
+    #[test]
+    fn test_send_fails_on_connection_closed_with_reason() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_0)),
+            source_id: Some("web-1".to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(connection::CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(r#"{"type":"CLOSE","reasonCode":4,"reason":"stopped"}"#.to_string()),
+            ..Default::default()
+        });
+        let message_manager = MessageManager::new(stream.clone());
+        let connect_message = CastMessage {
+            namespace: connection::CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_SENDER_ID.to_string(),
+            destination: "web-1".to_string(),
+            payload: string_payload(r#"{"type":"CONNECT"}"#),
+        };
+        let media_message = CastMessage {
+            namespace: crate::channels::media::CHANNEL_NAMESPACE.to_string(),
+            source: DEFAULT_SENDER_ID.to_string(),
+            destination: "web-1".to_string(),
+            payload: string_payload(r#"{"type":"GET_STATUS"}"#),
+        };
+        message_manager.track_connection(connect_message.clone());
+        message_manager.receive().unwrap();
+
+        match message_manager.send(media_message.clone()) {
+            Err(Error::ConnectionClosed {
+                destination,
+                reason,
+            }) => {
+                assert_eq!("web-1", destination);
+                assert_eq!(Some(4), reason.code);
+                assert_eq!(Some("stopped"), reason.description.as_deref());
+            }
+            result => panic!("unexpected result {result:?}"),
+        }
+        assert!(stream.received_message(0).is_none());
+
+        // Explicit connect reopens the connection.
+        message_manager.track_connection(connect_message);
+        message_manager.send(media_message).unwrap();
+        assert!(stream.received_message(0).is_some());
+    }
+
+    #[test]
+    fn test_close_only_once() {
+        let message_manager = MessageManager::new(MockTcpStream::new());
+        let close_count = Lock::new(0);
+
+        for _ in 0..2 {
+            message_manager
+                .close(|_| {
+                    *close_count.borrow_mut() += 1;
+                    Ok(())
+                })
+                .unwrap();
+        }
+
+        assert_eq!(1, *close_count.borrow_mut());
+    }
+
+    #[test]
+    fn test_receive_timeout() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(heartbeat_message(r#"{"type":"PING"}"#));
+        let message_manager = MessageManager::new(stream);
+
+        let message = message_manager
+            .receive_timeout(Duration::from_millis(10))
+            .expect("expected to receive a message");
+        assert_eq!(
+            Some(string_payload(r#"{"type":"PING"}"#)),
+            message.map(|message| message.payload)
+        );
+
+        let message = message_manager
+            .try_receive()
+            .expect("expected try_receive not to fail when there is no message");
+        assert_eq!(None, message);
+    }
+
+    #[test]
+    fn test_receive_find_map_cancelled() {
+        let message_manager = MessageManager::new_cancellable(MockTcpStream::new());
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let result = message_manager.with_cancellation(&token, || {
+            message_manager.receive_find_map(|_| Ok(Some(())))
+        });
+
+        assert!(matches!(result, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_receive_find_map_deadline() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(heartbeat_message(r#"{"type":"PING"}"#));
+        let message_manager = MessageManager::new_cancellable(stream);
+        let token = CancellationToken::with_timeout(Duration::from_millis(20));
+
+        let result = message_manager.with_cancellation(&token, || {
+            message_manager.receive_find_map(|_| Ok(None::<()>))
+        });
+
+        assert!(matches!(result, Err(Error::Timeout(_))));
+        // Message that has been received before the deadline is not lost.
+        assert_eq!(
+            string_payload(r#"{"type":"PING"}"#),
+            message_manager.receive().unwrap().payload
+        );
+    }
+
+    #[test]
+    fn test_write_retries_transient_errors() {
+        /// Stream that accepts at most 4 bytes per write and fails every other write.
+        #[derive(Default)]
+        struct FlakyStream {
+            written: Vec<u8>,
+            writes: usize,
+        }
+
+        impl Read for FlakyStream {
+            fn read(&mut self, _: &mut [u8]) -> Result<usize, IoError> {
+                Ok(0)
+            }
+        }
+
+        impl Write for FlakyStream {
+            fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+                self.writes += 1;
+                if self.writes % 2 == 1 {
+                    return Err(std::io::ErrorKind::WouldBlock.into());
+                }
+
+                let length = buf.len().min(4);
+                self.written.extend_from_slice(&buf[..length]);
+                Ok(length)
+            }
+
+            fn flush(&mut self) -> Result<(), IoError> {
+                Ok(())
+            }
+        }
+
+        let message_manager = MessageManager::new(FlakyStream::default());
+        message_manager.set_retry_policy(RetryPolicy {
+            initial_backoff: Duration::ZERO,
+            ..RetryPolicy::default()
+        });
+
+        message_manager
+            .write_buffer_to_stream(b"0123456789", MessagePriority::Normal)
+            .unwrap();
+        assert_eq!(
+            b"0123456789",
+            message_manager.stream.borrow_mut().written.as_slice()
+        );
+
+        // Write that doesn't succeed in time fails, nothing has been written so nothing waits.
+        message_manager.set_retry_policy(RetryPolicy::none());
+        assert!(matches!(
+            message_manager.write_buffer_to_stream(b"0123", MessagePriority::Normal),
+            Err(Error::Timeout(_))
+        ));
+        assert_eq!(0, message_manager.queued_messages());
+    }
+
+    #[test]
+    fn test_write_keeps_rest_of_partially_written_message() {
+        /// Stream that accepts at most 4 bytes until it's blocked.
+        #[derive(Default)]
+        struct ShortStream {
+            written: Vec<u8>,
+        }
+
+        impl Read for ShortStream {
+            fn read(&mut self, _: &mut [u8]) -> Result<usize, IoError> {
+                Ok(0)
+            }
+        }
+
+        impl Write for ShortStream {
+            fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+                if self.written.len() == 4 {
+                    return Err(std::io::ErrorKind::WouldBlock.into());
+                }
+
+                let length = buf.len().min(4);
+                self.written.extend_from_slice(&buf[..length]);
+                Ok(length)
+            }
+
+            fn flush(&mut self) -> Result<(), IoError> {
+                Ok(())
+            }
+        }
+
+        let message_manager = MessageManager::new(ShortStream::default());
+        message_manager.set_retry_policy(RetryPolicy::none());
+
+        assert!(matches!(
+            message_manager.write_buffer_to_stream(b"0123456789", MessagePriority::Normal),
+            Err(Error::Timeout(_))
+        ));
+        assert_eq!(1, message_manager.queued_messages());
+
+        // The rest of the message goes out before the next one once the stream accepts data.
+        message_manager.stream.borrow_mut().written.clear();
+        message_manager
+            .write_buffer_to_stream(b"ab", MessagePriority::Normal)
+            .unwrap_err();
+        assert_eq!(
+            b"4567",
+            message_manager.stream.borrow_mut().written.as_slice()
+        );
+    }
+
+    /// Write half of the stream that doesn't accept any data while it's blocked.
+    #[derive(Clone, Default)]
+    struct BlockedWriter(Arc<Mutex<BlockedWrites>>);
+
+    #[derive(Default)]
+    struct BlockedWrites {
+        written: Vec<u8>,
+        blocked: bool,
+        attempts: usize,
+        error: Option<ErrorKind>,
+    }
+
+    impl Write for BlockedWriter {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+            let mut writes = self.0.lock().unwrap();
+            writes.attempts += 1;
+            if let Some(kind) = writes.error {
+                return Err(kind.into());
+            }
+            if writes.blocked {
+                return Err(std::io::ErrorKind::WouldBlock.into());
+            }
+
+            writes.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), IoError> {
+            Ok(())
+        }
+    }
+
+    impl BlockedWriter {
+        fn blocked() -> BlockedWriter {
+            let writer = BlockedWriter::default();
+            writer.set_blocked(true);
+            writer
+        }
+
+        fn set_blocked(&self, blocked: bool) {
+            self.0.lock().unwrap().blocked = blocked;
+        }
+
+        /// Creates `MessageManager` which writer thread writes to this writer.
+        fn message_manager(&self) -> MessageManager<MockTcpStream> {
+            MessageManager::new_split(MockTcpStream::new(), Box::new(self.clone())).unwrap()
+        }
+
+        /// Waits until the writer thread has tried to write the frame it has taken from the queue.
+        fn wait_for_attempt(&self) {
+            wait_for(|| self.0.lock().unwrap().attempts > 0);
+        }
+
+        /// Returns UTF-8 payloads of the frames written so far.
+        fn written_payloads(&self) -> Vec<String> {
+            let written = &self.0.lock().unwrap().written;
+            let mut payloads = vec![];
+            let mut position = 0;
+            while position < written.len() {
+                let length = utils::read_u32_from_buffer(&written[position..]).unwrap() as usize;
+                let raw_message =
+                    cast_channel::CastMessage::parse_from_bytes(&written[position + 4..][..length])
+                        .unwrap();
+                payloads.push(raw_message.payload_utf8().to_string());
+                position += 4 + length;
+            }
+
+            payloads
+        }
+
+        /// Waits until the writer thread has written the frames with the `payloads`.
+        fn wait_for_payloads(&self, payloads: &[&str]) {
+            wait_for(|| self.written_payloads() == payloads);
+        }
+    }
+
+    /// Waits until the `condition` is met, panics if it isn't met within a few seconds.
+    fn wait_for<F>(condition: F)
+    where
+        F: Fn() -> bool,
+    {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(
+                Instant::now() < deadline,
+                "condition hasn't been met in time"
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn queued_message(namespace: &str, payload: &str) -> CastMessage {
+        CastMessage {
+            namespace: namespace.to_string(),
+            source: DEFAULT_SENDER_ID.to_string(),
+            destination: DEFAULT_RECEIVER_ID.to_string(),
+            payload: string_payload(payload),
+        }
+    }
+
+    #[test]
+    fn test_writer_thread_writes_queued_messages() {
+        let media = |payload| queued_message(crate::channels::media::CHANNEL_NAMESPACE, payload);
+        let writer = BlockedWriter::blocked();
+        let message_manager = writer.message_manager();
+
+        message_manager.send(media("1")).unwrap();
+        message_manager.send(media("2")).unwrap();
+        writer.wait_for_attempt();
+        assert_eq!(2, message_manager.queued_messages());
+
+        // Queued messages go out as soon as the stream accepts data again, nothing else has to be
+        // sent or received for that.
+        writer.set_blocked(false);
+        writer.wait_for_payloads(&["1", "2"]);
+        wait_for(|| message_manager.queued_messages() == 0);
+
+        // Failure of the writer thread is reported by the subsequent sends.
+        writer.0.lock().unwrap().error = Some(ErrorKind::BrokenPipe);
+        message_manager.send(media("3")).unwrap();
+        wait_for(|| message_manager.queued_messages() == 0);
+        assert!(matches!(
+            message_manager.send(media("4")),
+            Err(Error::Io(error)) if error.kind() == ErrorKind::BrokenPipe
+        ));
+        assert_eq!(ConnectionState::Closed, message_manager.state());
+    }
+
+    #[test]
+    fn test_send_queue_backpressure() {
+        let ping = |payload| queued_message(heartbeat::CHANNEL_NAMESPACE, payload);
+        let media = |payload| queued_message(crate::channels::media::CHANNEL_NAMESPACE, payload);
+
+        let writer = BlockedWriter::blocked();
+        let message_manager = writer.message_manager();
+        message_manager.set_send_queue(SendQueueOptions {
+            capacity: 2,
+            backpressure: BackpressurePolicy::DropOldest,
+        });
+
+        // The first message is taken by the writer thread, it doesn't occupy the queue.
+        message_manager.send(media("0")).unwrap();
+        writer.wait_for_attempt();
+
+        message_manager.send(ping("1")).unwrap();
+        message_manager.send(media("2")).unwrap();
+        // The oldest heartbeat makes room for the new one.
+        message_manager.send(ping("3")).unwrap();
+        assert_eq!(3, message_manager.queued_messages());
+
+        message_manager.set_send_queue(SendQueueOptions {
+            capacity: 2,
+            backpressure: BackpressurePolicy::Error,
+        });
+        assert!(matches!(
+            message_manager.send(media("4")),
+            Err(Error::QueueFull(2))
+        ));
+
+        // Blocked send gives up once the cancellation token deadline is reached.
+        message_manager.set_send_queue(SendQueueOptions {
+            capacity: 2,
+            backpressure: BackpressurePolicy::Block,
+        });
+        let token = CancellationToken::with_timeout(Duration::from_millis(20));
+        assert!(matches!(
+            message_manager.with_cancellation(&token, || message_manager.send(media("4"))),
+            Err(Error::Timeout(_))
+        ));
+
+        // Queued messages go out once the stream accepts data again, heartbeat goes before any
+        // other queued message.
+        writer.set_blocked(false);
+        message_manager.send(media("5")).unwrap();
+        writer.wait_for_payloads(&["0", "3", "2", "5"]);
+    }
+
+    #[test]
+    fn test_send_queue_priorities() {
+        let writer = BlockedWriter::blocked();
+        let message_manager = writer.message_manager();
+
+        let close = r#"{"type":"CLOSE"}"#;
+        let media_namespace = crate::channels::media::CHANNEL_NAMESPACE;
+        message_manager
+            .send(queued_message(media_namespace, "first"))
+            .unwrap();
+        writer.wait_for_attempt();
+        message_manager
+            .send(queued_message(media_namespace, "media"))
+            .unwrap();
+        message_manager
+            .send(queued_message(connection::CHANNEL_NAMESPACE, close))
+            .unwrap();
+        message_manager
+            .send(queued_message(heartbeat::CHANNEL_NAMESPACE, "ping"))
+            .unwrap();
+        assert_eq!(4, message_manager.queued_messages());
+
+        writer.set_blocked(false);
+        writer.wait_for_payloads(&["first", "ping", close, "media"]);
+    }
+
+    #[test]
+    fn test_send_queue_keeps_order_of_messages() {
+        let writer = BlockedWriter::blocked();
+        let message_manager = writer.message_manager();
+
+        // Small request sent after the large one must not overtake it, e.g. `PAUSE` after
+        // `QUEUE_LOAD` of the same media session.
+        let queue_load = "q".repeat(32 * 1024);
+        let media_namespace = crate::channels::media::CHANNEL_NAMESPACE;
+        for payload in [queue_load.as_str(), "pause", "play"] {
+            message_manager
+                .send(queued_message(media_namespace, payload))
+                .unwrap();
+        }
+        message_manager
+            .send(queued_message(
+                connection::CHANNEL_NAMESPACE,
+                r#"{"type":"CONNECT"}"#,
+            ))
+            .unwrap();
+
+        writer.set_blocked(false);
+        writer.wait_for_payloads(&[
+            queue_load.as_str(),
+            "pause",
+            "play",
+            r#"{"type":"CONNECT"}"#,
+        ]);
+    }
+
+    #[test]
+    fn test_read_frames() {
+        /// Stream that returns at most 3 bytes per read.
+        struct TrickleStream(std::io::Cursor<Vec<u8>>);
+
+        impl Read for TrickleStream {
+            fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+                let length = buf.len().min(3);
+                self.0.read(&mut buf[..length])
+            }
+        }
+
+        impl Write for TrickleStream {
+            fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> Result<(), IoError> {
+                Ok(())
+            }
+        }
+
+        fn frame(length: u32, body: &[u8]) -> MessageManager<TrickleStream> {
+            let mut bytes = length.to_be_bytes().to_vec();
+            bytes.extend_from_slice(body);
+            MessageManager::new(TrickleStream(std::io::Cursor::new(bytes)))
+        }
+
+        let body = heartbeat_message(r#"{"type":"PING"}"#)
+            .write_to_bytes()
+            .unwrap();
+
+        let message_manager = frame(body.len() as u32, &body);
+        assert_eq!(
+            string_payload(r#"{"type":"PING"}"#),
+            message_manager.receive().unwrap().payload
+        );
+
+        assert!(matches!(
+            frame(0, &[]).receive(),
+            Err(Error::Framing(FramingError::Empty))
+        ));
+
+        let message_manager = frame(body.len() as u32, &body);
+        message_manager.set_max_frame_length(8);
+        assert!(matches!(
+            message_manager.receive(),
+            Err(Error::Framing(FramingError::TooLarge { max_length: 8, .. }))
+        ));
+
+        let message_manager = frame(body.len() as u32, &body[..5]);
+        match message_manager.receive() {
+            Err(Error::Framing(FramingError::Truncated { read, length })) => {
+                assert_eq!((5, body.len()), (read, length));
+            }
+            result => panic!("Unexpected result: {result:?}"),
+        }
+        assert_eq!(ConnectionState::Closed, message_manager.state());
+    }
+
+    #[test]
+    fn test_connection_state() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(cast_channel::CastMessage {
+            namespace: Some(connection::CHANNEL_NAMESPACE.to_string()),
+            ..heartbeat_message(r#"{"type":"CLOSE"}"#)
+        });
+        let message_manager = MessageManager::new(stream);
+        let states = Arc::new(std::sync::Mutex::new(vec![]));
+        let listener_states = Arc::clone(&states);
+        message_manager.on_state_change(move |state| listener_states.lock().unwrap().push(state));
+        assert_eq!(ConnectionState::Connecting, message_manager.state());
+
+        message_manager
+            .send(CastMessage {
+                namespace: crate::channels::heartbeat::CHANNEL_NAMESPACE.to_string(),
+                source: DEFAULT_SENDER_ID.to_string(),
+                destination: DEFAULT_RECEIVER_ID.to_string(),
+                payload: string_payload(r#"{"type":"PING"}"#),
+            })
+            .unwrap();
+        assert_eq!(ConnectionState::Connected, message_manager.state());
+
+        // Receiver has closed the platform connection.
+        message_manager.receive().unwrap();
+        assert_eq!(ConnectionState::Degraded, message_manager.state());
+
+        // There are no more messages, so the stream is at EOF.
+        assert!(message_manager.receive().is_err());
+        assert_eq!(ConnectionState::Closed, message_manager.state());
+
+        assert_eq!(
+            vec![
+                ConnectionState::Connected,
+                ConnectionState::Degraded,
+                ConnectionState::Closed
+            ],
+            *states.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_connection_state_with_custom_receiver_id() {
+        let close_message = |source: &str| cast_channel::CastMessage {
+            namespace: Some(connection::CHANNEL_NAMESPACE.to_string()),
+            source_id: Some(source.to_string()),
+            ..heartbeat_message(r#"{"type":"CLOSE"}"#)
+        };
+        let mut stream = MockTcpStream::new();
+        stream.add_message(heartbeat_message(r#"{"type":"PONG"}"#));
+        stream.add_message(close_message(DEFAULT_RECEIVER_ID));
+        stream.add_message(close_message("receiver-7"));
+        let message_manager = MessageManager::new(stream);
+        message_manager.set_receiver_id("receiver-7");
+
+        message_manager.receive().unwrap();
+        assert_eq!(ConnectionState::Connected, message_manager.state());
+
+        // Closed connection with an unrelated receiver doesn't affect the connection state.
+        message_manager.receive().unwrap();
+        assert_eq!(ConnectionState::Connected, message_manager.state());
+
+        message_manager.receive().unwrap();
+        assert_eq!(ConnectionState::Degraded, message_manager.state());
+    }
+
+    #[test]
+    fn test_metrics_and_message_observer() {
+        let heartbeat_namespace = crate::channels::heartbeat::CHANNEL_NAMESPACE;
+        let mut stream = MockTcpStream::new();
+        stream.add_message(heartbeat_message(r#"{"type":"PONG"}"#));
+        let message_manager = MessageManager::new(stream);
+        let observed = Arc::new(std::sync::Mutex::new(vec![]));
+        let observer_messages = Arc::clone(&observed);
+        message_manager.set_message_observer(move |message, direction| {
+            observer_messages
+                .lock()
+                .unwrap()
+                .push((message.namespace.clone(), direction))
+        });
+
+        message_manager
+            .send(CastMessage {
+                namespace: heartbeat_namespace.to_string(),
+                source: DEFAULT_SENDER_ID.to_string(),
+                destination: DEFAULT_RECEIVER_ID.to_string(),
+                payload: string_payload(r#"{"type":"PING"}"#),
+            })
+            .unwrap();
+        message_manager.receive().unwrap();
+        message_manager.record_parse_failure();
+
+        let metrics = message_manager.metrics();
+        let traffic = metrics.namespaces[heartbeat_namespace];
+        assert_eq!(1, traffic.messages_sent);
+        assert_eq!(1, traffic.messages_received);
+        assert!(traffic.bytes_sent > 4);
+        assert!(traffic.bytes_received > 4);
+        assert_eq!(traffic, metrics.total());
+        assert_eq!(1, metrics.parse_failures);
+        assert_eq!(
+            vec![
+                (heartbeat_namespace.to_string(), Direction::Outgoing),
+                (heartbeat_namespace.to_string(), Direction::Incoming)
+            ],
+            *observed.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_wire_log() {
+        let mut stream = MockTcpStream::new();
+        stream.add_message(heartbeat_message(r#"{"type":"PONG"}"#));
+        let message_manager = MessageManager::new(stream);
+        let frames = Arc::new(std::sync::Mutex::new(vec![]));
+        let sink_frames = Arc::clone(&frames);
+        message_manager
+            .set_wire_log(move |frame: &WireFrame| sink_frames.lock().unwrap().push(frame.clone()));
+
+        message_manager
+            .send(CastMessage {
+                namespace: crate::channels::media::CHANNEL_NAMESPACE.to_string(),
+                source: DEFAULT_SENDER_ID.to_string(),
+                destination: "web-1".to_string(),
+                payload: string_payload(r#"{"type":"LOAD","credentials":"secret"}"#),
+            })
+            .unwrap();
+        message_manager.receive().unwrap();
+        message_manager.clear_wire_log();
+        message_manager
+            .send(CastMessage {
+                namespace: crate::channels::heartbeat::CHANNEL_NAMESPACE.to_string(),
+                source: DEFAULT_SENDER_ID.to_string(),
+                destination: DEFAULT_RECEIVER_ID.to_string(),
+                payload: string_payload(r#"{"type":"PING"}"#),
+            })
+            .unwrap();
+
+        let frames = frames.lock().unwrap();
+        assert_eq!(2, frames.len());
+        assert_eq!(Direction::Outgoing, frames[0].direction);
+        assert_eq!("web-1", frames[0].destination);
+        assert_eq!(
+            crate::wire_log::REDACTED,
+            frames[0].payload.to_json().unwrap()["credentials"]
+        );
+        assert_eq!(Direction::Incoming, frames[1].direction);
+        assert_eq!(string_payload(r#"{"type":"PONG"}"#), frames[1].payload);
+    }
+
+    #[test]
+    fn test_wire_log_redacts_chunked_message() {
+        let message_manager = MessageManager::new(MockTcpStream::new());
+        let frames = Arc::new(std::sync::Mutex::new(vec![]));
+        let sink_frames = Arc::clone(&frames);
+        message_manager
+            .set_wire_log(move |frame: &WireFrame| sink_frames.lock().unwrap().push(frame.clone()));
+        let payload = format!(
+            r#"{{"type":"LOAD","credentials":"secret","media":{{"contentId":"{}"}}}}"#,
+            "x".repeat(MAX_FRAME_SIZE)
+        );
+
+        message_manager
+            .send(CastMessage {
+                namespace: crate::channels::media::CHANNEL_NAMESPACE.to_string(),
+                source: DEFAULT_SENDER_ID.to_string(),
+                destination: "web-1".to_string(),
+                payload: string_payload(&payload),
+            })
+            .unwrap();
+
+        let frames = frames.lock().unwrap();
+        assert_eq!(
+            vec![true, false],
+            frames
+                .iter()
+                .map(|frame| frame.continued)
+                .collect::<Vec<_>>()
+        );
+        for frame in frames.iter() {
+            assert_eq!(string_payload(crate::wire_log::REDACTED), frame.payload);
+        }
+    }
+
+    fn heartbeat_message(payload: &str) -> cast_channel::CastMessage {
+        cast_channel::CastMessage {
+            protocol_version: Some(EnumOrUnknown::new(ProtocolVersion::CASTV2_1_2)),
+            source_id: Some(DEFAULT_RECEIVER_ID.to_string()),
+            destination_id: Some(DEFAULT_SENDER_ID.to_string()),
+            namespace: Some(crate::channels::heartbeat::CHANNEL_NAMESPACE.to_string()),
+            payload_type: Some(EnumOrUnknown::new(PayloadType::STRING)),
+            payload_utf8: Some(payload.to_string()),
+            payload_binary: None,
+            continued: None,
+            remaining_length: None,
+            special_fields: Default::default(),
+        }
+    }
+
+    fn string_payload(payload: &str) -> CastMessagePayload {
+        CastMessagePayload::String(payload.to_string())
     }
 }
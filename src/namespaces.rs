@@ -0,0 +1,90 @@
+//! Namespaces of the messages exchanged with the cast devices: the platform (`tp.*`), built-in
+//! receiver and application namespaces the channels use, so that custom channels and the code
+//! that inspects raw messages (e.g. `ChannelMessage::Raw` or message observer) don't have to
+//! repeat them.
+//!
+//! # Examples
+//!
+//! ```
+//! use rust_cast::namespaces;
+//!
+//! assert_eq!("urn:x-cast:com.google.cast.media", namespaces::MEDIA);
+//! assert!(namespaces::is_cast_namespace("urn:x-cast:com.example.custom"));
+//! assert!(!namespaces::is_cast_namespace("com.example.custom"));
+//! ```
+
+/// Prefix every namespace of the cast messages starts with.
+pub const PREFIX: &str = "urn:x-cast:";
+
+/// Virtual connections with the receiver and applications, see `ConnectionChannel`.
+pub const CONNECTION: &str = "urn:x-cast:com.google.cast.tp.connection";
+/// Keep-alive pings and pongs, see `HeartbeatChannel`.
+pub const HEARTBEAT: &str = "urn:x-cast:com.google.cast.tp.heartbeat";
+/// Authentication of the device with its certificate chain.
+pub const DEVICE_AUTH: &str = "urn:x-cast:com.google.cast.tp.deviceauth";
+/// Transport level errors, see `ErrorChannel`.
+pub const ERROR: &str = "urn:x-cast:com.google.cast.tp.error";
+/// Receiving platform: applications and volume, see `ReceiverChannel`.
+pub const RECEIVER: &str = "urn:x-cast:com.google.cast.receiver";
+/// Media playback, see `MediaChannel`.
+pub const MEDIA: &str = "urn:x-cast:com.google.cast.media";
+/// Members of the speaker groups, see `MultizoneChannel`.
+pub const MULTIZONE: &str = "urn:x-cast:com.google.cast.multizone";
+/// Settings of the device, see `SetupChannel`.
+pub const SETUP: &str = "urn:x-cast:com.google.cast.setup";
+/// Screen mirroring signaling, see `MirroringChannel`.
+pub const WEBRTC: &str = "urn:x-cast:com.google.cast.webrtc";
+/// Media remoting signaling, see `MirroringChannel`.
+pub const REMOTING: &str = "urn:x-cast:com.google.cast.remoting";
+/// Debug overlay of the custom receiver applications, see `DebugOverlayChannel`.
+pub const DEBUG_OVERLAY: &str = "urn:x-cast:com.google.cast.debugoverlay";
+/// Logs of the custom receiver applications, see `DebugOverlayChannel`.
+pub const DEBUG_LOGGER: &str = "urn:x-cast:com.google.cast.debuglogger";
+/// YouTube application, see `YouTubeChannel`.
+pub const YOUTUBE: &str = "urn:x-cast:com.google.youtube.mdx";
+
+/// All the namespaces above.
+pub const ALL: &[&str] = &[
+    CONNECTION,
+    HEARTBEAT,
+    DEVICE_AUTH,
+    ERROR,
+    RECEIVER,
+    MEDIA,
+    MULTIZONE,
+    SETUP,
+    WEBRTC,
+    REMOTING,
+    DEBUG_OVERLAY,
+    DEBUG_LOGGER,
+    YOUTUBE,
+];
+
+/// Determines whether the `namespace` is valid namespace of the cast messages, i.e. has `PREFIX`
+/// and a non-empty name after it.
+pub fn is_cast_namespace(namespace: &str) -> bool {
+    namespace
+        .strip_prefix(PREFIX)
+        .is_some_and(|name| !name.is_empty())
+}
+
+/// Determines whether the `namespace` is the namespace of the platform messages (e.g. connection
+/// or heartbeat) that aren't addressed to any application.
+pub fn is_platform_namespace(namespace: &str) -> bool {
+    namespace.starts_with("urn:x-cast:com.google.cast.tp.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_cast_namespace() {
+        assert!(ALL.iter().all(|namespace| is_cast_namespace(namespace)));
+        assert!(!is_cast_namespace(PREFIX));
+        assert!(!is_cast_namespace("urn:x-castcom.google.cast.media"));
+
+        assert!(is_platform_namespace(HEARTBEAT));
+        assert!(!is_platform_namespace(MEDIA));
+    }
+}
@@ -0,0 +1,125 @@
+//! Alternative TLS backend (requires `native-tls` feature): the connection is established with
+//! the platform TLS library (OpenSSL on Linux, Security.framework on macOS, SChannel on Windows)
+//! instead of rustls, see `CastDevice::connect_native_tls`.
+
+use std::{
+    io::{Error as IoError, ErrorKind},
+    net::{Shutdown, TcpStream},
+    time::Duration,
+};
+
+use native_tls::{Certificate, HandshakeError, TlsConnector};
+use rustls::pki_types::CertificateDer;
+
+use crate::{
+    errors::Error,
+    message_manager::{socket_shutdown_handle, CastStream, ReadTimeout, ShutdownHandle},
+    proxy::ProxyTarget,
+    strip_ip_brackets, CastDevice, ConnectOptions,
+};
+
+/// TLS stream of the `native-tls` backend.
+pub type NativeTlsStream = native_tls::TlsStream<TcpStream>;
+
+impl ReadTimeout for NativeTlsStream {
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), IoError> {
+        ReadTimeout::set_read_timeout(self.get_mut(), timeout)
+    }
+}
+
+impl CastStream for NativeTlsStream {
+    fn peer_certificates(&self) -> Vec<CertificateDer<'static>> {
+        // The platform libraries expose only the leaf certificate of the chain.
+        self.peer_certificate()
+            .ok()
+            .flatten()
+            .and_then(|certificate| certificate.to_der().ok())
+            .map(|der| vec![CertificateDer::from(der)])
+            .unwrap_or_default()
+    }
+
+    fn shutdown(&mut self) -> Result<(), IoError> {
+        native_tls::TlsStream::shutdown(self)?;
+        self.get_ref().shutdown(Shutdown::Both)
+    }
+
+    fn shutdown_handle(&self) -> Option<ShutdownHandle> {
+        socket_shutdown_handle(self.get_ref())
+    }
+}
+
+impl CastDevice<NativeTlsStream> {
+    /// Connects to the cast device using host name and port with the `native-tls` backend.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rust_cast::{CastDevice, ConnectOptions};
+    ///
+    /// let device = CastDevice::connect_native_tls("192.168.1.2", 8009, ConnectOptions::default())?;
+    /// # Ok::<(), rust_cast::errors::Error>(())
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - Cast device host name.
+    /// * `port` - Cast device port number.
+    /// * `options` - Additional options for the connection. The certificate is verified with the
+    ///   platform root certificates and the `fallback_root_certificates`, `crypto_provider` is
+    ///   rustls specific and is ignored.
+    ///
+    /// # Errors
+    ///
+    /// This method may fail if connection to Cast device can't be established for some reason
+    /// (e.g. wrong host name or port).
+    ///
+    /// # Return value
+    ///
+    /// Instance of `CastDevice` that allows you to manage connection.
+    pub fn connect_native_tls<S>(
+        host: S,
+        port: u16,
+        options: ConnectOptions,
+    ) -> Result<CastDevice<NativeTlsStream>, Error>
+    where
+        S: Into<String>,
+    {
+        let host = host.into();
+        trace_span!("cast_device.connect", %host, port, verify_host = options.verify_host);
+        log::debug!("Establishing native TLS connection with cast device at {host}:{port}…");
+
+        let address = strip_ip_brackets(host.as_ref());
+        let tcp_stream = match &options.proxy {
+            Some(proxy) => proxy.connect(ProxyTarget::Host(address, port))?,
+            None => TcpStream::connect((address, port))?,
+        };
+        options.socket.apply(&tcp_stream)?;
+
+        let mut builder = TlsConnector::builder();
+        for certificate in &options.fallback_root_certificates {
+            builder.add_root_certificate(Certificate::from_der(certificate)?);
+        }
+        if !options.verify_host {
+            builder
+                .danger_accept_invalid_certs(true)
+                .danger_accept_invalid_hostnames(true);
+        }
+        let domain = options.server_name.as_deref().unwrap_or(address);
+        let stream = builder
+            .build()?
+            .connect(domain, tcp_stream)
+            .map_err(|error| match error {
+                HandshakeError::Failure(error) => Error::from(error),
+                // Socket is blocking, so the handshake gets interrupted only by the read timeout.
+                HandshakeError::WouldBlock(_) => Error::Io(ErrorKind::TimedOut.into()),
+            })?;
+
+        log::debug!("Connection with {host}:{port} successfully established.");
+
+        CastDevice::from_stream_with_ids(
+            stream,
+            options.sender_id.as_str(),
+            options.receiver_id.as_str(),
+        )
+    }
+}
@@ -0,0 +1,273 @@
+//! Policies that restrict what senders may do with the cast device (e.g. quiet hours of the
+//! home-automation daemon): the policy is consulted before launch and volume requests are sent,
+//! see `CastDevice::set_policy` and `CastManager::set_policy`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of minutes in a day.
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Operation the policy is consulted about before its request is sent.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    /// Application is about to be launched.
+    Launch {
+        /// Id of the application.
+        app_id: String,
+    },
+    /// Volume level is about to be set (including `volume_up`, `volume_down` and every step of
+    /// `fade_volume`). Muting and unmuting is always allowed.
+    SetVolume {
+        /// Requested volume level, from `0.0` to `1.0`.
+        level: f32,
+    },
+}
+
+/// Decision of the policy about the operation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Decision {
+    /// Operation is performed as requested.
+    Allow,
+    /// Operation fails with `Error::PolicyBlocked` carrying the reason, nothing is sent.
+    Block(String),
+    /// Volume level is lowered to at most this level, other operations are allowed.
+    CapVolume(f32),
+}
+
+/// Policy that decides whether the operation is allowed. Implemented by `QuietHours` and by the
+/// closures taking `&Operation` and returning `Decision`.
+pub trait OperationPolicy: Send + Sync {
+    /// Decides whether the `operation` is allowed.
+    fn check(&self, operation: &Operation) -> Decision;
+}
+
+impl<F> OperationPolicy for F
+where
+    F: Fn(&Operation) -> Decision + Send + Sync,
+{
+    fn check(&self, operation: &Operation) -> Decision {
+        self(operation)
+    }
+}
+
+/// Local time of the day.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimeOfDay {
+    /// Hour, from `0` to `23`.
+    pub hour: u8,
+    /// Minute, from `0` to `59`.
+    pub minute: u8,
+}
+
+impl TimeOfDay {
+    /// Creates time of the day, out of range values wrap around.
+    pub fn new(hour: u8, minute: u8) -> TimeOfDay {
+        TimeOfDay { hour, minute }
+    }
+
+    /// Returns number of minutes since midnight.
+    fn minutes(self) -> u16 {
+        (u16::from(self.hour) * 60 + u16::from(self.minute)) % MINUTES_PER_DAY
+    }
+}
+
+/// Time window of `QuietHours` along with what is restricted during it.
+#[derive(Clone, Debug, PartialEq)]
+struct QuietWindow {
+    start: TimeOfDay,
+    end: TimeOfDay,
+    /// Maximum volume level, `None` if launches and volume changes are blocked altogether.
+    max_volume: Option<f32>,
+}
+
+impl QuietWindow {
+    /// Determines whether the window contains the `time`, windows that start later than they end
+    /// span midnight.
+    fn contains(&self, time: TimeOfDay) -> bool {
+        let (start, end, time) = (self.start.minutes(), self.end.minutes(), time.minutes());
+
+        if start <= end {
+            (start..end).contains(&time)
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// Policy that restricts launches and volume changes during the configured daily time windows:
+/// windows added with `block` reject them, windows added with `cap_volume` only lower the volume
+/// level. Decisions of the overlapping windows are combined, the strictest one wins.
+///
+/// # Examples
+///
+/// ```
+/// use rust_cast::policy::{Decision, Operation, QuietHours, TimeOfDay};
+///
+/// let quiet_hours = QuietHours::new(60)
+///     .block(TimeOfDay::new(23, 0), TimeOfDay::new(7, 0))
+///     .cap_volume(TimeOfDay::new(20, 0), TimeOfDay::new(23, 0), 0.3);
+///
+/// let operation = Operation::SetVolume { level: 0.8 };
+/// assert_eq!(
+///     Decision::CapVolume(0.3),
+///     quiet_hours.decide_at(&operation, TimeOfDay::new(21, 30))
+/// );
+/// assert!(matches!(
+///     quiet_hours.decide_at(&operation, TimeOfDay::new(2, 0)),
+///     Decision::Block(_)
+/// ));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuietHours {
+    /// Offset of the local time from UTC in minutes.
+    utc_offset: i32,
+    windows: Vec<QuietWindow>,
+}
+
+impl QuietHours {
+    /// Creates policy without any windows, local time is UTC shifted by `utc_offset` minutes
+    /// (e.g. `60` for CET). Standard library has no time zone database, so the offset has to be
+    /// updated (by setting new policy) when daylight saving time starts or ends.
+    pub fn new(utc_offset: i32) -> QuietHours {
+        QuietHours {
+            utc_offset,
+            windows: vec![],
+        }
+    }
+
+    /// Adds window from `start` (inclusive) to `end` (exclusive) that blocks launches and volume
+    /// changes.
+    pub fn block(mut self, start: TimeOfDay, end: TimeOfDay) -> Self {
+        self.windows.push(QuietWindow {
+            start,
+            end,
+            max_volume: None,
+        });
+        self
+    }
+
+    /// Adds window from `start` (inclusive) to `end` (exclusive) that caps volume level at
+    /// `max_level`.
+    pub fn cap_volume(mut self, start: TimeOfDay, end: TimeOfDay, max_level: f32) -> Self {
+        self.windows.push(QuietWindow {
+            start,
+            end,
+            max_volume: Some(max_level),
+        });
+        self
+    }
+
+    /// Decides whether the `operation` is allowed at the local `time`.
+    pub fn decide_at(&self, operation: &Operation, time: TimeOfDay) -> Decision {
+        let mut decision = Decision::Allow;
+
+        for window in self.windows.iter().filter(|window| window.contains(time)) {
+            let reason = || {
+                format!(
+                    "quiet hours from {:02}:{:02} to {:02}:{:02}",
+                    window.start.hour, window.start.minute, window.end.hour, window.end.minute
+                )
+            };
+
+            decision = match (window.max_volume, operation, decision) {
+                (None, _, _) => return Decision::Block(reason()),
+                (Some(_), Operation::Launch { .. }, decision) => decision,
+                (Some(max_level), Operation::SetVolume { .. }, Decision::CapVolume(level)) => {
+                    Decision::CapVolume(level.min(max_level))
+                }
+                (Some(max_level), Operation::SetVolume { .. }, _) => Decision::CapVolume(max_level),
+            };
+        }
+
+        decision
+    }
+
+    /// Returns the current local time of the day.
+    fn now(&self) -> TimeOfDay {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        let minutes = (seconds / 60 + i64::from(self.utc_offset))
+            .rem_euclid(i64::from(MINUTES_PER_DAY)) as u16;
+
+        TimeOfDay::new((minutes / 60) as u8, (minutes % 60) as u8)
+    }
+}
+
+impl OperationPolicy for QuietHours {
+    fn check(&self, operation: &Operation) -> Decision {
+        self.decide_at(operation, self.now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        channels::receiver::CastDeviceApp, errors::Error, testing::FakeReceiver, CastDevice,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_quiet_hours() {
+        let quiet_hours = QuietHours::new(0)
+            .cap_volume(TimeOfDay::new(20, 0), TimeOfDay::new(23, 30), 0.5)
+            .cap_volume(TimeOfDay::new(22, 0), TimeOfDay::new(23, 30), 0.2)
+            .block(TimeOfDay::new(23, 30), TimeOfDay::new(6, 0));
+        let launch = Operation::Launch {
+            app_id: "CC1AD845".to_string(),
+        };
+        let volume = Operation::SetVolume { level: 0.8 };
+
+        assert_eq!(
+            Decision::Allow,
+            quiet_hours.decide_at(&volume, TimeOfDay::new(19, 59))
+        );
+        assert_eq!(
+            Decision::CapVolume(0.5),
+            quiet_hours.decide_at(&volume, TimeOfDay::new(20, 0))
+        );
+        assert_eq!(
+            Decision::CapVolume(0.2),
+            quiet_hours.decide_at(&volume, TimeOfDay::new(22, 15))
+        );
+        assert_eq!(
+            Decision::Allow,
+            quiet_hours.decide_at(&launch, TimeOfDay::new(22, 15))
+        );
+        assert_eq!(
+            Decision::Block("quiet hours from 23:30 to 06:00".to_string()),
+            quiet_hours.decide_at(&launch, TimeOfDay::new(0, 10))
+        );
+        assert_eq!(
+            Decision::Allow,
+            quiet_hours.decide_at(&launch, TimeOfDay::new(6, 0))
+        );
+    }
+
+    #[test]
+    fn test_device_policy() {
+        let fake_receiver = FakeReceiver::new();
+        let device = CastDevice::from_stream(fake_receiver.clone()).unwrap();
+        device.set_policy(Arc::new(|operation: &Operation| match operation {
+            Operation::Launch { .. } => Decision::Block("bedtime".to_string()),
+            Operation::SetVolume { .. } => Decision::CapVolume(0.25),
+        }));
+
+        assert!(matches!(
+            device.receiver.launch_app(&CastDeviceApp::DefaultMediaReceiver),
+            Err(Error::PolicyBlocked(reason)) if reason == "bedtime"
+        ));
+        assert!(fake_receiver.running_app().is_none());
+        assert_eq!(Some(0.25), device.receiver.set_volume(0.9).unwrap().level);
+        assert_eq!(Some(true), device.receiver.set_volume(true).unwrap().muted);
+
+        device.clear_policy();
+        assert!(device
+            .receiver
+            .launch_app(&CastDeviceApp::DefaultMediaReceiver)
+            .is_ok());
+    }
+}
@@ -0,0 +1,778 @@
+//! Pre-flight checks of the adaptive streaming media (HLS and DASH) before it's loaded: manifest is
+//! fetched and its variants are checked against the capabilities of the device, so that the issues
+//! are reported with actionable errors instead of the opaque detailed error codes of the receiver.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use rust_cast::{
+//!     capabilities::DeviceCapabilities, channels::media::Media, preflight::check_media,
+//! };
+//!
+//! let media = Media::builder("https://example.com/stream/master.m3u8", "application/x-mpegurl")
+//!     .build();
+//! if let Err(err) = check_media(&media, &DeviceCapabilities::CHROMECAST, Duration::from_secs(5)) {
+//!     eprintln!("Media can't be played: {err}");
+//! }
+//! ```
+
+use std::{fmt, time::Duration};
+
+use crate::{
+    capabilities::DeviceCapabilities,
+    channels::media::{guess_content_type, HlsSegmentFormat, HlsVideoSegmentFormat, Media},
+    errors::Error,
+    http::{self, Response},
+};
+
+/// Content types of the HLS playlists.
+const HLS_CONTENT_TYPES: [&str; 4] = [
+    "application/x-mpegurl",
+    "application/vnd.apple.mpegurl",
+    "audio/mpegurl",
+    "audio/x-mpegurl",
+];
+/// Content type of the DASH manifests.
+const DASH_CONTENT_TYPE: &str = "application/dash+xml";
+/// DASH descriptor scheme of the video transfer characteristics (ISO/IEC 23001-8).
+const TRANSFER_CHARACTERISTICS_SCHEME: &str = "urn:mpeg:mpegB:cicp:TransferCharacteristics";
+/// Allowed excess of the frame rate, so that e.g. `60000/1001` isn't compared strictly.
+const FRAME_RATE_TOLERANCE: f32 = 0.5;
+
+/// Format of the adaptive streaming manifest.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// HTTP Live Streaming playlist.
+    Hls,
+    /// MPEG-DASH media presentation description.
+    Dash,
+}
+
+impl fmt::Display for ManifestFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ManifestFormat::Hls => write!(f, "HLS"),
+            ManifestFormat::Dash => write!(f, "DASH"),
+        }
+    }
+}
+
+/// Variant of the stream the receiver can pick: HLS variant stream or DASH representation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Variant {
+    /// Codecs of the variant (RFC 6381), e.g. `avc1.64001f` or `mp4a.40.2`.
+    pub codecs: Vec<String>,
+    /// Width of the video in pixels, if known.
+    pub width: Option<u32>,
+    /// Height of the video in pixels, if known.
+    pub height: Option<u32>,
+    /// Frame rate of the video, if known.
+    pub frame_rate: Option<f32>,
+    /// Whether the video is HDR (PQ or HLG transfer, or Dolby Vision).
+    pub hdr: bool,
+    /// URI of the variant playlist relative to the manifest (HLS only).
+    pub uri: Option<String>,
+}
+
+impl Variant {
+    fn is_video(&self) -> bool {
+        self.height.is_some() || self.codecs.iter().any(|c| codec(c).is_video())
+    }
+
+    fn is_audio(&self) -> bool {
+        !self.is_video() && self.codecs.iter().any(|c| codec(c).is_audio())
+    }
+
+    /// Returns reasons why the device can't play the variant, empty if it can.
+    fn issues(&self, capabilities: &DeviceCapabilities) -> Vec<String> {
+        let mut issues = vec![];
+
+        for name in &self.codecs {
+            let supported = match codec(name) {
+                Codec::H264 | Codec::Vp8 => true,
+                Codec::Hevc => capabilities.hevc,
+                Codec::Vp9 => capabilities.vp9,
+                Codec::Av1 => capabilities.av1,
+                Codec::DolbyVision => capabilities.hdr && capabilities.hevc,
+                Codec::Audio | Codec::Text => true,
+                Codec::Surround => capabilities.surround_sound,
+                Codec::Unknown => false,
+            };
+            if !supported {
+                issues.push(format!("codec {name} is not supported"));
+            }
+        }
+
+        if let Some(height) = self.height {
+            if height > capabilities.max_video_height {
+                issues.push(format!(
+                    "{height}p exceeds {}p maximum",
+                    capabilities.max_video_height
+                ));
+            }
+        }
+        if let Some(frame_rate) = self.frame_rate {
+            if frame_rate > capabilities.max_frame_rate + FRAME_RATE_TOLERANCE {
+                issues.push(format!(
+                    "{frame_rate} fps exceeds {} fps maximum",
+                    capabilities.max_frame_rate
+                ));
+            }
+        }
+        if self.hdr && !capabilities.hdr {
+            issues.push("HDR is not supported".to_string());
+        }
+
+        issues
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            write!(f, "{width}x{height} ")?;
+        }
+        write!(f, "[{}]", self.codecs.join(","))
+    }
+}
+
+/// Adaptive streaming manifest, see [`Manifest::parse`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Manifest {
+    /// Format of the manifest.
+    pub format: ManifestFormat,
+    /// Variants of the stream, empty for HLS media playlists (they describe the segments only).
+    pub variants: Vec<Variant>,
+    /// Whether the HLS segments are fragmented MP4 rather than MPEG-2 TS, `None` if unknown (e.g.
+    /// for DASH manifests and HLS master playlists).
+    pub fmp4_segments: Option<bool>,
+}
+
+impl Manifest {
+    /// Parses HLS playlist or DASH manifest.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::Validation` if the document is neither HLS playlist nor DASH manifest.
+    pub fn parse(document: &str) -> Result<Manifest, Error> {
+        let document = document.trim_start_matches('\u{feff}').trim_start();
+        if document.starts_with("#EXTM3U") {
+            Ok(parse_hls(document))
+        } else if document.contains("<MPD") {
+            Ok(parse_dash(document))
+        } else {
+            Err(Error::Validation(
+                "media is neither HLS playlist nor DASH manifest".to_string(),
+            ))
+        }
+    }
+
+    /// Checks that the `media` described by the manifest can be played by the device with the
+    /// `capabilities`: content type of the media matches the manifest, HLS segment formats are
+    /// declared for fragmented MP4 segments, and at least one video (unless the device has no
+    /// video output) and one audio variant use the codecs, resolution and frame rate the device
+    /// supports.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::Validation` describing what has to be changed otherwise.
+    pub fn check(&self, media: &Media, capabilities: &DeviceCapabilities) -> Result<(), Error> {
+        let content_type = media.content_type.to_ascii_lowercase();
+        let (matches, expected) = match self.format {
+            ManifestFormat::Hls => (
+                HLS_CONTENT_TYPES.contains(&content_type.as_str()),
+                HLS_CONTENT_TYPES[0],
+            ),
+            ManifestFormat::Dash => (content_type == DASH_CONTENT_TYPE, DASH_CONTENT_TYPE),
+        };
+        if !matches {
+            return Err(Error::Validation(format!(
+                "content type {} doesn't match {} manifest, load the media as {expected}",
+                media.content_type, self.format
+            )));
+        }
+
+        if self.format == ManifestFormat::Hls {
+            check_hls_segment_format(self.fmp4_segments, media)?;
+        }
+
+        let video = self.variants.iter().filter(|v| v.is_video());
+        let audio = self.variants.iter().filter(|v| v.is_audio());
+        if !capabilities.video_out {
+            if audio.clone().next().is_none() && video.clone().next().is_some() {
+                return Err(Error::Validation(
+                    "device has no video output and the stream has no audio-only variants"
+                        .to_string(),
+                ));
+            }
+        } else {
+            check_playable("video", video, capabilities)?;
+        }
+
+        check_playable("audio", audio, capabilities)
+    }
+}
+
+/// Fetches the manifest of the adaptive streaming `media` (HLS or DASH, detected by the content
+/// type or the URL) and checks it against the `capabilities` of the device, see
+/// [`Manifest::check`]. For HLS master playlists the first variant playlist is fetched as well to
+/// find out the format of the segments.
+///
+/// # Arguments
+///
+/// * `media` - Media that is going to be loaded.
+/// * `capabilities` - Capabilities of the device the media is going to be loaded on.
+/// * `timeout` - Maximum time connecting to the server and each read or write may take.
+///
+/// # Errors
+///
+/// Fails with `Error::Validation` if the manifest isn't available or the device can't play the
+/// media, and with `Error::Io`, `Error::Tls` or `Error::Parsing` if fetching the manifest fails.
+///
+/// # Return value
+///
+/// Checked manifest, `None` if the media isn't adaptive streaming media (nothing is fetched then).
+pub fn check_media(
+    media: &Media,
+    capabilities: &DeviceCapabilities,
+    timeout: Duration,
+) -> Result<Option<Manifest>, Error> {
+    let url = media.content_url.as_deref().unwrap_or(&media.content_id);
+    let content_type = media.content_type.to_ascii_lowercase();
+    let is_manifest = HLS_CONTENT_TYPES.contains(&content_type.as_str())
+        || content_type == DASH_CONTENT_TYPE
+        || matches!(
+            guess_content_type(url),
+            Some(content_type) if content_type == DASH_CONTENT_TYPE
+                || HLS_CONTENT_TYPES.contains(&content_type)
+        );
+    if !is_manifest {
+        return Ok(None);
+    }
+
+    let response = fetch(url, timeout)?;
+    let mut manifest = Manifest::parse(&String::from_utf8_lossy(&response.body))?;
+
+    if manifest.format == ManifestFormat::Hls && manifest.fmp4_segments.is_none() {
+        if let Some(uri) = manifest.variants.iter().find_map(|v| v.uri.as_deref()) {
+            let variant_url = response.url.join(uri).map_err(|err| {
+                Error::Parsing(format!("invalid variant playlist URI {uri}: {err}"))
+            })?;
+            let variant = fetch(variant_url.as_str(), timeout)?;
+            manifest.fmp4_segments =
+                Manifest::parse(&String::from_utf8_lossy(&variant.body))?.fmp4_segments;
+        }
+    }
+
+    manifest.check(media, capabilities)?;
+
+    Ok(Some(manifest))
+}
+
+fn fetch(url: &str, timeout: Duration) -> Result<Response, Error> {
+    let response = http::request("GET", url, timeout)?;
+    if !response.is_success() {
+        return Err(Error::Validation(format!(
+            "manifest {} is not available, server responded with status {}",
+            response.url, response.status
+        )));
+    }
+
+    Ok(response)
+}
+
+fn check_hls_segment_format(fmp4_segments: Option<bool>, media: &Media) -> Result<(), Error> {
+    let declared_fmp4 = media.hls_segment_format == Some(HlsSegmentFormat::Fmp4)
+        || media.hls_video_segment_format == Some(HlsVideoSegmentFormat::Fmp4);
+
+    match fmp4_segments {
+        Some(true)
+            if media.hls_segment_format != Some(HlsSegmentFormat::Fmp4)
+                || media.hls_video_segment_format != Some(HlsVideoSegmentFormat::Fmp4) =>
+        {
+            Err(Error::Validation(
+                "HLS segments are fragmented MP4, load the media with `hls_segment_format` and \
+                 `hls_video_segment_format` set to `Fmp4`"
+                    .to_string(),
+            ))
+        }
+        Some(false) if declared_fmp4 => Err(Error::Validation(
+            "HLS segments are MPEG-2 TS, but the media declares `Fmp4` segment format".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn check_playable<'a, I>(
+    kind: &str,
+    variants: I,
+    capabilities: &DeviceCapabilities,
+) -> Result<(), Error>
+where
+    I: Iterator<Item = &'a Variant>,
+{
+    let mut count = 0;
+    let mut reasons = vec![];
+    for variant in variants {
+        count += 1;
+        let issues = variant.issues(capabilities);
+        if issues.is_empty() {
+            return Ok(());
+        }
+        reasons.push(format!("{variant}: {}", issues.join(", ")));
+    }
+
+    if count == 0 {
+        return Ok(());
+    }
+
+    Err(Error::Validation(format!(
+        "none of {count} {kind} variants can be played by the device ({})",
+        reasons.join("; ")
+    )))
+}
+
+/// Codec families that differ in support across the device generations.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Codec {
+    H264,
+    Hevc,
+    Vp8,
+    Vp9,
+    Av1,
+    DolbyVision,
+    Audio,
+    Surround,
+    Text,
+    Unknown,
+}
+
+impl Codec {
+    fn is_video(self) -> bool {
+        matches!(
+            self,
+            Codec::H264 | Codec::Hevc | Codec::Vp8 | Codec::Vp9 | Codec::Av1 | Codec::DolbyVision
+        )
+    }
+
+    fn is_audio(self) -> bool {
+        matches!(self, Codec::Audio | Codec::Surround)
+    }
+}
+
+fn codec(name: &str) -> Codec {
+    let name = name.trim().to_ascii_lowercase();
+    let family = name.split('.').next().unwrap_or_default();
+
+    match family {
+        "avc1" | "avc3" => Codec::H264,
+        "hvc1" | "hev1" => Codec::Hevc,
+        "vp8" => Codec::Vp8,
+        "vp09" | "vp9" => Codec::Vp9,
+        "av01" => Codec::Av1,
+        "dvh1" | "dvhe" | "dvav" | "dva1" => Codec::DolbyVision,
+        "ac-3" | "ec-3" | "ec+3" => Codec::Surround,
+        "mp4a" if name == "mp4a.a5" || name == "mp4a.a6" => Codec::Surround,
+        "mp4a" | "opus" | "vorbis" | "flac" | "mp3" => Codec::Audio,
+        "wvtt" | "stpp" | "ttml" | "vtt" => Codec::Text,
+        _ => Codec::Unknown,
+    }
+}
+
+fn parse_hls(playlist: &str) -> Manifest {
+    let mut variants = vec![];
+    let mut pending: Option<Variant> = None;
+    let mut map = false;
+    let mut segments = vec![];
+
+    for line in playlist.lines().map(str::trim) {
+        if let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            let mut variant = Variant::default();
+            for (name, value) in hls_attributes(attributes) {
+                match name {
+                    "CODECS" => variant.codecs = split_codecs(value),
+                    "RESOLUTION" => {
+                        if let Some((width, height)) = value.split_once('x') {
+                            variant.width = width.parse().ok();
+                            variant.height = height.parse().ok();
+                        }
+                    }
+                    "FRAME-RATE" => variant.frame_rate = value.parse().ok(),
+                    "VIDEO-RANGE" => variant.hdr = value == "PQ" || value == "HLG",
+                    _ => {}
+                }
+            }
+            variant.hdr |= variant
+                .codecs
+                .iter()
+                .any(|c| codec(c) == Codec::DolbyVision);
+            pending = Some(variant);
+        } else if line.starts_with("#EXT-X-MAP") {
+            map = true;
+        } else if !line.is_empty() && !line.starts_with('#') {
+            match pending.take() {
+                Some(mut variant) => {
+                    variant.uri = Some(line.to_string());
+                    variants.push(variant);
+                }
+                None => segments.push(line),
+            }
+        }
+    }
+
+    let fmp4_segments = if !variants.is_empty() || segments.is_empty() {
+        None
+    } else {
+        Some(
+            map || segments.iter().any(|segment| {
+                let path = segment.split(['?', '#']).next().unwrap_or_default();
+                path.ends_with(".m4s") || path.ends_with(".mp4")
+            }),
+        )
+    };
+
+    Manifest {
+        format: ManifestFormat::Hls,
+        variants,
+        fmp4_segments,
+    }
+}
+
+/// Splits HLS attribute list (e.g. `BANDWIDTH=1280000,CODECS="avc1.4d401f,mp4a.40.2"`) into names
+/// and unquoted values.
+fn hls_attributes(attributes: &str) -> Vec<(&str, &str)> {
+    let mut result = vec![];
+    let mut rest = attributes;
+
+    while let Some((name, value)) = rest.split_once('=') {
+        let (value, next) = match value.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                let next = quoted[end..].trim_start_matches('"');
+                (&quoted[..end], next.strip_prefix(',').unwrap_or(next))
+            }
+            None => value.split_once(',').unwrap_or((value, "")),
+        };
+        result.push((name.trim(), value));
+        rest = next;
+    }
+
+    result
+}
+
+fn split_codecs(codecs: &str) -> Vec<String> {
+    codecs
+        .split(',')
+        .map(str::trim)
+        .filter(|codec| !codec.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Attributes of the DASH element that are inherited by its representations.
+#[derive(Clone, Default)]
+struct DashAttributes {
+    mime_type: Option<String>,
+    codecs: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    frame_rate: Option<f32>,
+    hdr: bool,
+}
+
+impl DashAttributes {
+    fn inherit(&self, tag: &str) -> DashAttributes {
+        let mut attributes = self.clone();
+        for (name, value) in xml_attributes(tag) {
+            match name {
+                "mimeType" | "contentType" => attributes.mime_type = Some(value.to_string()),
+                "codecs" => attributes.codecs = Some(value.to_string()),
+                "width" => attributes.width = value.parse().ok(),
+                "height" => attributes.height = value.parse().ok(),
+                "frameRate" => attributes.frame_rate = parse_frame_rate(value),
+                _ => {}
+            }
+        }
+
+        attributes
+    }
+
+    fn into_variant(self) -> Option<Variant> {
+        let mime_type = self.mime_type.unwrap_or_default();
+        // Subtitles and thumbnails aren't decoded by the media pipeline.
+        if mime_type.starts_with("text") || mime_type.starts_with("image") {
+            return None;
+        }
+
+        let codecs = split_codecs(self.codecs.as_deref().unwrap_or_default());
+        if codecs.iter().any(|c| codec(c) == Codec::Text) || mime_type.contains("ttml") {
+            return None;
+        }
+
+        Some(Variant {
+            hdr: self.hdr || codecs.iter().any(|c| codec(c) == Codec::DolbyVision),
+            codecs,
+            width: self.width,
+            height: self.height,
+            frame_rate: self.frame_rate,
+            uri: None,
+        })
+    }
+}
+
+fn parse_dash(manifest: &str) -> Manifest {
+    let mut variants: Vec<Variant> = vec![];
+    let mut adaptation_set = DashAttributes::default();
+    let mut in_representation = false;
+
+    for tag in manifest.split('<').skip(1) {
+        let tag = tag.split('>').next().unwrap_or_default();
+        let self_closing = tag.ends_with('/');
+        let name = tag
+            .split(char::is_whitespace)
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('/');
+
+        match name {
+            "AdaptationSet" => adaptation_set = DashAttributes::default().inherit(tag),
+            "Representation" => {
+                variants.extend(adaptation_set.inherit(tag).into_variant());
+                in_representation = !self_closing;
+            }
+            "/Representation" => in_representation = false,
+            "SupplementalProperty" | "EssentialProperty" => {
+                let attributes = xml_attributes(tag);
+                let attribute = |name| {
+                    attributes
+                        .iter()
+                        .find(|(attribute, _)| *attribute == name)
+                        .map(|(_, value)| *value)
+                };
+                // Transfer characteristics 16 is PQ and 18 is HLG.
+                if attribute("schemeIdUri") == Some(TRANSFER_CHARACTERISTICS_SCHEME)
+                    && matches!(attribute("value"), Some("16" | "18"))
+                {
+                    match variants.last_mut() {
+                        Some(variant) if in_representation => variant.hdr = true,
+                        _ => adaptation_set.hdr = true,
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Manifest {
+        format: ManifestFormat::Dash,
+        variants,
+        fmp4_segments: None,
+    }
+}
+
+/// Splits attributes of the XML tag (e.g. `Representation id="1" width="1280"`) into names and
+/// values.
+fn xml_attributes(tag: &str) -> Vec<(&str, &str)> {
+    let mut result = vec![];
+    let mut rest = tag;
+
+    while let Some((name, value)) = rest.split_once('=') {
+        let name = name.split_whitespace().last().unwrap_or_default();
+        let value = value.trim_start();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let value = &value[1..];
+        let end = value.find(quote).unwrap_or(value.len());
+        result.push((name, &value[..end]));
+        rest = value.get(end + 1..).unwrap_or_default();
+    }
+
+    result
+}
+
+/// Parses DASH frame rate, either a number or a fraction (e.g. `30000/1001`).
+fn parse_frame_rate(frame_rate: &str) -> Option<f32> {
+    match frame_rate.split_once('/') {
+        Some((numerator, denominator)) => {
+            let denominator = denominator.parse::<f32>().ok().filter(|d| *d > 0.)?;
+            Some(numerator.parse::<f32>().ok()? / denominator)
+        }
+        None => frame_rate.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASTER_PLAYLIST: &str = "#EXTM3U\n\
+        #EXT-X-STREAM-INF:BANDWIDTH=2000000,CODECS=\"avc1.640028,mp4a.40.2\",\
+        RESOLUTION=1920x1080,FRAME-RATE=30\n\
+        1080p.m3u8\n\
+        #EXT-X-STREAM-INF:BANDWIDTH=12000000,CODECS=\"hvc1.2.4.L150,mp4a.40.2\",\
+        RESOLUTION=3840x2160,FRAME-RATE=60,VIDEO-RANGE=PQ\n\
+        2160p.m3u8\n";
+
+    fn hls_media() -> Media {
+        Media::builder("https://example.com/master.m3u8", "application/x-mpegURL").build()
+    }
+
+    #[test]
+    fn test_parse_hls() {
+        let manifest = Manifest::parse(MASTER_PLAYLIST).unwrap();
+
+        assert_eq!(ManifestFormat::Hls, manifest.format);
+        assert_eq!(None, manifest.fmp4_segments);
+        assert_eq!(
+            vec![
+                Variant {
+                    codecs: vec!["avc1.640028".to_string(), "mp4a.40.2".to_string()],
+                    width: Some(1920),
+                    height: Some(1080),
+                    frame_rate: Some(30.),
+                    hdr: false,
+                    uri: Some("1080p.m3u8".to_string()),
+                },
+                Variant {
+                    codecs: vec!["hvc1.2.4.L150".to_string(), "mp4a.40.2".to_string()],
+                    width: Some(3840),
+                    height: Some(2160),
+                    frame_rate: Some(60.),
+                    hdr: true,
+                    uri: Some("2160p.m3u8".to_string()),
+                },
+            ],
+            manifest.variants
+        );
+
+        let playlist = "#EXTM3U\n#EXT-X-MAP:URI=\"init.mp4\"\n#EXTINF:4.0,\nsegment0.m4s\n";
+        assert_eq!(Some(true), Manifest::parse(playlist).unwrap().fmp4_segments);
+        let playlist = "#EXTM3U\n#EXTINF:4.0,\nsegment0.ts?token=1\n";
+        assert_eq!(
+            Some(false),
+            Manifest::parse(playlist).unwrap().fmp4_segments
+        );
+
+        assert!(matches!(
+            Manifest::parse("<html></html>"),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_dash() {
+        let manifest = Manifest::parse(
+            r#"<?xml version="1.0"?>
+            <MPD xmlns="urn:mpeg:dash:schema:mpd:2011" type="static">
+              <Period>
+                <AdaptationSet mimeType="video/mp4" codecs="avc1.4d401f" frameRate="30000/1001">
+                  <Representation id="720p" width="1280" height="720"/>
+                  <Representation id="2160p" codecs="vp09.02.51.10" width="3840" height="2160">
+                    <SupplementalProperty value="16"
+                      schemeIdUri="urn:mpeg:mpegB:cicp:TransferCharacteristics"/>
+                  </Representation>
+                </AdaptationSet>
+                <AdaptationSet mimeType='audio/mp4' codecs='ec-3'>
+                  <Representation id='surround'/>
+                </AdaptationSet>
+                <AdaptationSet mimeType="text/vtt">
+                  <Representation id="subtitles"/>
+                </AdaptationSet>
+              </Period>
+            </MPD>"#,
+        )
+        .unwrap();
+
+        assert_eq!(ManifestFormat::Dash, manifest.format);
+        assert_eq!(3, manifest.variants.len());
+        assert_eq!(Some(720), manifest.variants[0].height);
+        assert_eq!(
+            Some(29.97),
+            manifest.variants[0]
+                .frame_rate
+                .map(|rate| (rate * 100.).round() / 100.)
+        );
+        assert!(!manifest.variants[0].hdr);
+        assert_eq!(vec!["vp09.02.51.10"], manifest.variants[1].codecs);
+        assert!(manifest.variants[1].hdr);
+        assert_eq!(vec!["ec-3"], manifest.variants[2].codecs);
+    }
+
+    #[test]
+    fn test_check() {
+        let manifest = Manifest::parse(MASTER_PLAYLIST).unwrap();
+
+        assert!(manifest
+            .check(&hls_media(), &DeviceCapabilities::CHROMECAST)
+            .is_ok());
+        assert!(manifest
+            .check(&hls_media(), &DeviceCapabilities::CHROMECAST_ULTRA)
+            .is_ok());
+
+        let error = manifest
+            .check(&hls_media(), &DeviceCapabilities::SMART_DISPLAY)
+            .unwrap_err();
+        assert_eq!(
+            "validation failed, none of 2 video variants can be played by the device (1920x1080 \
+             [avc1.640028,mp4a.40.2]: 1080p exceeds 720p maximum; 3840x2160 \
+             [hvc1.2.4.L150,mp4a.40.2]: codec hvc1.2.4.L150 is not supported, 2160p exceeds \
+             720p maximum, HDR is not supported)",
+            error.to_string()
+        );
+
+        assert!(matches!(
+            manifest.check(&hls_media(), &DeviceCapabilities::AUDIO),
+            Err(Error::Validation(_))
+        ));
+
+        let media = Media::builder("https://example.com/master.m3u8", "video/mp4").build();
+        assert!(matches!(
+            manifest.check(&media, &DeviceCapabilities::CHROMECAST),
+            Err(Error::Validation(message)) if message.ends_with("application/x-mpegurl")
+        ));
+
+        let manifest = Manifest {
+            fmp4_segments: Some(true),
+            ..manifest
+        };
+        assert!(manifest
+            .check(&hls_media(), &DeviceCapabilities::CHROMECAST)
+            .is_err());
+        let media = Media::builder("https://example.com/master.m3u8", "application/x-mpegurl")
+            .hls_segment_format(HlsSegmentFormat::Fmp4)
+            .hls_video_segment_format(HlsVideoSegmentFormat::Fmp4)
+            .build();
+        assert!(manifest
+            .check(&media, &DeviceCapabilities::CHROMECAST)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_check_media() {
+        let base = http::serve(vec![
+            format!("HTTP/1.0 200 OK\r\n\r\n{MASTER_PLAYLIST}"),
+            "HTTP/1.0 200 OK\r\n\r\n#EXTM3U\n#EXTINF:4.0,\nsegment0.ts\n".to_string(),
+            "HTTP/1.0 404 Not Found\r\n\r\n".to_string(),
+        ]);
+        let timeout = Duration::from_secs(5);
+
+        let media = Media::builder(format!("{base}/master.m3u8"), "application/x-mpegurl").build();
+        let manifest = check_media(&media, &DeviceCapabilities::CHROMECAST, timeout)
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some(false), manifest.fmp4_segments);
+        assert!(matches!(
+            check_media(&media, &DeviceCapabilities::CHROMECAST, timeout),
+            Err(Error::Validation(message)) if message.contains("404")
+        ));
+
+        let media = Media::builder(format!("{base}/movie.mp4"), "video/mp4").build();
+        assert_eq!(
+            None,
+            check_media(&media, &DeviceCapabilities::CHROMECAST, timeout).unwrap()
+        );
+    }
+}
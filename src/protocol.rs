@@ -0,0 +1,25 @@
+//! Serde model of the JSON payloads exchanged over the built-in namespaces. High-level channel
+//! API covers the most common fields only, these structs let applications deserialize (or build)
+//! raw payloads with the correct field names and types when they need something the channels
+//! don't expose yet, e.g. payloads received as `ChannelMessage::Raw` or sent with `send_raw`.
+//!
+//! Struct and field names follow the Cast protocol messages, so changes to this module only
+//! happen in the breaking releases. New optional fields may still be added to the structs that
+//! are only received from the device.
+//!
+//! # Examples
+//!
+//! ```
+//! use rust_cast::protocol::receiver::StatusReply;
+//!
+//! let reply: StatusReply = serde_json::from_str(
+//!     r#"{"type":"RECEIVER_STATUS","requestId":1,"status":{"volume":{"level":0.5}}}"#,
+//! )?;
+//!
+//! assert_eq!(Some(0.5), reply.status.volume.level);
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+#[cfg(feature = "youtube")]
+pub use crate::cast::proxies::youtube;
+pub use crate::cast::proxies::{connection, error, heartbeat, media, multizone, receiver, setup};
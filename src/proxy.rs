@@ -0,0 +1,492 @@
+//! Proxies the TCP connection with the cast device can be established through (e.g. jump host in
+//! front of the remote LAN), see `ConnectOptions::proxy`. TLS session with the device is
+//! established over the proxied connection, so the proxy never sees the plain traffic. Host names
+//! passed to `CastDevice::connect_with_options` are resolved by the proxy.
+
+use std::{
+    fmt,
+    io::{Read, Write},
+    net::{IpAddr, SocketAddr, TcpStream},
+};
+
+use crate::errors::Error;
+
+/// SOCKS5 protocol version.
+const SOCKS_VERSION: u8 = 5;
+/// SOCKS5 username/password authentication sub-negotiation version.
+const SOCKS_AUTH_VERSION: u8 = 1;
+const SOCKS_NO_AUTH: u8 = 0;
+const SOCKS_USERNAME_PASSWORD: u8 = 2;
+const SOCKS_CONNECT: u8 = 1;
+const SOCKS_IPV4: u8 = 1;
+const SOCKS_DOMAIN_NAME: u8 = 3;
+const SOCKS_IPV6: u8 = 4;
+
+/// Maximum size of the HTTP proxy response head, proxies don't send much more than status line.
+const MAX_HTTP_RESPONSE_HEAD: usize = 8 * 1024;
+
+/// Credentials the proxy is authenticated with.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProxyCredentials {
+    /// Name of the proxy user.
+    pub username: String,
+    /// Password of the proxy user.
+    pub password: String,
+}
+
+impl fmt::Debug for ProxyCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyCredentials")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// Proxy the connection with the cast device is established through.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProxyConfig {
+    /// SOCKS5 proxy (RFC 1928), optionally with username/password authentication (RFC 1929).
+    Socks5 {
+        /// Proxy address in `host:port` form.
+        address: String,
+        /// Credentials of the proxy user, `None` if proxy doesn't require authentication.
+        credentials: Option<ProxyCredentials>,
+    },
+    /// HTTP proxy that supports `CONNECT` method, optionally with basic authentication.
+    HttpConnect {
+        /// Proxy address in `host:port` form.
+        address: String,
+        /// Credentials of the proxy user, `None` if proxy doesn't require authentication.
+        credentials: Option<ProxyCredentials>,
+    },
+}
+
+/// Cast device the proxy connects to.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ProxyTarget<'a> {
+    /// Address that has been resolved locally.
+    Addr(SocketAddr),
+    /// Host name (or IP literal) and port, the name is resolved by the proxy, so that devices
+    /// which names only resolve on the remote side can be reached.
+    Host(&'a str, u16),
+}
+
+impl ProxyTarget<'_> {
+    /// Returns IP address of the target, if it's known without resolving the host name.
+    fn ip(&self) -> Option<IpAddr> {
+        match self {
+            ProxyTarget::Addr(addr) => Some(addr.ip()),
+            ProxyTarget::Host(host, _) => host.parse().ok(),
+        }
+    }
+
+    fn port(&self) -> u16 {
+        match self {
+            ProxyTarget::Addr(addr) => addr.port(),
+            ProxyTarget::Host(_, port) => *port,
+        }
+    }
+}
+
+impl fmt::Display for ProxyTarget<'_> {
+    /// Formats target in `host:port` form, IPv6 addresses are wrapped into square brackets.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self, self.ip()) {
+            (_, Some(ip)) => write!(f, "{}", SocketAddr::new(ip, self.port())),
+            (ProxyTarget::Host(host, port), None) => write!(f, "{host}:{port}"),
+            (ProxyTarget::Addr(addr), None) => write!(f, "{addr}"),
+        }
+    }
+}
+
+impl ProxyConfig {
+    /// Establishes TCP connection with the `target` through the proxy.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `Error::Io` if proxy can't be reached and with `Error::Proxy` if proxy refuses
+    /// to connect to the `target`.
+    pub(crate) fn connect(&self, target: ProxyTarget<'_>) -> Result<TcpStream, Error> {
+        match self {
+            ProxyConfig::Socks5 {
+                address,
+                credentials,
+            } => {
+                log::debug!("Connecting to {target} through SOCKS5 proxy {address}…");
+                let mut stream = TcpStream::connect(address.as_str())?;
+                socks5_connect(&mut stream, target, credentials.as_ref())?;
+                Ok(stream)
+            }
+            ProxyConfig::HttpConnect {
+                address,
+                credentials,
+            } => {
+                log::debug!("Connecting to {target} through HTTP proxy {address}…");
+                let mut stream = TcpStream::connect(address.as_str())?;
+                http_connect(&mut stream, target, credentials.as_ref())?;
+                Ok(stream)
+            }
+        }
+    }
+}
+
+/// Performs SOCKS5 handshake over the `stream` established with the proxy and asks it to connect
+/// to the `target`.
+fn socks5_connect<S>(
+    stream: &mut S,
+    target: ProxyTarget<'_>,
+    credentials: Option<&ProxyCredentials>,
+) -> Result<(), Error>
+where
+    S: Read + Write,
+{
+    let method = if credentials.is_some() {
+        SOCKS_USERNAME_PASSWORD
+    } else {
+        SOCKS_NO_AUTH
+    };
+    stream.write_all(&[SOCKS_VERSION, 1, method])?;
+
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply)?;
+    if reply[0] != SOCKS_VERSION {
+        return Err(Error::Proxy(format!(
+            "unexpected SOCKS version {}",
+            reply[0]
+        )));
+    }
+    if reply[1] != method {
+        return Err(Error::Proxy(
+            "SOCKS5 proxy doesn't accept the authentication method".to_string(),
+        ));
+    }
+
+    if let Some(credentials) = credentials {
+        let username = credentials.username.as_bytes();
+        let password = credentials.password.as_bytes();
+        let (Ok(username_length), Ok(password_length)) =
+            (u8::try_from(username.len()), u8::try_from(password.len()))
+        else {
+            return Err(Error::Validation(
+                "SOCKS5 username and password can't be longer than 255 bytes".to_string(),
+            ));
+        };
+
+        let mut request = vec![SOCKS_AUTH_VERSION, username_length];
+        request.extend_from_slice(username);
+        request.push(password_length);
+        request.extend_from_slice(password);
+        stream.write_all(&request)?;
+
+        stream.read_exact(&mut reply)?;
+        if reply[1] != 0 {
+            return Err(Error::Proxy(
+                "SOCKS5 proxy rejected the credentials".to_string(),
+            ));
+        }
+    }
+
+    let mut request = vec![SOCKS_VERSION, SOCKS_CONNECT, 0];
+    match (target, target.ip()) {
+        (_, Some(IpAddr::V4(ip))) => {
+            request.push(SOCKS_IPV4);
+            request.extend_from_slice(&ip.octets());
+        }
+        (_, Some(IpAddr::V6(ip))) => {
+            request.push(SOCKS_IPV6);
+            request.extend_from_slice(&ip.octets());
+        }
+        (ProxyTarget::Host(host, _), None) => {
+            let Ok(host_length) = u8::try_from(host.len()) else {
+                return Err(Error::Validation(
+                    "SOCKS5 host name can't be longer than 255 bytes".to_string(),
+                ));
+            };
+            request.extend_from_slice(&[SOCKS_DOMAIN_NAME, host_length]);
+            request.extend_from_slice(host.as_bytes());
+        }
+        (ProxyTarget::Addr(_), None) => unreachable!("socket address always has IP"),
+    }
+    request.extend_from_slice(&target.port().to_be_bytes());
+    stream.write_all(&request)?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply)?;
+    if reply[1] != 0 {
+        return Err(Error::Proxy(format!(
+            "SOCKS5 proxy could not connect to {target}, {}",
+            socks5_reply_reason(reply[1])
+        )));
+    }
+
+    // Skip the address proxy has bound the connection to, it isn't needed.
+    let bound_address_length = match reply[3] {
+        SOCKS_IPV4 => 4,
+        SOCKS_IPV6 => 16,
+        SOCKS_DOMAIN_NAME => {
+            let mut length = [0; 1];
+            stream.read_exact(&mut length)?;
+            usize::from(length[0])
+        }
+        address_type => {
+            return Err(Error::Proxy(format!(
+                "unexpected SOCKS5 address type {address_type}"
+            )))
+        }
+    };
+    let mut bound_address = vec![0; bound_address_length + 2];
+    stream.read_exact(&mut bound_address)?;
+
+    Ok(())
+}
+
+/// Describes SOCKS5 reply code (RFC 1928, section 6).
+fn socks5_reply_reason(code: u8) -> &'static str {
+    match code {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+/// Asks HTTP proxy connected with the `stream` to tunnel the connection to the `target`.
+fn http_connect<S>(
+    stream: &mut S,
+    target: ProxyTarget<'_>,
+    credentials: Option<&ProxyCredentials>,
+) -> Result<(), Error>
+where
+    S: Read + Write,
+{
+    let mut request = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+    if let Some(credentials) = credentials {
+        let token = base64(format!("{}:{}", credentials.username, credentials.password).as_bytes());
+        request.push_str(&format!("Proxy-Authorization: Basic {token}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes())?;
+
+    // Response is read byte by byte, so that nothing past its head (i.e. TLS data of the device)
+    // is consumed.
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() >= MAX_HTTP_RESPONSE_HEAD {
+            return Err(Error::Proxy("HTTP proxy response is too large".to_string()));
+        }
+
+        let mut byte = [0; 1];
+        if stream.read(&mut byte)? == 0 {
+            return Err(Error::Proxy("HTTP proxy closed the connection".to_string()));
+        }
+        head.push(byte[0]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let status_line = head.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(Error::Proxy(format!(
+            "HTTP proxy could not connect to {target}, {status_line}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Encodes `bytes` with standard base64 alphabet and padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let triple = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |triple, (index, byte)| {
+                triple | u32::from(*byte) << (16 - index * 8)
+            });
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(char::from(
+                    ALPHABET[(triple >> (18 - index * 6)) as usize & 0x3f],
+                ));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread};
+
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("dXNlcjpwYXNz", base64(b"user:pass"));
+    }
+
+    #[test]
+    fn test_socks5_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let proxy = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 3];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!([5, 1, 2], request);
+            stream.write_all(&[5, 2]).unwrap();
+
+            let mut request = [0; 11];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(b"\x01\x04user\x04pass", &request);
+            stream.write_all(&[1, 0]).unwrap();
+
+            let mut request = [0; 10];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!([5, 1, 0, 1, 192, 168, 1, 2, 0x1f, 0x49], request);
+            stream
+                .write_all(&[5, 0, 0, 1, 10, 0, 0, 1, 0x1f, 0x49])
+                .unwrap();
+            stream.write_all(b"data").unwrap();
+        });
+
+        let mut stream = ProxyConfig::Socks5 {
+            address,
+            credentials: Some(ProxyCredentials {
+                username: "user".to_string(),
+                password: "pass".to_string(),
+            }),
+        }
+        .connect(ProxyTarget::Addr("192.168.1.2:8009".parse().unwrap()))
+        .unwrap();
+
+        let mut data = String::new();
+        stream.read_to_string(&mut data).unwrap();
+        assert_eq!("data", data);
+        proxy.join().unwrap();
+    }
+
+    #[test]
+    fn test_http_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let proxy = thread::spawn(move || {
+            for status in [
+                "200 Connection established",
+                "407 Proxy Authentication Required",
+            ] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                while !request.ends_with(b"\r\n\r\n") {
+                    let mut byte = [0; 1];
+                    stream.read_exact(&mut byte).unwrap();
+                    request.push(byte[0]);
+                }
+                assert_eq!(
+                    "CONNECT 192.168.1.2:8009 HTTP/1.1\r\nHost: 192.168.1.2:8009\r\n\r\n",
+                    String::from_utf8(request).unwrap()
+                );
+                write!(stream, "HTTP/1.1 {status}\r\n\r\ndata").unwrap();
+            }
+        });
+
+        let proxy_config = ProxyConfig::HttpConnect {
+            address,
+            credentials: None,
+        };
+        let target = ProxyTarget::Addr("192.168.1.2:8009".parse().unwrap());
+
+        let mut stream = proxy_config.connect(target).unwrap();
+        let mut data = String::new();
+        stream.read_to_string(&mut data).unwrap();
+        assert_eq!("data", data);
+
+        assert!(matches!(proxy_config.connect(target), Err(Error::Proxy(_))));
+        proxy.join().unwrap();
+    }
+
+    #[test]
+    fn test_socks5_connect_domain_name() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let proxy = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 3];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!([5, 1, 0], request);
+            stream.write_all(&[5, 0]).unwrap();
+
+            let mut request = [0; 15];
+            stream.read_exact(&mut request).unwrap();
+            assert_eq!(b"\x05\x01\x00\x03\x08cast.lan\x1f\x49", &request);
+            stream
+                .write_all(&[5, 0, 0, 1, 10, 0, 0, 1, 0x1f, 0x49])
+                .unwrap();
+            stream.write_all(b"data").unwrap();
+        });
+
+        let proxy_config = ProxyConfig::Socks5 {
+            address,
+            credentials: None,
+        };
+        let mut stream = proxy_config
+            .connect(ProxyTarget::Host("cast.lan", 8009))
+            .unwrap();
+
+        let mut data = String::new();
+        stream.read_to_string(&mut data).unwrap();
+        assert_eq!("data", data);
+        proxy.join().unwrap();
+    }
+
+    #[test]
+    fn test_http_connect_host_name() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let proxy = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                let mut byte = [0; 1];
+                stream.read_exact(&mut byte).unwrap();
+                request.push(byte[0]);
+            }
+            assert_eq!(
+                "CONNECT cast.lan:8009 HTTP/1.1\r\nHost: cast.lan:8009\r\n\r\n",
+                String::from_utf8(request).unwrap()
+            );
+            write!(stream, "HTTP/1.1 200 Connection established\r\n\r\n").unwrap();
+        });
+
+        ProxyConfig::HttpConnect {
+            address,
+            credentials: None,
+        }
+        .connect(ProxyTarget::Host("cast.lan", 8009))
+        .unwrap();
+        proxy.join().unwrap();
+
+        assert_eq!("[::1]:8009", ProxyTarget::Host("::1", 8009).to_string());
+    }
+}
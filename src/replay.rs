@@ -0,0 +1,207 @@
+//! Replay of the frames captured with the wire log (see `wire_log`), it turns the recorded
+//! conversation with the specific device into the deterministic test: incoming frames are fed
+//! through `MessageManager` (so chunked messages are assembled the same way) and can then be
+//! checked with the channel parsers.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use rust_cast::{
+//!     channels::media::{MediaChannel, MediaResponse},
+//!     message_manager::MessageManager,
+//!     replay::ReplayStream,
+//!     Lrc,
+//! };
+//!
+//! let message_manager = Lrc::new(MessageManager::new(ReplayStream::open("nest-hub.log")?));
+//! let media = MediaChannel::new("sender-0", Lrc::clone(&message_manager));
+//!
+//! while let Ok(message) = message_manager.receive() {
+//!     if media.can_handle(&message) {
+//!         assert!(!matches!(media.parse(&message)?, MediaResponse::NotImplemented(..)));
+//!     }
+//! }
+//! # Ok::<(), rust_cast::errors::Error>(())
+//! ```
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Cursor, Error as IoError, ErrorKind, Read, Write},
+    path::Path,
+    time::Duration,
+};
+
+use protobuf::Message;
+
+use crate::{
+    cast::{cast_channel, cast_channel::cast_message::ProtocolVersion},
+    errors::Error,
+    message_manager::{self, CastMessage, Direction, MessageManager, ReadTimeout},
+    utils,
+    wire_log::WireFrame,
+};
+
+/// Stream that returns the recorded incoming frames to the reader and accepts (and discards)
+/// everything that is written to it. Once all the frames are read, the stream is at EOF.
+///
+/// Channel requests wait for the response with the matching request id, so the recorded responses
+/// can only satisfy requests which ids match the recording. Read recorded messages with
+/// `MessageManager::receive` instead, unless the recording starts with a fresh connection.
+#[derive(Debug, Default)]
+pub struct ReplayStream {
+    incoming: Cursor<Vec<u8>>,
+}
+
+impl ReplayStream {
+    /// Creates stream that replays incoming `frames`, outgoing frames are skipped.
+    pub fn new<I>(frames: I) -> Result<ReplayStream, Error>
+    where
+        I: IntoIterator<Item = WireFrame>,
+    {
+        let mut incoming = vec![];
+
+        for frame in frames {
+            if frame.direction != Direction::Incoming {
+                continue;
+            }
+
+            let raw_message = raw_message(frame).write_to_bytes()?;
+            incoming.extend_from_slice(&utils::write_u32_to_buffer(raw_message.len() as u32)?);
+            incoming.extend_from_slice(&raw_message);
+        }
+
+        Ok(ReplayStream {
+            incoming: Cursor::new(incoming),
+        })
+    }
+
+    /// Creates stream that replays the wire log written by `JsonLinesSink`, empty lines are
+    /// ignored.
+    pub fn from_json_lines<R>(reader: R) -> Result<ReplayStream, Error>
+    where
+        R: BufRead,
+    {
+        ReplayStream::new(read_frames(reader)?)
+    }
+
+    /// Creates stream that replays the wire log file at `path` written by `JsonLinesSink`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<ReplayStream, Error> {
+        ReplayStream::from_json_lines(BufReader::new(File::open(path)?))
+    }
+}
+
+impl Read for ReplayStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.incoming.read(buf)
+    }
+}
+
+impl Write for ReplayStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ReadTimeout for ReplayStream {
+    fn set_read_timeout(&mut self, _: Option<Duration>) -> Result<(), IoError> {
+        // Recorded frames are available immediately, so reads never block.
+        Ok(())
+    }
+}
+
+/// Reads all the frames from the wire log written by `JsonLinesSink`, empty lines are ignored.
+pub fn read_frames<R>(reader: R) -> Result<Vec<WireFrame>, Error>
+where
+    R: BufRead,
+{
+    let mut frames = vec![];
+
+    for line in reader.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            frames.push(WireFrame::from_json(&serde_json::from_str(&line)?)?);
+        }
+    }
+
+    Ok(frames)
+}
+
+/// Feeds incoming `frames` through `MessageManager` and returns all the messages it has received,
+/// in the order they were received.
+pub fn replay_messages<I>(frames: I) -> Result<Vec<CastMessage>, Error>
+where
+    I: IntoIterator<Item = WireFrame>,
+{
+    let message_manager = MessageManager::new(ReplayStream::new(frames)?);
+    let mut messages = vec![];
+
+    loop {
+        match message_manager.receive() {
+            Ok(message) => messages.push(message),
+            Err(Error::Io(ref err)) if err.kind() == ErrorKind::UnexpectedEof => {
+                return Ok(messages)
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Converts recorded `frame` into the protobuf message.
+fn raw_message(frame: WireFrame) -> cast_channel::CastMessage {
+    let mut raw_message = message_manager::raw_message(CastMessage {
+        namespace: frame.namespace,
+        source: frame.source,
+        destination: frame.destination,
+        payload: frame.payload,
+    });
+
+    // Chunks are only allowed since 1.1 version of the protocol.
+    if frame.continued {
+        raw_message.set_protocol_version(ProtocolVersion::CASTV2_1_1);
+        raw_message.set_continued(true);
+    }
+
+    raw_message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::receiver::{self, ReceiverChannel, ReceiverResponse};
+
+    const WIRE_LOG: &str = r#"
+{"direction":"outgoing","timestamp_ms":1,"namespace":"urn:x-cast:com.google.cast.receiver","source":"sender-0","destination":"receiver-0","continued":false,"payload_utf8":"{\"type\":\"GET_STATUS\",\"requestId\":1}"}
+{"direction":"incoming","timestamp_ms":2,"namespace":"urn:x-cast:com.google.cast.receiver","source":"receiver-0","destination":"*","continued":true,"payload_utf8":"{\"type\":\"RECEIVER_STATUS\",\"requestId\":1,"}
+{"direction":"incoming","timestamp_ms":3,"namespace":"urn:x-cast:com.google.cast.tp.heartbeat","source":"receiver-0","destination":"sender-0","continued":false,"payload_utf8":"{\"type\":\"PING\"}"}
+{"direction":"incoming","timestamp_ms":4,"namespace":"urn:x-cast:com.google.cast.receiver","source":"receiver-0","destination":"*","continued":false,"payload_utf8":"\"status\":{\"volume\":{\"level\":0.25,\"muted\":false}}}"}
+"#;
+
+    #[test]
+    fn test_replay_messages() {
+        let frames = read_frames(WIRE_LOG.as_bytes()).unwrap();
+        assert_eq!(4, frames.len());
+
+        let messages = replay_messages(frames).unwrap();
+
+        assert_eq!(2, messages.len());
+        assert_eq!(
+            crate::channels::heartbeat::CHANNEL_NAMESPACE,
+            messages[0].namespace
+        );
+
+        let channel = ReceiverChannel::new(
+            "sender-0",
+            "receiver-0",
+            crate::Lrc::new(MessageManager::new(ReplayStream::default())),
+        );
+        assert_eq!(receiver::CHANNEL_NAMESPACE, messages[1].namespace);
+        match channel.parse(&messages[1]).unwrap() {
+            ReceiverResponse::Status(status) => assert_eq!(Some(0.25), status.volume.level),
+            response => panic!("unexpected response {response:?}"),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Additional virtual senders that share the connection with the cast device, see
+//! `CastDevice::new_sender`.
+
+use std::io::{Read, Write};
+
+use crate::{
+    channels::{
+        connection::ConnectionChannel, debug_overlay::DebugOverlayChannel, error::ErrorChannel,
+        heartbeat::HeartbeatChannel, media::MediaChannel, mirroring::MirroringChannel,
+        multizone::MultizoneChannel, receiver::ReceiverChannel, setup::SetupChannel,
+    },
+    errors::Error,
+    message_manager::{ConnectionState, MessageManager},
+    Lock, Lrc, TlsStream,
+};
+
+#[cfg(feature = "youtube")]
+use crate::channels::youtube::YouTubeChannel;
+
+/// Logical sender that talks to the cast device over the connection of the `CastDevice` it has
+/// been created with, but with its own sender id. Virtual connections of the sender are independent
+/// from the ones of the device (and of other senders), so the receiver treats it as a separate
+/// control surface.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_cast::CastDevice;
+///
+/// let device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// let remote = device.new_sender("sender-1");
+///
+/// remote.connection.connect("receiver-0")?;
+/// remote.receiver.set_volume(0.5)?;
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+pub struct CastSender<W = TlsStream>
+where
+    W: Read + Write,
+{
+    sender: String,
+
+    message_manager: Lrc<MessageManager<W>>,
+
+    /// Ids of the senders the device accepts messages for, see `CastDevice::new_sender`.
+    sender_ids: Lrc<Lock<Vec<String>>>,
+
+    /// Channel that manages connection responses/requests of this sender.
+    pub connection: ConnectionChannel<W>,
+
+    /// Channel that controls debug overlay and logs of the custom receiver applications.
+    pub debug_overlay: DebugOverlayChannel<W>,
+
+    /// Channel that reports transport level errors addressed to this sender.
+    pub error: ErrorChannel<W>,
+
+    /// Channel that allows connection of this sender to stay alive.
+    pub heartbeat: HeartbeatChannel<W>,
+
+    /// Channel that manages various media stuff.
+    pub media: MediaChannel<W>,
+
+    /// Channel that exchanges screen mirroring and remoting signaling messages.
+    pub mirroring: MirroringChannel<W>,
+
+    /// Channel that manages members of the speaker group (if device is a group leader).
+    pub multizone: MultizoneChannel<W>,
+
+    /// Channel that manages receiving platform (e.g. Chromecast).
+    pub receiver: ReceiverChannel<W>,
+
+    /// Channel that manages settings of the device (e.g. its name).
+    pub setup: SetupChannel<W>,
+
+    /// Channel that controls YouTube application.
+    #[cfg(feature = "youtube")]
+    pub youtube: YouTubeChannel<W>,
+}
+
+impl<W> CastSender<W>
+where
+    W: Read + Write,
+{
+    /// Creates sender with the `sender` id that sends its messages with the `message_manager`, the
+    /// requests of the platform channels go to the `receiver` id. The `sender` id is removed from
+    /// the `sender_ids` once the sender is dropped.
+    pub(crate) fn new(
+        sender: String,
+        receiver: String,
+        message_manager: Lrc<MessageManager<W>>,
+        sender_ids: Lrc<Lock<Vec<String>>>,
+    ) -> CastSender<W> {
+        CastSender {
+            connection: ConnectionChannel::new(sender.clone(), Lrc::clone(&message_manager)),
+            debug_overlay: DebugOverlayChannel::new(sender.clone(), Lrc::clone(&message_manager)),
+            error: ErrorChannel::new(sender.clone(), Lrc::clone(&message_manager)),
+            heartbeat: HeartbeatChannel::new(
+                sender.clone(),
+                receiver.clone(),
+                Lrc::clone(&message_manager),
+            ),
+            media: MediaChannel::new(sender.clone(), Lrc::clone(&message_manager)),
+            mirroring: MirroringChannel::new(sender.clone(), Lrc::clone(&message_manager)),
+            multizone: MultizoneChannel::new(
+                sender.clone(),
+                receiver.clone(),
+                Lrc::clone(&message_manager),
+            ),
+            receiver: ReceiverChannel::new(
+                sender.clone(),
+                receiver.clone(),
+                Lrc::clone(&message_manager),
+            ),
+            setup: SetupChannel::new(
+                sender.clone(),
+                receiver.clone(),
+                Lrc::clone(&message_manager),
+            ),
+            #[cfg(feature = "youtube")]
+            youtube: YouTubeChannel::new(sender.clone(), Lrc::clone(&message_manager)),
+            sender,
+            message_manager,
+            sender_ids,
+        }
+    }
+
+    /// Returns the id messages of this sender are sent with.
+    pub fn sender_id(&self) -> &str {
+        &self.sender
+    }
+
+    /// Closes all virtual connections of this sender, the connection with the cast device (and
+    /// virtual connections of other senders) stay intact.
+    ///
+    /// # Errors
+    ///
+    /// Usually fails if network connection with cast device is already lost for some reason.
+    pub fn disconnect(&self) -> Result<(), Error> {
+        for destination in self.connection.connected_destinations() {
+            self.connection.disconnect(destination)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W> Drop for CastSender<W>
+where
+    W: Read + Write,
+{
+    fn drop(&mut self) {
+        {
+            let mut sender_ids = self.sender_ids.borrow_mut();
+            if let Some(index) = sender_ids.iter().rposition(|id| *id == self.sender) {
+                sender_ids.remove(index);
+            }
+        }
+
+        // Nothing to close if the device connection is already gone.
+        if self.message_manager.state() == ConnectionState::Closed {
+            return;
+        }
+
+        if let Err(err) = self.disconnect() {
+            log::warn!("Failed to disconnect sender {}: {err}", self.sender);
+        }
+    }
+}
@@ -0,0 +1,563 @@
+//! In-process cast receiver simulator (requires `simulator` feature): it listens on localhost with
+//! a self-signed certificate and answers the requests with `FakeReceiver`, so the whole
+//! `CastDevice` stack, TLS included, can be exercised in the integration tests.
+
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use rustls::{
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+    ServerConfig, ServerConnection, StreamOwned,
+};
+
+use crate::{errors::Error, message_manager::ReadTimeout, testing::FakeReceiver};
+
+/// How often simulator checks whether it should shut down or forward unsolicited messages.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Cast receiver simulator listening on the localhost, see `Simulator::start`. Connections are
+/// served one at a time and all of them share the same `FakeReceiver`, simulator stops once it's
+/// dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rust_cast::{channels::receiver::CastDeviceApp, simulator::Simulator, CastDevice};
+///
+/// let simulator = Simulator::start()?;
+/// let device = CastDevice::connect_without_host_verification(
+///     simulator.addr().ip().to_string(),
+///     simulator.addr().port(),
+/// )?;
+/// device.connection.connect("receiver-0")?;
+///
+/// let app = device.receiver.launch_app(&CastDeviceApp::DefaultMediaReceiver)?;
+/// assert_eq!(Some(app.app_id), simulator.receiver().running_app().map(|app| app.app_id));
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+pub struct Simulator {
+    addr: SocketAddr,
+    certificate: CertificateDer<'static>,
+    receiver: FakeReceiver,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Simulator {
+    /// Starts simulator on the random free port of the loopback interface.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the certificate can't be generated or the port can't be bound.
+    pub fn start() -> Result<Simulator, Error> {
+        Simulator::start_with(FakeReceiver::new())
+    }
+
+    /// Starts simulator that answers the requests with the `receiver` (e.g. with the scripted
+    /// responses already set up), on the random free port of the loopback interface.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the certificate can't be generated or the port can't be bound.
+    pub fn start_with(receiver: FakeReceiver) -> Result<Simulator, Error> {
+        let certified_key = rcgen::generate_simple_self_signed(vec![
+            "localhost".to_string(),
+            Ipv4Addr::LOCALHOST.to_string(),
+        ])
+        .map_err(|err| Error::Internal(format!("Failed to generate certificate: {err}")))?;
+        let certificate = certified_key.cert.der().clone();
+        let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(
+            certified_key.key_pair.serialize_der(),
+        ));
+
+        let config = ServerConfig::builder_with_provider(crate::crypto_provider(None)?)
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_single_cert(vec![certificate.clone()], key)?;
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let config = Arc::new(config);
+            let receiver = receiver.clone();
+            let shutdown = Arc::clone(&shutdown);
+
+            thread::spawn(move || serve(listener, config, receiver, shutdown))
+        };
+
+        Ok(Simulator {
+            addr,
+            certificate,
+            receiver,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the address simulator accepts cast connections on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the self-signed certificate simulator presents during TLS handshake.
+    pub fn certificate(&self) -> &CertificateDer<'static> {
+        &self.certificate
+    }
+
+    /// Returns the fake receiver that answers the requests, e.g. to script responses or to
+    /// inspect the messages it has received so far.
+    pub fn receiver(&self) -> &FakeReceiver {
+        &self.receiver
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                log::warn!("Simulator thread has panicked.");
+            }
+        }
+    }
+}
+
+/// Accepts connections until simulator is shut down.
+fn serve(
+    listener: TcpListener,
+    config: Arc<ServerConfig>,
+    receiver: FakeReceiver,
+    shutdown: Arc<AtomicBool>,
+) {
+    while !shutdown.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                log::debug!("Simulator accepted connection from {peer}.");
+
+                if let Err(err) = serve_connection(stream, &config, receiver.clone(), &shutdown) {
+                    log::debug!("Simulator connection with {peer} failed: {err}");
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(err) => {
+                log::warn!("Simulator failed to accept connection: {err}");
+                return;
+            }
+        }
+    }
+}
+
+/// Forwards the frames between the sender connected with `stream` and the `receiver` until either
+/// the sender disconnects or simulator is shut down.
+fn serve_connection(
+    stream: TcpStream,
+    config: &Arc<ServerConfig>,
+    mut receiver: FakeReceiver,
+    shutdown: &AtomicBool,
+) -> Result<(), Error> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    // Fake receiver reports that there is nothing to read instead of returning EOF.
+    receiver.set_read_timeout(Some(Duration::ZERO))?;
+
+    let mut stream = StreamOwned::new(ServerConnection::new(Arc::clone(config))?, stream);
+    let mut buffer = [0; 4096];
+
+    while !shutdown.load(Ordering::SeqCst) {
+        match stream.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(length) => receiver.write_all(&buffer[..length])?,
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        // Forward responses and messages pushed with `FakeReceiver::push_message`.
+        loop {
+            match receiver.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(length) => stream.write_all(&buffer[..length])?,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        // Handshake of the slower clients (e.g. native-tls) may still wait for their records.
+        match stream.flush() {
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            result => result?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        channels::{
+            media::{self, Media, MediaResponse, PlayerState, StreamType},
+            receiver::CastDeviceApp,
+        },
+        message_manager::{CastMessage, CastMessagePayload},
+        CastDevice, ChannelMessage, DEFAULT_RECEIVER_ID,
+    };
+
+    #[test]
+    fn test_cast_device_with_simulator() {
+        let simulator = Simulator::start().unwrap();
+        let device = CastDevice::connect_without_host_verification(
+            simulator.addr().ip().to_string(),
+            simulator.addr().port(),
+        )
+        .unwrap();
+        assert_eq!(
+            Some(simulator.certificate()),
+            device.peer_certificates().first()
+        );
+
+        device.connection.connect(DEFAULT_RECEIVER_ID).unwrap();
+        let app = device
+            .receiver
+            .launch_app(&CastDeviceApp::DefaultMediaReceiver)
+            .unwrap();
+        device
+            .connection
+            .connect(app.transport_id.as_str())
+            .unwrap();
+
+        let status = device
+            .media
+            .load(
+                app.transport_id.as_str(),
+                app.session_id.as_str(),
+                &Media {
+                    content_id: "https://example.com/video.mp4".to_string(),
+                    content_url: None,
+                    entity: None,
+                    atv_entity: None,
+                    stream_type: StreamType::Buffered,
+                    content_type: "video/mp4".to_string(),
+                    metadata: None,
+                    duration: None,
+                    hls_segment_format: None,
+                    hls_video_segment_format: None,
+                    text_track_style: None,
+                    breaks: vec![],
+                    break_clips: vec![],
+                    tracks: vec![],
+                    custom_data: None,
+                },
+            )
+            .unwrap();
+        let media_session_id = status.entries[0].media_session_id;
+
+        let entry = device
+            .media
+            .seek(
+                app.transport_id.as_str(),
+                media_session_id,
+                Some(42.0),
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(PlayerState::Playing, entry.player_state);
+        assert_eq!(Some(42.0), entry.current_time);
+    }
+
+    #[cfg(feature = "native-tls")]
+    #[test]
+    fn test_cast_device_with_native_tls() {
+        let simulator = Simulator::start().unwrap();
+        let device = CastDevice::connect_native_tls(
+            simulator.addr().ip().to_string(),
+            simulator.addr().port(),
+            crate::ConnectOptions {
+                verify_host: false,
+                ..crate::ConnectOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            [simulator.certificate().clone()],
+            device.peer_certificates()
+        );
+
+        device.connection.connect(DEFAULT_RECEIVER_ID).unwrap();
+        let app = device
+            .receiver
+            .launch_app(&CastDeviceApp::DefaultMediaReceiver)
+            .unwrap();
+        assert_eq!(
+            Some(app.app_id),
+            simulator.receiver().running_app().map(|app| app.app_id)
+        );
+
+        device.disconnect().unwrap();
+    }
+
+    #[test]
+    fn test_receive_envelope() {
+        let simulator = Simulator::start().unwrap();
+        let device = CastDevice::connect_without_host_verification(
+            simulator.addr().ip().to_string(),
+            simulator.addr().port(),
+        )
+        .unwrap();
+
+        simulator.receiver().push_message(CastMessage {
+            namespace: media::CHANNEL_NAMESPACE.to_string(),
+            source: "web-7".to_string(),
+            destination: "*".to_string(),
+            payload: CastMessagePayload::String(
+                r#"{"type":"MEDIA_STATUS","requestId":0,"status":[]}"#.to_string(),
+            ),
+        });
+
+        let envelope = device.receive_envelope().unwrap();
+
+        assert_eq!(media::CHANNEL_NAMESPACE, envelope.namespace);
+        assert_eq!("web-7", envelope.source);
+        assert_eq!("*", envelope.destination);
+        assert!(matches!(
+            envelope.message,
+            ChannelMessage::Media(MediaResponse::Status(..))
+        ));
+    }
+
+    #[test]
+    fn test_receive_filters_destinations() {
+        let simulator = Simulator::start().unwrap();
+        let device = CastDevice::connect_without_host_verification(
+            simulator.addr().ip().to_string(),
+            simulator.addr().port(),
+        )
+        .unwrap();
+        let message = |destination: &str, payload: &str| CastMessage {
+            namespace: "urn:x-cast:com.example.custom".to_string(),
+            source: "web-7".to_string(),
+            destination: destination.to_string(),
+            payload: CastMessagePayload::String(payload.to_string()),
+        };
+
+        simulator
+            .receiver()
+            .push_message(message("sender-9", "other"));
+        simulator.receiver().push_message(message("*", "broadcast"));
+
+        match device.receive().unwrap() {
+            ChannelMessage::Raw(message) => assert_eq!(Some("broadcast"), message.payload.as_str()),
+            message => panic!("unexpected message {message:?}"),
+        }
+
+        device.set_destination_filter(false);
+        simulator
+            .receiver()
+            .push_message(message("sender-9", "other"));
+
+        match device.receive().unwrap() {
+            ChannelMessage::Raw(message) => assert_eq!("sender-9", message.destination),
+            message => panic!("unexpected message {message:?}"),
+        }
+    }
+
+    #[test]
+    fn test_receive_reports_parse_errors() {
+        let simulator = Simulator::start().unwrap();
+        let device = CastDevice::connect_without_host_verification(
+            simulator.addr().ip().to_string(),
+            simulator.addr().port(),
+        )
+        .unwrap();
+        let message = |payload: &str| CastMessage {
+            namespace: media::CHANNEL_NAMESPACE.to_string(),
+            source: "web-7".to_string(),
+            destination: "*".to_string(),
+            payload: CastMessagePayload::String(payload.to_string()),
+        };
+
+        simulator.receiver().push_message(message("{not json"));
+        assert!(device.receive().is_err());
+
+        device.set_report_parse_errors(true);
+        simulator.receiver().push_message(message("{not json"));
+        simulator.receiver().push_message(message(
+            r#"{"type":"MEDIA_STATUS","requestId":0,"status":[]}"#,
+        ));
+
+        match device.receive().unwrap() {
+            ChannelMessage::ParseError { namespace, raw, .. } => {
+                assert_eq!(media::CHANNEL_NAMESPACE, namespace);
+                assert_eq!(Some("{not json"), raw.as_str());
+            }
+            message => panic!("unexpected message {message:?}"),
+        }
+        assert!(matches!(
+            device.receive().unwrap(),
+            ChannelMessage::Media(MediaResponse::Status(..))
+        ));
+    }
+
+    #[test]
+    fn test_new_sender() {
+        let simulator = Simulator::start().unwrap();
+        let device = CastDevice::connect_without_host_verification(
+            simulator.addr().ip().to_string(),
+            simulator.addr().port(),
+        )
+        .unwrap();
+        device.connection.connect(DEFAULT_RECEIVER_ID).unwrap();
+
+        let remote = device.new_sender("sender-1");
+        remote.connection.connect(DEFAULT_RECEIVER_ID).unwrap();
+        remote.receiver.set_volume(0.5).unwrap();
+
+        assert_eq!("sender-1", remote.sender_id());
+        assert!(device.connection.is_connected(DEFAULT_RECEIVER_ID));
+        assert!(remote.connection.is_connected(DEFAULT_RECEIVER_ID));
+        let request = simulator.receiver().sent_messages().pop().unwrap();
+        assert_eq!("sender-1", request.source);
+
+        remote.disconnect().unwrap();
+        assert!(!remote.connection.is_connected(DEFAULT_RECEIVER_ID));
+        assert!(device.connection.is_connected(DEFAULT_RECEIVER_ID));
+
+        simulator.receiver().push_message(CastMessage {
+            namespace: "urn:x-cast:com.example.custom".to_string(),
+            source: "web-7".to_string(),
+            destination: "sender-1".to_string(),
+            payload: CastMessagePayload::String("remote".to_string()),
+        });
+        let envelope = device.receive_envelope().unwrap();
+        assert_eq!("sender-1", envelope.destination);
+    }
+
+    #[test]
+    fn test_launch_app_if_not_running() {
+        let simulator = Simulator::start().unwrap();
+        let device = CastDevice::connect_without_host_verification(
+            simulator.addr().ip().to_string(),
+            simulator.addr().port(),
+        )
+        .unwrap();
+        device.connection.connect(DEFAULT_RECEIVER_ID).unwrap();
+        let app = CastDeviceApp::DefaultMediaReceiver;
+
+        let launched = device.launch_app_if_not_running(&app, false).unwrap();
+        assert!(launched.media_status.is_none());
+        assert!(device
+            .connection
+            .is_connected(&launched.application.transport_id));
+
+        let joined = device.launch_app_if_not_running(&app, false).unwrap();
+        assert_eq!(
+            launched.application.session_id,
+            joined.application.session_id
+        );
+
+        let relaunched = device.launch_app_if_not_running(&app, true).unwrap();
+        assert_ne!(
+            launched.application.session_id,
+            relaunched.application.session_id
+        );
+    }
+
+    #[test]
+    fn test_load_media_requires_media_namespace() {
+        let simulator = Simulator::start().unwrap();
+        let device = CastDevice::connect_without_host_verification(
+            simulator.addr().ip().to_string(),
+            simulator.addr().port(),
+        )
+        .unwrap();
+        device.connection.connect(DEFAULT_RECEIVER_ID).unwrap();
+        let mut application = device
+            .receiver
+            .launch_app(&CastDeviceApp::DefaultMediaReceiver)
+            .unwrap();
+        device
+            .connection
+            .connect(application.transport_id.as_str())
+            .unwrap();
+        let media = Media {
+            content_id: "https://example.com/audio.mp3".to_string(),
+            content_url: None,
+            entity: None,
+            atv_entity: None,
+            stream_type: StreamType::Buffered,
+            content_type: "audio/mpeg".to_string(),
+            metadata: None,
+            duration: None,
+            hls_segment_format: None,
+            hls_video_segment_format: None,
+            text_track_style: None,
+            breaks: vec![],
+            break_clips: vec![],
+            tracks: vec![],
+            custom_data: None,
+        };
+
+        let status = device.load_media(&application, &media).unwrap();
+        assert_eq!(1, status.entries.len());
+
+        application.namespaces.clear();
+        assert!(matches!(
+            device.load_media(&application, &media),
+            Err(crate::errors::Error::Unsupported(..))
+        ));
+    }
+
+    #[cfg(feature = "thread_safe")]
+    #[test]
+    fn test_requests_while_receiving_on_another_thread() {
+        use std::{sync::mpsc, time::Instant};
+
+        use crate::channels::receiver::ReceiverResponse;
+
+        let simulator = Simulator::start().unwrap();
+        let device = CastDevice::connect_without_host_verification(
+            simulator.addr().ip().to_string(),
+            simulator.addr().port(),
+        )
+        .unwrap();
+        device.connection.connect(DEFAULT_RECEIVER_ID).unwrap();
+
+        let receiver = device.receiver.clone();
+        let (sender, answered) = mpsc::channel();
+        let requests = thread::spawn(move || {
+            for _ in 0..5 {
+                sender.send(receiver.get_status().unwrap()).unwrap();
+            }
+        });
+
+        // Responses read by the event loop are handed over to the thread waiting for them.
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut statuses = Vec::new();
+        while statuses.len() < 5 {
+            assert!(Instant::now() < deadline, "requests haven't been answered");
+            if let Some(ChannelMessage::Receiver(ReceiverResponse::Status(status))) =
+                device.receive_timeout(Duration::from_millis(10)).unwrap()
+            {
+                assert!(status.request_id.is_unsolicited());
+            }
+            statuses.extend(answered.try_iter());
+        }
+        requests.join().unwrap();
+
+        assert!(statuses
+            .iter()
+            .all(|status| !status.request_id.is_unsolicited()));
+    }
+}
@@ -0,0 +1,210 @@
+//! Photo slideshows: receiver keeps displaying the image until another media is loaded, so the
+//! timing of the slides is driven by the sender.
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    channels::{
+        heartbeat::HeartbeatResponse,
+        media::{IdleReason, Media, StreamType},
+        receiver::CastDeviceApp,
+    },
+    errors::Error,
+    message_manager::CastStream,
+    CastDevice, ChannelMessage,
+};
+
+/// Single image of the slideshow.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slide {
+    /// URL of the image.
+    pub url: String,
+    /// MIME type of the image.
+    pub content_type: String,
+    /// How long the image is displayed for.
+    pub duration: Duration,
+}
+
+impl Slide {
+    /// Creates slide that displays image at `url` for `duration`. Content type is guessed from the
+    /// extension of the URL path (`image/jpeg` if it's unknown), use `content_type` field to
+    /// override it.
+    pub fn new<S: Into<String>>(url: S, duration: Duration) -> Slide {
+        let url = url.into();
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let file_name = path.rsplit('/').next().unwrap_or_default();
+        let extension = file_name
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_ascii_lowercase());
+
+        let content_type = match extension.as_deref() {
+            Some("png") => "image/png",
+            Some("gif") => "image/gif",
+            Some("webp") => "image/webp",
+            Some("bmp") => "image/bmp",
+            _ => "image/jpeg",
+        };
+
+        Slide {
+            url,
+            content_type: content_type.to_string(),
+            duration,
+        }
+    }
+}
+
+/// Displays the slides one after another on the Default Media Receiver.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use rust_cast::{
+///     slideshow::{Slide, Slideshow},
+///     CastDevice,
+/// };
+///
+/// let device = CastDevice::connect_without_host_verification("192.168.1.2", 8009)?;
+/// device.connection.connect("receiver-0")?;
+///
+/// let slides = ["https://example.com/1.jpg", "https://example.com/2.png"]
+///     .into_iter()
+///     .map(|url| Slide::new(url, Duration::from_secs(10)))
+///     .collect();
+/// Slideshow::new(slides).with_repeat(true).run(&device)?;
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Slideshow {
+    slides: Vec<Slide>,
+    repeat: bool,
+}
+
+impl Slideshow {
+    /// Creates slideshow of the `slides` that is played once.
+    pub fn new(slides: Vec<Slide>) -> Slideshow {
+        Slideshow {
+            slides,
+            repeat: false,
+        }
+    }
+
+    /// Sets whether the slideshow starts over once the last slide has been displayed.
+    pub fn with_repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Returns slides of the slideshow.
+    pub fn slides(&self) -> &[Slide] {
+        &self.slides
+    }
+
+    /// Launches the Default Media Receiver on the `device` and displays the slides, blocking until
+    /// the slideshow is over. Heartbeat pings of the device are answered in the meantime, other
+    /// messages received are dropped. Slides the receiver fails to load are skipped. Slideshow
+    /// stops early once the slide is stopped or replaced by another sender.
+    ///
+    /// # Errors
+    ///
+    /// Usually fails if application can't be launched or connection with the device is lost.
+    pub fn run<W: CastStream>(&self, device: &CastDevice<W>) -> Result<(), Error> {
+        if self.slides.is_empty() {
+            return Ok(());
+        }
+
+        let app = device
+            .receiver
+            .launch_app(&CastDeviceApp::DefaultMediaReceiver)?;
+        device.connection.connect(app.transport_id.as_str())?;
+
+        loop {
+            for slide in &self.slides {
+                let media = Media::builder(slide.url.as_str(), slide.content_type.as_str())
+                    .stream_type(StreamType::None)
+                    .build();
+
+                let status = match device.media.load(
+                    app.transport_id.as_str(),
+                    app.session_id.as_str(),
+                    &media,
+                ) {
+                    Ok(status) => status,
+                    Err(err @ Error::LoadFailed { .. }) => {
+                        log::warn!("Skipping slide {} that can't be loaded: {err}", slide.url);
+                        continue;
+                    }
+                    Err(err) => return Err(err),
+                };
+
+                let Some(entry) = status.entries.first() else {
+                    continue;
+                };
+
+                let stopped = self.display(device, entry.media_session_id, slide.duration)?;
+                if let Some(IdleReason::Cancelled | IdleReason::Interrupted) = stopped {
+                    return Ok(());
+                }
+            }
+
+            if !self.repeat {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Waits till the slide with `media_session_id` has been displayed for `duration`.
+    ///
+    /// # Return value
+    ///
+    /// Reason the slide has stopped being displayed before the `duration` elapsed, if it has.
+    fn display<W: CastStream>(
+        &self,
+        device: &CastDevice<W>,
+        media_session_id: i32,
+        duration: Duration,
+    ) -> Result<Option<IdleReason>, Error> {
+        let deadline = Instant::now() + duration;
+
+        loop {
+            // Media channel tracks when the media session becomes idle.
+            if let Some(finished) = device.media.playback_finished(media_session_id) {
+                return Ok(Some(finished.reason));
+            }
+
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                return Ok(None);
+            }
+
+            if let Some(ChannelMessage::Heartbeat(HeartbeatResponse::Ping)) =
+                device.receive_timeout(timeout)?
+            {
+                device.heartbeat.pong()?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slide_content_type() {
+        let duration = Duration::from_secs(5);
+
+        assert_eq!(
+            "image/png",
+            Slide::new("https://example.com/a.PNG?size=large", duration).content_type
+        );
+        assert_eq!(
+            "image/webp",
+            Slide::new("https://example.com/a.webp#top", duration).content_type
+        );
+        assert_eq!(
+            "image/jpeg",
+            Slide::new("https://example.gif/photo", duration).content_type
+        );
+    }
+}
@@ -0,0 +1,761 @@
+//! Test doubles of the cast device stream (requires `test-util` feature), so that the casting
+//! logic built on top of `MessageManager` and the channels can be unit-tested without hardware.
+//! `MockTcpStream` replays the scripted responses as is, while `FakeReceiver` answers the most
+//! common requests the way the real receiver does.
+
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io::{ErrorKind, Read, Write},
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use byteorder::{BigEndian, WriteBytesExt};
+use log::warn;
+use protobuf::Message;
+use serde_json::{json, Value};
+
+use crate::{
+    cast::cast_channel,
+    channels::{heartbeat, media, receiver},
+    message_manager::{self, CastMessage, CastMessagePayload, CastStream, ReadTimeout},
+    utils::read_u32_from_buffer,
+};
+
+/// A mock implementation of a TCP stream for testing purposes.
+///
+/// # Example
+///
+/// ```rust
+/// use rust_cast::channels::media::MediaChannel;
+/// use rust_cast::message_manager::MessageManager;
+/// use rust_cast::testing::MockTcpStream;
+/// use rust_cast::Lrc;
+///
+/// let stream = MockTcpStream::new();
+/// let message_manager = Lrc::new(MessageManager::new(stream));
+/// let channel = MediaChannel::new(
+///     "sender-0",
+///     message_manager
+/// );
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MockTcpStream {
+    /// Inner stream of the TCP stream which allows cloning and referencing the same stream source.
+    inner: Arc<RwLock<InnerStream>>,
+}
+
+impl MockTcpStream {
+    /// Creates a new empty `MockTcpStream` instance.
+    pub fn new() -> Self {
+        MockTcpStream {
+            inner: Arc::new(RwLock::new(InnerStream::default())),
+        }
+    }
+
+    /// Add a response message to be returned by read operations on the stream.
+    pub fn add_message<M: protobuf::Message>(&mut self, message: M) {
+        let message = message.write_to_bytes().unwrap();
+        let mut mutex = self.inner.write().unwrap();
+        mutex.response_messages.push(message);
+    }
+
+    /// Add a response `CastMessage` to be returned by read operations on the stream.
+    pub fn add_cast_message(&mut self, message: CastMessage) {
+        self.add_message(message_manager::raw_message(message));
+    }
+
+    /// Returns the received message at the given index if present, else [None].
+    pub fn received_message(&self, index: usize) -> Option<TcpMessage> {
+        self.inner
+            .read()
+            .expect("expected to acquire read lock")
+            .received_messages
+            .get(index)
+            .cloned()
+    }
+
+    /// Returns how many times the sender has written to the stream.
+    pub fn write_count(&self) -> usize {
+        self.inner
+            .read()
+            .expect("expected to acquire read lock")
+            .write_count
+    }
+
+    fn inner_read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner.write().unwrap().read(buf)
+    }
+
+    fn inner_write(&self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner.write().unwrap().write(buf)
+    }
+
+    fn inner_flush(&self) -> std::io::Result<()> {
+        self.inner.write().unwrap().flush()
+    }
+}
+
+impl Read for MockTcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner_read(buf)
+    }
+}
+
+impl Write for MockTcpStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner_write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner_flush()
+    }
+}
+
+impl ReadTimeout for MockTcpStream {
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.inner.write().unwrap().read_timeout = timeout;
+        Ok(())
+    }
+}
+
+impl CastStream for MockTcpStream {}
+
+/// Represents a TCP message containing a received payload from the sender.
+#[derive(Debug, Clone)]
+pub struct TcpMessage {
+    /// The known length of the message.
+    pub message_length: u32,
+    /// The payload of the message.
+    pub payload: Vec<u8>,
+}
+
+impl TcpMessage {
+    /// Parses and returns the CastMessage contained in the payload.
+    pub fn cast_message(&self) -> CastMessage {
+        message_manager::cast_message(&mut self.message())
+    }
+
+    /// Parses and returns the protobuf message contained in the payload.
+    pub(crate) fn message(&self) -> cast_channel::CastMessage {
+        <cast_channel::CastMessage as Message>::parse_from_bytes(self.payload.as_slice()).unwrap()
+    }
+}
+
+impl Display for TcpMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(self.payload.as_slice()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CursorLocation {
+    Length,
+    Payload,
+}
+
+#[derive(Debug, Clone)]
+struct ReadCursor {
+    pub location: CursorLocation,
+    pub index: usize,
+}
+
+impl ReadCursor {
+    pub fn next(&self) -> Self {
+        match self.location {
+            CursorLocation::Length => Self {
+                location: CursorLocation::Payload,
+                index: self.index,
+            },
+            CursorLocation::Payload => Self {
+                location: CursorLocation::Length,
+                index: self.index + 1,
+            },
+        }
+    }
+}
+
+impl Default for ReadCursor {
+    fn default() -> Self {
+        Self {
+            location: CursorLocation::Length,
+            index: 0,
+        }
+    }
+}
+
+/// Inner representation of a stream used by `MockTcpStream` for testing purposes.
+#[derive(Debug, Default)]
+struct InnerStream {
+    /// The current position of the read cursor.
+    cursor: ReadCursor,
+    /// Buffer containing the messages which should be returned by the read operation.
+    response_messages: Vec<Vec<u8>>,
+    /// Buffer for storing the bytes written by the sender that don't make a complete frame yet.
+    write_buffer: Vec<u8>,
+    /// Number of the write calls made by the sender.
+    write_count: usize,
+    /// Vector containing the received messages from the sender.
+    received_messages: Vec<TcpMessage>,
+    /// Read timeout of the stream, reads fail with `WouldBlock` instead of returning EOF when
+    /// there are no more messages and timeout is set.
+    read_timeout: Option<Duration>,
+}
+
+impl Read for InnerStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(message) = self.response_messages.get(self.cursor.index) {
+            let result: std::io::Result<usize> = match &self.cursor.location {
+                CursorLocation::Length => {
+                    let mut len = Vec::<u8>::new();
+                    len.write_u32::<BigEndian>(message.len() as u32).unwrap();
+                    buf[..4].copy_from_slice(len.as_slice());
+                    Ok(4)
+                }
+                CursorLocation::Payload => {
+                    let len = message.len();
+                    buf[..len].copy_from_slice(message.as_slice());
+                    Ok(len)
+                }
+            };
+
+            self.cursor = self.cursor.next();
+            result
+        } else if self.read_timeout.is_some() {
+            Err(ErrorKind::WouldBlock.into())
+        } else {
+            warn!("No more messages to read");
+            Ok(0)
+        }
+    }
+}
+
+impl Write for InnerStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_count += 1;
+        self.write_buffer.extend_from_slice(buf);
+
+        // Single write may contain several frames, or just a part of the frame.
+        while self.write_buffer.len() >= 4 {
+            let length = read_u32_from_buffer(&self.write_buffer[..4]).unwrap();
+            if self.write_buffer.len() < 4 + length as usize {
+                break;
+            }
+
+            let frame: Vec<u8> = self.write_buffer.drain(..4 + length as usize).collect();
+            self.received_messages.push(TcpMessage {
+                message_length: length,
+                payload: frame[4..].to_vec(),
+            });
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        // frames are processed as soon as they are written
+        // so there is nothing to flush here
+        Ok(())
+    }
+}
+/// Handler of the request sent to `FakeReceiver`, returns payloads of the responses.
+type RequestHandler = Box<dyn FnMut(&Value) -> Vec<Value> + Send>;
+
+/// Application running on the `FakeReceiver`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FakeApp {
+    /// Id of the application passed with the `LAUNCH` request.
+    pub app_id: String,
+    /// Id of the session the application runs in.
+    pub session_id: String,
+    /// Id media requests should be sent to.
+    pub transport_id: String,
+}
+
+/// Scriptable fake of the cast receiver: instead of replaying the fixed responses it answers the
+/// requests written to it, so the flow of requests doesn't have to be known in advance.
+///
+/// Built-in responses cover `CONNECT`/`CLOSE` (no response), `PING` (`PONG`), receiver
+/// `GET_STATUS`/`LAUNCH`/`STOP`/`SET_VOLUME` (`RECEIVER_STATUS`) and media
+/// `GET_STATUS`/`LOAD`/`PLAY`/`PAUSE`/`SEEK`/`STOP` (`MEDIA_STATUS`), every response carries the
+/// `requestId` of the request. Other requests are left unanswered unless handled with
+/// `on_request`. Clones share the same state, so a clone can be kept for assertions.
+///
+/// # Example
+///
+/// ```rust
+/// use rust_cast::{
+///     channels::receiver::{CastDeviceApp, ReceiverChannel},
+///     message_manager::MessageManager,
+///     testing::FakeReceiver,
+///     Lrc,
+/// };
+///
+/// let fake_receiver = FakeReceiver::new();
+/// let message_manager = Lrc::new(MessageManager::new(fake_receiver.clone()));
+/// let receiver = ReceiverChannel::new("sender-0", "receiver-0", message_manager);
+///
+/// let app = receiver.launch_app(&CastDeviceApp::DefaultMediaReceiver)?;
+///
+/// assert_eq!(Some(app.transport_id), fake_receiver.running_app().map(|app| app.transport_id));
+/// # Ok::<(), rust_cast::errors::Error>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct FakeReceiver {
+    inner: Arc<Mutex<FakeReceiverState>>,
+}
+
+impl FakeReceiver {
+    /// Creates receiver that doesn't run any application.
+    pub fn new() -> Self {
+        FakeReceiver::default()
+    }
+
+    /// Overrides the response to the requests of the `message_type` on the `namespace` (both the
+    /// built-in ones and those the receiver doesn't know about).
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the request, e.g. `urn:x-cast:com.google.cast.media`.
+    /// * `message_type` - Value of the `type` field of the request, e.g. `QUEUE_INSERT`.
+    /// * `handler` - Function that receives the JSON payload of the request and returns payloads
+    ///   of the responses, which are sent back to the request sender.
+    pub fn on_request<S, F>(&self, namespace: S, message_type: S, handler: F)
+    where
+        S: Into<String>,
+        F: FnMut(&Value) -> Vec<Value> + Send + 'static,
+    {
+        self.lock().handlers.push(ScriptedHandler {
+            namespace: namespace.into(),
+            message_type: message_type.into(),
+            handler: Box::new(handler),
+        });
+    }
+
+    /// Queues unsolicited `message` (e.g. `CLOSE` or status broadcast) to be read by the sender.
+    pub fn push_message(&self, message: CastMessage) {
+        self.lock().push(message);
+    }
+
+    /// Returns all the messages the sender has written so far, in the order they were written.
+    pub fn sent_messages(&self) -> Vec<CastMessage> {
+        self.lock().sent_messages.clone()
+    }
+
+    /// Returns the application launched on the receiver, if any.
+    pub fn running_app(&self) -> Option<FakeApp> {
+        self.lock().running_app.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FakeReceiverState> {
+        self.inner.lock().unwrap()
+    }
+}
+
+impl std::fmt::Debug for FakeReceiver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+
+        f.debug_struct("FakeReceiver")
+            .field("running_app", &state.running_app)
+            .field("sent_messages", &state.sent_messages.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Read for FakeReceiver {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut state = self.lock();
+
+        if state.incoming.is_empty() {
+            if state.read_timeout.is_some() {
+                return Err(ErrorKind::WouldBlock.into());
+            }
+
+            warn!("No more messages to read");
+            return Ok(0);
+        }
+
+        let length = buf.len().min(state.incoming.len());
+        for (byte, incoming) in buf.iter_mut().zip(state.incoming.drain(..length)) {
+            *byte = incoming;
+        }
+
+        Ok(length)
+    }
+}
+
+impl Write for FakeReceiver {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut state = self.lock();
+        state.write_buffer.extend_from_slice(buf);
+
+        // Single write may contain several frames, or just a part of the frame.
+        while state.write_buffer.len() >= 4 {
+            let length = read_u32_from_buffer(&state.write_buffer[..4]).unwrap() as usize;
+            if state.write_buffer.len() < 4 + length {
+                break;
+            }
+
+            let frame: Vec<u8> = state.write_buffer.drain(..4 + length).collect();
+            let mut raw_message = cast_channel::CastMessage::parse_from_bytes(&frame[4..])
+                .map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err))?;
+
+            state.handle(message_manager::cast_message(&mut raw_message));
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ReadTimeout for FakeReceiver {
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.lock().read_timeout = timeout;
+        Ok(())
+    }
+}
+
+impl CastStream for FakeReceiver {}
+
+struct ScriptedHandler {
+    namespace: String,
+    message_type: String,
+    handler: RequestHandler,
+}
+
+/// Media loaded on the `FakeReceiver`.
+struct FakeMedia {
+    media_session_id: i32,
+    media: Value,
+    player_state: &'static str,
+    current_time: f64,
+}
+
+#[derive(Default)]
+struct FakeReceiverState {
+    /// Bytes of the response frames which haven't been read yet.
+    incoming: VecDeque<u8>,
+    /// Bytes written by the sender that don't make a complete frame yet.
+    write_buffer: Vec<u8>,
+    sent_messages: Vec<CastMessage>,
+    handlers: Vec<ScriptedHandler>,
+    running_app: Option<FakeApp>,
+    launch_count: u32,
+    volume_level: Option<f64>,
+    muted: bool,
+    media: Option<FakeMedia>,
+    read_timeout: Option<Duration>,
+}
+
+impl FakeReceiverState {
+    /// Queues `message` to be read by the sender.
+    fn push(&mut self, message: CastMessage) {
+        let frame = message_manager::raw_message(message)
+            .write_to_bytes()
+            .unwrap();
+
+        self.incoming.extend(
+            (frame.len() as u32)
+                .to_be_bytes()
+                .iter()
+                .chain(frame.iter()),
+        );
+    }
+
+    /// Answers the `request` written by the sender.
+    fn handle(&mut self, request: CastMessage) {
+        let payload = request.payload.to_json().unwrap_or(Value::Null);
+        let message_type = payload["type"].as_str().unwrap_or_default().to_string();
+
+        let responses = match self.handlers.iter_mut().find(|handler| {
+            handler.namespace == request.namespace && handler.message_type == message_type
+        }) {
+            Some(handler) => (handler.handler)(&payload),
+            None => self.respond(&request.namespace, &message_type, &payload),
+        };
+
+        for response in responses {
+            self.push(CastMessage {
+                namespace: request.namespace.clone(),
+                source: request.destination.clone(),
+                destination: request.source.clone(),
+                payload: CastMessagePayload::String(response.to_string()),
+            });
+        }
+
+        self.sent_messages.push(request);
+    }
+
+    /// Returns built-in responses to the request of the `message_type` on the `namespace`.
+    fn respond(&mut self, namespace: &str, message_type: &str, request: &Value) -> Vec<Value> {
+        let request_id = request["requestId"].clone();
+
+        match (namespace, message_type) {
+            (heartbeat::CHANNEL_NAMESPACE, "PING") => vec![json!({ "type": "PONG" })],
+            (receiver::CHANNEL_NAMESPACE, "GET_STATUS") => vec![self.receiver_status(request_id)],
+            (receiver::CHANNEL_NAMESPACE, "LAUNCH") => {
+                self.launch_count += 1;
+                self.running_app = Some(FakeApp {
+                    app_id: request["appId"].as_str().unwrap_or_default().to_string(),
+                    session_id: format!("session-{}", self.launch_count),
+                    transport_id: format!("web-{}", self.launch_count),
+                });
+                self.media = None;
+
+                vec![self.receiver_status(request_id)]
+            }
+            (receiver::CHANNEL_NAMESPACE, "STOP") => {
+                self.running_app = None;
+                self.media = None;
+
+                vec![self.receiver_status(request_id)]
+            }
+            (receiver::CHANNEL_NAMESPACE, "SET_VOLUME") => {
+                if let Some(level) = request["volume"]["level"].as_f64() {
+                    self.volume_level = Some(level);
+                }
+                if let Some(muted) = request["volume"]["muted"].as_bool() {
+                    self.muted = muted;
+                }
+
+                vec![self.receiver_status(request_id)]
+            }
+            (media::CHANNEL_NAMESPACE, "GET_STATUS") => vec![self.media_status(request_id)],
+            (media::CHANNEL_NAMESPACE, "LOAD") => {
+                let media_session_id = self
+                    .media
+                    .as_ref()
+                    .map_or(1, |media| media.media_session_id + 1);
+                self.media = Some(FakeMedia {
+                    media_session_id,
+                    media: request["media"].clone(),
+                    player_state: if request["autoplay"] == false {
+                        "PAUSED"
+                    } else {
+                        "PLAYING"
+                    },
+                    current_time: request["currentTime"].as_f64().unwrap_or_default(),
+                });
+
+                vec![self.media_status(request_id)]
+            }
+            (media::CHANNEL_NAMESPACE, "PLAY" | "PAUSE" | "SEEK" | "STOP") => {
+                let Some(media) = self.media.as_mut() else {
+                    return vec![
+                        json!({ "type": "INVALID_PLAYER_STATE", "requestId": request_id }),
+                    ];
+                };
+
+                match message_type {
+                    "PLAY" => media.player_state = "PLAYING",
+                    "PAUSE" => media.player_state = "PAUSED",
+                    "SEEK" => {
+                        media.current_time = request["currentTime"]
+                            .as_f64()
+                            .unwrap_or(media.current_time)
+                    }
+                    _ => {
+                        let status = json!({
+                            "type": "MEDIA_STATUS",
+                            "requestId": request_id,
+                            "status": [{
+                                "mediaSessionId": media.media_session_id,
+                                "playerState": "IDLE",
+                                "idleReason": "CANCELLED",
+                            }],
+                        });
+                        self.media = None;
+
+                        return vec![status];
+                    }
+                }
+
+                vec![self.media_status(request_id)]
+            }
+            // Neither `CONNECT`/`CLOSE` nor unknown requests are answered.
+            _ => vec![],
+        }
+    }
+
+    fn receiver_status(&self, request_id: Value) -> Value {
+        let applications: Vec<Value> = self
+            .running_app
+            .iter()
+            .map(|app| {
+                json!({
+                    "appId": app.app_id,
+                    "displayName": app.app_id,
+                    "sessionId": app.session_id,
+                    "transportId": app.transport_id,
+                    "namespaces": [{ "name": media::CHANNEL_NAMESPACE }],
+                    "statusText": "",
+                })
+            })
+            .collect();
+
+        json!({
+            "type": "RECEIVER_STATUS",
+            "requestId": request_id,
+            "status": {
+                "applications": applications,
+                "volume": {
+                    "level": self.volume_level.unwrap_or(1.0),
+                    "muted": self.muted,
+                },
+            },
+        })
+    }
+
+    fn media_status(&self, request_id: Value) -> Value {
+        let entries: Vec<Value> = self
+            .media
+            .iter()
+            .map(|media| {
+                json!({
+                    "mediaSessionId": media.media_session_id,
+                    "media": media.media,
+                    "playbackRate": 1,
+                    "playerState": media.player_state,
+                    "currentTime": media.current_time,
+                    // PAUSE, SEEK, STREAM_VOLUME and STREAM_MUTE.
+                    "supportedMediaCommands": 15,
+                    "volume": { "level": 1.0, "muted": false },
+                })
+            })
+            .collect();
+
+        json!({
+            "type": "MEDIA_STATUS",
+            "requestId": request_id,
+            "status": entries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        channels::{
+            heartbeat::{HeartbeatChannel, HeartbeatResponse},
+            media::{Media, MediaChannel, PlayerState, StreamType},
+            receiver::{CastDeviceApp, LaunchErrorReason, ReceiverChannel},
+        },
+        errors::Error,
+        message_manager::MessageManager,
+        CastDevice, Lrc, DEFAULT_RECEIVER_ID, DEFAULT_SENDER_ID,
+    };
+
+    #[test]
+    fn test_fake_receiver() {
+        let fake_receiver = FakeReceiver::new();
+        let message_manager = Lrc::new(MessageManager::new(fake_receiver.clone()));
+        let receiver = ReceiverChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::clone(&message_manager),
+        );
+        let media = MediaChannel::new(DEFAULT_SENDER_ID, Lrc::clone(&message_manager));
+        let heartbeat = HeartbeatChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::clone(&message_manager),
+        );
+
+        let app = receiver
+            .launch_app(&CastDeviceApp::DefaultMediaReceiver)
+            .unwrap();
+        assert_eq!("web-1", app.transport_id);
+
+        let status = media
+            .load(
+                app.transport_id.as_str(),
+                app.session_id.as_str(),
+                &Media {
+                    content_id: "https://example.com/video.mp4".to_string(),
+                    content_url: None,
+                    entity: None,
+                    atv_entity: None,
+                    stream_type: StreamType::Buffered,
+                    content_type: "video/mp4".to_string(),
+                    metadata: None,
+                    duration: None,
+                    hls_segment_format: None,
+                    hls_video_segment_format: None,
+                    text_track_style: None,
+                    breaks: vec![],
+                    break_clips: vec![],
+                    tracks: vec![],
+                    custom_data: None,
+                },
+            )
+            .unwrap();
+        let media_session_id = status.entries[0].media_session_id;
+        assert_eq!(PlayerState::Playing, status.entries[0].player_state);
+
+        let entry = media
+            .pause(app.transport_id.as_str(), media_session_id)
+            .unwrap();
+        assert_eq!(PlayerState::Paused, entry.player_state);
+
+        heartbeat.ping().unwrap();
+        let pong = message_manager.receive().unwrap();
+        assert!(matches!(
+            heartbeat.parse(&pong).unwrap(),
+            HeartbeatResponse::Pong
+        ));
+
+        receiver.stop_app(app.session_id.as_str()).unwrap();
+        assert_eq!(None, fake_receiver.running_app());
+        assert_eq!(5, fake_receiver.sent_messages().len());
+    }
+
+    #[test]
+    fn test_fake_receiver_scripted_response() {
+        let fake_receiver = FakeReceiver::new();
+        fake_receiver.on_request(receiver::CHANNEL_NAMESPACE, "LAUNCH", |request| {
+            vec![json!({
+                "type": "LAUNCH_ERROR",
+                "requestId": request["requestId"],
+                "reason": "NOT_FOUND",
+            })]
+        });
+        let receiver = ReceiverChannel::new(
+            DEFAULT_SENDER_ID,
+            DEFAULT_RECEIVER_ID,
+            Lrc::new(MessageManager::new(fake_receiver.clone())),
+        );
+
+        assert!(matches!(
+            receiver.launch_app(&CastDeviceApp::DefaultMediaReceiver),
+            Err(Error::LaunchError {
+                reason: Some(LaunchErrorReason::NotFound),
+                ..
+            })
+        ));
+        assert_eq!(None, fake_receiver.running_app());
+    }
+
+    #[test]
+    fn test_cast_device_over_fake_receiver() {
+        let fake_receiver = FakeReceiver::new();
+        let stream: Box<dyn CastStream> = Box::new(fake_receiver.clone());
+        let device = CastDevice::from_stream(stream).unwrap();
+        device.connection.connect(DEFAULT_RECEIVER_ID).unwrap();
+
+        let app = device
+            .receiver
+            .launch_app(&CastDeviceApp::DefaultMediaReceiver)
+            .unwrap();
+        assert_eq!(
+            Some(app.app_id),
+            fake_receiver.running_app().map(|app| app.app_id)
+        );
+        assert!(device.peer_certificates().is_empty());
+    }
+}
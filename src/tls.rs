@@ -0,0 +1,248 @@
+use std::{
+    io::{Error as IoError, ErrorKind, Read, Write},
+    net::{Shutdown, TcpStream},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rustls::{pki_types::CertificateDer, ClientConnection};
+
+use crate::message_manager::{
+    socket_shutdown_handle, CastStream, ReadTimeout, ShutdownHandle, StreamWriter,
+};
+
+/// Size of the buffer TLS records are read from the socket into.
+const READ_BUFFER_SIZE: usize = 16 * 1024;
+
+/// TLS stream the connection with the cast device is established over by default. Unlike rustls
+/// `StreamOwned` it can be read and written from different threads at the same time, see
+/// `CastStream::split_writer`.
+pub struct TlsStream {
+    connection: Arc<Mutex<ClientConnection>>,
+    socket: TcpStream,
+    /// TLS records that have been read from the socket, but haven't been processed yet.
+    incoming: Vec<u8>,
+    writer: TlsWriter,
+}
+
+/// Write half of the `TlsStream`.
+#[derive(Clone)]
+struct TlsWriter {
+    connection: Arc<Mutex<ClientConnection>>,
+    state: Arc<Mutex<WriteState>>,
+}
+
+struct WriteState {
+    socket: TcpStream,
+    /// TLS records that haven't been written to the socket yet.
+    pending: Vec<u8>,
+}
+
+impl TlsStream {
+    /// Wraps the `connection` that has completed the handshake over the `socket`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the socket can't be cloned for the write half.
+    pub fn new(connection: ClientConnection, socket: TcpStream) -> Result<TlsStream, IoError> {
+        let connection = Arc::new(Mutex::new(connection));
+
+        Ok(TlsStream {
+            writer: TlsWriter {
+                connection: Arc::clone(&connection),
+                state: Arc::new(Mutex::new(WriteState {
+                    socket: socket.try_clone()?,
+                    pending: Vec::new(),
+                })),
+            },
+            connection,
+            socket,
+            incoming: Vec::new(),
+        })
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &TcpStream {
+        &self.socket
+    }
+
+    /// Runs `f` with the TLS session, e.g. to check negotiated protocol version.
+    pub fn with_connection<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&ClientConnection) -> R,
+    {
+        f(&self.connection.lock().unwrap())
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+        loop {
+            let processed = !self.incoming.is_empty();
+            let wants_write = {
+                let mut connection = self.connection.lock().unwrap();
+                match connection.reader().read(buf) {
+                    Err(error) if error.kind() == ErrorKind::WouldBlock => {}
+                    result => return result,
+                }
+
+                // Records are handed to the session only once the previous plaintext has been
+                // consumed, so that its buffer never overflows.
+                if processed {
+                    let mut records = self.incoming.as_slice();
+                    let result = connection.read_tls(&mut records);
+                    let consumed = self.incoming.len() - records.len();
+                    self.incoming.drain(..consumed);
+                    result?;
+                    connection
+                        .process_new_packets()
+                        .map_err(|error| IoError::new(ErrorKind::InvalidData, error))?;
+                }
+
+                connection.wants_write()
+            };
+
+            // Alerts and key updates the session has to answer go out with the next write if the
+            // write half is busy right now.
+            if wants_write {
+                if let Ok(mut state) = self.writer.state.try_lock() {
+                    state.write_records(&mut self.connection.lock().unwrap())?;
+                }
+            }
+
+            if processed {
+                continue;
+            }
+
+            // Socket is read without holding the session lock, so that the write half isn't
+            // blocked while waiting for the data.
+            let mut records = [0; READ_BUFFER_SIZE];
+            let read = match self.socket.read(&mut records) {
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                result => result?,
+            };
+
+            if read == 0 {
+                let mut connection = self.connection.lock().unwrap();
+                connection.read_tls(&mut &records[..0])?;
+                connection
+                    .process_new_packets()
+                    .map_err(|error| IoError::new(ErrorKind::InvalidData, error))?;
+            } else {
+                self.incoming.extend_from_slice(&records[..read]);
+            }
+        }
+    }
+}
+
+impl WriteState {
+    /// Takes the TLS records the `connection` has prepared and writes them to the socket.
+    fn write_records(&mut self, connection: &mut ClientConnection) -> Result<(), IoError> {
+        while connection.wants_write() {
+            connection.write_tls(&mut self.pending)?;
+        }
+
+        self.write_pending()
+    }
+
+    /// Writes the pending TLS records to the socket, the records the socket doesn't accept stay
+    /// pending.
+    fn write_pending(&mut self) -> Result<(), IoError> {
+        while !self.pending.is_empty() {
+            match self.socket.write(&self.pending) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.pending.drain(..written);
+                }
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for TlsWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+        let mut state = self.state.lock().unwrap();
+
+        // Nothing is accepted until the records of the previous writes are written.
+        state.write_pending()?;
+
+        let written = {
+            let mut connection = self.connection.lock().unwrap();
+            let written = connection.writer().write(buf)?;
+            while connection.wants_write() {
+                connection.write_tls(&mut state.pending)?;
+            }
+            written
+        };
+
+        // The data has been accepted already, records the socket doesn't accept in time go out
+        // with the next write or flush.
+        match state.write_pending() {
+            Err(error) if !matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                Err(error)
+            }
+            _ => Ok(written),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), IoError> {
+        let mut state = self.state.lock().unwrap();
+        state.write_records(&mut self.connection.lock().unwrap())?;
+        state.socket.flush()
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), IoError> {
+        self.writer.flush()
+    }
+}
+
+impl ReadTimeout for TlsStream {
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), IoError> {
+        ReadTimeout::set_read_timeout(&mut self.socket, timeout)
+    }
+}
+
+impl CastStream for TlsStream {
+    fn peer_certificates(&self) -> Vec<CertificateDer<'static>> {
+        self.connection
+            .lock()
+            .unwrap()
+            .peer_certificates()
+            .map(|certificates| {
+                certificates
+                    .iter()
+                    .map(|certificate| certificate.clone().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn shutdown(&mut self) -> Result<(), IoError> {
+        {
+            let mut state = self.writer.state.lock().unwrap();
+            let mut connection = self.connection.lock().unwrap();
+            connection.send_close_notify();
+            state.write_records(&mut connection)?;
+        }
+
+        self.socket.shutdown(Shutdown::Both)
+    }
+
+    fn shutdown_handle(&self) -> Option<ShutdownHandle> {
+        socket_shutdown_handle(&self.socket)
+    }
+
+    fn split_writer(&mut self) -> Option<StreamWriter> {
+        Some(Box::new(self.writer.clone()))
+    }
+}
@@ -14,16 +14,46 @@ pub fn write_u32_to_buffer(number: u32) -> Result<Vec<u8>, Error> {
     Ok(buffer)
 }
 
-pub fn to_vec<M: protobuf::Message>(message: &M) -> Result<Vec<u8>, Error> {
-    let mut buffer = vec![];
-
-    message.write_to_writer(&mut buffer)?;
-
-    Ok(buffer)
-}
-
-pub fn from_vec<M: protobuf::Message>(buffer: Vec<u8>) -> Result<M, Error> {
-    let mut read_buffer = Cursor::new(buffer);
-
-    Ok(M::parse_from_reader(&mut read_buffer)?)
+/// Generates random (version 4) UUID, e.g. for the unique sender ids. Randomness comes from the
+/// randomly seeded hasher of the standard library, so the UUID isn't suitable for secrets.
+pub fn random_uuid() -> String {
+    use std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        process,
+        sync::atomic::{AtomicU64, Ordering},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let half = || {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(nanos);
+        hasher.write_u32(process::id());
+        hasher.write_u64(counter);
+        hasher.finish()
+    };
+    let mut bytes = [half().to_be_bytes(), half().to_be_bytes()].concat();
+    // Version 4 and RFC 4122 variant.
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
 }
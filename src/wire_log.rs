@@ -0,0 +1,320 @@
+//! Capture of the raw frames exchanged with the cast device, see
+//! `MessageManager::set_wire_log`. Captured frames make the protocol issues reproducible: sensitive
+//! payload fields are redacted before the frame reaches the sink, so the log can be attached to the
+//! bug report as is.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::Value;
+
+use crate::{
+    cast::cast_channel,
+    errors::Error,
+    message_manager::{CastMessagePayload, Direction},
+};
+
+/// Value the sensitive payload fields are replaced with.
+pub const REDACTED: &str = "<redacted>";
+
+/// Names of the JSON payload fields (matched case-insensitively at any depth) which values are
+/// replaced with `REDACTED`.
+pub const REDACTED_FIELDS: &[&str] = &[
+    "accessToken",
+    "authorization",
+    "credentials",
+    "customData",
+    "password",
+    "refreshToken",
+    "token",
+];
+
+/// Single frame sent to or received from the cast device. Large messages are split into several
+/// frames, in which case every frame but the last one is `continued`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WireFrame {
+    /// Whether frame has been sent or received.
+    pub direction: Direction,
+    /// Time the frame has been sent or received at.
+    pub timestamp: SystemTime,
+    /// Namespace of the message the frame belongs to.
+    pub namespace: String,
+    /// Sender id of the frame.
+    pub source: String,
+    /// Recipient id of the frame.
+    pub destination: String,
+    /// Whether frame is followed by other frames of the same message.
+    pub continued: bool,
+    /// Payload of the frame with the sensitive fields redacted, see `redact`.
+    pub payload: CastMessagePayload,
+}
+
+impl WireFrame {
+    /// Captures `raw_message` frame travelling in the `direction`, redacting its payload.
+    pub(crate) fn capture(direction: Direction, raw_message: &cast_channel::CastMessage) -> Self {
+        let payload = if raw_message.has_payload_binary() {
+            CastMessagePayload::Binary(raw_message.payload_binary().to_vec())
+        } else {
+            CastMessagePayload::String(raw_message.payload_utf8().to_string())
+        };
+
+        WireFrame {
+            direction,
+            timestamp: SystemTime::now(),
+            namespace: raw_message.namespace().to_string(),
+            source: raw_message.source_id().to_string(),
+            destination: raw_message.destination_id().to_string(),
+            continued: raw_message.continued(),
+            payload: redact(payload),
+        }
+    }
+
+    /// Serializes frame into the JSON object, binary payload is hex encoded.
+    pub fn to_json(&self) -> Value {
+        let mut frame = serde_json::json!({
+            "direction": match self.direction {
+                Direction::Incoming => "incoming",
+                Direction::Outgoing => "outgoing",
+            },
+            "timestamp_ms": self
+                .timestamp
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            "namespace": self.namespace,
+            "source": self.source,
+            "destination": self.destination,
+            "continued": self.continued,
+        });
+
+        match self.payload {
+            CastMessagePayload::String(ref payload) => {
+                frame["payload_utf8"] = Value::from(payload.as_str())
+            }
+            CastMessagePayload::Binary(ref payload) => {
+                frame["payload_binary"] = Value::from(to_hex(payload))
+            }
+        }
+
+        frame
+    }
+
+    /// Deserializes frame from the JSON object produced by `to_json`.
+    pub fn from_json(frame: &Value) -> Result<Self, Error> {
+        let field = |name: &str| {
+            frame
+                .get(name)
+                .ok_or_else(|| Error::Parsing(format!("Wire frame misses `{name}` field.")))
+        };
+        let string_field = |name: &str| {
+            field(name)?
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| Error::Parsing(format!("Wire frame `{name}` is not a string.")))
+        };
+
+        let direction = match string_field("direction")?.as_str() {
+            "incoming" => Direction::Incoming,
+            "outgoing" => Direction::Outgoing,
+            direction => {
+                return Err(Error::Parsing(format!(
+                    "Unknown wire frame direction: {direction}"
+                )))
+            }
+        };
+
+        let payload = match frame.get("payload_binary") {
+            Some(_) => CastMessagePayload::Binary(from_hex(&string_field("payload_binary")?)?),
+            None => CastMessagePayload::String(string_field("payload_utf8")?),
+        };
+
+        Ok(WireFrame {
+            direction,
+            timestamp: UNIX_EPOCH
+                + Duration::from_millis(field("timestamp_ms")?.as_u64().unwrap_or_default()),
+            namespace: string_field("namespace")?,
+            source: string_field("source")?,
+            destination: string_field("destination")?,
+            continued: frame
+                .get("continued")
+                .and_then(Value::as_bool)
+                .unwrap_or_default(),
+            payload,
+        })
+    }
+}
+
+/// Destination of the captured frames, see `MessageManager::set_wire_log`. Sink is called while
+/// the stream is in use, so it must not send or receive any messages itself.
+pub trait WireLogSink: Send {
+    /// Stores the captured `frame`.
+    fn write_frame(&mut self, frame: &WireFrame) -> Result<(), Error>;
+}
+
+impl<F> WireLogSink for F
+where
+    F: FnMut(&WireFrame) + Send,
+{
+    fn write_frame(&mut self, frame: &WireFrame) -> Result<(), Error> {
+        self(frame);
+        Ok(())
+    }
+}
+
+/// Sink that writes every frame as a single line JSON object (see `WireFrame::to_json`).
+pub struct JsonLinesSink<W>
+where
+    W: Write + Send,
+{
+    writer: W,
+}
+
+impl<W> JsonLinesSink<W>
+where
+    W: Write + Send,
+{
+    /// Creates sink that writes frames to the `writer`.
+    pub fn new(writer: W) -> Self {
+        JsonLinesSink { writer }
+    }
+
+    /// Consumes the sink, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl JsonLinesSink<BufWriter<File>> {
+    /// Creates sink that writes frames to the file at `path`, the file is truncated if it exists.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(JsonLinesSink::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W> WireLogSink for JsonLinesSink<W>
+where
+    W: Write + Send,
+{
+    fn write_frame(&mut self, frame: &WireFrame) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.writer, &frame.to_json())?;
+        self.writer.write_all(b"\n")?;
+        // Frames are usually inspected after something went wrong, so nothing should be lost.
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Replaces values of the `REDACTED_FIELDS` in the JSON `payload`. String payloads that aren't
+/// JSON (e.g. chunks of the large message, which fields can't be told apart) are replaced with
+/// `REDACTED` as a whole, binary payloads are returned unchanged.
+pub fn redact(payload: CastMessagePayload) -> CastMessagePayload {
+    let CastMessagePayload::String(ref json) = payload else {
+        return payload;
+    };
+
+    match serde_json::from_str::<Value>(json) {
+        Ok(mut value) => {
+            if redact_value(&mut value) {
+                CastMessagePayload::String(value.to_string())
+            } else {
+                payload
+            }
+        }
+        Err(_) => CastMessagePayload::String(REDACTED.to_string()),
+    }
+}
+
+/// Redacts sensitive fields of the `value` in place, returns `true` if anything was redacted.
+fn redact_value(value: &mut Value) -> bool {
+    let mut redacted = false;
+
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if REDACTED_FIELDS
+                    .iter()
+                    .any(|field| field.eq_ignore_ascii_case(key))
+                {
+                    *value = Value::from(REDACTED);
+                    redacted = true;
+                } else {
+                    redacted |= redact_value(value);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values.iter_mut() {
+                redacted |= redact_value(value);
+            }
+        }
+        _ => {}
+    }
+
+    redacted
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|byte| {
+            std::str::from_utf8(byte)
+                .ok()
+                .filter(|byte| byte.len() == 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| Error::Parsing("Malformed hex encoded payload.".to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let payload = CastMessagePayload::String(
+            r#"{"type":"LOAD","credentials":"secret","media":{"customData":{"t":1}},"items":[{"Token":"x"}]}"#
+                .to_string(),
+        );
+
+        let redacted = redact(payload).to_json().unwrap();
+
+        assert_eq!("LOAD", redacted["type"]);
+        assert_eq!(REDACTED, redacted["credentials"]);
+        assert_eq!(REDACTED, redacted["media"]["customData"]);
+        assert_eq!(REDACTED, redacted["items"][0]["Token"]);
+
+        let chunk = CastMessagePayload::String(r#"{"credentials":"sec"#.to_string());
+        assert_eq!(
+            CastMessagePayload::String(REDACTED.to_string()),
+            redact(chunk)
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let frame = WireFrame {
+            direction: Direction::Outgoing,
+            timestamp: UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+            namespace: crate::namespaces::DEVICE_AUTH.to_string(),
+            source: "sender-0".to_string(),
+            destination: "receiver-0".to_string(),
+            continued: false,
+            payload: CastMessagePayload::Binary(vec![0, 1, 0xab, 0xff]),
+        };
+
+        let json = frame.to_json();
+
+        assert_eq!("0001abff", json["payload_binary"]);
+        assert_eq!(frame, WireFrame::from_json(&json).unwrap());
+    }
+}